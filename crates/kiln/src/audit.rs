@@ -0,0 +1,418 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use strum::AsRefStr;
+
+use crate::config::Config;
+use crate::content::discovery::discover_content;
+use crate::content::format::ContentFormat;
+use crate::directive::DirectiveKind;
+use crate::directive::parser::parse_directives;
+use crate::markdown::{for_each_non_code_line, scan_code_span};
+use crate::render::RenderOptions;
+use crate::render::emoji::EMOJI_RE;
+use crate::render::icon::ICON_RE;
+use crate::render::snippet::SNIPPET_DIRECTIVE;
+
+/// Directive and shortcode usage across all non-draft pages, except HTML pages
+/// (whose bodies skip the markdown pipeline).
+///
+/// Keys are directive names (e.g., `"callout"`) and shortcode literals (e.g.,
+/// `":smile:"`, `":(fas fa-link):"`). Anonymous fenced divs (`::: {.class}`)
+/// are plain Pandoc divs rather than directives and are not reported.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UsageReport {
+    pub directives: BTreeMap<String, DirectiveUsage>,
+    pub shortcodes: BTreeMap<String, Usage>,
+}
+
+/// How a directive name is rendered during a build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum DirectiveHandler {
+    /// Handled by the engine itself (e.g., `callout`).
+    Builtin,
+    /// Rendered through a `directives/<name>.html` template.
+    Template,
+    /// No handler; falls back to a generic `<div>` wrapper.
+    Fallback,
+}
+
+/// Usage of a single directive name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveUsage {
+    pub handler: DirectiveHandler,
+    pub usage: Usage,
+}
+
+/// Occurrence count plus the content-relative paths of the pages using it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub count: usize,
+    pub pages: BTreeSet<PathBuf>,
+}
+
+impl Usage {
+    fn record(&mut self, page: &Path) {
+        self.count += 1;
+        self.pages.insert(page.to_owned());
+    }
+}
+
+/// Scans the site's content for directive and shortcode usage.
+///
+/// Directive handlers are resolved against `directives/<name>.html` in the
+/// site and theme `templates/` directories, mirroring the lookup the render
+/// pipeline performs. Emoji and icon shortcodes are only counted when the
+/// corresponding `emojis` / `fontawesome` param is enabled, since they are
+/// plain text otherwise.
+///
+/// # Errors
+///
+/// Returns an error if configuration loading or content discovery fails.
+pub fn audit(root: &Path) -> Result<UsageReport> {
+    let config = Config::load(root).context("failed to load config")?;
+    let options = RenderOptions::from_params(&config.params);
+    let template_dirs: Vec<PathBuf> = [Some(root.to_owned()), config.theme_dir(root)]
+        .into_iter()
+        .flatten()
+        .map(|d| d.join("templates").join("directives"))
+        .collect();
+
+    let content = discover_content(root, config.slugify)?;
+    let mut report = UsageReport::default();
+
+    for page in content
+        .pages
+        .iter()
+        .filter(|p| p.format == ContentFormat::Markdown)
+    {
+        let rel = page
+            .source_path
+            .strip_prefix(&content.content_dir)
+            .unwrap_or(&page.source_path);
+
+        for block in parse_directives(&page.raw_content) {
+            let (name, handler) = match &block.kind {
+                DirectiveKind::Callout { .. }
+                | DirectiveKind::Csv { .. }
+                | DirectiveKind::Gallery { .. }
+                | DirectiveKind::Spoiler { .. }
+                | DirectiveKind::Raw => (block.kind.name(), DirectiveHandler::Builtin),
+                DirectiveKind::Unknown { name, .. } if name.is_empty() => continue,
+                // Expanded from the site's snippets before rendering.
+                DirectiveKind::Unknown { name, .. } if name == SNIPPET_DIRECTIVE => {
+                    (name.as_str(), DirectiveHandler::Builtin)
+                }
+                DirectiveKind::Unknown { name, .. } => {
+                    let file = format!("{name}.html");
+                    let handler = if template_dirs.iter().any(|d| d.join(&file).is_file()) {
+                        DirectiveHandler::Template
+                    } else {
+                        DirectiveHandler::Fallback
+                    };
+                    (name.as_str(), handler)
+                }
+            };
+            report
+                .directives
+                .entry(name.to_owned())
+                .or_insert_with(|| DirectiveUsage {
+                    handler,
+                    usage: Usage::default(),
+                })
+                .usage
+                .record(rel);
+        }
+
+        for shortcode in find_shortcodes(&page.raw_content, &options) {
+            report.shortcodes.entry(shortcode).or_default().record(rel);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Collects the emoji / icon shortcodes that the render pipeline would
+/// replace, skipping fenced code blocks and inline code spans.
+fn find_shortcodes(content: &str, options: &RenderOptions) -> Vec<String> {
+    let mut found = Vec::new();
+    if !options.emojis && !options.fontawesome {
+        return found;
+    }
+
+    let mut scratch = String::new();
+    for_each_non_code_line(content, &mut scratch, |line, _| {
+        let bytes = line.as_bytes();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if bytes[i] == b'`' {
+                i = scan_code_span(line, i).0;
+                continue;
+            }
+
+            if bytes[i] == b':'
+                && let Some(shortcode) = match_shortcode(&line[i..], options)
+            {
+                i += shortcode.len();
+                found.push(shortcode.to_owned());
+                continue;
+            }
+
+            i += line[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    });
+
+    found
+}
+
+/// Matches an enabled shortcode at the very start of `rest`.
+fn match_shortcode<'a>(rest: &'a str, options: &RenderOptions) -> Option<&'a str> {
+    if options.emojis
+        && let Some(caps) = EMOJI_RE.captures(rest)
+        && let Some(m) = caps.get(0)
+        && m.start() == 0
+        && gh_emoji::get(&caps[1]).is_some()
+    {
+        return Some(m.as_str());
+    }
+    if options.fontawesome
+        && let Some(m) = ICON_RE.find(rest)
+        && m.start() == 0
+    {
+        return Some(m.as_str());
+    }
+    None
+}
+
+impl fmt::Display for UsageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Directives:")?;
+        if self.directives.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for (name, entry) in &self.directives {
+            write_usage(f, name, &entry.usage, Some(entry.handler))?;
+        }
+
+        writeln!(f, "Shortcodes:")?;
+        if self.shortcodes.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for (shortcode, usage) in &self.shortcodes {
+            write_usage(f, shortcode, usage, None)?;
+        }
+
+        let fallbacks: Vec<&str> = self
+            .directives
+            .iter()
+            .filter(|(_, entry)| entry.handler == DirectiveHandler::Fallback)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !fallbacks.is_empty() {
+            writeln!(
+                f,
+                "Unhandled directives (rendered as generic <div>): {}",
+                fallbacks.join(", ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_usage(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    usage: &Usage,
+    handler: Option<DirectiveHandler>,
+) -> fmt::Result {
+    write!(
+        f,
+        "  {name}: {count} use(s) in {pages} page(s)",
+        count = usage.count,
+        pages = usage.pages.len(),
+    )?;
+    if let Some(handler) = handler {
+        write!(f, " [{}]", handler.as_ref())?;
+    }
+    writeln!(f)?;
+    for page in &usage.pages {
+        writeln!(f, "    {}", page.display())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    // ── audit ──
+
+    #[test]
+    fn audit_counts_directives_by_handler() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "config.toml", "");
+        write_test_file(root.path(), "templates/directives/widget.html", "W");
+        write_test_file(
+            root.path(),
+            "content/posts/a/index.md",
+            indoc! {r#"
+                +++
+                title = "A"
+                +++
+
+                :::: callout
+                ::: widget
+                Body
+                :::
+                ::::
+
+                ::: mystery
+                Body
+                :::
+
+                ::: {.plain}
+                Body
+                :::
+
+                ::: snippet {"disclaimer"}
+                :::
+            "#},
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/b/index.md",
+            indoc! {r#"
+                +++
+                title = "B"
+                +++
+
+                ::: callout
+                Body
+                :::
+            "#},
+        );
+
+        let report = audit(root.path()).unwrap();
+
+        let names: Vec<&str> = report.directives.keys().map(String::as_str).collect();
+        assert_eq!(names, ["callout", "mystery", "snippet", "widget"]);
+
+        let callout = &report.directives["callout"];
+        assert_eq!(callout.handler, DirectiveHandler::Builtin);
+        assert_eq!(callout.usage.count, 2);
+        assert_eq!(
+            callout.usage.pages,
+            BTreeSet::from([
+                PathBuf::from("posts/a/index.md"),
+                PathBuf::from("posts/b/index.md"),
+            ]),
+        );
+        assert_eq!(
+            report.directives["widget"].handler,
+            DirectiveHandler::Template
+        );
+        assert_eq!(
+            report.directives["mystery"].handler,
+            DirectiveHandler::Fallback
+        );
+        assert_eq!(
+            report.directives["snippet"].handler,
+            DirectiveHandler::Builtin
+        );
+        assert!(report.shortcodes.is_empty());
+    }
+
+    #[test]
+    fn audit_counts_shortcodes_when_enabled() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "config.toml",
+            indoc! {"
+                [params]
+                emojis = true
+                fontawesome = true
+            "},
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/a/index.md",
+            indoc! {r#"
+                +++
+                title = "A"
+                +++
+
+                Hi :smile: :smile: :not_an_emoji: :(fas fa-link):
+
+                Code `:smile:` stays.
+
+                ```text
+                :smile:
+                ```
+            "#},
+        );
+
+        let report = audit(root.path()).unwrap();
+
+        let keys: Vec<&str> = report.shortcodes.keys().map(String::as_str).collect();
+        assert_eq!(keys, [":(fas fa-link):", ":smile:"]);
+        assert_eq!(report.shortcodes[":smile:"].count, 2);
+        assert_eq!(report.shortcodes[":(fas fa-link):"].count, 1);
+    }
+
+    #[test]
+    fn audit_ignores_shortcodes_when_disabled() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "config.toml", "");
+        write_test_file(
+            root.path(),
+            "content/posts/a/index.md",
+            indoc! {r#"
+                +++
+                title = "A"
+                +++
+
+                Hi :smile: :(fas fa-link):
+            "#},
+        );
+
+        let report = audit(root.path()).unwrap();
+        assert!(report.shortcodes.is_empty());
+    }
+
+    // ── UsageReport: Display ──
+
+    #[test]
+    fn report_display_lists_pages_and_fallbacks() {
+        let mut report = UsageReport::default();
+        report.directives.insert(
+            "mystery".into(),
+            DirectiveUsage {
+                handler: DirectiveHandler::Fallback,
+                usage: Usage {
+                    count: 3,
+                    pages: BTreeSet::from([PathBuf::from("posts/a/index.md")]),
+                },
+            },
+        );
+        assert_eq!(
+            report.to_string(),
+            indoc! {"
+                Directives:
+                  mystery: 3 use(s) in 1 page(s) [fallback]
+                    posts/a/index.md
+                Shortcodes:
+                  (none)
+                Unhandled directives (rendered as generic <div>): mystery
+            "},
+        );
+    }
+}
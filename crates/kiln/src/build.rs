@@ -1,37 +1,83 @@
+mod analytics;
 mod archive;
+mod authors;
+mod compress;
+mod csp;
+mod drafts;
 mod error;
 mod feed;
+mod github_pages;
+mod headers;
 mod home;
+mod hooks;
+mod json_ld;
+mod lastmod;
 mod listing;
+mod microformats;
+mod og_image;
 mod overview;
 mod paginate;
+mod podcast;
+mod redirect;
+mod related;
+mod resize;
+mod sass;
 mod sitemap;
-mod url;
+mod summary;
+mod thumbnail;
+mod translation;
+pub(crate) mod url;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{Context, Result};
 use jiff::tz::TimeZone;
+use serde::{Serialize, Serializer};
 use syntect::parsing::SyntaxSet;
 
-use crate::config::Config;
-use crate::content::discovery::discover_content;
-use crate::content::page::{Page, PageKind};
+use crate::check;
+use crate::config::{Config, Images};
+use crate::content::discovery::{
+    ContentSet, discover_loadable_content, discover_passthrough, discover_section_assets,
+};
+use crate::content::format::ContentFormat;
+use crate::content::page::{Page, PageErrors, PageKind};
+use crate::diagnostic::{Diagnostic, collect_warnings};
+use crate::directive::parser::directive_problems;
 use crate::i18n::I18n;
-use crate::minify::{self, MinifyStats};
-use crate::output::{clean_output_dir, copy_file, copy_static, write_output};
+use crate::minify::{self, MinifyStats, format_bytes};
+use crate::output::{
+    StagingDir, copy_file, dir_size, output_file, static_files, write_output, write_output_with,
+};
 use crate::render::RenderOptions;
-use crate::render::pipeline::render_page;
+use crate::render::assets::PageAssets;
+use crate::render::citation::Bibliography;
+use crate::render::highlight::load_syntax_set;
+use crate::render::link::ContentLinks;
+use crate::render::pipeline::{RenderedPage, render_page};
+use crate::render::responsive::ImageVariant;
+use crate::render::sanitize::sanitize_html;
+use crate::render::snippet::Snippets;
 use crate::search;
-use crate::section::collect_sections;
-use crate::taxonomy::build_taxonomies;
-use crate::template::TemplateEngine;
-use crate::template::vars::PostTemplateVars;
+use crate::section::{Section, collect_sections};
+use crate::taxonomy::{TaxonomySet, build_taxonomies};
+use crate::template::asset_url::AssetUrls;
+use crate::template::cache::{self, CACHE_DIR, DirectiveCache, HighlightCache};
+use crate::template::vars::{PageSummary, PostTemplateVars, Translation};
+use crate::template::{TemplateEngine, checked_relative_path};
+use crate::text::case::TitleCaser;
+use crate::transform::{self, ContentTransform};
 
 use self::listing::{
-    build_listing_artifacts, format_page_date, page_section, resolve_featured_image,
+    ListingArtifacts, build_listing_artifacts, format_page_date, page_section,
+    resolve_featured_image,
 };
+use self::og_image::{OG_IMAGE_FILE, OgImageRenderer};
+use self::thumbnail::Thumbnail;
 use self::url::{page_url, resolve_relative_url};
 
 /// Shared build state, created once per build invocation.
@@ -39,8 +85,21 @@ struct BuildContext {
     config: Config,
     i18n: I18n,
     time_zone: Option<TimeZone>,
-    syntax_set: SyntaxSet,
+    syntax_set: Arc<SyntaxSet>,
+    /// Highlighted code kept across builds, when `[cache] highlight` is on.
+    highlight_cache: Option<Arc<HighlightCache>>,
     template_engine: TemplateEngine,
+    snippets: Snippets,
+    /// Site and theme `static/` directories, for sizing images served from
+    /// them.
+    static_dirs: Vec<PathBuf>,
+    /// Card renderer for pages without a featured image, with `[og_image]`.
+    og_images: Option<OgImageRenderer>,
+    /// Custom page processing from [`BuildOptions::transforms`].
+    transforms: Vec<Arc<dyn ContentTransform>>,
+    /// Whether drafts are published like any other page, from
+    /// [`BuildOptions::drafts`].
+    publish_drafts: bool,
 }
 
 /// Options controlling a single `build()` invocation.
@@ -52,192 +111,953 @@ struct BuildContext {
 /// kiln::build(&root, BuildOptions { minify: true, ..Default::default() })?;
 /// ```
 #[derive(Default)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent build switches, each set from its own CLI flag or `Site` method"
+)]
 pub struct BuildOptions<'a> {
+    /// Builds with this config instead of loading `config.toml`, as
+    /// [`Site`](crate::Site) does with the one it loaded.
+    pub config: Option<&'a Config>,
     /// Replaces `base_url` from config when set. Used by `kiln serve` so
     /// rendered URLs match the actual server port.
     pub base_url_override: Option<&'a str>,
     /// Writes into this directory instead of `root/<config.output_dir>`.
-    /// Used by the dev server to stage a fresh build before swapping it in.
     pub output_dir_override: Option<&'a Path>,
     /// Runs HTML / CSS / JS minification over the output directory before
     /// Pagefind indexing.
     pub minify: bool,
+    /// Fails the build if any internal link or `#fragment` in the output
+    /// is broken (see [`check::check_output`]).
+    pub check_links: bool,
+    /// Fails the build on soft failures, as `strict = true` in config does
+    /// (see [`Config::strict`]). Implies `check_links`.
+    pub strict: bool,
+    /// Publishes drafts like any other page, for previews. Drafts are then
+    /// no longer shared under `[drafts] share` preview URLs.
+    pub drafts: bool,
+    /// Builds for `kiln serve` previews: leaves out the `[analytics]`
+    /// script, so they are not counted as visits, and the `[csp]` policy,
+    /// which would block the live reload script.
+    pub dev: bool,
+    /// Custom processing applied to every page, in order (see
+    /// [`ContentTransform`]).
+    pub transforms: &'a [Arc<dyn ContentTransform>],
+    /// Receives each page built, each phase's timing, and the final totals
+    /// as they happen, in place of the human summary printed otherwise.
+    pub on_event: Option<&'a dyn Fn(&BuildEvent<'_>)>,
 }
 
-/// Builds the site from the given project root directory.
-///
-/// Search indexing (Pagefind) runs when `[search] enabled = true` in config.
-///
-/// # Errors
+/// A step of a build, passed to [`BuildOptions::on_event`] and printed as
+/// one JSON record per line by `--message-format json`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum BuildEvent<'a> {
+    /// A published page was written.
+    Page {
+        /// The page's source file, relative to the site root.
+        file: &'a Path,
+        url: &'a str,
+    },
+    /// A phase of the build ended.
+    Timing(&'a PhaseTiming),
+    /// The build ran to the end, with or without failed pages.
+    Finished {
+        pages: usize,
+        assets: usize,
+        bytes_written: u64,
+        warnings: usize,
+        failures: usize,
+        seconds: f64,
+    },
+}
+
+/// Outcome of a `build()` that ran to the end.
 ///
-/// Returns an error if configuration loading, content discovery, rendering,
-/// or output writing fails.
-#[expect(
-    clippy::needless_pass_by_value,
-    reason = "BuildOptions is an owned options bag: callers construct it inline with `..Default::default()`, so taking it by value keeps call sites concise and lets future non-Copy fields land without a signature churn"
-)]
-pub fn build(root: &Path, options: BuildOptions<'_>) -> Result<()> {
-    let BuildOptions {
-        base_url_override,
-        output_dir_override,
-        minify,
-    } = options;
-
-    let mut config = Config::load(root).context("failed to load config")?;
-    if let Some(base_url) = base_url_override {
-        base_url.clone_into(&mut config.base_url);
+/// Pages that fail to load, render, or write do not stop the build: they
+/// are left out of the output and listed in `failures`.
+#[derive(Debug, Default, Serialize)]
+pub struct BuildReport {
+    /// Pages written, shared drafts excluded.
+    pub pages: usize,
+    /// Files copied verbatim: static files, passthrough content files,
+    /// section assets, and page bundle assets.
+    pub assets: usize,
+    /// Total size of the output directory once built, in bytes.
+    pub bytes_written: u64,
+    /// Time spent in each phase of the build, in order.
+    pub phases: Vec<PhaseTiming>,
+    /// Time the `render` phase spent on each step of building pages, summed
+    /// over every page: `markdown` (with highlighting and directives),
+    /// `templates` (rendering pages straight into their files), and `output`
+    /// (writing cards and bundle assets).
+    pub render_steps: Vec<PhaseTiming>,
+    /// Warnings logged while building.
+    pub warnings: Vec<Diagnostic>,
+    /// Pages (and shared drafts) that failed, with their errors.
+    pub failures: PageErrors,
+    /// Totals of the minification pass, when `minify` is set.
+    pub minify_stats: Option<MinifyStats>,
+    /// When the `[hooks]` commands ran, so `kiln serve` can tell the files
+    /// they write from edits made during the build.
+    #[serde(skip)]
+    pub hook_runs: Vec<Range<SystemTime>>,
+}
+
+/// Time spent in one phase of a build, or one step of a phase.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    /// `load`, `copy`, `render`, `listings`, or `finish` for a phase.
+    pub name: &'static str,
+    #[serde(rename = "seconds", serialize_with = "serialize_seconds")]
+    pub duration: Duration,
+}
+
+/// Time one page spent on each step of being built, see
+/// [`BuildReport::render_steps`].
+#[derive(Debug, Default, Clone, Copy)]
+struct PageTimings {
+    markdown: Duration,
+    templates: Duration,
+    output: Duration,
+}
+
+impl PageTimings {
+    fn add(&mut self, other: Self) {
+        self.markdown += other.markdown;
+        self.templates += other.templates;
+        self.output += other.output;
     }
-    let time_zone = config
-        .time_zone()
-        .context("failed to resolve configured time zone")?;
-    let syntax_set = two_face::syntax::extra_newlines();
 
-    let site_templates = root.join("templates");
-    let theme_dir = config.theme_dir(root);
-    let theme_templates = theme_dir.as_ref().map(|d| d.join("templates"));
+    fn into_steps(self) -> Vec<PhaseTiming> {
+        [
+            ("markdown", self.markdown),
+            ("templates", self.templates),
+            ("output", self.output),
+        ]
+        .into_iter()
+        .map(|(name, duration)| PhaseTiming { name, duration })
+        .collect()
+    }
+}
 
-    if config.theme.is_none() {
-        tracing::warn!("no theme configured; set `theme` in config.toml to use a theme");
+fn serialize_seconds<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+impl BuildReport {
+    /// Total time spent building.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.phases.iter().map(|phase| phase.duration).sum()
     }
-    if !site_templates.is_dir() && theme_templates.as_ref().is_none_or(|d| !d.is_dir()) {
-        tracing::warn!("no templates found; provide templates/ or configure a theme");
+
+    /// Returns `Ok` if every page was built, or a
+    /// [`PageFailures`](crate::content::page::PageFailures) error
+    /// listing the pages that failed otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns the page failures.
+    pub fn into_result(self) -> Result<()> {
+        self.failures.into_result("build")
     }
+}
 
-    let i18n = I18n::load(root, theme_dir.as_deref(), &config.language)
-        .context("failed to load i18n strings")?;
+/// A [`BuildReport`] being filled in, with each step passed on to the
+/// [`BuildOptions::on_event`] callback when there is one.
+struct Progress<'a> {
+    report: BuildReport,
+    root: &'a Path,
+    on_event: Option<&'a dyn Fn(&BuildEvent<'_>)>,
+    phase_start: Instant,
+}
 
-    let template_engine =
-        TemplateEngine::new(Some(&site_templates), theme_templates.as_deref(), &i18n)
-            .context("failed to initialize template engine")?;
+impl<'a> Progress<'a> {
+    fn new(root: &'a Path, on_event: Option<&'a dyn Fn(&BuildEvent<'_>)>) -> Self {
+        Self {
+            report: BuildReport::default(),
+            root,
+            on_event,
+            phase_start: Instant::now(),
+        }
+    }
 
-    let ctx = BuildContext {
-        config,
-        i18n,
-        time_zone,
-        syntax_set,
-        template_engine,
-    };
+    fn emit(&self, event: &BuildEvent<'_>) {
+        if let Some(on_event) = self.on_event {
+            on_event(event);
+        }
+    }
 
-    let content = discover_content(root)?;
-    let output_dir = match output_dir_override {
-        Some(path) => path.to_owned(),
-        None => ctx.config.resolved_output_dir(root)?,
-    };
+    /// Ends phase `name` and begins the next one.
+    fn end_phase(&mut self, name: &'static str) {
+        let timing = PhaseTiming {
+            name,
+            duration: lap(&mut self.phase_start),
+        };
+        self.emit(&BuildEvent::Timing(&timing));
+        self.report.phases.push(timing);
+    }
 
-    clean_output_dir(&output_dir)?;
+    /// Counts `page`, written at `url`, along with its bundle assets.
+    fn page_built(&mut self, page: &Page, url: &str) {
+        self.report.pages += 1;
+        self.report.assets += page.assets.len();
+        let file = page.source_path.strip_prefix(self.root);
+        self.emit(&BuildEvent::Page {
+            file: file.unwrap_or(&page.source_path),
+            url,
+        });
+    }
+}
 
-    if let Some(ref td) = theme_dir {
-        copy_static(&td.join("static"), &output_dir)?;
+/// Builds the site from the given project root directory.
+///
+/// Search indexing (Pagefind) runs when `[search] enabled = true` in config.
+/// A page that fails does not stop the build; every failure is collected
+/// into the returned [`BuildReport`] and summarized once the rest of the
+/// site is built. Link checking is skipped when a page failed, as links to
+/// it would all be reported broken.
+///
+/// The site is written to a staging directory beside the output directory
+/// and synced into it only when every page built, so a failed build leaves
+/// the previous output untouched. The sync replaces files one by one and is
+/// not atomic; see [`StagingDir::promote`].
+///
+/// # Errors
+///
+/// Returns an error if configuration loading, reading the content
+/// directory, listing or site-wide output generation, or output writing
+/// fails.
+#[expect(
+    clippy::needless_pass_by_value,
+    reason = "BuildOptions is an owned options bag: callers construct it inline with `..Default::default()`, so taking it by value keeps call sites concise and lets future non-Copy fields land without a signature churn"
+)]
+pub fn build(root: &Path, options: BuildOptions<'_>) -> Result<BuildReport> {
+    let (report, warnings) = collect_warnings(|| build_site(root, &options));
+    let mut report = report?;
+    report.warnings = warnings;
+    match options.on_event {
+        Some(on_event) => on_event(&BuildEvent::Finished {
+            pages: report.pages,
+            assets: report.assets,
+            bytes_written: report.bytes_written,
+            warnings: report.warnings.len(),
+            failures: report.failures.len(),
+            seconds: report.duration().as_secs_f64(),
+        }),
+        None => report_build_summary(root, &report),
     }
-    copy_static(&root.join("static"), &output_dir)?;
+    Ok(report)
+}
+
+/// Runs every phase of [`build`], timing each.
+fn build_site(root: &Path, options: &BuildOptions<'_>) -> Result<BuildReport> {
+    let mut progress = Progress::new(root, options.on_event);
+    let (mut ctx, theme_dir) = load_build_context(root, options, &mut progress.report.hook_runs)?;
+    let (mut content, redirects) = load_content(root, &ctx, &mut progress.report.failures)?;
+    let staging = StagingDir::create(&output_dir_for(root, &ctx.config, options)?)?;
+    let output_dir = staging.path().to_owned();
+    progress.end_phase("load");
+
+    let section_assets = discover_section_assets(&content.content_dir)?;
+    progress.report.assets = prepare_output_dir(
+        &ctx,
+        root,
+        theme_dir.as_deref(),
+        &content,
+        &section_assets,
+        &staging,
+    )?;
+    progress.end_phase("copy");
+    let asset_urls = Arc::new(AssetUrls::new(
+        &output_dir,
+        &ctx.config.base_url,
+        ctx.config.assets.fingerprint,
+    ));
+    ctx.template_engine.set_asset_urls(Arc::clone(&asset_urls));
 
     let sections = collect_sections(&content.pages, &content.content_dir);
+    apply_page_templates(&mut content, &sections);
     let section_titles: HashMap<&str, &str> = sections
         .iter()
         .map(|s| (s.slug.as_str(), s.title.as_str()))
         .collect();
 
+    let content_links = Arc::new(collect_content_links(
+        &ctx.config.base_url,
+        content.pages.iter().chain(&redirects),
+        &section_assets,
+        &content.content_dir,
+    )?);
+
+    let summaries = summary::render_summaries(&ctx, &content, &content_links);
+    let listed_summaries: Vec<&str> = summaries
+        .iter()
+        .map(|summary| summary.as_deref().unwrap_or_default())
+        .collect();
     let artifacts = build_listing_artifacts(
         &content.pages,
+        &listed_summaries,
         &content.content_dir,
         &ctx.config.base_url,
         ctx.time_zone.as_ref(),
         &section_titles,
+        ctx.config.thumbnails.width,
+        ctx.config.slugify.mode(),
     )?;
+    ctx.template_engine.set_site_pages(&artifacts.site_pages());
+    let (taxonomy_set, related, translations) = link_pages(&ctx, &content, &artifacts)?;
 
-    for page in &content.pages {
-        build_page(
-            &ctx,
-            page,
-            &content.content_dir,
-            &output_dir,
-            &section_titles,
-        )?;
-    }
-
-    let taxonomy_set = build_taxonomies(&content.pages, Some(&content.content_dir));
-
-    home::build_home_pages(&ctx, &artifacts.listed_posts, &output_dir)?;
-    archive::build_archive_pages(
+    build_pages(
         &ctx,
-        &artifacts,
-        &sections,
-        &taxonomy_set,
-        &content.content_dir,
+        &mut content,
         &output_dir,
+        &section_titles,
+        &content_links,
+        summaries,
+        &related,
+        &translations,
+        &mut progress,
     )?;
-    overview::build_overview_pages(&ctx, &artifacts, &sections, &taxonomy_set, &output_dir)?;
+    progress.end_phase("render");
 
-    feed::build_feeds(
+    build_listing_outputs(
         &ctx,
         &artifacts,
         &sections,
         &taxonomy_set,
+        &translations,
         &content.content_dir,
         &output_dir,
     )?;
-    sitemap::build_sitemap_and_robots(&ctx, &artifacts.listed_pages, &output_dir)?;
     error::build_404(&ctx, &output_dir)?;
+    redirect::build_redirects(
+        &ctx,
+        &redirects,
+        &content.pages,
+        &content.content_dir,
+        &output_dir,
+    )?;
+    progress.end_phase("listings");
+
+    let check_links =
+        (options.check_links || ctx.config.strict) && progress.report.failures.is_empty();
+    progress.report.minify_stats = finish_output(
+        &ctx,
+        root,
+        &content,
+        &staging,
+        &asset_urls.fingerprinted(),
+        options.minify,
+        check_links,
+    )?;
+    progress.report.bytes_written = dir_size(&output_dir)?;
+    if progress.report.failures.is_empty() {
+        staging.promote()?;
+        let ran = hooks::run_hooks(root, "post_build", &ctx.config.hooks.post_build)?;
+        progress.report.hook_runs.extend(ran);
+    }
+    progress.end_phase("finish");
+    Ok(progress.report)
+}
+
+/// Returns the directory a build writes to: `options.output_dir_override`,
+/// or the configured `output_dir`.
+fn output_dir_for(root: &Path, config: &Config, options: &BuildOptions<'_>) -> Result<PathBuf> {
+    match options.output_dir_override {
+        Some(path) => Ok(path.to_owned()),
+        None => config.resolved_output_dir(root),
+    }
+}
+
+/// The site's taxonomy terms, then the related posts and translations of
+/// each page.
+type PageRelations = (TaxonomySet, Vec<Vec<PageSummary>>, Vec<Vec<Translation>>);
+
+/// Groups the site's pages by taxonomy term, and finds the related posts
+/// and translations of each, index-aligned with `content.pages`.
+fn link_pages(
+    ctx: &BuildContext,
+    content: &ContentSet,
+    artifacts: &ListingArtifacts,
+) -> Result<PageRelations> {
+    let mut taxonomy_set = build_taxonomies(
+        &content.pages,
+        authors::taxonomy_kinds(&ctx.config),
+        ctx.config.slugify.mode(),
+        Some(&content.content_dir),
+    );
+    authors::name_author_terms(&mut taxonomy_set, &ctx.config.authors);
+    let related = related::build_related(
+        &content.pages,
+        &artifacts.listed_pages,
+        &taxonomy_set,
+        &ctx.config.related,
+        ctx.config.slugify.mode(),
+    );
+    let translations = translation::build_translations(
+        &content.pages,
+        &artifacts.listed_pages,
+        &ctx.config.language,
+    )?;
+    Ok((taxonomy_set, related, translations))
+}
+
+/// Runs the passes over the finished output directory: link checking,
+/// minification, the `[csp]` policy, Pagefind indexing, GitHub Pages files,
+/// `_headers`, and precompression.
+///
+/// Returns the minification stats when `minify` is set.
+fn finish_output(
+    ctx: &BuildContext,
+    root: &Path,
+    content: &ContentSet,
+    output: &StagingDir,
+    fingerprinted: &[PathBuf],
+    minify: bool,
+    check_links: bool,
+) -> Result<Option<MinifyStats>> {
+    if check_links {
+        check_output_links(root, content, output, &ctx.config.base_url)?;
+    }
+    let output_dir = output.path();
 
     let minify_stats = if minify {
         eprintln!("Minifying...");
-        Some(minify::minify_output_dir(&output_dir).context("minification failed")?)
+        Some(minify::minify_output_dir(output_dir).context("minification failed")?)
     } else {
         None
     };
+    // After minification, so the hashes match the HTML served.
+    if ctx.config.csp.enabled {
+        csp::build_csp(&ctx.config.csp, output_dir)?;
+    }
 
     if ctx.config.search.enabled {
         eprintln!("Running Pagefind...");
-        search::run_pagefind(&output_dir, ctx.config.search.binary.as_deref())
+        // Shared drafts must stay unlisted, so they are kept out of the index.
+        let exclude: &[&str] = if ctx.config.drafts.share {
+            &[drafts::DRAFTS_DIR]
+        } else {
+            &[]
+        };
+        search::run_pagefind(output_dir, ctx.config.search.binary.as_deref(), exclude)
             .context("search indexing failed")?;
     }
+    if ctx.config.github_pages.enabled {
+        github_pages::build_github_pages_files(
+            &ctx.config.github_pages,
+            &ctx.config.base_url,
+            output_dir,
+        )?;
+    }
+    // After every pass adding files, so the rules cover Pagefind's index too.
+    if ctx.config.headers.enabled {
+        headers::build_headers(&ctx.config.headers, output_dir, fingerprinted)?;
+    }
+    // Last, so each file is compressed in its final form.
+    compress::precompress_output(&ctx.config.compress, output_dir)?;
+    Ok(minify_stats)
+}
 
-    report_build_summary(content.pages.len(), minify_stats.as_ref());
+/// Runs the link checker over the fresh output, printing every broken link
+/// before failing.
+fn check_output_links(
+    root: &Path,
+    content: &ContentSet,
+    output: &StagingDir,
+    base_url: &str,
+) -> Result<()> {
+    eprintln!("Checking links...");
+    let sources = check::page_sources(root, content)?;
+    let mut report = check::check_output(output.path(), base_url, &sources)?;
+    // Name pages by where they are published; the staging copy is discarded.
+    output.target().clone_into(&mut report.output_dir);
+    if !report.issues.is_empty() {
+        eprint!("{report}");
+        return Err(check::BrokenLinks {
+            root: root.to_owned(),
+            report,
+        }
+        .into());
+    }
     Ok(())
 }
 
-/// Prints the end-of-build summary line(s).
+/// Writes the outputs generated from listings: home, archive, and overview
+/// pages, feeds, and the sitemap.
+///
+/// `translations` holds each listed page's translations, index-aligned with
+/// `artifacts.listed_pages`.
+fn build_listing_outputs(
+    ctx: &BuildContext,
+    artifacts: &ListingArtifacts,
+    sections: &[Section],
+    taxonomy_set: &TaxonomySet,
+    translations: &[Vec<Translation>],
+    content_dir: &Path,
+    output_dir: &Path,
+) -> Result<()> {
+    home::build_home_pages(ctx, &artifacts.listed_posts, output_dir)?;
+    archive::build_archive_pages(
+        ctx,
+        artifacts,
+        sections,
+        taxonomy_set,
+        content_dir,
+        output_dir,
+    )?;
+    overview::build_overview_pages(ctx, artifacts, sections, taxonomy_set, output_dir)?;
+    feed::build_feeds(
+        ctx,
+        artifacts,
+        sections,
+        taxonomy_set,
+        content_dir,
+        output_dir,
+    )?;
+    sitemap::build_sitemap_and_robots(ctx, &artifacts.listed_pages, translations, output_dir)
+}
+
+/// Prints the end-of-build summary line(s), listing the source files of
+/// the pages that failed relative to `root`.
 ///
 /// All build output goes to stderr so stdout stays free for future
 /// machine-readable output (e.g., `--json`) and piping (`kiln build
 /// 2>/dev/null` silences progress). This also matches `serve.rs`,
 /// which uses `eprintln!` uniformly for its live-reload diagnostics.
-fn report_build_summary(page_count: usize, minify_stats: Option<&MinifyStats>) {
-    eprintln!("Build complete: {page_count} page(s).");
-    if let Some(stats) = minify_stats {
+fn report_build_summary(root: &Path, report: &BuildReport) {
+    let page_count = report.pages;
+    if report.failures.is_empty() {
+        eprintln!(
+            "Build complete: {page_count} page(s), {} asset(s), {} in {:.2}s.",
+            report.assets,
+            format_bytes(report.bytes_written),
+            report.duration().as_secs_f64()
+        );
+    } else {
+        let failed = report.failures.len();
+        eprintln!("Build finished with errors: {page_count} page(s), {failed} failed:");
+        for (source, _) in report.failures.iter() {
+            eprintln!(
+                "  {}",
+                source.strip_prefix(root).unwrap_or(source).display()
+            );
+        }
+    }
+    if let Some(stats) = &report.minify_stats {
         eprintln!("{stats}");
     }
 }
 
+/// Applies the overrides of `options` to `config`: base URL, strictness,
+/// and the `kiln serve` changes of `dev`.
+fn apply_config_overrides(config: &mut Config, options: &BuildOptions<'_>) {
+    if let Some(base_url) = options.base_url_override {
+        base_url.clone_into(&mut config.base_url);
+    }
+    config.strict |= options.strict;
+    if options.dev {
+        config.analytics = None;
+        config.csp.enabled = false;
+    }
+}
+
+/// Loads config from `config.toml`, unless `options` passes one in, with
+/// the `--base-url` and `--strict` overrides of `options`.
+///
+/// Runs the `[hooks] pre_build` commands once config is loaded, adding when
+/// they ran to `hook_runs`.
+fn load_config(
+    root: &Path,
+    options: &BuildOptions<'_>,
+    hook_runs: &mut Vec<Range<SystemTime>>,
+) -> Result<Config> {
+    let mut config = match options.config {
+        Some(config) => config.clone(),
+        None => Config::load(root).context("failed to load config")?,
+    };
+    // First, so the hooks can generate templates, static files, or content.
+    let ran = hooks::run_hooks(root, "pre_build", &config.hooks.pre_build)?;
+    hook_runs.extend(ran);
+    apply_config_overrides(&mut config, options);
+    Ok(config)
+}
+
+/// Loads config (see [`load_config`]), syntaxes, i18n strings, templates,
+/// and snippets into a [`BuildContext`].
+///
+/// Also returns the resolved theme directory, if a theme is configured.
+fn load_build_context(
+    root: &Path,
+    options: &BuildOptions<'_>,
+    hook_runs: &mut Vec<Range<SystemTime>>,
+) -> Result<(BuildContext, Option<PathBuf>)> {
+    let config = load_config(root, options, hook_runs)?;
+    let time_zone = config
+        .time_zone()
+        .context("failed to resolve configured time zone")?;
+
+    let site_templates = root.join("templates");
+    let theme_dir = config.theme_dir(root);
+    let theme_templates = theme_dir.as_ref().map(|d| d.join("templates"));
+
+    let theme_syntaxes = theme_dir.as_ref().map(|d| d.join("syntaxes"));
+    let site_syntaxes = root.join("syntaxes");
+    let syntax_dirs: Vec<&Path> = theme_syntaxes
+        .as_deref()
+        .into_iter()
+        .chain([site_syntaxes.as_path()])
+        .collect();
+    let syntax_set = load_syntax_set(&syntax_dirs).context("failed to load syntaxes")?;
+    let highlight_cache = if config.cache.highlight {
+        let fingerprint =
+            cache::fingerprint(&syntax_dirs).context("failed to hash cache inputs")?;
+        Some(Arc::new(HighlightCache::load(
+            &root.join(CACHE_DIR).join("highlight.json"),
+            fingerprint,
+        )))
+    } else {
+        None
+    };
+
+    if config.theme.is_none() {
+        tracing::warn!("no theme configured; set `theme` in config.toml to use a theme");
+    }
+    if !site_templates.is_dir() && theme_templates.as_ref().is_none_or(|d| !d.is_dir()) {
+        tracing::warn!("no templates found; provide templates/ or configure a theme");
+    }
+
+    let i18n = I18n::load(root, theme_dir.as_deref(), &config.language)
+        .context("failed to load i18n strings")?;
+
+    let mut template_engine =
+        TemplateEngine::new(Some(&site_templates), theme_templates.as_deref(), &i18n)
+            .context("failed to initialize template engine")?;
+    template_engine.set_data_dir(&root.join("data"));
+    template_engine.set_slugify_mode(config.slugify.mode());
+    if config.strict || config.templates.strict {
+        template_engine.set_strict();
+    }
+    if let Some(analytics) = &config.analytics {
+        template_engine.set_head_html(analytics::analytics_script(analytics));
+    }
+    if config.cache.directives {
+        let theme_i18n = theme_dir.as_ref().map(|d| d.join("i18n"));
+        let inputs = [
+            Some(root.join("config.toml")),
+            Some(root.join("i18n")),
+            Some(site_templates),
+            theme_templates,
+            theme_i18n,
+        ];
+        let inputs: Vec<&Path> = inputs.iter().flatten().map(PathBuf::as_path).collect();
+        let fingerprint = cache::fingerprint(&inputs).context("failed to hash cache inputs")?;
+        template_engine.set_directive_cache(DirectiveCache::load(
+            &root.join(CACHE_DIR).join("directives.json"),
+            fingerprint,
+        ));
+    }
+
+    let snippets = Snippets::load(
+        &root.join("data").join("snippets.toml"),
+        &root.join("content").join("_snippets"),
+    )
+    .context("failed to load snippets")?;
+
+    let static_dirs = [
+        Some(root.join("static")),
+        theme_dir.as_ref().map(|d| d.join("static")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let og_images = config
+        .og_image
+        .enabled
+        .then(|| OgImageRenderer::new(root, &config.og_image))
+        .transpose()
+        .context("failed to load Open Graph image fonts")?;
+    let ctx = BuildContext {
+        config,
+        i18n,
+        time_zone,
+        syntax_set,
+        highlight_cache,
+        template_engine,
+        snippets,
+        static_dirs,
+        og_images,
+        transforms: options.transforms.to_vec(),
+        publish_drafts: options.drafts,
+    };
+    Ok((ctx, theme_dir))
+}
+
+/// Maps the source paths of `pages` and `section_assets` to their output
+/// URLs, for resolving links to content files.
+fn collect_content_links<'a>(
+    base_url: &str,
+    pages: impl Iterator<Item = &'a Page>,
+    section_assets: &[PathBuf],
+    content_dir: &Path,
+) -> Result<ContentLinks> {
+    let mut content_links = ContentLinks::new(content_dir);
+    for page in pages {
+        let output_path = page.output_path(content_dir)?;
+        content_links.insert(&page.source_path, page_url(base_url, &output_path));
+    }
+    for asset in section_assets {
+        let relative = asset
+            .strip_prefix(content_dir)
+            .context("section asset outside the content directory")?;
+        content_links.insert(asset, page_url(base_url, relative));
+    }
+    Ok(content_links)
+}
+
+/// Copies theme static files, site static files, allowlisted passthrough
+/// content files, and section assets into the output directory — later
+/// sources win on path conflicts — then compiles Sass stylesheets into it.
+///
+/// Files unchanged since the live output was built are linked rather than
+/// copied; see [`StagingDir::stage_file`].
+fn prepare_output_dir(
+    ctx: &BuildContext,
+    root: &Path,
+    theme_dir: Option<&Path>,
+    content: &ContentSet,
+    section_assets: &[PathBuf],
+    output: &StagingDir,
+) -> Result<usize> {
+    let content_dir = &content.content_dir;
+    let mut files = BTreeMap::new();
+    let static_dirs = theme_dir.map(|td| td.join("static")).into_iter();
+    for static_dir in static_dirs.chain([root.join("static")]) {
+        for (source, dest) in static_files(&static_dir, Path::new(""))? {
+            files.insert(dest, source);
+        }
+    }
+    let passthrough = discover_passthrough(content_dir, &ctx.config.content.passthrough)?;
+    for file in passthrough
+        .into_iter()
+        .chain(section_assets.iter().cloned())
+    {
+        let relative = file
+            .strip_prefix(content_dir)
+            .expect("discovered content files live under content_dir");
+        files.insert(relative.to_owned(), file);
+    }
+    for (dest, source) in &files {
+        output
+            .stage_file(source, dest)
+            .with_context(|| format!("failed to copy {}", source.display()))?;
+    }
+    sass::compile_sass(root, theme_dir, output.path())?;
+    Ok(files.len())
+}
+
+/// Discovers the site's content, recasing titles per `[markup] title_case`,
+/// dating pages without `updated` from git per `lastmod_from_git`, and
+/// running bodies through the [`ContentTransform`]s. Pages that fail to
+/// load or transform are added to `errors`.
+///
+/// Drafts join the other pages when [`BuildOptions::drafts`] is set.
+///
+/// Moved pages (`redirect_to`) are split off and returned separately: they
+/// only get a redirect stub, and everything else skips them.
+fn load_content(
+    root: &Path,
+    ctx: &BuildContext,
+    errors: &mut PageErrors,
+) -> Result<(ContentSet, Vec<Page>)> {
+    let config = &ctx.config;
+    let mut content = discover_loadable_content(root, config.slugify, errors)?;
+    if ctx.publish_drafts {
+        content.pages.append(&mut content.drafts);
+    }
+    if config.lastmod_from_git {
+        lastmod::apply_git_lastmod(root, &mut content);
+    }
+    if let Some(caser) = TitleCaser::from_config(config) {
+        for page in content.pages.iter_mut().chain(&mut content.drafts) {
+            page.frontmatter.title = caser.apply(&page.frontmatter.title);
+        }
+    }
+    if !ctx.transforms.is_empty() {
+        for pages in [&mut content.pages, &mut content.drafts] {
+            pages.retain_mut(|page| {
+                if page.format == ContentFormat::Html {
+                    return true;
+                }
+                let transformed = transform::transform_markdown(&ctx.transforms, page)
+                    .context("content transform failed");
+                errors.collect(&page.source_path, transformed).is_some()
+            });
+        }
+    }
+    let redirects = content
+        .pages
+        .extract_if(.., |page| page.frontmatter.redirect_to.is_some())
+        .collect();
+    Ok((content, redirects))
+}
+
 // ── Single-page rendering ──
 
-/// Renders a single page and writes it to the output directory.
+/// Gives posts (and drafts) without a `template` of their own the
+/// `page_template` of their section.
+fn apply_page_templates(content: &mut ContentSet, sections: &[Section]) {
+    for page in content.pages.iter_mut().chain(&mut content.drafts) {
+        if page.frontmatter.template.is_some() {
+            continue;
+        }
+        if let PageKind::Post {
+            section: Some(slug),
+        } = &page.kind
+            && let Some(section) = sections.iter().find(|section| section.slug == *slug)
+        {
+            page.frontmatter.template.clone_from(&section.page_template);
+        }
+    }
+}
+
+/// Renders every published page, plus drafts at their preview URLs when
+/// `[drafts] share` is enabled, then saves the directive cache.
+///
+/// `related` and `translations` hold each published page's related posts
+/// and translations, index-aligned with `content.pages`.
+///
+/// A page that fails to build does not stop the others: the failures are
+/// added to the report in `progress`, to be reported once the rest of the
+/// site is built, along with the pages and bundle assets written.
+///
+/// Each page's `raw_content` is emptied once the page is built.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "each argument is a distinct piece of site-wide state shared by every page"
+)]
+fn build_pages(
+    ctx: &BuildContext,
+    content: &mut ContentSet,
+    output_dir: &Path,
+    section_titles: &HashMap<&str, &str>,
+    content_links: &Arc<ContentLinks>,
+    summaries: Vec<Result<String>>,
+    related: &[Vec<PageSummary>],
+    translations: &[Vec<Translation>],
+    progress: &mut Progress<'_>,
+) -> Result<()> {
+    let mut timings = PageTimings::default();
+    let pages = content
+        .pages
+        .iter_mut()
+        .zip(summaries)
+        .zip(related)
+        .zip(translations);
+    for (((page, summary_html), related), translations) in pages {
+        let built = progress.report.failures.collect(
+            &page.source_path,
+            page.output_path(&content.content_dir)
+                .and_then(|output_path| {
+                    build_page(
+                        ctx,
+                        page,
+                        &output_path,
+                        output_dir,
+                        section_titles,
+                        content_links,
+                        &summary_html?,
+                        PageLinks {
+                            related,
+                            translations,
+                        },
+                    )
+                    .map(|page_timings| timings.add(page_timings))?;
+                    Ok(page_url(&ctx.config.base_url, &output_path))
+                }),
+        );
+        if let Some(url) = built {
+            progress.page_built(page, &url);
+        }
+        // Nothing reads the body once the page is built; dropping it keeps
+        // memory flat however many pages the site has.
+        page.raw_content = String::new();
+    }
+    if ctx.config.drafts.share {
+        drafts::build_shared_drafts(
+            ctx,
+            &mut content.drafts,
+            &content.content_dir,
+            output_dir,
+            section_titles,
+            content_links,
+            &mut progress.report.failures,
+        )?;
+    }
+    progress.report.render_steps = timings.into_steps();
+    // Every directive is rendered by now, so the cache is complete.
+    ctx.template_engine
+        .save_directive_cache()
+        .context("failed to save directive cache")?;
+    if let Some(cache) = &ctx.highlight_cache {
+        cache.save().context("failed to save highlight cache")?;
+    }
+    Ok(())
+}
+
+/// Links from a page to other pages, found across the whole site.
+#[derive(Debug, Default, Clone, Copy)]
+struct PageLinks<'a> {
+    /// Related posts, best match first.
+    related: &'a [PageSummary],
+    /// Translations, the page itself included.
+    translations: &'a [Translation],
+}
+
+/// Renders a single page, with its already rendered `summary_html`, and
+/// writes it to `output_path` under the output directory, returning the
+/// time each step took.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "each argument is a distinct piece of site-wide or per-page state"
+)]
 fn build_page(
     ctx: &BuildContext,
     page: &Page,
-    content_dir: &Path,
+    output_path: &Path,
     output_dir: &Path,
     section_titles: &HashMap<&str, &str>,
-) -> Result<()> {
-    let options = RenderOptions::from_params(&ctx.config.params);
-
-    let rendered = render_page(
-        &page.raw_content,
-        &ctx.syntax_set,
-        &ctx.template_engine,
-        &options,
-        page.source_path.parent(),
-    )
-    .with_context(|| format!("failed to render {}", page.source_path.display()))?;
+    content_links: &Arc<ContentLinks>,
+    summary_html: &str,
+    links: PageLinks<'_>,
+) -> Result<PageTimings> {
+    let mut timings = PageTimings::default();
+    let mut step_start = Instant::now();
+    let rendered = render_content(ctx, page, content_links)
+        .with_context(|| format!("failed to render {}", page.source_path.display()))?;
 
-    // `output_path` already includes the source and content-dir paths in
-    // its error, so no extra `with_context` is needed here.
-    let output_path = page.output_path(content_dir)?;
-    let url = page_url(&ctx.config.base_url, &output_path);
+    let url = page_url(&ctx.config.base_url, output_path);
 
     let featured_image = resolve_featured_image(page.frontmatter.featured_image.as_ref(), &url);
+    // Cards need a page directory of their own to sit in.
+    let og_card = ctx
+        .og_images
+        .as_ref()
+        .filter(|_| featured_image.is_none() && output_path.ends_with("index.html"));
+    let og_image = match &featured_image {
+        Some(image) => Some(image.src.clone()),
+        None => og_card.map(|_| resolve_relative_url(OG_IMAGE_FILE, &url)),
+    };
+    timings.markdown = lap(&mut step_start);
+    let thumbnail = thumbnail::page_thumbnail(page, ctx.config.thumbnails.width);
     let page_css = find_page_css(&page.assets, page.source_path.parent(), &url);
-    let vars = PostTemplateVars {
+    let mut vars = PostTemplateVars {
+        id: &page.id,
         title: &page.frontmatter.title,
         description: page
             .frontmatter
@@ -245,50 +1065,228 @@ fn build_page(
             .as_deref()
             .or(page.summary.as_deref())
             .unwrap_or(""),
+        summary_html,
         url: &url,
         featured_image,
+        featured_image_thumb: thumbnail
+            .as_ref()
+            .map(|thumbnail| resolve_relative_url(&thumbnail.src, &url)),
+        audio: podcast::page_audio(page, &url),
+        og_image,
         page_css,
         date: page
             .frontmatter
             .date
             .map(|date| format_page_date(date, ctx.time_zone.as_ref())),
+        updated: page
+            .frontmatter
+            .updated
+            .map(|updated| format_page_date(updated, ctx.time_zone.as_ref())),
+        authors: authors::page_authors(page, &ctx.config),
         section: page_section(page, &ctx.config.base_url, section_titles),
         assets: rendered.assets,
         content: &rendered.content_html,
         toc: &rendered.toc_html,
+        toc_entries: &rendered.toc_entries,
+        related: links.related,
+        noindex: page.frontmatter.noindex.unwrap_or(false),
+        language: translation::page_language(page, &ctx.config.language),
+        translations: links.translations,
+        json_ld: String::new(),
+        h_entry: String::new(),
         config: &ctx.config,
     };
+    vars.json_ld = json_ld::page_json_ld(&vars, page);
+    vars.h_entry = microformats::page_h_entry(&vars, page);
+    let wrapped_content;
+    if ctx.config.microformats.wrap_content {
+        wrapped_content = microformats::wrap_content(&vars.h_entry, vars.content);
+        vars.content = &wrapped_content;
+    }
 
-    let html = match page.kind {
-        PageKind::Page if ctx.template_engine.has_template("page.html") => {
-            ctx.template_engine.render_page(&vars)
-        }
-        _ => ctx.template_engine.render_post(&vars),
-    }
-    .with_context(|| format!("failed to render {}", page.source_path.display()))?;
-
-    let dest = output_dir.join(&output_path);
-    write_output(&dest, &html).with_context(|| format!("failed to write {}", dest.display()))?;
-
-    if let Some(bundle_dir) = page.source_path.parent() {
-        let asset_output_dir = dest.parent().expect("output file should have a parent");
-        for asset in &page.assets {
-            let relative = asset.strip_prefix(bundle_dir).with_context(|| {
-                format!(
-                    "asset {} is not under {}",
-                    asset.display(),
-                    bundle_dir.display()
-                )
-            })?;
-            let asset_dest = asset_output_dir.join(relative);
-            copy_file(asset, &asset_dest)
-                .with_context(|| format!("failed to copy asset {}", asset.display()))?;
-        }
+    let template = match (&page.frontmatter.template, &page.kind) {
+        (Some(template), _) => template.as_str(),
+        (None, PageKind::Page) if ctx.template_engine.has_template("page.html") => "page.html",
+        _ => "post.html",
+    };
+    let dest = output_file(output_dir, output_path)?;
+    ctx.template_engine
+        .with_bundle_dir(page.bundle_dir(), || {
+            write_page_html(ctx, page, template, &vars, &dest)
+        })
+        .with_context(|| format!("failed to render {}", page.source_path.display()))?;
+    timings.templates = lap(&mut step_start);
+
+    let page_dir = dest.parent().expect("output file should have a parent");
+    if let Some(renderer) = og_card {
+        let card_dest = page_dir.join(OG_IMAGE_FILE);
+        renderer
+            .write(&ctx.template_engine, &vars, &card_dest)
+            .with_context(|| format!("failed to write Open Graph image {}", card_dest.display()))?;
     }
+    write_page_assets(ctx, page, page_dir, &rendered.images, thumbnail)?;
+    timings.output = lap(&mut step_start);
+    Ok(timings)
+}
 
+/// Renders `page` through `template` into `dest`, streamed to the file so
+/// the page is never held in memory whole, unless [`ContentTransform`]s
+/// need the whole HTML to rewrite it.
+fn write_page_html(
+    ctx: &BuildContext,
+    page: &Page,
+    template: &str,
+    vars: &PostTemplateVars<'_>,
+    dest: &Path,
+) -> Result<()> {
+    if ctx.transforms.is_empty() {
+        return write_output_with(dest, |out| {
+            ctx.template_engine.render_template_to(template, vars, out)
+        });
+    }
+    let html = ctx.template_engine.render_template(template, vars)?;
+    let html = transform::transform_html(&ctx.transforms, page, html)
+        .context("content transform failed")?;
+    write_output(dest, html)
+}
+
+/// Returns the time elapsed since `start`, and restarts it.
+fn lap(start: &mut Instant) -> Duration {
+    let now = Instant::now();
+    let elapsed = now - *start;
+    *start = now;
+    elapsed
+}
+
+/// Copies a page bundle's assets to `asset_output_dir`, with the responsive
+/// `images` variants and featured image `thumbnail` generated from them.
+fn write_page_assets(
+    ctx: &BuildContext,
+    page: &Page,
+    asset_output_dir: &Path,
+    images: &[ImageVariant],
+    thumbnail: Option<Thumbnail>,
+) -> Result<()> {
+    let Some(bundle_dir) = page.source_path.parent() else {
+        return Ok(());
+    };
+    for asset in &page.assets {
+        let relative = asset.strip_prefix(bundle_dir).with_context(|| {
+            format!(
+                "asset {} is not under {}",
+                asset.display(),
+                bundle_dir.display()
+            )
+        })?;
+        let asset_dest = asset_output_dir.join(relative);
+        copy_file(asset, &asset_dest)
+            .with_context(|| format!("failed to copy asset {}", asset.display()))?;
+    }
+    let quality = ctx.config.images.quality;
+    resize::write_image_variants(bundle_dir, asset_output_dir, images, quality)?;
+    if let Some(thumbnail) = thumbnail {
+        let thumbnail_dest = asset_output_dir.join(&thumbnail.src);
+        thumbnail::write_thumbnail(
+            &thumbnail.source,
+            &thumbnail_dest,
+            ctx.config.thumbnails.width,
+            quality,
+        )
+        .with_context(|| format!("failed to write thumbnail {}", thumbnail_dest.display()))?;
+    }
     Ok(())
 }
 
+/// Renders a page body according to its source format.
+///
+/// Org and AsciiDoc bodies were converted to markdown on load and go through
+/// the markdown pipeline, after snippet expansion. HTML bodies bypass it
+/// entirely: no snippets, directives, shortcodes, or `ToC`, and no
+/// auto-detected assets; only `[markdown] sanitize` applies.
+fn render_content(
+    ctx: &BuildContext,
+    page: &Page,
+    content_links: &Arc<ContentLinks>,
+) -> Result<RenderedPage> {
+    if page.format == ContentFormat::Html {
+        let content_html = if ctx.config.markdown.sanitize {
+            sanitize_html(&page.raw_content)
+        } else {
+            page.raw_content.clone()
+        };
+        return Ok(RenderedPage {
+            content_html,
+            toc_html: String::new(),
+            toc_entries: Vec::new(),
+            assets: PageAssets::default(),
+            images: Vec::new(),
+        });
+    }
+    warn_directive_problems(page);
+    let content = ctx.snippets.expand(&page.raw_content)?;
+    let options = page_render_options(ctx, page, content_links)?;
+    ctx.template_engine.with_bundle_dir(page.bundle_dir(), || {
+        render_page(
+            &content,
+            &ctx.syntax_set,
+            &ctx.template_engine,
+            &options,
+            page.source_path.parent(),
+        )
+    })
+}
+
+/// Logs a warning, located in `page`'s source file, for each problem in its
+/// directive markup, which would otherwise render as text or lose
+/// attributes without a word.
+fn warn_directive_problems(page: &Page) {
+    for problem in directive_problems(&page.raw_content) {
+        let line = page.body_line.map(|first| first + problem.line - 1);
+        tracing::warn!(
+            file = %page.source_path.display(),
+            line,
+            "{}",
+            problem.message
+        );
+    }
+}
+
+/// Returns the options `page` renders with: the site's, plus the page's
+/// bibliography, `toc = false`, and responsive images for page bundles.
+fn page_render_options(
+    ctx: &BuildContext,
+    page: &Page,
+    content_links: &Arc<ContentLinks>,
+) -> Result<RenderOptions> {
+    let source_dir = page.source_path.parent();
+    let bibliography = page
+        .frontmatter
+        .bibliography
+        .as_ref()
+        .map(|file| {
+            let rel = checked_relative_path(file)?;
+            Bibliography::load(&source_dir.unwrap_or(Path::new("")).join(rel))
+        })
+        .transpose()?;
+    let mut options = RenderOptions {
+        bibliography,
+        content_links: Some(Arc::clone(content_links)),
+        static_dirs: ctx.static_dirs.clone(),
+        bundle_assets: page.assets.clone(),
+        highlight_cache: ctx.highlight_cache.clone(),
+        i18n: Some(ctx.i18n.clone()),
+        ..RenderOptions::from_config(&ctx.config)
+    };
+    if page.frontmatter.toc == Some(false) {
+        options.toc_levels = None;
+    }
+    // Only page bundles have images of their own to process.
+    if page.assets.is_empty() {
+        options.images = Images::default();
+    }
+    Ok(options)
+}
+
 /// Finds a `style.css` file in the page bundle's assets and returns its
 /// resolved URL path (e.g., `/posts/my-post/style.css`).
 fn find_page_css(assets: &[PathBuf], bundle_dir: Option<&Path>, page_url: &str) -> Option<String> {
@@ -302,9 +1300,10 @@ fn find_page_css(assets: &[PathBuf], bundle_dir: Option<&Path>, page_url: &str)
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
     use std::fs;
 
-    use indoc::indoc;
+    use indoc::{formatdoc, indoc};
 
     use super::*;
 
@@ -328,6 +1327,11 @@ mod tests {
         }
     }
 
+    /// Builds the site, failing if any page failed.
+    fn build_all(root: &Path, options: BuildOptions<'_>) -> Result<()> {
+        build(root, options)?.into_result()
+    }
+
     // ── build ──
 
     #[test]
@@ -403,6 +1407,12 @@ mod tests {
             html.contains(r#"<link rel="canonical" href="https://example.com/posts/hello/">"#),
             "should have canonical URL, html:\n{html}"
         );
+        assert!(
+            html.contains(
+                r#"<script type="application/ld+json">{"@context":"https://schema.org","@graph":[{"@type":"BlogPosting","headline":"Hello World","#
+            ),
+            "should have JSON-LD, html:\n{html}"
+        );
 
         assert!(
             html.contains("<h1>Hello World</h1>"),
@@ -593,161 +1603,161 @@ mod tests {
     }
 
     #[test]
-    fn build_cleans_stale_output() {
+    fn build_copies_passthrough_content_files() {
         let root = tempfile::tempdir().unwrap();
-        fs::write(root.path().join("config.toml"), "").unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                [content]
+                passthrough = ["pdf"]
+            "#},
+        )
+        .unwrap();
         copy_templates(&root.path().join("templates"));
-
-        let output_dir = root.path().join("public");
-        fs::create_dir_all(output_dir.join("old")).unwrap();
-        fs::write(output_dir.join("old").join("stale.html"), "stale").unwrap();
+        write_test_file(root.path(), "content/resume.pdf", "pdf-data");
+        write_test_file(root.path(), "content/notes/slides.pdf", "slides-data");
+        write_test_file(root.path(), "content/notes/todo.txt", "not allowlisted");
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        assert!(
-            !output_dir.join("old").exists(),
-            "stale output should be removed"
+        let output_dir = root.path().join("public");
+        assert_eq!(
+            fs::read_to_string(output_dir.join("resume.pdf")).unwrap(),
+            "pdf-data"
         );
-    }
-
-    // ── build: theme ──
-
-    fn setup_theme(root: &Path, theme_name: &str) {
-        let theme_dir = root.join("themes").join(theme_name);
-        let tmpl_dir = theme_dir.join("templates");
-        fs::create_dir_all(&tmpl_dir).unwrap();
-        copy_templates(&tmpl_dir);
-        fs::write(theme_dir.join("theme.toml"), "").unwrap();
+        assert_eq!(
+            fs::read_to_string(output_dir.join("notes").join("slides.pdf")).unwrap(),
+            "slides-data"
+        );
+        assert!(!output_dir.join("notes").join("todo.txt").exists());
     }
 
     #[test]
-    fn build_with_theme() {
+    fn build_publishes_section_assets_once() {
         let root = tempfile::tempdir().unwrap();
         fs::write(
             root.path().join("config.toml"),
-            indoc! {r#"
-                base_url = "https://example.com"
-                title = "Test"
-                theme = "my-theme"
-            "#},
+            r#"base_url = "https://example.com""#,
         )
         .unwrap();
-        setup_theme(root.path(), "my-theme");
-
+        copy_templates(&root.path().join("templates"));
+        write_test_file(root.path(), "content/posts/_assets/logo.png", "png-data");
         write_page(
             root.path(),
             "posts/hello",
-            indoc! {r#"
-                +++
-                title = "Hello"
-                +++
-                Body
-            "#},
+            "+++\ntitle = \"Hello\"\n+++\n![Logo](../_assets/logo.png)\n",
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/flat.md",
+            "+++\ntitle = \"Flat\"\n+++\n[logo](_assets/logo.png)\n",
         );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output = root
-            .path()
-            .join("public")
-            .join("posts")
-            .join("hello")
-            .join("index.html");
-        assert!(output.exists(), "output file should exist");
-        let html = fs::read_to_string(&output).unwrap();
-        assert!(
-            html.contains("<h1>Hello</h1>"),
-            "should render with theme templates, html:\n{html}"
+        let posts_dir = root.path().join("public").join("posts");
+        assert_eq!(
+            fs::read_to_string(posts_dir.join("_assets").join("logo.png")).unwrap(),
+            "png-data"
         );
+        for page in ["hello", "flat"] {
+            let html = fs::read_to_string(posts_dir.join(page).join("index.html")).unwrap();
+            assert!(
+                html.contains(r#"="https://example.com/posts/_assets/logo.png""#),
+                "{page}:\n{html}"
+            );
+        }
     }
 
     #[test]
-    fn build_theme_static_files_with_site_override() {
+    fn build_renders_html_content_through_layout() {
         let root = tempfile::tempdir().unwrap();
-        fs::write(root.path().join("config.toml"), r#"theme = "my-theme""#).unwrap();
-        setup_theme(root.path(), "my-theme");
-
-        let theme_static = root.path().join("themes/my-theme/static");
-        fs::create_dir_all(&theme_static).unwrap();
-        fs::write(theme_static.join("theme.css"), "theme-default").unwrap();
-        fs::write(theme_static.join("shared.css"), "from-theme").unwrap();
-
-        let site_static = root.path().join("static");
-        fs::create_dir_all(&site_static).unwrap();
-        fs::write(site_static.join("shared.css"), "from-site").unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_test_file(
+            root.path(),
+            "content/landing.html",
+            indoc! {r#"
+                +++
+                title = "Landing"
+                +++
+                <section class="hero">*not markdown*</section>
+            "#},
+        );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output_dir = root.path().join("public");
-        assert_eq!(
-            fs::read_to_string(output_dir.join("theme.css")).unwrap(),
-            "theme-default",
-            "theme-only static file should be copied"
+        let html = fs::read_to_string(
+            root.path()
+                .join("public")
+                .join("landing")
+                .join("index.html"),
+        )
+        .unwrap();
+        assert!(
+            html.contains(r#"<section class="hero">*not markdown*</section>"#),
+            "HTML body should be inserted verbatim, html:\n{html}"
         );
-        assert_eq!(
-            fs::read_to_string(output_dir.join("shared.css")).unwrap(),
-            "from-site",
-            "site static file should override theme"
+        assert!(
+            html.contains("<title>Landing"),
+            "page should use the site layout, html:\n{html}"
         );
     }
 
-    // ── build: page template ──
-
+    #[cfg(feature = "org")]
     #[test]
-    fn build_uses_page_template_for_standalone() {
+    fn build_renders_org_content() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
-
-        write_page(
+        write_test_file(
             root.path(),
-            "about-me",
-            indoc! {r#"
-                +++
-                title = "About Me"
-                +++
-                Hello world.
-            "#},
+            "content/notes.org",
+            indoc! {"
+                #+title: Notes
+
+                * Section
+                Some /italic/ text.
+            "},
         );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output = root
-            .path()
-            .join("public")
-            .join("about-me")
-            .join("index.html");
-        assert!(output.exists(), "should generate about-me page");
-        let html = fs::read_to_string(&output).unwrap();
-        assert!(
-            html.contains(r#"<article class="page">"#),
-            "should use page.html template, html:\n{html}"
-        );
+        let html = fs::read_to_string(root.path().join("public").join("notes").join("index.html"))
+            .unwrap();
+        assert!(html.contains("<em>italic</em>"), "html:\n{html}");
+        assert!(html.contains(">Section</"), "html:\n{html}");
     }
 
     #[test]
-    fn build_renders_dates_in_configured_timezone() {
+    fn build_rewrites_links_to_content_files() {
         let root = tempfile::tempdir().unwrap();
         fs::write(
             root.path().join("config.toml"),
-            indoc! {r#"
-                timezone = "Asia/Shanghai"
-            "#},
+            r#"base_url = "https://example.com""#,
         )
         .unwrap();
         copy_templates(&root.path().join("templates"));
-
         write_page(
             root.path(),
-            "posts/note/hello",
+            "posts/first",
             indoc! {r#"
                 +++
-                title = "Hello"
-                date = "2026-03-13T09:36:00Z"
+                title = "First"
                 +++
-                Body
+                See [the second post](../second/index.md#intro) and [about](@/about.md).
             "#},
         );
+        write_page(
+            root.path(),
+            "posts/second",
+            "+++\ntitle = \"Second\"\n+++\n",
+        );
+        write_test_file(
+            root.path(),
+            "content/about.md",
+            "+++\ntitle = \"About\"\n+++\n",
+        );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
@@ -755,41 +1765,59 @@ mod tests {
             root.path()
                 .join("public")
                 .join("posts")
-                .join("note")
-                .join("hello")
+                .join("first")
                 .join("index.html"),
         )
         .unwrap();
         assert!(
-            html.contains("2026-03-13T17:36:00+08:00"),
-            "should render the configured time zone offset, html:\n{html}"
+            html.contains(r#"<a href="https://example.com/posts/second/#intro">"#),
+            "html:\n{html}"
         );
         assert!(
-            !html.contains("2026-03-13T09:36:00Z"),
-            "should not leave the date in UTC, html:\n{html}"
+            html.contains(r#"<a href="https://example.com/about/">"#),
+            "html:\n{html}"
         );
     }
 
-    // ── build: page CSS ──
-
     #[test]
-    fn build_injects_page_css_link() {
+    fn build_broken_content_link_returns_error() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/first",
+            "+++\ntitle = \"First\"\n+++\n[gone](../gone/index.md)\n",
+        );
+
+        let err = build_all(root.path(), BuildOptions::default()).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("broken link `../gone/index.md`"),
+            "got: {err:#}"
+        );
+    }
 
+    #[test]
+    fn build_resolves_citations_from_page_bibliography() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
         write_page(
             root.path(),
-            "posts/hello",
+            "posts/paper",
             indoc! {r#"
                 +++
-                title = "Hello"
+                title = "Paper"
+                bibliography = "refs.bib"
                 +++
-                Body
+                Annotated programs [@castellan84].
             "#},
         );
-        let bundle = root.path().join("content").join("posts").join("hello");
-        fs::write(bundle.join("style.css"), ".custom { color: red; }").unwrap();
+        write_test_file(
+            root.path(),
+            "content/posts/paper/refs.bib",
+            "@article{castellan84, author = {Mira Castellan}, title = {Annotated Programs}, year = {1984}}",
+        );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
@@ -797,377 +1825,427 @@ mod tests {
             root.path()
                 .join("public")
                 .join("posts")
-                .join("hello")
+                .join("paper")
                 .join("index.html"),
         )
         .unwrap();
         assert!(
-            html.contains(r#"<link rel="stylesheet" href="/posts/hello/style.css">"#),
-            "should inject per-page CSS link, html:\n{html}"
+            html.contains(r##"<a href="#ref-castellan84">1</a>"##),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(
+                r#"<li id="ref-castellan84">Mira Castellan (1984). Annotated Programs.</li>"#
+            ),
+            "html:\n{html}"
         );
     }
 
     #[test]
-    fn build_omits_page_css_without_style() {
+    fn build_rejects_bibliography_outside_content() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
-
         write_page(
             root.path(),
-            "posts/hello",
+            "posts/paper",
             indoc! {r#"
                 +++
-                title = "Hello"
+                title = "Paper"
+                bibliography = "../../../secret.bib"
                 +++
-                Body
+                Body.
             "#},
         );
 
-        build(root.path(), BuildOptions::default()).unwrap();
-
-        let html = fs::read_to_string(
-            root.path()
-                .join("public")
-                .join("posts")
-                .join("hello")
-                .join("index.html"),
-        )
-        .unwrap();
+        let err = build_all(root.path(), BuildOptions::default()).unwrap_err();
         assert!(
-            !html.contains("style.css"),
-            "should NOT inject page CSS link when no style.css, html:\n{html}"
+            format!("{err:#}").contains("path traversal not allowed"),
+            "got: {err:#}"
         );
     }
 
-    // ── build: home page ──
-
     #[test]
-    fn build_generates_home_page() {
+    fn build_writes_redirect_stub_for_moved_page() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
-
         write_page(
             root.path(),
-            "posts/note/hello",
+            "posts/moved",
             indoc! {r#"
                 +++
-                title = "Hello"
-                date = "2026-01-01T00:00:00Z"
+                title = "Moved Post"
+                tags = ["moved"]
+                redirect_to = "https://new.example/posts/moved/"
                 +++
-                Body
+                Old body.
             "#},
         );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let home = root.path().join("public").join("index.html");
-        assert!(home.exists(), "should generate home page /index.html");
-        let html = fs::read_to_string(&home).unwrap();
-        assert!(
-            html.contains("Hello"),
-            "home page should list posts, html:\n{html}"
-        );
+        let public = root.path().join("public");
+        let html =
+            fs::read_to_string(public.join("posts").join("moved").join("index.html")).unwrap();
         assert!(
-            html.contains(r#"<a href="http://localhost:5456/posts/note/hello/">Hello</a>"#),
-            "home page should link to the post under /posts/, html:\n{html}"
+            html.contains(
+                r#"<meta http-equiv="refresh" content="0; url=https://new.example/posts/moved/">"#
+            ),
+            "html:\n{html}"
         );
+        assert!(!html.contains("Old body."), "html:\n{html}");
+        let sitemap = fs::read_to_string(public.join("sitemap.xml")).unwrap();
+        assert!(!sitemap.contains("/posts/moved/"), "sitemap:\n{sitemap}");
+        assert!(!public.join("tags").join("moved").exists());
     }
 
     #[test]
-    fn build_home_orders_by_date_descending() {
+    fn build_writes_alias_redirects() {
         let root = tempfile::tempdir().unwrap();
-        fs::write(root.path().join("config.toml"), "").unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            "base_url = \"https://example.com\"\n[redirects]\nnetlify = true\n",
+        )
+        .unwrap();
         copy_templates(&root.path().join("templates"));
-
         write_page(
             root.path(),
-            "posts/aaa-old",
+            "posts/new",
             indoc! {r#"
                 +++
-                title = "Old Post"
-                date = "2025-01-01T00:00:00Z"
+                title = "New Post"
+                aliases = ["/old-url/", "/2019/post.html"]
                 +++
-                Body
+                Body.
             "#},
         );
-        write_page(
-            root.path(),
-            "posts/zzz-new",
-            indoc! {r#"
-                +++
-                title = "New Post"
-                date = "2026-06-01T00:00:00Z"
-                +++
-                Body
-            "#},
-        );
-
-        build(root.path(), BuildOptions::default()).unwrap();
-
-        let html = fs::read_to_string(root.path().join("public").join("index.html")).unwrap();
-        let new_pos = html.find("New Post").expect("should list New Post");
-        let old_pos = html.find("Old Post").expect("should list Old Post");
-        assert!(
-            new_pos < old_pos,
-            "newer post should appear before older post on home page, html:\n{html}"
-        );
-    }
-
-    #[test]
-    fn build_empty_home_page() {
-        let root = tempfile::tempdir().unwrap();
-        fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates(&root.path().join("templates"));
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let home = root.path().join("public").join("index.html");
-        assert!(
-            home.exists(),
-            "should generate home page even with zero posts"
+        let public = root.path().join("public");
+        for stub in [
+            public.join("old-url").join("index.html"),
+            public.join("2019").join("post.html"),
+        ] {
+            let html = fs::read_to_string(&stub).unwrap();
+            assert!(
+                html.contains(
+                    r#"<meta http-equiv="refresh" content="0; url=https://example.com/posts/new/">"#
+                ),
+                "{}:\n{html}",
+                stub.display()
+            );
+        }
+        assert_eq!(
+            fs::read_to_string(public.join("_redirects")).unwrap(),
+            indoc! {"
+                /old-url/ https://example.com/posts/new/ 301
+                /2019/post.html https://example.com/posts/new/ 301
+            "}
         );
     }
 
     #[test]
-    fn build_orphan_posts_on_home_not_in_sections() {
+    fn build_alias_colliding_with_page_returns_error() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
-
         write_page(
             root.path(),
-            "posts/note/sectioned",
+            "posts/a",
             indoc! {r#"
                 +++
-                title = "Sectioned Post"
-                date = "2026-01-01T00:00:00Z"
+                title = "A"
+                aliases = ["/posts/b/"]
                 +++
-                Body
             "#},
         );
+        write_page(root.path(), "posts/b", "+++\ntitle = \"B\"\n+++\n");
+
+        let err = format!(
+            "{:#}",
+            build_all(root.path(), BuildOptions::default()).unwrap_err()
+        );
+        assert!(err.contains("alias `/posts/b/`"), "got: {err}");
+        assert!(err.contains("collides with"), "got: {err}");
+    }
+
+    #[test]
+    fn build_shares_drafts_under_preview_urls() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            "[drafts]\nshare = true\nsecret = \"s3cret\"\n",
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
         write_page(
             root.path(),
-            "posts/orphan",
+            "posts/wip",
             indoc! {r#"
                 +++
-                title = "Orphan Post"
-                date = "2026-01-02T00:00:00Z"
+                title = "Work in Progress"
+                tags = ["wip"]
+                draft = true
                 +++
-                Body
+                Draft body.
             "#},
         );
+        write_test_file(root.path(), "content/posts/wip/diagram.svg", "<svg/>");
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let home_html = fs::read_to_string(root.path().join("public").join("index.html")).unwrap();
+        let public = root.path().join("public");
+        assert!(!public.join("posts").join("wip").exists());
+        let previews: Vec<_> = fs::read_dir(public.join("drafts"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(previews.len(), 1, "previews: {previews:?}");
+        let preview = &previews[0];
         assert!(
-            home_html.contains("Sectioned Post"),
-            "sectioned post should also appear on home page, html:\n{home_html}"
-        );
-        assert!(
-            home_html.contains("Orphan Post"),
-            "orphan post should appear on home page, html:\n{home_html}"
+            preview
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("wip-"),
+            "preview: {}",
+            preview.display()
         );
+        let html = fs::read_to_string(preview.join("index.html")).unwrap();
+        assert!(html.contains("Draft body."), "html:\n{html}");
+        assert!(preview.join("diagram.svg").exists());
+
+        let sitemap = fs::read_to_string(public.join("sitemap.xml")).unwrap();
+        assert!(!sitemap.contains("/drafts/"), "sitemap:\n{sitemap}");
+        let feed = fs::read_to_string(public.join("index.xml")).unwrap();
+        assert!(!feed.contains("Work in Progress"), "feed:\n{feed}");
+        assert!(!public.join("tags").join("wip").exists());
+    }
 
-        let note_html = fs::read_to_string(
-            root.path()
-                .join("public")
-                .join("posts")
-                .join("note")
-                .join("index.html"),
+    #[cfg(unix)]
+    #[test]
+    fn build_keeps_shared_drafts_out_of_search() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tempfile::tempdir().unwrap();
+        // Stands in for Pagefind, recording the files it would index.
+        let binary = root.path().join("fake-pagefind");
+        fs::write(
+            &binary,
+            "#!/bin/sh\nfind \"$2\" -name '*.html' > \"$2/../indexed\"\n",
         )
         .unwrap();
-        assert!(
-            note_html.contains("Sectioned Post"),
-            "sectioned post should appear in section page, html:\n{note_html}"
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            formatdoc! {r#"
+                [drafts]
+                share = true
+                secret = "s3cret"
+
+                [search]
+                enabled = true
+                binary = "{}"
+            "#, binary.display()},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            "+++\ntitle = \"Hello\"\n+++\nPublished body.\n",
         );
-        assert!(
-            !note_html.contains("Orphan Post"),
-            "orphan post should NOT appear in section page, html:\n{note_html}"
+        write_page(
+            root.path(),
+            "posts/wip",
+            "+++\ntitle = \"WIP\"\ndraft = true\n+++\nDraft body.\n",
         );
+
+        build_all(root.path(), BuildOptions::default()).unwrap();
+
+        let indexed = fs::read_to_string(root.path().join("indexed")).unwrap();
+        assert!(indexed.contains("hello"), "indexed:\n{indexed}");
+        assert!(!indexed.contains("drafts"), "indexed:\n{indexed}");
+
+        let public = root.path().join("public");
+        let preview = fs::read_dir(public.join("drafts"))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let html = fs::read_to_string(preview.join("index.html")).unwrap();
+        assert!(html.contains(r#"content="noindex""#), "html:\n{html}");
+        let html = fs::read_to_string(public.join("posts/hello/index.html")).unwrap();
+        assert!(!html.contains(r#"content="noindex""#), "html:\n{html}");
     }
 
     #[test]
-    fn build_skips_home_without_template() {
+    fn build_skips_drafts_without_sharing() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates_except(&root.path().join("templates"), &["home.html"]);
-
+        copy_templates(&root.path().join("templates"));
         write_page(
             root.path(),
-            "posts/note/hello",
-            indoc! {r#"
-                +++
-                title = "Hello"
-                +++
-                Body
-            "#},
+            "posts/wip",
+            "+++\ntitle = \"WIP\"\ndraft = true\n+++\nDraft body.\n",
         );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let home = root.path().join("public").join("index.html");
-        assert!(
-            !home.exists(),
-            "should NOT generate home page without home.html template"
-        );
+        let public = root.path().join("public");
+        assert!(!public.join("drafts").exists());
+        assert!(!public.join("posts").join("wip").exists());
     }
 
     #[test]
-    fn build_home_pagination() {
+    fn build_limits_template_file_reads_to_page_bundles() {
         let root = tempfile::tempdir().unwrap();
-        fs::write(
-            root.path().join("config.toml"),
-            indoc! {r"
-                [params.home]
-                paginate = 2
-            "},
-        )
-        .unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
+        write_test_file(
+            root.path(),
+            "templates/directives/peek.html",
+            "PEEK:{{ read_file(positional_args[0]) }}",
+        );
+        write_page(
+            root.path(),
+            "posts/bundle",
+            "+++\ntitle = \"Bundle\"\n+++\n::: peek {\"notes.txt\"}\n:::\n",
+        );
+        write_test_file(root.path(), "content/posts/bundle/notes.txt", "bundled");
+        write_test_file(
+            root.path(),
+            "content/posts/loose.md",
+            "+++\ntitle = \"Loose\"\n+++\n::: peek {\"secret.md\"}\n:::\n",
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/secret.md",
+            "+++\ntitle = \"S\"\n+++\n",
+        );
 
-        for i in 1..=3 {
-            write_page(
-                root.path(),
-                &format!("posts/note/post-{i}"),
-                &format!(
-                    indoc! {r#"
-                        +++
-                        title = "Post {i}"
-                        date = "2026-01-0{i}T00:00:00Z"
-                        +++
-                        Body
-                    "#},
-                    i = i,
-                ),
-            );
-        }
-
-        build(root.path(), BuildOptions::default()).unwrap();
-
-        let output_dir = root.path().join("public");
-        let page1 = output_dir.join("index.html");
-        assert!(page1.exists(), "should generate home page 1");
-        let html1 = fs::read_to_string(&page1).unwrap();
+        let err = format!(
+            "{:#}",
+            build_all(root.path(), BuildOptions::default()).unwrap_err()
+        );
+        assert!(err.contains("loose.md"), "got: {err}");
         assert!(
-            html1.contains("Page 1 / 2"),
-            "should show pagination, html:\n{html1}"
+            err.contains("read_file requires a page bundle"),
+            "got: {err}"
         );
+        assert!(!err.contains("bundle/index.md"), "got: {err}");
 
-        let page2 = output_dir.join("page").join("2").join("index.html");
-        assert!(page2.exists(), "should generate home page 2");
+        fs::remove_file(root.path().join("content/posts/loose.md")).unwrap();
+        build_all(root.path(), BuildOptions::default()).unwrap();
+        let html = fs::read_to_string(root.path().join("public/posts/bundle/index.html")).unwrap();
+        assert!(html.contains("PEEK:bundled"), "html:\n{html}");
     }
 
     #[test]
-    fn build_standalone_excluded_from_home() {
+    fn build_expands_snippets() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
-
-        write_page(
+        write_test_file(
             root.path(),
-            "posts/note/hello",
-            indoc! {r#"
-                +++
-                title = "Hello Post"
-                date = "2026-01-01T00:00:00Z"
-                +++
-                Body
-            "#},
+            "data/snippets.toml",
+            r#"disclaimer = "*Links may be affiliate links.*""#,
         );
-        write_page(
+        write_test_file(
             root.path(),
-            "about-me",
+            "content/review.md",
             indoc! {r#"
                 +++
-                title = "About Me"
+                title = "Review"
                 +++
-                Bio
+                ::: snippet { "disclaimer" }
+                :::
             "#},
         );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let html = fs::read_to_string(root.path().join("public").join("index.html")).unwrap();
+        let html = fs::read_to_string(root.path().join("public").join("review").join("index.html"))
+            .unwrap();
         assert!(
-            html.contains("Hello Post"),
-            "home page should list posts, html:\n{html}"
+            html.contains("<em>Links may be affiliate links.</em>"),
+            "html:\n{html}"
         );
         assert!(
-            !html.contains("About Me"),
-            "home page should NOT list standalone pages, html:\n{html}"
+            !root.path().join("public").join("_snippets").exists(),
+            "snippet sources should not be published"
         );
     }
 
-    // ── build: posts index ──
-
     #[test]
-    fn build_generates_posts_index() {
+    fn build_unknown_snippet_returns_error() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
-
-        write_page(
-            root.path(),
-            "posts/note/post-a",
-            indoc! {r#"
-                +++
-                title = "Post A"
-                date = "2026-01-01T00:00:00Z"
-                +++
-                Body
-            "#},
-        );
-        write_page(
+        write_test_file(
             root.path(),
-            "posts/essay/post-b",
-            indoc! {r#"
-                +++
-                title = "Post B"
-                date = "2026-01-02T00:00:00Z"
-                +++
-                Body
-            "#},
+            "content/review.md",
+            "+++\ntitle = \"Review\"\n+++\n::: snippet { \"nope\" }\n:::\n",
         );
 
-        build(root.path(), BuildOptions::default()).unwrap();
-
-        let posts_index = root.path().join("public").join("posts").join("index.html");
-        assert!(posts_index.exists(), "should generate /posts/index.html");
-        let html = fs::read_to_string(&posts_index).unwrap();
+        let err = build_all(root.path(), BuildOptions::default()).unwrap_err();
         assert!(
-            html.contains("Post A") && html.contains("Post B"),
-            "posts index should list all posts across sections, html:\n{html}"
+            format!("{err:#}").contains("unknown snippet `nope`"),
+            "got: {err:#}"
         );
     }
 
     #[test]
-    fn build_posts_index_uses_index_title() {
+    fn build_cleans_stale_output() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
 
-        let posts_dir = root.path().join("content").join("posts");
-        fs::create_dir_all(&posts_dir).unwrap();
+        let output_dir = root.path().join("public");
+        fs::create_dir_all(output_dir.join("old")).unwrap();
+        fs::write(output_dir.join("old").join("stale.html"), "stale").unwrap();
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        assert!(
+            !output_dir.join("old").exists(),
+            "stale output should be removed"
+        );
+    }
+
+    // ── build: theme ──
+
+    fn setup_theme(root: &Path, theme_name: &str) {
+        let theme_dir = root.join("themes").join(theme_name);
+        let tmpl_dir = theme_dir.join("templates");
+        fs::create_dir_all(&tmpl_dir).unwrap();
+        copy_templates(&tmpl_dir);
+        fs::write(theme_dir.join("theme.toml"), "").unwrap();
+    }
+
+    #[test]
+    fn build_with_theme() {
+        let root = tempfile::tempdir().unwrap();
         fs::write(
-            posts_dir.join("_index.md"),
+            root.path().join("config.toml"),
             indoc! {r#"
-                +++
-                title = "文章"
-                +++
+                base_url = "https://example.com"
+                title = "Test"
+                theme = "my-theme"
             "#},
         )
         .unwrap();
+        setup_theme(root.path(), "my-theme");
 
         write_page(
             root.path(),
-            "posts/note/hello",
+            "posts/hello",
             indoc! {r#"
                 +++
                 title = "Hello"
-                date = "2026-01-01T00:00:00Z"
                 +++
                 Body
             "#},
@@ -1175,198 +2253,284 @@ mod tests {
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let html = fs::read_to_string(root.path().join("public").join("posts").join("index.html"))
-            .unwrap();
-        assert!(
-            html.contains("文章"),
-            "should use _index.md title for posts index, html:\n{html}"
-        );
-    }
+        let output = root
+            .path()
+            .join("public")
+            .join("posts")
+            .join("hello")
+            .join("index.html");
+        assert!(output.exists(), "output file should exist");
+        let html = fs::read_to_string(&output).unwrap();
+        assert!(
+            html.contains("<h1>Hello</h1>"),
+            "should render with theme templates, html:\n{html}"
+        );
+    }
 
     #[test]
-    fn build_posts_index_generated_even_when_empty() {
+    fn build_site_templates_override_theme() {
         let root = tempfile::tempdir().unwrap();
-        fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates(&root.path().join("templates"));
-
+        fs::write(root.path().join("config.toml"), r#"theme = "my-theme""#).unwrap();
+        setup_theme(root.path(), "my-theme");
+        write_test_file(
+            root.path(),
+            "templates/post.html",
+            "<title>site: {{ title }}</title>",
+        );
         write_page(
             root.path(),
-            "about-me",
+            "posts/hello",
             indoc! {r#"
                 +++
-                title = "About Me"
+                title = "Hello"
                 +++
-                Bio
             "#},
         );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let posts_index = root.path().join("public").join("posts").join("index.html");
+        let output_dir = root.path().join("public");
+        let html = fs::read_to_string(output_dir.join("posts/hello/index.html")).unwrap();
+        assert_eq!(html, "<title>site: Hello</title>");
+        let home = fs::read_to_string(output_dir.join("index.html")).unwrap();
         assert!(
-            posts_index.exists(),
-            "should generate /posts/index.html even with no posts"
+            !home.starts_with("<title>site:"),
+            "templates missing from the site fall back to the theme, html:\n{home}"
         );
     }
 
     #[test]
-    fn build_posts_index_pagination() {
+    fn build_fingerprints_assets_for_asset_url() {
         let root = tempfile::tempdir().unwrap();
         fs::write(
             root.path().join("config.toml"),
-            indoc! {r"
-                [params.section]
-                paginate = 2
-            "},
+            indoc! {r#"
+                theme = "my-theme"
+
+                [assets]
+                fingerprint = true
+
+                [headers]
+                enabled = true
+            "#},
         )
         .unwrap();
-        copy_templates(&root.path().join("templates"));
-
-        for i in 1..=3 {
-            write_page(
-                root.path(),
-                &format!("posts/note/post-{i}"),
-                &format!(
-                    indoc! {r#"
-                        +++
-                        title = "Post {i}"
-                        date = "2026-01-0{i}T00:00:00Z"
-                        +++
-                        Body
-                    "#},
-                    i = i,
-                ),
-            );
-        }
+        setup_theme(root.path(), "my-theme");
+        write_test_file(root.path(), "static/css/style.css", "a {}");
+        write_test_file(
+            root.path(),
+            "templates/post.html",
+            r#"<link href="{{ asset_url('css/style.css') | safe }}">"#,
+        );
+        write_page(root.path(), "posts/hello", "+++\ntitle = \"Hello\"\n+++\n");
 
         build(root.path(), BuildOptions::default()).unwrap();
 
         let output_dir = root.path().join("public");
-        let page1 = output_dir.join("posts").join("index.html");
-        assert!(page1.exists(), "should generate /posts/ page 1");
-        let html1 = fs::read_to_string(&page1).unwrap();
+        let html = fs::read_to_string(output_dir.join("posts/hello/index.html")).unwrap();
+        let href = html
+            .strip_prefix(r#"<link href="http://localhost:5456/"#)
+            .and_then(|rest| rest.strip_suffix(r#"">"#))
+            .unwrap_or_else(|| panic!("html:\n{html}"));
+        let hash = href
+            .strip_prefix("css/style.")
+            .and_then(|rest| rest.strip_suffix(".css"));
+        assert_eq!(hash.map(str::len), Some(8), "href: {href}");
+        assert_eq!(fs::read_to_string(output_dir.join(href)).unwrap(), "a {}");
         assert!(
-            html1.contains("Page 1 / 2"),
-            "should show pagination on /posts/, html:\n{html1}"
+            output_dir.join("css/style.css").exists(),
+            "the original stays"
         );
 
-        let page2 = output_dir
-            .join("posts")
-            .join("page")
-            .join("2")
-            .join("index.html");
-        assert!(page2.exists(), "should generate /posts/ page 2");
+        let headers = fs::read_to_string(output_dir.join("_headers")).unwrap();
+        assert!(
+            headers.contains(&format!(
+                "/{href}\n  Cache-Control: public, max-age=31536000"
+            )),
+            "headers:\n{headers}"
+        );
     }
 
-    // ── build: section pages ──
+    #[test]
+    fn build_precompresses_output() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        fs::write(root.path().join("config.toml"), "[compress]\ngzip = true\n").unwrap();
+
+        build_all(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        assert!(output_dir.join("posts/hello/index.html.gz").exists());
+        assert!(!output_dir.join("posts/hello/index.html.br").exists());
+    }
 
     #[test]
-    fn build_generates_section_pages() {
+    fn build_theme_static_files_with_site_override() {
         let root = tempfile::tempdir().unwrap();
-        fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates(&root.path().join("templates"));
+        fs::write(root.path().join("config.toml"), r#"theme = "my-theme""#).unwrap();
+        setup_theme(root.path(), "my-theme");
 
-        for (section, name) in [("note", "post-a"), ("note", "post-b"), ("essay", "hello")] {
-            write_page(
-                root.path(),
-                &format!("posts/{section}/{name}"),
-                &format!(
-                    indoc! {r#"
-                        +++
-                        title = "{name}"
-                        date = "2026-01-01T00:00:00Z"
-                        +++
-                        Body
-                    "#},
-                    name = name,
-                ),
-            );
-        }
+        let theme_static = root.path().join("themes/my-theme/static");
+        fs::create_dir_all(&theme_static).unwrap();
+        fs::write(theme_static.join("theme.css"), "theme-default").unwrap();
+        fs::write(theme_static.join("shared.css"), "from-theme").unwrap();
+
+        let site_static = root.path().join("static");
+        fs::create_dir_all(&site_static).unwrap();
+        fs::write(site_static.join("shared.css"), "from-site").unwrap();
 
         build(root.path(), BuildOptions::default()).unwrap();
 
         let output_dir = root.path().join("public");
-        let note_index = output_dir.join("posts").join("note").join("index.html");
-        assert!(
-            note_index.exists(),
-            "should generate /posts/note/index.html"
-        );
-        let html = fs::read_to_string(&note_index).unwrap();
-        assert!(
-            html.contains("Note"),
-            "should have section title, html:\n{html}"
-        );
-        assert!(
-            html.contains("post-a") && html.contains("post-b"),
-            "should list section posts, html:\n{html}"
-        );
-        assert!(
-            html.contains(r#"<a href="http://localhost:5456/posts/note/post-a/">post-a</a>"#),
-            "section page should link to posts under /posts/, html:\n{html}"
+        assert_eq!(
+            fs::read_to_string(output_dir.join("theme.css")).unwrap(),
+            "theme-default",
+            "theme-only static file should be copied"
         );
-
-        let essay_index = output_dir.join("posts").join("essay").join("index.html");
-        assert!(
-            essay_index.exists(),
-            "should generate /posts/essay/index.html"
+        assert_eq!(
+            fs::read_to_string(output_dir.join("shared.css")).unwrap(),
+            "from-site",
+            "site static file should override theme"
         );
     }
 
+    // ── build: page template ──
+
     #[test]
-    fn build_skips_archives_without_template() {
+    fn build_uses_page_template_for_standalone() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates_except(&root.path().join("templates"), &["archive.html"]);
+        copy_templates(&root.path().join("templates"));
 
         write_page(
             root.path(),
-            "posts/note/my-post",
+            "about-me",
             indoc! {r#"
                 +++
-                title = "My Post"
+                title = "About Me"
                 +++
-                Body
+                Hello world.
             "#},
         );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let section_index = root
+        let output = root
             .path()
             .join("public")
-            .join("posts")
-            .join("note")
+            .join("about-me")
             .join("index.html");
+        assert!(output.exists(), "should generate about-me page");
+        let html = fs::read_to_string(&output).unwrap();
         assert!(
-            !section_index.exists(),
-            "should NOT generate archive pages without archive.html template"
+            html.contains(r#"<article class="page">"#),
+            "should use page.html template, html:\n{html}"
         );
     }
 
     #[test]
-    fn build_section_uses_index_title() {
+    fn build_uses_frontmatter_and_section_templates() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates(&root.path().join("templates"));
+        let templates_dir = root.path().join("templates");
+        copy_templates(&templates_dir);
+        fs::write(templates_dir.join("landing.html"), "landing: {{ title }}").unwrap();
+        fs::write(templates_dir.join("photo.html"), "photo: {{ title }}").unwrap();
 
-        let section_dir = root.path().join("content").join("posts").join("note");
-        fs::create_dir_all(&section_dir).unwrap();
-        fs::write(
-            section_dir.join("_index.md"),
+        write_page(
+            root.path(),
+            "welcome",
             indoc! {r#"
                 +++
-                title = "笔记"
+                title = "Welcome"
+                template = "landing.html"
+                +++
+            "#},
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/gallery/_index.md",
+            indoc! {r#"
+                +++
+                page_template = "photo.html"
+                +++
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/gallery/sunset",
+            indoc! {r#"
+                +++
+                title = "Sunset"
+                +++
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/gallery/notes",
+            indoc! {r#"
+                +++
+                title = "Notes"
+                template = "landing.html"
+                +++
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let public = root.path().join("public");
+        let read = |path: &str| fs::read_to_string(public.join(path)).unwrap();
+        assert_eq!(read("welcome/index.html"), "landing: Welcome");
+        assert_eq!(read("posts/gallery/sunset/index.html"), "photo: Sunset");
+        assert_eq!(
+            read("posts/gallery/notes/index.html"),
+            "landing: Notes",
+            "page template overrides the section default"
+        );
+    }
+
+    #[test]
+    fn build_missing_frontmatter_template_returns_error() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "welcome",
+            indoc! {r#"
+                +++
+                title = "Welcome"
+                template = "nope.html"
                 +++
             "#},
+        );
+
+        let err = build_all(root.path(), BuildOptions::default()).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed to load nope.html template"),
+            "got: {err:#}"
+        );
+    }
+
+    #[test]
+    fn build_renders_dates_in_configured_timezone() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                timezone = "Asia/Shanghai"
+            "#},
         )
         .unwrap();
+        copy_templates(&root.path().join("templates"));
 
         write_page(
             root.path(),
-            "posts/note/my-post",
+            "posts/note/hello",
             indoc! {r#"
                 +++
-                title = "My Post"
+                title = "Hello"
+                date = "2026-03-13T09:36:00Z"
                 +++
                 Body
             "#},
@@ -1379,91 +2543,105 @@ mod tests {
                 .join("public")
                 .join("posts")
                 .join("note")
+                .join("hello")
                 .join("index.html"),
         )
         .unwrap();
         assert!(
-            html.contains("笔记"),
-            "should use _index.md title, html:\n{html}"
+            html.contains("2026-03-13T17:36:00+08:00"),
+            "should render the configured time zone offset, html:\n{html}"
+        );
+        assert!(
+            !html.contains("2026-03-13T09:36:00Z"),
+            "should not leave the date in UTC, html:\n{html}"
         );
     }
 
+    // ── build: page CSS ──
+
     #[test]
-    fn build_section_pagination() {
+    fn build_injects_page_css_link() {
         let root = tempfile::tempdir().unwrap();
-        fs::write(
-            root.path().join("config.toml"),
-            indoc! {r"
-                [params.section]
-                paginate = 2
-            "},
-        )
-        .unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
 
-        for i in 1..=3 {
-            write_page(
-                root.path(),
-                &format!("posts/note/post-{i}"),
-                &format!(
-                    indoc! {r#"
-                        +++
-                        title = "Post {i}"
-                        date = "2026-01-0{i}T00:00:00Z"
-                        +++
-                        Body
-                    "#},
-                    i = i,
-                ),
-            );
-        }
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                +++
+                Body
+            "#},
+        );
+        let bundle = root.path().join("content").join("posts").join("hello");
+        fs::write(bundle.join("style.css"), ".custom { color: red; }").unwrap();
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output_dir = root.path().join("public");
-        let page1 = output_dir.join("posts").join("note").join("index.html");
-        assert!(page1.exists(), "should generate section page 1");
-        let html1 = fs::read_to_string(&page1).unwrap();
+        let html = fs::read_to_string(
+            root.path()
+                .join("public")
+                .join("posts")
+                .join("hello")
+                .join("index.html"),
+        )
+        .unwrap();
         assert!(
-            html1.contains("Page 1 / 2"),
-            "should show pagination, html:\n{html1}"
+            html.contains(r#"<link rel="stylesheet" href="/posts/hello/style.css">"#),
+            "should inject per-page CSS link, html:\n{html}"
         );
-
-        let page2 = output_dir
-            .join("posts")
-            .join("note")
-            .join("page")
-            .join("2")
-            .join("index.html");
-        assert!(page2.exists(), "should generate section page 2");
     }
 
-    // ── build: sections index ──
-
     #[test]
-    fn build_sections_index_generates_page() {
+    fn build_omits_page_css_without_style() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
 
         write_page(
             root.path(),
-            "posts/note/post-a",
+            "posts/hello",
             indoc! {r#"
                 +++
-                title = "Post A"
-                date = "2026-01-01T00:00:00Z"
+                title = "Hello"
                 +++
                 Body
             "#},
         );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let html = fs::read_to_string(
+            root.path()
+                .join("public")
+                .join("posts")
+                .join("hello")
+                .join("index.html"),
+        )
+        .unwrap();
+        assert!(
+            !html.contains("style.css"),
+            "should NOT inject page CSS link when no style.css, html:\n{html}"
+        );
+    }
+
+    // ── build: home page ──
+
+    #[test]
+    fn build_generates_home_page() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
         write_page(
             root.path(),
-            "posts/essay/post-b",
+            "posts/note/hello",
             indoc! {r#"
                 +++
-                title = "Post B"
-                date = "2026-01-02T00:00:00Z"
+                title = "Hello"
+                date = "2026-01-01T00:00:00Z"
                 +++
                 Body
             "#},
@@ -1471,38 +2649,43 @@ mod tests {
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let sections_index = root
-            .path()
-            .join("public")
-            .join("sections")
-            .join("index.html");
-        assert!(
-            sections_index.exists(),
-            "should generate /sections/index.html"
-        );
-        let html = fs::read_to_string(&sections_index).unwrap();
+        let home = root.path().join("public").join("index.html");
+        assert!(home.exists(), "should generate home page /index.html");
+        let html = fs::read_to_string(&home).unwrap();
         assert!(
-            html.contains("Essay") && html.contains("Note"),
-            "should list section names, html:\n{html}"
+            html.contains("Hello"),
+            "home page should list posts, html:\n{html}"
         );
         assert!(
-            html.contains("Post A") && html.contains("Post B"),
-            "should list section posts, html:\n{html}"
+            html.contains(r#"<a href="http://localhost:5456/posts/note/hello/">Hello</a>"#),
+            "home page should link to the post under /posts/, html:\n{html}"
         );
     }
 
     #[test]
-    fn build_sections_index_skipped_without_overview_template() {
+    fn build_home_orders_by_date_descending() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates_except(&root.path().join("templates"), &["overview.html"]);
+        copy_templates(&root.path().join("templates"));
 
         write_page(
             root.path(),
-            "posts/note/post-a",
+            "posts/aaa-old",
             indoc! {r#"
                 +++
-                title = "Post A"
+                title = "Old Post"
+                date = "2025-01-01T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/zzz-new",
+            indoc! {r#"
+                +++
+                title = "New Post"
+                date = "2026-06-01T00:00:00Z"
                 +++
                 Body
             "#},
@@ -1510,32 +2693,54 @@ mod tests {
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let sections_index = root
-            .path()
-            .join("public")
-            .join("sections")
-            .join("index.html");
+        let html = fs::read_to_string(root.path().join("public").join("index.html")).unwrap();
+        let new_pos = html.find("New Post").expect("should list New Post");
+        let old_pos = html.find("Old Post").expect("should list Old Post");
         assert!(
-            !sections_index.exists(),
-            "should NOT generate sections index without overview.html"
+            new_pos < old_pos,
+            "newer post should appear before older post on home page, html:\n{html}"
         );
     }
 
-    // ── build: taxonomies ──
+    #[test]
+    fn build_empty_home_page() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let home = root.path().join("public").join("index.html");
+        assert!(
+            home.exists(),
+            "should generate home page even with zero posts"
+        );
+    }
 
     #[test]
-    fn build_generates_taxonomy_index_pages() {
+    fn build_orphan_posts_on_home_not_in_sections() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
 
         write_page(
             root.path(),
-            "posts/hello",
+            "posts/note/sectioned",
             indoc! {r#"
                 +++
-                title = "Hello"
-                tags = ["rust", "web"]
+                title = "Sectioned Post"
+                date = "2026-01-01T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/orphan",
+            indoc! {r#"
+                +++
+                title = "Orphan Post"
+                date = "2026-01-02T00:00:00Z"
                 +++
                 Body
             "#},
@@ -1543,63 +2748,67 @@ mod tests {
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output_dir = root.path().join("public");
-        let tags_index = output_dir.join("tags").join("index.html");
-        assert!(tags_index.exists(), "should generate /tags/index.html");
-        let html = fs::read_to_string(&tags_index).unwrap();
+        let home_html = fs::read_to_string(root.path().join("public").join("index.html")).unwrap();
         assert!(
-            html.contains("rust") && html.contains("web"),
-            "tags index should list terms, html:\n{html}"
+            home_html.contains("Sectioned Post"),
+            "sectioned post should also appear on home page, html:\n{home_html}"
+        );
+        assert!(
+            home_html.contains("Orphan Post"),
+            "orphan post should appear on home page, html:\n{home_html}"
+        );
+
+        let note_html = fs::read_to_string(
+            root.path()
+                .join("public")
+                .join("posts")
+                .join("note")
+                .join("index.html"),
+        )
+        .unwrap();
+        assert!(
+            note_html.contains("Sectioned Post"),
+            "sectioned post should appear in section page, html:\n{note_html}"
+        );
+        assert!(
+            !note_html.contains("Orphan Post"),
+            "orphan post should NOT appear in section page, html:\n{note_html}"
         );
     }
 
     #[test]
-    fn build_generates_tag_archive_pages() {
+    fn build_skips_home_without_template() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates(&root.path().join("templates"));
+        copy_templates_except(&root.path().join("templates"), &["home.html"]);
 
-        for (name, tag) in [("post-1", "rust"), ("post-2", "rust"), ("post-3", "web")] {
-            write_page(
-                root.path(),
-                &format!("posts/{name}"),
-                &format!(
-                    indoc! {r#"
-                        +++
-                        title = "{name}"
-                        tags = ["{tag}"]
-                        +++
-                        Body
-                    "#},
-                    name = name,
-                    tag = tag,
-                ),
-            );
-        }
+        write_page(
+            root.path(),
+            "posts/note/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                +++
+                Body
+            "#},
+        );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output_dir = root.path().join("public");
-        let rust_page = output_dir.join("tags").join("rust").join("index.html");
-        assert!(rust_page.exists(), "should generate /tags/rust/index.html");
-        let html = fs::read_to_string(&rust_page).unwrap();
-        assert!(
-            html.contains("post-1") && html.contains("post-2"),
-            "tag archive should list posts, html:\n{html}"
-        );
+        let home = root.path().join("public").join("index.html");
         assert!(
-            !html.contains("post-3"),
-            "tag archive should not include unrelated posts, html:\n{html}"
+            !home.exists(),
+            "should NOT generate home page without home.html template"
         );
     }
 
     #[test]
-    fn build_generates_paginated_tag_archive_pages() {
+    fn build_home_pagination() {
         let root = tempfile::tempdir().unwrap();
         fs::write(
             root.path().join("config.toml"),
             indoc! {r"
-                [params]
+                [params.home]
                 paginate = 2
             "},
         )
@@ -1609,12 +2818,11 @@ mod tests {
         for i in 1..=3 {
             write_page(
                 root.path(),
-                &format!("posts/post-{i}"),
+                &format!("posts/note/post-{i}"),
                 &format!(
                     indoc! {r#"
                         +++
                         title = "Post {i}"
-                        tags = ["rust"]
                         date = "2026-01-0{i}T00:00:00Z"
                         +++
                         Body
@@ -1627,49 +2835,85 @@ mod tests {
         build(root.path(), BuildOptions::default()).unwrap();
 
         let output_dir = root.path().join("public");
-
-        let page1 = output_dir.join("tags").join("rust").join("index.html");
-        assert!(page1.exists(), "should generate page 1");
+        let page1 = output_dir.join("index.html");
+        assert!(page1.exists(), "should generate home page 1");
         let html1 = fs::read_to_string(&page1).unwrap();
         assert!(
             html1.contains("Page 1 / 2"),
             "should show pagination, html:\n{html1}"
         );
-        assert!(
-            html1.contains("Next"),
-            "page 1 should have next link, html:\n{html1}"
+
+        let page2 = output_dir.join("page").join("2").join("index.html");
+        assert!(page2.exists(), "should generate home page 2");
+    }
+
+    #[test]
+    fn build_standalone_excluded_from_home() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "posts/note/hello",
+            indoc! {r#"
+                +++
+                title = "Hello Post"
+                date = "2026-01-01T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+        write_page(
+            root.path(),
+            "about-me",
+            indoc! {r#"
+                +++
+                title = "About Me"
+                +++
+                Bio
+            "#},
         );
 
-        let page2 = output_dir
-            .join("tags")
-            .join("rust")
-            .join("page")
-            .join("2")
-            .join("index.html");
-        assert!(page2.exists(), "should generate page 2");
-        let html2 = fs::read_to_string(&page2).unwrap();
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let html = fs::read_to_string(root.path().join("public").join("index.html")).unwrap();
         assert!(
-            html2.contains("Page 2 / 2"),
-            "should show page 2, html:\n{html2}"
+            html.contains("Hello Post"),
+            "home page should list posts, html:\n{html}"
         );
         assert!(
-            html2.contains("Prev"),
-            "page 2 should have prev link, html:\n{html2}"
+            !html.contains("About Me"),
+            "home page should NOT list standalone pages, html:\n{html}"
         );
     }
 
+    // ── build: posts index ──
+
     #[test]
-    fn build_no_tag_archive_pages_without_tags() {
+    fn build_generates_posts_index() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
 
         write_page(
             root.path(),
-            "posts/hello",
+            "posts/note/post-a",
             indoc! {r#"
                 +++
-                title = "Hello"
+                title = "Post A"
+                date = "2026-01-01T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/essay/post-b",
+            indoc! {r#"
+                +++
+                title = "Post B"
+                date = "2026-01-02T00:00:00Z"
                 +++
                 Body
             "#},
@@ -1677,37 +2921,39 @@ mod tests {
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output_dir = root.path().join("public");
-        let tags_index = output_dir.join("tags").join("index.html");
+        let posts_index = root.path().join("public").join("posts").join("index.html");
+        assert!(posts_index.exists(), "should generate /posts/index.html");
+        let html = fs::read_to_string(&posts_index).unwrap();
         assert!(
-            tags_index.exists(),
-            "should generate /tags/index.html even with no tags"
+            html.contains("Post A") && html.contains("Post B"),
+            "posts index should list all posts across sections, html:\n{html}"
         );
     }
 
     #[test]
-    fn build_tag_archive_correct_with_standalone_pages() {
+    fn build_posts_index_uses_index_title() {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
         copy_templates(&root.path().join("templates"));
 
-        write_page(
-            root.path(),
-            "about-me",
+        let posts_dir = root.path().join("content").join("posts");
+        fs::create_dir_all(&posts_dir).unwrap();
+        fs::write(
+            posts_dir.join("_index.md"),
             indoc! {r#"
                 +++
-                title = "About Me"
+                title = "文章"
                 +++
-                Bio
             "#},
-        );
+        )
+        .unwrap();
+
         write_page(
             root.path(),
             "posts/note/hello",
             indoc! {r#"
                 +++
-                title = "Hello Post"
-                tags = ["rust"]
+                title = "Hello"
                 date = "2026-01-01T00:00:00Z"
                 +++
                 Body
@@ -1716,218 +2962,1493 @@ mod tests {
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let term_page = root
-            .path()
-            .join("public")
-            .join("tags")
-            .join("rust")
-            .join("index.html");
-        assert!(term_page.exists(), "should generate /tags/rust/index.html");
-        let html = fs::read_to_string(&term_page).unwrap();
+        let html = fs::read_to_string(root.path().join("public").join("posts").join("index.html"))
+            .unwrap();
         assert!(
-            html.contains("Hello Post"),
-            "tag archive should list the tagged post, html:\n{html}"
+            html.contains("文章"),
+            "should use _index.md title for posts index, html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn build_posts_index_generated_even_when_empty() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "about-me",
+            indoc! {r#"
+                +++
+                title = "About Me"
+                +++
+                Bio
+            "#},
         );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let posts_index = root.path().join("public").join("posts").join("index.html");
         assert!(
-            !html.contains("About Me"),
-            "tag archive should NOT list standalone pages, html:\n{html}"
+            posts_index.exists(),
+            "should generate /posts/index.html even with no posts"
         );
     }
 
-    // ── build: pagination config ──
-
     #[test]
-    fn build_zero_paginate_falls_back_to_defaults() {
+    fn build_posts_index_pagination() {
         let root = tempfile::tempdir().unwrap();
         fs::write(
             root.path().join("config.toml"),
             indoc! {r"
-                [params]
-                paginate = 0
-
-                [params.home]
-                paginate = 0
-
                 [params.section]
-                paginate = 0
+                paginate = 2
             "},
         )
         .unwrap();
         copy_templates(&root.path().join("templates"));
 
-        write_page(
-            root.path(),
-            "posts/note/hello",
-            indoc! {r#"
-                +++
-                title = "Hello"
-                tags = ["rust"]
-                date = "2026-01-01T00:00:00Z"
-                +++
-                Body
-            "#},
+        for i in 1..=3 {
+            write_page(
+                root.path(),
+                &format!("posts/note/post-{i}"),
+                &format!(
+                    indoc! {r#"
+                        +++
+                        title = "Post {i}"
+                        date = "2026-01-0{i}T00:00:00Z"
+                        +++
+                        Body
+                    "#},
+                    i = i,
+                ),
+            );
+        }
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let page1 = output_dir.join("posts").join("index.html");
+        assert!(page1.exists(), "should generate /posts/ page 1");
+        let html1 = fs::read_to_string(&page1).unwrap();
+        assert!(
+            html1.contains("Page 1 / 2"),
+            "should show pagination on /posts/, html:\n{html1}"
         );
 
+        let page2 = output_dir
+            .join("posts")
+            .join("page")
+            .join("2")
+            .join("index.html");
+        assert!(page2.exists(), "should generate /posts/ page 2");
+    }
+
+    // ── build: section pages ──
+
+    #[test]
+    fn build_generates_section_pages() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        for (section, name) in [("note", "post-a"), ("note", "post-b"), ("essay", "hello")] {
+            write_page(
+                root.path(),
+                &format!("posts/{section}/{name}"),
+                &format!(
+                    indoc! {r#"
+                        +++
+                        title = "{name}"
+                        date = "2026-01-01T00:00:00Z"
+                        +++
+                        Body
+                    "#},
+                    name = name,
+                ),
+            );
+        }
+
         build(root.path(), BuildOptions::default()).unwrap();
 
         let output_dir = root.path().join("public");
+        let note_index = output_dir.join("posts").join("note").join("index.html");
         assert!(
-            output_dir.join("index.html").exists(),
-            "should build home page"
+            note_index.exists(),
+            "should generate /posts/note/index.html"
         );
+        let html = fs::read_to_string(&note_index).unwrap();
         assert!(
-            output_dir.join("posts").join("index.html").exists(),
-            "should build posts index"
+            html.contains("Note"),
+            "should have section title, html:\n{html}"
         );
         assert!(
-            output_dir
-                .join("posts")
-                .join("note")
-                .join("index.html")
-                .exists(),
-            "should build section page"
+            html.contains("post-a") && html.contains("post-b"),
+            "should list section posts, html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<a href="http://localhost:5456/posts/note/post-a/">post-a</a>"#),
+            "section page should link to posts under /posts/, html:\n{html}"
+        );
+
+        let essay_index = output_dir.join("posts").join("essay").join("index.html");
+        assert!(
+            essay_index.exists(),
+            "should generate /posts/essay/index.html"
+        );
+    }
+
+    #[test]
+    fn build_skips_archives_without_template() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates_except(&root.path().join("templates"), &["archive.html"]);
+
+        write_page(
+            root.path(),
+            "posts/note/my-post",
+            indoc! {r#"
+                +++
+                title = "My Post"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let section_index = root
+            .path()
+            .join("public")
+            .join("posts")
+            .join("note")
+            .join("index.html");
+        assert!(
+            !section_index.exists(),
+            "should NOT generate archive pages without archive.html template"
+        );
+    }
+
+    #[test]
+    fn build_section_uses_index_title() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        let section_dir = root.path().join("content").join("posts").join("note");
+        fs::create_dir_all(&section_dir).unwrap();
+        fs::write(
+            section_dir.join("_index.md"),
+            indoc! {r#"
+                +++
+                title = "笔记"
+                +++
+            "#},
+        )
+        .unwrap();
+
+        write_page(
+            root.path(),
+            "posts/note/my-post",
+            indoc! {r#"
+                +++
+                title = "My Post"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let html = fs::read_to_string(
+            root.path()
+                .join("public")
+                .join("posts")
+                .join("note")
+                .join("index.html"),
+        )
+        .unwrap();
+        assert!(
+            html.contains("笔记"),
+            "should use _index.md title, html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn build_section_pagination() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r"
+                [params.section]
+                paginate = 2
+            "},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        for i in 1..=3 {
+            write_page(
+                root.path(),
+                &format!("posts/note/post-{i}"),
+                &format!(
+                    indoc! {r#"
+                        +++
+                        title = "Post {i}"
+                        date = "2026-01-0{i}T00:00:00Z"
+                        +++
+                        Body
+                    "#},
+                    i = i,
+                ),
+            );
+        }
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let page1 = output_dir.join("posts").join("note").join("index.html");
+        assert!(page1.exists(), "should generate section page 1");
+        let html1 = fs::read_to_string(&page1).unwrap();
+        assert!(
+            html1.contains("Page 1 / 2"),
+            "should show pagination, html:\n{html1}"
+        );
+
+        let page2 = output_dir
+            .join("posts")
+            .join("note")
+            .join("page")
+            .join("2")
+            .join("index.html");
+        assert!(page2.exists(), "should generate section page 2");
+    }
+
+    // ── build: sections index ──
+
+    #[test]
+    fn build_sections_index_generates_page() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "posts/note/post-a",
+            indoc! {r#"
+                +++
+                title = "Post A"
+                date = "2026-01-01T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/essay/post-b",
+            indoc! {r#"
+                +++
+                title = "Post B"
+                date = "2026-01-02T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let sections_index = root
+            .path()
+            .join("public")
+            .join("sections")
+            .join("index.html");
+        assert!(
+            sections_index.exists(),
+            "should generate /sections/index.html"
+        );
+        let html = fs::read_to_string(&sections_index).unwrap();
+        assert!(
+            html.contains("Essay") && html.contains("Note"),
+            "should list section names, html:\n{html}"
+        );
+        assert!(
+            html.contains("Post A") && html.contains("Post B"),
+            "should list section posts, html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn build_sections_index_skipped_without_overview_template() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates_except(&root.path().join("templates"), &["overview.html"]);
+
+        write_page(
+            root.path(),
+            "posts/note/post-a",
+            indoc! {r#"
+                +++
+                title = "Post A"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let sections_index = root
+            .path()
+            .join("public")
+            .join("sections")
+            .join("index.html");
+        assert!(
+            !sections_index.exists(),
+            "should NOT generate sections index without overview.html"
+        );
+    }
+
+    // ── build: taxonomies ──
+
+    #[test]
+    fn build_generates_taxonomy_index_pages() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                tags = ["rust", "web"]
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let tags_index = output_dir.join("tags").join("index.html");
+        assert!(tags_index.exists(), "should generate /tags/index.html");
+        let html = fs::read_to_string(&tags_index).unwrap();
+        assert!(
+            html.contains("rust") && html.contains("web"),
+            "tags index should list terms, html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn build_generates_author_pages_when_enabled() {
+        let root = tempfile::tempdir().unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                authors = ["alice"]
+                +++
+                Body
+            "#},
+        );
+        let output_dir = root.path().join("public");
+
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        build(root.path(), BuildOptions::default()).unwrap();
+        assert!(!output_dir.join("authors").exists());
+
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                author_pages = true
+
+                [authors.alice]
+                name = "Alice Liddell"
+            "#},
+        )
+        .unwrap();
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let html = fs::read_to_string(output_dir.join("authors/alice/index.html")).unwrap();
+        assert!(html.contains("<h1>Alice Liddell</h1>"), "html:\n{html}");
+        assert!(html.contains("Hello"), "html:\n{html}");
+        assert!(output_dir.join("authors/alice/index.xml").exists());
+        let overview = fs::read_to_string(output_dir.join("authors/index.html")).unwrap();
+        assert!(overview.contains("Alice Liddell"), "html:\n{overview}");
+    }
+
+    #[test]
+    fn build_generates_date_archive_pages() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                timezone = "Asia/Shanghai"
+
+                [archive]
+                enabled = true
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        for (name, date) in [
+            ("june", "date = 2024-06-15T12:00:00Z"),
+            // July 1st in the site time zone.
+            ("july", "date = 2024-06-30T20:00:00Z"),
+            ("older", "date = 2023-01-01T12:00:00Z"),
+            ("undated", ""),
+        ] {
+            write_page(
+                root.path(),
+                &format!("posts/{name}"),
+                &format!("+++\ntitle = \"{name}\"\n{date}\n+++\nBody\n"),
+            );
+        }
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let archive = root.path().join("public/archive");
+        let titles = |path: &str| {
+            let html = fs::read_to_string(archive.join(path).join("index.html")).unwrap();
+            ["june", "july", "older", "undated"]
+                .into_iter()
+                .filter(|name| html.contains(&format!(">{name}</a>")))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(""), ["june", "july", "older"]);
+        assert_eq!(titles("2024"), ["june", "july"]);
+        assert_eq!(titles("2024/06"), ["june"]);
+        assert_eq!(titles("2024/07"), ["july"]);
+        assert_eq!(titles("2023/01"), ["older"]);
+        assert!(!archive.join("2023/06").exists());
+    }
+
+    #[test]
+    fn build_generates_tag_archive_pages() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        for (name, tag) in [("post-1", "rust"), ("post-2", "rust"), ("post-3", "web")] {
+            write_page(
+                root.path(),
+                &format!("posts/{name}"),
+                &format!(
+                    indoc! {r#"
+                        +++
+                        title = "{name}"
+                        tags = ["{tag}"]
+                        +++
+                        Body
+                    "#},
+                    name = name,
+                    tag = tag,
+                ),
+            );
+        }
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let rust_page = output_dir.join("tags").join("rust").join("index.html");
+        assert!(rust_page.exists(), "should generate /tags/rust/index.html");
+        let html = fs::read_to_string(&rust_page).unwrap();
+        assert!(
+            html.contains("post-1") && html.contains("post-2"),
+            "tag archive should list posts, html:\n{html}"
+        );
+        assert!(
+            !html.contains("post-3"),
+            "tag archive should not include unrelated posts, html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn build_generates_paginated_tag_archive_pages() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r"
+                [params]
+                paginate = 2
+            "},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        for i in 1..=3 {
+            write_page(
+                root.path(),
+                &format!("posts/post-{i}"),
+                &format!(
+                    indoc! {r#"
+                        +++
+                        title = "Post {i}"
+                        tags = ["rust"]
+                        date = "2026-01-0{i}T00:00:00Z"
+                        +++
+                        Body
+                    "#},
+                    i = i,
+                ),
+            );
+        }
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+
+        let page1 = output_dir.join("tags").join("rust").join("index.html");
+        assert!(page1.exists(), "should generate page 1");
+        let html1 = fs::read_to_string(&page1).unwrap();
+        assert!(
+            html1.contains("Page 1 / 2"),
+            "should show pagination, html:\n{html1}"
+        );
+        assert!(
+            html1.contains("Next"),
+            "page 1 should have next link, html:\n{html1}"
+        );
+
+        let page2 = output_dir
+            .join("tags")
+            .join("rust")
+            .join("page")
+            .join("2")
+            .join("index.html");
+        assert!(page2.exists(), "should generate page 2");
+        let html2 = fs::read_to_string(&page2).unwrap();
+        assert!(
+            html2.contains("Page 2 / 2"),
+            "should show page 2, html:\n{html2}"
+        );
+        assert!(
+            html2.contains("Prev"),
+            "page 2 should have prev link, html:\n{html2}"
+        );
+    }
+
+    #[test]
+    fn build_no_tag_archive_pages_without_tags() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let tags_index = output_dir.join("tags").join("index.html");
+        assert!(
+            tags_index.exists(),
+            "should generate /tags/index.html even with no tags"
+        );
+    }
+
+    #[test]
+    fn build_tag_archive_correct_with_standalone_pages() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "about-me",
+            indoc! {r#"
+                +++
+                title = "About Me"
+                +++
+                Bio
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/note/hello",
+            indoc! {r#"
+                +++
+                title = "Hello Post"
+                tags = ["rust"]
+                date = "2026-01-01T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let term_page = root
+            .path()
+            .join("public")
+            .join("tags")
+            .join("rust")
+            .join("index.html");
+        assert!(term_page.exists(), "should generate /tags/rust/index.html");
+        let html = fs::read_to_string(&term_page).unwrap();
+        assert!(
+            html.contains("Hello Post"),
+            "tag archive should list the tagged post, html:\n{html}"
+        );
+        assert!(
+            !html.contains("About Me"),
+            "tag archive should NOT list standalone pages, html:\n{html}"
+        );
+    }
+
+    // ── build: pagination config ──
+
+    #[test]
+    fn build_zero_paginate_falls_back_to_defaults() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r"
+                [params]
+                paginate = 0
+
+                [params.home]
+                paginate = 0
+
+                [params.section]
+                paginate = 0
+            "},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "posts/note/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                tags = ["rust"]
+                date = "2026-01-01T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        assert!(
+            output_dir.join("index.html").exists(),
+            "should build home page"
+        );
+        assert!(
+            output_dir.join("posts").join("index.html").exists(),
+            "should build posts index"
+        );
+        assert!(
+            output_dir
+                .join("posts")
+                .join("note")
+                .join("index.html")
+                .exists(),
+            "should build section page"
+        );
+        assert!(
+            output_dir
+                .join("tags")
+                .join("rust")
+                .join("index.html")
+                .exists(),
+            "should build tag archive page"
+        );
+    }
+
+    // ── build: RSS feeds ──
+
+    #[test]
+    fn build_generates_rss_feeds() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+                title = "Test Site"
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "posts/note/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                tags = ["rust"]
+                date = "2026-01-15T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let main_feed = fs::read_to_string(output_dir.join("index.xml")).unwrap();
+        assert!(
+            main_feed.contains("<title>Test Site</title>"),
+            "main feed should have site title, xml:\n{main_feed}"
+        );
+        assert!(
+            main_feed.contains("<title>Hello</title>"),
+            "main feed should include post, xml:\n{main_feed}"
+        );
+
+        let posts_feed = fs::read_to_string(output_dir.join("posts").join("index.xml")).unwrap();
+        assert!(
+            posts_feed.contains("<title>Hello</title>"),
+            "all-posts feed should include post, xml:\n{posts_feed}"
+        );
+
+        let section_feed =
+            fs::read_to_string(output_dir.join("posts").join("note").join("index.xml")).unwrap();
+        assert!(
+            section_feed.contains("<title>Hello</title>"),
+            "section feed should include section post, xml:\n{section_feed}"
+        );
+
+        let tag_feed =
+            fs::read_to_string(output_dir.join("tags").join("rust").join("index.xml")).unwrap();
+        assert!(
+            tag_feed.contains("<title>Hello</title>"),
+            "tag feed should include tagged post, xml:\n{tag_feed}"
+        );
+    }
+
+    #[test]
+    fn build_rss_feed_empty_site() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let main_feed = fs::read_to_string(output_dir.join("index.xml")).unwrap();
+        assert!(
+            !main_feed.contains("<item>"),
+            "empty site should have no items, xml:\n{main_feed}"
+        );
+        assert!(
+            !main_feed.contains("<lastBuildDate>"),
+            "empty site should have no lastBuildDate, xml:\n{main_feed}"
+        );
+    }
+
+    // ── build: sitemap + robots.txt ──
+
+    #[test]
+    fn build_generates_sitemap_and_robots() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                date = "2026-01-15T00:00:00Z"
+                +++
+                Body
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+
+        let sitemap = fs::read_to_string(output_dir.join("sitemap.xml")).unwrap();
+        assert!(
+            sitemap.contains("<loc>https://example.com/</loc>"),
+            "sitemap should contain home URL, xml:\n{sitemap}"
+        );
+        assert!(
+            sitemap.contains("<loc>https://example.com/posts/hello/</loc>"),
+            "sitemap should contain post URL, xml:\n{sitemap}"
+        );
+        assert!(
+            sitemap.contains("<lastmod>"),
+            "sitemap should have lastmod for dated page, xml:\n{sitemap}"
+        );
+
+        let robots = fs::read_to_string(output_dir.join("robots.txt")).unwrap();
+        assert!(
+            robots.contains("Sitemap: https://example.com/sitemap.xml"),
+            "robots.txt should reference sitemap, txt:\n{robots}"
+        );
+    }
+
+    #[test]
+    fn build_links_translations_in_templates_and_sitemap() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+            "#},
+        )
+        .unwrap();
+        let templates_dir = root.path().join("templates");
+        copy_templates(&templates_dir);
+        fs::write(
+            templates_dir.join("page.html"),
+            "{{ language }}:{% for t in translations %} {{ t.language }}={{ t.url | safe }}{% endfor %}",
+        )
+        .unwrap();
+        write_page(
+            root.path(),
+            "about",
+            indoc! {r#"
+                +++
+                title = "About"
+                translation_key = "about"
+                +++
+            "#},
+        );
+        write_page(
+            root.path(),
+            "guanyu",
+            indoc! {r#"
+                +++
+                title = "关于"
+                language = "zh"
+                translation_key = "about"
+                +++
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let expected = "en=https://example.com/about/ zh=https://example.com/guanyu/";
+        assert_eq!(
+            fs::read_to_string(output_dir.join("guanyu/index.html")).unwrap(),
+            format!("zh: {expected}")
+        );
+        let sitemap = fs::read_to_string(output_dir.join("sitemap.xml")).unwrap();
+        assert!(
+            sitemap.contains(
+                r#"<xhtml:link rel="alternate" hreflang="zh" href="https://example.com/guanyu/"/>"#
+            ),
+            "xml:\n{sitemap}"
+        );
+    }
+
+    #[test]
+    fn build_cascade_excludes_pages_from_sitemap_and_feeds() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_test_file(
+            root.path(),
+            "content/posts/note/_index.md",
+            indoc! {r"
+                +++
+                [cascade]
+                noindex = true
+                exclude_from_feed = true
+                +++
+            "},
+        );
+        write_page(
+            root.path(),
+            "posts/note/scratch",
+            indoc! {r#"
+                +++
+                title = "Scratch"
+                date = "2026-01-16T00:00:00Z"
+                +++
+            "#},
+        );
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                date = "2026-01-15T00:00:00Z"
+                +++
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let sitemap = fs::read_to_string(output_dir.join("sitemap.xml")).unwrap();
+        assert!(sitemap.contains("/posts/hello/"), "xml:\n{sitemap}");
+        assert!(!sitemap.contains("/posts/note/scratch/"), "xml:\n{sitemap}");
+        for feed in ["index.xml", "posts/index.xml", "posts/note/index.xml"] {
+            let xml = fs::read_to_string(output_dir.join(feed)).unwrap();
+            assert!(!xml.contains("Scratch"), "{feed}:\n{xml}");
+        }
+        assert!(
+            output_dir.join("posts/note/scratch/index.html").exists(),
+            "the page itself is still built"
+        );
+    }
+
+    #[test]
+    fn build_writes_podcast_feed() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+
+                [podcast]
+                category = "Technology"
+                image = "/cover.jpg"
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/episode-1",
+            indoc! {r#"
+                +++
+                title = "Episode 1"
+                date = "2026-01-15T00:00:00Z"
+
+                [audio]
+                file = "episode-1.mp3"
+                duration = "00:42:10"
+                +++
+            "#},
+        );
+        write_test_file(root.path(), "content/posts/episode-1/episode-1.mp3", "ID3");
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let xml = fs::read_to_string(root.path().join("public/index.xml")).unwrap();
+        assert!(
+            xml.contains(r#"<itunes:category text="Technology" />"#),
+            "xml:\n{xml}"
+        );
+        assert!(
+            xml.contains(
+                r#"<enclosure url="https://example.com/posts/episode-1/episode-1.mp3" length="3" type="audio/mpeg" />"#
+            ),
+            "xml:\n{xml}"
+        );
+        assert!(
+            xml.contains("<itunes:duration>00:42:10</itunes:duration>"),
+            "xml:\n{xml}"
+        );
+    }
+
+    #[test]
+    fn build_wraps_content_in_h_entry() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+
+                [microformats]
+                wrap_content = true
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                date = "2026-01-15T00:00:00Z"
+                +++
+                Hi there.
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let html = fs::read_to_string(root.path().join("public/posts/hello/index.html")).unwrap();
+        assert!(html.contains(r#"<div class="h-entry">"#), "html:\n{html}");
+        assert!(
+            html.contains(
+                r#"<data class="u-url" value="https://example.com/posts/hello/"></data>"#
+            ),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains("<div class=\"e-content\">\n<p>Hi there.</p>"),
+            "html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn build_injects_analytics_script() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+
+                [analytics]
+                provider = "plausible"
+                domain = "example.com"
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            "+++\ntitle = \"Hello\"\n+++\nHi there.\n",
+        );
+        let script = r#"<script defer data-domain="example.com" src="https://plausible.io/js/script.js"></script>"#;
+
+        build(root.path(), BuildOptions::default()).unwrap();
+        for page in ["posts/hello/index.html", "index.html", "404.html"] {
+            let html = fs::read_to_string(root.path().join("public").join(page)).unwrap();
+            assert!(
+                html.contains(&format!("{script}\n</head>")),
+                "{page}:\n{html}"
+            );
+        }
+
+        let options = BuildOptions {
+            dev: true,
+            ..BuildOptions::default()
+        };
+        build(root.path(), options).unwrap();
+        let html = fs::read_to_string(root.path().join("public/posts/hello/index.html")).unwrap();
+        assert!(!html.contains("plausible"), "html:\n{html}");
+    }
+
+    #[test]
+    fn build_writes_csp_meta_tags() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+
+                [csp]
+                enabled = true
+                output = "meta"
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            "+++\ntitle = \"Hello\"\n+++\nHi there.\n",
+        );
+        let meta =
+            r#"<meta http-equiv="Content-Security-Policy" content="default-src &#39;self&#39;">"#;
+
+        build(root.path(), BuildOptions::default()).unwrap();
+        let html = fs::read_to_string(root.path().join("public/posts/hello/index.html")).unwrap();
+        assert!(html.contains(&format!("<head>{meta}")), "html:\n{html}");
+
+        let options = BuildOptions {
+            dev: true,
+            ..BuildOptions::default()
+        };
+        build(root.path(), options).unwrap();
+        let html = fs::read_to_string(root.path().join("public/posts/hello/index.html")).unwrap();
+        assert!(!html.contains("Content-Security-Policy"), "html:\n{html}");
+    }
+
+    #[test]
+    fn build_writes_featured_image_thumbnails() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+
+                [thumbnails]
+                width = 40
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                date = "2026-01-15T00:00:00Z"
+
+                [featured_image]
+                src = "cover.png"
+                +++
+            "#},
+        );
+        image::RgbImage::new(80, 60)
+            .save(root.path().join("content/posts/hello/cover.png"))
+            .unwrap();
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let output_dir = root.path().join("public");
+        let thumb = image::open(output_dir.join("posts/hello/cover.thumb.png")).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (40, 30));
+        let feed = fs::read_to_string(output_dir.join("index.xml")).unwrap();
+        assert!(
+            feed.contains(
+                r#"<media:thumbnail url="https://example.com/posts/hello/cover.thumb.png" />"#
+            ),
+            "xml:\n{feed}"
+        );
+    }
+
+    #[test]
+    fn build_writes_responsive_image_variants() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                base_url = "https://example.com"
+
+                [images]
+                widths = [40]
+                formats = ["webp"]
+            "#},
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                date = "2026-01-15T00:00:00Z"
+                +++
+
+                ![A cover](cover.png)
+            "#},
+        );
+        image::RgbImage::new(80, 60)
+            .save(root.path().join("content/posts/hello/cover.png"))
+            .unwrap();
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let page_dir = root.path().join("public/posts/hello");
+        let html = fs::read_to_string(page_dir.join("index.html")).unwrap();
+        assert!(
+            html.contains(
+                r#"<source type="image/webp" srcset="cover.40w.webp 40w, cover.80w.webp 80w" sizes="100vw" />"#
+            ),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"srcset="cover.40w.png 40w, cover.png 80w""#),
+            "html:\n{html}"
+        );
+        for (path, width) in [
+            ("cover.40w.png", 40),
+            ("cover.40w.webp", 40),
+            ("cover.80w.webp", 80),
+        ] {
+            assert_eq!(image::open(page_dir.join(path)).unwrap().width(), width);
+        }
+    }
+
+    #[test]
+    fn build_sizes_bundle_and_static_images() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            r#"base_url = "https://example.com""#,
+        )
+        .unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/hello",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                date = "2026-01-15T00:00:00Z"
+                +++
+
+                ![A cover](cover.png)
+
+                Logo: ![](/images/logo.png) and ![](/images/missing.png)
+            "#},
+        );
+        image::RgbImage::new(80, 60)
+            .save(root.path().join("content/posts/hello/cover.png"))
+            .unwrap();
+        fs::create_dir_all(root.path().join("static/images")).unwrap();
+        image::RgbImage::new(32, 16)
+            .save(root.path().join("static/images/logo.png"))
+            .unwrap();
+
+        build(root.path(), BuildOptions::default()).unwrap();
+
+        let html = fs::read_to_string(root.path().join("public/posts/hello/index.html")).unwrap();
+        assert!(
+            html.contains(r#"<img src="cover.png" alt="A cover" width="80" height="60""#),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<img src="/images/logo.png" alt="" width="32" height="16""#),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<img src="/images/missing.png" alt="" loading="lazy""#),
+            "html:\n{html}"
+        );
+        assert!(!html.contains("srcset"), "no [images] variants:\n{html}");
+    }
+
+    #[test]
+    fn build_strict_fails_on_soft_failures() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        copy_templates(&root.path().join("templates"));
+        write_page(
+            root.path(),
+            "posts/sloppy",
+            indoc! {r#"
+                +++
+                title = "Sloppy"
+                +++
+
+                ![](photo.png)
+
+                ::: callout
+                Never closed
+            "#},
+        );
+        let strict = || {
+            build_all(
+                root.path(),
+                BuildOptions {
+                    strict: true,
+                    ..Default::default()
+                },
+            )
+        };
+
+        build(root.path(), BuildOptions::default()).unwrap();
+        let err = format!("{:#}", strict().unwrap_err());
+        assert!(
+            err.contains("unclosed directive `callout`")
+                && err.contains("image `photo.png` has no alt text"),
+            "got: {err}"
+        );
+
+        write_page(
+            root.path(),
+            "posts/sloppy",
+            "+++\ntitle = \"Sloppy\"\n+++\n\n[Gone](/missing/)\n",
+        );
+        let err = format!("{:#}", strict().unwrap_err());
+        assert!(err.contains("broken link"), "got: {err}");
+
+        write_test_file(root.path(), "templates/post.html", "{{ subtitle }}");
+        fs::write(root.path().join("config.toml"), "strict = true").unwrap();
+        let err = format!(
+            "{:#}",
+            build_all(root.path(), BuildOptions::default()).unwrap_err()
+        );
+        assert!(err.contains("undefined"), "got: {err}");
+    }
+
+    #[test]
+    fn build_strict_templates_report_undefined_variable() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        write_test_file(
+            root.path(),
+            "templates/post.html",
+            "{% if subtitle %}{{ subtitle }}{% endif %}\n{{ titel }}",
+        );
+        fs::write(
+            root.path().join("config.toml"),
+            "[templates]\nstrict = true",
+        )
+        .unwrap();
+
+        let err = format!(
+            "{:#}",
+            build_all(root.path(), BuildOptions::default()).unwrap_err()
         );
         assert!(
-            output_dir
-                .join("tags")
-                .join("rust")
-                .join("index.html")
-                .exists(),
-            "should build tag archive page"
+            err.contains("undefined value (in post.html:2)"),
+            "got: {err}"
         );
     }
 
-    // ── build: RSS feeds ──
-
     #[test]
-    fn build_generates_rss_feeds() {
+    fn build_renders_summaries() {
         let root = tempfile::tempdir().unwrap();
         fs::write(
             root.path().join("config.toml"),
             indoc! {r#"
                 base_url = "https://example.com"
-                title = "Test Site"
+
+                [summary]
+                words = 3
             "#},
         )
         .unwrap();
         copy_templates(&root.path().join("templates"));
-
+        write_test_file(
+            root.path(),
+            "templates/home.html",
+            "{% for page in pages %}{{ page.summary_html | safe }}\n{% endfor %}",
+        );
+        write_test_file(
+            root.path(),
+            "templates/post.html",
+            "{{ summary_html | safe }}",
+        );
         write_page(
             root.path(),
-            "posts/note/hello",
+            "posts/separated",
             indoc! {r#"
                 +++
-                title = "Hello"
-                tags = ["rust"]
-                date = "2026-01-15T00:00:00Z"
+                title = "Separated"
+                date = "2026-01-16T00:00:00Z"
                 +++
-                Body
-            "#},
-        );
 
-        build(root.path(), BuildOptions::default()).unwrap();
+                A **bold** [intro](notes.txt).
 
-        let output_dir = root.path().join("public");
-        let main_feed = fs::read_to_string(output_dir.join("index.xml")).unwrap();
-        assert!(
-            main_feed.contains("<title>Test Site</title>"),
-            "main feed should have site title, xml:\n{main_feed}"
-        );
-        assert!(
-            main_feed.contains("<title>Hello</title>"),
-            "main feed should include post, xml:\n{main_feed}"
-        );
+                <!--more-->
 
-        let posts_feed = fs::read_to_string(output_dir.join("posts").join("index.xml")).unwrap();
-        assert!(
-            posts_feed.contains("<title>Hello</title>"),
-            "all-posts feed should include post, xml:\n{posts_feed}"
+                Rest.
+            "#},
         );
+        write_page(
+            root.path(),
+            "posts/auto",
+            indoc! {r#"
+                +++
+                title = "Auto"
+                date = "2026-01-15T00:00:00Z"
+                +++
 
-        let section_feed =
-            fs::read_to_string(output_dir.join("posts").join("note").join("index.xml")).unwrap();
-        assert!(
-            section_feed.contains("<title>Hello</title>"),
-            "section feed should include section post, xml:\n{section_feed}"
-        );
+                One two.
 
-        let tag_feed =
-            fs::read_to_string(output_dir.join("tags").join("rust").join("index.xml")).unwrap();
-        assert!(
-            tag_feed.contains("<title>Hello</title>"),
-            "tag feed should include tagged post, xml:\n{tag_feed}"
-        );
-    }
+                Three four.
 
-    #[test]
-    fn build_rss_feed_empty_site() {
-        let root = tempfile::tempdir().unwrap();
-        fs::write(root.path().join("config.toml"), "").unwrap();
-        copy_templates(&root.path().join("templates"));
+                Five six.
+            "#},
+        );
 
         build(root.path(), BuildOptions::default()).unwrap();
 
-        let output_dir = root.path().join("public");
-        let main_feed = fs::read_to_string(output_dir.join("index.xml")).unwrap();
-        assert!(
-            !main_feed.contains("<item>"),
-            "empty site should have no items, xml:\n{main_feed}"
-        );
-        assert!(
-            !main_feed.contains("<lastBuildDate>"),
-            "empty site should have no lastBuildDate, xml:\n{main_feed}"
-        );
+        let public = root.path().join("public");
+        let separated = r#"<p>A <strong>bold</strong> <a href="https://example.com/posts/separated/notes.txt">intro</a>.</p>"#;
+        let html = fs::read_to_string(public.join("posts/separated/index.html")).unwrap();
+        assert_eq!(html.trim(), separated);
+        let html = fs::read_to_string(public.join("posts/auto/index.html")).unwrap();
+        assert_eq!(html.trim(), "<p>One two.</p>\n<p>Three four.</p>");
+
+        let home = fs::read_to_string(public.join("index.html")).unwrap();
+        assert!(home.starts_with(separated), "home:\n{home}");
+        assert!(home.contains("<p>Three four.</p>"), "home:\n{home}");
+        assert!(!home.contains("Five"), "home:\n{home}");
     }
 
-    // ── build: sitemap + robots.txt ──
-
     #[test]
-    fn build_generates_sitemap_and_robots() {
+    fn build_generates_og_images_without_featured_image() {
         let root = tempfile::tempdir().unwrap();
         fs::write(
             root.path().join("config.toml"),
-            indoc! {r#"
+            indoc! {r##"
                 base_url = "https://example.com"
-            "#},
+
+                [og_image]
+                enabled = true
+                accent = "#f97316"
+            "##},
         )
         .unwrap();
         copy_templates(&root.path().join("templates"));
-
         write_page(
             root.path(),
-            "posts/hello",
+            "posts/plain",
             indoc! {r#"
                 +++
-                title = "Hello"
+                title = "A post without a cover"
                 date = "2026-01-15T00:00:00Z"
                 +++
-                Body
+
+                Body.
             "#},
         );
+        write_page(
+            root.path(),
+            "posts/covered",
+            indoc! {r#"
+                +++
+                title = "Covered"
+                date = "2026-01-16T00:00:00Z"
 
-        build(root.path(), BuildOptions::default()).unwrap();
+                [featured_image]
+                src = "/images/cover.png"
+                +++
+            "#},
+        );
 
-        let output_dir = root.path().join("public");
+        build(root.path(), BuildOptions::default()).unwrap();
 
-        let sitemap = fs::read_to_string(output_dir.join("sitemap.xml")).unwrap();
-        assert!(
-            sitemap.contains("<loc>https://example.com/</loc>"),
-            "sitemap should contain home URL, xml:\n{sitemap}"
-        );
-        assert!(
-            sitemap.contains("<loc>https://example.com/posts/hello/</loc>"),
-            "sitemap should contain post URL, xml:\n{sitemap}"
-        );
+        let public = root.path().join("public/posts");
+        let html = fs::read_to_string(public.join("plain/index.html")).unwrap();
         assert!(
-            sitemap.contains("<lastmod>"),
-            "sitemap should have lastmod for dated page, xml:\n{sitemap}"
+            html.contains(
+                r#"<meta property="og:image" content="https://example.com/posts/plain/og.png">"#
+            ),
+            "html:\n{html}"
         );
+        let card = image::open(public.join("plain/og.png")).unwrap().to_rgb8();
+        assert_eq!(card.dimensions(), (1200, 630));
+        assert_eq!(card.get_pixel(4, 4).0, [0xf9, 0x73, 0x16], "accent edge");
 
-        let robots = fs::read_to_string(output_dir.join("robots.txt")).unwrap();
+        let html = fs::read_to_string(public.join("covered/index.html")).unwrap();
         assert!(
-            robots.contains("Sitemap: https://example.com/sitemap.xml"),
-            "robots.txt should reference sitemap, txt:\n{robots}"
+            html.contains(
+                r#"<meta property="og:image" content="https://example.com/images/cover.png">"#
+            ),
+            "html:\n{html}"
         );
+        assert!(!public.join("covered/og.png").exists());
     }
 
     // ── build: 404 page ──
@@ -1990,7 +4511,7 @@ mod tests {
 
         let err = format!(
             "{:#}",
-            build(root.path(), BuildOptions::default()).unwrap_err()
+            build_all(root.path(), BuildOptions::default()).unwrap_err()
         );
         assert!(
             err.contains("failed to load config"),
@@ -2003,7 +4524,7 @@ mod tests {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), r#"timezone = "Mars/Base""#).unwrap();
 
-        let err = build(root.path(), BuildOptions::default()).unwrap_err();
+        let err = build_all(root.path(), BuildOptions::default()).unwrap_err();
         let chain: Vec<String> = err.chain().map(ToString::to_string).collect();
         assert!(
             chain
@@ -2018,7 +4539,7 @@ mod tests {
         let root = tempfile::tempdir().unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
 
-        let err = build(root.path(), BuildOptions::default())
+        let err = build_all(root.path(), BuildOptions::default())
             .unwrap_err()
             .to_string();
         assert!(
@@ -2038,7 +4559,7 @@ mod tests {
         )
         .unwrap();
 
-        let err = build(root.path(), BuildOptions::default())
+        let err = build_all(root.path(), BuildOptions::default())
             .unwrap_err()
             .to_string();
         assert!(
@@ -2053,15 +4574,14 @@ mod tests {
         setup_site_with_page(root.path());
 
         build(root.path(), BuildOptions::default()).unwrap();
-        let output_dir = root.path().join("public");
-        let _guard = PermissionGuard::restrict(&output_dir, 0o555);
+        let _guard = PermissionGuard::restrict(root.path(), 0o555);
 
-        let err = build(root.path(), BuildOptions::default())
+        let err = build_all(root.path(), BuildOptions::default())
             .unwrap_err()
             .to_string();
         assert!(
-            err.contains("failed to write") || err.contains("failed to clean"),
-            "should report write or clean failure, got: {err}"
+            err.contains("failed to create output directory"),
+            "should report output directory failure, got: {err}"
         );
     }
 
@@ -2073,17 +4593,14 @@ mod tests {
         let page_dir = root.path().join("content").join("posts").join("hello");
         fs::write(page_dir.join("image.png"), "img-data").unwrap();
 
-        build(root.path(), BuildOptions::default()).unwrap();
-
-        let page_output = root.path().join("public").join("posts").join("hello");
-        let _guard = PermissionGuard::restrict(&page_output, 0o555);
+        let _guard = PermissionGuard::restrict(&page_dir.join("image.png"), 0o000);
 
-        let err = build(root.path(), BuildOptions::default())
+        let err = build_all(root.path(), BuildOptions::default())
             .unwrap_err()
             .to_string();
         assert!(
-            err.contains("failed to copy asset") || err.contains("failed to clean"),
-            "should report asset copy or clean failure, got: {err}"
+            err.contains("failed to copy asset"),
+            "should report asset copy failure, got: {err}"
         );
     }
 
@@ -2113,7 +4630,7 @@ mod tests {
             "#},
         );
 
-        let err = build(root.path(), BuildOptions::default())
+        let err = build_all(root.path(), BuildOptions::default())
             .unwrap_err()
             .to_string();
         assert!(
@@ -2122,6 +4639,300 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_reports_every_failing_page() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        build(root.path(), BuildOptions::default()).unwrap();
+        let published = root.path().join("public/posts/hello/index.html");
+        let original = fs::read_to_string(&published).unwrap();
+        for name in ["a", "b"] {
+            write_test_file(
+                root.path(),
+                &format!("content/posts/{name}.md"),
+                "+++\ntitle = \"Broken\"\n+++\n::: snippet { \"nope\" }\n:::\n",
+            );
+        }
+
+        let err = build_all(root.path(), BuildOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("failed to build 2 pages:"), "got: {err}");
+        assert!(err.contains("a.md") && err.contains("b.md"), "got: {err}");
+        assert_eq!(
+            fs::read_to_string(&published).unwrap(),
+            original,
+            "a failed build keeps the previous output"
+        );
+        assert!(!root.path().join("public/posts/a").exists());
+    }
+
+    #[test]
+    fn build_syncs_into_previous_output() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        write_page(
+            root.path(),
+            "posts/gone",
+            "+++\ntitle = \"Gone\"\n+++\nBody\n",
+        );
+        write_test_file(root.path(), "static/logo.png", "logo");
+        build_all(root.path(), BuildOptions::default()).unwrap();
+        let logo = root.path().join("public/logo.png");
+        let copied = fs::metadata(&logo).unwrap().modified().unwrap();
+        build_all(root.path(), BuildOptions::default()).unwrap();
+        assert_eq!(
+            fs::metadata(&logo).unwrap().modified().unwrap(),
+            copied,
+            "unchanged output is left in place"
+        );
+
+        fs::remove_dir_all(root.path().join("content/posts/gone")).unwrap();
+        build_all(root.path(), BuildOptions::default()).unwrap();
+
+        let public = root.path().join("public");
+        assert!(public.join("posts/hello/index.html").exists());
+        assert_eq!(fs::read_to_string(public.join("logo.png")).unwrap(), "logo");
+        assert!(
+            !public.join("posts/gone").exists(),
+            "output of removed pages is swept"
+        );
+        assert!(
+            !root.path().join("public.staging").exists(),
+            "no copy of the previous output is kept"
+        );
+    }
+
+    #[test]
+    fn build_report_collects_load_and_render_failures() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        write_page(root.path(), "posts/unparsable", "+++\ntitle = \n+++\n");
+        write_page(
+            root.path(),
+            "posts/unrenderable",
+            "+++\ntitle = \"Broken\"\n+++\n::: snippet { \"nope\" }\n:::\n",
+        );
+
+        let report = build(root.path(), BuildOptions::default()).unwrap();
+        assert_eq!(report.pages, 1);
+        let failed: Vec<_> = report
+            .failures
+            .iter()
+            .map(|(source, _)| source.strip_prefix(root.path()).unwrap())
+            .collect();
+        assert_eq!(
+            failed,
+            [
+                Path::new("content/posts/unparsable/index.md"),
+                Path::new("content/posts/unrenderable/index.md"),
+            ]
+        );
+        assert!(!root.path().join("public").exists());
+
+        let err = report.into_result().unwrap_err().to_string();
+        assert!(err.starts_with("failed to build 2 pages:"), "got: {err}");
+    }
+
+    #[test]
+    fn build_report_counts_output() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        write_test_file(root.path(), "static/favicon.ico", "icon");
+        write_test_file(root.path(), "content/posts/hello/cover.webp", "cover");
+
+        let report = build(root.path(), BuildOptions::default()).unwrap();
+        assert_eq!(report.pages, 1);
+        assert_eq!(report.assets, 2);
+        assert_eq!(
+            report.bytes_written,
+            dir_size(&root.path().join("public")).unwrap()
+        );
+        let phases: Vec<_> = report.phases.iter().map(|phase| phase.name).collect();
+        assert_eq!(phases, ["load", "copy", "render", "listings", "finish"]);
+        let steps: Vec<_> = report.render_steps.iter().map(|step| step.name).collect();
+        assert_eq!(steps, ["markdown", "templates", "output"]);
+        let render = &report.phases[2];
+        assert!(
+            report
+                .render_steps
+                .iter()
+                .map(|step| step.duration)
+                .sum::<Duration>()
+                <= render.duration,
+            "render steps fall within the render phase"
+        );
+        let warnings: Vec<_> = report.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert!(
+            warnings.contains(&"no theme configured; set `theme` in config.toml to use a theme"),
+            "{warnings:?}"
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["phases"][0]["name"], "load");
+        assert!(json["phases"][0]["seconds"].is_f64(), "{json}");
+        assert_eq!(json["failures"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn build_reports_directive_problems() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        write_page(
+            root.path(),
+            "posts/broken",
+            indoc! {r#"
+                +++
+                title = "Broken"
+                +++
+                Intro.
+
+                ::: callout {type=tip
+                Body
+            "#},
+        );
+
+        let report = build(root.path(), BuildOptions::default()).unwrap();
+        let located: Vec<_> = report
+            .warnings
+            .iter()
+            .filter(|w| w.file.is_some())
+            .map(|w| (w.file.as_deref(), w.line, w.message.as_str()))
+            .collect();
+        let file = root.path().join("content/posts/broken/index.md");
+        assert_eq!(
+            located,
+            [
+                (
+                    file.to_str(),
+                    Some(6),
+                    "directive attributes are missing their closing `}`; they are ignored"
+                ),
+                (file.to_str(), Some(6), "unclosed directive `callout`"),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_runs_hooks_around_build() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        fs::write(
+            root.path().join("config.toml"),
+            indoc! {r#"
+                [hooks]
+                pre_build = ["mkdir -p static && echo 'a {}' > static/gen.css"]
+                post_build = ["ls public/posts/hello > built.txt"]
+            "#},
+        )
+        .unwrap();
+
+        let report = build(root.path(), BuildOptions::default()).unwrap();
+        assert!(report.failures.is_empty());
+        assert!(root.path().join("public/gen.css").is_file());
+        let built = fs::read_to_string(root.path().join("built.txt")).unwrap();
+        assert_eq!(built.trim(), "index.html");
+    }
+
+    #[test]
+    fn build_fails_on_failing_hook() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        fs::write(
+            root.path().join("config.toml"),
+            "[hooks]\npre_build = [\"exit 1\"]\n",
+        )
+        .unwrap();
+
+        let err = build(root.path(), BuildOptions::default()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("pre_build hook `exit 1` exited with"),
+            "got: {err}"
+        );
+        assert!(!root.path().join("public").exists());
+    }
+
+    #[test]
+    fn build_reuses_highlight_cache() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        fs::write(
+            root.path().join("config.toml"),
+            "[cache]\nhighlight = true\n",
+        )
+        .unwrap();
+        write_page(
+            root.path(),
+            "posts/code",
+            indoc! {r#"
+                +++
+                title = "Code"
+                +++
+                ```rust
+                fn main() {}
+                ```
+            "#},
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+        let output = root.path().join("public/posts/code/index.html");
+        let first = fs::read_to_string(&output).unwrap();
+        let cache = fs::read_to_string(root.path().join(".kiln-cache/highlight.json")).unwrap();
+        assert!(
+            cache.contains("storage type function rust"),
+            "cache:\n{cache}"
+        );
+
+        build(root.path(), BuildOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), first);
+    }
+
+    #[test]
+    fn build_streams_events() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site_with_page(root.path());
+        let events = RefCell::new(Vec::new());
+        let record = |event: &BuildEvent<'_>| {
+            events
+                .borrow_mut()
+                .push(serde_json::to_value(event).unwrap());
+        };
+
+        let report = build(
+            root.path(),
+            BuildOptions {
+                on_event: Some(&record),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let events = events.into_inner();
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|e| e["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "timing", "timing", "page", "timing", "timing", "timing", "finished"
+            ]
+        );
+        assert_eq!(events[0]["name"], "load");
+        assert_eq!(events[2]["file"], "content/posts/hello/index.md");
+        assert!(
+            events[2]["url"]
+                .as_str()
+                .unwrap()
+                .ends_with("/posts/hello/")
+        );
+        let finished = &events[6];
+        assert_eq!(finished["pages"], 1);
+        assert_eq!(finished["failures"], 0);
+        assert_eq!(finished["warnings"], report.warnings.len());
+    }
+
     // ── find_page_css ──
 
     #[test]
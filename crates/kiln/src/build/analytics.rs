@@ -0,0 +1,56 @@
+use crate::config::Analytics;
+use crate::html::escape;
+
+/// Hosted counting script of the `goatcounter` provider.
+const GOATCOUNTER_SCRIPT: &str = "https://gc.zgo.at/count.js";
+
+/// Renders the `<script>` tag of the `[analytics]` service, as each one
+/// documents it, for the `<head>` of every page.
+#[must_use]
+pub(crate) fn analytics_script(analytics: &Analytics) -> String {
+    match analytics {
+        Analytics::Plausible { domain, script } => format!(
+            "<script defer data-domain=\"{}\" src=\"{}\"></script>\n",
+            escape(domain),
+            escape(script),
+        ),
+        Analytics::Umami { website_id, script } => format!(
+            "<script defer data-website-id=\"{}\" src=\"{}\"></script>\n",
+            escape(website_id),
+            escape(script),
+        ),
+        Analytics::GoatCounter { code } => format!(
+            "<script async data-goatcounter=\"https://{}.goatcounter.com/count\" src=\"{GOATCOUNTER_SCRIPT}\"></script>\n",
+            escape(code),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── analytics_script ──
+
+    #[test]
+    fn analytics_script_per_provider() {
+        assert_eq!(
+            analytics_script(&Analytics::Plausible {
+                domain: "example.com".into(),
+                script: "https://plausible.io/js/script.js".into(),
+            }),
+            "<script defer data-domain=\"example.com\" src=\"https://plausible.io/js/script.js\"></script>\n"
+        );
+        assert_eq!(
+            analytics_script(&Analytics::Umami {
+                website_id: "94db1cb1".into(),
+                script: "https://stats.example.com/script.js".into(),
+            }),
+            "<script defer data-website-id=\"94db1cb1\" src=\"https://stats.example.com/script.js\"></script>\n"
+        );
+        assert_eq!(
+            analytics_script(&Analytics::GoatCounter { code: "me".into() }),
+            "<script async data-goatcounter=\"https://me.goatcounter.com/count\" src=\"https://gc.zgo.at/count.js\"></script>\n"
+        );
+    }
+}
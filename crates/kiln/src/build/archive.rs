@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::{Context, Result};
+use jiff::tz::TimeZone;
 
 use crate::section::{Section, load_index_title};
 use crate::taxonomy::TaxonomySet;
@@ -10,7 +12,8 @@ use super::BuildContext;
 use super::listing::{ListedPage, ListingArtifacts, group_by_year, resolve_term_pages};
 use super::paginate::{paginate_config, write_paginated};
 
-/// Generates all archive pages: `/posts/`, `/posts/<section>/`, and `/tags/<slug>/`.
+/// Generates all archive pages: `/posts/`, `/posts/<section>/`,
+/// `/<taxonomy>/<slug>/`, and the chronological `/archive/` pages.
 ///
 /// Skipped when `archive.html` is not present in the template set.
 pub(crate) fn build_archive_pages(
@@ -83,6 +86,91 @@ pub(crate) fn build_archive_pages(
         }
     }
 
+    if ctx.config.archive.enabled {
+        let archive_title = load_index_title(&content_dir.join(DATE_ARCHIVE_DIR))
+            .unwrap_or_else(|| ctx.i18n.t("archive").into_owned());
+        build_date_archives(
+            ctx,
+            &artifacts.listed_posts,
+            &archive_title,
+            tag_per_page,
+            output_dir,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Output directory of the chronological archive (see `[archive]`).
+const DATE_ARCHIVE_DIR: &str = "archive";
+
+/// Generates `/archive/` with every dated post, then `/archive/<year>/`
+/// and `/archive/<year>/<month>/` for each year and month with posts.
+///
+/// Dates are bucketed in the site time zone. `posts` must be sorted by date
+/// descending.
+fn build_date_archives(
+    ctx: &BuildContext,
+    posts: &[ListedPage],
+    title: &str,
+    per_page: usize,
+    output_dir: &Path,
+) -> Result<()> {
+    let mut dated = Vec::new();
+    let mut years: BTreeMap<i16, Vec<ListedPage>> = BTreeMap::new();
+    let mut months: BTreeMap<(i16, i8), Vec<ListedPage>> = BTreeMap::new();
+    for post in posts {
+        let Some(timestamp) = post.timestamp else {
+            continue;
+        };
+        let date = timestamp.to_zoned(ctx.time_zone.clone().unwrap_or(TimeZone::UTC));
+        dated.push(post.clone());
+        years.entry(date.year()).or_default().push(post.clone());
+        months
+            .entry((date.year(), date.month()))
+            .or_default()
+            .push(post.clone());
+    }
+
+    let base_path = format!("/{DATE_ARCHIVE_DIR}");
+    write_archive(
+        ctx,
+        &ArchiveSpec::new(DATE_ARCHIVE_DIR, "archive", title, "", &base_path),
+        &dated,
+        per_page,
+        output_dir,
+    )?;
+    for (year, pages) in years {
+        let name = year.to_string();
+        write_archive(
+            ctx,
+            &ArchiveSpec::new(
+                DATE_ARCHIVE_DIR,
+                "year",
+                &name,
+                &name,
+                &format!("{base_path}/{year}"),
+            ),
+            &pages,
+            per_page,
+            output_dir,
+        )?;
+    }
+    for ((year, month), pages) in months {
+        write_archive(
+            ctx,
+            &ArchiveSpec::new(
+                DATE_ARCHIVE_DIR,
+                "month",
+                &format!("{year}-{month:02}"),
+                &format!("{year}/{month:02}"),
+                &format!("{base_path}/{year}/{month:02}"),
+            ),
+            &pages,
+            per_page,
+            output_dir,
+        )?;
+    }
     Ok(())
 }
 
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use crate::config::{Author, Config};
+use crate::content::page::Page;
+use crate::taxonomy::{TaxonomyKind, TaxonomySet, sort_terms, term_slug};
+use crate::template::vars::PageAuthor;
+
+/// Returns the taxonomy kinds the site is built with: tags, plus authors
+/// with `author_pages`.
+pub(super) fn taxonomy_kinds(config: &Config) -> &'static [TaxonomyKind] {
+    if config.author_pages {
+        &[TaxonomyKind::Tags, TaxonomyKind::Authors]
+    } else {
+        &[TaxonomyKind::Tags]
+    }
+}
+
+/// Resolves the authors `page` credits: each of its `authors` from
+/// `[authors]`, else the site `[author]` when named.
+///
+/// An ID missing from `[authors]` logs a warning and is credited under
+/// the ID itself.
+pub(super) fn page_authors(page: &Page, config: &Config) -> Vec<PageAuthor> {
+    let ids: Vec<&str> = page
+        .frontmatter
+        .authors
+        .iter()
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty())
+        .collect();
+    if ids.is_empty() {
+        return if config.author.name.is_empty() {
+            Vec::new()
+        } else {
+            vec![page_author("", &config.author, None)]
+        };
+    }
+
+    ids.into_iter()
+        .map(|id| {
+            let url = config.author_pages.then(|| {
+                format!(
+                    "{}/authors/{}/",
+                    config.base_url,
+                    term_slug(id, config.slugify.mode())
+                )
+            });
+            if let Some(author) = config.authors.get(id) {
+                page_author(id, author, url)
+            } else {
+                tracing::warn!(
+                    author = id,
+                    "unknown author; add it to [authors] in config.toml"
+                );
+                PageAuthor {
+                    id: id.to_owned(),
+                    name: id.to_owned(),
+                    url,
+                    ..PageAuthor::default()
+                }
+            }
+        })
+        .collect()
+}
+
+fn page_author(id: &str, author: &Author, url: Option<String>) -> PageAuthor {
+    PageAuthor {
+        id: id.to_owned(),
+        name: if author.name.is_empty() {
+            id.to_owned()
+        } else {
+            author.name.clone()
+        },
+        email: author.email.clone(),
+        link: author.link.clone(),
+        avatar: author.avatar.clone(),
+        url,
+    }
+}
+
+/// Names author terms after their `[authors]` entry, in place of the ID
+/// written in frontmatter. Terms renamed by an `_index.md` keep its title.
+pub(super) fn name_author_terms(
+    taxonomy_set: &mut TaxonomySet,
+    authors: &BTreeMap<String, Author>,
+) {
+    let taxonomies = taxonomy_set
+        .taxonomies
+        .iter_mut()
+        .filter(|taxonomy| taxonomy.kind == TaxonomyKind::Authors);
+    for taxonomy in taxonomies {
+        for term in &mut taxonomy.terms {
+            if let Some(author) = authors.get(&term.name)
+                && !author.name.is_empty()
+            {
+                term.name.clone_from(&author.name);
+            }
+        }
+        sort_terms(&mut taxonomy.terms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::config::SlugifyMode;
+    use crate::taxonomy::build_taxonomies;
+    use crate::test_utils::{test_config, test_page};
+
+    fn config_with_authors() -> Config {
+        let mut config: Config = toml::from_str(indoc! {r#"
+            base_url = "https://example.com"
+            author_pages = true
+
+            [author]
+            name = "Site Owner"
+
+            [authors.alice]
+            name = "Alice Liddell"
+            link = "https://alice.example.com"
+            avatar = "/images/alice.png"
+        "#})
+        .unwrap();
+        config.authors.insert("bob".into(), Author::default());
+        config
+    }
+
+    // ── page_authors ──
+
+    #[test]
+    fn page_authors_resolves_ids() {
+        let config = config_with_authors();
+        let mut page = test_page("Post");
+        page.frontmatter.authors = vec!["alice".into(), "bob".into(), "carol".into()];
+
+        let authors = page_authors(&page, &config);
+        assert_eq!(
+            authors[0],
+            PageAuthor {
+                id: "alice".into(),
+                name: "Alice Liddell".into(),
+                email: String::new(),
+                link: "https://alice.example.com".into(),
+                avatar: "/images/alice.png".into(),
+                url: Some("https://example.com/authors/alice/".into()),
+            }
+        );
+        assert_eq!(authors[1].name, "bob", "unnamed entry falls back to its ID");
+        assert_eq!(authors[2].name, "carol", "unknown ID is credited as is");
+        assert_eq!(
+            authors[2].url.as_deref(),
+            Some("https://example.com/authors/carol/")
+        );
+    }
+
+    #[test]
+    fn page_authors_defaults_to_site_author() {
+        let config = config_with_authors();
+        let authors = page_authors(&test_page("Post"), &config);
+        assert_eq!(authors.len(), 1);
+        assert_eq!(authors[0].name, "Site Owner");
+        assert_eq!(authors[0].url, None);
+
+        assert!(page_authors(&test_page("Post"), &test_config()).is_empty());
+    }
+
+    #[test]
+    fn page_authors_without_author_pages_has_no_url() {
+        let mut config = config_with_authors();
+        config.author_pages = false;
+        let mut page = test_page("Post");
+        page.frontmatter.authors = vec!["alice".into()];
+
+        assert_eq!(page_authors(&page, &config)[0].url, None);
+    }
+
+    // ── name_author_terms ──
+
+    #[test]
+    fn name_author_terms_uses_config_names() {
+        let config = config_with_authors();
+        let mut page = test_page("Post");
+        page.frontmatter.authors = vec!["alice".into(), "bob".into()];
+        page.frontmatter.tags = vec!["alice".into()];
+        let mut set = build_taxonomies(&[page], taxonomy_kinds(&config), SlugifyMode::Safe, None);
+
+        name_author_terms(&mut set, &config.authors);
+
+        let names = |kind| {
+            let taxonomy = set.taxonomies.iter().find(|t| t.kind == kind).unwrap();
+            taxonomy
+                .terms
+                .iter()
+                .map(|term| term.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(TaxonomyKind::Authors), ["Alice Liddell", "bob"]);
+        assert_eq!(names(TaxonomyKind::Tags), ["alice"], "tags are left alone");
+    }
+}
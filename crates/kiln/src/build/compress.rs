@@ -0,0 +1,178 @@
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use walkdir::WalkDir;
+
+use crate::config::Compress;
+use crate::output::{OutputKind, append_suffix, write_output};
+
+/// Brotli quality, from 0 to 11: the densest, as each file is compressed
+/// once per build rather than per request.
+const BROTLI_QUALITY: u32 = 11;
+
+/// Brotli window size, as a power of two (the format's default, 4 MiB).
+const BROTLI_WINDOW: u32 = 22;
+
+/// Compresses a whole file into one of the formats of [`Compress`].
+type Encoder = fn(&[u8]) -> io::Result<Vec<u8>>;
+
+/// Writes the siblings enabled in `config` (`.gz`, `.br`) next to every
+/// compressible ([`OutputKind::Text`]) file under `output_dir`, and returns
+/// how many were written.
+///
+/// A sibling is skipped when it would be no smaller than the file itself,
+/// or when one already exists, such as a precompressed file shipped in
+/// `static/`.
+pub(crate) fn precompress_output(config: &Compress, output_dir: &Path) -> Result<usize> {
+    let mut encoders: Vec<(&str, Encoder)> = Vec::new();
+    if config.gzip {
+        encoders.push((".gz", gzip));
+    }
+    if config.brotli {
+        encoders.push((".br", brotli));
+    }
+    if encoders.is_empty() {
+        return Ok(0);
+    }
+
+    // Collected up front, so the walk never sees the siblings it writes.
+    let mut files = Vec::new();
+    for entry in WalkDir::new(output_dir).follow_links(false) {
+        let entry = entry.with_context(|| format!("failed to walk {}", output_dir.display()))?;
+        if entry.file_type().is_file() && OutputKind::of(entry.path()) == OutputKind::Text {
+            files.push(entry.into_path());
+        }
+    }
+
+    let mut written = 0;
+    for path in files {
+        let data = fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        for (suffix, encode) in &encoders {
+            let dest: PathBuf = append_suffix(&path, suffix);
+            if dest.exists() {
+                continue;
+            }
+            let compressed =
+                encode(&data).with_context(|| format!("failed to compress {}", path.display()))?;
+            if compressed.len() < data.len() {
+                write_output(&dest, compressed)?;
+                written += 1;
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// Compresses `data` with gzip at the highest level. The header carries no
+/// timestamp, so the output is the same on every build.
+fn gzip(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Compresses `data` with Brotli at [`BROTLI_QUALITY`].
+fn brotli(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut writer = brotli::CompressorWriter::new(Vec::new(), 4096, BROTLI_QUALITY, BROTLI_WINDOW);
+    writer.write_all(data)?;
+    Ok(writer.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read as _;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn compress(gzip: bool, brotli: bool) -> Compress {
+        Compress { gzip, brotli }
+    }
+
+    /// Text that compresses well.
+    fn page() -> String {
+        "<p>Hello, world!</p>\n".repeat(50)
+    }
+
+    // ── precompress_output ──
+
+    #[test]
+    fn precompress_output_writes_decodable_siblings() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "posts/hello/index.html", &page());
+
+        let written = precompress_output(&compress(true, true), dir.path()).unwrap();
+
+        assert_eq!(written, 2);
+        let html = dir.path().join("posts/hello/index.html");
+        let mut gunzipped = String::new();
+        GzDecoder::new(fs::File::open(append_suffix(&html, ".gz")).unwrap())
+            .read_to_string(&mut gunzipped)
+            .unwrap();
+        assert_eq!(gunzipped, page());
+        let mut unbrotlied = String::new();
+        brotli::Decompressor::new(fs::File::open(append_suffix(&html, ".br")).unwrap(), 4096)
+            .read_to_string(&mut unbrotlied)
+            .unwrap();
+        assert_eq!(unbrotlied, page());
+    }
+
+    #[test]
+    fn precompress_output_only_enabled_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "index.html", &page());
+
+        precompress_output(&compress(false, true), dir.path()).unwrap();
+
+        assert!(!dir.path().join("index.html.gz").exists());
+        assert!(dir.path().join("index.html.br").exists());
+    }
+
+    #[test]
+    fn precompress_output_disabled_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "index.html", &page());
+
+        assert_eq!(
+            precompress_output(&compress(false, false), dir.path()).unwrap(),
+            0
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn precompress_output_skips_binary_and_incompressible_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "logo.png", &page());
+        write_test_file(dir.path(), "tiny.css", "a{}");
+
+        assert_eq!(
+            precompress_output(&compress(true, true), dir.path()).unwrap(),
+            0
+        );
+        assert!(!dir.path().join("logo.png.gz").exists());
+        assert!(!dir.path().join("tiny.css.gz").exists());
+    }
+
+    #[test]
+    fn precompress_output_keeps_existing_siblings() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "app.js", &page());
+        write_test_file(dir.path(), "app.js.gz", "shipped");
+
+        assert_eq!(
+            precompress_output(&compress(true, false), dir.path()).unwrap(),
+            0
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("app.js.gz")).unwrap(),
+            "shipped"
+        );
+    }
+}
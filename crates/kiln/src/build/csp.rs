@@ -0,0 +1,357 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use super::headers::HEADERS_FILE;
+use crate::config::{Csp, CspOutput};
+use crate::html::escape;
+use crate::output::{append_output, write_output};
+
+/// nginx config file with the `[csp]` header, relative to the output
+/// directory.
+pub(super) const CSP_NGINX_FILE: &str = "csp.nginx.conf";
+
+/// Inline `<script>` elements, capturing their attributes and code.
+static SCRIPT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<script\b([^>]*)>(.*?)</script\s*>").expect("script regex should compile")
+});
+
+/// `<style>` elements, capturing their rules.
+static STYLE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<style\b[^>]*>(.*?)</style\s*>").expect("style regex should compile")
+});
+
+/// A `src` attribute, marking an external script.
+static SRC_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(?:^|\s)src\s*=").expect("src regex should compile"));
+
+/// A `type` attribute, capturing its value.
+static TYPE_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)(?:^|\s)type\s*=\s*["']?([^"'\s>]*)"#).expect("type regex should compile")
+});
+
+/// The `<head>` start tag, which the `<meta>` policy follows.
+static HEAD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<head\b[^>]*>").expect("head regex should compile"));
+
+/// Writes the `[csp]` policy for the HTML pages under `output_dir`, with
+/// the hashes of their inline scripts and styles, to `config.output`.
+///
+/// Runs over the final output, after minification, so the hashes match
+/// the bytes served.
+pub(crate) fn build_csp(config: &Csp, output_dir: &Path) -> Result<()> {
+    let pages = html_files(output_dir)?;
+    match config.output {
+        CspOutput::Headers => {
+            let policy = site_policy(&config.policy, &pages)?;
+            append_output(
+                &output_dir.join(HEADERS_FILE),
+                &format!("/*\n  Content-Security-Policy: {policy}\n"),
+            )
+        }
+        CspOutput::Nginx => {
+            let policy = site_policy(&config.policy, &pages)?;
+            write_output(
+                &output_dir.join(CSP_NGINX_FILE),
+                format!("add_header Content-Security-Policy \"{policy}\" always;\n"),
+            )
+        }
+        CspOutput::Meta => {
+            for path in pages {
+                let html = read_page(&path)?;
+                let policy = csp_policy(&config.policy, &InlineHashes::of(&html));
+                if let Some(html) = insert_meta(&html, &policy) {
+                    write_output(&path, html)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// ── Helpers ──
+
+/// `'sha256-…'` sources of a document's inline code.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct InlineHashes {
+    scripts: BTreeSet<String>,
+    styles: BTreeSet<String>,
+}
+
+impl InlineHashes {
+    /// Hashes the inline scripts and `<style>` elements of `html`. External
+    /// scripts and data blocks (`application/ld+json`, ...) are left out,
+    /// as browsers never run them as inline code.
+    fn of(html: &str) -> Self {
+        let scripts = SCRIPT_RE
+            .captures_iter(html)
+            .filter(|caps| is_inline_code(&caps[1]))
+            .map(|caps| hash_source(&caps[2]))
+            .collect();
+        let styles = STYLE_RE
+            .captures_iter(html)
+            .map(|caps| hash_source(&caps[1]))
+            .collect();
+        Self { scripts, styles }
+    }
+}
+
+/// Returns the policy covering the inline code of every page in `pages`.
+fn site_policy(policy: &str, pages: &[PathBuf]) -> Result<String> {
+    let mut hashes = InlineHashes::default();
+    for path in pages {
+        let page = InlineHashes::of(&read_page(path)?);
+        hashes.scripts.extend(page.scripts);
+        hashes.styles.extend(page.styles);
+    }
+    Ok(csp_policy(policy, &hashes))
+}
+
+/// Lists the HTML files under `output_dir`, in name order.
+fn html_files(output_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(output_dir)
+        .follow_links(false)
+        .sort_by_file_name()
+    {
+        let entry = entry.with_context(|| format!("failed to walk {}", output_dir.display()))?;
+        let is_html = entry
+            .path()
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("html"));
+        if entry.file_type().is_file() && is_html {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+fn read_page(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// Returns `true` for a script element with the given attributes whose
+/// code runs: not external, and typed as JavaScript if typed at all.
+fn is_inline_code(attrs: &str) -> bool {
+    if SRC_ATTR_RE.is_match(attrs) {
+        return false;
+    }
+    TYPE_ATTR_RE.captures(attrs).is_none_or(|caps| {
+        let kind = caps[1].to_ascii_lowercase();
+        kind.is_empty() || kind == "module" || kind.contains("javascript")
+    })
+}
+
+/// Returns the CSP source allowing inline `code`.
+fn hash_source(code: &str) -> String {
+    format!("'sha256-{}'", BASE64.encode(Sha256::digest(code)))
+}
+
+/// Adds `hashes` to `policy`: script hashes to `script-src`, style hashes
+/// to `style-src`.
+fn csp_policy(policy: &str, hashes: &InlineHashes) -> String {
+    let mut directives: Vec<String> = policy
+        .split(';')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(str::to_owned)
+        .collect();
+    add_sources(&mut directives, "script-src", &hashes.scripts);
+    add_sources(&mut directives, "style-src", &hashes.styles);
+    directives.join("; ")
+}
+
+/// Appends `sources` to the directive `name`. Without one, it starts from
+/// the `default-src` sources, which it would otherwise fall back to;
+/// without either, inline code is already allowed and nothing is added.
+fn add_sources(directives: &mut Vec<String>, name: &str, sources: &BTreeSet<String>) {
+    if sources.is_empty() {
+        return;
+    }
+    let sources = sources.iter().map(String::as_str).collect::<Vec<_>>();
+    let named = |directive: &str, name: &str| {
+        directive
+            .split_whitespace()
+            .next()
+            .is_some_and(|n| n.eq_ignore_ascii_case(name))
+    };
+    if let Some(directive) = directives.iter_mut().find(|d| named(d, name)) {
+        directive.push(' ');
+        directive.push_str(&sources.join(" "));
+    } else if let Some(default) = directives.iter().find(|d| named(d, "default-src")) {
+        // `'none'` cannot be combined with other sources.
+        let fallback = default
+            .split_whitespace()
+            .skip(1)
+            .filter(|source| !source.eq_ignore_ascii_case("'none'"));
+        let directive: Vec<&str> = [name].into_iter().chain(fallback).chain(sources).collect();
+        directives.push(directive.join(" "));
+    }
+}
+
+/// Inserts `policy` as a `<meta http-equiv>` tag right after the `<head>`
+/// start tag of `html`, ahead of any code it covers. Returns `None` for
+/// documents without a `<head>` tag.
+fn insert_meta(html: &str, policy: &str) -> Option<String> {
+    let head = HEAD_RE.find(html)?;
+    Some(format!(
+        "{}<meta http-equiv=\"Content-Security-Policy\" content=\"{}\">{}",
+        &html[..head.end()],
+        escape(policy),
+        &html[head.end()..],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    const ALERT_HASH: &str = "'sha256-bhHHL3z2vDgxUt0W3dWQOrprscmda2Y5pLsLg4GF+pI='";
+    const STYLE_HASH: &str = "'sha256-FcQqt3aNlV7AZnGV4zkQRVeCeJOxbMPnQSx258L803E='";
+
+    const PAGE: &str = indoc! {r#"
+        <html><head><style>body{color:red}</style></head>
+        <body><script>alert(1)</script><script src="/app.js"></script></body></html>
+    "#};
+
+    // ── build_csp ──
+
+    #[test]
+    fn build_csp_appends_site_wide_header_rule() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "index.html", PAGE);
+        write_test_file(
+            root.path(),
+            "posts/hello/index.html",
+            "<html><head></head><body><script>alert(1)</script></body></html>",
+        );
+        write_test_file(root.path(), "_headers", "/\n  Cache-Control: no-cache\n");
+        let config = Csp {
+            enabled: true,
+            ..Csp::default()
+        };
+
+        build_csp(&config, root.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.path().join("_headers")).unwrap(),
+            format!(
+                "/\n  Cache-Control: no-cache\n/*\n  Content-Security-Policy: default-src 'self'; script-src 'self' {ALERT_HASH}; style-src 'self' {STYLE_HASH}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn build_csp_writes_nginx_snippet() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "index.html", PAGE);
+        let config = Csp {
+            enabled: true,
+            policy: "script-src 'self'".into(),
+            output: CspOutput::Nginx,
+        };
+
+        build_csp(&config, root.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.path().join(CSP_NGINX_FILE)).unwrap(),
+            format!(
+                "add_header Content-Security-Policy \"script-src 'self' {ALERT_HASH}\" always;\n"
+            )
+        );
+    }
+
+    #[test]
+    fn build_csp_inserts_meta_per_page() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "index.html", PAGE);
+        write_test_file(
+            root.path(),
+            "plain/index.html",
+            "<html><head></head></html>",
+        );
+        let config = Csp {
+            enabled: true,
+            output: CspOutput::Meta,
+            ..Csp::default()
+        };
+
+        build_csp(&config, root.path()).unwrap();
+        let html = fs::read_to_string(root.path().join("index.html")).unwrap();
+        assert!(
+            html.starts_with(&format!(
+                "<html><head><meta http-equiv=\"Content-Security-Policy\" content=\"default-src &#39;self&#39;; script-src &#39;self&#39; {}",
+                escape(ALERT_HASH)
+            )),
+            "html:\n{html}"
+        );
+        assert_eq!(
+            fs::read_to_string(root.path().join("plain/index.html")).unwrap(),
+            "<html><head><meta http-equiv=\"Content-Security-Policy\" content=\"default-src &#39;self&#39;\"></head></html>"
+        );
+    }
+
+    // ── InlineHashes::of ──
+
+    #[test]
+    fn inline_hashes_skip_external_scripts_and_data_blocks() {
+        let html = indoc! {r#"
+            <script type="module">alert(1)</script>
+            <script data-src="x">alert(1)</script>
+            <script defer src="/app.js"></script>
+            <script type="application/ld+json">{"@type":"BlogPosting"}</script>
+            <STYLE media="print">body{color:red}</STYLE>
+        "#};
+        assert_eq!(
+            InlineHashes::of(html),
+            InlineHashes {
+                scripts: BTreeSet::from([ALERT_HASH.to_owned()]),
+                styles: BTreeSet::from([STYLE_HASH.to_owned()]),
+            }
+        );
+    }
+
+    // ── csp_policy ──
+
+    #[test]
+    fn csp_policy_extends_existing_directives() {
+        let hashes = InlineHashes {
+            scripts: BTreeSet::from([ALERT_HASH.to_owned()]),
+            styles: BTreeSet::from([STYLE_HASH.to_owned()]),
+        };
+        assert_eq!(
+            csp_policy(
+                "default-src 'none'; script-src 'self' https://cdn.example.com;",
+                &hashes
+            ),
+            format!(
+                "default-src 'none'; script-src 'self' https://cdn.example.com {ALERT_HASH}; style-src {STYLE_HASH}"
+            )
+        );
+        assert_eq!(csp_policy("img-src *", &hashes), "img-src *");
+        assert_eq!(
+            csp_policy("default-src 'self'", &InlineHashes::default()),
+            "default-src 'self'"
+        );
+    }
+
+    // ── insert_meta ──
+
+    #[test]
+    fn insert_meta_needs_head() {
+        assert_eq!(
+            insert_meta("<HEAD lang=en><title>x</title>", "img-src *").unwrap(),
+            "<HEAD lang=en><meta http-equiv=\"Content-Security-Policy\" content=\"img-src *\"><title>x</title>"
+        );
+        assert_eq!(insert_meta("<p>fragment</p>", "img-src *"), None);
+    }
+}
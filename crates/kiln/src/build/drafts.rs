@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::content::page::{Page, PageErrors};
+use crate::output::url_path;
+use crate::render::link::ContentLinks;
+use crate::text::hex;
+
+use super::summary::render_summary;
+use super::url::page_url;
+use super::{BuildContext, PageLinks, build_page};
+
+/// Output directory holding shared draft previews.
+pub(crate) const DRAFTS_DIR: &str = "drafts";
+
+/// Hex digits of the digest kept in a preview token (64 bits).
+const TOKEN_LEN: usize = 16;
+
+/// Renders each draft at its preview URL, `/drafts/<slug>-<token>/`, and
+/// prints the URLs so they can be handed to reviewers.
+///
+/// Drafts are never passed to listings, feeds, taxonomies, or the sitemap,
+/// and are left out of the content link map so published pages cannot leak
+/// a preview URL by linking to a draft. Previews are always `noindex`, and
+/// the search index skips them. Drafts that fail to build are added to
+/// `errors`.
+pub(crate) fn build_shared_drafts(
+    ctx: &BuildContext,
+    drafts: &mut [Page],
+    content_dir: &Path,
+    output_dir: &Path,
+    section_titles: &HashMap<&str, &str>,
+    content_links: &Arc<ContentLinks>,
+    errors: &mut PageErrors,
+) -> Result<()> {
+    let secret = ctx
+        .config
+        .drafts
+        .secret
+        .as_deref()
+        .context("[drafts] share requires a secret")?;
+    for draft in drafts {
+        draft.frontmatter.noindex = Some(true);
+        let draft = &*draft;
+        let built = draft_output_path(draft, content_dir, secret).and_then(|output_path| {
+            let url = page_url(&ctx.config.base_url, &output_path);
+            let summary_html =
+                render_summary(ctx, draft, &url, content_links).with_context(|| {
+                    format!(
+                        "failed to render summary of {}",
+                        draft.source_path.display()
+                    )
+                })?;
+            build_page(
+                ctx,
+                draft,
+                &output_path,
+                output_dir,
+                section_titles,
+                content_links,
+                &summary_html,
+                PageLinks::default(),
+            )?;
+            Ok(output_path)
+        });
+        let Some(output_path) = errors.collect(&draft.source_path, built) else {
+            continue;
+        };
+        eprintln!(
+            "Shared draft: {} ({})",
+            page_url(&ctx.config.base_url, &output_path),
+            draft.source_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Computes the output path of a draft's preview:
+/// `drafts/<slug>-<token>/index.html`.
+///
+/// The token is a keyed hash of the draft's path under `content_dir`, so it
+/// stays the same across builds while the draft does not move.
+fn draft_output_path(draft: &Page, content_dir: &Path, secret: &str) -> Result<PathBuf> {
+    let relative = draft
+        .source_path
+        .strip_prefix(content_dir)
+        .with_context(|| {
+            format!(
+                "{} is not under {}",
+                draft.source_path.display(),
+                content_dir.display()
+            )
+        })?;
+    let token = share_token(secret, relative);
+    Ok(Path::new(DRAFTS_DIR)
+        .join(format!("{}-{token}", draft.slug))
+        .join("index.html"))
+}
+
+/// Derives the preview token for the draft at `relative` (its path under the
+/// content directory): an HMAC-SHA256 of the path keyed with the site's
+/// draft secret.
+fn share_token(secret: &str, relative: &Path) -> String {
+    let mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length")
+        .chain_update(url_path(relative))
+        .finalize();
+    hex(&mac.into_bytes()[..TOKEN_LEN / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_page;
+
+    // ── draft_output_path ──
+
+    #[test]
+    fn draft_output_path_appends_token_to_slug() {
+        let mut draft = test_page("wip");
+        draft.source_path = PathBuf::from("/site/content/posts/wip/index.md");
+        let path = draft_output_path(&draft, Path::new("/site/content"), "secret").unwrap();
+
+        let dir = path.parent().unwrap();
+        assert_eq!(path.file_name().unwrap(), "index.html");
+        assert_eq!(dir.parent().unwrap(), Path::new("drafts"));
+        let name = dir.file_name().unwrap().to_str().unwrap();
+        let token = name.strip_prefix("wip-").unwrap();
+        assert_eq!(token.len(), TOKEN_LEN);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()), "{token}");
+    }
+
+    // ── share_token ──
+
+    #[test]
+    fn share_token_depends_on_secret_and_path() {
+        let path = Path::new("posts/wip/index.md");
+        assert_eq!(share_token("a", path), share_token("a", path));
+        assert_ne!(share_token("a", path), share_token("b", path));
+        assert_ne!(
+            share_token("a", path),
+            share_token("a", Path::new("posts/other/index.md"))
+        );
+    }
+}
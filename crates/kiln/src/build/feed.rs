@@ -6,6 +6,7 @@ use crate::feed::{self, Channel, DEFAULT_FEED_LIMIT};
 use crate::output::write_output;
 use crate::section::{self, Section};
 use crate::taxonomy::{TaxonomyKind, TaxonomySet, Term};
+use crate::template::vars::PageSummary;
 
 use super::BuildContext;
 use super::listing::{ListedPage, ListingArtifacts, resolve_term_pages};
@@ -20,7 +21,7 @@ pub(crate) fn build_feeds(
     output_dir: &Path,
 ) -> Result<()> {
     let base = ctx.config.base_url.trim_end_matches('/');
-    let last_build_date = newest_date(&artifacts.listed_posts);
+    let (items, last_build_date) = feed_items(&artifacts.listed_posts);
 
     let main_channel = Channel {
         title: ctx.config.title.clone(),
@@ -29,12 +30,8 @@ pub(crate) fn build_feeds(
         description: ctx.config.description.clone(),
         language: ctx.config.language.clone(),
         last_build_date,
+        podcast: ctx.config.podcast.clone(),
     };
-    let items: Vec<_> = artifacts
-        .listed_posts
-        .iter()
-        .map(|lp| lp.summary.clone())
-        .collect();
     let xml = feed::generate_rss(&main_channel, &items, DEFAULT_FEED_LIMIT);
     write_output(&output_dir.join("index.xml"), &xml).context("failed to write main RSS feed")?;
 
@@ -87,15 +84,16 @@ fn write_section_feed(
     listed_posts: &[ListedPage],
     output_dir: &Path,
 ) -> Result<()> {
+    let (items, last_build_date) = feed_items(listed_posts);
     let channel = Channel {
         title: format!("{title} - {}", ctx.config.title),
         link: format!("{base}/{dir_slug}/"),
         feed_url: format!("{base}/{dir_slug}/index.xml"),
         description: ctx.config.description.clone(),
         language: ctx.config.language.clone(),
-        last_build_date: newest_date(listed_posts),
+        last_build_date,
+        podcast: ctx.config.podcast.clone(),
     };
-    let items: Vec<_> = listed_posts.iter().map(|lp| lp.summary.clone()).collect();
     let xml = feed::generate_rss(&channel, &items, DEFAULT_FEED_LIMIT);
     let dest = output_dir.join(dir_slug).join("index.xml");
     write_output(&dest, &xml).with_context(|| format!("failed to write RSS feed for {dir_slug}"))
@@ -112,15 +110,16 @@ fn write_term_feed(
 ) -> Result<()> {
     let pages = resolve_term_pages(taxonomy_set, kind, &term.slug, listed_pages);
     let dir_slug = format!("{}/{}", kind.plural(), term.slug);
+    let (items, last_build_date) = feed_items(&pages);
     let channel = Channel {
         title: format!("{} - {}", term.name, ctx.config.title),
         link: format!("{base}/{dir_slug}/"),
         feed_url: format!("{base}/{dir_slug}/index.xml"),
         description: ctx.config.description.clone(),
         language: ctx.config.language.clone(),
-        last_build_date: newest_date(&pages),
+        last_build_date,
+        podcast: ctx.config.podcast.clone(),
     };
-    let items: Vec<_> = pages.iter().map(|lp| lp.summary.clone()).collect();
     let xml = feed::generate_rss(&channel, &items, DEFAULT_FEED_LIMIT);
     let dest = output_dir
         .join(kind.plural())
@@ -129,11 +128,15 @@ fn write_term_feed(
     write_output(&dest, &xml).with_context(|| format!("failed to write RSS feed for {dir_slug}"))
 }
 
-/// Returns the RFC 2822 date of the newest page, for `lastBuildDate`.
-fn newest_date(pages: &[ListedPage]) -> Option<String> {
-    pages
-        .iter()
+/// Returns the feed items among `pages`, skipping those with
+/// `exclude_from_feed`, and the RFC 2822 date of the newest one, for
+/// `lastBuildDate`.
+fn feed_items(pages: &[ListedPage]) -> (Vec<PageSummary>, Option<String>) {
+    let included = || pages.iter().filter(|lp| !lp.exclude_from_feed);
+    let items = included().map(|lp| lp.summary.clone()).collect();
+    let newest = included()
         .filter_map(|lp| lp.timestamp)
         .max()
-        .map(feed::format_rfc2822)
+        .map(feed::format_rfc2822);
+    (items, newest)
 }
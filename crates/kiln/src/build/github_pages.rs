@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::config::GithubPages;
+use crate::output::write_output;
+use crate::render::external::url_host;
+
+/// Marker file that turns off Jekyll processing, relative to the output
+/// directory.
+pub(super) const NOJEKYLL_FILE: &str = ".nojekyll";
+
+/// Custom domain file, relative to the output directory.
+pub(super) const CNAME_FILE: &str = "CNAME";
+
+/// Writes `.nojekyll` and, when the site has a custom domain, `CNAME` to
+/// the output directory. A `CNAME` copied from `static/` is kept as is.
+pub(crate) fn build_github_pages_files(
+    config: &GithubPages,
+    base_url: &str,
+    output_dir: &Path,
+) -> Result<()> {
+    write_output(&output_dir.join(NOJEKYLL_FILE), "")?;
+    let cname = output_dir.join(CNAME_FILE);
+    if !cname.exists()
+        && let Some(domain) = custom_domain(config, base_url)
+    {
+        write_output(&cname, format!("{domain}\n"))?;
+    }
+    Ok(())
+}
+
+/// Returns the configured `cname`, or else the host of `base_url` unless
+/// GitHub Pages serves it without one (`*.github.io`) or it is local.
+fn custom_domain<'a>(config: &'a GithubPages, base_url: &'a str) -> Option<&'a str> {
+    if let Some(cname) = &config.cname {
+        return Some(cname.trim()).filter(|cname| !cname.is_empty());
+    }
+    let host = url_host(base_url)?;
+    // Ports are not part of a domain name; bracketed IPv6 hosts are local
+    // or bare addresses either way.
+    let domain = host.rsplit_once(':').map_or(host, |(domain, _)| domain);
+    let lower = domain.to_ascii_lowercase();
+    let is_local = lower == "localhost"
+        || lower.starts_with('[')
+        || lower.parse::<std::net::Ipv4Addr>().is_ok();
+    (!is_local && !lower.ends_with(".github.io")).then_some(domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn github_pages(cname: Option<&str>) -> GithubPages {
+        GithubPages {
+            enabled: true,
+            cname: cname.map(str::to_owned),
+        }
+    }
+
+    // ── build_github_pages_files ──
+
+    #[test]
+    fn build_github_pages_files_writes_nojekyll_and_cname() {
+        let dir = tempfile::tempdir().unwrap();
+
+        build_github_pages_files(&github_pages(None), "https://blog.example.com/", dir.path())
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join(NOJEKYLL_FILE)).unwrap(),
+            ""
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join(CNAME_FILE)).unwrap(),
+            "blog.example.com\n"
+        );
+    }
+
+    #[test]
+    fn build_github_pages_files_without_custom_domain() {
+        let dir = tempfile::tempdir().unwrap();
+
+        build_github_pages_files(
+            &github_pages(None),
+            "https://me.github.io/blog/",
+            dir.path(),
+        )
+        .unwrap();
+
+        assert!(dir.path().join(NOJEKYLL_FILE).exists());
+        assert!(!dir.path().join(CNAME_FILE).exists());
+    }
+
+    #[test]
+    fn build_github_pages_files_keeps_static_cname() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), CNAME_FILE, "shipped.example.com\n");
+
+        build_github_pages_files(&github_pages(None), "https://blog.example.com/", dir.path())
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join(CNAME_FILE)).unwrap(),
+            "shipped.example.com\n"
+        );
+    }
+
+    // ── custom_domain ──
+
+    #[test]
+    fn custom_domain_prefers_configured_cname() {
+        let config = github_pages(Some(" www.example.com "));
+        assert_eq!(
+            custom_domain(&config, "https://me.github.io/"),
+            Some("www.example.com")
+        );
+        assert_eq!(
+            custom_domain(&github_pages(Some("")), "https://example.com/"),
+            None
+        );
+    }
+
+    #[test]
+    fn custom_domain_from_base_url() {
+        let config = github_pages(None);
+        assert_eq!(
+            custom_domain(&config, "https://example.com:8443/blog/"),
+            Some("example.com")
+        );
+        assert_eq!(custom_domain(&config, "https://Me.GitHub.io/"), None);
+        assert_eq!(custom_domain(&config, "http://localhost:5456"), None);
+        assert_eq!(custom_domain(&config, "http://127.0.0.1:8080/"), None);
+        assert_eq!(custom_domain(&config, "/"), None);
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, ensure};
+
+use crate::config::Headers;
+use crate::output::{append_output, output_path, url_path};
+
+use super::csp::CSP_NGINX_FILE;
+use super::github_pages::{CNAME_FILE, NOJEKYLL_FILE};
+
+/// Header rules file, relative to the output directory.
+pub(super) const HEADERS_FILE: &str = "_headers";
+
+/// Host config files at the output root, read by the host rather than
+/// served.
+const HOST_FILES: [&str; 5] = [
+    HEADERS_FILE,
+    "_redirects",
+    CNAME_FILE,
+    NOJEKYLL_FILE,
+    CSP_NGINX_FILE,
+];
+
+/// Appends a `Cache-Control` rule for every path of the output directory to
+/// `_headers`: the `assets` policy for the `immutable` directories, and the
+/// `html` policy for everything else. The `fingerprinted` files (relative
+/// to the output directory) get the `assets` policy too.
+///
+/// Hosts merge the values of every rule matching a path, so rules never
+/// overlap: rather than one catch-all `/*`, each top-level entry gets its own
+/// rule, and directories holding an `immutable` one are split further.
+pub(crate) fn build_headers(
+    config: &Headers,
+    output_dir: &Path,
+    fingerprinted: &[PathBuf],
+) -> Result<()> {
+    let mut immutable = config
+        .immutable
+        .iter()
+        .map(|dir| immutable_dir(dir))
+        .collect::<Result<Vec<_>>>()?;
+    immutable.extend_from_slice(fingerprinted);
+
+    let mut rules = vec![("/".to_owned(), config.html.as_str())];
+    collect_rules(output_dir, Path::new(""), &immutable, config, &mut rules)?;
+
+    let mut contents = String::new();
+    for (path, cache_control) in rules {
+        _ = writeln!(contents, "{path}\n  Cache-Control: {cache_control}");
+    }
+    append_output(&output_dir.join(HEADERS_FILE), &contents)
+}
+
+/// Validates an `immutable` entry and turns it into a path relative to the
+/// output directory.
+fn immutable_dir(dir: &str) -> Result<PathBuf> {
+    ensure!(
+        !dir.trim_matches('/').is_empty(),
+        "[headers] immutable entry `{dir}` would cover the whole site"
+    );
+    output_path(dir).with_context(|| {
+        format!("[headers] immutable entry `{dir}` has an empty, `.`, or `..` segment")
+    })
+}
+
+/// Adds a rule for each entry of `output_dir.join(relative)`, in name order,
+/// descending only into directories with an `immutable` directory below.
+fn collect_rules<'a>(
+    output_dir: &Path,
+    relative: &Path,
+    immutable: &[PathBuf],
+    config: &'a Headers,
+    rules: &mut Vec<(String, &'a str)>,
+) -> Result<()> {
+    let dir = output_dir.join(relative);
+    let mut entries = fs::read_dir(&dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("failed to read {}", dir.display()))?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+
+    for entry in entries {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if relative.as_os_str().is_empty() && HOST_FILES.contains(&name.as_ref()) {
+            continue;
+        }
+        let path = relative.join(name.as_ref());
+        let url = format!("/{}", url_path(&path));
+        let is_dir = entry
+            .file_type()
+            .with_context(|| format!("failed to read {}", entry.path().display()))?
+            .is_dir();
+
+        if immutable.contains(&path) {
+            let pattern = if is_dir { format!("{url}/*") } else { url };
+            rules.push((pattern, config.assets.as_str()));
+        } else if !is_dir {
+            rules.push((url, config.html.as_str()));
+        } else if immutable.iter().any(|dir| dir.starts_with(&path)) {
+            collect_rules(output_dir, &path, immutable, config, rules)?;
+        } else {
+            rules.push((format!("{url}/*"), config.html.as_str()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn headers(immutable: &[&str]) -> Headers {
+        Headers {
+            enabled: true,
+            html: "no-cache".into(),
+            assets: "immutable".into(),
+            immutable: immutable.iter().map(|&dir| dir.to_owned()).collect(),
+        }
+    }
+
+    // ── build_headers ──
+
+    #[test]
+    fn build_headers_splits_immutable_dirs_from_html() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path();
+        write_test_file(output_dir, "index.html", "");
+        write_test_file(output_dir, "_redirects", "");
+        write_test_file(output_dir, "posts/hello/index.html", "");
+        write_test_file(output_dir, "fonts/inter.3f2a.woff2", "");
+        write_test_file(output_dir, "static/js/app.9c1d.js", "");
+        write_test_file(output_dir, "static/favicon.ico", "");
+
+        build_headers(&headers(&["fonts", "/static/js/"]), output_dir, &[]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output_dir.join(HEADERS_FILE)).unwrap(),
+            indoc! {"
+                /
+                  Cache-Control: no-cache
+                /fonts/*
+                  Cache-Control: immutable
+                /index.html
+                  Cache-Control: no-cache
+                /posts/*
+                  Cache-Control: no-cache
+                /static/favicon.ico
+                  Cache-Control: no-cache
+                /static/js/*
+                  Cache-Control: immutable
+            "}
+        );
+    }
+
+    #[test]
+    fn build_headers_appends_to_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path();
+        write_test_file(output_dir, HEADERS_FILE, "/*\n  X-Frame-Options: DENY\n");
+
+        build_headers(&headers(&[]), output_dir, &[]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output_dir.join(HEADERS_FILE)).unwrap(),
+            indoc! {"
+                /*
+                  X-Frame-Options: DENY
+                /
+                  Cache-Control: no-cache
+            "}
+        );
+    }
+
+    #[test]
+    fn build_headers_caches_fingerprinted_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path();
+        write_test_file(output_dir, "css/style.css", "");
+        write_test_file(output_dir, "css/style.0badf00d.css", "");
+
+        let fingerprinted = [PathBuf::from("css/style.0badf00d.css")];
+        build_headers(&headers(&[]), output_dir, &fingerprinted).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output_dir.join(HEADERS_FILE)).unwrap(),
+            indoc! {"
+                /
+                  Cache-Control: no-cache
+                /css/style.0badf00d.css
+                  Cache-Control: immutable
+                /css/style.css
+                  Cache-Control: no-cache
+            "}
+        );
+    }
+
+    // ── immutable_dir ──
+
+    #[test]
+    fn immutable_dir_rejects_root_and_traversal() {
+        assert_eq!(
+            immutable_dir("/assets/fonts/").unwrap(),
+            Path::new("assets/fonts")
+        );
+        for dir in ["/", "", "assets/../secret", "a//b"] {
+            assert!(immutable_dir(dir).is_err(), "{dir:?} should be rejected");
+        }
+    }
+}
@@ -5,6 +5,7 @@ use anyhow::{Context, Result};
 use crate::template::vars::HomePageVars;
 
 use super::BuildContext;
+use super::json_ld::website_json_ld;
 use super::listing::{ListedPage, collect_page_summaries, sort_pinned_first};
 use super::paginate::{paginate_config, write_paginated};
 
@@ -44,6 +45,7 @@ pub(crate) fn build_home_pages(
                 url: home_url.clone(),
                 pages: collect_page_summaries(pages),
                 pagination,
+                json_ld: website_json_ld(&ctx.config),
                 config: &ctx.config,
             };
             ctx.template_engine
@@ -0,0 +1,115 @@
+use std::ops::Range;
+use std::path::Path;
+use std::process::Command;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use indoc::formatdoc;
+
+/// Runs the `[hooks]` commands of `stage` (`pre_build`, `post_build`) in
+/// order, each through the shell with `root` as the working directory.
+///
+/// Each command's output is captured and echoed to stderr, so it never
+/// mixes into `--message-format json` records on stdout.
+///
+/// Returns when the commands ran, or `None` if there are none.
+///
+/// # Errors
+///
+/// Returns an error, with the command's output, at the first command that
+/// cannot be started or exits with a non-zero status.
+pub(crate) fn run_hooks(
+    root: &Path,
+    stage: &str,
+    commands: &[String],
+) -> Result<Option<Range<SystemTime>>> {
+    if commands.is_empty() {
+        return Ok(None);
+    }
+    let start = SystemTime::now();
+    for command in commands {
+        eprintln!("Running {stage} hook: {command}");
+        let output = shell(command)
+            .current_dir(root)
+            .output()
+            .with_context(|| format!("failed to run {stage} hook `{command}`"))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            bail!(formatdoc! {"
+                {stage} hook `{command}` exited with {status}
+
+                stdout:
+                {stdout}
+                stderr:
+                {stderr}",
+                status = output.status,
+            });
+        }
+        eprint!("{stdout}{stderr}");
+    }
+    Ok(Some(start..SystemTime::now()))
+}
+
+/// Builds a command running `command` through the platform shell.
+fn shell(command: &str) -> Command {
+    let (program, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut shell = Command::new(program);
+    shell.args([flag, command]);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── run_hooks ──
+
+    #[test]
+    fn run_hooks_runs_commands_in_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let commands = [
+            "echo one > out.txt".to_owned(),
+            "echo two >> out.txt".to_owned(),
+        ];
+        let ran = run_hooks(dir.path(), "pre_build", &commands)
+            .unwrap()
+            .unwrap();
+        let modified = std::fs::metadata(dir.path().join("out.txt"))
+            .and_then(|meta| meta.modified())
+            .unwrap();
+        // File times come from a coarser clock than `SystemTime::now`.
+        let slack = std::time::Duration::from_millis(50);
+        assert!(ran.start - slack <= modified && modified <= ran.end + slack);
+        let out = std::fs::read_to_string(dir.path().join("out.txt")).unwrap();
+        assert_eq!(out.lines().collect::<Vec<_>>(), ["one", "two"]);
+    }
+
+    #[test]
+    fn run_hooks_without_commands_runs_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(run_hooks(dir.path(), "pre_build", &[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn run_hooks_stops_at_failure_with_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let commands = [
+            "echo broken >&2; exit 3".to_owned(),
+            "touch never.txt".to_owned(),
+        ];
+        let err = run_hooks(dir.path(), "post_build", &commands)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("post_build hook `echo broken >&2; exit 3` exited with"),
+            "got: {err}"
+        );
+        assert!(err.contains("broken"), "should include stderr, got: {err}");
+        assert!(!dir.path().join("never.txt").exists());
+    }
+}
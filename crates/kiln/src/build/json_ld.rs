@@ -0,0 +1,338 @@
+use serde::{Serialize, Serializer};
+
+use crate::config::Config;
+use crate::content::page::{Page, PageKind};
+use crate::template::vars::PostTemplateVars;
+
+/// A JSON-LD document: schema.org nodes sharing one `@context`.
+#[derive(Debug, Serialize)]
+struct Graph<'a> {
+    #[serde(rename = "@context")]
+    context: &'static str,
+    #[serde(rename = "@graph")]
+    graph: Vec<Node<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "@type")]
+enum Node<'a> {
+    BlogPosting(CreativeWork<'a>),
+    WebPage(CreativeWork<'a>),
+    BreadcrumbList {
+        #[serde(rename = "itemListElement")]
+        items: Vec<ListItem<'a>>,
+    },
+    WebSite(WebSite<'a>),
+}
+
+/// A post or standalone page.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreativeWork<'a> {
+    headline: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    description: &'a str,
+    url: &'a str,
+    main_entity_of_page: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_published: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_modified: Option<&'a str>,
+    #[serde(
+        skip_serializing_if = "<[_]>::is_empty",
+        serialize_with = "serialize_one_or_many"
+    )]
+    author: Vec<Person<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    article_section: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    keywords: &'a [String],
+    in_language: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct Person<'a> {
+    #[serde(rename = "@type")]
+    kind: &'static str,
+    name: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    url: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ListItem<'a> {
+    #[serde(rename = "@type")]
+    kind: &'static str,
+    position: usize,
+    name: &'a str,
+    item: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebSite<'a> {
+    name: &'a str,
+    url: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    description: &'a str,
+    in_language: &'a str,
+}
+
+/// Returns the JSON-LD of a post (`BlogPosting`) or standalone page
+/// (`WebPage`) rendered with `vars`, followed by its `BreadcrumbList`:
+/// home, the post's section, then the page itself.
+///
+/// The page's `authors` are the authors; `updated` is the modification
+/// date, else `date`.
+pub(crate) fn page_json_ld(vars: &PostTemplateVars<'_>, page: &Page) -> String {
+    let config = vars.config;
+    let work = CreativeWork {
+        headline: vars.title,
+        description: vars.description,
+        url: vars.url,
+        main_entity_of_page: vars.url,
+        image: vars
+            .og_image
+            .as_deref()
+            .map(|src| absolute_url(&config.base_url, src)),
+        date_published: vars.date.as_deref(),
+        date_modified: vars.updated.as_deref().or(vars.date.as_deref()),
+        author: vars
+            .authors
+            .iter()
+            .map(|author| Person {
+                kind: "Person",
+                name: &author.name,
+                url: &author.link,
+            })
+            .collect(),
+        article_section: vars.section.as_ref().map(|section| section.name.as_str()),
+        keywords: &page.frontmatter.tags,
+        in_language: vars.language,
+    };
+    let node = match page.kind {
+        PageKind::Post { .. } => Node::BlogPosting(work),
+        PageKind::Page => Node::WebPage(work),
+    };
+
+    let home_url = home_url(config);
+    let mut crumbs = vec![(config.title.as_str(), home_url.as_str())];
+    if let Some(section) = &vars.section {
+        crumbs.push((&section.name, &section.url));
+    }
+    crumbs.push((vars.title, vars.url));
+    let items = crumbs
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, item))| ListItem {
+            kind: "ListItem",
+            position: i + 1,
+            name,
+            item,
+        })
+        .collect();
+
+    serialize(vec![node, Node::BreadcrumbList { items }])
+}
+
+/// Returns the `WebSite` JSON-LD of the home page.
+pub(crate) fn website_json_ld(config: &Config) -> String {
+    let url = home_url(config);
+    serialize(vec![Node::WebSite(WebSite {
+        name: &config.title,
+        url: &url,
+        description: &config.description,
+        in_language: &config.language,
+    })])
+}
+
+/// Serializes `graph` for a `<script type="application/ld+json">`, with `<`
+/// escaped so no string value can close the script.
+fn serialize(graph: Vec<Node<'_>>) -> String {
+    let graph = Graph {
+        context: "https://schema.org",
+        graph,
+    };
+    serde_json::to_string(&graph)
+        .expect("JSON-LD should serialize")
+        .replace('<', "\\u003c")
+}
+
+/// Serializes a single item as itself rather than a one-element array.
+fn serialize_one_or_many<S: Serializer, T: Serialize>(
+    items: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match items {
+        [item] => item.serialize(serializer),
+        items => items.serialize(serializer),
+    }
+}
+
+fn home_url(config: &Config) -> String {
+    format!("{}/", config.base_url.trim_end_matches('/'))
+}
+
+/// Prefixes a root-relative `src` with the site `base_url`.
+fn absolute_url(base_url: &str, src: &str) -> String {
+    if src.contains("://") {
+        src.to_owned()
+    } else {
+        format!("{}{src}", base_url.trim_end_matches('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::template::vars::PageAuthor;
+    use crate::test_utils::{test_config, test_page, test_post_vars};
+
+    // ── page_json_ld ──
+
+    #[test]
+    fn page_json_ld_describes_post_and_breadcrumbs() {
+        let mut config = test_config();
+        config.base_url = "https://example.com".into();
+        config.title = "My Site".into();
+        let mut page = test_page("Hello");
+        page.kind = PageKind::Post {
+            section: Some("note".into()),
+        };
+        page.frontmatter.tags = vec!["rust".into()];
+        let vars = PostTemplateVars {
+            updated: Some("2026-02-01T00:00:00Z".into()),
+            authors: vec![PageAuthor {
+                name: "Ada Quill".into(),
+                ..PageAuthor::default()
+            }],
+            ..test_post_vars(&config, "Hello")
+        };
+
+        let json: Value = serde_json::from_str(&page_json_ld(&vars, &page)).unwrap();
+        assert_eq!(json["@context"], "https://schema.org");
+        assert_eq!(
+            json["@graph"][0],
+            json!({
+                "@type": "BlogPosting",
+                "headline": "Hello",
+                "description": "A post",
+                "url": "https://example.com/posts/note/hello/",
+                "mainEntityOfPage": "https://example.com/posts/note/hello/",
+                "image": "https://example.com/posts/note/hello/og.png",
+                "datePublished": "2026-01-15T00:00:00Z",
+                "dateModified": "2026-02-01T00:00:00Z",
+                "author": { "@type": "Person", "name": "Ada Quill" },
+                "articleSection": "Notes",
+                "keywords": ["rust"],
+                "inLanguage": "en",
+            })
+        );
+        let crumbs: Vec<_> = json["@graph"][1]["itemListElement"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                (
+                    item["position"].as_u64().unwrap(),
+                    item["item"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            crumbs,
+            [
+                (1, "https://example.com/"),
+                (2, "https://example.com/posts/note/"),
+                (3, "https://example.com/posts/note/hello/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_json_ld_lists_several_authors() {
+        let config = test_config();
+        let author = |name: &str, link: &str| PageAuthor {
+            name: name.into(),
+            link: link.into(),
+            ..PageAuthor::default()
+        };
+        let vars = PostTemplateVars {
+            authors: vec![
+                author("Alice", "https://alice.example.com"),
+                author("Bob", ""),
+            ],
+            ..test_post_vars(&config, "Hello")
+        };
+
+        let json: Value = serde_json::from_str(&page_json_ld(&vars, &test_page("x"))).unwrap();
+        assert_eq!(
+            json["@graph"][0]["author"],
+            json!([
+                { "@type": "Person", "name": "Alice", "url": "https://alice.example.com" },
+                { "@type": "Person", "name": "Bob" },
+            ])
+        );
+    }
+
+    #[test]
+    fn page_json_ld_standalone_page_without_author() {
+        let config = test_config();
+        let mut vars = test_post_vars(&config, "About");
+        vars.section = None;
+        vars.og_image = None;
+
+        let json: Value = serde_json::from_str(&page_json_ld(&vars, &test_page("About"))).unwrap();
+        let node = &json["@graph"][0];
+        assert_eq!(node["@type"], "WebPage");
+        assert_eq!(
+            node["dateModified"], "2026-01-15T00:00:00Z",
+            "falls back to date"
+        );
+        for key in ["author", "image", "articleSection", "keywords"] {
+            assert!(node.get(key).is_none(), "{key}: {node}");
+        }
+        assert_eq!(
+            json["@graph"][1]["itemListElement"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn page_json_ld_cannot_close_the_script() {
+        let config = test_config();
+        let title = "</script><script>alert(1)</script>";
+        let json_ld = page_json_ld(&test_post_vars(&config, title), &test_page("x"));
+        assert!(!json_ld.contains('<'), "{json_ld}");
+
+        let json: Value = serde_json::from_str(&json_ld).unwrap();
+        assert_eq!(json["@graph"][0]["headline"], title);
+    }
+
+    // ── website_json_ld ──
+
+    #[test]
+    fn website_json_ld_describes_site() {
+        let mut config = test_config();
+        config.base_url = "https://example.com/".into();
+        config.title = "My Site".into();
+
+        let json: Value = serde_json::from_str(&website_json_ld(&config)).unwrap();
+        assert_eq!(
+            json["@graph"][0],
+            json!({
+                "@type": "WebSite",
+                "name": "My Site",
+                "url": "https://example.com/",
+                "inLanguage": "en",
+            })
+        );
+    }
+}
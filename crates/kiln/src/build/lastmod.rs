@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, ensure};
+use jiff::Timestamp;
+
+use crate::content::discovery::ContentSet;
+
+/// Gives pages without an `updated` date the author date of the last git
+/// commit touching their source file (see `lastmod_from_git`).
+///
+/// Files never committed keep no `updated`. Outside a git repository, or
+/// without `git` installed, every page does, with a warning.
+pub(super) fn apply_git_lastmod(root: &Path, content: &mut ContentSet) {
+    let pages = || content.pages.iter().chain(&content.drafts);
+    if pages().all(|page| page.frontmatter.updated.is_some()) {
+        return;
+    }
+    let lastmod = match git_lastmod(root, &content.content_dir) {
+        Ok(lastmod) => lastmod,
+        Err(e) => {
+            tracing::warn!("lastmod_from_git: {e:#}");
+            return;
+        }
+    };
+    for page in content.pages.iter_mut().chain(&mut content.drafts) {
+        if page.frontmatter.updated.is_none() {
+            page.frontmatter.updated = lastmod.get(&page.source_path).copied();
+        }
+    }
+}
+
+/// Maps each file under `dir` in git history to the author date of the
+/// last commit touching it, with one `git log` over the whole directory.
+fn git_lastmod(root: &Path, dir: &Path) -> Result<HashMap<PathBuf, Timestamp>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        // Keeps non-ASCII file names as they are, rather than quoted.
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--relative",
+            "--name-only",
+        ])
+        .args(["--format=%x00%aI", "--"])
+        .arg(dir)
+        .output()
+        .context("failed to run `git` — is it installed?")?;
+    ensure!(
+        output.status.success(),
+        "`git log` exited with {}:\n{}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim_end()
+    );
+
+    // Commits come newest first, each a NUL-prefixed date line followed
+    // by the files it touched.
+    let mut lastmod = HashMap::new();
+    let mut date = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(stamp) = line.strip_prefix('\0') {
+            date = stamp.parse::<Timestamp>().ok();
+        } else if !line.is_empty()
+            && let Some(date) = date
+        {
+            lastmod.entry(root.join(line)).or_insert(date);
+        }
+    }
+    Ok(lastmod)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Slugify;
+    use crate::content::discovery::discover_content;
+    use crate::test_utils::write_test_file;
+
+    fn git(root: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["-c", "user.name=kiln", "-c", "user.email=kiln@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn commit(root: &Path, date: &str) {
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "--quiet", "--date", date, "-m", date]);
+    }
+
+    fn updated(content: &ContentSet, title: &str) -> Option<String> {
+        content
+            .pages
+            .iter()
+            .find(|page| page.frontmatter.title == title)
+            .unwrap()
+            .frontmatter
+            .updated
+            .map(|updated| updated.to_string())
+    }
+
+    // ── apply_git_lastmod ──
+
+    #[test]
+    fn apply_git_lastmod_uses_last_commit() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        git(root, &["init", "--quiet"]);
+        write_test_file(root, "content/posts/a.md", "+++\ntitle = \"A\"\n+++\n");
+        write_test_file(root, "content/posts/文章.md", "+++\ntitle = \"B\"\n+++\n");
+        write_test_file(
+            root,
+            "content/posts/c.md",
+            "+++\ntitle = \"C\"\nupdated = 2020-01-01T00:00:00Z\n+++\n",
+        );
+        commit(root, "2024-01-01T00:00:00Z");
+        write_test_file(
+            root,
+            "content/posts/文章.md",
+            "+++\ntitle = \"B\"\n+++\nedited\n",
+        );
+        commit(root, "2024-06-01T00:00:00Z");
+        write_test_file(root, "content/posts/d.md", "+++\ntitle = \"D\"\n+++\n");
+
+        let mut content = discover_content(root, Slugify::default()).unwrap();
+        apply_git_lastmod(root, &mut content);
+
+        assert_eq!(
+            updated(&content, "A").as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            updated(&content, "B").as_deref(),
+            Some("2024-06-01T00:00:00Z")
+        );
+        assert_eq!(
+            updated(&content, "C").as_deref(),
+            Some("2020-01-01T00:00:00Z"),
+            "frontmatter wins"
+        );
+        assert_eq!(updated(&content, "D"), None, "uncommitted");
+    }
+
+    #[test]
+    fn apply_git_lastmod_outside_repository_leaves_pages() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "content/a.md", "+++\ntitle = \"A\"\n+++\n");
+
+        let mut content = discover_content(root.path(), Slugify::default()).unwrap();
+        apply_git_lastmod(root.path(), &mut content);
+
+        assert_eq!(updated(&content, "A"), None);
+    }
+}
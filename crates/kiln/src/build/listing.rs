@@ -4,12 +4,14 @@ use std::path::Path;
 use anyhow::{Context, Result};
 use jiff::{Timestamp, tz::TimeZone};
 
+use crate::config::SlugifyMode;
 use crate::content::frontmatter::FeaturedImage;
 use crate::content::page::{Page, PageKind};
-use crate::taxonomy::{TaxonomyKind, TaxonomySet};
+use crate::taxonomy::{TaxonomyKind, TaxonomySet, term_slug};
 use crate::template::vars::{LinkedTerm, PageGroup, PageSummary};
-use crate::text::slugify;
 
+use super::podcast::page_audio;
+use super::thumbnail::page_thumbnail;
 use super::url::{page_url, resolve_relative_url};
 
 // ── Listing model ──
@@ -21,6 +23,10 @@ pub(crate) struct ListedPage {
     pub(crate) timestamp: Option<Timestamp>,
     pub(crate) weight: Option<i64>,
     pub(crate) year: String,
+    /// Left out of the sitemap (`noindex`).
+    pub(crate) noindex: bool,
+    /// Left out of RSS feeds (`exclude_from_feed`).
+    pub(crate) exclude_from_feed: bool,
 }
 
 impl ListedPage {
@@ -44,33 +50,62 @@ pub(crate) struct ListingArtifacts {
     pub(crate) section_posts: HashMap<String, Vec<ListedPage>>,
 }
 
+impl ListingArtifacts {
+    /// Summaries of every listable page, exposed to templates as
+    /// `site.pages`.
+    #[must_use]
+    pub(crate) fn site_pages(&self) -> Vec<PageSummary> {
+        self.listed_pages
+            .iter()
+            .map(|listed| listed.summary.clone())
+            .collect()
+    }
+}
+
 // ── Listing construction ──
 
 /// Builds listing artifacts from discovered pages in a single pass.
 ///
 /// Every discovered page produces exactly one `ListedPage`, maintaining
 /// index alignment with the input slice (required by `TaxonomySet::term_pages`).
+/// `summaries` holds the rendered summary of each page, in the same order.
 /// Posts are additionally collected into `listed_posts` and `section_posts`.
 /// Post lists are pre-sorted by date descending.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "each argument is a distinct piece of site-wide state shared by every page"
+)]
 pub(crate) fn build_listing_artifacts(
     pages: &[Page],
+    summaries: &[&str],
     content_dir: &Path,
     base_url: &str,
     time_zone: Option<&TimeZone>,
     section_titles: &HashMap<&str, &str>,
+    thumbnail_width: u32,
+    slugify_mode: SlugifyMode,
 ) -> Result<ListingArtifacts> {
     let mut listed_pages = Vec::with_capacity(pages.len());
     let mut listed_posts = Vec::new();
     let mut section_posts: HashMap<String, Vec<ListedPage>> = HashMap::new();
 
-    for page in pages {
-        let lp = build_listed_page(page, content_dir, base_url, time_zone, section_titles)
-            .with_context(|| {
-                format!(
-                    "failed to build listing entry for {}",
-                    page.source_path.display()
-                )
-            })?;
+    for (page, summary_html) in pages.iter().zip(summaries) {
+        let lp = build_listed_page(
+            page,
+            summary_html,
+            content_dir,
+            base_url,
+            time_zone,
+            section_titles,
+            thumbnail_width,
+            slugify_mode,
+        )
+        .with_context(|| {
+            format!(
+                "failed to build listing entry for {}",
+                page.source_path.display()
+            )
+        })?;
 
         if let PageKind::Post { section } = &page.kind {
             if let Some(slug) = section {
@@ -97,12 +132,19 @@ pub(crate) fn build_listing_artifacts(
 }
 
 /// Builds a `ListedPage` from a content page.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "mirrors `build_listing_artifacts`, one page at a time"
+)]
 fn build_listed_page(
     page: &Page,
+    summary_html: &str,
     content_dir: &Path,
     base_url: &str,
     time_zone: Option<&TimeZone>,
     section_titles: &HashMap<&str, &str>,
+    thumbnail_width: u32,
+    slugify_mode: SlugifyMode,
 ) -> Result<ListedPage> {
     // `output_path` already includes the source and content-dir paths in
     // its error, so no extra `with_context` is needed here.
@@ -112,6 +154,9 @@ fn build_listed_page(
     let weight = page.frontmatter.weight;
     let section = page_section(page, base_url, section_titles);
     let featured_image = resolve_featured_image(page.frontmatter.featured_image.as_ref(), &url);
+    let featured_image_thumb = page_thumbnail(page, thumbnail_width)
+        .map(|thumbnail| resolve_relative_url(&thumbnail.src, &url));
+    let audio = page_audio(page, &url);
 
     Ok(ListedPage {
         summary: PageSummary {
@@ -125,8 +170,11 @@ fn build_listed_page(
                 .clone()
                 .or_else(|| page.summary.clone())
                 .unwrap_or_default(),
+            summary_html: summary_html.to_owned(),
             featured_image,
-            tags: linked_tags(&page.frontmatter.tags, base_url),
+            featured_image_thumb,
+            audio,
+            tags: linked_tags(&page.frontmatter.tags, base_url, slugify_mode),
             section,
         },
         timestamp,
@@ -134,6 +182,8 @@ fn build_listed_page(
         year: timestamp
             .map(|date| page_year(date, time_zone))
             .unwrap_or_default(),
+        noindex: page.frontmatter.noindex.unwrap_or(false),
+        exclude_from_feed: page.frontmatter.exclude_from_feed.unwrap_or(false),
     })
 }
 
@@ -262,11 +312,11 @@ pub(crate) fn resolve_featured_image(
 }
 
 /// Converts raw tag strings into `LinkedTerm`s with pre-computed URLs.
-fn linked_tags(tags: &[String], base_url: &str) -> Vec<LinkedTerm> {
+fn linked_tags(tags: &[String], base_url: &str, slugify_mode: SlugifyMode) -> Vec<LinkedTerm> {
     tags.iter()
         .map(|tag| LinkedTerm {
             name: tag.clone(),
-            url: format!("{base_url}/tags/{}/", slugify(tag)),
+            url: format!("{base_url}/tags/{}/", term_slug(tag, slugify_mode)),
         })
         .collect()
 }
@@ -310,7 +360,10 @@ mod tests {
                 date: timestamp.map(|date: Timestamp| date.to_string()),
                 pinned: weight.is_some(),
                 description: String::new(),
+                summary_html: String::new(),
                 featured_image: None,
+                featured_image_thumb: None,
+                audio: None,
                 tags: Vec::new(),
                 section: None,
             },
@@ -319,6 +372,8 @@ mod tests {
             year: timestamp
                 .map(|date| page_year(date, None))
                 .unwrap_or_default(),
+            noindex: false,
+            exclude_from_feed: false,
         }
     }
 
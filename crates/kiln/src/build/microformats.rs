@@ -0,0 +1,172 @@
+use std::fmt::Write as _;
+
+use crate::content::page::Page;
+use crate::html::{escape, writeln_indented};
+use crate::template::vars::{PageAuthor, PostTemplateVars};
+
+/// Renders the microformats2 `h-entry` properties of a page for the
+/// `h_entry` template variable: its name, URL, summary, dates, authors (as
+/// `h-card`s), and tags.
+///
+/// The properties sit in a `hidden` element of `<data>` and `<time>`
+/// values, so themes can drop them into any `h-entry` element without
+/// changing its look:
+///
+/// ```html
+/// <div hidden>
+///   <data class="u-url" value="https://example.com/posts/hello/"></data>
+///   <data class="p-name" value="Hello"></data>
+///   <time class="dt-published" datetime="2026-01-15T00:00:00Z"></time>
+///   <span class="p-author h-card"><data class="p-name" value="Ada Quill"></data></span>
+///   <data class="p-category" value="rust"></data>
+/// </div>
+/// ```
+#[must_use]
+pub(crate) fn page_h_entry(vars: &PostTemplateVars<'_>, page: &Page) -> String {
+    let mut html = String::new();
+    writeln_indented!(&mut html, 0, "<div hidden>");
+    write_data(&mut html, "u-url", vars.url);
+    write_data(&mut html, "p-name", vars.title);
+    if !vars.description.is_empty() {
+        write_data(&mut html, "p-summary", vars.description);
+    }
+    for (class, date) in [("dt-published", &vars.date), ("dt-updated", &vars.updated)] {
+        if let Some(date) = date {
+            writeln_indented!(
+                &mut html,
+                1,
+                r#"<time class="{class}" datetime="{}"></time>"#,
+                escape(date),
+            );
+        }
+    }
+    for author in &vars.authors {
+        writeln_indented!(&mut html, 1, "{}", h_card(author));
+    }
+    for tag in &page.frontmatter.tags {
+        write_data(&mut html, "p-category", tag);
+    }
+    writeln_indented!(&mut html, 0, "</div>");
+    html
+}
+
+/// Wraps the rendered `content` of a page in an `h-entry` holding its
+/// `h_entry` properties and the content as `e-content`, for
+/// `[microformats] wrap_content`.
+#[must_use]
+pub(crate) fn wrap_content(h_entry: &str, content: &str) -> String {
+    format!(
+        "<div class=\"h-entry\">\n{h_entry}<div class=\"e-content\">\n{content}</div>\n</div>\n"
+    )
+}
+
+// ── Helpers ──
+
+/// Writes a hidden `class` property with `value`.
+fn write_data(html: &mut String, class: &str, value: &str) {
+    writeln_indented!(
+        html,
+        1,
+        r#"<data class="{class}" value="{}"></data>"#,
+        escape(value),
+    );
+}
+
+/// Renders `author` as an embedded `p-author h-card`, linking their own
+/// site, else their author page.
+fn h_card(author: &PageAuthor) -> String {
+    let mut card = format!(
+        r#"<span class="p-author h-card"><data class="p-name" value="{}"></data>"#,
+        escape(&author.name),
+    );
+    let link = Some(author.link.as_str())
+        .filter(|link| !link.is_empty())
+        .or(author.url.as_deref());
+    for (class, value) in [("u-url", link), ("u-photo", Some(author.avatar.as_str()))] {
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            _ = write!(
+                card,
+                r#"<data class="{class}" value="{}"></data>"#,
+                escape(value)
+            );
+        }
+    }
+    card.push_str("</span>");
+    card
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::{test_config, test_page, test_post_vars};
+
+    // ── page_h_entry ──
+
+    #[test]
+    fn page_h_entry_lists_properties() {
+        let config = test_config();
+        let mut page = test_page("Hello");
+        page.frontmatter.tags = vec!["rust".into(), "web & dev".into()];
+        let vars = PostTemplateVars {
+            updated: Some("2026-02-01T00:00:00Z".into()),
+            authors: vec![PageAuthor {
+                name: "Ada Quill".into(),
+                url: Some("https://example.com/authors/ada-quill/".into()),
+                avatar: "/images/ada-quill.png".into(),
+                ..PageAuthor::default()
+            }],
+            ..test_post_vars(&config, "Hello")
+        };
+
+        assert_eq!(
+            page_h_entry(&vars, &page),
+            indoc! {r#"
+                <div hidden>
+                  <data class="u-url" value="https://example.com/posts/note/hello/"></data>
+                  <data class="p-name" value="Hello"></data>
+                  <data class="p-summary" value="A post"></data>
+                  <time class="dt-published" datetime="2026-01-15T00:00:00Z"></time>
+                  <time class="dt-updated" datetime="2026-02-01T00:00:00Z"></time>
+                  <span class="p-author h-card"><data class="p-name" value="Ada Quill"></data><data class="u-url" value="https://example.com/authors/ada-quill/"></data><data class="u-photo" value="/images/ada-quill.png"></data></span>
+                  <data class="p-category" value="rust"></data>
+                  <data class="p-category" value="web &amp; dev"></data>
+                </div>
+            "#}
+        );
+    }
+
+    // ── h_card ──
+
+    #[test]
+    fn h_card_prefers_author_link() {
+        let author = PageAuthor {
+            name: "Alice".into(),
+            link: "https://alice.example.com".into(),
+            url: Some("https://example.com/authors/alice/".into()),
+            ..PageAuthor::default()
+        };
+        assert_eq!(
+            h_card(&author),
+            r#"<span class="p-author h-card"><data class="p-name" value="Alice"></data><data class="u-url" value="https://alice.example.com"></data></span>"#
+        );
+    }
+
+    // ── wrap_content ──
+
+    #[test]
+    fn wrap_content_nests_e_content_in_h_entry() {
+        assert_eq!(
+            wrap_content("<div hidden></div>\n", "<p>Hi</p>\n"),
+            indoc! {r#"
+                <div class="h-entry">
+                <div hidden></div>
+                <div class="e-content">
+                <p>Hi</p>
+                </div>
+                </div>
+            "#}
+        );
+    }
+}
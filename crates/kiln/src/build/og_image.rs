@@ -0,0 +1,196 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use indoc::indoc;
+use resvg::usvg::fontdb::{self, Database};
+use resvg::{tiny_skia, usvg};
+
+use crate::config::OgImage;
+use crate::output::write_output;
+use crate::template::TemplateEngine;
+use crate::template::vars::{OgImageVars, PostTemplateVars};
+use crate::text::wrap_lines;
+
+/// File name of a generated card, written next to the page's `index.html`.
+pub(crate) const OG_IMAGE_FILE: &str = "og.png";
+
+/// Columns and lines the title is wrapped to for the built-in card.
+const TITLE_COLUMNS: usize = 24;
+const TITLE_MAX_LINES: usize = 3;
+
+/// The card rendered when no template directory has an `[og_image]`
+/// template: 1200 × 630, the size social networks display.
+const DEFAULT_TEMPLATE: &str = indoc! {r#"
+    <svg xmlns="http://www.w3.org/2000/svg" width="1200" height="630" viewBox="0 0 1200 630">
+      <rect width="1200" height="630" fill="{{ background }}" />
+      {%- if accent %}
+      <rect width="24" height="630" fill="{{ accent }}" />
+      {%- endif %}
+      <text x="96" y="{{ 300 - (title_lines | length - 1) * 44 }}" font-family="{{ font_family }}" font-size="72" font-weight="bold" fill="{{ foreground }}">
+        {%- for line in title_lines %}
+        <tspan x="96" dy="{{ 0 if loop.first else 88 }}">{{ line }}</tspan>
+        {%- endfor %}
+      </text>
+      <text x="96" y="540" font-family="{{ font_family }}" font-size="36" fill="{{ accent or foreground }}">{{ site_name }}</text>
+    </svg>
+"#};
+
+/// Renders `[og_image]` cards to PNG, with the fonts loaded once per build.
+#[derive(Debug)]
+pub(crate) struct OgImageRenderer {
+    fontdb: Arc<Database>,
+}
+
+impl OgImageRenderer {
+    /// Loads the configured fonts (relative to `root`), then system fonts.
+    ///
+    /// The generic `sans-serif` and `serif` families map to the first
+    /// configured font, or to an installed font when the platform default
+    /// (Arial / Times New Roman) is missing, so text is never dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured font cannot be read.
+    pub(crate) fn new(root: &Path, config: &OgImage) -> Result<Self> {
+        let mut fontdb = Database::new();
+        for font in &config.fonts {
+            let path = root.join(font);
+            fontdb
+                .load_font_file(&path)
+                .with_context(|| format!("failed to load font {}", path.display()))?;
+        }
+        let configured = first_family(&fontdb);
+        fontdb.load_system_fonts();
+
+        let fallback = configured.or_else(|| {
+            let query = fontdb::Query {
+                families: &[fontdb::Family::SansSerif],
+                ..fontdb::Query::default()
+            };
+            fontdb
+                .query(&query)
+                .is_none()
+                .then(|| system_family(&fontdb))?
+        });
+        if let Some(family) = fallback {
+            fontdb.set_sans_serif_family(family.clone());
+            fontdb.set_serif_family(family);
+        }
+        Ok(Self {
+            fontdb: Arc::new(fontdb),
+        })
+    }
+
+    /// Renders the card of the page described by `page` to `dest`, through
+    /// the `[og_image]` template.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template fails to render, its output is not
+    /// a valid SVG with a size, or writing fails.
+    pub(crate) fn write(
+        &self,
+        engine: &TemplateEngine,
+        page: &PostTemplateVars<'_>,
+        dest: &Path,
+    ) -> Result<()> {
+        let config = &page.config.og_image;
+        let vars = OgImageVars {
+            title: page.title,
+            title_lines: wrap_lines(page.title, TITLE_COLUMNS, TITLE_MAX_LINES),
+            description: page.description,
+            date: page.date.clone(),
+            site_name: &page.config.title,
+            url: page.url,
+            font_family: &config.font_family,
+            background: &config.background,
+            foreground: &config.foreground,
+            accent: config.accent.as_deref(),
+        };
+        let svg = engine.render_template_or(&config.template, DEFAULT_TEMPLATE, &vars)?;
+        let png = self.render(&svg)?;
+        write_output(dest, png)
+    }
+
+    /// Rasterizes `svg` at its own size to PNG.
+    fn render(&self, svg: &str) -> Result<Vec<u8>> {
+        let options = usvg::Options {
+            fontdb: Arc::clone(&self.fontdb),
+            ..usvg::Options::default()
+        };
+        let tree = usvg::Tree::from_str(svg, &options).context("failed to parse card SVG")?;
+        let size = tree.size().to_int_size();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+            .context("card SVG has an empty size")?;
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+        pixmap.encode_png().context("failed to encode card PNG")
+    }
+}
+
+/// Returns the family name of the first font loaded into `fontdb`.
+fn first_family(fontdb: &Database) -> Option<String> {
+    let face = fontdb.faces().next()?;
+    face.families.first().map(|(name, _)| name.clone())
+}
+
+/// Returns an installed family to stand in for the generic ones: the first
+/// proportional sans-serif by name (e.g., `DejaVu Sans`), else any.
+fn system_family(fontdb: &Database) -> Option<String> {
+    let families = || {
+        fontdb
+            .faces()
+            .filter(|face| !face.monospaced)
+            .filter_map(|face| face.families.first().map(|(name, _)| name))
+    };
+    families()
+        .find(|name| name.contains("Sans"))
+        .or_else(|| families().next())
+        .or_else(|| {
+            fontdb
+                .faces()
+                .next()?
+                .families
+                .first()
+                .map(|(name, _)| name)
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── OgImageRenderer::new ──
+
+    #[test]
+    fn new_rejects_missing_fonts() {
+        let root = tempfile::tempdir().unwrap();
+        let config = OgImage {
+            fonts: vec!["fonts/missing.ttf".into()],
+            ..OgImage::default()
+        };
+        let err = OgImageRenderer::new(root.path(), &config).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed to load font"),
+            "got: {err:#}"
+        );
+    }
+
+    // ── OgImageRenderer::render ──
+
+    #[test]
+    fn render_rasterizes_at_svg_size() {
+        let renderer = OgImageRenderer {
+            fontdb: Arc::new(Database::new()),
+        };
+        let png = renderer
+            .render(r##"<svg xmlns="http://www.w3.org/2000/svg" width="120" height="63"><rect width="120" height="63" fill="#f97316" /></svg>"##)
+            .unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (120, 63));
+        assert_eq!(image.get_pixel(60, 30).0, [0xf9, 0x73, 0x16]);
+
+        assert!(renderer.render("<svg").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::url::resolve_relative_url;
+use crate::content::page::Page;
+use crate::template::checked_relative_path;
+use crate::template::vars::PageAudio;
+
+/// MIME types of common audio formats, by extension.
+const AUDIO_MIME_TYPES: [(&str, &str); 8] = [
+    ("aac", "audio/aac"),
+    ("flac", "audio/flac"),
+    ("m4a", "audio/x-m4a"),
+    ("mp3", "audio/mpeg"),
+    ("oga", "audio/ogg"),
+    ("ogg", "audio/ogg"),
+    ("opus", "audio/opus"),
+    ("wav", "audio/wav"),
+];
+
+/// MIME type of audio files with an unknown extension.
+const FALLBACK_MIME_TYPE: &str = "audio/mpeg";
+
+/// Resolves `page`'s frontmatter `audio` against its URL `page_url`.
+///
+/// A relative `file` among the bundle's assets gets its size as `length`,
+/// which podcast apps use to show download sizes; other files get `0`.
+#[must_use]
+pub(crate) fn page_audio(page: &Page, page_url: &str) -> Option<PageAudio> {
+    let audio = page.frontmatter.audio.as_ref()?;
+    let length = bundle_asset(page, &audio.file)
+        .and_then(|path| fs::metadata(path).ok())
+        .map_or(0, |metadata| metadata.len());
+    let mime_type = audio
+        .mime_type
+        .clone()
+        .unwrap_or_else(|| audio_mime_type(&audio.file).to_owned());
+    Some(PageAudio {
+        url: resolve_relative_url(&audio.file, page_url),
+        length,
+        mime_type,
+        duration: audio.duration.clone(),
+    })
+}
+
+// ── Helpers ──
+
+/// Returns the bundle asset `file` refers to, or `None` unless it is a
+/// relative path to one of `page`'s assets.
+fn bundle_asset(page: &Page, file: &str) -> Option<PathBuf> {
+    let path = page
+        .source_path
+        .parent()?
+        .join(checked_relative_path(file).ok()?);
+    page.assets.contains(&path).then_some(path)
+}
+
+/// Infers the MIME type of the audio `file` from its extension.
+fn audio_mime_type(file: &str) -> &'static str {
+    let path = file.split(['?', '#']).next().unwrap_or(file);
+    let Some(ext) = Path::new(path).extension().and_then(|ext| ext.to_str()) else {
+        return FALLBACK_MIME_TYPE;
+    };
+    AUDIO_MIME_TYPES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(ext))
+        .map_or(FALLBACK_MIME_TYPE, |(_, mime_type)| mime_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::frontmatter::Audio;
+    use crate::test_utils::test_page;
+
+    const PAGE_URL: &str = "https://example.com/episodes/one/";
+
+    fn episode_page(root: &Path, file: &str) -> Page {
+        let mut page = test_page("one");
+        page.source_path = root.join("content/episodes/one/index.md");
+        page.assets = vec![root.join("content/episodes/one/one.mp3")];
+        page.frontmatter.audio = Some(Audio {
+            file: file.into(),
+            duration: Some("00:42:10".into()),
+            mime_type: None,
+        });
+        page
+    }
+
+    // ── page_audio ──
+
+    #[test]
+    fn page_audio_measures_bundle_asset() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("content/episodes/one");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("one.mp3"), [0; 1234]).unwrap();
+
+        let audio = page_audio(&episode_page(root.path(), "one.mp3"), PAGE_URL).unwrap();
+        assert_eq!(
+            audio,
+            PageAudio {
+                url: "/episodes/one/one.mp3".into(),
+                length: 1234,
+                mime_type: "audio/mpeg".into(),
+                duration: Some("00:42:10".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn page_audio_keeps_external_urls() {
+        let root = tempfile::tempdir().unwrap();
+        let mut page = episode_page(root.path(), "https://cdn.example.com/one.m4a");
+        let audio = page_audio(&page, PAGE_URL).unwrap();
+        assert_eq!(audio.url, "https://cdn.example.com/one.m4a");
+        assert_eq!(audio.length, 0);
+        assert_eq!(audio.mime_type, "audio/x-m4a");
+
+        page.frontmatter.audio.as_mut().unwrap().mime_type = Some("audio/mp4".into());
+        assert_eq!(page_audio(&page, PAGE_URL).unwrap().mime_type, "audio/mp4");
+
+        page.frontmatter.audio = None;
+        assert_eq!(page_audio(&page, PAGE_URL), None);
+    }
+
+    // ── audio_mime_type ──
+
+    #[test]
+    fn audio_mime_type_by_extension() {
+        assert_eq!(audio_mime_type("one.MP3"), "audio/mpeg");
+        assert_eq!(audio_mime_type("one.ogg?dl=1"), "audio/ogg");
+        assert_eq!(audio_mime_type("one.opus"), "audio/opus");
+        assert_eq!(audio_mime_type("one"), FALLBACK_MIME_TYPE);
+    }
+}
@@ -0,0 +1,224 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail, ensure};
+use indoc::formatdoc;
+
+use crate::content::page::Page;
+use crate::html::escape;
+use crate::output::{append_output, output_file, output_path, write_output};
+
+use super::BuildContext;
+use super::url::page_url;
+
+/// Netlify redirect rules file, relative to the output directory.
+const NETLIFY_REDIRECTS: &str = "_redirects";
+
+/// Writes a redirect stub at the output path of each `moved` page (those with
+/// `redirect_to` set), in place of the rendered page, and at every alias of
+/// `pages` and `moved`.
+///
+/// Aliases must not collide with any other output, so this runs after all
+/// other pages are written. With `[redirects] netlify` enabled, every
+/// redirect is also appended to `_redirects`.
+pub(crate) fn build_redirects(
+    ctx: &BuildContext,
+    moved: &[Page],
+    pages: &[Page],
+    content_dir: &Path,
+    output_dir: &Path,
+) -> Result<()> {
+    let language = &ctx.config.language;
+    let mut rules = Vec::new();
+    for page in moved {
+        let Some(target) = page.frontmatter.redirect_to.as_deref() else {
+            continue;
+        };
+        if target.trim().is_empty() {
+            bail!("empty `redirect_to` in {}", page.source_path.display());
+        }
+        let output_path = page.output_path(content_dir)?;
+        let dest = output_file(output_dir, &output_path)?;
+        write_output(&dest, redirect_html(target, language))
+            .with_context(|| format!("failed to write {}", dest.display()))?;
+        rules.push((page_url("", &output_path), target.to_owned()));
+    }
+
+    for page in pages.iter().chain(moved) {
+        if page.frontmatter.aliases.is_empty() {
+            continue;
+        }
+        let target = match &page.frontmatter.redirect_to {
+            Some(target) => target.clone(),
+            None => page_url(&ctx.config.base_url, &page.output_path(content_dir)?),
+        };
+        for alias in &page.frontmatter.aliases {
+            let output_path = alias_output_path(alias)
+                .with_context(|| format!("invalid alias in {}", page.source_path.display()))?;
+            let dest = output_file(output_dir, &output_path)?;
+            if dest.exists() {
+                bail!(
+                    "alias `{alias}` in {} collides with {}",
+                    page.source_path.display(),
+                    dest.display()
+                );
+            }
+            write_output(&dest, redirect_html(&target, language))
+                .with_context(|| format!("failed to write {}", dest.display()))?;
+            rules.push((page_url("", &output_path), target.clone()));
+        }
+    }
+
+    if ctx.config.redirects.netlify {
+        append_netlify_rules(&output_dir.join(NETLIFY_REDIRECTS), &rules)?;
+    }
+    Ok(())
+}
+
+/// Maps a site-root alias path to the output path of its redirect stub:
+/// `/old-url/` → `old-url/index.html`, `/old.html` → `old.html`.
+fn alias_output_path(alias: &str) -> Result<PathBuf> {
+    ensure!(
+        !alias.contains("://"),
+        "alias `{alias}` must be a site-root path, not a URL"
+    );
+    ensure!(
+        !alias.contains(char::is_whitespace),
+        "alias `{alias}` must not contain whitespace"
+    );
+    ensure!(
+        !alias.trim_matches('/').is_empty(),
+        "alias `{alias}` would replace the home page"
+    );
+    let mut path = output_path(alias)
+        .with_context(|| format!("alias `{alias}` has an empty, `.`, or `..` segment"))?;
+    let is_file = !alias.ends_with('/')
+        && path
+            .extension()
+            .is_some_and(|ext| ext == "html" || ext == "htm");
+    if !is_file {
+        path.push("index.html");
+    }
+    Ok(path)
+}
+
+/// Appends a permanent (`301`) Netlify redirect rule for each
+/// `(from, to)` pair, after any rules already in the file (e.g., one copied
+/// from `static/`), which take precedence.
+fn append_netlify_rules(path: &Path, rules: &[(String, String)]) -> Result<()> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let mut contents = String::new();
+    for (from, to) in rules {
+        _ = writeln!(contents, "{from} {to} 301");
+    }
+    append_output(path, &contents)
+}
+
+/// Renders a page that sends visitors and crawlers straight to `target`.
+///
+/// The meta refresh fires immediately; the canonical link tells search
+/// engines where the content now lives, and `noindex` keeps the stub itself
+/// out of results.
+fn redirect_html(target: &str, language: &str) -> String {
+    let target = escape(target);
+    let language = escape(language);
+    formatdoc! {r#"
+        <!DOCTYPE html>
+        <html lang="{language}">
+        <head>
+        <meta charset="utf-8">
+        <title>Redirecting…</title>
+        <link rel="canonical" href="{target}">
+        <meta name="robots" content="noindex">
+        <meta http-equiv="refresh" content="0; url={target}">
+        </head>
+        <body>
+        <p>This page has moved to <a href="{target}">{target}</a>.</p>
+        </body>
+        </html>
+    "#}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    // ── alias_output_path ──
+
+    #[test]
+    fn alias_output_path_maps_directories_and_files() {
+        assert_eq!(
+            alias_output_path("/old-url/").unwrap(),
+            Path::new("old-url/index.html")
+        );
+        assert_eq!(
+            alias_output_path("2019/05/post").unwrap(),
+            Path::new("2019/05/post/index.html")
+        );
+        assert_eq!(
+            alias_output_path("/old/post.html").unwrap(),
+            Path::new("old/post.html")
+        );
+    }
+
+    #[test]
+    fn alias_output_path_rejects_invalid_aliases() {
+        for (alias, message) in [
+            ("https://old.example/a/", "not a URL"),
+            ("/old url/", "whitespace"),
+            ("/", "home page"),
+            ("/a/../../etc/", "`..` segment"),
+            ("/a//b/", "empty"),
+        ] {
+            let err = alias_output_path(alias).unwrap_err().to_string();
+            assert!(err.contains(message), "{alias}: {err}");
+        }
+    }
+
+    // ── append_netlify_rules ──
+
+    #[test]
+    fn append_netlify_rules_keeps_existing_rules_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(NETLIFY_REDIRECTS);
+        fs::write(&path, "/feed /index.xml 301").unwrap();
+
+        let rules = [("/old/".to_owned(), "https://example.com/new/".to_owned())];
+        append_netlify_rules(&path, &rules).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/feed /index.xml 301\n/old/ https://example.com/new/ 301\n"
+        );
+    }
+
+    #[test]
+    fn append_netlify_rules_skips_file_without_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(NETLIFY_REDIRECTS);
+        append_netlify_rules(&path, &[]).unwrap();
+        assert!(!path.exists());
+    }
+
+    // ── redirect_html ──
+
+    #[test]
+    fn redirect_html_escapes_target() {
+        let html = redirect_html("https://new.example/a?b=1&c=2", "en");
+        assert!(
+            html.contains(
+                r#"<meta http-equiv="refresh" content="0; url=https://new.example/a?b=1&amp;c=2">"#
+            ),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<link rel="canonical" href="https://new.example/a?b=1&amp;c=2">"#),
+            "html:\n{html}"
+        );
+        assert!(html.contains(r#"<html lang="en">"#), "html:\n{html}");
+    }
+}
@@ -0,0 +1,161 @@
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+use crate::config::{Related, SlugifyMode};
+use crate::content::page::{Page, PageKind};
+use crate::taxonomy::{TaxonomyKind, TaxonomySet, term_slug};
+use crate::template::vars::PageSummary;
+
+use super::listing::ListedPage;
+
+/// Picks the related posts of every page, index-aligned with `pages` (and
+/// with `listed_pages` and `taxonomy_set`, which are built from the same
+/// slice). Standalone pages get none, and are never picked.
+///
+/// See [`Related`] for how posts are scored.
+#[must_use]
+pub(crate) fn build_related(
+    pages: &[Page],
+    listed_pages: &[ListedPage],
+    taxonomy_set: &TaxonomySet,
+    config: &Related,
+    slugify_mode: SlugifyMode,
+) -> Vec<Vec<PageSummary>> {
+    pages
+        .iter()
+        .enumerate()
+        .map(|(idx, page)| {
+            if config.limit == 0 {
+                return Vec::new();
+            }
+            let PageKind::Post { section } = &page.kind else {
+                return Vec::new();
+            };
+
+            let tags: BTreeSet<String> = page
+                .frontmatter
+                .tags
+                .iter()
+                .map(|tag| term_slug(tag.trim(), slugify_mode))
+                .collect();
+            let mut scores = vec![0; pages.len()];
+            for tag in tags {
+                let key = (TaxonomyKind::Tags, tag);
+                for &other in taxonomy_set.term_pages.get(&key).into_iter().flatten() {
+                    scores[other] += 1;
+                }
+            }
+            if section.is_some() {
+                for (other, candidate) in pages.iter().enumerate() {
+                    if matches!(&candidate.kind, PageKind::Post { section: s } if s == section) {
+                        scores[other] += config.section_weight;
+                    }
+                }
+            }
+
+            let mut ranked: Vec<usize> = (0..pages.len())
+                .filter(|&other| other != idx && scores[other] > 0 && pages[other].is_post())
+                .collect();
+            ranked.sort_by_key(|&other| {
+                (
+                    Reverse(scores[other]),
+                    Reverse(listed_pages[other].timestamp),
+                )
+            });
+            ranked
+                .into_iter()
+                .take(config.limit)
+                .map(|other| listed_pages[other].summary.clone())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::build::listing::build_listing_artifacts;
+    use crate::taxonomy::build_taxonomies;
+    use crate::test_utils::test_page;
+
+    fn post(slug: &str, section: Option<&str>, tags: &[&str], day: u8) -> Page {
+        let mut page = test_page(slug);
+        page.kind = PageKind::Post {
+            section: section.map(String::from),
+        };
+        page.source_path = PathBuf::from(format!("/site/content/posts/{slug}/index.md"));
+        page.frontmatter.tags = tags.iter().map(|s| (*s).to_owned()).collect();
+        page.frontmatter.date = Some(format!("2026-01-{day:02}T00:00:00Z").parse().unwrap());
+        page
+    }
+
+    fn related_titles(pages: &[Page], config: &Related) -> Vec<Vec<String>> {
+        let artifacts = build_listing_artifacts(
+            pages,
+            &vec![""; pages.len()],
+            Path::new("/site/content"),
+            "https://example.com",
+            None,
+            &HashMap::new(),
+            0,
+            SlugifyMode::Safe,
+        )
+        .unwrap();
+        let taxonomy_set = build_taxonomies(pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
+        build_related(
+            pages,
+            &artifacts.listed_pages,
+            &taxonomy_set,
+            config,
+            SlugifyMode::Safe,
+        )
+        .into_iter()
+        .map(|related| related.into_iter().map(|page| page.title).collect())
+        .collect()
+    }
+
+    // ── build_related ──
+
+    #[test]
+    fn build_related_ranks_by_shared_tags_then_date() {
+        let mut about = test_page("about");
+        about.source_path = PathBuf::from("/site/content/about/index.md");
+        about.frontmatter.tags = vec!["rust".into()];
+        let pages = vec![
+            post("a", None, &["rust", "web"], 1),
+            post("b", None, &["Rust"], 2),
+            post("c", None, &["rust", "web"], 3),
+            post("d", None, &["go"], 4),
+            about,
+        ];
+        let related = related_titles(&pages, &Related::default());
+        assert_eq!(related[0], ["c", "b"]);
+        assert_eq!(related[1], ["c", "a"]);
+        assert_eq!(related[2], ["a", "b"]);
+        assert!(related[3].is_empty(), "no shared tags: {:?}", related[3]);
+        assert!(related[4].is_empty(), "standalone page: {:?}", related[4]);
+    }
+
+    #[test]
+    fn build_related_weights_section_and_respects_limit() {
+        let pages = vec![
+            post("a", Some("note"), &["rust"], 1),
+            post("b", None, &["rust"], 2),
+            post("c", Some("note"), &[], 3),
+        ];
+        let config = Related {
+            limit: 1,
+            section_weight: 2,
+        };
+        assert_eq!(related_titles(&pages, &config)[0], ["c"]);
+
+        let disabled = Related {
+            limit: 0,
+            ..Related::default()
+        };
+        assert!(related_titles(&pages, &disabled)[0].is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use webp::{Encoder, WebPMemory};
+
+use crate::output::write_output;
+use crate::render::responsive::ImageVariant;
+
+/// Decodes the image at `source`, guessing its format from the contents.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or decoded.
+pub(crate) fn decode_image(source: &Path) -> Result<DynamicImage> {
+    image::ImageReader::open(source)
+        .with_context(|| format!("failed to open {}", source.display()))?
+        .with_guessed_format()
+        .with_context(|| format!("failed to read {}", source.display()))?
+        .decode()
+        .with_context(|| format!("failed to decode {}", source.display()))
+}
+
+/// Writes `image` scaled down to `width` pixels wide, keeping its aspect
+/// ratio, to `dest`, in the format of its extension. Images not wider than
+/// `width` are written at their own size. WebP is encoded lossily at
+/// `quality` (`0` to `100`).
+///
+/// # Errors
+///
+/// Returns an error if the format is unsupported, encoding fails, or
+/// writing fails.
+pub(crate) fn write_resized(
+    image: &DynamicImage,
+    dest: &Path,
+    width: u32,
+    quality: u8,
+) -> Result<()> {
+    let format = ImageFormat::from_path(dest)
+        .with_context(|| format!("unsupported image format for {}", dest.display()))?;
+    let resized;
+    let mut image = image;
+    if image.width() > width {
+        resized = image.resize(width, u32::MAX, FilterType::Lanczos3);
+        image = &resized;
+    }
+    if format == ImageFormat::WebP {
+        return write_output(dest, &*encode_webp(image, quality));
+    }
+
+    let mut bytes = Cursor::new(Vec::new());
+    image
+        .write_to(&mut bytes, format)
+        .with_context(|| format!("failed to encode {}", dest.display()))?;
+    write_output(dest, bytes.into_inner())
+}
+
+/// Encodes `image` as lossy WebP at `quality`.
+fn encode_webp(image: &DynamicImage, quality: u8) -> WebPMemory {
+    // libwebp only takes 8-bit RGB(A).
+    let converted = match image {
+        DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_) => None,
+        image if image.color().has_alpha() => Some(DynamicImage::ImageRgba8(image.to_rgba8())),
+        image => Some(DynamicImage::ImageRgb8(image.to_rgb8())),
+    };
+    let image = converted.as_ref().unwrap_or(image);
+    let (width, height) = (image.width(), image.height());
+    let encoder = if image.color().has_alpha() {
+        Encoder::from_rgba(image.as_bytes(), width, height)
+    } else {
+        Encoder::from_rgb(image.as_bytes(), width, height)
+    };
+    encoder.encode(f32::from(quality))
+}
+
+/// Writes the responsive `variants` of a page bundle's images (see
+/// [`crate::render::responsive`]) under `asset_output_dir`, the bundle's
+/// output directory. Each source image is decoded once, and each variant
+/// written once, however often it is listed, with lossy formats encoded at
+/// `quality`.
+///
+/// # Errors
+///
+/// Returns an error if a source image cannot be decoded or a variant cannot
+/// be written.
+pub(crate) fn write_image_variants(
+    bundle_dir: &Path,
+    asset_output_dir: &Path,
+    variants: &[ImageVariant],
+    quality: u8,
+) -> Result<()> {
+    let mut by_source: BTreeMap<&Path, BTreeSet<&ImageVariant>> = BTreeMap::new();
+    for variant in variants {
+        by_source
+            .entry(&variant.source)
+            .or_default()
+            .insert(variant);
+    }
+    for (source, variants) in by_source {
+        let image = decode_image(&bundle_dir.join(source))?;
+        for variant in variants {
+            let dest = asset_output_dir.join(&variant.path);
+            write_resized(&image, &dest, variant.width, quality)
+                .with_context(|| format!("failed to write image variant {}", dest.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, Rgb, RgbImage};
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn variant(source: &str, path: &str, width: u32) -> ImageVariant {
+        ImageVariant {
+            source: PathBuf::from(source),
+            path: PathBuf::from(path),
+            width,
+        }
+    }
+
+    // ── write_image_variants ──
+
+    #[test]
+    fn write_image_variants_resizes_and_converts() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("bundle");
+        std::fs::create_dir_all(bundle.join("assets")).unwrap();
+        RgbImage::from_pixel(200, 100, Rgb([200, 10, 10]))
+            .save(bundle.join("assets/cover.png"))
+            .unwrap();
+
+        let output = dir.path().join("public/posts/hello");
+        write_image_variants(
+            &bundle,
+            &output,
+            &[
+                variant("assets/cover.png", "assets/cover.50w.png", 50),
+                variant("assets/cover.png", "assets/cover.50w.webp", 50),
+                variant("assets/cover.png", "assets/cover.200w.webp", 200),
+            ],
+            80,
+        )
+        .unwrap();
+
+        for (path, size) in [
+            ("assets/cover.50w.png", (50, 25)),
+            ("assets/cover.50w.webp", (50, 25)),
+            ("assets/cover.200w.webp", (200, 100)),
+        ] {
+            let image = image::open(output.join(path)).unwrap();
+            assert_eq!(image.dimensions(), size, "{path}");
+        }
+        assert_eq!(
+            ImageFormat::from_path(output.join("assets/cover.200w.webp")).unwrap(),
+            image::guess_format(&std::fs::read(output.join("assets/cover.200w.webp")).unwrap())
+                .unwrap(),
+            "encoded as WebP, not copied"
+        );
+        let webp = std::fs::read(output.join("assets/cover.200w.webp")).unwrap();
+        assert_eq!(&webp[12..16], b"VP8 ", "lossy, not lossless `VP8L`");
+    }
+
+    #[test]
+    fn write_image_variants_invalid_image_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("cover.png"), "not an image").unwrap();
+
+        let err = write_image_variants(
+            dir.path(),
+            &dir.path().join("out"),
+            &[variant("cover.png", "cover.50w.png", 50)],
+            80,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("failed to decode"), "got: {err}");
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use grass::{Options, OutputStyle};
+use walkdir::WalkDir;
+
+use crate::output::write_output;
+
+/// Source directory of Sass stylesheets, in the site root and in themes.
+const SASS_DIR: &str = "sass";
+
+/// Compiles the `sass/**/*.scss` stylesheets of the theme and the site to
+/// CSS at the same path under `output_dir` (`sass/css/main.scss` →
+/// `css/main.css`). A site stylesheet replaces the theme's at the same path.
+///
+/// Partials (`_*.scss`) are only compiled where imported. Imports resolve
+/// against the importing file's directory, then the site's and the theme's
+/// `sass/` directories, so site stylesheets can build on theme partials.
+///
+/// The CSS is left expanded: `--minify` compresses it with the rest of the
+/// output.
+///
+/// # Errors
+///
+/// Returns an error if a `sass/` directory cannot be read, a stylesheet
+/// fails to compile, or writing the CSS fails.
+pub(crate) fn compile_sass(root: &Path, theme_dir: Option<&Path>, output_dir: &Path) -> Result<()> {
+    let sass_dirs: Vec<PathBuf> = [
+        Some(root.join(SASS_DIR)),
+        theme_dir.map(|td| td.join(SASS_DIR)),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|dir| dir.is_dir())
+    .collect();
+    let options = Options::default()
+        .style(OutputStyle::Expanded)
+        .load_paths(&sass_dirs);
+
+    // Theme first, so site stylesheets at the same path replace them.
+    let mut stylesheets = BTreeMap::new();
+    for dir in sass_dirs.iter().rev() {
+        for entry in WalkDir::new(dir).sort_by_file_name() {
+            let entry = entry.with_context(|| format!("failed to read {}", dir.display()))?;
+            let path = entry.path();
+            let is_stylesheet = entry.file_type().is_file()
+                && path.extension().is_some_and(|ext| ext == "scss")
+                && !entry.file_name().to_string_lossy().starts_with('_');
+            if is_stylesheet {
+                let relative = path
+                    .strip_prefix(dir)
+                    .expect("walked paths live under the sass directory");
+                stylesheets.insert(relative.with_extension("css"), path.to_owned());
+            }
+        }
+    }
+
+    for (relative, source) in stylesheets {
+        let css = grass::from_path(&source, &options)
+            .map_err(|e| anyhow!("failed to compile {}: {e}", source.display()))?;
+        let dest = output_dir.join(relative);
+        write_output(&dest, css).with_context(|| format!("failed to write {}", dest.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    // ── compile_sass ──
+
+    #[test]
+    fn compile_sass_writes_css_at_same_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_test_file(root, "sass/_colors.scss", "$accent: #c00;\n");
+        write_test_file(
+            root,
+            "sass/css/main.scss",
+            indoc! {"
+                @use 'colors';
+                a { color: colors.$accent; &:hover { color: black; } }
+            "},
+        );
+
+        let output = root.join("public");
+        compile_sass(root, None, &output).unwrap();
+
+        let css = fs::read_to_string(output.join("css/main.css")).unwrap();
+        assert!(css.contains("a {\n  color: #c00;\n}"), "css:\n{css}");
+        assert!(css.contains("a:hover {"), "css:\n{css}");
+        assert!(
+            !output.join("_colors.css").exists(),
+            "partials are not output"
+        );
+    }
+
+    #[test]
+    fn compile_sass_site_overrides_theme_and_imports_theme_partials() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let theme = root.join("themes/ignit");
+        write_test_file(&theme, "sass/_base.scss", "body { margin: 0; }\n");
+        write_test_file(&theme, "sass/main.scss", "@use 'base';\n");
+        write_test_file(&theme, "sass/print.scss", "p { color: black; }\n");
+        write_test_file(root, "sass/main.scss", "@use 'base';\nh1 { color: red; }\n");
+
+        let output = root.join("public");
+        compile_sass(root, Some(&theme), &output).unwrap();
+
+        let main = fs::read_to_string(output.join("main.css")).unwrap();
+        assert!(
+            main.contains("margin: 0") && main.contains("color: red"),
+            "css:\n{main}"
+        );
+        assert!(output.join("print.css").exists());
+    }
+
+    #[test]
+    fn compile_sass_invalid_stylesheet_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "sass/main.scss", "a { color: $undefined; }\n");
+
+        let err = compile_sass(dir.path(), None, &dir.path().join("public")).unwrap_err();
+        assert!(err.to_string().contains("failed to compile"), "got: {err}");
+    }
+
+    #[test]
+    fn compile_sass_without_sass_dir_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        compile_sass(dir.path(), None, &dir.path().join("public")).unwrap();
+        assert!(!dir.path().join("public").exists());
+    }
+}
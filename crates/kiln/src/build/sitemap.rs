@@ -4,36 +4,57 @@ use anyhow::{Context, Result};
 use jiff::Timestamp;
 
 use crate::output::write_output;
-use crate::sitemap::{self, SitemapEntry};
+use crate::sitemap::{self, SitemapAlternate, SitemapEntry};
+use crate::template::vars::Translation;
 
 use super::BuildContext;
 use super::listing::ListedPage;
 
 /// Generates `sitemap.xml` and `robots.txt` in the output directory.
+///
+/// `translations` holds each page's translations, index-aligned with
+/// `listed_pages`.
 pub(crate) fn build_sitemap_and_robots(
     ctx: &BuildContext,
     listed_pages: &[ListedPage],
+    translations: &[Vec<Translation>],
     output_dir: &Path,
 ) -> Result<()> {
-    build_sitemap(ctx, listed_pages, output_dir)?;
+    build_sitemap(ctx, listed_pages, translations, output_dir)?;
     build_robots_txt(ctx, output_dir)
 }
 
 // ── Sitemap ──
 
-fn build_sitemap(ctx: &BuildContext, listed_pages: &[ListedPage], output_dir: &Path) -> Result<()> {
+fn build_sitemap(
+    ctx: &BuildContext,
+    listed_pages: &[ListedPage],
+    translations: &[Vec<Translation>],
+    output_dir: &Path,
+) -> Result<()> {
     let base = ctx.config.base_url.trim_end_matches('/');
     let mut entries = Vec::with_capacity(listed_pages.len() + 1);
 
     entries.push(SitemapEntry {
         loc: format!("{base}/"),
         lastmod: None,
+        alternates: Vec::new(),
     });
 
-    for lp in listed_pages {
+    for (lp, translations) in listed_pages.iter().zip(translations) {
+        if lp.noindex {
+            continue;
+        }
         entries.push(SitemapEntry {
             loc: lp.summary.url.clone(),
             lastmod: lp.timestamp.map(format_iso_date),
+            alternates: translations
+                .iter()
+                .map(|translation| SitemapAlternate {
+                    hreflang: translation.language.clone(),
+                    href: translation.url.clone(),
+                })
+                .collect(),
         });
     }
 
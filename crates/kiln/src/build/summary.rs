@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::config::Images;
+use crate::content::discovery::ContentSet;
+use crate::content::page::Page;
+use crate::render::link::ContentLinks;
+use crate::render::pipeline::render_page;
+use crate::xml::absolutize_urls;
+
+use super::url::page_url;
+use super::{BuildContext, page_render_options};
+
+/// Renders the summary of `page` (see [`Page::summary_source`]) through the
+/// markdown pipeline, without a table of contents or responsive images.
+///
+/// Links are made absolute against the page's `url`, so the summary works
+/// unchanged on list pages. Empty when the page has no summary.
+///
+/// # Errors
+///
+/// Returns an error if snippet expansion or rendering fails.
+pub(super) fn render_summary(
+    ctx: &BuildContext,
+    page: &Page,
+    url: &str,
+    content_links: &Arc<ContentLinks>,
+) -> Result<String> {
+    let Some(source) = page.summary_source(ctx.config.summary.words) else {
+        return Ok(String::new());
+    };
+    let mut options = page_render_options(ctx, page, content_links)?;
+    options.toc_levels = None;
+    options.images = Images::default();
+    // An automatic summary can end inside a directive; the page's own
+    // render checks the full content.
+    options.strict = false;
+    let content = ctx.snippets.expand(&source)?;
+    let rendered = ctx.template_engine.with_bundle_dir(page.bundle_dir(), || {
+        render_page(
+            &content,
+            &ctx.syntax_set,
+            &ctx.template_engine,
+            &options,
+            page.source_path.parent(),
+        )
+    })?;
+    Ok(absolutize_urls(&rendered.content_html, url))
+}
+
+/// Renders the summary of each page of `content`, in order, once for both
+/// the listings and the page itself.
+///
+/// A failed summary is kept as its error, for building the page to report;
+/// listings show the page without one.
+pub(super) fn render_summaries(
+    ctx: &BuildContext,
+    content: &ContentSet,
+    content_links: &Arc<ContentLinks>,
+) -> Vec<Result<String>> {
+    content
+        .pages
+        .iter()
+        .map(|page| {
+            page.output_path(&content.content_dir)
+                .and_then(|output_path| {
+                    let url = page_url(&ctx.config.base_url, &output_path);
+                    render_summary(ctx, page, &url, content_links)
+                })
+                .with_context(|| {
+                    format!("failed to render summary of {}", page.source_path.display())
+                })
+        })
+        .collect()
+}
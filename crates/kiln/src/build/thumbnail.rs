@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::resize::{decode_image, write_resized};
+use crate::content::page::Page;
+use crate::output::copy_file;
+use crate::render::responsive::bundle_image;
+
+/// Image formats thumbnails are generated for, by extension.
+const THUMBNAIL_EXTENSIONS: [&str; 5] = ["gif", "jpeg", "jpg", "png", "webp"];
+
+/// A featured image's bundle asset and the thumbnail generated from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Thumbnail {
+    /// The full-size image in the page bundle.
+    pub(crate) source: PathBuf,
+    /// Thumbnail path relative to the page, like the `featured_image` it
+    /// comes from (`assets/cover.webp` → `assets/cover.thumb.webp`).
+    pub(crate) src: String,
+}
+
+/// Returns the thumbnail of `page`'s featured image, or `None` unless it
+/// is a relative path to a supported image among the bundle's assets, and
+/// thumbnails are enabled (`width` above `0`).
+#[must_use]
+pub(crate) fn page_thumbnail(page: &Page, width: u32) -> Option<Thumbnail> {
+    if width == 0 {
+        return None;
+    }
+    let src = &page.frontmatter.featured_image.as_ref()?.src;
+    let image = bundle_image(src, &THUMBNAIL_EXTENSIONS)?;
+    let source = page.source_path.parent()?.join(&image.path);
+    if !page.assets.contains(&source) {
+        return None;
+    }
+    Some(Thumbnail {
+        source,
+        src: format!("{}.thumb.{}", image.stem, image.ext),
+    })
+}
+
+/// Writes a copy of the image at `source` scaled down to `width` pixels
+/// wide, keeping its aspect ratio, to `dest`. Images already narrower are
+/// copied as-is. Lossy formats are encoded at `quality`.
+///
+/// # Errors
+///
+/// Returns an error if the image cannot be decoded or encoded, or writing
+/// fails.
+pub(crate) fn write_thumbnail(source: &Path, dest: &Path, width: u32, quality: u8) -> Result<()> {
+    let image = decode_image(source)?;
+    if image.width() <= width {
+        return copy_file(source, dest);
+    }
+    write_resized(&image, dest, width, quality)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, RgbImage};
+
+    use super::*;
+    use crate::content::frontmatter::FeaturedImage;
+    use crate::test_utils::test_page;
+
+    fn bundle_page(featured_image: &str, assets: &[&str]) -> Page {
+        let mut page = test_page("hello");
+        page.source_path = PathBuf::from("/site/content/posts/hello/index.md");
+        page.assets = assets
+            .iter()
+            .map(|asset| Path::new("/site/content/posts/hello").join(asset))
+            .collect();
+        page.frontmatter.featured_image = Some(FeaturedImage {
+            src: featured_image.into(),
+            ..FeaturedImage::default()
+        });
+        page
+    }
+
+    // ── page_thumbnail ──
+
+    #[test]
+    fn page_thumbnail_for_bundle_asset() {
+        let page = bundle_page("./assets/cover.webp", &["assets/cover.webp"]);
+        assert_eq!(
+            page_thumbnail(&page, 480),
+            Some(Thumbnail {
+                source: PathBuf::from("/site/content/posts/hello/assets/cover.webp"),
+                src: "./assets/cover.thumb.webp".into(),
+            })
+        );
+        assert_eq!(page_thumbnail(&page, 0), None, "disabled");
+    }
+
+    #[test]
+    fn page_thumbnail_skips_other_images() {
+        for (src, assets) in [
+            ("/images/cover.webp", &["cover.webp"][..]),
+            ("https://cdn.example.com/cover.webp", &["cover.webp"]),
+            ("../other/cover.webp", &[]),
+            ("missing.png", &["cover.png"]),
+            ("diagram.svg", &["diagram.svg"]),
+        ] {
+            let page = bundle_page(src, assets);
+            assert_eq!(page_thumbnail(&page, 480), None, "{src}");
+        }
+    }
+
+    // ── write_thumbnail ──
+
+    #[test]
+    fn write_thumbnail_scales_down_to_width() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("cover.png");
+        RgbImage::new(200, 100).save(&source).unwrap();
+
+        let dest = dir.path().join("out/cover.thumb.png");
+        write_thumbnail(&source, &dest, 50, 80).unwrap();
+        assert_eq!(image::open(&dest).unwrap().dimensions(), (50, 25));
+
+        let dest = dir.path().join("out/cover.copy.png");
+        write_thumbnail(&source, &dest, 480, 80).unwrap();
+        assert_eq!(
+            std::fs::read(&dest).unwrap(),
+            std::fs::read(&source).unwrap(),
+            "narrower images are copied as-is"
+        );
+    }
+
+    #[test]
+    fn write_thumbnail_invalid_image_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("cover.png");
+        std::fs::write(&source, "not an image").unwrap();
+
+        let err =
+            write_thumbnail(&source, &dir.path().join("cover.thumb.png"), 50, 80).unwrap_err();
+        assert!(err.to_string().contains("failed to decode"), "got: {err}");
+    }
+}
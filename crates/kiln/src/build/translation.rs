@@ -0,0 +1,145 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Result, bail};
+
+use crate::content::page::Page;
+use crate::template::vars::Translation;
+
+use super::listing::ListedPage;
+
+/// Links the translations of every page, index-aligned with `pages` (and
+/// with `listed_pages`, which is built from the same slice).
+///
+/// Pages sharing a `translation_key` are translations of each other: each
+/// gets the whole group, itself included, sorted by language. Pages without
+/// a key, or alone with theirs, get none.
+///
+/// # Errors
+///
+/// Returns an error if two pages of a group have the same language.
+pub(crate) fn build_translations(
+    pages: &[Page],
+    listed_pages: &[ListedPage],
+    default_language: &str,
+) -> Result<Vec<Vec<Translation>>> {
+    let mut groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (idx, page) in pages.iter().enumerate() {
+        if let Some(key) = page.frontmatter.translation_key.as_deref() {
+            groups.entry(key).or_default().push(idx);
+        }
+    }
+
+    let mut translations = vec![Vec::new(); pages.len()];
+    for (key, members) in groups {
+        if members.len() < 2 {
+            continue;
+        }
+        let mut languages: HashMap<&str, usize> = HashMap::new();
+        let mut group = Vec::with_capacity(members.len());
+        for &idx in &members {
+            let language = page_language(&pages[idx], default_language);
+            if let Some(&other) = languages.get(language) {
+                bail!(
+                    "{} and {} both translate `{key}` into `{language}`",
+                    pages[other].source_path.display(),
+                    pages[idx].source_path.display()
+                );
+            }
+            languages.insert(language, idx);
+            group.push(Translation {
+                language: language.to_owned(),
+                url: listed_pages[idx].summary.url.clone(),
+            });
+        }
+        group.sort_by(|a, b| a.language.cmp(&b.language));
+        for idx in members {
+            translations[idx].clone_from(&group);
+        }
+    }
+    Ok(translations)
+}
+
+/// Returns the language of `page`: its frontmatter `language`, else
+/// `default_language`.
+pub(crate) fn page_language<'a>(page: &'a Page, default_language: &'a str) -> &'a str {
+    page.frontmatter
+        .language
+        .as_deref()
+        .unwrap_or(default_language)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::build::listing::build_listing_artifacts;
+    use crate::config::SlugifyMode;
+    use crate::test_utils::test_page;
+
+    fn page(slug: &str, key: Option<&str>, language: Option<&str>) -> Page {
+        let mut page = test_page(slug);
+        page.source_path = PathBuf::from(format!("/site/content/{slug}/index.md"));
+        page.frontmatter.translation_key = key.map(String::from);
+        page.frontmatter.language = language.map(String::from);
+        page
+    }
+
+    fn translations(pages: &[Page]) -> Result<Vec<Vec<Translation>>> {
+        let artifacts = build_listing_artifacts(
+            pages,
+            &vec![""; pages.len()],
+            Path::new("/site/content"),
+            "https://example.com",
+            None,
+            &HashMap::new(),
+            0,
+            SlugifyMode::Safe,
+        )
+        .unwrap();
+        build_translations(pages, &artifacts.listed_pages, "en")
+    }
+
+    // ── build_translations ──
+
+    #[test]
+    fn build_translations_links_pages_sharing_a_key() {
+        let pages = vec![
+            page("hello", Some("hello"), None),
+            page("ni-hao", Some("hello"), Some("zh")),
+            page("hallo", Some("hello"), Some("de")),
+            page("lonely", Some("lonely"), Some("fr")),
+            page("about", None, None),
+        ];
+        let translations = translations(&pages).unwrap();
+
+        let expected = [
+            ("de", "https://example.com/hallo/"),
+            ("en", "https://example.com/hello/"),
+            ("zh", "https://example.com/ni-hao/"),
+        ]
+        .map(|(language, url)| Translation {
+            language: language.into(),
+            url: url.into(),
+        });
+        assert_eq!(translations[0], expected);
+        assert_eq!(translations[1], expected);
+        assert_eq!(translations[2], expected);
+        assert!(translations[3].is_empty(), "{:?}", translations[3]);
+        assert!(translations[4].is_empty(), "{:?}", translations[4]);
+    }
+
+    #[test]
+    fn build_translations_duplicate_language_returns_error() {
+        let pages = vec![
+            page("hello", Some("hello"), None),
+            page("hi", Some("hello"), Some("en")),
+        ];
+        let err = translations(&pages).unwrap_err().to_string();
+        assert!(
+            err.contains("both translate `hello` into `en`"),
+            "got: {err}"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::content::discovery::{ContentSet, discover_content};
+use crate::html::decode_href;
+
+/// Matches `href` / `src` attribute values.
+static LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(?:href|src)="([^"]*)""#).expect("link regex should compile"));
+
+/// Matches anchor targets: `id` and `name` attribute values.
+static ANCHOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\b(?:id|name)="([^"]*)""#).expect("anchor regex should compile")
+});
+
+/// Broken internal links found in a built site.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LinkReport {
+    /// The output directory checked.
+    pub output_dir: PathBuf,
+    /// HTML files scanned.
+    pub files_checked: usize,
+    /// Internal links verified across all files.
+    pub links_checked: usize,
+    pub issues: Vec<LinkIssue>,
+}
+
+/// A single broken link, located in the output file that contains it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkIssue {
+    /// Output file, relative to the output directory.
+    pub file: PathBuf,
+    /// Content file the output was rendered from, relative to the site root,
+    /// when the file is a content page.
+    pub source: Option<PathBuf>,
+    /// 1-based line of the link in `file`.
+    pub line: usize,
+    /// The link as written in the attribute.
+    pub target: String,
+    pub kind: LinkIssueKind,
+}
+
+impl LinkIssue {
+    /// Describes what is wrong with the link, without its location.
+    #[must_use]
+    pub fn problem(&self) -> String {
+        match &self.kind {
+            LinkIssueKind::MissingTarget => format!("`{}` not found", self.target),
+            LinkIssueKind::MissingAnchor(anchor) => {
+                format!("`{}` has no anchor `#{anchor}`", self.target)
+            }
+        }
+    }
+}
+
+/// Error for a link check that found broken links, carrying the report so
+/// each link can be reported on its own (see [`crate::diagnostic`]).
+#[derive(Debug)]
+pub struct BrokenLinks {
+    /// Site root the issues' source files are relative to.
+    pub root: PathBuf,
+    pub report: LinkReport,
+}
+
+/// Why a link is broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkIssueKind {
+    /// No output file exists at the link's path.
+    MissingTarget,
+    /// The target page has no element with this `id` / `name`.
+    MissingAnchor(String),
+}
+
+/// Checks the internal links of the site already built at `root`'s output
+/// directory.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded, the site has not been
+/// built, or the output cannot be read.
+pub fn check(root: &Path) -> Result<LinkReport> {
+    let config = Config::load(root).context("failed to load config")?;
+    let output_dir = config.resolved_output_dir(root)?;
+    if !output_dir.is_dir() {
+        bail!(
+            "output directory {} does not exist; run `kiln build` first",
+            output_dir.display()
+        );
+    }
+    let content = discover_content(root, config.slugify)?;
+    let sources = page_sources(root, &content)?;
+    check_output(&output_dir, &config.base_url, &sources)
+}
+
+/// Maps each content page's output file (relative to the output directory)
+/// to its source file (relative to `root`).
+///
+/// # Errors
+///
+/// Returns an error if a page's output path cannot be computed.
+pub fn page_sources(root: &Path, content: &ContentSet) -> Result<BTreeMap<PathBuf, PathBuf>> {
+    content
+        .pages
+        .iter()
+        .map(|page| {
+            let output = page.output_path(&content.content_dir)?;
+            let source = page
+                .source_path
+                .strip_prefix(root)
+                .unwrap_or(&page.source_path);
+            Ok((output, source.to_owned()))
+        })
+        .collect()
+}
+
+/// Verifies that every internal `href` / `src` in the HTML files under
+/// `output_dir` points to an existing output file, and that `#fragment`s
+/// match an `id` or `name` on the target page.
+///
+/// Links are internal when root-relative, relative, fragment-only, or
+/// prefixed with `base_url`; other absolute URLs are not checked. `sources`
+/// maps output files to their content source for reporting (see
+/// [`page_sources`]).
+///
+/// # Errors
+///
+/// Returns an error if walking the directory or reading a file fails.
+pub fn check_output(
+    output_dir: &Path,
+    base_url: &str,
+    sources: &BTreeMap<PathBuf, PathBuf>,
+) -> Result<LinkReport> {
+    let mut files = HashSet::new();
+    let mut pages = BTreeMap::new();
+    for entry in WalkDir::new(output_dir).follow_links(false) {
+        let entry = entry.with_context(|| format!("failed to walk {}", output_dir.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(output_dir)
+            .context("walked entry outside the output directory")?
+            .to_owned();
+        if relative.extension().is_some_and(|ext| ext == "html") {
+            let html = fs::read_to_string(entry.path())
+                .with_context(|| format!("failed to read {}", entry.path().display()))?;
+            pages.insert(relative.clone(), html);
+        }
+        files.insert(relative);
+    }
+
+    let anchors: HashMap<&Path, HashSet<String>> = pages
+        .iter()
+        .map(|(path, html)| {
+            let ids = ANCHOR_RE
+                .captures_iter(html)
+                .map(|caps| decode_href(&caps[1]))
+                .collect();
+            (path.as_path(), ids)
+        })
+        .collect();
+
+    let site = Site {
+        files: &files,
+        anchors: &anchors,
+        base_url: base_url.trim_end_matches('/'),
+    };
+    let mut report = LinkReport {
+        output_dir: output_dir.to_owned(),
+        files_checked: pages.len(),
+        ..LinkReport::default()
+    };
+    for (file, html) in &pages {
+        for value in LINK_RE.captures_iter(html).filter_map(|caps| caps.get(1)) {
+            let Some(kind) = site.check_link(file, value.as_str(), &mut report.links_checked)
+            else {
+                continue;
+            };
+            report.issues.push(LinkIssue {
+                file: file.clone(),
+                source: sources.get(file).cloned(),
+                line: html[..value.start()].matches('\n').count() + 1,
+                target: value.as_str().to_owned(),
+                kind,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// The output files of a built site, with the anchors of each HTML page.
+struct Site<'a> {
+    files: &'a HashSet<PathBuf>,
+    anchors: &'a HashMap<&'a Path, HashSet<String>>,
+    base_url: &'a str,
+}
+
+impl Site<'_> {
+    /// Checks one link found in `file`, counting it in `checked` when it is
+    /// internal. Returns the problem, if any.
+    fn check_link(&self, file: &Path, href: &str, checked: &mut usize) -> Option<LinkIssueKind> {
+        let href = decode_href(href);
+        let (path, fragment) = href.split_once('#').unwrap_or((&href, ""));
+        let path = path.split('?').next().unwrap_or_default();
+
+        let target = if path.is_empty() {
+            if fragment.is_empty() {
+                return None;
+            }
+            file.to_owned()
+        } else {
+            let url_path = self.internal_path(path)?;
+            let joined = if url_path.starts_with('/') {
+                PathBuf::from(url_path)
+            } else {
+                Path::new("/")
+                    .join(file.parent().unwrap_or(Path::new("")))
+                    .join(url_path)
+            };
+            *checked += 1;
+            match self.resolve_file(&joined, url_path.ends_with('/')) {
+                Some(target) => target,
+                None => return Some(LinkIssueKind::MissingTarget),
+            }
+        };
+        if path.is_empty() {
+            *checked += 1;
+        }
+
+        let anchors = self.anchors.get(target.as_path())?;
+        (!fragment.is_empty() && fragment != "top" && !anchors.contains(fragment))
+            .then(|| LinkIssueKind::MissingAnchor(fragment.to_owned()))
+    }
+
+    /// Returns the path part of an internal link, or `None` for external
+    /// URLs and non-HTTP schemes.
+    fn internal_path<'p>(&self, path: &'p str) -> Option<&'p str> {
+        if !self.base_url.is_empty()
+            && let Some(rest) = path.strip_prefix(self.base_url)
+        {
+            return (rest.is_empty() || rest.starts_with('/')).then_some(rest);
+        }
+        let has_scheme = path
+            .find(':')
+            .is_some_and(|colon| !path[..colon].contains('/'));
+        (!has_scheme && !path.starts_with("//")).then_some(path)
+    }
+
+    /// Maps a root-relative URL path to an output file: the file itself, or
+    /// the `index.html` of a directory.
+    fn resolve_file(&self, url_path: &Path, is_dir: bool) -> Option<PathBuf> {
+        let mut relative = PathBuf::new();
+        for component in url_path.components() {
+            match component {
+                Component::Normal(part) => relative.push(part),
+                Component::ParentDir => {
+                    relative.pop();
+                }
+                _ => {}
+            }
+        }
+        let index = relative.join("index.html");
+        if !is_dir && self.files.contains(&relative) {
+            Some(relative)
+        } else {
+            self.files.contains(&index).then_some(index)
+        }
+    }
+}
+
+impl fmt::Display for LinkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+        let sources: BTreeSet<_> = self.issues.iter().map(|issue| &issue.file).collect();
+        writeln!(
+            f,
+            "Checked {} link(s) in {} file(s): {} broken in {} file(s).",
+            self.links_checked,
+            self.files_checked,
+            self.issues.len(),
+            sources.len()
+        )
+    }
+}
+
+impl fmt::Display for BrokenLinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "found {} broken link(s)", self.report.issues.len())
+    }
+}
+
+impl std::error::Error for BrokenLinks {}
+
+impl fmt::Display for LinkIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)?;
+        if let Some(source) = &self.source {
+            write!(f, " (from {})", source.display())?;
+        }
+        write!(f, ": {}", self.problem())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn write_site(root: &Path) {
+        write_test_file(
+            root,
+            "posts/hello/index.html",
+            indoc! {r##"
+                <link rel="canonical" href="https://example.com/posts/hello/">
+                <h2 id="intro">Intro</h2>
+                <a href="#intro">ok</a>
+                <a href="#missing">bad anchor</a>
+                <a href="../world/">ok</a>
+                <a href="/posts/world/#setup">ok</a>
+                <a href="/posts/world/#nope">bad anchor</a>
+                <img src="cover.png">
+                <img src="gone.png">
+                <a href="https://example.com/about/">bad page</a>
+                <a href="https://other.example/x">external</a>
+                <a href="mailto:me@example.com">mail</a>
+                <a href="/tags/caf%C3%A9/">ok</a>
+            "##},
+        );
+        write_test_file(root, "posts/hello/cover.png", "png");
+        write_test_file(
+            root,
+            "posts/world/index.html",
+            r#"<h2 id="setup">Setup</h2>"#,
+        );
+        write_test_file(root, "tags/café/index.html", "");
+    }
+
+    // ── check_output ──
+
+    #[test]
+    fn check_output_reports_missing_targets_and_anchors() {
+        let dir = tempfile::tempdir().unwrap();
+        write_site(dir.path());
+        let sources = BTreeMap::from([(
+            PathBuf::from("posts/hello/index.html"),
+            PathBuf::from("content/posts/hello/index.md"),
+        )]);
+
+        let report = check_output(dir.path(), "https://example.com/", &sources).unwrap();
+        let issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.line, issue.target.as_str(), issue.kind.clone()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (
+                    4,
+                    "#missing",
+                    LinkIssueKind::MissingAnchor("missing".into())
+                ),
+                (
+                    7,
+                    "/posts/world/#nope",
+                    LinkIssueKind::MissingAnchor("nope".into())
+                ),
+                (9, "gone.png", LinkIssueKind::MissingTarget),
+                (
+                    10,
+                    "https://example.com/about/",
+                    LinkIssueKind::MissingTarget
+                ),
+            ]
+        );
+        assert_eq!(report.files_checked, 3);
+        assert_eq!(report.links_checked, 10);
+        assert_eq!(
+            report.issues[0].to_string(),
+            "posts/hello/index.html:4 (from content/posts/hello/index.md): `#missing` has no anchor `#missing`"
+        );
+    }
+
+    #[test]
+    fn check_output_clean_site_has_no_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "index.html", r#"<a href="posts/">Posts</a>"#);
+        write_test_file(dir.path(), "posts/index.html", r#"<a href="/">Home</a>"#);
+
+        let report = check_output(dir.path(), "", &BTreeMap::new()).unwrap();
+        assert!(report.issues.is_empty(), "issues: {:?}", report.issues);
+        assert_eq!(
+            report.to_string(),
+            "Checked 2 link(s) in 2 file(s): 0 broken in 0 file(s).\n"
+        );
+    }
+
+    // ── check ──
+
+    #[test]
+    fn check_without_build_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config.toml"), "").unwrap();
+        let err = check(dir.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("run `kiln build` first"),
+            "got: {err}"
+        );
+    }
+}
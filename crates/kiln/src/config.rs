@@ -1,12 +1,16 @@
+use std::collections::BTreeMap;
 use std::fs;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 use jiff::tz::TimeZone;
 use serde::{Deserialize, Serialize};
 
+mod env;
+
 /// Site-wide configuration loaded from `config.toml`.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
     #[serde(default = "default_base_url")]
     pub base_url: String,
@@ -30,6 +34,18 @@ pub struct Config {
     #[serde(default = "default_output_dir")]
     pub output_dir: String,
 
+    /// Fails the build on problems that otherwise only degrade the output:
+    /// unrecognized code block languages, unclosed directives, images
+    /// without alt text, broken internal links, and undefined template
+    /// variables. Also set by `kiln build --strict`.
+    #[serde(default)]
+    pub strict: bool,
+
+    /// Dates pages without a frontmatter `updated` by the last git commit
+    /// touching their source file.
+    #[serde(default)]
+    pub lastmod_from_git: bool,
+
     /// Theme name, resolved to `themes/<name>/` under the site root.
     #[serde(default)]
     pub theme: Option<String>,
@@ -39,63 +55,936 @@ pub struct Config {
     #[serde(default)]
     pub params: toml::Table,
 
+    #[serde(default)]
+    pub content: ContentConfig,
+
+    /// Short names for frequently cited URLs, usable in markdown as
+    /// `[text](link:name)`.
+    #[serde(default)]
+    pub links: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub external_links: ExternalLinksConfig,
+
+    #[serde(default)]
+    pub markup: Markup,
+
+    #[serde(default)]
+    pub markdown: Markdown,
+
+    #[serde(default)]
+    pub slugify: Slugify,
+
+    #[serde(default)]
+    pub toc: Toc,
+
     #[serde(default)]
     pub search: Search,
 
-    #[serde(default)]
-    pub menu: Menu,
+    #[serde(default)]
+    pub drafts: Drafts,
+
+    #[serde(default)]
+    pub related: Related,
+
+    #[serde(default)]
+    pub archive: Archive,
+
+    #[serde(default)]
+    pub summary: Summary,
+
+    #[serde(default)]
+    pub thumbnails: Thumbnails,
+
+    #[serde(default)]
+    pub images: Images,
+
+    #[serde(default)]
+    pub og_image: OgImage,
+
+    #[serde(default)]
+    pub redirects: Redirects,
+
+    #[serde(default)]
+    pub headers: Headers,
+
+    #[serde(default)]
+    pub github_pages: GithubPages,
+
+    #[serde(default)]
+    pub compress: Compress,
+
+    #[serde(default)]
+    pub csp: Csp,
+
+    #[serde(default)]
+    pub deploy: Option<Deploy>,
+
+    /// Turns the RSS feeds into podcast feeds, with iTunes channel tags.
+    #[serde(default)]
+    pub podcast: Option<Podcast>,
+
+    /// Comment threads under pages, rendered by the built-in
+    /// `comments.html` partial.
+    #[serde(default)]
+    pub comments: Option<Comments>,
+
+    /// Visitor analytics script added to the `<head>` of every page.
+    #[serde(default)]
+    pub analytics: Option<Analytics>,
+
+    #[serde(default)]
+    pub cache: Cache,
+
+    #[serde(default)]
+    pub assets: Assets,
+
+    #[serde(default)]
+    pub hooks: Hooks,
+
+    #[serde(default)]
+    pub templates: Templates,
+
+    #[serde(default)]
+    pub microformats: Microformats,
+
+    #[serde(default)]
+    pub menu: Menu,
+
+    #[serde(default)]
+    pub author: Author,
+
+    /// Authors pages credit with frontmatter `authors`, keyed by ID.
+    #[serde(default)]
+    pub authors: BTreeMap<String, Author>,
+
+    /// Generates a `/authors/<id>/` listing page and feed for each author
+    /// credited by a page, and an `/authors/` overview, like tags.
+    #[serde(default)]
+    pub author_pages: bool,
+}
+
+/// Theme metadata loaded from `themes/<name>/theme.toml`.
+#[derive(Debug, Clone, Deserialize)]
+struct ThemeMeta {
+    #[serde(default)]
+    min_kiln_version: Option<String>,
+
+    #[serde(default)]
+    params: toml::Table,
+}
+
+/// The site `[author]`, or an `[authors.<id>]` entry.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Author {
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub email: String,
+
+    #[serde(default)]
+    pub link: String,
+
+    /// Avatar image URL.
+    #[serde(default)]
+    pub avatar: String,
+}
+
+/// Content directory handling beyond markdown pages.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ContentConfig {
+    /// File extensions (e.g., `"pdf"`, `"html"`) copied verbatim from
+    /// `content/` to the matching output path. Files inside page bundles are
+    /// already copied as bundle assets and are not affected.
+    #[serde(default)]
+    pub passthrough: Vec<String>,
+}
+
+/// Markdown syntax extensions beyond `CommonMark`, all enabled by default,
+/// and raw HTML handling. Disabling all extensions parses content as strict
+/// `CommonMark`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent syntax extensions, each toggled by its own config key"
+)]
+pub struct Markdown {
+    /// GitHub-style pipe tables.
+    #[serde(default = "default_true")]
+    pub tables: bool,
+
+    /// `[^label]` footnote references and definitions.
+    #[serde(default = "default_true")]
+    pub footnotes: bool,
+
+    /// `- [ ]` / `- [x]` task list items.
+    #[serde(default = "default_true")]
+    pub tasklists: bool,
+
+    /// `~~deleted~~` text.
+    #[serde(default = "default_true")]
+    pub strikethrough: bool,
+
+    /// `$inline$` and `$$display$$` math.
+    #[serde(default = "default_true")]
+    pub math: bool,
+
+    /// `{#id .class}` attributes after heading text.
+    #[serde(default = "default_true")]
+    pub heading_attributes: bool,
+
+    /// Definition lists: a term line followed by `: definition` lines.
+    #[serde(default)]
+    pub definition_lists: bool,
+
+    /// `^superscript^` text.
+    #[serde(default)]
+    pub superscript: bool,
+
+    /// `~subscript~` text. Strikethrough then needs a double `~~`.
+    #[serde(default)]
+    pub subscript: bool,
+
+    /// `==highlighted==` text, rendered as `<mark>`.
+    #[serde(default)]
+    pub mark: bool,
+
+    /// `*[HTML]: HyperText Markup Language` definitions, wrapping each
+    /// occurrence on the page in `<abbr>`.
+    #[serde(default = "default_true")]
+    pub abbreviations: bool,
+
+    /// Cleans raw HTML in content against an allowlist, for sites that
+    /// render untrusted markdown; see [`sanitize_html`].
+    ///
+    /// [`sanitize_html`]: crate::render::sanitize::sanitize_html
+    #[serde(default)]
+    pub sanitize: bool,
+}
+
+impl Default for Markdown {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            footnotes: true,
+            tasklists: true,
+            strikethrough: true,
+            math: true,
+            heading_attributes: true,
+            definition_lists: false,
+            superscript: false,
+            subscript: false,
+            mark: false,
+            abbreviations: true,
+            sanitize: false,
+        }
+    }
+}
+
+/// Markdown rendering settings.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Markup {
+    #[serde(default)]
+    pub heading_ids: HeadingIds,
+
+    /// Casing applied to page titles and headings.
+    #[serde(default)]
+    pub title_case: TitleCase,
+
+    /// Words kept exactly as written by `title_case` (e.g., `"iOS"`,
+    /// `"Rust"`), matched case-insensitively.
+    #[serde(default)]
+    pub title_case_exceptions: Vec<String>,
+
+    /// Class names given to highlighted code tokens.
+    #[serde(default)]
+    pub highlight_classes: HighlightClasses,
+
+    /// Prefix of `chroma` class names (e.g., `"hl-"` for `hl-k`).
+    #[serde(default)]
+    pub highlight_class_prefix: String,
+}
+
+/// Which headings receive an `id` attribute (and so a table of contents
+/// entry).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeadingIds {
+    /// Every heading: explicit `{#id}`s, otherwise slugified from the text.
+    #[default]
+    Auto,
+    /// Only headings with an explicit `{#id}`.
+    ExplicitOnly,
+    /// No heading, not even those with an explicit `{#id}`.
+    None,
+}
+
+/// Slug generation settings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Slugify {
+    /// How heading IDs, taxonomy term slugs, and page slugs derived from
+    /// file names are generated. Unset, page slugs keep the file name as
+    /// written, as they did before `[slugify]` existed.
+    #[serde(default)]
+    pub mode: Option<SlugifyMode>,
+}
+
+impl Slugify {
+    /// The mode heading IDs and taxonomy term slugs are generated under.
+    #[must_use]
+    pub fn mode(self) -> SlugifyMode {
+        self.mode.unwrap_or_default()
+    }
+}
+
+/// How text is turned into a slug.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SlugifyMode {
+    /// Lowercased letters and digits of any script (`1-1-café-测试`).
+    #[default]
+    Safe,
+    /// Lowercased ASCII letters and digits only: accents are stripped and
+    /// other characters dropped (`1-1-cafe`).
+    Ascii,
+    /// Text kept as written, except for whitespace and characters that are
+    /// unsafe in a URL (`1.1-Café-测试`).
+    Off,
+    /// GitHub / Gitea heading anchors, so fragment links work both in a
+    /// repository README and on the site (`11-café---测试`).
+    Github,
+}
+
+/// Casing style for page titles and headings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TitleCase {
+    /// Titles are used as written.
+    #[default]
+    None,
+    /// Every word capitalized except minor words (`The Art of Rust`).
+    Title,
+    /// Only the first word of each clause capitalized (`The art of rust`).
+    Sentence,
+}
+
+/// Class names given to highlighted code tokens.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HighlightClasses {
+    /// syntect scope names (`keyword control rust`), styled by a
+    /// `kiln highlight-css` stylesheet.
+    #[default]
+    Syntect,
+    /// Chroma / Pygments short names (`k`, `s2`, `c1`), so stylesheets from
+    /// Hugo themes work unmodified.
+    Chroma,
+}
+
+/// Table of contents settings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Toc {
+    /// Shallowest heading level listed (1 for `<h1>`).
+    #[serde(default = "default_toc_min_level")]
+    pub min_level: u8,
+
+    /// Deepest heading level listed (6 for `<h6>`).
+    #[serde(default = "default_toc_max_level")]
+    pub max_level: u8,
+}
+
+impl Default for Toc {
+    fn default() -> Self {
+        Self {
+            min_level: default_toc_min_level(),
+            max_level: default_toc_max_level(),
+        }
+    }
+}
+
+impl Toc {
+    /// Returns the heading levels listed in the table of contents.
+    #[must_use]
+    pub fn levels(&self) -> RangeInclusive<u8> {
+        self.min_level..=self.max_level
+    }
+
+    fn validate(&self) -> Result<()> {
+        let valid = default_toc_min_level()..=default_toc_max_level();
+        if !valid.contains(&self.min_level)
+            || !valid.contains(&self.max_level)
+            || self.min_level > self.max_level
+        {
+            bail!(
+                "invalid [toc] levels {}..={}: expected 1 <= min_level <= max_level <= 6",
+                self.min_level,
+                self.max_level
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Decoration of content links that point away from the site.
+///
+/// A link is external when its host differs from the host of `base_url`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExternalLinksConfig {
+    /// Open external links in a new tab (`target="_blank"`, with
+    /// `rel="noopener noreferrer"`).
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Class added to external links (e.g., `"external"` for an icon).
+    #[serde(default)]
+    pub class: Option<String>,
+}
+
+/// Full-text search configuration.
+///
+/// When enabled, kiln runs Pagefind as a post-build step to generate a search
+/// index under `{output_dir}/pagefind/`. The `pagefind` binary must be
+/// installed separately — see <https://pagefind.app/docs/installation/>.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Search {
+    /// Enable Pagefind search indexing after build.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Path or name of the Pagefind binary (defaults to `"pagefind"` on `$PATH`).
+    #[serde(default)]
+    pub binary: Option<String>,
+}
+
+/// Environment variable read for `[drafts] secret` when the config leaves it
+/// unset, so public site repositories need not commit it.
+pub const DRAFTS_SECRET_ENV: &str = "KILN_DRAFTS_SECRET";
+
+/// Draft sharing settings.
+///
+/// When `share` is enabled, drafts are built under unguessable preview URLs
+/// (`/drafts/<slug>-<token>/`) left out of listings, feeds, and the sitemap.
+/// Tokens are derived from `secret`, so they stay stable across builds and
+/// change everywhere when the secret is rotated.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Drafts {
+    /// Build drafts under preview URLs.
+    #[serde(default)]
+    pub share: bool,
+
+    /// Key for preview URL tokens. Falls back to `$KILN_DRAFTS_SECRET`.
+    /// Never exposed to templates.
+    #[serde(default, skip_serializing)]
+    pub secret: Option<String>,
+}
+
+impl Drafts {
+    fn resolve_secret(&mut self) -> Result<()> {
+        if !self.share {
+            return Ok(());
+        }
+        if self.secret.is_none() {
+            self.secret = std::env::var(DRAFTS_SECRET_ENV).ok();
+        }
+        if self.secret.as_deref().is_none_or(str::is_empty) {
+            bail!("[drafts] share requires a secret: set `secret` or ${DRAFTS_SECRET_ENV}");
+        }
+        Ok(())
+    }
+}
+
+/// Related posts listed on each post page.
+///
+/// Every other post scores one point per shared tag, plus `section_weight`
+/// when both posts are in the same section. The `limit` highest-scoring
+/// posts with a positive score are listed, newest first among equal scores.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Related {
+    /// Maximum related posts per page; `0` disables related posts.
+    #[serde(default = "default_related_limit")]
+    pub limit: usize,
+
+    /// Points for sharing a section.
+    #[serde(default = "default_related_section_weight")]
+    pub section_weight: usize,
+}
+
+impl Default for Related {
+    fn default() -> Self {
+        Self {
+            limit: default_related_limit(),
+            section_weight: default_related_section_weight(),
+        }
+    }
+}
+
+/// Rendered page summaries (`summary_html` in templates).
+///
+/// A page's summary is its content before `<!--more-->`. Pages without the
+/// separator get their leading blocks instead, up to the one that brings
+/// them to `words` words.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Summary {
+    /// Words of an automatic summary; `0` leaves pages without a separator
+    /// without a summary.
+    #[serde(default = "default_summary_words")]
+    pub words: usize,
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Self {
+            words: default_summary_words(),
+        }
+    }
+}
+
+/// Thumbnails of featured images, for list pages and feeds.
+///
+/// A `featured_image` pointing at a JPEG, PNG, WebP, or GIF in the page
+/// bundle gets a `<name>.thumb.<ext>` variant next to it, scaled down to
+/// `width` pixels wide.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Thumbnails {
+    /// Thumbnail width in pixels; `0` disables thumbnails.
+    #[serde(default = "default_thumbnail_width")]
+    pub width: u32,
+}
+
+impl Default for Thumbnails {
+    fn default() -> Self {
+        Self {
+            width: default_thumbnail_width(),
+        }
+    }
+}
+
+/// Responsive variants of the images in page bundles.
+///
+/// A markdown image pointing at a JPEG, PNG, or WebP in the page bundle gets
+/// copies scaled down to each of `widths` (those below its own width), and
+/// converted to each of `formats`, listed in a `srcset` (and `<picture>`
+/// sources for other formats).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Images {
+    /// Widths in pixels of the scaled-down copies.
+    #[serde(default)]
+    pub widths: Vec<u32>,
+
+    /// Formats each image is also converted to.
+    #[serde(default)]
+    pub formats: Vec<ImageFormat>,
+
+    /// `sizes` attribute telling browsers how wide images are displayed.
+    #[serde(default = "default_image_sizes")]
+    pub sizes: String,
+
+    /// Quality of lossy encodings (WebP variants and thumbnails), from `0`
+    /// to `100`.
+    #[serde(default = "default_image_quality")]
+    pub quality: u8,
+}
+
+impl Default for Images {
+    fn default() -> Self {
+        Self {
+            widths: Vec::new(),
+            formats: Vec::new(),
+            sizes: default_image_sizes(),
+            quality: default_image_quality(),
+        }
+    }
+}
+
+impl Images {
+    /// Returns whether any image variants are generated.
+    #[must_use]
+    pub fn enabled(&self) -> bool {
+        !self.widths.is_empty() || !self.formats.is_empty()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.quality > 100 {
+            bail!(
+                "invalid [images] quality {}: expected 0 <= quality <= 100",
+                self.quality
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Image format bundle images can be converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImageFormat {
+    /// Lossy WebP, at the configured `quality`.
+    Webp,
+}
+
+impl ImageFormat {
+    /// File extension of the format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Webp => "webp",
+        }
+    }
+
+    /// MIME type of the format, for `<source type>`.
+    #[must_use]
+    pub const fn mime_type(self) -> &'static str {
+        match self {
+            Self::Webp => "image/webp",
+        }
+    }
+}
+
+/// Generated Open Graph images for pages without a `featured_image`.
+///
+/// Each such page gets an `og.png` card next to it, rendered from the SVG
+/// `template` and exposed to templates as `og_image`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OgImage {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// SVG template looked up in `templates/` (site, then theme), falling
+    /// back to a built-in card.
+    #[serde(default = "default_og_image_template")]
+    pub template: String,
+
+    /// Font files, relative to the site root, loaded before system fonts.
+    /// The first one backs the generic `sans-serif` and `serif` families.
+    #[serde(default)]
+    pub fonts: Vec<PathBuf>,
+
+    #[serde(default = "default_og_image_font_family")]
+    pub font_family: String,
+
+    #[serde(default = "default_og_image_background")]
+    pub background: String,
+
+    #[serde(default = "default_og_image_foreground")]
+    pub foreground: String,
+
+    /// Highlight color of the built-in card's edge and site name.
+    #[serde(default)]
+    pub accent: Option<String>,
+}
+
+impl Default for OgImage {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            template: default_og_image_template(),
+            fonts: Vec::new(),
+            font_family: default_og_image_font_family(),
+            background: default_og_image_background(),
+            foreground: default_og_image_foreground(),
+            accent: None,
+        }
+    }
+}
+
+/// Redirect output settings, covering `redirect_to` pages and `aliases`.
+///
+/// Redirect stubs with a meta refresh are always written; `netlify`
+/// additionally lists every redirect in a Netlify `_redirects` file, so the
+/// host answers with a real `301` instead.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Redirects {
+    /// Append redirect rules to `_redirects` in the output directory.
+    #[serde(default)]
+    pub netlify: bool,
+}
+
+/// Static asset handling for the `asset_url()` template function.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Assets {
+    /// Point `asset_url()` at copies of the assets named after a hash of
+    /// their contents, so they can be cached for good.
+    #[serde(default)]
+    pub fingerprint: bool,
+}
+
+/// iTunes metadata of a podcast feed. Episodes are pages with frontmatter
+/// `audio`.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Podcast {
+    /// Apple Podcasts category (e.g., `"Technology"`).
+    pub category: String,
+
+    /// Subcategory within `category` (e.g., `"Software How-To"`).
+    #[serde(default)]
+    pub subcategory: Option<String>,
+
+    /// Cover artwork URL, a square image of 1400 to 3000 pixels. Relative
+    /// URLs resolve against `base_url`.
+    #[serde(default)]
+    pub image: Option<String>,
+
+    /// Marks the podcast as containing explicit content.
+    #[serde(default)]
+    pub explicit: bool,
+}
+
+/// Microformats2 markup of pages, read by webmention and other social
+/// web tools.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Microformats {
+    /// Wrap each page's rendered `content` in an `h-entry` with its
+    /// properties and an `e-content`, for themes without microformats.
+    #[serde(default)]
+    pub wrap_content: bool,
+}
+
+/// Template rendering settings.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Templates {
+    /// Fail the build on undefined template variables, as `strict` does,
+    /// without its other checks.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Build cache, kept in `.kiln-cache/` under the site root.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Cache {
+    /// Reuse the output of directive templates across builds while their
+    /// context, their template, and the files they read are unchanged.
+    #[serde(default)]
+    pub directives: bool,
+    /// Reuse highlighted code blocks across builds while their language,
+    /// their code, and the site's custom syntaxes are unchanged.
+    #[serde(default)]
+    pub highlight: bool,
+}
+
+/// Shell commands run around each build, from the site root, such as an
+/// external CSS or JS toolchain. A failing command fails the build.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Hooks {
+    /// Run before anything is loaded, so they can generate templates,
+    /// static files, or content.
+    #[serde(default)]
+    pub pre_build: Vec<String>,
+    /// Run once the output is in place, only when every page built.
+    #[serde(default)]
+    pub post_build: Vec<String>,
+}
+
+impl Hooks {
+    /// Returns whether no hook command is configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pre_build.is_empty() && self.post_build.is_empty()
+    }
+}
+
+/// Cache rules written to a `_headers` file, read by Netlify and Cloudflare
+/// Pages.
+///
+/// Files under the `immutable` directories (fingerprinted assets, whose
+/// names change with their content) are cached for good; everything else,
+/// HTML included, gets the `html` policy.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Headers {
+    /// Append cache rules to `_headers` in the output directory.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// `Cache-Control` for HTML and other unfingerprinted files.
+    #[serde(default = "default_html_cache_control")]
+    pub html: String,
+
+    /// `Cache-Control` for files under `immutable`.
+    #[serde(default = "default_assets_cache_control")]
+    pub assets: String,
 
+    /// Output directories holding fingerprinted assets (e.g., `"assets"`,
+    /// `"fonts"`), relative to the output directory.
     #[serde(default)]
-    pub author: Author,
+    pub immutable: Vec<String>,
 }
 
-/// Theme metadata loaded from `themes/<name>/theme.toml`.
-#[derive(Debug, Deserialize)]
-struct ThemeMeta {
+impl Default for Headers {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            html: default_html_cache_control(),
+            assets: default_assets_cache_control(),
+            immutable: Vec::new(),
+        }
+    }
+}
+
+/// Files GitHub Pages reads from the published output.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GithubPages {
+    /// Write `.nojekyll`, so the output is served as is rather than run
+    /// through Jekyll (which drops `_`-prefixed paths), and `CNAME`.
     #[serde(default)]
-    min_kiln_version: Option<String>,
+    pub enabled: bool,
 
+    /// Custom domain written to `CNAME`. Defaults to the host of
+    /// `base_url`, unless that is a `github.io` or local address.
     #[serde(default)]
-    params: toml::Table,
+    pub cname: Option<String>,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
-pub struct Author {
+/// Chronological archive pages, rendered with `archive.html`: `/archive/`
+/// with every dated post, then `/archive/<year>/` and
+/// `/archive/<year>/<month>/` for each year and month with posts.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Archive {
     #[serde(default)]
-    pub name: String,
+    pub enabled: bool,
+}
 
+/// Precompressed siblings of the text output (`index.html.gz`,
+/// `index.html.br`), which hosts like nginx (`gzip_static`,
+/// `brotli_static`) serve in place of compressing every response.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Compress {
+    /// Write a `.gz` sibling next to each compressible output file.
     #[serde(default)]
-    pub email: String,
+    pub gzip: bool,
 
+    /// Write a `.br` sibling next to each compressible output file.
     #[serde(default)]
-    pub link: String,
+    pub brotli: bool,
 }
 
-/// Full-text search configuration.
+/// Content Security Policy allowing the output's inline `<script>` and
+/// `<style>` blocks by their SHA-256 hashes, so a strict policy needs no
+/// `'unsafe-inline'` and no hand-maintained hashes.
 ///
-/// When enabled, kiln runs Pagefind as a post-build step to generate a search
-/// index under `{output_dir}/pagefind/`. The `pagefind` binary must be
-/// installed separately — see <https://pagefind.app/docs/installation/>.
-#[derive(Debug, Default, Deserialize, Serialize)]
-pub struct Search {
-    /// Enable Pagefind search indexing after build.
+/// The hashes are added to the `script-src` and `style-src` directives of
+/// `policy`, which start from its `default-src` when missing.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Csp {
     #[serde(default)]
     pub enabled: bool,
 
-    /// Path or name of the Pagefind binary (defaults to `"pagefind"` on `$PATH`).
+    /// Policy the hashes are added to.
+    #[serde(default = "default_csp_policy")]
+    pub policy: String,
+
     #[serde(default)]
-    pub binary: Option<String>,
+    pub output: CspOutput,
+}
+
+impl Default for Csp {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            policy: default_csp_policy(),
+            output: CspOutput::default(),
+        }
+    }
+}
+
+/// Where the `[csp]` policy is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CspOutput {
+    /// One site-wide rule appended to `_headers`, read by Netlify and
+    /// Cloudflare Pages.
+    #[default]
+    Headers,
+    /// One site-wide `add_header` directive in `csp.nginx.conf` at the
+    /// output root, for an nginx `include`.
+    Nginx,
+    /// A `<meta http-equiv>` tag at the top of each page's `<head>`, with
+    /// only that page's hashes, for hosts without custom headers.
+    Meta,
+}
+
+/// Where `kiln deploy` publishes the output directory, picked by `target`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "target", rename_all = "kebab-case")]
+pub enum Deploy {
+    /// `rsync` over SSH, mirroring the output to `destination`
+    /// (`user@host:/var/www/site`).
+    Rsync { destination: String },
+    /// `aws s3 sync` to `bucket` (`s3://bucket/prefix`), through `endpoint`
+    /// for S3-compatible services like Cloudflare R2.
+    S3 {
+        bucket: String,
+        #[serde(default)]
+        endpoint: Option<String>,
+    },
+    /// A commit of the output pushed to `branch` of `repository`, like
+    /// GitHub Pages' `gh-pages`.
+    Git {
+        repository: String,
+        #[serde(default = "default_deploy_branch")]
+        branch: String,
+    },
+}
+
+/// Comment threads rendered by the built-in `comments.html` partial,
+/// picked by `provider`. Threads are keyed by page `id`, so they survive
+/// slug and permalink changes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "provider", rename_all = "kebab-case")]
+pub enum Comments {
+    /// [giscus](https://giscus.app/): GitHub Discussions of `repo`
+    /// (`owner/name`) in `category`. The IDs come from the configurator on
+    /// giscus.app.
+    Giscus {
+        repo: String,
+        repo_id: String,
+        category: String,
+        category_id: String,
+        #[serde(default = "default_giscus_theme")]
+        theme: String,
+    },
+    /// [utterances](https://utteranc.es/): GitHub issues of `repo`
+    /// (`owner/name`).
+    Utterances {
+        repo: String,
+        #[serde(default = "default_utterances_theme")]
+        theme: String,
+    },
+}
+
+/// Analytics service whose script is added to the `<head>` of every page,
+/// picked by `provider`. Left out of `kiln serve` builds.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "provider", rename_all = "kebab-case")]
+pub enum Analytics {
+    /// [Plausible](https://plausible.io/), counting visits to `domain`, with
+    /// `script` pointing at a self-hosted instance.
+    Plausible {
+        domain: String,
+        #[serde(default = "default_plausible_script")]
+        script: String,
+    },
+    /// [Umami](https://umami.is/), counting visits to the website
+    /// `website_id`, with `script` pointing at a self-hosted instance.
+    Umami {
+        website_id: String,
+        #[serde(default = "default_umami_script")]
+        script: String,
+    },
+    /// [GoatCounter](https://www.goatcounter.com/), counting visits at
+    /// `https://<code>.goatcounter.com`.
+    #[serde(rename = "goatcounter")]
+    GoatCounter { code: String },
 }
 
 /// Site navigation menus.
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Menu {
     #[serde(default)]
     pub main: Vec<MenuItem>,
 }
 
 /// A single navigation menu entry.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MenuItem {
     pub name: String,
     pub url: String,
@@ -115,21 +1004,44 @@ pub struct MenuItem {
 impl Config {
     /// Loads site configuration from `config.toml` in the given root.
     ///
-    /// When a theme is configured, also loads its `theme.toml` and merges
-    /// default params. Falls back to defaults if the file does not exist.
+    /// `${VAR}` references in string values are expanded from the
+    /// environment, then `KILN_*` variables override single values
+    /// (`KILN_BASE_URL`, `KILN_SEARCH__ENABLED`). When a theme is configured,
+    /// also loads its `theme.toml` and merges default params. Falls back to
+    /// defaults if the file does not exist.
     ///
     /// # Errors
     ///
     /// Returns an error if the config file exists but cannot be read or parsed,
-    /// or if a configured theme's `theme.toml` is missing or incompatible.
+    /// if it references an unset variable, if an override has the wrong type,
+    /// if a configured theme's `theme.toml` is missing or incompatible, or if
+    /// the `[toc]` levels are out of range.
     pub fn load(root: &Path) -> Result<Self> {
         let path = root.join("config.toml");
-        let mut config: Self = if path.exists() {
-            let contents = fs::read_to_string(&path).context("failed to read config.toml")?;
-            toml::from_str(&contents).context("failed to parse config.toml")?
+        let contents = if path.exists() {
+            fs::read_to_string(&path).context("failed to read config.toml")?
         } else {
-            toml::from_str("").context("failed to construct default config")?
+            String::new()
         };
+        let parsed: toml::Table =
+            toml::from_str(&contents).context("failed to parse config.toml")?;
+        let mut table = parsed.clone();
+        env::interpolate(&mut table, &|name| std::env::var(name).ok())
+            .context("failed to expand environment variables in config.toml")?;
+        let vars = std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+        let defaults = toml::Table::try_from(toml::from_str::<Self>("")?)
+            .context("failed to serialize the default config")?;
+        env::apply_overrides(&mut table, &defaults, vars).context("invalid config override")?;
+
+        // Deserializing the file itself keeps line numbers in error messages.
+        let mut config: Self = if table == parsed {
+            toml::from_str(&contents)
+        } else {
+            toml::Value::Table(table).try_into()
+        }
+        .context("failed to parse config.toml")?;
 
         if let Some(ref theme_name) = config.theme {
             let theme_toml = root.join("themes").join(theme_name).join("theme.toml");
@@ -140,6 +1052,9 @@ impl Config {
         }
 
         config.menu.main.sort_by_key(|item| item.weight);
+        config.toc.validate()?;
+        config.images.validate()?;
+        config.drafts.resolve_secret()?;
 
         Ok(config)
     }
@@ -157,14 +1072,15 @@ impl Config {
     /// Relative paths are resolved against `root`. Absolute paths replace it.
     /// `..` segments are canonicalized away. The returned path is always
     /// canonical: parent directories must exist, but the leaf (the output
-    /// directory itself) need not — it is created by `clean_output_dir`.
+    /// directory itself) need not — it is created when the build output is
+    /// promoted into place.
     ///
     /// # Errors
     ///
     /// Returns an error if `output_dir` is empty, if its parent cannot be
     /// canonicalized (e.g., does not exist), or if the resolved path equals or
     /// is an ancestor of the project root — any of those would let
-    /// `clean_output_dir`'s `remove_dir_all` walk into the source tree.
+    /// the output sync (`prune_dir`) delete files from the source tree.
     pub fn resolved_output_dir(&self, root: &Path) -> Result<PathBuf> {
         if self.output_dir.is_empty() {
             bail!("output_dir cannot be empty");
@@ -233,6 +1149,17 @@ impl ThemeMeta {
     }
 }
 
+/// Checks that `theme_dir` holds a theme this kiln can use: a parsable
+/// `theme.toml` whose `min_kiln_version`, if any, is met.
+///
+/// # Errors
+///
+/// Returns an error if `theme.toml` is missing, invalid, or requires a newer
+/// kiln.
+pub fn check_theme(theme_dir: &Path, theme_name: &str) -> Result<()> {
+    ThemeMeta::load(&theme_dir.join("theme.toml"))?.check_min_kiln_version(theme_name)
+}
+
 /// Merges theme default params into site params. Site values take precedence.
 /// Nested tables are merged recursively. Returns an error on type mismatch.
 fn merge_params(site: &mut toml::Table, theme_defaults: &toml::Table) -> Result<()> {
@@ -265,6 +1192,10 @@ fn default_base_url() -> String {
     crate::serve::localhost_url(crate::serve::DEFAULT_PORT)
 }
 
+const fn default_true() -> bool {
+    true
+}
+
 fn default_title() -> String {
     String::from("My Site")
 }
@@ -273,6 +1204,86 @@ fn default_language() -> String {
     String::from("en")
 }
 
+const fn default_related_limit() -> usize {
+    5
+}
+
+const fn default_related_section_weight() -> usize {
+    1
+}
+
+const fn default_summary_words() -> usize {
+    70
+}
+
+const fn default_thumbnail_width() -> u32 {
+    480
+}
+
+fn default_image_sizes() -> String {
+    "100vw".into()
+}
+
+const fn default_image_quality() -> u8 {
+    80
+}
+
+fn default_og_image_template() -> String {
+    "og_image.svg".into()
+}
+
+fn default_og_image_font_family() -> String {
+    "sans-serif".into()
+}
+
+fn default_og_image_background() -> String {
+    "#0f172a".into()
+}
+
+fn default_og_image_foreground() -> String {
+    "#f8fafc".into()
+}
+
+fn default_csp_policy() -> String {
+    "default-src 'self'".into()
+}
+
+fn default_html_cache_control() -> String {
+    "public, max-age=0, must-revalidate".into()
+}
+
+fn default_assets_cache_control() -> String {
+    "public, max-age=31536000, immutable".into()
+}
+
+fn default_deploy_branch() -> String {
+    "gh-pages".into()
+}
+
+fn default_plausible_script() -> String {
+    "https://plausible.io/js/script.js".into()
+}
+
+fn default_umami_script() -> String {
+    "https://cloud.umami.is/script.js".into()
+}
+
+fn default_giscus_theme() -> String {
+    "preferred_color_scheme".into()
+}
+
+fn default_utterances_theme() -> String {
+    "preferred-color-scheme".into()
+}
+
+const fn default_toc_min_level() -> u8 {
+    1
+}
+
+const fn default_toc_max_level() -> u8 {
+    6
+}
+
 fn default_output_dir() -> String {
     String::from("public")
 }
@@ -329,10 +1340,58 @@ mod tests {
         assert_eq!(config.language, "en");
         assert!(config.timezone.is_none());
         assert_eq!(config.output_dir, "public");
+        assert!(!config.strict);
+        assert!(!config.lastmod_from_git);
+        assert!(config.authors.is_empty());
+        assert!(!config.author_pages);
+        assert!(!config.archive.enabled);
+        assert_eq!(config.markdown, Markdown::default());
+        assert_eq!(config.slugify.mode(), SlugifyMode::Safe);
         assert!(config.theme.is_none());
         assert!(config.params.is_empty());
+        assert!(config.content.passthrough.is_empty());
+        assert!(config.links.is_empty());
+        assert_eq!(config.markup.heading_ids, HeadingIds::Auto);
+        assert_eq!(config.markup.title_case, TitleCase::None);
+        assert!(config.markup.title_case_exceptions.is_empty());
+        assert_eq!(config.markup.highlight_classes, HighlightClasses::Syntect);
+        assert!(config.markup.highlight_class_prefix.is_empty());
+        assert_eq!(config.toc.levels(), 1..=6);
         assert!(!config.search.enabled);
         assert!(config.search.binary.is_none());
+        assert!(!config.drafts.share);
+        assert_eq!(config.related.limit, 5);
+        assert_eq!(config.related.section_weight, 1);
+        assert!(!config.cache.directives);
+        assert!(!config.cache.highlight);
+        assert!(!config.assets.fingerprint);
+        assert!(config.hooks.pre_build.is_empty());
+        assert!(config.hooks.post_build.is_empty());
+        assert!(!config.templates.strict);
+        assert_eq!(config.summary.words, 70);
+        assert_eq!(config.thumbnails.width, 480);
+        assert!(!config.images.enabled());
+        assert_eq!(config.images.sizes, "100vw");
+        assert!(!config.og_image.enabled);
+        assert_eq!(config.og_image.template, "og_image.svg");
+        assert_eq!(config.og_image.accent, None);
+        assert!(!config.headers.enabled);
+        assert_eq!(config.headers.html, "public, max-age=0, must-revalidate");
+        assert_eq!(config.headers.assets, "public, max-age=31536000, immutable");
+        assert!(config.headers.immutable.is_empty());
+        assert!(!config.redirects.netlify);
+        assert!(!config.github_pages.enabled);
+        assert!(config.github_pages.cname.is_none());
+        assert!(!config.compress.gzip);
+        assert!(!config.compress.brotli);
+        assert!(config.deploy.is_none());
+        assert!(config.podcast.is_none());
+        assert!(config.comments.is_none());
+        assert!(config.analytics.is_none());
+        assert!(!config.csp.enabled);
+        assert_eq!(config.csp.policy, "default-src 'self'");
+        assert_eq!(config.csp.output, CspOutput::Headers);
+        assert!(!config.microformats.wrap_content);
         assert!(config.menu.main.is_empty());
         assert!(config.author.name.is_empty());
         assert!(config.author.email.is_empty());
@@ -348,6 +1407,7 @@ mod tests {
             language = "zh-CN"
             timezone = "Asia/Shanghai"
             output_dir = "dist"
+            strict = true
             theme = "IgnIt"
 
             [params]
@@ -365,6 +1425,7 @@ mod tests {
         assert_eq!(config.language, "zh-CN");
         assert_eq!(config.timezone.as_deref(), Some("Asia/Shanghai"));
         assert_eq!(config.output_dir, "dist");
+        assert!(config.strict);
         assert_eq!(config.theme.as_deref(), Some("IgnIt"));
         assert_eq!(
             config.params.get("fontawesome"),
@@ -375,6 +1436,300 @@ mod tests {
         assert_eq!(config.author.link, "https://alice.example.com");
     }
 
+    #[test]
+    fn links_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [links]
+            rustbook = "https://docs.example.org/book/"
+        "#})
+        .unwrap();
+        assert_eq!(config.links["rustbook"], "https://docs.example.org/book/");
+    }
+
+    #[test]
+    fn summary_from_toml() {
+        let config: Config = toml::from_str("[summary]\nwords = 0\n").unwrap();
+        assert_eq!(config.summary.words, 0);
+    }
+
+    #[test]
+    fn images_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [images]
+            widths = [480, 960]
+            formats = ["webp"]
+        "#})
+        .unwrap();
+        assert!(config.images.enabled());
+        assert_eq!(config.images.widths, [480, 960]);
+        assert_eq!(config.images.formats, [ImageFormat::Webp]);
+        assert_eq!(config.images.quality, 80);
+
+        let err = toml::from_str::<Config>("[images]\nformats = [\"avif\"]\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant"), "got: {err}");
+    }
+
+    #[test]
+    fn og_image_from_toml() {
+        let config: Config = toml::from_str(indoc! {r##"
+            [og_image]
+            enabled = true
+            fonts = ["static/fonts/Inter.ttf"]
+            font_family = "Inter"
+            accent = "#f97316"
+        "##})
+        .unwrap();
+        assert!(config.og_image.enabled);
+        assert_eq!(
+            config.og_image.fonts,
+            [PathBuf::from("static/fonts/Inter.ttf")]
+        );
+        assert_eq!(config.og_image.font_family, "Inter");
+        assert_eq!(config.og_image.background, "#0f172a");
+        assert_eq!(config.og_image.accent.as_deref(), Some("#f97316"));
+    }
+
+    #[test]
+    fn markup_heading_ids_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [markup]
+            heading_ids = "explicit-only"
+        "#})
+        .unwrap();
+        assert_eq!(config.markup.heading_ids, HeadingIds::ExplicitOnly);
+    }
+
+    #[test]
+    fn markdown_extensions_from_toml() {
+        let config: Config = toml::from_str(indoc! {r"
+            [markdown]
+            tables = false
+            math = false
+        "})
+        .unwrap();
+        assert_eq!(
+            config.markdown,
+            Markdown {
+                tables: false,
+                math: false,
+                ..Markdown::default()
+            }
+        );
+    }
+
+    #[test]
+    fn slugify_mode_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [slugify]
+            mode = "ascii"
+        "#})
+        .unwrap();
+        assert_eq!(config.slugify.mode(), SlugifyMode::Ascii);
+    }
+
+    #[test]
+    fn markup_title_case_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [markup]
+            title_case = "sentence"
+            title_case_exceptions = ["GitHub"]
+        "#})
+        .unwrap();
+        assert_eq!(config.markup.title_case, TitleCase::Sentence);
+        assert_eq!(config.markup.title_case_exceptions, ["GitHub"]);
+    }
+
+    #[test]
+    fn markup_highlight_classes_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [markup]
+            highlight_classes = "chroma"
+            highlight_class_prefix = "hl-"
+        "#})
+        .unwrap();
+        assert_eq!(config.markup.highlight_classes, HighlightClasses::Chroma);
+        assert_eq!(config.markup.highlight_class_prefix, "hl-");
+    }
+
+    #[test]
+    fn deploy_targets_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [deploy]
+            target = "git"
+            repository = "git@github.com:me/me.github.io.git"
+        "#})
+        .unwrap();
+        assert_eq!(
+            config.deploy,
+            Some(Deploy::Git {
+                repository: "git@github.com:me/me.github.io.git".into(),
+                branch: "gh-pages".into(),
+            })
+        );
+
+        let config: Config = toml::from_str(indoc! {r#"
+            [deploy]
+            target = "s3"
+            bucket = "s3://site"
+            endpoint = "https://r2.example.com"
+        "#})
+        .unwrap();
+        assert_eq!(
+            config.deploy,
+            Some(Deploy::S3 {
+                bucket: "s3://site".into(),
+                endpoint: Some("https://r2.example.com".into()),
+            })
+        );
+
+        let err = toml::from_str::<Config>("[deploy]\ntarget = \"ftp\"\n").unwrap_err();
+        assert!(err.to_string().contains("ftp"), "got: {err}");
+    }
+
+    #[test]
+    fn comments_providers_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [comments]
+            provider = "utterances"
+            repo = "me/blog-comments"
+        "#})
+        .unwrap();
+        assert_eq!(
+            config.comments,
+            Some(Comments::Utterances {
+                repo: "me/blog-comments".into(),
+                theme: "preferred-color-scheme".into(),
+            })
+        );
+
+        let err = toml::from_str::<Config>(indoc! {r#"
+            [comments]
+            provider = "giscus"
+            repo = "me/blog"
+        "#})
+        .unwrap_err();
+        assert!(err.to_string().contains("repo_id"), "got: {err}");
+    }
+
+    #[test]
+    fn analytics_providers_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [analytics]
+            provider = "plausible"
+            domain = "example.com"
+        "#})
+        .unwrap();
+        assert_eq!(
+            config.analytics,
+            Some(Analytics::Plausible {
+                domain: "example.com".into(),
+                script: "https://plausible.io/js/script.js".into(),
+            })
+        );
+
+        let config: Config = toml::from_str(indoc! {r#"
+            [analytics]
+            provider = "goatcounter"
+            code = "me"
+        "#})
+        .unwrap();
+        assert_eq!(
+            config.analytics,
+            Some(Analytics::GoatCounter { code: "me".into() })
+        );
+    }
+
+    #[test]
+    fn toc_levels_from_toml() {
+        let config: Config = toml::from_str(indoc! {"
+            [toc]
+            min_level = 2
+            max_level = 3
+        "})
+        .unwrap();
+        assert_eq!(config.toc.levels(), 2..=3);
+    }
+
+    #[test]
+    fn load_invalid_toc_levels_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            "[toc]\nmin_level = 4\nmax_level = 2\n",
+        )
+        .unwrap();
+        let err = Config::load(dir.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid [toc] levels 4..=2"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn load_invalid_image_quality_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config.toml"), "[images]\nquality = 101\n").unwrap();
+        let err = Config::load(dir.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid [images] quality 101"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn drafts_secret_not_serialized() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [drafts]
+            share = true
+            secret = "hunter2"
+        "#})
+        .unwrap();
+        assert!(config.drafts.share);
+        assert_eq!(config.drafts.secret.as_deref(), Some("hunter2"));
+        let value = minijinja::Value::from_serialize(&config);
+        assert!(
+            !value.to_string().contains("hunter2"),
+            "secret should not reach templates: {value}"
+        );
+    }
+
+    #[test]
+    fn load_drafts_share_without_secret_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            "[drafts]\nshare = true\nsecret = \"\"\n",
+        )
+        .unwrap();
+        let err = Config::load(dir.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("[drafts] share requires a secret"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn external_links_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [external_links]
+            enabled = true
+            class = "external"
+        "#})
+        .unwrap();
+        assert!(config.external_links.enabled);
+        assert_eq!(config.external_links.class.as_deref(), Some("external"));
+    }
+
+    #[test]
+    fn content_passthrough_from_toml() {
+        let config: Config = toml::from_str(indoc! {r#"
+            [content]
+            passthrough = ["pdf", "html"]
+        "#})
+        .unwrap();
+        assert_eq!(config.content.passthrough, ["pdf", "html"]);
+    }
+
     #[test]
     fn search_from_toml() {
         let config: Config = toml::from_str(indoc! {r#"
@@ -489,6 +1844,35 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn load_expands_environment_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            r#"title = "${KILN_TEST_UNSET_TITLE:-Fallback} $${literal}""#,
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.title, "Fallback ${literal}");
+    }
+
+    #[test]
+    fn load_unset_environment_variable_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            r#"base_url = "${KILN_TEST_UNSET_URL}""#,
+        )
+        .unwrap();
+
+        let err = format!("{:#}", Config::load(dir.path()).unwrap_err());
+        assert!(
+            err.contains("`KILN_TEST_UNSET_URL` used in `base_url` is not set"),
+            "got: {err}"
+        );
+    }
+
     #[test]
     fn menu_sorts_by_weight_on_load() {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,330 @@
+use anyhow::{Result, bail};
+
+/// Prefix of environment variables overriding config values.
+const OVERRIDE_PREFIX: &str = "KILN_";
+
+/// Separator between table levels in override names:
+/// `KILN_SEARCH__ENABLED` sets `enabled` in `[search]`.
+const TABLE_SEPARATOR: &str = "__";
+
+/// Expands `${VAR}` references in every string value of `table`, looking
+/// variables up with `lookup`.
+///
+/// `${VAR:-fallback}` uses `fallback` when `VAR` is unset or empty, and
+/// `$${` writes a literal `${`. Keys are left as written.
+///
+/// # Errors
+///
+/// Returns an error naming the config key if a variable without a fallback
+/// is unset, or a `${` is never closed.
+pub fn interpolate(
+    table: &mut toml::Table,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<()> {
+    for (key, value) in table {
+        interpolate_value(value, key, lookup)?;
+    }
+    Ok(())
+}
+
+fn interpolate_value(
+    value: &mut toml::Value,
+    path: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<()> {
+    match value {
+        toml::Value::String(s) if s.contains('$') => *s = expand(s, path, lookup)?,
+        toml::Value::Array(items) => {
+            for item in items {
+                interpolate_value(item, path, lookup)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                interpolate_value(value, &format!("{path}.{key}"), lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand(s: &str, path: &str, lookup: &impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            out.push_str("${");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let Some(end) = after.find('}') else {
+                bail!("unclosed `${{` in `{path}`");
+            };
+            let (name, fallback) = match after[..end].split_once(":-") {
+                Some((name, fallback)) => (name, Some(fallback)),
+                None => (&after[..end], None),
+            };
+            match (lookup(name), fallback) {
+                (Some(value), Some(fallback)) if value.is_empty() => out.push_str(fallback),
+                (Some(value), _) => out.push_str(&value),
+                (None, Some(fallback)) => out.push_str(fallback),
+                (None, None) => bail!("environment variable `{name}` used in `{path}` is not set"),
+            }
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Applies `KILN_*` variables among `vars` to `table`, each replacing the
+/// value at the key path its name spells (see [`TABLE_SEPARATOR`]).
+///
+/// A value replacing a string is taken verbatim. Any other value is parsed
+/// as TOML (`true`, `3`, `["a", "b"]`) and must keep the replaced value's
+/// type. A key not in the file is typed after its value in `defaults`, the
+/// default config, and taken as a string when it has none there. Overrides
+/// apply in name order, and names matching no config field are ignored like
+/// unknown keys in the file.
+///
+/// # Errors
+///
+/// Returns an error if an override would replace a value with one of
+/// another type, or descend into a value that is not a table.
+pub fn apply_overrides(
+    table: &mut toml::Table,
+    defaults: &toml::Table,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<()> {
+    let mut vars: Vec<_> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(OVERRIDE_PREFIX))
+        .collect();
+    vars.sort_unstable();
+
+    for (name, raw) in vars {
+        let keys: Vec<String> = name[OVERRIDE_PREFIX.len()..]
+            .split(TABLE_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect();
+        if keys.iter().any(String::is_empty) {
+            continue;
+        }
+        let Some((leaf, tables)) = keys.split_last() else {
+            continue;
+        };
+
+        let mut current = &mut *table;
+        let mut schema = Some(defaults);
+        for key in tables {
+            schema = schema
+                .and_then(|schema| schema.get(key))
+                .and_then(toml::Value::as_table);
+            let value = current
+                .entry(key.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            let toml::Value::Table(next) = value else {
+                bail!(
+                    "`{name}` sets a key under `{key}`, which is a {}",
+                    value.type_str()
+                );
+            };
+            current = next;
+        }
+
+        let existing = current
+            .get(leaf)
+            .or_else(|| schema.and_then(|schema| schema.get(leaf)));
+        let value = match existing {
+            None | Some(toml::Value::String(_)) => toml::Value::String(raw),
+            Some(existing) => match raw.parse::<toml::Value>() {
+                Ok(value) if value.type_str() == existing.type_str() => value,
+                _ => bail!("`{name}` should be a {}, got `{raw}`", existing.type_str()),
+            },
+        };
+        current.insert(leaf.clone(), value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use indoc::indoc;
+
+    use super::*;
+
+    fn table(toml: &str) -> toml::Table {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn defaults() -> toml::Table {
+        table(indoc! {r#"
+            title = ""
+            [summary]
+            words = 70
+        "#})
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect()
+    }
+
+    // ── interpolate ──
+
+    #[test]
+    fn interpolate_expands_nested_strings() {
+        let mut config = table(indoc! {r#"
+            base_url = "https://${HOST}/"
+            [deploy]
+            target = "s3"
+            bucket = "s3://${BUCKET}"
+            [params]
+            tags = ["${HOST}", "plain"]
+        "#});
+        interpolate(
+            &mut config,
+            &env(&[("HOST", "example.com"), ("BUCKET", "site")]),
+        )
+        .unwrap();
+
+        assert_eq!(config["base_url"].as_str(), Some("https://example.com/"));
+        assert_eq!(config["deploy"]["bucket"].as_str(), Some("s3://site"));
+        assert_eq!(config["params"]["tags"][0].as_str(), Some("example.com"));
+    }
+
+    #[test]
+    fn interpolate_fallback_and_escape() {
+        let mut config = table(indoc! {r#"
+            a = "${UNSET:-fallback}"
+            b = "${EMPTY:-fallback}"
+            c = "$${HOME} costs $5"
+        "#});
+        interpolate(&mut config, &env(&[("EMPTY", "")])).unwrap();
+
+        assert_eq!(config["a"].as_str(), Some("fallback"));
+        assert_eq!(config["b"].as_str(), Some("fallback"));
+        assert_eq!(config["c"].as_str(), Some("${HOME} costs $5"));
+    }
+
+    #[test]
+    fn interpolate_unset_variable_returns_error() {
+        let mut config = table("[drafts]\nsecret = \"${SECRET}\"\n");
+        let err = interpolate(&mut config, &env(&[])).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "environment variable `SECRET` used in `drafts.secret` is not set"
+        );
+    }
+
+    #[test]
+    fn interpolate_unclosed_reference_returns_error() {
+        let mut config = table(r#"title = "${TITLE""#);
+        let err = interpolate(&mut config, &env(&[])).unwrap_err().to_string();
+        assert!(err.contains("unclosed"), "got: {err}");
+    }
+
+    // ── apply_overrides ──
+
+    #[test]
+    fn apply_overrides_sets_nested_keys() {
+        let mut config = table(indoc! {r#"
+            base_url = "https://example.com/"
+            [search]
+            enabled = false
+        "#});
+        apply_overrides(
+            &mut config,
+            &defaults(),
+            vars(&[
+                ("KILN_BASE_URL", "https://preview.example.com/"),
+                ("KILN_SEARCH__ENABLED", "true"),
+                ("KILN_SUMMARY__WORDS", "80"),
+                ("KILN_TIMEZONE", "Asia/Shanghai"),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            config["base_url"].as_str(),
+            Some("https://preview.example.com/")
+        );
+        assert_eq!(config["search"]["enabled"].as_bool(), Some(true));
+        assert_eq!(config["summary"]["words"].as_integer(), Some(80));
+        assert_eq!(config["timezone"].as_str(), Some("Asia/Shanghai"));
+        assert!(!config.contains_key("path"));
+    }
+
+    #[test]
+    fn apply_overrides_keeps_string_values_verbatim() {
+        let mut config = table(r#"title = "Blog""#);
+        apply_overrides(&mut config, &defaults(), vars(&[("KILN_TITLE", "2024")])).unwrap();
+        assert_eq!(config["title"].as_str(), Some("2024"));
+    }
+
+    #[test]
+    fn apply_overrides_types_missing_keys_from_defaults() {
+        let mut config = table("");
+        apply_overrides(
+            &mut config,
+            &defaults(),
+            vars(&[
+                ("KILN_TITLE", "2024"),
+                ("KILN_SUMMARY__WORDS", "80"),
+                ("KILN_PARAMS__VERSION", "1.0"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config["title"].as_str(), Some("2024"));
+        assert_eq!(config["summary"]["words"].as_integer(), Some(80));
+        assert_eq!(config["params"]["version"].as_str(), Some("1.0"));
+
+        let err = apply_overrides(
+            &mut table(""),
+            &defaults(),
+            vars(&[("KILN_SUMMARY__WORDS", "many")]),
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(err, "`KILN_SUMMARY__WORDS` should be a integer, got `many`");
+    }
+
+    #[test]
+    fn apply_overrides_type_mismatch_returns_error() {
+        let mut config = table("[search]\nenabled = false\n");
+        let err = apply_overrides(
+            &mut config,
+            &defaults(),
+            vars(&[("KILN_SEARCH__ENABLED", "yes")]),
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(err, "`KILN_SEARCH__ENABLED` should be a boolean, got `yes`");
+    }
+
+    #[test]
+    fn apply_overrides_through_non_table_returns_error() {
+        let mut config = table(r#"title = "Blog""#);
+        let err = apply_overrides(&mut config, &defaults(), vars(&[("KILN_TITLE__MAIN", "x")]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("which is a string"), "got: {err}");
+    }
+}
@@ -1,3 +1,4 @@
 pub mod discovery;
+pub mod format;
 pub mod frontmatter;
 pub mod page;
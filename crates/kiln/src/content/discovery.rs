@@ -1,39 +1,74 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use walkdir::WalkDir;
 
-use super::page::{Page, derive_page_kind};
+use super::format::ContentFormat;
+use super::frontmatter::{self, Cascade};
+use super::page::{Page, PageErrors, derive_page_id, derive_page_kind};
+use crate::config::Slugify;
+
+/// Name of the directories holding assets shared by a section's pages.
+pub const SECTION_ASSETS_DIR: &str = "_assets";
 
 /// All content discovered from the content directory.
 #[derive(Debug)]
 pub struct ContentSet {
     pub pages: Vec<Page>,
+    /// Pages with `draft = true`, kept apart so only draft sharing sees them.
+    pub drafts: Vec<Page>,
     pub content_dir: PathBuf,
 }
 
-/// Walks the content directory, loading all non-draft markdown pages.
+/// Walks the content directory, loading all content pages (see
+/// [`ContentFormat`] for the supported file types). Pages with `draft = true`
+/// in frontmatter go to [`ContentSet::drafts`] instead of `pages`.
 ///
 /// Excludes:
 /// - Files and directories whose names start with `_`
-/// - Non-markdown files
-/// - Markdown files without `+++` frontmatter (e.g., CLAUDE.md, README.md)
-/// - Pages with `draft = true` in frontmatter
+/// - Files of other types
+/// - Content files without page metadata (e.g., CLAUDE.md, README.md, or
+///   plain HTML assets)
 ///
 /// # Errors
 ///
 /// Returns an error if the content directory cannot be read, or if any
-/// non-draft markdown file has invalid frontmatter.
-pub fn discover_content(root: &Path) -> Result<ContentSet> {
+/// content file has invalid frontmatter. Every invalid file is reported,
+/// not just the first.
+pub fn discover_content(root: &Path, slugify: Slugify) -> Result<ContentSet> {
+    let mut errors = PageErrors::default();
+    let content = discover_loadable_content(root, slugify, &mut errors)?;
+    errors.into_result("load")?;
+    Ok(content)
+}
+
+/// Like [`discover_content`], but leaves out content files that fail to
+/// load, adding them to `errors`, so the rest of the site can still be
+/// built.
+///
+/// # Errors
+///
+/// Returns an error if the content directory cannot be read.
+pub fn discover_loadable_content(
+    root: &Path,
+    slugify: Slugify,
+    errors: &mut PageErrors,
+) -> Result<ContentSet> {
     let content_dir = root.join("content");
     if !content_dir.is_dir() {
         return Ok(ContentSet {
             pages: Vec::new(),
+            drafts: Vec::new(),
             content_dir,
         });
     }
 
     let mut pages = Vec::new();
+    let mut drafts = Vec::new();
+    let mut cascades = HashMap::new();
 
     for entry in WalkDir::new(&content_dir)
         .follow_links(false)
@@ -48,10 +83,18 @@ pub fn discover_content(root: &Path) -> Result<ContentSet> {
         }
 
         let path = entry.path();
-        if path.extension().is_some_and(|ext| ext == "md") && has_frontmatter(path) {
-            let mut page = Page::from_file(path)?;
-            if !page.frontmatter.draft {
-                page.kind = derive_page_kind(&page.source_path, &content_dir);
+        if is_content_page(path) {
+            let Some(mut page) = errors.collect(path, Page::from_file(path, slugify)) else {
+                continue;
+            };
+            page.kind = derive_page_kind(&page.source_path, &content_dir);
+            if page.id.is_empty() {
+                page.id = derive_page_id(&page.source_path, &content_dir);
+            }
+            apply_cascades(&mut page, &content_dir, &mut cascades)?;
+            if page.frontmatter.draft {
+                drafts.push(page);
+            } else {
                 pages.push(page);
             }
         }
@@ -59,25 +102,180 @@ pub fn discover_content(root: &Path) -> Result<ContentSet> {
 
     // Sort by date descending (newest first), undated pages last.
     // Tiebreak by source path for deterministic output across platforms.
-    pages.sort_by(|a, b| {
-        b.frontmatter
-            .date
-            .cmp(&a.frontmatter.date)
-            .then_with(|| a.source_path.cmp(&b.source_path))
-    });
-
-    Ok(ContentSet { pages, content_dir })
+    for pages in [&mut pages, &mut drafts] {
+        pages.sort_by(|a, b| {
+            b.frontmatter
+                .date
+                .cmp(&a.frontmatter.date)
+                .then_with(|| a.source_path.cmp(&b.source_path))
+        });
+    }
+
+    Ok(ContentSet {
+        pages,
+        drafts,
+        content_dir,
+    })
 }
 
-/// Returns `true` if the file starts with a `+++` frontmatter delimiter
-/// (optionally preceded by a UTF-8 BOM).
+/// Fills the keys `page` leaves unset from the [`Cascade`] tables of the
+/// `_index.md` files in its directory and every parent up to `content_dir`,
+/// nearest first.
+///
+/// `cascades` caches the table of each directory visited.
+fn apply_cascades(
+    page: &mut Page,
+    content_dir: &Path,
+    cascades: &mut HashMap<PathBuf, Option<Cascade>>,
+) -> Result<()> {
+    let dirs = page
+        .source_path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(content_dir));
+    for dir in dirs {
+        if !cascades.contains_key(dir) {
+            cascades.insert(dir.to_owned(), load_cascade(dir)?);
+        }
+        if let Some(cascade) = &cascades[dir] {
+            let fm = &mut page.frontmatter;
+            fm.noindex = fm.noindex.or(cascade.noindex);
+            fm.exclude_from_feed = fm.exclude_from_feed.or(cascade.exclude_from_feed);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the `cascade` table of `_index.md` in `dir`, if both exist.
+fn load_cascade(dir: &Path) -> Result<Option<Cascade>> {
+    let path = dir.join("_index.md");
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    let (fm, _) = frontmatter::parse(&content)
+        .with_context(|| format!("invalid frontmatter in {}", path.display()))?;
+    Ok(fm.cascade)
+}
+
+/// Collects non-markdown files under `content_dir` whose extension matches
+/// `extensions` (case-insensitive, leading `.` optional), for verbatim copying
+/// to the same relative output path.
+///
+/// Excludes content pages (e.g., `.html` with frontmatter), `_`-prefixed
+/// entries, and anything inside a page bundle (a directory below
+/// `content_dir` holding an `index.*` page), since bundle assets
+/// are copied with their page — or withheld, for draft bundles.
+///
+/// Returns sorted absolute paths for deterministic output.
+///
+/// # Errors
+///
+/// Returns an error if the content directory cannot be walked.
+pub fn discover_passthrough(content_dir: &Path, extensions: &[String]) -> Result<Vec<PathBuf>> {
+    if extensions.is_empty() || !content_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(content_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !is_excluded(e) && !is_bundle_dir(e, content_dir))
+    {
+        let entry =
+            entry.with_context(|| format!("failed to read entry in {}", content_dir.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let matches = entry
+            .path()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                ext != "md"
+                    && extensions
+                        .iter()
+                        .any(|allowed| allowed.trim_start_matches('.').eq_ignore_ascii_case(ext))
+            });
+        if matches && !is_content_page(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Collects the files of section asset directories: `_assets/` directories
+/// (e.g., `content/posts/_assets/`) whose files are shared by every page
+/// below them and published once, at the same relative output path.
+///
+/// `_assets/` directories inside other `_`-prefixed directories are skipped,
+/// like the rest of their contents.
 ///
-/// Markdown files without frontmatter (e.g., CLAUDE.md, README.md) are
-/// skipped during discovery rather than causing a parse error.
-fn has_frontmatter(path: &Path) -> bool {
-    std::fs::read_to_string(path).is_ok_and(|content| {
-        let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
-        content.starts_with("+++")
+/// Returns sorted absolute paths for deterministic output.
+///
+/// # Errors
+///
+/// Returns an error if the content directory cannot be walked.
+pub fn discover_section_assets(content_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !content_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(content_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !is_excluded(e) || in_section_assets(e.path(), content_dir))
+    {
+        let entry =
+            entry.with_context(|| format!("failed to read entry in {}", content_dir.display()))?;
+        if entry.file_type().is_file() && in_section_assets(entry.path(), content_dir) {
+            files.push(entry.into_path());
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Returns `true` for content files (see [`ContentFormat`]) that carry page
+/// metadata.
+///
+/// Content files without it (e.g., CLAUDE.md, README.md, or plain HTML
+/// assets) are skipped during discovery rather than causing a parse error.
+fn is_content_page(path: &Path) -> bool {
+    ContentFormat::from_path(path).is_some_and(|format| {
+        std::fs::read_to_string(path).is_ok_and(|content| format.has_metadata(&content))
+    })
+}
+
+/// Returns `true` for directories below `content_dir` that hold an `index.md`
+/// or another `index.*` content page.
+fn is_bundle_dir(entry: &walkdir::DirEntry, content_dir: &Path) -> bool {
+    if !entry.file_type().is_dir() || entry.path() == content_dir {
+        return false;
+    }
+    entry.path().join("index.md").is_file()
+        || std::fs::read_dir(entry.path()).is_ok_and(|entries| {
+            entries.flatten().any(|e| {
+                let path = e.path();
+                path.file_stem().is_some_and(|stem| stem == "index") && is_content_page(&path)
+            })
+        })
+}
+
+/// Returns `true` for `_assets` directories below `content_dir` and anything
+/// inside them.
+fn in_section_assets(path: &Path, content_dir: &Path) -> bool {
+    path.strip_prefix(content_dir).is_ok_and(|relative| {
+        relative
+            .components()
+            .any(|component| component.as_os_str() == SECTION_ASSETS_DIR)
     })
 }
 
@@ -123,12 +321,12 @@ mod tests {
             "#},
         );
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages.len(), 2);
     }
 
     #[test]
-    fn discover_content_excludes_drafts() {
+    fn discover_content_separates_drafts() {
         let root = tempfile::tempdir().unwrap();
         write_test_file(
             root.path(),
@@ -152,9 +350,12 @@ mod tests {
             "#},
         );
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages.len(), 1);
         assert_eq!(set.pages[0].frontmatter.title, "Published");
+        assert_eq!(set.drafts.len(), 1);
+        assert_eq!(set.drafts[0].frontmatter.title, "Draft");
+        assert!(set.drafts[0].is_post());
     }
 
     #[test]
@@ -181,7 +382,7 @@ mod tests {
             "#},
         );
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages.len(), 1);
         assert_eq!(set.pages[0].frontmatter.title, "Visible");
     }
@@ -206,7 +407,7 @@ mod tests {
             "# Notes\nSome reference notes.",
         );
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages.len(), 1);
         assert_eq!(set.pages[0].frontmatter.title, "Hello");
     }
@@ -226,14 +427,14 @@ mod tests {
         );
         write_test_file(root.path(), "content/posts/hello/image.png", "not-a-png");
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages.len(), 1);
     }
 
     #[test]
     fn discover_content_missing_dir_returns_empty() {
         let root = tempfile::tempdir().unwrap();
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert!(set.pages.is_empty());
     }
 
@@ -263,7 +464,7 @@ mod tests {
             "#},
         );
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages[0].frontmatter.title, "New");
         assert_eq!(set.pages[1].frontmatter.title, "Old");
     }
@@ -292,7 +493,7 @@ mod tests {
             "#},
         );
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages[0].frontmatter.title, "Alpha");
         assert_eq!(set.pages[1].frontmatter.title, "Beta");
     }
@@ -331,7 +532,7 @@ mod tests {
             "#},
         );
 
-        let set = discover_content(root.path()).unwrap();
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
         assert_eq!(set.pages.len(), 3);
 
         let section_post = set
@@ -360,4 +561,243 @@ mod tests {
             .unwrap();
         assert_eq!(about.kind, PageKind::Page);
     }
+
+    #[test]
+    fn discover_content_includes_html_pages_with_frontmatter() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "content/landing.html",
+            indoc! {r#"
+                +++
+                title = "Landing"
+                +++
+                <section>Hi</section>
+            "#},
+        );
+        write_test_file(
+            root.path(),
+            "content/widget.html",
+            "<div>no frontmatter</div>",
+        );
+
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
+        assert_eq!(set.pages.len(), 1);
+        assert_eq!(set.pages[0].format, ContentFormat::Html);
+        assert_eq!(set.pages[0].slug, "landing");
+    }
+
+    #[cfg(all(feature = "org", feature = "asciidoc"))]
+    #[test]
+    fn discover_content_includes_org_and_asciidoc_pages() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "content/posts/org-post/index.org",
+            "#+title: Org Post\n#+date: 2024-01-15\n\nBody.\n",
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/org-post/notes.org",
+            "Plain notes without a header.\n",
+        );
+        write_test_file(
+            root.path(),
+            "content/guide.adoc",
+            "= Guide\n:tags: docs\n\nBody.\n",
+        );
+
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
+        assert_eq!(set.pages.len(), 2);
+        let org = set.pages.iter().find(|p| p.slug == "org-post").unwrap();
+        assert_eq!(org.format, ContentFormat::Org);
+        assert_eq!(org.frontmatter.title, "Org Post");
+        assert!(org.assets.is_empty(), "markup sources are not assets");
+        let adoc = set.pages.iter().find(|p| p.slug == "guide").unwrap();
+        assert_eq!(adoc.format, ContentFormat::AsciiDoc);
+        assert_eq!(adoc.frontmatter.tags, ["docs"]);
+    }
+
+    #[test]
+    fn discover_content_applies_cascades() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "content/notes/_index.md",
+            indoc! {r"
+                +++
+                [cascade]
+                noindex = true
+                exclude_from_feed = true
+                +++
+            "},
+        );
+        write_test_file(
+            root.path(),
+            "content/notes/public/_index.md",
+            indoc! {r"
+                +++
+                [cascade]
+                exclude_from_feed = false
+                +++
+            "},
+        );
+        for (path, extra) in [
+            ("content/notes/a.md", ""),
+            ("content/notes/b.md", "noindex = false\n"),
+            ("content/notes/public/c/index.md", ""),
+            ("content/about.md", ""),
+        ] {
+            write_test_file(
+                root.path(),
+                path,
+                &format!("+++\ntitle = \"{path}\"\n{extra}+++\n"),
+            );
+        }
+
+        let set = discover_content(root.path(), Slugify::default()).unwrap();
+        let flags = |name: &str| {
+            let page = set
+                .pages
+                .iter()
+                .find(|page| page.source_path.ends_with(name))
+                .unwrap();
+            (page.frontmatter.noindex, page.frontmatter.exclude_from_feed)
+        };
+        assert_eq!(flags("notes/a.md"), (Some(true), Some(true)));
+        assert_eq!(flags("notes/b.md"), (Some(false), Some(true)));
+        assert_eq!(flags("public/c/index.md"), (Some(true), Some(false)));
+        assert_eq!(flags("about.md"), (None, None));
+    }
+
+    #[test]
+    fn discover_content_invalid_cascade_returns_error() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "content/notes/_index.md",
+            "+++\n[cascade]\nnoindex = \"yes\"\n+++\n",
+        );
+        write_test_file(
+            root.path(),
+            "content/notes/a.md",
+            "+++\ntitle = \"A\"\n+++\n",
+        );
+
+        let err = discover_content(root.path(), Slugify::default()).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("invalid frontmatter in"),
+            "got: {err:#}"
+        );
+    }
+
+    #[test]
+    fn discover_content_reports_every_invalid_page() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "content/a.md", "+++\ntitle = 1\n+++\n");
+        write_test_file(root.path(), "content/b.md", "+++\ntitle = \"B\"\n+++\n");
+        write_test_file(root.path(), "content/c.md", "+++\ndate = \"soon\"\n+++\n");
+
+        let err = discover_content(root.path(), Slugify::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("failed to load 2 pages:"), "got: {err}");
+        assert!(err.contains("a.md") && err.contains("c.md"), "got: {err}");
+        assert!(!err.contains("b.md"), "got: {err}");
+    }
+
+    // ── discover_loadable_content ──
+
+    #[test]
+    fn discover_loadable_content_skips_invalid_pages() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "content/a.md", "+++\ntitle = 1\n+++\n");
+        write_test_file(root.path(), "content/b.md", "+++\ntitle = \"B\"\n+++\n");
+
+        let mut errors = PageErrors::default();
+        let set = discover_loadable_content(root.path(), Slugify::default(), &mut errors).unwrap();
+        let titles: Vec<_> = set.pages.iter().map(|p| &p.frontmatter.title).collect();
+        assert_eq!(titles, ["B"]);
+        let failed: Vec<_> = errors.iter().map(|(path, _)| path).collect();
+        assert_eq!(failed, [root.path().join("content/a.md")]);
+    }
+
+    // ── discover_passthrough ──
+
+    #[test]
+    fn discover_passthrough_matches_allowlisted_extensions() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "content/resume.PDF", "pdf");
+        write_test_file(root.path(), "content/notes/file.html", "<p>Hi</p>");
+        write_test_file(root.path(), "content/notes/skip.txt", "txt");
+        write_test_file(root.path(), "content/notes/_draft.html", "hidden");
+        write_test_file(root.path(), "content/posts/hello/index.md", "+++\n+++\n");
+        write_test_file(root.path(), "content/posts/hello/attachment.pdf", "bundle");
+
+        let content_dir = root.path().join("content");
+        let files = discover_passthrough(&content_dir, &["pdf".into(), ".html".into()]).unwrap();
+        assert_eq!(
+            files,
+            [
+                content_dir.join("notes/file.html"),
+                content_dir.join("resume.PDF"),
+            ],
+        );
+    }
+
+    #[test]
+    fn discover_passthrough_skips_html_pages() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "content/landing.html",
+            "+++\ntitle = \"L\"\n+++\n<p>L</p>",
+        );
+        write_test_file(root.path(), "content/demo/index.html", "+++\n+++\n<p>D</p>");
+        write_test_file(root.path(), "content/demo/frame.html", "<p>asset</p>");
+        write_test_file(root.path(), "content/raw.html", "<p>raw</p>");
+
+        let content_dir = root.path().join("content");
+        let files = discover_passthrough(&content_dir, &["html".into()]).unwrap();
+        assert_eq!(files, [content_dir.join("raw.html")]);
+    }
+
+    #[test]
+    fn discover_passthrough_empty_allowlist_returns_nothing() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "content/resume.pdf", "pdf");
+        let files = discover_passthrough(&root.path().join("content"), &[]).unwrap();
+        assert!(files.is_empty());
+    }
+
+    // ── discover_section_assets ──
+
+    #[test]
+    fn discover_section_assets_collects_nested_assets_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "content/posts/_assets/logo.png", "png");
+        write_test_file(root.path(), "content/posts/_assets/icons/rss.svg", "svg");
+        write_test_file(root.path(), "content/posts/rust/_assets/crab.png", "png");
+        write_test_file(root.path(), "content/posts/hello/index.md", "+++\n+++\n");
+        write_test_file(root.path(), "content/posts/hello/cover.png", "bundle");
+        write_test_file(root.path(), "content/_drafts/_assets/secret.png", "hidden");
+
+        let content_dir = root.path().join("content");
+        let files = discover_section_assets(&content_dir).unwrap();
+        assert_eq!(
+            files,
+            [
+                content_dir.join("posts/_assets/icons/rss.svg"),
+                content_dir.join("posts/_assets/logo.png"),
+                content_dir.join("posts/rust/_assets/crab.png"),
+            ],
+        );
+    }
+
+    #[test]
+    fn discover_section_assets_missing_content_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let files = discover_section_assets(&root.path().join("content")).unwrap();
+        assert!(files.is_empty());
+    }
 }
@@ -0,0 +1,282 @@
+#[cfg(feature = "asciidoc")]
+mod asciidoc;
+#[cfg(feature = "org")]
+mod org;
+
+use std::borrow::Cow;
+use std::path::Path;
+
+#[cfg(any(feature = "org", feature = "asciidoc"))]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(any(feature = "org", feature = "asciidoc"))]
+use jiff::{Timestamp, civil::DateTime, tz::TimeZone};
+
+use super::frontmatter::{self, Frontmatter};
+use crate::config::SlugifyMode;
+
+/// Source format of a content file, determined by its extension.
+///
+/// Every format accepts `+++` TOML frontmatter. Org and AsciiDoc files may
+/// instead carry their native document header (`#+title:` keywords,
+/// `= Title` plus `:attr:` entries), and their bodies are converted to
+/// markdown on load so they share the rest of the render pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentFormat {
+    /// Markdown (`.md`), rendered through the full pipeline.
+    Markdown,
+    /// Hand-crafted HTML (`.html`), inserted into the page layout as-is.
+    Html,
+    /// Org-mode (`.org`), converted to markdown.
+    #[cfg(feature = "org")]
+    Org,
+    /// AsciiDoc (`.adoc`), converted to markdown.
+    #[cfg(feature = "asciidoc")]
+    AsciiDoc,
+}
+
+impl ContentFormat {
+    /// Returns the format of a content file, or `None` for other extensions.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "md" => Some(Self::Markdown),
+            "html" => Some(Self::Html),
+            #[cfg(feature = "org")]
+            "org" => Some(Self::Org),
+            #[cfg(feature = "asciidoc")]
+            "adoc" | "asciidoc" => Some(Self::AsciiDoc),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if `content` carries page metadata in a form this
+    /// format understands. Files without it are not treated as pages.
+    #[must_use]
+    pub fn has_metadata(self, content: &str) -> bool {
+        if has_toml_frontmatter(content) {
+            return true;
+        }
+        match self {
+            Self::Markdown | Self::Html => false,
+            #[cfg(feature = "org")]
+            Self::Org => org::has_header(content),
+            #[cfg(feature = "asciidoc")]
+            Self::AsciiDoc => asciidoc::has_header(content),
+        }
+    }
+
+    /// Splits a content file into its frontmatter and body, converting the
+    /// body to markdown for Org and AsciiDoc sources. Org heading links
+    /// (`[[*Heading]]`) point at IDs generated under `slugify_mode`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frontmatter or native header is invalid.
+    #[cfg_attr(
+        not(feature = "org"),
+        expect(unused_variables, reason = "only Org links point at heading IDs")
+    )]
+    pub(crate) fn parse(
+        self,
+        content: &str,
+        slugify_mode: SlugifyMode,
+    ) -> Result<(Frontmatter, Cow<'_, str>)> {
+        match self {
+            Self::Markdown | Self::Html => {
+                frontmatter::parse(content).map(|(fm, body)| (fm, Cow::Borrowed(body)))
+            }
+            #[cfg(feature = "org")]
+            Self::Org => parse_converted(content, org::split_header, |body| {
+                org::to_markdown(body, slugify_mode)
+            }),
+            #[cfg(feature = "asciidoc")]
+            Self::AsciiDoc => {
+                parse_converted(content, asciidoc::split_header, asciidoc::to_markdown)
+            }
+        }
+    }
+}
+
+/// Returns `true` if `content` starts with a `+++` delimiter (optionally
+/// preceded by a UTF-8 BOM).
+fn has_toml_frontmatter(content: &str) -> bool {
+    content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(content)
+        .starts_with("+++")
+}
+
+/// Reads metadata from TOML frontmatter when present, otherwise from the
+/// native header, then converts the body with `to_markdown`.
+#[cfg(any(feature = "org", feature = "asciidoc"))]
+fn parse_converted(
+    content: &str,
+    split_header: fn(&str) -> (NativeHeader, &str),
+    to_markdown: impl FnOnce(&str) -> String,
+) -> Result<(Frontmatter, Cow<'static, str>)> {
+    let (frontmatter, body) = if has_toml_frontmatter(content) {
+        frontmatter::parse(content)?
+    } else {
+        let (header, body) = split_header(content);
+        (header.into_frontmatter()?, body)
+    };
+    Ok((frontmatter, Cow::Owned(to_markdown(body))))
+}
+
+/// Frontmatter fields gathered from a native document header, keyed by kiln
+/// frontmatter name (`title`, `date`, `tags`, ...).
+#[derive(Debug, Default)]
+#[cfg(any(feature = "org", feature = "asciidoc"))]
+struct NativeHeader {
+    fields: toml::Table,
+}
+
+#[cfg(any(feature = "org", feature = "asciidoc"))]
+impl NativeHeader {
+    /// Records a field, coercing dates, `draft`, and `weight` to the types
+    /// frontmatter expects. Later values for the same key win.
+    fn insert(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        let value = match key {
+            "date" | "updated" => toml::Value::String(normalize_date(value)),
+            "draft" => toml::Value::Boolean(matches!(
+                value.to_ascii_lowercase().as_str(),
+                "t" | "true" | "yes" | "1" | ""
+            )),
+            "weight" => value
+                .parse()
+                .map_or_else(|_| value.into(), toml::Value::Integer),
+            _ => value.into(),
+        };
+        self.fields.insert(key.to_owned(), value);
+    }
+
+    /// Records the page tags, skipping empty entries.
+    fn insert_tags<'a>(&mut self, tags: impl IntoIterator<Item = &'a str>) {
+        let tags: Vec<toml::Value> = tags
+            .into_iter()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(Into::into)
+            .collect();
+        self.fields.insert("tags".to_owned(), tags.into());
+    }
+
+    fn into_frontmatter(self) -> Result<Frontmatter> {
+        toml::Value::Table(self.fields)
+            .try_into()
+            .context("invalid document header")
+    }
+}
+
+/// Normalizes a header date to an RFC 3339 timestamp.
+///
+/// Accepts full timestamps as-is, plus the offset-less forms Org and
+/// AsciiDoc headers typically use (`2024-01-15`, `<2024-01-15 Mon 10:30>`),
+/// which are read as UTC. Anything else is returned unchanged so that
+/// frontmatter deserialization reports it.
+#[cfg(any(feature = "org", feature = "asciidoc"))]
+fn normalize_date(value: &str) -> String {
+    if value.parse::<Timestamp>().is_ok() {
+        return value.to_owned();
+    }
+
+    let inner = value.trim_matches(['<', '>', '[', ']']);
+    let mut parts = inner
+        .split_whitespace()
+        .filter(|p| !p.chars().all(char::is_alphabetic));
+    let civil = match (parts.next(), parts.next()) {
+        (Some(date), Some(time)) => format!("{date}T{time}"),
+        (Some(date), None) if !date.contains('T') => format!("{date}T00:00"),
+        (Some(date), None) => date.to_owned(),
+        _ => return value.to_owned(),
+    };
+
+    civil
+        .parse::<DateTime>()
+        .and_then(|dt| dt.to_zoned(TimeZone::UTC))
+        .map_or_else(|_| value.to_owned(), |zdt| zdt.timestamp().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── ContentFormat::from_path ──
+
+    #[test]
+    fn from_path_recognizes_extensions() {
+        assert_eq!(
+            ContentFormat::from_path(Path::new("a.md")),
+            Some(ContentFormat::Markdown)
+        );
+        assert_eq!(
+            ContentFormat::from_path(Path::new("a.html")),
+            Some(ContentFormat::Html)
+        );
+        assert_eq!(ContentFormat::from_path(Path::new("a.txt")), None);
+    }
+
+    // ── ContentFormat::has_metadata ──
+
+    #[test]
+    fn has_metadata_requires_frontmatter_for_markdown() {
+        assert!(ContentFormat::Markdown.has_metadata("\u{feff}+++\n+++\n"));
+        assert!(!ContentFormat::Html.has_metadata("<p>Hi</p>"));
+    }
+
+    // ── ContentFormat::parse ──
+
+    #[cfg(feature = "org")]
+    #[test]
+    fn parse_org_accepts_toml_frontmatter() {
+        let (fm, body) = ContentFormat::Org
+            .parse("+++\ntitle = \"T\"\n+++\n* Heading\n", SlugifyMode::Safe)
+            .unwrap();
+        assert_eq!(fm.title, "T");
+        assert_eq!(body, "# Heading\n");
+    }
+
+    // ── NativeHeader ──
+
+    #[cfg(any(feature = "org", feature = "asciidoc"))]
+    #[test]
+    fn native_header_coerces_fields() {
+        let mut header = NativeHeader::default();
+        header.insert("title", " Hello ");
+        header.insert("date", "<2024-01-15 Mon 10:30>");
+        header.insert("draft", "t");
+        header.insert("weight", "3");
+        header.insert_tags(["a", " ", "b "]);
+
+        let fm = header.into_frontmatter().unwrap();
+        assert_eq!(fm.title, "Hello");
+        assert_eq!(fm.date.unwrap().to_string(), "2024-01-15T10:30:00Z");
+        assert!(fm.draft);
+        assert_eq!(fm.weight, Some(3));
+        assert_eq!(fm.tags, ["a", "b"]);
+    }
+
+    #[cfg(any(feature = "org", feature = "asciidoc"))]
+    #[test]
+    fn native_header_invalid_date_returns_error() {
+        let mut header = NativeHeader::default();
+        header.insert("date", "someday");
+        let err = format!("{:#}", header.into_frontmatter().unwrap_err());
+        assert!(err.contains("someday"), "got: {err}");
+    }
+
+    // ── normalize_date ──
+
+    #[cfg(any(feature = "org", feature = "asciidoc"))]
+    #[test]
+    fn normalize_date_forms() {
+        assert_eq!(normalize_date("2024-01-15"), "2024-01-15T00:00:00Z");
+        assert_eq!(
+            normalize_date("2024-01-15T10:30:00+08:00"),
+            "2024-01-15T10:30:00+08:00"
+        );
+        assert_eq!(normalize_date("[2024-01-15 Mon]"), "2024-01-15T00:00:00Z");
+    }
+}
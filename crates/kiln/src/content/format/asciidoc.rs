@@ -0,0 +1,572 @@
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::NativeHeader;
+use crate::directive::CalloutKind;
+use crate::markdown::{push_code_block, push_directive};
+use crate::render::alert::alert_kind;
+
+/// `:name: value` attribute entry.
+static ATTRIBUTE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^:([\w-]+!?):\s*(.*)$").expect("attribute regex should compile"));
+
+/// Section title: `=` marks (level 0 to 5) and the title.
+static SECTION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(={1,6})\s+(.+?)\s*$").expect("section regex should compile"));
+
+/// Unordered (`*`, `-`) or ordered (`.`) list item.
+static LIST_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\*{1,5}|-|\.{1,5})\s+(.*)$").expect("list regex should compile")
+});
+
+/// Admonition paragraph: `NOTE: text`.
+static ADMONITION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(NOTE|TIP|IMPORTANT|WARNING|CAUTION):\s+(.*)$")
+        .expect("admonition regex should compile")
+});
+
+/// Inline macros and URLs with a `[text]` suffix, plus cross references.
+static MACRO_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:(image):([^\s\[\]]+)\[([^\],]*)[^\]]*\]|(?:link:|xref:)?((?:https?://)?[^\s\[\]]+)\[([^\]]*)\]|<<([^,>]+)(?:,\s*([^>]+))?>>)",
+    )
+    .expect("macro regex should compile")
+});
+
+/// Returns `true` if the document starts with a `= Title` header line.
+pub(super) fn has_header(content: &str) -> bool {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    content
+        .lines()
+        .find(|l| !l.trim().is_empty() && !l.starts_with("//"))
+        .is_some_and(|l| l.starts_with("= "))
+}
+
+/// Reads the document header (`= Title`, author / revision lines, and
+/// `:name: value` attributes up to the first blank line) into a
+/// [`NativeHeader`] and returns it with the remaining body.
+///
+/// Recognized attributes: `description`, `slug`, `tags` / `keywords`
+/// (comma-separated), `revdate` / `date`, `updated`, `draft`, `weight`, and
+/// `license`. Without a `= Title` line the whole input is body.
+pub(super) fn split_header(content: &str) -> (NativeHeader, &str) {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut header = NativeHeader::default();
+    if !has_header(content) {
+        return (header, content);
+    }
+
+    let mut len = 0;
+    let mut seen_title = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if seen_title && trimmed.is_empty() {
+            len += line.len();
+            break;
+        }
+        len += line.len();
+
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
+        if !seen_title {
+            seen_title = true;
+            header.insert("title", &trimmed[2..]);
+        } else if let Some(caps) = ATTRIBUTE_RE.captures(trimmed) {
+            let value = &caps[2];
+            match caps[1].to_ascii_lowercase().as_str() {
+                key @ ("description" | "slug" | "updated" | "draft" | "weight" | "license") => {
+                    header.insert(key, value);
+                }
+                "revdate" | "date" => header.insert("date", value),
+                "tags" | "keywords" => header.insert_tags(value.split(',')),
+                _ => {}
+            }
+        }
+    }
+
+    (header, &content[len..])
+}
+
+/// Converts an AsciiDoc body to markdown.
+///
+/// Handles section titles, constrained / unconstrained formatting, links,
+/// images, cross references, lists, simple tables, admonition paragraphs,
+/// block titles, and delimited blocks: listing / literal become code fences
+/// (using a preceding `[source,lang]`), quote blocks blockquotes, passthrough
+/// blocks raw HTML, `[NOTE]`-style example blocks callouts, and sidebars a
+/// `::: sidebar` directive. Comments and other block attributes are dropped.
+pub(super) fn to_markdown(body: &str) -> String {
+    let lines: Vec<&str> = body.lines().collect();
+    let mut output = String::with_capacity(body.len());
+    convert_lines(&lines, &mut output);
+    output
+}
+
+fn convert_lines(lines: &[&str], output: &mut String) {
+    let mut attrs: Option<&str> = None;
+    let mut title: Option<&str> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+
+        if let Some(delimiter) = block_delimiter(line)
+            && let Some(offset) = lines[i + 1..].iter().position(|l| l.trim_end() == line)
+        {
+            let end = i + 1 + offset;
+            push_block(
+                output,
+                delimiter,
+                attrs.take(),
+                title.take(),
+                &lines[i + 1..end],
+            );
+            i = end + 1;
+            continue;
+        }
+        i += 1;
+
+        if line.starts_with('[') && line.ends_with(']') && !line.starts_with("[[") {
+            attrs = Some(&line[1..line.len() - 1]);
+            continue;
+        }
+        if line.len() > 1
+            && line.starts_with('.')
+            && !line.starts_with("..")
+            && !line[1..].starts_with(char::is_whitespace)
+        {
+            title = Some(&line[1..]);
+            continue;
+        }
+        if line.starts_with("//") {
+            continue;
+        }
+        if line.starts_with("|===") {
+            let end = lines[i..]
+                .iter()
+                .position(|l| l.trim_end().starts_with("|==="))
+                .map_or(lines.len(), |offset| i + offset);
+            push_table(output, &lines[i..end]);
+            i = (end + 1).min(lines.len());
+            continue;
+        }
+        if let Some(caps) = ADMONITION_RE.captures(line) {
+            let mut body = caps[2].to_owned();
+            while i < lines.len() && !lines[i].trim().is_empty() {
+                body.push('\n');
+                body.push_str(lines[i]);
+                i += 1;
+            }
+            let kind = alert_kind(&caps[1]).unwrap_or(CalloutKind::Note);
+            push_callout(output, kind, title.take(), &to_markdown(&body));
+            continue;
+        }
+
+        if let Some(title) = title.take() {
+            _ = writeln!(output, "**{}**\n", convert_inline(title));
+        }
+        attrs = None;
+        convert_line(line, output);
+        output.push('\n');
+    }
+}
+
+/// Converts a single line outside any block.
+fn convert_line(line: &str, output: &mut String) {
+    if let Some(caps) = SECTION_RE.captures(line) {
+        _ = write!(
+            output,
+            "{} {}",
+            "#".repeat(caps[1].len()),
+            convert_inline(&caps[2])
+        );
+    } else if line == "'''" || line == "---" || line == "***" {
+        output.push_str("---");
+    } else if line == "<<<" {
+        // Page breaks have no web equivalent.
+    } else if let Some(rest) = line.strip_prefix("image::") {
+        output.push_str(&convert_inline(&format!("image:{rest}")));
+    } else if let Some(caps) = LIST_RE.captures(line) {
+        let marker = &caps[1];
+        let indent = "  ".repeat(marker.len() - 1);
+        let bullet = if marker.starts_with('.') { "1." } else { "-" };
+        let text = &caps[2];
+        let text = match text.get(..4) {
+            Some("[ ] ") => format!("[ ] {}", convert_inline(&text[4..])),
+            Some("[x] " | "[*] ") => format!("[x] {}", convert_inline(&text[4..])),
+            _ => convert_inline(text),
+        };
+        _ = write!(output, "{indent}{bullet} {text}");
+    } else if let Some((term, desc)) = line.split_once(":: ")
+        && !term.contains(char::is_whitespace)
+        && !term.is_empty()
+    {
+        _ = write!(
+            output,
+            "- **{}**: {}",
+            convert_inline(term),
+            convert_inline(desc)
+        );
+    } else {
+        match line.strip_suffix(" +") {
+            Some(text) => _ = write!(output, "{}\\", convert_inline(text)),
+            None => output.push_str(&convert_inline(line)),
+        }
+    }
+}
+
+/// Returns the delimiter character of a delimited block opener (four or more
+/// of `-`, `.`, `_`, `=`, `*`, `+`, or `/`).
+fn block_delimiter(line: &str) -> Option<u8> {
+    let first = *line.as_bytes().first()?;
+    (line.len() >= 4
+        && matches!(first, b'-' | b'.' | b'_' | b'=' | b'*' | b'+' | b'/')
+        && line.bytes().all(|b| b == first))
+    .then_some(first)
+}
+
+fn push_block(
+    output: &mut String,
+    delimiter: u8,
+    attrs: Option<&str>,
+    title: Option<&str>,
+    inner: &[&str],
+) {
+    let verbatim = inner.iter().fold(String::new(), |mut acc, line| {
+        _ = writeln!(acc, "{line}");
+        acc
+    });
+    let style = attrs
+        .and_then(|a| a.split(',').next())
+        .map(|s| s.trim().to_ascii_lowercase());
+
+    match delimiter {
+        b'-' | b'.' => {
+            let lang = attrs
+                .filter(|_| {
+                    style
+                        .as_deref()
+                        .is_some_and(|s| s == "source" || s.is_empty())
+                })
+                .and_then(|a| a.split(',').nth(1))
+                .map_or("", str::trim);
+            let info = match title {
+                Some(title) => format!("{lang} title={}", quote_attr(title)),
+                None => lang.to_owned(),
+            };
+            push_code_block(output, &info, &verbatim);
+        }
+        b'+' => output.push_str(&verbatim),
+        b'/' => {}
+        b'_' => {
+            for line in to_markdown(&verbatim).lines() {
+                output.push('>');
+                if !line.is_empty() {
+                    _ = write!(output, " {line}");
+                }
+                output.push('\n');
+            }
+        }
+        b'=' => {
+            let kind = style
+                .as_deref()
+                .and_then(alert_kind)
+                .unwrap_or(CalloutKind::Example);
+            push_callout(output, kind, title, &to_markdown(&verbatim));
+        }
+        _ => push_directive(output, "sidebar", &to_markdown(&verbatim)),
+    }
+}
+
+/// Appends a `::: callout` for an admonition or example block.
+fn push_callout(output: &mut String, kind: CalloutKind, title: Option<&str>, body: &str) {
+    let mut header = format!("callout {{type={}", kind.as_ref());
+    if let Some(title) = title {
+        _ = write!(header, " title={}", quote_attr(title));
+    }
+    header.push('}');
+    push_directive(output, &header, body);
+}
+
+/// Quotes an attribute value, escaping `\` and `"`.
+fn quote_attr(value: &str) -> String {
+    format!(r#""{}""#, value.replace('\\', r"\\").replace('"', r#"\""#))
+}
+
+/// Appends a markdown table from the lines between `|===` delimiters.
+///
+/// The column count comes from the first row; cells on following lines
+/// are regrouped into rows of that width. The first row is the header.
+fn push_table(output: &mut String, lines: &[&str]) {
+    let mut columns = 0;
+    let mut cells = Vec::new();
+    for line in lines
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| l.starts_with('|'))
+    {
+        let row: Vec<String> = line[1..]
+            .split('|')
+            .map(|c| convert_inline(c.trim()))
+            .collect();
+        if columns == 0 {
+            columns = row.len();
+        }
+        cells.extend(row);
+    }
+    if columns == 0 {
+        return;
+    }
+
+    for (index, row) in cells.chunks(columns).enumerate() {
+        _ = writeln!(output, "| {} |", row.join(" | "));
+        if index == 0 {
+            _ = writeln!(output, "|{}", "---|".repeat(columns));
+        }
+    }
+}
+
+/// Converts AsciiDoc inline formatting, links, images, and cross references
+/// to markdown.
+fn convert_inline(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        let at_boundary = text[..i]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+
+        if at_boundary && let Some(caps) = MACRO_RE.captures(rest) {
+            let is_macro = caps.get(1).is_some()
+                || caps.get(6).is_some()
+                || caps[0].starts_with("link:")
+                || caps[0].starts_with("xref:")
+                || caps[0].starts_with("http");
+            if is_macro {
+                if caps.get(1).is_some() {
+                    _ = write!(output, "![{}]({})", &caps[3], &caps[2]);
+                } else if let Some(id) = caps.get(6) {
+                    let label = caps.get(7).map_or(id.as_str(), |m| m.as_str());
+                    _ = write!(output, "[{label}](#{})", id.as_str());
+                } else {
+                    let target = &caps[4];
+                    let label = if caps[5].is_empty() { target } else { &caps[5] };
+                    _ = write!(output, "[{}]({target})", convert_inline(label));
+                }
+                i += caps[0].len();
+                continue;
+            }
+        }
+
+        let marker = rest.as_bytes()[0];
+        if matches!(marker, b'*' | b'_' | b'`' | b'#')
+            && let Some((inner, len)) = find_formatted(text, i)
+        {
+            match marker {
+                b'`' => _ = write!(output, "`{inner}`"),
+                b'*' => _ = write!(output, "**{}**", convert_inline(inner)),
+                b'_' => _ = write!(output, "*{}*", convert_inline(inner)),
+                _ => _ = write!(output, "<mark>{}</mark>", convert_inline(inner)),
+            }
+            i += len;
+            continue;
+        }
+
+        let ch = rest.chars().next().expect("rest should be non-empty");
+        output.push(ch);
+        i += ch.len_utf8();
+    }
+
+    output
+}
+
+/// Matches formatted text opened at `start`, returning the inner text and
+/// the total span length.
+///
+/// A doubled marker (`**bold**`) is unconstrained and may appear mid-word. A
+/// single marker (`*bold*`) is constrained: it must follow a non-word
+/// character, enclose text that does not start or end with whitespace, and
+/// close before a non-word character.
+fn find_formatted(text: &str, start: usize) -> Option<(&str, usize)> {
+    let marker = &text[start..=start];
+    let doubled = marker.repeat(2);
+
+    if text[start..].starts_with(&doubled) {
+        let inner_start = start + 2;
+        let close = text[inner_start..].find(&doubled)?;
+        return (close > 0).then(|| (&text[inner_start..inner_start + close], close + 4));
+    }
+
+    let prev = text[..start].chars().next_back();
+    if prev.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let inner_start = start + 1;
+    if text[inner_start..]
+        .chars()
+        .next()
+        .is_none_or(char::is_whitespace)
+    {
+        return None;
+    }
+
+    let mut j = inner_start + 1;
+    while let Some(offset) = text.get(j..).and_then(|r| r.find(marker)) {
+        let close = j + offset;
+        let next = text[close + 1..].chars().next();
+        if !text[..close].ends_with(char::is_whitespace)
+            && next.is_none_or(|c| !c.is_alphanumeric() && c != '_')
+        {
+            return Some((&text[inner_start..close], close + 1 - start));
+        }
+        j = close + 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── split_header ──
+
+    #[test]
+    fn split_header_reads_title_and_attributes() {
+        let content = indoc! {"
+            // leading comment
+            = Hello AsciiDoc
+            Jane Doe <jane@example.com>
+            :revdate: 2024-01-15
+            :tags: rust, docs
+            :description: A test page.
+            :toc: left
+
+            First paragraph.
+        "};
+        assert!(has_header(content));
+
+        let (header, body) = split_header(content);
+        let fm = header.into_frontmatter().unwrap();
+        assert_eq!(fm.title, "Hello AsciiDoc");
+        assert_eq!(fm.date.unwrap().to_string(), "2024-01-15T00:00:00Z");
+        assert_eq!(fm.tags, ["rust", "docs"]);
+        assert_eq!(fm.description.as_deref(), Some("A test page."));
+        assert_eq!(body, "First paragraph.\n");
+    }
+
+    #[test]
+    fn has_header_requires_document_title() {
+        assert!(!has_header("== Section\n\nText\n"));
+        assert!(!has_header("Text\n= Late\n"));
+    }
+
+    // ── to_markdown ──
+
+    #[test]
+    fn to_markdown_structure() {
+        let body = indoc! {"
+            == Intro
+            Some *bold*, _italic_, `code`, #marked# and **un**constrained.
+            See https://example.com[the site], link:/about[About], <<intro,here>>.
+            image::cat.png[A cat,200]
+
+            * one
+            ** nested
+            . first
+            * [x] done
+            CPU:: Processor
+            // comment
+            '''
+            Line one +
+            line two
+        "};
+        assert_eq!(
+            to_markdown(body),
+            indoc! {"
+                ## Intro
+                Some **bold**, *italic*, `code`, <mark>marked</mark> and **un**constrained.
+                See [the site](https://example.com), [About](/about), [here](#intro).
+                ![A cat](cat.png)
+
+                - one
+                  - nested
+                1. first
+                - [x] done
+                - **CPU**: Processor
+                ---
+                Line one\\
+                line two
+            "},
+        );
+    }
+
+    #[test]
+    fn to_markdown_blocks() {
+        let body = indoc! {"
+            .src/main.rs
+            [source,rust]
+            ----
+            fn main() {}
+            ----
+
+            NOTE: Remember *this*.
+
+            .Careful
+            [WARNING]
+            ====
+            Hot surface.
+            ====
+
+            ____
+            Quoted _text_.
+            ____
+
+            ++++
+            <div class=\"raw\"></div>
+            ++++
+
+            |===
+            | Name | Value
+            | a | 1
+            |===
+        "};
+        assert_eq!(
+            to_markdown(body),
+            indoc! {r#"
+                ```rust title="src/main.rs"
+                fn main() {}
+                ```
+
+                ::: callout {type=note}
+                Remember **this**.
+                :::
+
+                ::: callout {type=warning title="Careful"}
+                Hot surface.
+                :::
+
+                > Quoted *text*.
+
+                <div class="raw"></div>
+
+                | Name | Value |
+                |---|---|
+                | a | 1 |
+            "#},
+        );
+    }
+
+    #[test]
+    fn to_markdown_leaves_non_formatting_alone() {
+        let body = "snake_case_name, 2 * 3 * 4, a#b#c\n";
+        assert_eq!(to_markdown(body), body);
+    }
+}
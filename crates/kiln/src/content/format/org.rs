@@ -0,0 +1,546 @@
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::NativeHeader;
+use crate::config::SlugifyMode;
+use crate::markdown::{push_code_block, push_directive};
+use crate::render::alert::alert_kind;
+use crate::text::slugify;
+
+/// `#+KEY: value` keyword line.
+static KEYWORD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*#\+([A-Za-z_]+):\s*(.*)$").expect("keyword regex should compile")
+});
+
+/// `#+begin_NAME args` block opener.
+static BLOCK_BEGIN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^\s*#\+begin_(\w+)(?:\s+(.*))?$").expect("block regex should compile")
+});
+
+/// Headline: stars, title, optional trailing `:tag1:tag2:`.
+static HEADLINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\*+)\s+(.*?)(?:\s+:[\w@#%:]+:)?\s*$").expect("headline regex should compile")
+});
+
+/// Plain or ordered list item. A leading `*` only counts when indented,
+/// since a column-0 `*` is a headline.
+static LIST_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\s*)([-+]|\d+[.)])\s+(.*)$|^(\s+)\*\s+(.*)$").expect("list regex should compile")
+});
+
+/// Returns `true` if the leading keyword block sets `#+title:`.
+pub(super) fn has_header(content: &str) -> bool {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    content[..header_len(content)]
+        .lines()
+        .filter_map(|l| KEYWORD_RE.captures(l))
+        .any(|caps| caps[1].eq_ignore_ascii_case("title"))
+}
+
+/// Reads the leading `#+KEY: value` keywords into a [`NativeHeader`] and
+/// returns it with the remaining body.
+///
+/// Recognized keys: `title`, `date`, `description`, `filetags` / `tags`,
+/// `slug`, `draft`, `updated` (or `lastmod` / `last_modified`), `weight`,
+/// and `license`. Others (`author`, `options`, ...) are ignored.
+pub(super) fn split_header(content: &str) -> (NativeHeader, &str) {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let len = header_len(content);
+    let mut header = NativeHeader::default();
+
+    for caps in content[..len]
+        .lines()
+        .filter_map(|l| KEYWORD_RE.captures(l))
+    {
+        let value = &caps[2];
+        match caps[1].to_ascii_lowercase().as_str() {
+            key @ ("title" | "date" | "description" | "slug" | "draft" | "weight" | "license") => {
+                header.insert(key, value);
+            }
+            "updated" | "lastmod" | "last_modified" => header.insert("updated", value),
+            "filetags" | "tags" => header.insert_tags(value.split([':', ' '])),
+            _ => {}
+        }
+    }
+
+    (header, &content[len..])
+}
+
+/// Byte length of the leading run of keyword, comment, and blank lines.
+fn header_len(content: &str) -> usize {
+    let mut len = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let is_header = trimmed.is_empty()
+            || is_comment(trimmed)
+            || (KEYWORD_RE.is_match(trimmed) && !BLOCK_BEGIN_RE.is_match(trimmed));
+        if !is_header {
+            break;
+        }
+        len += line.len();
+    }
+    len
+}
+
+/// Converts an Org body to markdown.
+///
+/// Handles headlines, emphasis, links, lists (including checkboxes and
+/// description items), tables, rules, fixed-width lines, and blocks:
+/// `src` / `example` become code fences, `quote` / `verse` blockquotes,
+/// `export html` raw HTML, admonition names (`note`, `warning`, ...) callouts,
+/// and any other special block a `::: name` directive. Comments, drawers,
+/// and in-body keywords are dropped.
+pub(super) fn to_markdown(body: &str, slugify_mode: SlugifyMode) -> String {
+    let lines: Vec<&str> = body.lines().collect();
+    let mut output = String::with_capacity(body.len());
+    convert_lines(&lines, &mut output, slugify_mode);
+    output
+}
+
+fn convert_lines(lines: &[&str], output: &mut String, slugify_mode: SlugifyMode) {
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if let Some(caps) = BLOCK_BEGIN_RE.captures(line) {
+            let name = caps[1].to_ascii_lowercase();
+            if let Some(end) = find_block_end(lines, i, &name) {
+                let args = caps.get(2).map_or("", |m| m.as_str().trim());
+                push_block(output, &name, args, &lines[i + 1..end], slugify_mode);
+                i = end + 1;
+                continue;
+            }
+        }
+
+        if is_drawer_start(trimmed)
+            && let Some(end) = lines[i..]
+                .iter()
+                .position(|l| l.trim().eq_ignore_ascii_case(":END:"))
+        {
+            i += end + 1;
+            continue;
+        }
+
+        if is_fixed_width(trimmed) {
+            let run = lines[i..]
+                .iter()
+                .take_while(|l| is_fixed_width(l.trim()))
+                .count();
+            let code = lines[i..i + run].iter().fold(String::new(), |mut acc, l| {
+                let t = l.trim_start();
+                _ = writeln!(acc, "{}", t.strip_prefix(": ").unwrap_or(&t[1..]));
+                acc
+            });
+            push_code_block(output, "", &code);
+            i += run;
+            continue;
+        }
+
+        i += 1;
+        if is_comment(trimmed) || KEYWORD_RE.is_match(trimmed) {
+            continue;
+        }
+        convert_line(line, output, slugify_mode);
+        output.push('\n');
+    }
+}
+
+/// Converts a single line outside any block.
+fn convert_line(line: &str, output: &mut String, slugify_mode: SlugifyMode) {
+    let trimmed = line.trim();
+
+    if let Some(caps) = HEADLINE_RE.captures(line) {
+        let level = caps[1].len().min(6);
+        _ = write!(
+            output,
+            "{} {}",
+            "#".repeat(level),
+            convert_inline(&caps[2], slugify_mode)
+        );
+    } else if trimmed.len() >= 5 && trimmed.bytes().all(|b| b == b'-') {
+        output.push_str("---");
+    } else if trimmed.starts_with("|-") {
+        output.push_str(&trimmed.replace('+', "|"));
+    } else if let Some(caps) = LIST_RE.captures(line) {
+        let (indent, marker, text) = match caps.get(1) {
+            Some(indent) => (indent.as_str(), &caps[2], &caps[3]),
+            None => (&caps[4], "-", &caps[5]),
+        };
+        let marker = match marker.strip_suffix(['.', ')']) {
+            Some(number) => format!("{number}."),
+            None => "-".to_owned(),
+        };
+        _ = write!(
+            output,
+            "{indent}{marker} {}",
+            convert_item(text, slugify_mode)
+        );
+    } else {
+        let (text, hard_break) = match line.trim_end().strip_suffix(r"\\") {
+            Some(text) => (text, true),
+            None => (line, false),
+        };
+        output.push_str(&convert_inline(text, slugify_mode));
+        if hard_break {
+            output.push('\\');
+        }
+    }
+}
+
+/// Converts list item text: checkboxes and `term :: description` items.
+fn convert_item(text: &str, slugify_mode: SlugifyMode) -> String {
+    let (checkbox, text) = match text.get(..4) {
+        Some("[ ] " | "[-] ") => ("[ ] ", &text[4..]),
+        Some("[X] " | "[x] ") => ("[x] ", &text[4..]),
+        _ => ("", text),
+    };
+    match text.split_once(" :: ") {
+        Some((term, desc)) => format!(
+            "{checkbox}**{}**: {}",
+            convert_inline(term, slugify_mode),
+            convert_inline(desc, slugify_mode)
+        ),
+        None => format!("{checkbox}{}", convert_inline(text, slugify_mode)),
+    }
+}
+
+/// Finds the line index of the `#+end_NAME` matching the block opened at
+/// `start`, accounting for nested blocks of the same name.
+fn find_block_end(lines: &[&str], start: usize, name: &str) -> Option<usize> {
+    let begin = format!("#+begin_{name}");
+    let end = format!("#+end_{name}");
+    let mut depth = 0;
+    for (offset, line) in lines[start + 1..].iter().enumerate() {
+        let lower = line.trim().to_ascii_lowercase();
+        if lower == end {
+            if depth == 0 {
+                return Some(start + 1 + offset);
+            }
+            depth -= 1;
+        } else if lower.starts_with(&begin)
+            && lower[begin.len()..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace)
+        {
+            depth += 1;
+        }
+    }
+    None
+}
+
+fn push_block(
+    output: &mut String,
+    name: &str,
+    args: &str,
+    inner: &[&str],
+    slugify_mode: SlugifyMode,
+) {
+    let verbatim = || -> String {
+        inner.iter().fold(String::new(), |mut acc, l| {
+            // Org escapes block-significant lines with a leading comma.
+            let unescaped = match l.trim_start().strip_prefix(',') {
+                Some(rest) if rest.starts_with('*') || rest.starts_with("#+") => rest,
+                _ => l,
+            };
+            _ = writeln!(acc, "{unescaped}");
+            acc
+        })
+    };
+    let converted = || to_markdown(&inner.join("\n"), slugify_mode);
+
+    match name {
+        "src" => {
+            let lang = args.split_whitespace().next().unwrap_or("");
+            push_code_block(output, lang, &verbatim());
+        }
+        "example" => push_code_block(output, "", &verbatim()),
+        "export" => {
+            if args.eq_ignore_ascii_case("html") {
+                output.push_str(&verbatim());
+            }
+        }
+        "quote" | "verse" => {
+            for line in converted().lines() {
+                output.push('>');
+                if !line.is_empty() {
+                    _ = write!(output, " {line}");
+                }
+                output.push('\n');
+            }
+        }
+        "comment" => {}
+        _ => match alert_kind(name) {
+            Some(kind) => push_directive(
+                output,
+                &format!("callout {{type={}}}", kind.as_ref()),
+                &converted(),
+            ),
+            None => push_directive(output, name, &converted()),
+        },
+    }
+}
+
+/// Returns `true` for `# comment` lines (a lone `#` included).
+fn is_comment(trimmed: &str) -> bool {
+    trimmed == "#" || trimmed.starts_with("# ")
+}
+
+/// Returns `true` for `: text` fixed-width lines (a lone `:` included).
+fn is_fixed_width(trimmed: &str) -> bool {
+    trimmed == ":" || trimmed.starts_with(": ")
+}
+
+/// Returns `true` for a drawer opener such as `:PROPERTIES:` or `:LOGBOOK:`.
+fn is_drawer_start(trimmed: &str) -> bool {
+    trimmed.len() > 2
+        && trimmed.starts_with(':')
+        && trimmed.ends_with(':')
+        && trimmed[1..trimmed.len() - 1]
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b == b'_' || b == b'-')
+}
+
+/// Converts Org inline markup (emphasis, verbatim, links) to markdown.
+fn convert_inline(text: &str, slugify_mode: SlugifyMode) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        if let Some((len, link)) = convert_link(rest, slugify_mode) {
+            output.push_str(&link);
+            i += len;
+            continue;
+        }
+
+        let marker = rest.as_bytes()[0];
+        if matches!(marker, b'*' | b'/' | b'_' | b'=' | b'~' | b'+')
+            && let Some(end) = find_emphasis_end(text, i)
+        {
+            let inner = &text[i + 1..end];
+            match marker {
+                b'=' | b'~' if inner.contains('`') => _ = write!(output, "`` {inner} ``"),
+                b'=' | b'~' => _ = write!(output, "`{inner}`"),
+                b'*' => _ = write!(output, "**{}**", convert_inline(inner, slugify_mode)),
+                b'/' => _ = write!(output, "*{}*", convert_inline(inner, slugify_mode)),
+                b'_' => _ = write!(output, "<u>{}</u>", convert_inline(inner, slugify_mode)),
+                _ => _ = write!(output, "~~{}~~", convert_inline(inner, slugify_mode)),
+            }
+            i = end + 1;
+            continue;
+        }
+
+        let ch = rest.chars().next().expect("rest should be non-empty");
+        output.push(ch);
+        i += ch.len_utf8();
+    }
+
+    output
+}
+
+/// Finds the closing marker of an emphasis span opened at `start`, following
+/// Org's border rules: the opener follows whitespace or `-('"{`, the contents
+/// neither start nor end with whitespace, and the closer precedes whitespace
+/// or punctuation.
+fn find_emphasis_end(text: &str, start: usize) -> Option<usize> {
+    let marker = text.as_bytes()[start];
+    let prev = text[..start].chars().next_back();
+    if prev.is_some_and(|c| !c.is_whitespace() && !"-('\"{".contains(c)) {
+        return None;
+    }
+    if text[start + 1..]
+        .chars()
+        .next()
+        .is_none_or(char::is_whitespace)
+    {
+        return None;
+    }
+
+    let mut j = start + 2;
+    while j < text.len() {
+        if text.as_bytes()[j] == marker
+            && !text[..j].ends_with(char::is_whitespace)
+            && text[j + 1..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace() || "-.,;:!?')}\"".contains(c))
+        {
+            return Some(j);
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Converts a `[[target][description]]` or `[[target]]` link at the start of
+/// `rest`, returning the consumed length and the markdown.
+fn convert_link(rest: &str, slugify_mode: SlugifyMode) -> Option<(usize, String)> {
+    let body = rest.strip_prefix("[[")?;
+    let end = body.find("]]")?;
+    let (target, desc) = match body[..end].split_once("][") {
+        Some((target, desc)) => (target, Some(desc)),
+        None => (&body[..end], None),
+    };
+
+    let target = target.strip_prefix("file:").unwrap_or(target);
+    let (target, label) = match target.strip_prefix('*') {
+        Some(heading) => (format!("#{}", slugify(heading, slugify_mode)), heading),
+        None if target.contains(char::is_whitespace) => (format!("<{target}>"), target),
+        None => (target.to_owned(), target),
+    };
+
+    let markdown = match desc {
+        Some(desc) => format!("[{}]({target})", convert_inline(desc, slugify_mode)),
+        None if is_image(&target) => format!("![]({target})"),
+        None => format!("[{label}]({target})"),
+    };
+    Some((end + 4, markdown))
+}
+
+fn is_image(target: &str) -> bool {
+    let lower = target.to_ascii_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp", ".avif"]
+        .iter()
+        .any(|ext| lower.ends_with(ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── split_header ──
+
+    #[test]
+    fn split_header_reads_keywords() {
+        let content = indoc! {"
+            #+TITLE: Hello Org
+            #+DATE: <2024-01-15 Mon>
+            #+FILETAGS: :rust:emacs:
+            #+AUTHOR: Someone
+            # A comment
+
+            First paragraph.
+        "};
+        assert!(has_header(content));
+
+        let (header, body) = split_header(content);
+        let fm = header.into_frontmatter().unwrap();
+        assert_eq!(fm.title, "Hello Org");
+        assert_eq!(fm.date.unwrap().to_string(), "2024-01-15T00:00:00Z");
+        assert_eq!(fm.tags, ["rust", "emacs"]);
+        assert_eq!(body, "First paragraph.\n");
+    }
+
+    #[test]
+    fn has_header_requires_title() {
+        assert!(!has_header("#+AUTHOR: Someone\n\nText\n"));
+        assert!(!has_header("Text\n#+TITLE: Late\n"));
+    }
+
+    // ── to_markdown ──
+
+    #[test]
+    fn to_markdown_structure() {
+        let body = indoc! {"
+            * Intro :tag:
+            Some *bold*, /italic/, =code=, +gone+ and [[https://example.com][a link]].
+            ** Details
+            - item one
+              + nested [X] no
+            1. [X] done
+            - term :: meaning
+            -----
+            | a | b |
+            |---+---|
+            | 1 | 2 |
+            :PROPERTIES:
+            :ID: x
+            :END:
+            # comment
+            #+CAPTION: dropped
+            See [[*Details]] and [[file:img/cat.png]].
+        "};
+        assert_eq!(
+            to_markdown(body, SlugifyMode::Safe),
+            indoc! {"
+                # Intro
+                Some **bold**, *italic*, `code`, ~~gone~~ and [a link](https://example.com).
+                ## Details
+                - item one
+                  - nested [X] no
+                1. [x] done
+                - **term**: meaning
+                ---
+                | a | b |
+                |---|---|
+                | 1 | 2 |
+                See [Details](#details) and ![](img/cat.png).
+            "},
+        );
+    }
+
+    #[test]
+    fn to_markdown_blocks() {
+        let body = indoc! {"
+            #+BEGIN_SRC rust :exports code
+            fn main() {}
+            ,* not a headline
+            #+END_SRC
+
+            #+begin_note
+            Remember *this*.
+            #+end_note
+
+            #+begin_quote
+            Quoted /text/.
+            #+end_quote
+
+            #+BEGIN_EXPORT html
+            <div class=\"raw\"></div>
+            #+END_EXPORT
+
+            #+begin_center
+            Centered.
+            #+end_center
+
+            : fixed width
+        "};
+        assert_eq!(
+            to_markdown(body, SlugifyMode::Safe),
+            indoc! {r#"
+                ```rust
+                fn main() {}
+                * not a headline
+                ```
+
+                ::: callout {type=note}
+                Remember **this**.
+                :::
+
+                > Quoted *text*.
+
+                <div class="raw"></div>
+
+                ::: center
+                Centered.
+                :::
+
+                ```
+                fixed width
+                ```
+            "#},
+        );
+    }
+
+    #[test]
+    fn to_markdown_leaves_non_emphasis_alone() {
+        let body = "a*b*c, 2 * 3 * 4, snake_case_name, https://x.org/a/b/\n";
+        assert_eq!(to_markdown(body, SlugifyMode::Safe), body);
+    }
+}
@@ -17,6 +17,11 @@ pub struct Frontmatter {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub slug: Option<String>,
 
+    /// Stable page identifier for comment threads and analytics keys, kept
+    /// across slug and permalink changes. Written by `kiln fix`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
     #[serde(
         default,
         deserialize_with = "timestamp_serde::deserialize_option",
@@ -41,9 +46,19 @@ pub struct Frontmatter {
     )]
     pub featured_image: Option<FeaturedImage>,
 
+    /// Podcast episode published with this page, attached to its feed items
+    /// as an `<enclosure>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<Audio>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
 
+    /// IDs of the page's authors, each a key of `[authors]` in
+    /// `config.toml`. Without any, the site `[author]` is credited.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     pub draft: bool,
 
@@ -52,6 +67,71 @@ pub struct Frontmatter {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub license: Option<String>,
+
+    /// `BibTeX` (`.bib`) or CSL-JSON (`.json`) file with the entries cited by
+    /// `[@key]`, relative to the content file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bibliography: Option<String>,
+
+    /// Set to `false` to render this page without a table of contents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toc: Option<bool>,
+
+    /// Absolute URL this page has moved to. The page is replaced by a
+    /// redirect stub at its usual URL and left out of listings, feeds,
+    /// taxonomies, and the sitemap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_to: Option<String>,
+
+    /// Old site-root paths of this page (e.g., `/old-url/`). Each gets a
+    /// redirect stub pointing at the page's current URL.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    /// Template this page renders with (e.g., `"landing.html"`), in place of
+    /// `post.html` / `page.html`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+
+    /// In a section's `_index.md`: default `template` for the section's
+    /// posts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_template: Option<String>,
+
+    /// Set to `true` to keep this page out of search engines: it is left out
+    /// of the sitemap, and templates get `noindex` to emit a robots meta tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noindex: Option<bool>,
+
+    /// Set to `true` to leave this page out of every RSS feed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_from_feed: Option<bool>,
+
+    /// Language of this page (a BCP 47 tag, e.g., `"zh-Hans"`), if not the
+    /// site `language`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// Key shared by the translations of one piece of content. Each
+    /// translation keeps its own path and slug, and links to the others.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation_key: Option<String>,
+
+    /// In an `_index.md`: defaults for every page below its directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cascade: Option<Cascade>,
+}
+
+/// Frontmatter defaults an `_index.md` passes down to the pages below it.
+///
+/// A page's own value wins, then the nearest `_index.md` setting the key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Cascade {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noindex: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_from_feed: Option<bool>,
 }
 
 /// Featured image metadata including source URL, display position, and credit.
@@ -82,6 +162,23 @@ pub struct ImageCredit {
     pub url: Option<String>,
 }
 
+/// Audio file of a podcast episode.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Audio {
+    /// Path relative to the content file (a bundle asset), site-root path,
+    /// or absolute URL.
+    pub file: String,
+
+    /// Running time, as `HH:MM:SS`, `MM:SS`, or seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+
+    /// MIME type (e.g., `"audio/mpeg"`). Inferred from the file extension
+    /// when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
 fn is_default<T: Default + PartialEq>(t: &T) -> bool {
     *t == T::default()
 }
@@ -326,6 +423,7 @@ mod tests {
             draft = true
             weight = 10
             license = "CC BY-NC-SA 4.0"
+            bibliography = "refs.bib"
 
             [featured_image]
             src = "/images/example.webp"
@@ -364,6 +462,7 @@ mod tests {
         assert!(fm.draft);
         assert_eq!(fm.weight, Some(10));
         assert_eq!(fm.license.as_deref(), Some("CC BY-NC-SA 4.0"));
+        assert_eq!(fm.bibliography.as_deref(), Some("refs.bib"));
         assert_eq!(body, "Content here.\n");
     }
 
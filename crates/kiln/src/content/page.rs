@@ -1,11 +1,126 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, ensure};
 use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::{Serialize, Serializer};
+use sha1::{Digest, Sha1};
 use walkdir::WalkDir;
 
-use super::frontmatter::{self, Frontmatter};
+use super::format::ContentFormat;
+use super::frontmatter::Frontmatter;
+use crate::config::Slugify;
+use crate::directive::parser::parse_directives;
+use crate::output::url_path;
+use crate::text::{hex, slugify, word_count};
+
+/// Failures of individual pages, collected so one broken page does not hide
+/// the rest: every failure is reported at once.
+#[derive(Debug, Default)]
+pub struct PageErrors(Vec<(PathBuf, anyhow::Error)>);
+
+impl PageErrors {
+    /// Records the error of `result` against the page at `source`, if any,
+    /// and returns its value otherwise.
+    pub fn collect<T>(&mut self, source: &Path, result: Result<T>) -> Option<T> {
+        result.map_err(|e| self.0.push((source.to_owned(), e))).ok()
+    }
+
+    /// Returns whether no page failed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of pages that failed.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns each failed page's source file and error.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &anyhow::Error)> {
+        self.0.iter().map(|(path, e)| (path.as_path(), e))
+    }
+
+    /// Returns `Ok` if no page failed, or a [`PageFailures`] error otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns the collected failures.
+    pub fn into_result(self, action: &str) -> Result<()> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        Err(PageFailures {
+            action: action.to_owned(),
+            failures: self.0,
+        }
+        .into())
+    }
+}
+
+/// Serializes as a list of `{ "file": ..., "message": ... }`, the message
+/// carrying the whole error chain.
+impl Serialize for PageErrors {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Failure<'a> {
+            file: &'a Path,
+            message: String,
+        }
+        serializer.collect_seq(self.iter().map(|(file, e)| Failure {
+            file,
+            message: format!("{e:#}"),
+        }))
+    }
+}
+
+/// The pages that failed to load or build, with their source files.
+///
+/// A single failure displays as-is; several are combined into one message
+/// listing each, after "failed to `action` N pages".
+#[derive(Debug)]
+pub struct PageFailures {
+    action: String,
+    failures: Vec<(PathBuf, anyhow::Error)>,
+}
+
+impl PageFailures {
+    /// Returns each failed page's source file and error.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &anyhow::Error)> {
+        self.failures.iter().map(|(path, e)| (path.as_path(), e))
+    }
+}
+
+impl fmt::Display for PageFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [(_, e)] = self.failures.as_slice() {
+            return write!(f, "{e}");
+        }
+        write!(
+            f,
+            "failed to {} {} pages:",
+            self.action,
+            self.failures.len()
+        )?;
+        for (_, e) in &self.failures {
+            write!(f, "\n  - {e:#}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PageFailures {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.failures.as_slice() {
+            [(_, e)] => e.source(),
+            _ => None,
+        }
+    }
+}
 
 /// Distinguishes blog posts (under `content/posts/`) from standalone pages.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,15 +135,34 @@ pub enum PageKind {
 #[derive(Debug)]
 pub struct Page {
     pub frontmatter: Frontmatter,
+    /// The page body. Emptied by the build once the page is written, as
+    /// nothing after that reads it.
     pub raw_content: String,
+    /// 1-based line of the source file `raw_content` starts on, to locate
+    /// problems in it. `None` for Org and AsciiDoc sources, and for bodies
+    /// rewritten by a [`ContentTransform`], whose lines map to no source
+    /// line.
+    ///
+    /// [`ContentTransform`]: crate::ContentTransform
+    pub body_line: Option<usize>,
+    /// Source format, from the file extension. Paths with an unrecognized
+    /// extension are treated as markdown. Org and AsciiDoc sources are
+    /// converted on load, so `raw_content` is markdown for every format
+    /// except HTML.
+    pub format: ContentFormat,
     /// Whether this is a blog post or a standalone page.
     /// Set by content discovery based on the file's position in the content
     /// directory; defaults to `PageKind::Page` when created via `from_content`.
     pub kind: PageKind,
     pub slug: String,
+    /// Stable identifier for comment threads and analytics: the frontmatter
+    /// `id`, else derived from the source path by content discovery. Empty
+    /// for pages created via `from_content` without an `id`.
+    pub id: String,
     pub summary: Option<String>,
     pub source_path: PathBuf,
-    /// Co-located non-markdown files for page bundles (e.g., images).
+    /// Co-located non-markup files for page bundles (e.g., images),
+    /// excluding the page's own source file.
     /// Empty for standalone pages and pages created via `from_content`.
     pub assets: Vec<PathBuf>,
 }
@@ -43,23 +177,36 @@ impl Page {
         matches!(self.kind, PageKind::Post { .. })
     }
 
-    /// Loads a page from a markdown file on disk.
+    /// The page bundle directory: the directory of an `index.*` source.
+    /// `None` for standalone pages, whose directory holds other pages.
+    #[must_use]
+    pub fn bundle_dir(&self) -> Option<&Path> {
+        self.source_path
+            .parent()
+            .filter(|_| is_page_bundle(&self.source_path))
+    }
+
+    /// Loads a page from a content file on disk (see [`ContentFormat`]).
+    ///
+    /// A slug derived from the file name is generated under the `[slugify]`
+    /// mode, or kept as written when no mode is set; an explicit frontmatter
+    /// `slug` is always used as written.
     ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be read, the frontmatter is invalid,
     /// or a slug cannot be derived from the file path.
-    pub fn from_file(path: &Path) -> Result<Self> {
+    pub fn from_file(path: &Path, slugify_config: Slugify) -> Result<Self> {
         let content = fs::read_to_string(path)
             .with_context(|| format!("failed to read {}", path.display()))?;
-        let mut page = Self::from_content(&content, path)
+        let mut page = Self::from_content(&content, path, slugify_config)
             .with_context(|| format!("failed to parse {}", path.display()))?;
 
         // Discover co-located assets for page bundles.
         if is_page_bundle(path)
             && let Some(dir) = path.parent()
         {
-            page.assets = discover_assets(dir)
+            page.assets = discover_assets(dir, path)
                 .with_context(|| format!("failed to read assets in {}", dir.display()))?;
         }
 
@@ -73,28 +220,64 @@ impl Page {
     /// # Errors
     ///
     /// Returns an error if the frontmatter is invalid or a slug cannot be derived.
-    pub fn from_content(content: &str, path: &Path) -> Result<Self> {
-        let (frontmatter, body) = frontmatter::parse(content)
+    pub fn from_content(content: &str, path: &Path, slugify_config: Slugify) -> Result<Self> {
+        let format = ContentFormat::from_path(path).unwrap_or(ContentFormat::Markdown);
+        let (frontmatter, body) = format
+            .parse(content, slugify_config.mode())
             .with_context(|| format!("invalid frontmatter in {}", path.display()))?;
 
+        if let Some(slug) = &frontmatter.slug {
+            ensure!(
+                is_path_segment(slug),
+                "invalid slug `{slug}` in {}: must be a single path segment, \
+                 without `/`, `\\`, or `..`",
+                path.display()
+            );
+        }
         // Explicit frontmatter slug takes priority over the filename-derived slug.
-        let slug = frontmatter
-            .slug
-            .clone()
-            .or_else(|| derive_slug(path))
-            .with_context(|| {
+        let slug = if let Some(slug) = &frontmatter.slug {
+            slug.clone()
+        } else {
+            let name = derive_slug(path).with_context(|| {
                 format!(
                     "cannot derive slug from {}: \
                      page bundles (index.md) must be inside a named directory",
                     path.display()
                 )
             })?;
-        let summary = extract_summary(body);
+            match slugify_config.mode {
+                Some(mode) => {
+                    let slug = slugify(&name, mode);
+                    ensure!(
+                        !slug.is_empty(),
+                        "cannot derive slug from {}: `{name}` is empty once slugified; \
+                         set `slug` in the frontmatter",
+                        path.display()
+                    );
+                    slug
+                }
+                None => name,
+            }
+        };
+        // HTML bodies have no markdown to strip; use `description` instead.
+        let summary = if format == ContentFormat::Html {
+            None
+        } else {
+            extract_summary(&body)
+        };
+
+        let id = frontmatter.id.clone().unwrap_or_default();
+        // Unconverted bodies are the tail of the file.
+        let body_line = matches!(body, Cow::Borrowed(_))
+            .then(|| content[..content.len() - body.len()].matches('\n').count() + 1);
 
         Ok(Self {
             frontmatter,
-            raw_content: body.to_owned(),
+            raw_content: body.into_owned(),
+            body_line,
+            format,
             kind: PageKind::Page,
+            id,
             slug,
             summary,
             source_path: path.to_owned(),
@@ -102,6 +285,18 @@ impl Page {
         })
     }
 
+    /// Replaces the markdown body with `body`, re-deriving the summary from
+    /// it. A changed body no longer maps to source lines, so `body_line` is
+    /// cleared.
+    pub fn set_body(&mut self, body: String) {
+        if body == self.raw_content {
+            return;
+        }
+        self.summary = extract_summary(&body);
+        self.body_line = None;
+        self.raw_content = body;
+    }
+
     /// Computes the output path relative to the build output directory.
     ///
     /// Strips the `content/` prefix and keeps the remaining directory
@@ -111,6 +306,7 @@ impl Page {
     /// - `content/posts/foo/bar/index.md` → `posts/foo/bar/index.html`
     /// - `content/posts/hello-world.md` → `posts/hello-world/index.html`
     /// - `content/example/index.md` → `example/index.html`
+    /// - `content/landing.html` → `landing/index.html`
     ///
     /// # Errors
     ///
@@ -136,6 +332,46 @@ impl Page {
             Ok(relative.with_extension("").join("index.html"))
         }
     }
+
+    /// Returns the markdown the page's rendered summary is made from: the
+    /// body before `<!--more-->`, or else its leading top-level blocks up to
+    /// the one that brings them to `words` words. `words = 0` disables the
+    /// fallback.
+    ///
+    /// Reference link definitions later in the body are appended, so links
+    /// in the summary still resolve. `None` for HTML pages and empty
+    /// summaries.
+    #[must_use]
+    pub fn summary_source(&self, words: usize) -> Option<String> {
+        if self.format == ContentFormat::Html {
+            return None;
+        }
+        let body = &self.raw_content;
+        let end = match body.find(SUMMARY_SEPARATOR) {
+            Some(offset) => offset,
+            None if words > 0 => leading_blocks_end(body, words),
+            None => return None,
+        };
+        let summary = body[..end].trim_end();
+        if summary.trim_start().is_empty() {
+            return None;
+        }
+
+        let parser = Parser::new_ext(body, Options::all());
+        let mut definitions: Vec<_> = parser
+            .reference_definitions()
+            .iter()
+            .map(|(_, definition)| definition.span.clone())
+            .filter(|span| span.start >= end)
+            .collect();
+        definitions.sort_by_key(|span| span.start);
+        let mut source = summary.to_owned();
+        for span in definitions {
+            source.push_str("\n\n");
+            source.push_str(body[span].trim_end());
+        }
+        Some(source)
+    }
 }
 
 /// Derives the page kind from its position in the content directory.
@@ -168,15 +404,32 @@ pub fn derive_page_kind(source_path: &Path, content_dir: &Path) -> PageKind {
     PageKind::Post { section }
 }
 
-/// Returns `true` if the file is a page bundle entry point (`index.md`).
+/// Hex digits kept in a derived page ID (64 bits).
+const PAGE_ID_LEN: usize = 16;
+
+/// Derives a page ID from the source path relative to the content directory.
+///
+/// The ID changes when the file moves, so `kiln fix` writes it into the
+/// frontmatter once to pin it down.
+#[must_use]
+pub fn derive_page_id(source_path: &Path, content_dir: &Path) -> String {
+    let relative = source_path.strip_prefix(content_dir).unwrap_or(source_path);
+    let digest = Sha1::digest(url_path(relative));
+    hex(&digest[..PAGE_ID_LEN / 2])
+}
+
+/// Returns `true` if the file is a page bundle entry point (`index.md`,
+/// `index.html`, ...).
 fn is_page_bundle(path: &Path) -> bool {
     path.file_stem().and_then(|s| s.to_str()) == Some("index")
 }
 
-/// Recursively discovers co-located non-markdown files in a page bundle directory.
+/// Recursively discovers co-located assets in a page bundle directory:
+/// everything except markup content files (`.md`, `.org`, `.adoc`) and the
+/// bundle's own `source` file.
 ///
 /// Returns sorted absolute paths for deterministic output.
-fn discover_assets(dir: &Path) -> Result<Vec<PathBuf>> {
+fn discover_assets(dir: &Path, source: &Path) -> Result<Vec<PathBuf>> {
     let mut assets = Vec::new();
     for entry in WalkDir::new(dir).follow_links(false) {
         let entry = entry.with_context(|| format!("failed to read entry in {}", dir.display()))?;
@@ -184,7 +437,8 @@ fn discover_assets(dir: &Path) -> Result<Vec<PathBuf>> {
             continue;
         }
         let path = entry.into_path();
-        if path.extension().is_none_or(|ext| ext != "md") {
+        let is_markup = ContentFormat::from_path(&path).is_some_and(|f| f != ContentFormat::Html);
+        if !is_markup && path != source {
             assets.push(path);
         }
     }
@@ -219,6 +473,12 @@ fn derive_slug(path: &Path) -> Option<String> {
     }
 }
 
+/// Returns whether `slug` is usable as one path segment: non-empty, with no
+/// separators, and not `.` or `..`.
+fn is_path_segment(slug: &str) -> bool {
+    !matches!(slug, "" | "." | "..") && !slug.contains(['/', '\\'])
+}
+
 /// Extracts the summary from markdown content (text before `<!--more-->`).
 ///
 /// The raw markdown is stripped to plain text so that link syntax, formatting,
@@ -237,6 +497,53 @@ fn extract_summary(body: &str) -> Option<String> {
     if plain.is_empty() { None } else { Some(plain) }
 }
 
+/// Returns the offset where the leading top-level blocks of `body` reach
+/// `words` words, or the end of `body` if they never do.
+///
+/// A `:::` directive counts as one block. Markdown would see its fences as
+/// paragraphs (or definitions), so the markdown between directives is
+/// parsed on its own.
+fn leading_blocks_end(body: &str, words: usize) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    // Sorted by start, so nested directives follow the one holding them.
+    for directive in parse_directives(body) {
+        if directive.range.start < start {
+            continue;
+        }
+        let before = &body[start..directive.range.start];
+        if let Some(end) = markdown_blocks_end(before, words, &mut count) {
+            return start + end;
+        }
+        count += word_count(&directive.body);
+        if count >= words {
+            return directive.range.end;
+        }
+        start = directive.range.end;
+    }
+    markdown_blocks_end(&body[start..], words, &mut count).map_or(body.len(), |end| start + end)
+}
+
+/// Adds the words of each top-level markdown block of `text` to `count`,
+/// returning the offset where a block brings it to `words`, if one does.
+fn markdown_blocks_end(text: &str, words: usize, count: &mut usize) -> Option<usize> {
+    let mut depth = 0_usize;
+    for (event, range) in Parser::new_ext(text, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => {
+                depth -= 1;
+                if depth == 0 && *count >= words {
+                    return Some(range.end);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => *count += word_count(&text),
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Strips markdown syntax from the region before `summary_end`, producing a
 /// plain-text representation.
 ///
@@ -284,6 +591,7 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::config::SlugifyMode;
     use crate::test_utils::{PermissionGuard, test_page};
 
     // ── from_file: basic ──
@@ -308,7 +616,7 @@ mod tests {
         )
         .unwrap();
 
-        let page = Page::from_file(&file).unwrap();
+        let page = Page::from_file(&file, Slugify::default()).unwrap();
         assert_eq!(page.frontmatter.title, "Test");
         assert_eq!(page.slug, "test");
         assert_eq!(page.summary.unwrap(), "Summary here.");
@@ -316,7 +624,7 @@ mod tests {
 
     #[test]
     fn from_file_nonexistent_returns_error() {
-        let err = Page::from_file(Path::new("/nonexistent/test.md"))
+        let err = Page::from_file(Path::new("/nonexistent/test.md"), Slugify::default())
             .unwrap_err()
             .to_string();
         assert!(
@@ -339,7 +647,9 @@ mod tests {
         )
         .unwrap();
 
-        let err = Page::from_file(&file).unwrap_err().to_string();
+        let err = Page::from_file(&file, Slugify::default())
+            .unwrap_err()
+            .to_string();
         assert!(
             err.contains("failed to parse"),
             "should report parse failure, got: {err}"
@@ -368,7 +678,7 @@ mod tests {
         fs::write(assets_dir.join("screenshot.webp"), "fake-webp").unwrap();
         fs::write(assets_dir.join("data.json"), "{}").unwrap();
 
-        let page = Page::from_file(&bundle.join("index.md")).unwrap();
+        let page = Page::from_file(&bundle.join("index.md"), Slugify::default()).unwrap();
         let relative_paths: Vec<_> = page
             .assets
             .iter()
@@ -398,7 +708,7 @@ mod tests {
         fs::write(bundle.join("notes.md"), "other markdown").unwrap();
         fs::write(bundle.join("image.png"), "fake-png").unwrap();
 
-        let page = Page::from_file(&bundle.join("index.md")).unwrap();
+        let page = Page::from_file(&bundle.join("index.md"), Slugify::default()).unwrap();
         let relative_paths: Vec<_> = page
             .assets
             .iter()
@@ -407,6 +717,22 @@ mod tests {
         assert_eq!(relative_paths, vec!["image.png"]);
     }
 
+    #[test]
+    fn from_file_html_bundle_excludes_own_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("landing");
+        fs::create_dir_all(&bundle).unwrap();
+        fs::write(
+            bundle.join("index.html"),
+            "+++\ntitle = \"Landing\"\n+++\n<p>Hi</p>",
+        )
+        .unwrap();
+        fs::write(bundle.join("demo.html"), "<p>demo</p>").unwrap();
+
+        let page = Page::from_file(&bundle.join("index.html"), Slugify::default()).unwrap();
+        assert_eq!(page.assets, [bundle.join("demo.html")]);
+    }
+
     #[test]
     fn from_file_non_index_has_no_assets() {
         let dir = tempfile::tempdir().unwrap();
@@ -423,7 +749,7 @@ mod tests {
         .unwrap();
         fs::write(dir.path().join("image.png"), "fake-png").unwrap();
 
-        let page = Page::from_file(&file).unwrap();
+        let page = Page::from_file(&file, Slugify::default()).unwrap();
         assert!(page.assets.is_empty());
     }
 
@@ -446,7 +772,7 @@ mod tests {
         // Remove read permission but keep execute so the file can still be read by path.
         let _guard = PermissionGuard::restrict(&bundle, 0o111);
 
-        let err = Page::from_file(&bundle.join("index.md"))
+        let err = Page::from_file(&bundle.join("index.md"), Slugify::default())
             .unwrap_err()
             .to_string();
         assert!(
@@ -476,7 +802,7 @@ mod tests {
         // Make the subdirectory unreadable so WalkDir yields an error entry.
         let _guard = PermissionGuard::restrict(&subdir, 0o000);
 
-        let err = Page::from_file(&bundle.join("index.md"))
+        let err = Page::from_file(&bundle.join("index.md"), Slugify::default())
             .unwrap_err()
             .to_string();
         assert!(
@@ -500,12 +826,38 @@ mod tests {
 
             Full content here.
         "#};
-        let page = Page::from_content(content, Path::new("content/posts/test/index.md")).unwrap();
+        let page = Page::from_content(
+            content,
+            Path::new("content/posts/test/index.md"),
+            Slugify::default(),
+        )
+        .unwrap();
         assert_eq!(page.frontmatter.title, "Test");
         assert_eq!(page.slug, "test");
         assert_eq!(page.summary.unwrap(), "Summary here.");
     }
 
+    #[test]
+    fn from_content_html_keeps_body_verbatim() {
+        let content = indoc! {r#"
+            +++
+            title = "Landing"
+            +++
+            <h1>Hi</h1>
+            <!--more-->
+        "#};
+        let page = Page::from_content(
+            content,
+            Path::new("content/landing.html"),
+            Slugify::default(),
+        )
+        .unwrap();
+        assert_eq!(page.format, ContentFormat::Html);
+        assert_eq!(page.raw_content, "<h1>Hi</h1>\n<!--more-->\n");
+        assert_eq!(page.summary, None);
+        assert_eq!(page.slug, "landing");
+    }
+
     #[test]
     fn from_content_explicit_slug_overrides_filename() {
         let content = indoc! {r#"
@@ -515,10 +867,53 @@ mod tests {
             +++
             Body
         "#};
-        let page = Page::from_content(content, Path::new("content/posts/foobar/index.md")).unwrap();
+        let page = Page::from_content(
+            content,
+            Path::new("content/posts/foobar/index.md"),
+            Slugify::default(),
+        )
+        .unwrap();
         assert_eq!(page.slug, "custom-slug");
     }
 
+    #[test]
+    fn from_content_slugifies_derived_slug_when_mode_is_set() {
+        let content = "+++\ntitle = \"T\"\n+++\n";
+        let path = Path::new("content/posts/Café Notes/index.md");
+        let slug = |mode| {
+            Page::from_content(content, path, Slugify { mode })
+                .unwrap()
+                .slug
+        };
+        assert_eq!(slug(None), "Café Notes");
+        assert_eq!(slug(Some(SlugifyMode::Safe)), "café-notes");
+        assert_eq!(slug(Some(SlugifyMode::Ascii)), "cafe-notes");
+        assert_eq!(slug(Some(SlugifyMode::Off)), "Café-Notes");
+
+        let ascii = Slugify {
+            mode: Some(SlugifyMode::Ascii),
+        };
+        let err = Page::from_content(content, Path::new("content/你好.md"), ascii)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("set `slug` in the frontmatter"), "{err}");
+    }
+
+    #[test]
+    fn from_content_path_like_slug_returns_error() {
+        for slug in ["../../etc", "/abs", "a/b", "a\\\\b", "..", ""] {
+            let content = format!("+++\ntitle = \"T\"\nslug = \"{slug}\"\n+++\n");
+            let err = Page::from_content(
+                &content,
+                Path::new("content/posts/t.md"),
+                Slugify::default(),
+            )
+            .unwrap_err()
+            .to_string();
+            assert!(err.contains("invalid slug"), "{slug:?}: {err}");
+        }
+    }
+
     #[test]
     fn from_content_bare_index_no_slug_returns_error() {
         let content = indoc! {r#"
@@ -527,7 +922,7 @@ mod tests {
             +++
             Body
         "#};
-        let err = Page::from_content(content, Path::new("index.md"))
+        let err = Page::from_content(content, Path::new("index.md"), Slugify::default())
             .unwrap_err()
             .to_string();
         assert!(
@@ -536,6 +931,35 @@ mod tests {
         );
     }
 
+    // ── set_body ──
+
+    #[test]
+    fn set_body_rederives_summary() {
+        let content = "+++\ntitle = \"T\"\n+++\nOld.\n<!--more-->\n";
+        let mut page =
+            Page::from_content(content, Path::new("content/t.md"), Slugify::default()).unwrap();
+        page.set_body(page.raw_content.clone());
+        assert_eq!(page.body_line, Some(4));
+
+        page.set_body("New **intro**.\n<!--more-->\nRest.\n".into());
+        assert_eq!(page.summary.as_deref(), Some("New intro."));
+        assert_eq!(page.body_line, None);
+    }
+
+    // ── bundle_dir ──
+
+    #[test]
+    fn bundle_dir_is_set_for_index_pages_only() {
+        let mut page = test_page("hello");
+        page.source_path = PathBuf::from("/site/content/posts/hello/index.md");
+        assert_eq!(
+            page.bundle_dir(),
+            Some(Path::new("/site/content/posts/hello"))
+        );
+        page.source_path = PathBuf::from("/site/content/posts/hello.md");
+        assert_eq!(page.bundle_dir(), None);
+    }
+
     // ── output_path ──
 
     #[test]
@@ -562,6 +986,23 @@ mod tests {
         assert_eq!(out, PathBuf::from("posts/hello-world/index.html"));
     }
 
+    #[test]
+    fn output_path_keeps_file_names_under_slugify_mode() {
+        let ascii = Slugify {
+            mode: Some(SlugifyMode::Ascii),
+        };
+        let content = "+++\ntitle = \"T\"\n+++\n";
+        let page = Page::from_content(
+            content,
+            Path::new("/site/content/posts/Zürich Notes.md"),
+            ascii,
+        )
+        .unwrap();
+        assert_eq!(page.slug, "zurich-notes");
+        let out = page.output_path(Path::new("/site/content")).unwrap();
+        assert_eq!(out, Path::new("posts/Zürich Notes/index.html"));
+    }
+
     #[test]
     fn output_path_outside_content_dir_returns_error() {
         let mut page = test_page("test");
@@ -569,6 +1010,56 @@ mod tests {
         assert!(page.output_path(Path::new("/site/content")).is_err());
     }
 
+    // ── PageErrors ──
+
+    #[test]
+    fn page_errors_into_result() {
+        assert!(PageErrors::default().into_result("build").is_ok());
+        let (a, b) = (Path::new("content/a.md"), Path::new("content/b.md"));
+
+        let mut errors = PageErrors::default();
+        assert_eq!(errors.collect(a, Ok(1)), Some(1));
+        let broke = anyhow::anyhow!("a broke").context("failed to render a");
+        assert_eq!(errors.collect::<()>(a, Err(broke)), None);
+        assert!(!errors.is_empty());
+        assert_eq!(errors.len(), 1);
+        let err = errors.into_result("build").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to render a",
+            "a single failure displays as-is"
+        );
+        assert_eq!(format!("{err:#}"), "failed to render a: a broke");
+
+        let mut errors = PageErrors::default();
+        errors.collect::<()>(a, Err(anyhow::anyhow!("a broke")));
+        errors.collect::<()>(
+            b,
+            Err(anyhow::anyhow!("b broke").context("failed to render b")),
+        );
+        let err = errors.into_result("build").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to build 2 pages:\n  - a broke\n  - failed to render b: b broke"
+        );
+        let failures = err.downcast_ref::<PageFailures>().unwrap();
+        let sources: Vec<_> = failures.iter().map(|(path, _)| path).collect();
+        assert_eq!(sources, [a, b]);
+    }
+
+    #[test]
+    fn page_errors_serialize_with_sources() {
+        let mut errors = PageErrors::default();
+        errors.collect::<()>(
+            Path::new("content/a.md"),
+            Err(anyhow::anyhow!("a broke").context("failed to render a")),
+        );
+        assert_eq!(
+            serde_json::to_string(&errors).unwrap(),
+            r#"[{"file":"content/a.md","message":"failed to render a: a broke"}]"#
+        );
+    }
+
     // ── derive_page_kind ──
 
     #[test]
@@ -646,6 +1137,27 @@ mod tests {
         assert_eq!(kind, PageKind::Page);
     }
 
+    // ── derive_page_id ──
+
+    #[test]
+    fn derive_page_id_hashes_relative_path() {
+        let content_dir = Path::new("/site/content");
+        let id = derive_page_id(Path::new("/site/content/posts/a.md"), content_dir);
+        assert_eq!(id.len(), PAGE_ID_LEN);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()), "{id}");
+        assert_eq!(
+            id,
+            derive_page_id(
+                Path::new("/other/content/posts/a.md"),
+                Path::new("/other/content")
+            )
+        );
+        assert_ne!(
+            id,
+            derive_page_id(Path::new("/site/content/posts/b.md"), content_dir)
+        );
+    }
+
     // ── derive_slug ──
 
     #[test]
@@ -665,6 +1177,108 @@ mod tests {
         assert!(derive_slug(Path::new("index.md")).is_none());
     }
 
+    // ── summary_source ──
+
+    fn page_with_body(body: &str) -> Page {
+        Page {
+            raw_content: body.to_owned(),
+            ..test_page("Post")
+        }
+    }
+
+    #[test]
+    fn summary_source_stops_at_separator() {
+        let page = page_with_body(indoc! {r"
+            See [the docs][docs] and [home].
+
+            <!--more-->
+
+            Full content here.
+
+            [home]: https://example.com
+            [docs]: https://example.com/docs
+        "});
+        assert_eq!(
+            page.summary_source(70).unwrap(),
+            indoc! {r"
+                See [the docs][docs] and [home].
+
+                [home]: https://example.com
+
+                [docs]: https://example.com/docs"}
+        );
+    }
+
+    #[test]
+    fn summary_source_falls_back_to_leading_blocks() {
+        let page = page_with_body(indoc! {r"
+            # Heading
+
+            One two three.
+
+            - four
+            - five
+
+            Six.
+        "});
+        assert_eq!(
+            page.summary_source(4).unwrap(),
+            "# Heading\n\nOne two three.",
+            "the heading counts toward the words"
+        );
+        assert_eq!(
+            page.summary_source(5).unwrap(),
+            "# Heading\n\nOne two three.\n\n- four\n- five"
+        );
+        assert_eq!(
+            page.summary_source(100).unwrap(),
+            page.raw_content.trim_end()
+        );
+        assert!(page.summary_source(0).is_none());
+    }
+
+    #[test]
+    fn summary_source_keeps_directives_whole() {
+        let page = page_with_body(indoc! {r"
+            Intro words here.
+
+            ::: note
+            One two.
+
+            :::: details
+            Three four.
+            ::::
+            :::
+
+            After.
+        "});
+        assert_eq!(
+            page.summary_source(4).unwrap(),
+            indoc! {r"
+                Intro words here.
+
+                ::: note
+                One two.
+
+                :::: details
+                Three four.
+                ::::
+                :::"}
+        );
+    }
+
+    #[test]
+    fn summary_source_none_for_empty_or_html() {
+        let page = page_with_body("<!--more-->\n\nContent after.\n");
+        assert!(page.summary_source(70).is_none());
+
+        let page = Page {
+            format: ContentFormat::Html,
+            ..page_with_body("<p>Raw HTML</p>\n<!--more-->\n")
+        };
+        assert!(page.summary_source(70).is_none());
+    }
+
     // ── extract_summary ──
 
     #[test]
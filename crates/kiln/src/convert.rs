@@ -1,4 +1,4 @@
-mod frontmatter;
+pub(crate) mod frontmatter;
 mod shortcode;
 
 use std::fs;
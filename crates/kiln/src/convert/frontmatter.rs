@@ -27,6 +27,22 @@ pub(crate) fn convert_frontmatter(yaml_str: &str) -> Result<String> {
     Ok(toml_str)
 }
 
+/// Returns the keys of a YAML frontmatter string that
+/// [`convert_frontmatter`] would drop, as dotted paths.
+///
+/// # Errors
+///
+/// Returns an error if the YAML is invalid.
+pub(crate) fn unknown_frontmatter_keys(yaml_str: &str) -> Result<Vec<String>> {
+    let mut unknown = Vec::new();
+    let _: Frontmatter =
+        serde_ignored::deserialize(serde_yaml::Deserializer::from_str(yaml_str), |path| {
+            unknown.push(path.to_string());
+        })
+        .context("failed to parse YAML frontmatter")?;
+    Ok(unknown)
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -161,4 +177,26 @@ mod tests {
             "#}
         );
     }
+
+    // ── unknown_frontmatter_keys ──
+
+    #[test]
+    fn unknown_frontmatter_keys_lists_dropped_keys() {
+        let yaml = indoc! {"
+            title: Post
+            featuredImage: /img.webp
+            categories: [tutorial]
+            params:
+              mood: calm
+        "};
+        assert_eq!(
+            unknown_frontmatter_keys(yaml).unwrap(),
+            ["categories", "params"]
+        );
+        assert!(
+            unknown_frontmatter_keys("title: Post\n")
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -0,0 +1,366 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail, ensure};
+use jiff::Zoned;
+
+use crate::config::{Config, Deploy};
+use crate::template::cache::CACHE_DIR;
+
+/// Options for [`deploy`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DeployOptions {
+    /// Show what would be published without publishing it.
+    pub dry_run: bool,
+}
+
+/// Publishes the built output directory to the `[deploy]` target in
+/// `config.toml`: mirrored with `rsync` or `aws s3 sync`, or committed onto
+/// a git branch.
+///
+/// Deploys the output as it is; run [`build`](crate::build) first.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded or has no `[deploy]`
+/// table, the output directory does not exist, or the deploy tool cannot
+/// be run or fails.
+pub fn deploy(root: &Path, options: DeployOptions) -> Result<()> {
+    let config = Config::load(root).context("failed to load config")?;
+    let Some(target) = &config.deploy else {
+        bail!("no deploy target: add a [deploy] table to config.toml");
+    };
+    let output_dir = config.resolved_output_dir(root)?;
+    ensure!(
+        output_dir.is_dir(),
+        "output directory {} does not exist; run `kiln build` first",
+        output_dir.display()
+    );
+
+    match target {
+        Deploy::Rsync { destination } => {
+            eprintln!("Deploying to {destination} with rsync...");
+            run(rsync_command(&output_dir, destination, options.dry_run))
+        }
+        Deploy::S3 { bucket, endpoint } => {
+            eprintln!("Deploying to {bucket}...");
+            run(s3_command(
+                &output_dir,
+                bucket,
+                endpoint.as_deref(),
+                options.dry_run,
+            ))
+        }
+        Deploy::Git { repository, branch } => {
+            push_branch(root, &output_dir, repository, branch, options.dry_run)
+        }
+    }
+}
+
+/// Mirrors the contents of `output_dir` to `destination`, deleting files
+/// that are no longer in the output.
+fn rsync_command(output_dir: &Path, destination: &str, dry_run: bool) -> Command {
+    // The trailing slash copies the directory's contents, not the directory.
+    let mut source = OsString::from(output_dir);
+    source.push("/");
+    let mut command = Command::new("rsync");
+    command.args(["--archive", "--compress", "--delete", "--human-readable"]);
+    if dry_run {
+        command.args(["--dry-run", "--itemize-changes"]);
+    }
+    command.arg(source).arg(destination);
+    command
+}
+
+/// Mirrors `output_dir` to `bucket`, deleting objects that are no longer in
+/// the output.
+fn s3_command(output_dir: &Path, bucket: &str, endpoint: Option<&str>, dry_run: bool) -> Command {
+    let mut command = Command::new("aws");
+    command
+        .args(["s3", "sync"])
+        .arg(output_dir)
+        .args([bucket, "--delete"]);
+    if let Some(endpoint) = endpoint {
+        command.args(["--endpoint-url", endpoint]);
+    }
+    if dry_run {
+        command.arg("--dryrun");
+    }
+    command
+}
+
+/// Runs a deploy tool, its output passed through to the terminal.
+fn run(mut command: Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .status()
+        .with_context(|| format!("failed to run `{program}` — is it installed?"))?;
+    ensure!(status.success(), "`{program}` exited with {status}");
+    Ok(())
+}
+
+/// Commits `output_dir` onto `branch` of `repository` and pushes it.
+///
+/// The commit's parent is the branch's current tip, so the branch keeps
+/// its history, and unchanged output pushes nothing. The git state lives
+/// in `.kiln-cache/deploy.git`, apart from any repository the site itself
+/// is in.
+fn push_branch(
+    root: &Path,
+    output_dir: &Path,
+    repository: &str,
+    branch: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let git_dir = root.join(CACHE_DIR).join("deploy.git");
+    if !git_dir.exists() {
+        fs::create_dir_all(&git_dir)
+            .with_context(|| format!("failed to create {}", git_dir.display()))?;
+        git(&git_dir, None, &["init", "--quiet", "--bare"])?;
+    }
+    let git = |args: &[&str]| git(&git_dir, Some(output_dir), args);
+
+    let branch_ref = format!("refs/heads/{branch}");
+    let tip = git(&["ls-remote", "--", repository, &branch_ref])?;
+    let parent = tip.split_whitespace().next();
+    if parent.is_some() {
+        git(&["fetch", "--quiet", "--", repository, &branch_ref])?;
+    }
+
+    git(&["add", "--all"])?;
+    let tree = git(&["write-tree"])?;
+    if let Some(parent) = parent
+        && git(&["rev-parse", &format!("{parent}^{{tree}}")])? == tree
+    {
+        eprintln!("Nothing to deploy: {branch} of {repository} is up to date.");
+        return Ok(());
+    }
+
+    let message = format!("Deploy {}", Zoned::now().strftime("%Y-%m-%d %H:%M:%S %z"));
+    let mut args = Vec::new();
+    // Deploys from CI often run without a git identity.
+    if git(&["var", "GIT_COMMITTER_IDENT"]).is_err() {
+        args.extend(["-c", "user.name=kiln", "-c", "user.email=kiln@localhost"]);
+    }
+    args.extend(["commit-tree", &tree, "-m", &message]);
+    if let Some(parent) = parent {
+        args.extend(["-p", parent]);
+    }
+    let commit = git(&args)?;
+
+    if dry_run {
+        eprintln!("Would push {commit} to {branch} of {repository}.");
+        return Ok(());
+    }
+    git(&[
+        "push",
+        "--quiet",
+        "--",
+        repository,
+        &format!("{commit}:{branch_ref}"),
+    ])?;
+    eprintln!("Deployed {commit} to {branch} of {repository}.");
+    Ok(())
+}
+
+/// Runs `git` on the repository at `git_dir`, with `work_tree` as its
+/// working tree if given, and returns its trimmed stdout.
+fn git(git_dir: &Path, work_tree: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = Command::new("git");
+    command.arg("--git-dir").arg(git_dir);
+    if let Some(work_tree) = work_tree {
+        command.arg("--work-tree").arg(work_tree);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .context("failed to run `git` — is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` exited with {}:\n{}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::formatdoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Creates a site with a built page and a `[deploy]` table pushing to a
+    /// fresh bare repository, returned as its path.
+    fn setup_git_site(root: &Path) -> String {
+        let remote = root.join("remote.git");
+        fs::create_dir(&remote).unwrap();
+        git(&remote, None, &["init", "--quiet", "--bare"]).unwrap();
+        let remote = remote.to_str().unwrap().to_owned();
+        write_test_file(
+            root,
+            "config.toml",
+            &formatdoc! {r#"
+                [deploy]
+                target = "git"
+                repository = "{remote}"
+            "#},
+        );
+        write_test_file(root, "public/index.html", "v1");
+        remote
+    }
+
+    /// Lists the files on `branch` of the bare repository at `remote`.
+    fn branch_files(remote: &str, branch: &str) -> String {
+        let remote = Path::new(remote);
+        git(remote, None, &["ls-tree", "-r", "--name-only", branch]).unwrap()
+    }
+
+    fn commit_count(remote: &str, branch: &str) -> String {
+        let remote = Path::new(remote);
+        git(remote, None, &["rev-list", "--count", branch]).unwrap()
+    }
+
+    // ── deploy ──
+
+    #[test]
+    fn deploy_pushes_output_to_branch() {
+        let root = tempfile::tempdir().unwrap();
+        let remote = setup_git_site(root.path());
+
+        deploy(root.path(), DeployOptions::default()).unwrap();
+        assert_eq!(branch_files(&remote, "gh-pages"), "index.html");
+
+        fs::remove_file(root.path().join("public/index.html")).unwrap();
+        write_test_file(root.path(), "public/posts/hello/index.html", "v2");
+        deploy(root.path(), DeployOptions::default()).unwrap();
+
+        assert_eq!(branch_files(&remote, "gh-pages"), "posts/hello/index.html");
+        assert_eq!(commit_count(&remote, "gh-pages"), "2", "history is kept");
+    }
+
+    #[test]
+    fn deploy_unchanged_output_pushes_nothing() {
+        let root = tempfile::tempdir().unwrap();
+        let remote = setup_git_site(root.path());
+
+        deploy(root.path(), DeployOptions::default()).unwrap();
+        deploy(root.path(), DeployOptions::default()).unwrap();
+
+        assert_eq!(commit_count(&remote, "gh-pages"), "1");
+    }
+
+    #[test]
+    fn deploy_dry_run_pushes_nothing() {
+        let root = tempfile::tempdir().unwrap();
+        let remote = setup_git_site(root.path());
+
+        deploy(root.path(), DeployOptions { dry_run: true }).unwrap();
+
+        let remote = Path::new(&remote);
+        let branches = git(remote, None, &["branch", "--list"]).unwrap();
+        assert!(branches.is_empty(), "branches: {branches}");
+    }
+
+    #[test]
+    fn deploy_without_target_returns_error() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "config.toml", "");
+
+        let err = deploy(root.path(), DeployOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no deploy target"), "got: {err}");
+    }
+
+    #[test]
+    fn deploy_without_output_returns_error() {
+        let root = tempfile::tempdir().unwrap();
+        setup_git_site(root.path());
+        fs::remove_dir_all(root.path().join("public")).unwrap();
+
+        let err = deploy(root.path(), DeployOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("run `kiln build` first"), "got: {err}");
+    }
+
+    // ── rsync_command ──
+
+    #[test]
+    fn rsync_command_mirrors_directory_contents() {
+        let command = rsync_command(Path::new("/site/public"), "me@host:/var/www", false);
+        assert_eq!(command.get_program(), "rsync");
+        assert_eq!(
+            args(&command),
+            [
+                "--archive",
+                "--compress",
+                "--delete",
+                "--human-readable",
+                "/site/public/",
+                "me@host:/var/www",
+            ]
+        );
+    }
+
+    #[test]
+    fn rsync_command_dry_run() {
+        let command = rsync_command(Path::new("/site/public"), "me@host:/var/www", true);
+        assert!(args(&command).contains(&"--dry-run".to_owned()));
+    }
+
+    // ── s3_command ──
+
+    #[test]
+    fn s3_command_with_endpoint() {
+        let command = s3_command(
+            Path::new("/site/public"),
+            "s3://site",
+            Some("https://r2.example.com"),
+            true,
+        );
+        assert_eq!(command.get_program(), "aws");
+        assert_eq!(
+            args(&command),
+            [
+                "s3",
+                "sync",
+                "/site/public",
+                "s3://site",
+                "--delete",
+                "--endpoint-url",
+                "https://r2.example.com",
+                "--dryrun",
+            ]
+        );
+    }
+
+    // ── run ──
+
+    #[test]
+    fn run_missing_tool_returns_error() {
+        let err = run(Command::new("nonexistent-deploy-tool-xyz"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("is it installed?"), "got: {err}");
+    }
+
+    #[test]
+    fn run_failing_tool_returns_error() {
+        let err = run(Command::new("false")).unwrap_err().to_string();
+        assert!(err.contains("`false` exited with"), "got: {err}");
+    }
+}
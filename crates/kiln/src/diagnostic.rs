@@ -0,0 +1,501 @@
+use std::fmt;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::Serialize;
+use tracing::dispatcher::{self, Dispatch};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::check::{BrokenLinks, LinkIssue, LinkIssueKind};
+use crate::content::page::PageFailures;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A warning or error as a machine-readable record, printed as one JSON
+/// line per diagnostic with `--message-format json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// What kind of problem this is: `page` for a page that failed to load
+    /// or build, `missing-target` / `missing-anchor` for broken links,
+    /// `error` for other errors, and the reporting module (e.g., `i18n`)
+    /// for warnings.
+    pub code: String,
+    /// File the problem is in, relative to the working directory when
+    /// inside it.
+    pub file: Option<String>,
+    /// 1-based line in `file`.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Splits an error into diagnostics: one per failed page or broken
+    /// link, when it carries them, or a single located-nowhere one.
+    /// File paths are made relative to `base`.
+    #[must_use]
+    pub fn from_error(err: &anyhow::Error, base: &Path) -> Vec<Self> {
+        for cause in err.chain() {
+            if let Some(failures) = cause.downcast_ref::<PageFailures>() {
+                return failures
+                    .iter()
+                    .map(|(source, e)| Self {
+                        severity: Severity::Error,
+                        code: "page".into(),
+                        file: Some(relative_path(source, base)),
+                        line: None,
+                        message: format!("{e:#}"),
+                    })
+                    .collect();
+            }
+            if let Some(broken) = cause.downcast_ref::<BrokenLinks>() {
+                return broken
+                    .report
+                    .issues
+                    .iter()
+                    .map(|issue| Self::from_link_issue(issue, broken, base))
+                    .collect();
+            }
+        }
+        vec![Self {
+            severity: Severity::Error,
+            code: "error".into(),
+            file: None,
+            line: None,
+            message: format!("{err:#}"),
+        }]
+    }
+
+    /// Turns a warning or error event into a diagnostic, located by its
+    /// `file` and `line` fields and coded by its target module; other
+    /// fields are appended to the message as `key=value`. File paths are
+    /// made relative to `base`.
+    ///
+    /// Returns `None` for events below warning level.
+    fn from_event(event: &Event<'_>, base: &Path) -> Option<Self> {
+        let severity = match *event.metadata().level() {
+            Level::ERROR => Severity::Error,
+            Level::WARN => Severity::Warning,
+            _ => return None,
+        };
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+
+        let target = event.metadata().target();
+        let mut message = fields.message;
+        for field in fields.extra {
+            message.push(' ');
+            message.push_str(&field);
+        }
+        Some(Self {
+            severity,
+            code: target.strip_prefix("kiln::").unwrap_or(target).to_owned(),
+            file: fields
+                .file
+                .map(|file| relative_path(Path::new(&file), base)),
+            line: fields.line,
+            message,
+        })
+    }
+
+    /// Locates a broken link in the content source it was rendered from,
+    /// or in the output file for pages without one.
+    fn from_link_issue(issue: &LinkIssue, broken: &BrokenLinks, base: &Path) -> Self {
+        let output = broken.report.output_dir.join(&issue.file);
+        let (file, line, message) = match &issue.source {
+            Some(source) => (
+                broken.root.join(source),
+                None,
+                format!(
+                    "{} (in {}:{})",
+                    issue.problem(),
+                    relative_path(&output, base),
+                    issue.line
+                ),
+            ),
+            None => (output, Some(issue.line), issue.problem()),
+        };
+        let code = match issue.kind {
+            LinkIssueKind::MissingTarget => "missing-target",
+            LinkIssueKind::MissingAnchor(_) => "missing-anchor",
+        };
+        Self {
+            severity: Severity::Error,
+            code: code.into(),
+            file: Some(relative_path(&file, base)),
+            line,
+            message,
+        }
+    }
+}
+
+/// A tracing layer turning warning and error events into [`Diagnostic`]s,
+/// handed to `emit`.
+///
+/// The event's `file` and `line` fields locate the diagnostic; other fields
+/// are appended to the message as `key=value`.
+pub struct DiagnosticLayer<F> {
+    base: PathBuf,
+    emit: F,
+}
+
+impl<F: Fn(Diagnostic)> DiagnosticLayer<F> {
+    /// Creates a layer making `file` fields relative to `base`.
+    #[must_use]
+    pub fn new(base: &Path, emit: F) -> Self {
+        Self {
+            base: base.to_owned(),
+            emit,
+        }
+    }
+}
+
+impl<S, F> Layer<S> for DiagnosticLayer<F>
+where
+    S: Subscriber,
+    F: Fn(Diagnostic) + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if let Some(diagnostic) = Diagnostic::from_event(event, &self.base) {
+            (self.emit)(diagnostic);
+        }
+    }
+}
+
+/// Runs `f`, returning its result along with the warnings logged on this
+/// thread meanwhile, as [`Diagnostic`]s with file paths kept as logged.
+///
+/// The warnings still reach the subscriber in effect, as does every other
+/// event and span.
+pub fn collect_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<Diagnostic>) {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let recorder = WarningRecorder {
+        inner: dispatcher::get_default(Dispatch::clone),
+        warnings: Arc::clone(&warnings),
+    };
+    let result = dispatcher::with_default(&Dispatch::new(recorder), f);
+    let warnings = mem::take(&mut *warnings.lock().unwrap_or_else(PoisonError::into_inner));
+    (result, warnings)
+}
+
+/// A subscriber forwarding everything to `inner`, recording warning events
+/// on the way, whether or not `inner` is interested in them.
+struct WarningRecorder {
+    inner: Dispatch,
+    warnings: Arc<Mutex<Vec<Diagnostic>>>,
+}
+
+fn is_warning(metadata: &Metadata<'_>) -> bool {
+    metadata.is_event() && *metadata.level() == Level::WARN
+}
+
+impl Subscriber for WarningRecorder {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if is_warning(metadata) {
+            Interest::always()
+        } else {
+            self.inner.register_callsite(metadata)
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        is_warning(metadata) || self.inner.enabled(metadata)
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.inner.new_span(span)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        self.inner.record(span, values);
+    }
+
+    fn record_follows_from(&self, span: &Id, follows: &Id) {
+        self.inner.record_follows_from(span, follows);
+    }
+
+    fn event(&self, event: &Event<'_>) {
+        if is_warning(event.metadata())
+            && let Some(diagnostic) = Diagnostic::from_event(event, Path::new(""))
+        {
+            self.warnings
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(diagnostic);
+        }
+        if self.inner.enabled(event.metadata()) {
+            self.inner.event(event);
+        }
+    }
+
+    fn enter(&self, span: &Id) {
+        self.inner.enter(span);
+    }
+
+    fn exit(&self, span: &Id) {
+        self.inner.exit(span);
+    }
+
+    fn clone_span(&self, id: &Id) -> Id {
+        self.inner.clone_span(id)
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        self.inner.try_close(id)
+    }
+}
+
+/// The fields of a tracing event, split into location and message.
+#[derive(Default)]
+struct EventFields {
+    message: String,
+    file: Option<String>,
+    line: Option<usize>,
+    /// Other fields, as `key=value`.
+    extra: Vec<String>,
+}
+
+impl Visit for EventFields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "line" {
+            self.line = usize::try_from(value).ok();
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            "file" => self.file = Some(format!("{value:?}").trim_matches('"').to_owned()),
+            name => self.extra.push(format!("{name}={value:?}")),
+        }
+    }
+}
+
+/// Formats `path` relative to `base` when inside it.
+fn relative_path(path: &Path, base: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::check::LinkReport;
+    use crate::content::page::PageErrors;
+
+    // ── Diagnostic::from_error ──
+
+    #[test]
+    fn from_error_splits_page_failures() {
+        let mut errors = PageErrors::default();
+        errors.collect::<()>(
+            Path::new("/site/content/a.md"),
+            Err(anyhow::anyhow!("bad date").context("failed to parse frontmatter")),
+        );
+        errors.collect::<()>(
+            Path::new("/site/content/b.md"),
+            Err(anyhow::anyhow!("unknown snippet")),
+        );
+        let err = errors
+            .into_result("build")
+            .unwrap_err()
+            .context("build failed");
+
+        let diagnostics = Diagnostic::from_error(&err, Path::new("/site"));
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    severity: Severity::Error,
+                    code: "page".into(),
+                    file: Some("content/a.md".into()),
+                    line: None,
+                    message: "failed to parse frontmatter: bad date".into(),
+                },
+                Diagnostic {
+                    severity: Severity::Error,
+                    code: "page".into(),
+                    file: Some("content/b.md".into()),
+                    line: None,
+                    message: "unknown snippet".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn from_error_locates_broken_links() {
+        let issue = |source: Option<&str>, kind| LinkIssue {
+            file: PathBuf::from("posts/hello/index.html"),
+            source: source.map(PathBuf::from),
+            line: 4,
+            target: "/about/#team".into(),
+            kind,
+        };
+        let err = anyhow::Error::new(BrokenLinks {
+            root: PathBuf::from("/site"),
+            report: LinkReport {
+                output_dir: PathBuf::from("/site/public"),
+                issues: vec![
+                    issue(
+                        Some("content/posts/hello.md"),
+                        LinkIssueKind::MissingAnchor("team".into()),
+                    ),
+                    issue(None, LinkIssueKind::MissingTarget),
+                ],
+                ..LinkReport::default()
+            },
+        });
+
+        let diagnostics = Diagnostic::from_error(&err, Path::new("/site"));
+        let located: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.code.as_str(), d.file.as_deref(), d.line))
+            .collect();
+        assert_eq!(
+            located,
+            [
+                ("missing-anchor", Some("content/posts/hello.md"), None),
+                (
+                    "missing-target",
+                    Some("public/posts/hello/index.html"),
+                    Some(4)
+                ),
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "`/about/#team` has no anchor `#team` (in public/posts/hello/index.html:4)"
+        );
+    }
+
+    #[test]
+    fn from_error_other_errors_are_unlocated() {
+        let err = anyhow::anyhow!("no such file").context("failed to load config");
+        let diagnostics = Diagnostic::from_error(&err, Path::new("/site"));
+        assert_eq!(
+            diagnostics,
+            [Diagnostic {
+                severity: Severity::Error,
+                code: "error".into(),
+                file: None,
+                line: None,
+                message: "failed to load config: no such file".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn diagnostic_serializes_every_field() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Warning,
+            code: "i18n".into(),
+            file: None,
+            line: None,
+            message: "missing i18n key".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&diagnostic).unwrap(),
+            r#"{"severity":"warning","code":"i18n","file":null,"line":null,"message":"missing i18n key"}"#
+        );
+    }
+
+    // ── DiagnosticLayer ──
+
+    #[test]
+    fn diagnostic_layer_records_warnings() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&emitted);
+        let layer = DiagnosticLayer::new(Path::new("/site"), move |d| {
+            sink.lock().unwrap().push(d);
+        });
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::info!("not a diagnostic");
+            tracing::warn!(key = "nav.home", "missing i18n key");
+            tracing::warn!(
+                target: "kiln::minify",
+                file = %Path::new("/site/public/app.js").display(),
+                line = 3_u64,
+                "skipping minification"
+            );
+        });
+
+        let emitted = emitted.lock().unwrap();
+        assert_eq!(emitted.len(), 2);
+        assert_eq!(emitted[0].severity, Severity::Warning);
+        assert_eq!(emitted[0].message, r#"missing i18n key key="nav.home""#);
+        assert_eq!(
+            emitted[1],
+            Diagnostic {
+                severity: Severity::Warning,
+                code: "minify".into(),
+                file: Some("public/app.js".into()),
+                line: Some(3),
+                message: "skipping minification".into(),
+            }
+        );
+    }
+
+    // ── collect_warnings ──
+
+    #[test]
+    fn collect_warnings_records_and_forwards_warnings() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&emitted);
+        let layer = DiagnosticLayer::new(Path::new("/site"), move |d| {
+            sink.lock().unwrap().push(d);
+        });
+
+        let (result, warnings) =
+            tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+                collect_warnings(|| {
+                    tracing::info!("not a warning");
+                    tracing::error!("not a warning either");
+                    tracing::warn!(file = "/site/content/a.md", "missing alt text");
+                    42
+                })
+            });
+
+        assert_eq!(result, 42);
+        assert_eq!(
+            warnings,
+            [Diagnostic {
+                severity: Severity::Warning,
+                code: "diagnostic::tests".into(),
+                file: Some("/site/content/a.md".into()),
+                line: None,
+                message: "missing alt text".into(),
+            }]
+        );
+        let emitted = emitted.lock().unwrap();
+        let forwarded: Vec<_> = emitted.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(forwarded, ["not a warning either", "missing alt text"]);
+    }
+
+    #[test]
+    fn collect_warnings_without_subscriber() {
+        let ((), warnings) = collect_warnings(|| tracing::warn!("unheard"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message, "unheard");
+    }
+}
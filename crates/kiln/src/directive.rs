@@ -1,6 +1,9 @@
 pub mod callout;
+pub mod csv;
 pub mod div;
+pub mod gallery;
 pub mod parser;
+pub mod spoiler;
 
 use std::borrow::Cow;
 use std::collections::BTreeMap;
@@ -43,8 +46,9 @@ impl fmt::Display for CalloutKind {
     }
 }
 
-/// Parsed directive type — either a callout or an unrecognized name preserved
-/// for future extension.
+/// Parsed directive type — a built-in callout, CSV table, gallery, spoiler,
+/// or raw passthrough, or an
+/// unrecognized name preserved for future extension.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DirectiveKind {
     Callout {
@@ -52,6 +56,20 @@ pub enum DirectiveKind {
         title: Option<String>,
         open: bool,
     },
+    /// CSV data from the body, or from `file` in the page bundle, rendered
+    /// as a `<table>`.
+    Csv {
+        file: Option<String>,
+        header: bool,
+        delimiter: u8,
+    },
+    /// Images of the page bundle, optionally filtered by `glob`, rendered
+    /// as a grid of linked thumbnails.
+    Gallery { glob: Option<String> },
+    /// Collapsed reveal element for quiz answers and plot spoilers.
+    Spoiler { label: Option<String> },
+    /// Body emitted verbatim, bypassing markdown and directive processing.
+    Raw,
     /// Unrecognized type — rendered as a `<div>` or passed through as-is.
     Unknown {
         name: String,
@@ -61,6 +79,20 @@ pub enum DirectiveKind {
 }
 
 impl DirectiveKind {
+    /// Returns the directive name as written after the opening fence, with
+    /// callouts normalized to `callout`.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Callout { .. } => "callout",
+            Self::Csv { .. } => "csv",
+            Self::Gallery { .. } => "gallery",
+            Self::Spoiler { .. } => "spoiler",
+            Self::Raw => "raw",
+            Self::Unknown { name, .. } => name,
+        }
+    }
+
     /// Parses a directive name and structured arguments into the appropriate
     /// variant.
     pub(crate) fn from_parsed(
@@ -72,6 +104,25 @@ impl DirectiveKind {
             let (kind, title, open) = callout::parse_named_args(&named_args);
             return Self::Callout { kind, title, open };
         }
+        if name.eq_ignore_ascii_case("csv") {
+            let (file, header, delimiter) = csv::parse_named_args(&named_args);
+            return Self::Csv {
+                file,
+                header,
+                delimiter,
+            };
+        }
+        if name.eq_ignore_ascii_case("gallery") {
+            let glob = gallery::parse_named_args(&named_args);
+            return Self::Gallery { glob };
+        }
+        if name.eq_ignore_ascii_case("spoiler") {
+            let label = spoiler::parse_named_args(&named_args);
+            return Self::Spoiler { label };
+        }
+        if name.eq_ignore_ascii_case("raw") {
+            return Self::Raw;
+        }
         Self::Unknown {
             name: name.to_string(),
             positional_args,
@@ -177,6 +228,8 @@ pub(crate) struct DirectiveArgs {
     pub named: BTreeMap<String, String>,
     pub id: Option<String>,
     pub classes: Vec<String>,
+    /// Whether a quoted value is never closed, running to the end of input.
+    pub unclosed_quote: bool,
 }
 
 /// Parses a directive attribute block into structured components.
@@ -197,6 +250,7 @@ pub(crate) fn parse_directive_args(input: &str) -> DirectiveArgs {
         named: BTreeMap::new(),
         id: None,
         classes: Vec::new(),
+        unclosed_quote: false,
     };
     let mut rest = input.trim();
 
@@ -224,6 +278,7 @@ pub(crate) fn parse_directive_args(input: &str) -> DirectiveArgs {
         // Quoted string → positional arg.
         if let Some(after_quote) = rest.strip_prefix('"') {
             let (end, has_escapes) = scan_quoted_value(after_quote);
+            result.unclosed_quote |= end == after_quote.len();
             let raw = &after_quote[..end];
             let value = if has_escapes {
                 unescape_quoted(raw)
@@ -246,6 +301,7 @@ pub(crate) fn parse_directive_args(input: &str) -> DirectiveArgs {
 
             if let Some(after_q) = after_eq.strip_prefix('"') {
                 let (end, has_escapes) = scan_quoted_value(after_q);
+                result.unclosed_quote |= end == after_q.len();
                 let raw = &after_q[..end];
                 let value = if has_escapes {
                     unescape_quoted(raw)
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::html::{escape, writeln_indented};
+use crate::template::checked_relative_path;
+
+/// Renders CSV data to an HTML `<table>`.
+///
+/// Output structure:
+///
+/// ```html
+/// <table class="csv-table">
+///   <thead>
+///     <tr><th>Name</th><th>Score</th></tr>
+///   </thead>
+///   <tbody>
+///     <tr><td>Alice</td><td>90</td></tr>
+///   </tbody>
+/// </table>
+/// ```
+///
+/// - `header`: when `false`, every record goes into `<tbody>`.
+/// - `delimiter`: the field separator, e.g. `b';'` or `b'\t'`.
+/// - Fields are trimmed and HTML-escaped; short records are padded with
+///   empty cells so every row has the same width.
+/// - `id` / `classes`: optional Pandoc attributes rendered on the `<table>`.
+///
+/// # Errors
+///
+/// Returns an error if `data` is not valid CSV.
+pub fn render_csv_table(
+    data: &str,
+    header: bool,
+    delimiter: u8,
+    id: Option<&str>,
+    classes: &[String],
+) -> Result<String> {
+    let records = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .delimiter(delimiter)
+        .from_reader(data.as_bytes())
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .context("invalid CSV")?;
+    let width = records
+        .iter()
+        .map(csv::StringRecord::len)
+        .max()
+        .unwrap_or(0);
+
+    let id_attr = id
+        .map(|v| format!(r#" id="{}""#, escape(v)))
+        .unwrap_or_default();
+    let mut class_val = "csv-table".to_owned();
+    for class in classes {
+        class_val.push(' ');
+        class_val.push_str(&escape(class));
+    }
+
+    let mut html = String::new();
+    writeln_indented!(&mut html, 0, r#"<table{id_attr} class="{class_val}">"#);
+    let (head, body) = match records.split_first() {
+        Some((first, rest)) if header => (Some(first), rest),
+        _ => (None, records.as_slice()),
+    };
+    if let Some(head) = head {
+        writeln_indented!(&mut html, 1, "<thead>");
+        writeln_indented!(&mut html, 2, "{}", table_row(head, "th", width));
+        writeln_indented!(&mut html, 1, "</thead>");
+    }
+    if !body.is_empty() {
+        writeln_indented!(&mut html, 1, "<tbody>");
+        for record in body {
+            writeln_indented!(&mut html, 2, "{}", table_row(record, "td", width));
+        }
+        writeln_indented!(&mut html, 1, "</tbody>");
+    }
+    writeln_indented!(&mut html, 0, "</table>");
+    Ok(html)
+}
+
+/// Renders one `<tr>` of `width` `cell` elements.
+fn table_row(record: &csv::StringRecord, cell: &str, width: usize) -> String {
+    let mut row = String::from("<tr>");
+    for i in 0..width {
+        let field = record.get(i).unwrap_or_default();
+        _ = write!(row, "<{cell}>{}</{cell}>", escape(field));
+    }
+    row.push_str("</tr>");
+    row
+}
+
+/// Reads the CSV file `file` of a `csv` directive from the page bundle at
+/// `source_dir`.
+///
+/// # Errors
+///
+/// Returns an error if `file` is not a relative path inside the bundle
+/// (no `..`, absolute, or rooted components), the page has no bundle
+/// directory, or the file cannot be read.
+pub fn read_csv_file(file: &str, source_dir: Option<&Path>) -> Result<String> {
+    let rel = checked_relative_path(file)?;
+    let dir = source_dir.with_context(|| format!("csv file {file} needs a page bundle"))?;
+    let path = dir.join(rel);
+    fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// Extracts CSV directive parameters from pre-parsed named arguments.
+///
+/// Recognized keys: `file` (a CSV file in the page bundle, read instead of
+/// the body), `header` (defaults to `true`), and `delimiter` (a single ASCII
+/// character or `tab`, defaults to `,`).
+#[must_use]
+pub(super) fn parse_named_args(named: &BTreeMap<String, String>) -> (Option<String>, bool, u8) {
+    let file = named.get("file").filter(|v| !v.is_empty()).cloned();
+
+    let header = named
+        .get("header")
+        .is_none_or(|v| !v.eq_ignore_ascii_case("false"));
+
+    let delimiter = match named.get("delimiter").map(String::as_str) {
+        Some("tab" | "\\t" | "\t") => b'\t',
+        Some(v) if v.len() == 1 && v.is_ascii() => v.as_bytes()[0],
+        _ => b',',
+    };
+
+    (file, header, delimiter)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── render_csv_table ──
+
+    #[test]
+    fn render_csv_table_with_header() {
+        let html =
+            render_csv_table("Name, Score\nAlice, 90\nBob\n", true, b',', None, &[]).unwrap();
+        assert_eq!(
+            html,
+            indoc! {"
+                <table class=\"csv-table\">
+                  <thead>
+                    <tr><th>Name</th><th>Score</th></tr>
+                  </thead>
+                  <tbody>
+                    <tr><td>Alice</td><td>90</td></tr>
+                    <tr><td>Bob</td><td></td></tr>
+                  </tbody>
+                </table>
+            "}
+        );
+    }
+
+    #[test]
+    fn render_csv_table_without_header_escapes_fields() {
+        let html = render_csv_table(
+            "\"a;b\";<i>x</i>\n",
+            false,
+            b';',
+            Some("data"),
+            &["compact".into()],
+        )
+        .unwrap();
+        assert_eq!(
+            html,
+            indoc! {r#"
+                <table id="data" class="csv-table compact">
+                  <tbody>
+                    <tr><td>a;b</td><td>&lt;i&gt;x&lt;/i&gt;</td></tr>
+                  </tbody>
+                </table>
+            "#}
+        );
+    }
+
+    #[test]
+    fn render_csv_table_empty_input() {
+        let html = render_csv_table("", true, b',', None, &[]).unwrap();
+        assert_eq!(html, "<table class=\"csv-table\">\n</table>\n");
+    }
+
+    // ── read_csv_file ──
+
+    #[test]
+    fn read_csv_file_rejects_paths_outside_bundle() {
+        let dir = Path::new("/site/content/posts/a");
+        for file in ["../secret.csv", "/etc/passwd"] {
+            let err = read_csv_file(file, Some(dir)).unwrap_err().to_string();
+            assert!(err.contains("path traversal not allowed"), "{file}: {err}");
+        }
+    }
+
+    // ── parse_named_args ──
+
+    #[test]
+    fn parse_named_args_defaults() {
+        assert_eq!(parse_named_args(&BTreeMap::new()), (None, true, b','));
+    }
+
+    #[test]
+    fn parse_named_args_all_keys() {
+        let named = BTreeMap::from([
+            ("file".to_owned(), "data.csv".to_owned()),
+            ("header".to_owned(), "false".to_owned()),
+            ("delimiter".to_owned(), "tab".to_owned()),
+        ]);
+        assert_eq!(
+            parse_named_args(&named),
+            (Some("data.csv".to_owned()), false, b'\t')
+        );
+    }
+}
@@ -1,13 +1,45 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
 use crate::html::escape;
 
 /// Renders an unknown directive as a `<div>` wrapper.
 ///
 /// - The directive name (if non-empty) becomes the first CSS class.
 /// - Extra `.class` tokens from Pandoc attributes are appended.
-/// - When no name, id, or classes are present, the body is passed through as-is.
+/// - `key=value` attributes become `data-key="value"`, as in Pandoc's HTML
+///   output. Keys are lowercased; keys with characters other than ASCII
+///   alphanumerics, `-`, and `_` are skipped.
+/// - When no name, id, classes, or attributes are present, the body is
+///   passed through as-is.
 #[must_use]
-pub fn render_div(name: &str, id: Option<&str>, classes: &[String], body_html: &str) -> String {
-    let has_attrs = id.is_some() || !name.is_empty() || !classes.is_empty();
+pub fn render_div(
+    name: &str,
+    id: Option<&str>,
+    classes: &[String],
+    attrs: &BTreeMap<String, String>,
+    body_html: &str,
+) -> String {
+    let mut data_attrs = String::new();
+    for (key, value) in attrs {
+        let key = key.strip_prefix("data-").unwrap_or(key);
+        if key.is_empty()
+            || !key
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            continue;
+        }
+        _ = write!(
+            data_attrs,
+            r#" data-{}="{}""#,
+            key.to_ascii_lowercase(),
+            escape(value)
+        );
+    }
+
+    let has_attrs =
+        id.is_some() || !name.is_empty() || !classes.is_empty() || !data_attrs.is_empty();
     if !has_attrs {
         return body_html.to_owned();
     }
@@ -29,7 +61,7 @@ pub fn render_div(name: &str, id: Option<&str>, classes: &[String], body_html: &
         format!(r#" class="{}""#, class_parts.join(" "))
     };
 
-    format!("<div{id_attr}{class_attr}>{body_html}</div>\n")
+    format!("<div{id_attr}{class_attr}{data_attrs}>{body_html}</div>\n")
 }
 
 #[cfg(test)]
@@ -42,7 +74,13 @@ mod tests {
 
     #[test]
     fn render_with_name() {
-        let html = render_div("compact-table", None, &[], "<p>Content</p>\n");
+        let html = render_div(
+            "compact-table",
+            None,
+            &[],
+            &BTreeMap::new(),
+            "<p>Content</p>\n",
+        );
         assert_eq!(
             html,
             indoc! {r#"
@@ -54,7 +92,13 @@ mod tests {
 
     #[test]
     fn render_with_id() {
-        let html = render_div("", Some("section-1"), &[], "<p>Content</p>\n");
+        let html = render_div(
+            "",
+            Some("section-1"),
+            &[],
+            &BTreeMap::new(),
+            "<p>Content</p>\n",
+        );
         assert_eq!(
             html,
             indoc! {r#"
@@ -67,7 +111,7 @@ mod tests {
     #[test]
     fn render_with_extra_classes() {
         let classes = vec!["compact".into(), "wide".into()];
-        let html = render_div("", None, &classes, "<p>Content</p>\n");
+        let html = render_div("", None, &classes, &BTreeMap::new(), "<p>Content</p>\n");
         assert_eq!(
             html,
             indoc! {r#"
@@ -80,7 +124,13 @@ mod tests {
     #[test]
     fn render_with_name_id_and_classes() {
         let classes = vec!["extra".into(), "wide".into()];
-        let html = render_div("wrapper", Some("main"), &classes, "<p>Body</p>\n");
+        let html = render_div(
+            "wrapper",
+            Some("main"),
+            &classes,
+            &BTreeMap::new(),
+            "<p>Body</p>\n",
+        );
         assert_eq!(
             html,
             indoc! {r#"
@@ -92,13 +142,30 @@ mod tests {
 
     #[test]
     fn render_without_attrs() {
-        let html = render_div("", None, &[], "<p>Content</p>\n");
+        let html = render_div("", None, &[], &BTreeMap::new(), "<p>Content</p>\n");
         assert_eq!(html, "<p>Content</p>\n");
     }
 
+    #[test]
+    fn render_with_data_attrs() {
+        let attrs = BTreeMap::from([
+            ("Cols".to_owned(), "3".to_owned()),
+            ("data-theme".to_owned(), r#"dark"1"#.to_owned()),
+            ("on click".to_owned(), "x".to_owned()),
+        ]);
+        let html = render_div("", None, &[], &attrs, "<p>Content</p>\n");
+        assert_eq!(
+            html,
+            indoc! {r#"
+                <div data-cols="3" data-theme="dark&quot;1"><p>Content</p>
+                </div>
+            "#},
+        );
+    }
+
     #[test]
     fn render_escapes_name() {
-        let html = render_div("<script>", None, &[], "");
+        let html = render_div("<script>", None, &[], &BTreeMap::new(), "");
         assert!(
             html.contains(r#"class="&lt;script&gt;""#),
             "name should be escaped, html:\n{html}"
@@ -112,7 +179,7 @@ mod tests {
     #[test]
     fn render_escapes_id_and_classes() {
         let classes = vec![r#"a"b"#.into()];
-        let html = render_div("", Some(r#"x"y"#), &classes, "");
+        let html = render_div("", Some(r#"x"y"#), &classes, &BTreeMap::new(), "");
         assert!(
             html.contains(r#"id="x&quot;y""#),
             "id should be escaped, html:\n{html}"
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::html::{escape, writeln_indented};
+use crate::render::image::render_inline_image;
+use crate::render::responsive::ResolvedImage;
+
+/// Image formats a gallery lists, by extension.
+const GALLERY_EXTENSIONS: [&str; 7] = ["avif", "gif", "jpeg", "jpg", "png", "svg", "webp"];
+
+/// Returns the images among a page bundle's `assets` for a `gallery`
+/// directive, as paths relative to `bundle_dir` with `/` separators.
+///
+/// A `glob` with a `/` matches the whole relative path
+/// (`photos/*.jpg`), one without matches the file name alone (`*.jpg`).
+/// `*` and `?` stay within a path segment; `**` crosses them.
+///
+/// # Errors
+///
+/// Returns an error if `glob` is not a valid glob.
+pub fn gallery_images(
+    assets: &[PathBuf],
+    bundle_dir: &Path,
+    glob: Option<&str>,
+) -> Result<Vec<String>> {
+    let pattern = match glob {
+        Some(glob) => Some((glob_matcher(glob)?, glob.contains('/'))),
+        None => None,
+    };
+    let images = assets
+        .iter()
+        .filter(|asset| {
+            asset
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| GALLERY_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        })
+        .filter_map(|asset| relative_src(asset.strip_prefix(bundle_dir).ok()?))
+        .filter(|src| {
+            pattern.as_ref().is_none_or(|(matcher, whole_path)| {
+                let target = if *whole_path {
+                    src.as_str()
+                } else {
+                    src.rsplit('/').next().unwrap_or(src)
+                };
+                matcher.is_match(target)
+            })
+        })
+        .collect();
+    Ok(images)
+}
+
+/// Joins the segments of a bundle-relative path with `/`, or returns `None`
+/// for paths that are not plain segments.
+fn relative_src(path: &Path) -> Option<String> {
+    let segments = path
+        .components()
+        .map(|c| match c {
+            Component::Normal(segment) => segment.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(segments.join("/"))
+}
+
+/// Compiles `glob` into a matcher whose `*` and `?` do not match `/`.
+fn glob_matcher(glob: &str) -> Result<GlobMatcher> {
+    let glob = GlobBuilder::new(glob)
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("invalid gallery glob `{glob}`"))?;
+    Ok(glob.compile_matcher())
+}
+
+/// Renders a `gallery` directive as a grid of thumbnails, each linking to
+/// its full-size image.
+///
+/// Output structure:
+///
+/// ```html
+/// <div class="gallery">
+///   <figure><a href="photos/a.jpg"><img src="photos/a.jpg" alt="a" ... /></a></figure>
+/// </div>
+/// ```
+///
+/// - `images`: bundle-relative sources, each with its intrinsic size and
+///   responsive variants when found on disk; the `<img>` gets these like
+///   any markdown image.
+/// - The file stem is the alt text.
+/// - `id` / `classes`: optional Pandoc attributes rendered on the `<div>`.
+#[must_use]
+pub fn render_gallery(
+    images: &[(String, Option<ResolvedImage>)],
+    id: Option<&str>,
+    classes: &[String],
+) -> String {
+    let id_attr = id
+        .map(|v| format!(r#" id="{}""#, escape(v)))
+        .unwrap_or_default();
+    let mut class_val = "gallery".to_owned();
+    for class in classes {
+        class_val.push(' ');
+        class_val.push_str(&escape(class));
+    }
+
+    let mut html = String::new();
+    writeln_indented!(&mut html, 0, r#"<div{id_attr} class="{class_val}">"#);
+    for (src, image) in images {
+        let alt = Path::new(src)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let img = render_inline_image(src, alt, "", None, image.as_ref());
+        writeln_indented!(
+            &mut html,
+            1,
+            r#"<figure><a href="{}">{img}</a></figure>"#,
+            escape(src)
+        );
+    }
+    writeln_indented!(&mut html, 0, "</div>");
+    html
+}
+
+/// Extracts gallery directive parameters from pre-parsed named arguments.
+///
+/// Recognized keys: `glob` (filters the bundle's images).
+#[must_use]
+pub(super) fn parse_named_args(named: &BTreeMap<String, String>) -> Option<String> {
+    named.get("glob").filter(|v| !v.is_empty()).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn assets(bundle: &Path, files: &[&str]) -> Vec<PathBuf> {
+        files.iter().map(|f| bundle.join(f)).collect()
+    }
+
+    // ── gallery_images ──
+
+    #[test]
+    fn gallery_images_lists_images_only() {
+        let bundle = Path::new("/site/content/posts/trip");
+        let assets = assets(bundle, &["a.JPG", "data.csv", "photos/b.webp", "style.css"]);
+        assert_eq!(
+            gallery_images(&assets, bundle, None).unwrap(),
+            ["a.JPG", "photos/b.webp"]
+        );
+    }
+
+    #[test]
+    fn gallery_images_filters_by_glob() {
+        let bundle = Path::new("/site/content/posts/trip");
+        let assets = assets(
+            bundle,
+            &[
+                "cover.png",
+                "photos/a.jpg",
+                "photos/day2/b.jpg",
+                "photos/c.png",
+            ],
+        );
+        assert_eq!(
+            gallery_images(&assets, bundle, Some("*.jpg")).unwrap(),
+            ["photos/a.jpg", "photos/day2/b.jpg"]
+        );
+        assert_eq!(
+            gallery_images(&assets, bundle, Some("photos/*")).unwrap(),
+            ["photos/a.jpg", "photos/c.png"]
+        );
+        assert_eq!(
+            gallery_images(&assets, bundle, Some("photos/**/?.jpg")).unwrap(),
+            ["photos/a.jpg", "photos/day2/b.jpg"]
+        );
+    }
+
+    #[test]
+    fn gallery_images_invalid_glob_returns_error() {
+        let bundle = Path::new("/site/content/posts/trip");
+        let assets = assets(bundle, &["photos/a.jpg"]);
+        let err = gallery_images(&assets, bundle, Some("photos/[a.jpg"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid gallery glob `photos/[a.jpg`"),
+            "{err}"
+        );
+    }
+
+    // ── render_gallery ──
+
+    #[test]
+    fn render_gallery_links_thumbnails() {
+        let images = [
+            ("photos/a.jpg".to_owned(), None),
+            ("b&c.png".to_owned(), None),
+        ];
+        let html = render_gallery(&images, Some("trip"), &["wide".into()]);
+        assert_eq!(
+            html,
+            indoc! {r#"
+                <div id="trip" class="gallery wide">
+                  <figure><a href="photos/a.jpg"><img src="photos/a.jpg" alt="a" loading="lazy" decoding="async" /></a></figure>
+                  <figure><a href="b&amp;c.png"><img src="b&amp;c.png" alt="b&amp;c" loading="lazy" decoding="async" /></a></figure>
+                </div>
+            "#}
+        );
+    }
+
+    // ── parse_named_args ──
+
+    #[test]
+    fn parse_named_args_reads_glob() {
+        assert_eq!(parse_named_args(&BTreeMap::new()), None);
+        let named = BTreeMap::from([("glob".to_owned(), "*.jpg".to_owned())]);
+        assert_eq!(parse_named_args(&named), Some("*.jpg".to_owned()));
+    }
+}
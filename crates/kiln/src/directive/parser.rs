@@ -12,6 +12,8 @@ struct StackEntry {
     body_start: usize,
     /// Byte offset of the opening fence line.
     range_start: usize,
+    /// 1-based line of the opening fence.
+    line: usize,
 }
 
 /// Parsed result from the text after the opening colon fence.
@@ -21,20 +23,80 @@ struct DirectiveHead {
     named_args: BTreeMap<String, String>,
     id: Option<String>,
     classes: Vec<String>,
+    /// Why some of the text after the colons was ignored, if it was.
+    problem: Option<&'static str>,
+}
+
+/// A problem in directive markup, which otherwise renders as plain text or
+/// loses attributes without a word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveProblem {
+    /// 1-based line of the content the problem is on.
+    pub line: usize,
+    pub message: String,
+}
+
+/// The result of [`scan_directives`].
+struct Scan {
+    /// Closed blocks, sorted by ascending byte offset.
+    blocks: Vec<DirectiveBlock>,
+    /// Blocks still open at the end of the content.
+    open: Vec<StackEntry>,
+    /// Problems other than unclosed blocks, in line order.
+    problems: Vec<DirectiveProblem>,
 }
 
 /// Scans content for `:::`-fenced directive blocks.
 ///
 /// Returns blocks sorted by ascending byte offset.
-/// Unclosed directives are silently skipped.
+/// Unclosed directives are skipped (see [`unclosed_directives`] and
+/// [`directive_problems`]).
 #[must_use]
 pub fn parse_directives(content: &str) -> Vec<DirectiveBlock> {
+    scan_directives(content).blocks
+}
+
+/// Returns the names of the directives in `content` that are never closed,
+/// in order. Anonymous fenced divs (`::: {.class}`) have an empty name.
+#[must_use]
+pub fn unclosed_directives(content: &str) -> Vec<String> {
+    scan_directives(content)
+        .open
+        .into_iter()
+        .map(|entry| entry.kind.name().to_owned())
+        .collect()
+}
+
+/// Returns the problems in `content`'s directive markup, in line order:
+/// directives never closed, closing fences that close nothing (too short
+/// for the open directive, or with none open), and opening fences whose
+/// attributes are malformed or partly ignored.
+#[must_use]
+pub fn directive_problems(content: &str) -> Vec<DirectiveProblem> {
+    let scan = scan_directives(content);
+    let mut problems = scan.problems;
+    problems.extend(scan.open.into_iter().map(|entry| DirectiveProblem {
+        line: entry.line,
+        message: match entry.kind.name() {
+            "" => "unclosed fenced div".to_owned(),
+            name => format!("unclosed directive `{name}`"),
+        },
+    }));
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
+
+/// Scans content for directive blocks, returning the closed blocks, the
+/// still-open ones left on the stack, and the problems met on the way.
+fn scan_directives(content: &str) -> Scan {
     let mut blocks = Vec::new();
-    let mut stack = Vec::new();
+    let mut stack: Vec<StackEntry> = Vec::new();
+    let mut problems = Vec::new();
     let mut code_fence = None;
     let mut offset = 0;
 
-    for raw_line in content.split('\n') {
+    for (index, raw_line) in content.split('\n').enumerate() {
+        let line_number = index + 1;
         // +1 for the '\n' delimiter, but cap at content length for the final
         // segment which has no trailing newline.
         let next_offset = (offset + raw_line.len() + 1).min(content.len());
@@ -61,11 +123,26 @@ pub fn parse_directives(content: &str) -> Vec<DirectiveBlock> {
                 // Closing fence — only matches the topmost stack entry if its
                 // opening colon count ≤ the closing count. This prevents a
                 // closing fence from "reaching through" unclosed inner blocks.
-                if stack
-                    .last()
-                    .is_some_and(|e: &StackEntry| e.colon_count <= colon_count)
-                    && let Some(entry) = stack.pop()
-                {
+                let message = match stack.last() {
+                    Some(e) if e.colon_count <= colon_count => None,
+                    Some(e) => Some(format!(
+                        "closing fence `{}` is shorter than the `{}` fence opened on \
+                         line {}; it is ignored",
+                        ":".repeat(colon_count),
+                        ":".repeat(e.colon_count),
+                        e.line
+                    )),
+                    None => Some(format!(
+                        "closing fence `{}` has no open directive; it is ignored",
+                        ":".repeat(colon_count)
+                    )),
+                };
+                if let Some(message) = message {
+                    problems.push(DirectiveProblem {
+                        line: line_number,
+                        message,
+                    });
+                } else if let Some(entry) = stack.pop() {
                     let body = extract_body(content, entry.body_start, offset);
                     blocks.push(DirectiveBlock {
                         kind: entry.kind,
@@ -77,6 +154,12 @@ pub fn parse_directives(content: &str) -> Vec<DirectiveBlock> {
                 }
             } else {
                 let head = parse_directive_head(after_colons);
+                if let Some(problem) = head.problem {
+                    problems.push(DirectiveProblem {
+                        line: line_number,
+                        message: problem.to_owned(),
+                    });
+                }
                 stack.push(StackEntry {
                     colon_count,
                     kind: DirectiveKind::from_parsed(
@@ -88,6 +171,7 @@ pub fn parse_directives(content: &str) -> Vec<DirectiveBlock> {
                     classes: head.classes,
                     body_start: next_offset,
                     range_start: offset,
+                    line: line_number,
                 });
             }
         }
@@ -96,7 +180,11 @@ pub fn parse_directives(content: &str) -> Vec<DirectiveBlock> {
     }
 
     blocks.sort_by_key(|b| b.range.start);
-    blocks
+    Scan {
+        blocks,
+        open: stack,
+        problems,
+    }
 }
 
 /// Returns the number of leading `:` characters if there are at least 3.
@@ -140,16 +228,27 @@ fn parse_directive_head(text: &str) -> DirectiveHead {
             named_args: args.named,
             id: args.id,
             classes: args.classes,
+            problem: args
+                .unclosed_quote
+                .then_some("unclosed `\"` in directive attributes; the value runs to the `}`"),
         };
     }
 
     // Name only — text after the name without braces is ignored.
+    let problem = if rest.starts_with('{') {
+        Some("directive attributes are missing their closing `}`; they are ignored")
+    } else if !rest.is_empty() && !rest.starts_with("<!--") {
+        Some("text after the directive name is ignored; put attributes in `{...}`")
+    } else {
+        None
+    };
     DirectiveHead {
         name: name.to_string(),
         positional_args: Vec::new(),
         named_args: BTreeMap::new(),
         id: None,
         classes: Vec::new(),
+        problem,
     }
 }
 
@@ -602,6 +701,88 @@ mod tests {
         assert!(blocks.is_empty(), "unclosed directive should be skipped");
     }
 
+    #[test]
+    fn unclosed_directives_lists_open_blocks() {
+        let input = indoc! {"
+            ::: callout
+            Closed
+            :::
+            :::: outer
+            ::: {.note}
+            Body
+        "};
+        assert_eq!(unclosed_directives(input), ["outer", ""]);
+        assert!(unclosed_directives("::: callout\nBody\n:::\n").is_empty());
+    }
+
+    // ── directive_problems ──
+
+    #[test]
+    fn directive_problems_locates_each_problem() {
+        let input = indoc! {r#"
+            Intro.
+
+            ::: callout {type=tip
+            Body
+            :::
+            :::
+            :::: outer
+            ::: site https://example.com
+            :::
+            :::
+            ::: music {title="unclosed}
+            :::
+            ```markdown
+            :::
+            ```
+            ::: {.note}
+        "#};
+        let problems: Vec<_> = directive_problems(input)
+            .into_iter()
+            .map(|p| (p.line, p.message))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                (
+                    3,
+                    "directive attributes are missing their closing `}`; they are ignored".into()
+                ),
+                (
+                    6,
+                    "closing fence `:::` has no open directive; it is ignored".into()
+                ),
+                (7, "unclosed directive `outer`".into()),
+                (
+                    8,
+                    "text after the directive name is ignored; put attributes in `{...}`".into()
+                ),
+                (
+                    10,
+                    "closing fence `:::` is shorter than the `::::` fence opened on line 7; \
+                     it is ignored"
+                        .into()
+                ),
+                (
+                    11,
+                    "unclosed `\"` in directive attributes; the value runs to the `}`".into()
+                ),
+                (16, "unclosed fenced div".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn directive_problems_accepts_well_formed_directives() {
+        let input = indoc! {r#"
+            :::: callout {type=tip title="A \"quoted\" title"} <!-- cspell:disable-line -->
+            ::: site <!-- comment -->
+            :::
+            ::::
+        "#};
+        assert!(directive_problems(input).is_empty());
+    }
+
     #[test]
     fn closing_fence_colon_count() {
         let input = indoc! {"
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use crate::html::{escape, writeln_indented};
+
+/// Label of a spoiler without a `label` attribute, when the site and theme
+/// do not translate the `spoiler` i18n string.
+pub(crate) const DEFAULT_LABEL: &str = "Spoiler";
+
+/// Renders a spoiler to HTML as a collapsed `<details>` element, revealed
+/// by clicking its label or focusing it and pressing Enter / Space.
+///
+/// Output structure:
+///
+/// ```html
+/// <details class="spoiler">
+///   <summary class="spoiler-label">Answer</summary>
+///   <div class="spoiler-body">...</div>
+/// </details>
+/// ```
+///
+/// - `label`: the `label` attribute, or the `spoiler` i18n string without one.
+/// - `id` / `classes`: optional Pandoc attributes rendered on the outer element.
+/// - `body_html` must be pre-rendered — the caller handles markdown recursion.
+#[must_use]
+pub fn render_spoiler(
+    label: &str,
+    id: Option<&str>,
+    classes: &[String],
+    body_html: &str,
+) -> String {
+    let label = escape(label);
+
+    let id_attr = id
+        .map(|v| format!(r#" id="{}""#, escape(v)))
+        .unwrap_or_default();
+
+    let mut class_val = "spoiler".to_owned();
+    for class in classes {
+        class_val.push(' ');
+        class_val.push_str(&escape(class));
+    }
+
+    let mut html = String::new();
+    writeln_indented!(&mut html, 0, r#"<details{id_attr} class="{class_val}">"#);
+    writeln_indented!(
+        &mut html,
+        1,
+        r#"<summary class="spoiler-label">{label}</summary>"#
+    );
+    writeln_indented!(
+        &mut html,
+        1,
+        r#"<div class="spoiler-body">{body_html}</div>"#
+    );
+    writeln_indented!(&mut html, 0, "</details>");
+    html
+}
+
+/// Extracts spoiler parameters from pre-parsed named arguments.
+///
+/// Recognized keys: `label`.
+#[must_use]
+pub(super) fn parse_named_args(named: &BTreeMap<String, String>) -> Option<String> {
+    named.get("label").filter(|v| !v.is_empty()).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── render_spoiler ──
+
+    #[test]
+    fn render_spoiler_wraps_body() {
+        let html = render_spoiler(
+            DEFAULT_LABEL,
+            None,
+            &[],
+            "<p>The lighthouse keeper did it.</p>\n",
+        );
+        assert_eq!(
+            html,
+            indoc! {r#"
+                <details class="spoiler">
+                  <summary class="spoiler-label">Spoiler</summary>
+                  <div class="spoiler-body"><p>The lighthouse keeper did it.</p>
+                </div>
+                </details>
+            "#}
+        );
+    }
+
+    #[test]
+    fn render_spoiler_with_label_and_attrs() {
+        let html = render_spoiler("Q & A", Some("q1"), &["quiz".into()], "");
+        assert!(
+            html.starts_with(r#"<details id="q1" class="spoiler quiz">"#),
+            "html:\n{html}"
+        );
+        assert!(html.contains(">Q &amp; A</summary>"), "html:\n{html}");
+    }
+
+    // ── parse_named_args ──
+
+    #[test]
+    fn parse_named_args_reads_label() {
+        assert_eq!(parse_named_args(&BTreeMap::new()), None);
+        let named = BTreeMap::from([("label".to_owned(), "Answer".to_owned())]);
+        assert_eq!(parse_named_args(&named), Some("Answer".to_owned()));
+    }
+}
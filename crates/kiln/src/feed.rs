@@ -2,8 +2,10 @@ use indoc::indoc;
 use jiff::Timestamp;
 use jiff::tz::TimeZone;
 
-use crate::html::{self, writeln_indented};
+use crate::config::Podcast;
+use crate::html::writeln_indented;
 use crate::template::vars::PageSummary;
+use crate::xml::{self, write_element};
 
 /// RSS channel metadata.
 #[derive(Debug)]
@@ -14,6 +16,8 @@ pub struct Channel {
     pub description: String,
     pub language: String,
     pub last_build_date: Option<String>,
+    /// iTunes metadata, making the feed a podcast feed (see `[podcast]`).
+    pub podcast: Option<Podcast>,
 }
 
 /// Default number of items per feed.
@@ -25,47 +29,80 @@ pub const DEFAULT_FEED_LIMIT: usize = 20;
 /// descending (newest first). The feed limits output to `limit` items.
 #[must_use]
 pub fn generate_rss(channel: &Channel, items: &[PageSummary], limit: usize) -> String {
-    let mut xml = String::from(indoc! {r#"
-        <?xml version="1.0" encoding="utf-8" standalone="yes"?>
-        <rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">
-          <channel>
-    "#});
-
-    write_escaped_element(&mut xml, 2, "title", &channel.title);
-    write_escaped_element(&mut xml, 2, "link", &channel.link);
-    write_escaped_element(&mut xml, 2, "description", &channel.description);
-    write_escaped_element(&mut xml, 2, "language", &channel.language);
+    let items = &items[..limit.min(items.len())];
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8" standalone="yes"?>"#);
+    xml.push_str("\n<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\"");
+    if items.iter().any(|item| item.featured_image_thumb.is_some()) {
+        xml.push_str(r#" xmlns:media="http://search.yahoo.com/mrss/""#);
+    }
+    if channel.podcast.is_some() {
+        xml.push_str(r#" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd""#);
+    }
+    xml.push_str(">\n  <channel>\n");
+
+    write_element(&mut xml, 2, "title", &channel.title);
+    write_element(&mut xml, 2, "link", &channel.link);
+    write_element(&mut xml, 2, "description", &channel.description);
+    write_element(&mut xml, 2, "language", &channel.language);
     writeln_indented!(
         &mut xml,
         2,
         r#"<atom:link href="{}" rel="self" type="application/rss+xml" />"#,
-        html::escape(&channel.feed_url),
+        xml::escape(&channel.feed_url),
     );
 
     if let Some(date) = channel.last_build_date.as_deref() {
-        write_escaped_element(&mut xml, 2, "lastBuildDate", date);
+        write_element(&mut xml, 2, "lastBuildDate", date);
+    }
+    if let Some(podcast) = &channel.podcast {
+        write_podcast_channel(&mut xml, podcast, &channel.link);
     }
 
-    for item in items.iter().take(limit) {
+    for item in items {
         writeln_indented!(&mut xml, 2, "<item>");
-        write_escaped_element(&mut xml, 3, "title", &item.title);
-        write_escaped_element(&mut xml, 3, "link", &item.url);
+        write_element(&mut xml, 3, "title", &item.title);
+        write_element(&mut xml, 3, "link", &item.url);
 
         if !item.description.is_empty() {
-            write_escaped_element(&mut xml, 3, "description", &item.description);
+            write_element(&mut xml, 3, "description", &item.description);
         }
 
         if let Some(ref date) = item.date
             && let Some(rfc2822) = iso_to_rfc2822(date)
         {
-            write_escaped_element(&mut xml, 3, "pubDate", &rfc2822);
+            write_element(&mut xml, 3, "pubDate", &rfc2822);
+        }
+
+        if let Some(thumb) = &item.featured_image_thumb {
+            writeln_indented!(
+                &mut xml,
+                3,
+                r#"<media:thumbnail url="{}" />"#,
+                xml::escape(&xml::absolute_url(thumb, &item.url)),
+            );
+        }
+
+        if let Some(audio) = &item.audio {
+            writeln_indented!(
+                &mut xml,
+                3,
+                r#"<enclosure url="{}" length="{}" type="{}" />"#,
+                xml::escape(&xml::absolute_url(&audio.url, &item.url)),
+                audio.length,
+                xml::escape(&audio.mime_type),
+            );
+            if channel.podcast.is_some()
+                && let Some(duration) = &audio.duration
+            {
+                write_element(&mut xml, 3, "itunes:duration", duration);
+            }
         }
 
         writeln_indented!(
             &mut xml,
             3,
             r#"<guid isPermaLink="true">{}</guid>"#,
-            html::escape(&item.url),
+            xml::escape(&item.url),
         );
         writeln_indented!(&mut xml, 2, "</item>");
     }
@@ -87,8 +124,32 @@ pub fn format_rfc2822(ts: Timestamp) -> String {
 
 // ── Helpers ──
 
-fn write_escaped_element(xml: &mut String, level: u8, tag: &str, content: &str) {
-    writeln_indented!(xml, level, "<{tag}>{}</{tag}>", html::escape(content));
+/// Writes the iTunes channel tags of `podcast`, resolving its artwork
+/// against the site `link`.
+fn write_podcast_channel(xml: &mut String, podcast: &Podcast, link: &str) {
+    if let Some(image) = &podcast.image {
+        writeln_indented!(
+            xml,
+            2,
+            r#"<itunes:image href="{}" />"#,
+            xml::escape(&xml::absolute_url(image, link)),
+        );
+    }
+    let category = xml::escape(&podcast.category);
+    if let Some(subcategory) = &podcast.subcategory {
+        writeln_indented!(xml, 2, r#"<itunes:category text="{category}">"#);
+        writeln_indented!(
+            xml,
+            3,
+            r#"<itunes:category text="{}" />"#,
+            xml::escape(subcategory),
+        );
+        writeln_indented!(xml, 2, "</itunes:category>");
+    } else {
+        writeln_indented!(xml, 2, r#"<itunes:category text="{category}" />"#);
+    }
+    let explicit = if podcast.explicit { "true" } else { "false" };
+    write_element(xml, 2, "itunes:explicit", explicit);
 }
 
 /// Converts an ISO 8601 date string to RFC 2822 format for RSS `<pubDate>`.
@@ -102,6 +163,7 @@ fn iso_to_rfc2822(iso: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::template::vars::PageAudio;
 
     fn make_summary(title: &str, url: &str, date: Option<&str>) -> PageSummary {
         PageSummary {
@@ -110,7 +172,10 @@ mod tests {
             date: date.map(String::from),
             pinned: false,
             description: String::new(),
+            summary_html: String::new(),
             featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
             tags: Vec::new(),
             section: None,
         }
@@ -127,6 +192,7 @@ mod tests {
             description: "A test site".into(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
         let items = vec![
             make_summary(
@@ -163,6 +229,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
         let items = vec![make_summary(
             r#"Post "with" <tags>"#,
@@ -191,6 +258,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
         let items: Vec<_> = (1..=5)
             .map(|i| {
@@ -218,6 +286,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
 
         let xml = generate_rss(&channel, &[], DEFAULT_FEED_LIMIT);
@@ -237,6 +306,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: Some("Sun, 15 Mar 2026 10:00:00 +0000".into()),
+            podcast: None,
         };
 
         let xml = generate_rss(&channel, &[], DEFAULT_FEED_LIMIT);
@@ -253,6 +323,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
         let items = vec![make_summary("Post", "https://example.com/post/", None)];
 
@@ -274,6 +345,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
         let mut item = make_summary("Post", "https://example.com/post/", None);
         item.description = "A summary of the post".into();
@@ -286,6 +358,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn generate_rss_includes_media_thumbnail() {
+        let channel = Channel {
+            title: "Site".into(),
+            link: "https://example.com/".into(),
+            feed_url: "https://example.com/index.xml".into(),
+            description: String::new(),
+            language: "en".into(),
+            last_build_date: None,
+            podcast: None,
+        };
+        let mut item = make_summary("Post", "https://example.com/post/", None);
+        item.featured_image_thumb = Some("/post/cover.thumb.webp".into());
+
+        let xml = generate_rss(&channel, &[item], DEFAULT_FEED_LIMIT);
+
+        assert!(
+            xml.contains(r#"xmlns:media="http://search.yahoo.com/mrss/""#),
+            "should declare the media namespace, xml:\n{xml}"
+        );
+        assert!(
+            xml.contains(r#"<media:thumbnail url="https://example.com/post/cover.thumb.webp" />"#),
+            "should include an absolute thumbnail URL, xml:\n{xml}"
+        );
+
+        let plain = make_summary("Post", "https://example.com/post/", None);
+        let xml = generate_rss(&channel, &[plain], DEFAULT_FEED_LIMIT);
+        assert!(!xml.contains("xmlns:media"), "xml:\n{xml}");
+    }
+
+    #[test]
+    fn generate_rss_includes_audio_enclosure() {
+        let mut channel = Channel {
+            title: "Site".into(),
+            link: "https://example.com/".into(),
+            feed_url: "https://example.com/index.xml".into(),
+            description: String::new(),
+            language: "en".into(),
+            last_build_date: None,
+            podcast: None,
+        };
+        let mut item = make_summary("Episode 1", "https://example.com/episodes/one/", None);
+        item.audio = Some(PageAudio {
+            url: "/episodes/one/one.mp3".into(),
+            length: 1234,
+            mime_type: "audio/mpeg".into(),
+            duration: Some("42:10".into()),
+        });
+        let items = [item];
+
+        let xml = generate_rss(&channel, &items, DEFAULT_FEED_LIMIT);
+        assert!(
+            xml.contains(r#"<enclosure url="https://example.com/episodes/one/one.mp3" length="1234" type="audio/mpeg" />"#),
+            "should include an absolute enclosure URL, xml:\n{xml}"
+        );
+        assert!(!xml.contains("itunes"), "xml:\n{xml}");
+
+        channel.podcast = Some(Podcast {
+            category: "Technology".into(),
+            image: Some("/cover.jpg".into()),
+            ..Podcast::default()
+        });
+        let xml = generate_rss(&channel, &items, DEFAULT_FEED_LIMIT);
+        assert!(
+            xml.contains("<itunes:duration>42:10</itunes:duration>"),
+            "xml:\n{xml}"
+        );
+    }
+
+    #[test]
+    fn generate_rss_includes_podcast_channel_tags() {
+        let channel = Channel {
+            title: "Site".into(),
+            link: "https://example.com/".into(),
+            feed_url: "https://example.com/index.xml".into(),
+            description: String::new(),
+            language: "en".into(),
+            last_build_date: None,
+            podcast: Some(Podcast {
+                category: "Society & Culture".into(),
+                subcategory: Some("Documentary".into()),
+                image: Some("/cover.jpg".into()),
+                explicit: true,
+            }),
+        };
+
+        let xml = generate_rss(&channel, &[], DEFAULT_FEED_LIMIT);
+
+        assert!(
+            xml.contains(r#"xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd""#),
+            "should declare the itunes namespace, xml:\n{xml}"
+        );
+        assert!(
+            xml.contains(concat!(
+                "    <itunes:image href=\"https://example.com/cover.jpg\" />\n",
+                "    <itunes:category text=\"Society &amp; Culture\">\n",
+                "      <itunes:category text=\"Documentary\" />\n",
+                "    </itunes:category>\n",
+                "    <itunes:explicit>true</itunes:explicit>\n",
+            )),
+            "xml:\n{xml}"
+        );
+    }
+
     #[test]
     fn generate_rss_omits_pub_date_without_date() {
         let channel = Channel {
@@ -295,6 +471,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
         let items = vec![make_summary("Post", "https://example.com/post/", None)];
 
@@ -316,6 +493,7 @@ mod tests {
             description: String::new(),
             language: "en".into(),
             last_build_date: None,
+            podcast: None,
         };
         let items = vec![make_summary(
             "Post",
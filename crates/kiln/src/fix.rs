@@ -0,0 +1,758 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use jiff::Timestamp;
+use jiff::civil::{Date, DateTime};
+use jiff::tz::TimeZone;
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::content::format::ContentFormat;
+use crate::content::frontmatter::{Frontmatter, split_frontmatter};
+use crate::content::page::{PageKind, derive_page_id, derive_page_kind};
+use crate::convert::frontmatter::{
+    convert_frontmatter, split_yaml_frontmatter, unknown_frontmatter_keys,
+};
+use crate::output::write_output;
+
+/// Deprecated frontmatter keys and their replacements.
+const RENAMED_KEYS: &[(&str, &str)] = &[("featuredImage", "featured_image")];
+
+/// Matches a top-level `date` or `updated` assignment, with an optional
+/// trailing comment.
+static TIMESTAMP_KEY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r##"^(\s*(date|updated)\s*=\s*)("[^"]*"|[^"#]*?)(\s*(?:#.*)?)$"##)
+        .expect("timestamp key regex should compile")
+});
+
+/// Options for [`fix`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FixOptions {
+    /// Report the changes as diffs without writing them.
+    pub dry_run: bool,
+}
+
+/// Files rewritten (or, in a dry run, due to be rewritten) by [`fix`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FixReport {
+    pub files: Vec<FixedFile>,
+    /// Sources with a native Org / AsciiDoc header instead of frontmatter,
+    /// which need fixing by hand.
+    pub skipped: Vec<PathBuf>,
+    /// YAML sources left untouched because converting them would drop the
+    /// listed keys, which need moving by hand.
+    pub unknown_keys: Vec<(PathBuf, Vec<String>)>,
+    pub dry_run: bool,
+}
+
+/// A content source and the migrations applied to its frontmatter.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FixedFile {
+    /// Source file, relative to the site root.
+    pub path: PathBuf,
+    pub changes: Vec<Change>,
+    /// Line diff of the frontmatter, before and after.
+    pub diff: String,
+}
+
+/// A single frontmatter migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// `---` YAML frontmatter was rewritten as `+++` TOML.
+    ConvertedYaml,
+    /// A deprecated key was renamed.
+    RenamedKey { from: String, to: String },
+    /// A `date` / `updated` value without a UTC offset was given the site
+    /// time zone's offset.
+    NormalizedTimestamp { key: String },
+    /// The page ID derived from the source path was pinned as `id`.
+    AddedId,
+    /// A post without a `date` got the file's creation date, from git
+    /// history or else the file's modification time.
+    AddedDate,
+}
+
+/// Rewrites the frontmatter of every content source in place for mechanical
+/// migrations:
+///
+/// - `---` YAML frontmatter is converted to `+++` TOML as in `kiln convert`;
+///   files with keys the conversion would drop are reported and left as is
+/// - deprecated keys are renamed (`featuredImage` → `featured_image`)
+/// - `date` / `updated` values without a UTC offset get the site time zone's
+///   offset
+/// - pages without an `id` get the one derived from their current path, so
+///   it survives later moves and slug changes
+/// - posts without a `date` get their creation date
+///
+/// Edits are made line by line, keeping comments and key order intact.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded, or a source file cannot
+/// be read, parsed, or written.
+pub fn fix(root: &Path, options: FixOptions) -> Result<FixReport> {
+    let config = Config::load(root).context("failed to load config")?;
+    let time_zone = config.time_zone()?.unwrap_or(TimeZone::UTC);
+    let content_dir = root.join("content");
+
+    let mut report = FixReport {
+        dry_run: options.dry_run,
+        ..FixReport::default()
+    };
+    if !content_dir.is_dir() {
+        return Ok(report);
+    }
+    let migrator = Migrator {
+        root,
+        content_dir: &content_dir,
+        time_zone: &time_zone,
+    };
+    for entry in WalkDir::new(&content_dir)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('_'))
+    {
+        let entry =
+            entry.with_context(|| format!("failed to read entry in {}", content_dir.display()))?;
+        let path = entry.path();
+        let Some(format) = ContentFormat::from_path(path) else {
+            continue;
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let relative = path.strip_prefix(root).unwrap_or(path).to_owned();
+
+        let Some(header) = FrontmatterBlock::find(&source) else {
+            if format.has_metadata(&source) {
+                report.skipped.push(relative);
+            }
+            continue;
+        };
+        if header.yaml {
+            let (yaml, _) = split_yaml_frontmatter(&source)?;
+            let unknown = unknown_frontmatter_keys(yaml)
+                .with_context(|| format!("failed to fix {}", path.display()))?;
+            if !unknown.is_empty() {
+                report.unknown_keys.push((relative, unknown));
+                continue;
+            }
+        }
+        let (fixed, changes) = migrator
+            .migrate(path, &source, &header)
+            .with_context(|| format!("failed to fix {}", path.display()))?;
+        if changes.is_empty() {
+            continue;
+        }
+        if !options.dry_run {
+            write_output(path, &fixed)?;
+        }
+        let body_len = source.len() - header.end;
+        report.files.push(FixedFile {
+            diff: line_diff(&source[..header.end], &fixed[..fixed.len() - body_len]),
+            path: relative,
+            changes,
+        });
+    }
+    Ok(report)
+}
+
+/// Location of the frontmatter block at the start of a content source.
+struct FrontmatterBlock {
+    yaml: bool,
+    /// End of the closing delimiter line; the body starts here.
+    end: usize,
+}
+
+impl FrontmatterBlock {
+    /// Finds the `+++` TOML or `---` YAML frontmatter of `source`. A `---`
+    /// block only counts if it holds a YAML mapping, so markdown opening with
+    /// a thematic break is left alone.
+    fn find(source: &str) -> Option<Self> {
+        let (yaml, body) = if let Ok((_, body)) = split_frontmatter(source) {
+            (false, body)
+        } else {
+            let (yaml, body) = split_yaml_frontmatter(source).ok()?;
+            serde_yaml::from_str::<serde_yaml::Mapping>(yaml).ok()?;
+            (true, body)
+        };
+        Some(Self {
+            yaml,
+            end: source.len() - body.len(),
+        })
+    }
+}
+
+/// Site-wide inputs of the migrations.
+struct Migrator<'a> {
+    root: &'a Path,
+    content_dir: &'a Path,
+    time_zone: &'a TimeZone,
+}
+
+impl Migrator<'_> {
+    /// Returns the migrated source of the file at `path`, and the changes
+    /// made to it.
+    fn migrate(
+        &self,
+        path: &Path,
+        source: &str,
+        header: &FrontmatterBlock,
+    ) -> Result<(String, Vec<Change>)> {
+        let mut changes = Vec::new();
+        let body = &source[header.end..];
+        let newline = if source[..header.end].contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+
+        let mut lines: Vec<String> = if header.yaml {
+            let (yaml, _) = split_yaml_frontmatter(source)?;
+            changes.push(Change::ConvertedYaml);
+            convert_frontmatter(yaml)?
+                .lines()
+                .map(String::from)
+                .collect()
+        } else {
+            let (toml, _) = split_frontmatter(source)?;
+            toml.lines().map(String::from).collect()
+        };
+
+        for line in &mut lines {
+            for &(from, to) in RENAMED_KEYS {
+                if let Some(renamed) = rename_key(line, from, to) {
+                    *line = renamed;
+                    changes.push(Change::RenamedKey {
+                        from: from.to_owned(),
+                        to: to.to_owned(),
+                    });
+                }
+            }
+        }
+        // Only top-level keys, before the first table header, are page fields.
+        let top_level = lines
+            .iter()
+            .position(|line| line.trim_start().starts_with('['))
+            .unwrap_or(lines.len());
+        for line in &mut lines[..top_level] {
+            if let Some((key, normalized)) = normalize_timestamp(line, self.time_zone) {
+                *line = normalized;
+                changes.push(Change::NormalizedTimestamp { key });
+            }
+        }
+
+        let frontmatter: Frontmatter =
+            toml::from_str(&lines.join("\n")).context("invalid frontmatter")?;
+        let mut added = Vec::new();
+        if frontmatter.id.is_none() {
+            let id = derive_page_id(path, self.content_dir);
+            added.push(format!("id = \"{id}\""));
+            changes.push(Change::AddedId);
+        }
+        let is_post = matches!(
+            derive_page_kind(path, self.content_dir),
+            PageKind::Post { .. }
+        );
+        if frontmatter.date.is_none() && is_post {
+            let date = created_at(self.root, path)?;
+            added.push(format!(
+                "date = {}",
+                format_offset_datetime(date, self.time_zone)
+            ));
+            changes.push(Change::AddedDate);
+        }
+        added.extend(lines);
+        let lines = added;
+
+        let bom = if source.starts_with('\u{feff}') {
+            "\u{feff}"
+        } else {
+            ""
+        };
+        let mut fixed = format!("{bom}+++{newline}");
+        for line in lines {
+            fixed.push_str(&line);
+            fixed.push_str(newline);
+        }
+        fixed.push_str("+++");
+        fixed.push_str(newline);
+        fixed.push_str(body);
+        Ok((fixed, changes))
+    }
+}
+
+/// Renames key `from` to `to` in a key assignment or table header line.
+fn rename_key(line: &str, from: &str, to: &str) -> Option<String> {
+    let indent = line.len() - line.trim_start().len();
+    let (prefix, rest) = line.split_at(indent);
+    let brackets = rest.len() - rest.trim_start_matches('[').len();
+    let (brackets, rest) = rest.split_at(brackets);
+    let after = rest.strip_prefix(from)?;
+    after
+        .trim_start()
+        .starts_with(['=', ']', '.'])
+        .then(|| format!("{prefix}{brackets}{to}{after}"))
+}
+
+/// Gives a `date` / `updated` line without a UTC offset the offset of
+/// `time_zone`. Bare dates become midnight. Returns the key and the new line,
+/// or `None` when the value already has an offset or is not a datetime.
+fn normalize_timestamp(line: &str, time_zone: &TimeZone) -> Option<(String, String)> {
+    let caps = TIMESTAMP_KEY_RE.captures(line)?;
+    let value = caps[3].trim_matches('"');
+    if value.parse::<Timestamp>().is_ok() {
+        return None;
+    }
+    let datetime = value
+        .parse::<DateTime>()
+        .ok()
+        .or_else(|| value.parse::<Date>().ok().map(|date| date.at(0, 0, 0, 0)))?;
+    let timestamp = datetime.to_zoned(time_zone.clone()).ok()?.timestamp();
+    Some((
+        caps[2].to_owned(),
+        format!(
+            "{}{}{}",
+            &caps[1],
+            format_offset_datetime(timestamp, time_zone),
+            &caps[4]
+        ),
+    ))
+}
+
+/// Formats `timestamp` as a TOML offset datetime in `time_zone`.
+fn format_offset_datetime(timestamp: Timestamp, time_zone: &TimeZone) -> String {
+    timestamp
+        .to_zoned(time_zone.clone())
+        .strftime("%Y-%m-%dT%H:%M:%S%:z")
+        .to_string()
+}
+
+/// Returns when the file at `path` was created: the author date of the
+/// commit adding it, or its modification time outside git history.
+fn created_at(root: &Path, path: &Path) -> Result<Timestamp> {
+    let added = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["log", "--follow", "--diff-filter=A", "--format=%aI", "--"])
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            // Newest first, so the original addition comes last.
+            stdout.lines().last()?.trim().parse::<Timestamp>().ok()
+        });
+    if let Some(added) = added {
+        return Ok(added);
+    }
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| format!("failed to read modification time of {}", path.display()))?;
+    Timestamp::try_from(modified).context("modification time out of range")
+}
+
+/// Renders a line diff of `old` and `new`: unchanged lines prefixed with
+/// ` `, removed ones with `-`, added ones with `+`.
+fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j]: length of the longest common subsequence of old[i..], new[j..].
+    let mut lcs = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        let (mark, line) = if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            (' ', old[i - 1])
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            i += 1;
+            ('-', old[i - 1])
+        } else {
+            j += 1;
+            ('+', new[j - 1])
+        };
+        diff.push(mark);
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    diff
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConvertedYaml => f.write_str("converted YAML frontmatter to TOML"),
+            Self::RenamedKey { from, to } => write!(f, "renamed `{from}` to `{to}`"),
+            Self::NormalizedTimestamp { key } => write!(f, "added UTC offset to `{key}`"),
+            Self::AddedId => f.write_str("added `id`"),
+            Self::AddedDate => f.write_str("added `date`"),
+        }
+    }
+}
+
+impl fmt::Display for FixReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            let changes: Vec<String> = file.changes.iter().map(ToString::to_string).collect();
+            writeln!(f, "{}: {}", file.path.display(), changes.join(", "))?;
+            if self.dry_run {
+                write!(f, "{}", file.diff)?;
+            }
+        }
+        for path in &self.skipped {
+            writeln!(f, "Skipped (native header): {}", path.display())?;
+        }
+        for (path, keys) in &self.unknown_keys {
+            let keys: Vec<String> = keys.iter().map(|key| format!("`{key}`")).collect();
+            writeln!(
+                f,
+                "Skipped (unknown keys {}): {}",
+                keys.join(", "),
+                path.display()
+            )?;
+        }
+        if self.dry_run {
+            writeln!(f, "{} file(s) would be updated.", self.files.len())
+        } else {
+            writeln!(f, "{} file(s) updated.", self.files.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::{formatdoc, indoc};
+
+    use super::*;
+    use crate::config::Slugify;
+    use crate::content::discovery::discover_content;
+    use crate::content::page::Page;
+    use crate::test_utils::write_test_file;
+
+    fn changes(report: &FixReport) -> Vec<(String, Vec<Change>)> {
+        report
+            .files
+            .iter()
+            .map(|file| {
+                (
+                    file.path.to_string_lossy().into_owned(),
+                    file.changes.clone(),
+                )
+            })
+            .collect()
+    }
+
+    // ── fix ──
+
+    #[test]
+    fn fix_pins_derived_ids() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "content/about.md",
+            "+++\ntitle = \"About\"\n+++\nBody.\n",
+        );
+        write_test_file(
+            root.path(),
+            "content/contact.md",
+            "+++\nid = \"kept\"\ntitle = \"Contact\"\n+++\n",
+        );
+        let derived = discover_content(root.path(), Slugify::default())
+            .unwrap()
+            .pages[0]
+            .id
+            .clone();
+
+        let report = fix(root.path(), FixOptions::default()).unwrap();
+        assert_eq!(
+            changes(&report),
+            [("content/about.md".to_owned(), vec![Change::AddedId])]
+        );
+
+        // Moving the file no longer changes its ID.
+        let moved = root.path().join("content/renamed.md");
+        fs::rename(root.path().join("content/about.md"), &moved).unwrap();
+        let page = Page::from_file(&moved, Slugify::default()).unwrap();
+        assert_eq!(page.id, derived);
+        assert_eq!(page.raw_content, "Body.\n");
+
+        let report = fix(root.path(), FixOptions::default()).unwrap();
+        assert!(report.files.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn fix_migrates_frontmatter() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "config.toml", "timezone = \"Asia/Shanghai\"\n");
+        write_test_file(
+            root.path(),
+            "content/posts/legacy.md",
+            indoc! {r#"
+                +++
+                title = "Legacy" # keep me
+                date = 2024-01-15T10:30:00
+                updated = "2024-02-01"
+                featuredImage = "/img.webp"
+                +++
+                Body.
+            "#},
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/hugo.md",
+            indoc! {"
+                ---
+                title: Hugo
+                date: 2024-01-15T10:30:00+08:00
+                ---
+                Body.
+            "},
+        );
+        write_test_file(
+            root.path(),
+            "content/posts/undated.md",
+            "+++\nid = \"u\"\n+++\n",
+        );
+        write_test_file(root.path(), "content/README.md", "# Not a page\n");
+
+        let report = fix(root.path(), FixOptions::default()).unwrap();
+        assert_eq!(
+            changes(&report),
+            [
+                (
+                    "content/posts/hugo.md".to_owned(),
+                    vec![Change::ConvertedYaml, Change::AddedId]
+                ),
+                (
+                    "content/posts/legacy.md".to_owned(),
+                    vec![
+                        Change::RenamedKey {
+                            from: "featuredImage".into(),
+                            to: "featured_image".into()
+                        },
+                        Change::NormalizedTimestamp { key: "date".into() },
+                        Change::NormalizedTimestamp {
+                            key: "updated".into()
+                        },
+                        Change::AddedId,
+                    ]
+                ),
+                (
+                    "content/posts/undated.md".to_owned(),
+                    vec![Change::AddedDate]
+                ),
+            ]
+        );
+
+        let legacy = fs::read_to_string(root.path().join("content/posts/legacy.md")).unwrap();
+        let id = derive_page_id(
+            &root.path().join("content/posts/legacy.md"),
+            &root.path().join("content"),
+        );
+        assert_eq!(
+            legacy,
+            formatdoc! {r#"
+                +++
+                id = "{id}"
+                title = "Legacy" # keep me
+                date = 2024-01-15T10:30:00+08:00
+                updated = 2024-02-01T00:00:00+08:00
+                featured_image = "/img.webp"
+                +++
+                Body.
+            "#},
+            "comments and key order are kept"
+        );
+        let hugo = fs::read_to_string(root.path().join("content/posts/hugo.md")).unwrap();
+        assert!(hugo.starts_with("+++\nid = "), "hugo:\n{hugo}");
+        assert!(
+            hugo.contains("date = \"2024-01-15T02:30:00Z\"\n"),
+            "hugo:\n{hugo}"
+        );
+
+        // Every page parses now.
+        let content = discover_content(root.path(), Slugify::default()).unwrap();
+        assert_eq!(content.pages.len(), 3);
+        assert!(
+            content
+                .pages
+                .iter()
+                .all(|page| page.frontmatter.date.is_some())
+        );
+    }
+
+    #[test]
+    fn fix_leaves_yaml_with_unknown_keys_untouched() {
+        let root = tempfile::tempdir().unwrap();
+        let source = indoc! {"
+            ---
+            title: Hugo
+            categories: [tutorial]
+            ---
+            Body.
+        "};
+        write_test_file(root.path(), "content/posts/hugo.md", source);
+
+        let report = fix(root.path(), FixOptions::default()).unwrap();
+        assert!(report.files.is_empty(), "{report}");
+        assert_eq!(
+            report.unknown_keys,
+            [(
+                PathBuf::from("content/posts/hugo.md"),
+                vec!["categories".to_owned()]
+            )]
+        );
+        assert_eq!(
+            fs::read_to_string(root.path().join("content/posts/hugo.md")).unwrap(),
+            source
+        );
+    }
+
+    #[test]
+    fn fix_dry_run_leaves_files_untouched() {
+        let root = tempfile::tempdir().unwrap();
+        let source = "+++\r\nid = \"a\"\r\ndate = 2024-01-15\r\n+++\r\nBody.\r\n";
+        write_test_file(root.path(), "content/posts/a.md", source);
+        write_test_file(root.path(), "content/posts/b.org", "#+title: B\n");
+
+        let report = fix(root.path(), FixOptions { dry_run: true }).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.path().join("content/posts/a.md")).unwrap(),
+            source
+        );
+        assert_eq!(
+            report.files[0].diff,
+            indoc! {r#"
+             +++
+             id = "a"
+            -date = 2024-01-15
+            +date = 2024-01-15T00:00:00+00:00
+             +++
+        "#}
+        );
+        assert_eq!(report.skipped, [PathBuf::from("content/posts/b.org")]);
+    }
+
+    // ── rename_key ──
+
+    #[test]
+    fn rename_key_handles_assignments_and_headers() {
+        let rename = |line| rename_key(line, "featuredImage", "featured_image");
+        assert_eq!(
+            rename("featuredImage = \"/a.webp\"").unwrap(),
+            "featured_image = \"/a.webp\""
+        );
+        assert_eq!(rename("[featuredImage]").unwrap(), "[featured_image]");
+        assert_eq!(
+            rename("[featuredImage.credit]").unwrap(),
+            "[featured_image.credit]"
+        );
+        assert_eq!(rename("featuredImages = []"), None);
+        assert_eq!(rename("title = \"featuredImage\""), None);
+    }
+
+    // ── normalize_timestamp ──
+
+    #[test]
+    fn normalize_timestamp_adds_offset() {
+        let tz = TimeZone::get("Asia/Shanghai").unwrap();
+        assert_eq!(
+            normalize_timestamp("date = 2024-01-15T10:30:00", &tz).unwrap(),
+            ("date".into(), "date = 2024-01-15T10:30:00+08:00".into())
+        );
+        assert_eq!(
+            normalize_timestamp("updated = \"2024-01-15 10:30\"", &tz).unwrap(),
+            (
+                "updated".into(),
+                "updated = 2024-01-15T10:30:00+08:00".into()
+            )
+        );
+        assert_eq!(
+            normalize_timestamp("date = 2024-01-15T10:30:00Z", &tz),
+            None
+        );
+        assert_eq!(normalize_timestamp("date = \"soon\"", &tz), None);
+    }
+
+    #[test]
+    fn normalize_timestamp_keeps_trailing_comment() {
+        let tz = TimeZone::get("Asia/Shanghai").unwrap();
+        assert_eq!(
+            normalize_timestamp("date = 2024-01-15T10:30:00 # first draft", &tz).unwrap(),
+            (
+                "date".into(),
+                "date = 2024-01-15T10:30:00+08:00 # first draft".into()
+            )
+        );
+        assert_eq!(
+            normalize_timestamp("updated = \"2024-01-15\"  # note", &tz).unwrap(),
+            (
+                "updated".into(),
+                "updated = 2024-01-15T00:00:00+08:00  # note".into()
+            )
+        );
+        assert_eq!(normalize_timestamp("dated = 2024-01-15", &tz), None);
+    }
+
+    // ── line_diff ──
+
+    #[test]
+    fn line_diff_marks_changed_lines() {
+        assert_eq!(
+            line_diff("a\nb\nc\n", "a\nx\nc\nd\n"),
+            " a\n-b\n+x\n c\n+d\n"
+        );
+    }
+
+    // ── Display ──
+
+    #[test]
+    fn report_display() {
+        let report = FixReport {
+            files: vec![FixedFile {
+                path: PathBuf::from("content/a.md"),
+                changes: vec![Change::ConvertedYaml, Change::AddedId],
+                diff: "-a\n+b\n".into(),
+            }],
+            skipped: vec![PathBuf::from("content/b.org")],
+            unknown_keys: vec![(
+                PathBuf::from("content/c.md"),
+                vec!["categories".into(), "params".into()],
+            )],
+            dry_run: true,
+        };
+        assert_eq!(
+            report.to_string(),
+            indoc! {"
+                content/a.md: converted YAML frontmatter to TOML, added `id`
+                -a
+                +b
+                Skipped (native header): content/b.org
+                Skipped (unknown keys `categories`, `params`): content/c.md
+                1 file(s) would be updated.
+            "}
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use jiff::civil::Date;
+use jiff::tz::TimeZone;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::build::url::page_url;
+use crate::config::{Config, SlugifyMode};
+use crate::content::discovery::discover_content;
+use crate::content::page::{Page, PageKind};
+use crate::taxonomy::term_slug;
+use crate::text::slugify;
+
+/// Filters and pattern for [`grep`].
+#[derive(Debug, Default)]
+pub struct GrepQuery {
+    /// Regex searched for in each page's source file, line by line. Without
+    /// one, every page passing the filters matches.
+    pub pattern: Option<String>,
+    pub ignore_case: bool,
+    /// Tags the page must all carry (compared by slug, so `Rust` = `rust`).
+    pub tags: Vec<String>,
+    /// Section slug the post must belong to.
+    pub section: Option<String>,
+    /// Earliest page date, inclusive, in the site's time zone.
+    pub after: Option<Date>,
+    /// Latest page date, inclusive, in the site's time zone.
+    pub before: Option<Date>,
+    /// Also search pages with `draft = true`.
+    pub drafts: bool,
+}
+
+/// Pages matched by [`grep`], newest first (drafts last).
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GrepReport {
+    pub pages: Vec<PageMatch>,
+}
+
+/// A page passing the filters, with the source lines matching the pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageMatch {
+    /// Source file, relative to the site root.
+    pub path: PathBuf,
+    /// Published URL; `None` for drafts.
+    pub url: Option<String>,
+    pub title: String,
+    pub lines: Vec<LineMatch>,
+}
+
+/// A source line matching the pattern, frontmatter included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineMatch {
+    /// 1-based line number in the source file.
+    pub line: usize,
+    pub text: String,
+}
+
+/// Searches the site's content sources: titles, other frontmatter fields,
+/// and body text alike, since the pattern is matched against every line of
+/// each source file.
+///
+/// # Errors
+///
+/// Returns an error if configuration loading or content discovery fails, the
+/// pattern is not a valid regex, or a source file cannot be read.
+pub fn grep(root: &Path, query: &GrepQuery) -> Result<GrepReport> {
+    let config = Config::load(root).context("failed to load config")?;
+    let time_zone = config.time_zone()?.unwrap_or(TimeZone::UTC);
+    let pattern = query
+        .pattern
+        .as_deref()
+        .map(|pattern| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(query.ignore_case)
+                .build()
+                .with_context(|| format!("invalid pattern `{pattern}`"))
+        })
+        .transpose()?;
+    let content = discover_content(root, config.slugify)?;
+
+    let drafts = if query.drafts {
+        content.drafts.as_slice()
+    } else {
+        &[]
+    };
+    let mut pages = Vec::new();
+    for (page, published) in content
+        .pages
+        .iter()
+        .map(|page| (page, true))
+        .chain(drafts.iter().map(|page| (page, false)))
+    {
+        if !passes_filters(page, query, &time_zone, config.slugify.mode()) {
+            continue;
+        }
+        let lines = match &pattern {
+            Some(pattern) => {
+                let lines = matching_lines(&page.source_path, pattern)?;
+                if lines.is_empty() {
+                    continue;
+                }
+                lines
+            }
+            None => Vec::new(),
+        };
+        let url = if published {
+            let output_path = page.output_path(&content.content_dir)?;
+            Some(page_url(&config.base_url, &output_path))
+        } else {
+            None
+        };
+        pages.push(PageMatch {
+            path: page
+                .source_path
+                .strip_prefix(root)
+                .unwrap_or(&page.source_path)
+                .to_owned(),
+            url,
+            title: page.frontmatter.title.clone(),
+            lines,
+        });
+    }
+    Ok(GrepReport { pages })
+}
+
+/// Applies the tag, section, and date filters of `query` to `page`. Undated
+/// pages fail any date filter.
+fn passes_filters(
+    page: &Page,
+    query: &GrepQuery,
+    time_zone: &TimeZone,
+    slugify_mode: SlugifyMode,
+) -> bool {
+    let has_tags = query.tags.iter().all(|wanted| {
+        let wanted = term_slug(wanted.trim(), slugify_mode);
+        page.frontmatter
+            .tags
+            .iter()
+            .any(|tag| term_slug(tag.trim(), slugify_mode) == wanted)
+    });
+    let in_section = query.section.as_deref().is_none_or(|wanted| {
+        matches!(&page.kind, PageKind::Post { section: Some(section) } if *section == slugify(wanted, slugify_mode))
+    });
+    let in_range = if query.after.is_none() && query.before.is_none() {
+        true
+    } else {
+        page.frontmatter.date.is_some_and(|date| {
+            let date = date.to_zoned(time_zone.clone()).date();
+            query.after.is_none_or(|after| date >= after)
+                && query.before.is_none_or(|before| date <= before)
+        })
+    };
+    has_tags && in_section && in_range
+}
+
+/// Returns the lines of the file at `path` matching `pattern`.
+fn matching_lines(path: &Path, pattern: &Regex) -> Result<Vec<LineMatch>> {
+    let source =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(source
+        .lines()
+        .enumerate()
+        .filter(|(_, text)| pattern.is_match(text))
+        .map(|(idx, text)| LineMatch {
+            line: idx + 1,
+            text: text.trim().to_owned(),
+        })
+        .collect())
+}
+
+impl fmt::Display for GrepReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for page in &self.pages {
+            write!(f, "{}", page.path.display())?;
+            match &page.url {
+                Some(url) => writeln!(f, " ({url})")?,
+                None => writeln!(f, " (draft)")?,
+            }
+            for line in &page.lines {
+                writeln!(f, "  {}: {}", line.line, line.text)?;
+            }
+        }
+        writeln!(f, "{} page(s) matched.", self.pages.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn write_site(root: &Path) {
+        write_test_file(
+            root,
+            "config.toml",
+            indoc! {r#"
+                base_url = "https://example.com"
+                timezone = "Asia/Shanghai"
+            "#},
+        );
+        write_test_file(
+            root,
+            "content/posts/note/borrow/index.md",
+            indoc! {r#"
+                +++
+                title = "Taming the Borrow Checker"
+                date = 2024-03-01T00:00:00Z
+                tags = ["Rust"]
+                +++
+                The borrow checker rejects this.
+            "#},
+        );
+        write_test_file(
+            root,
+            "content/posts/old.md",
+            indoc! {r#"
+                +++
+                title = "Old Post"
+                date = 2023-12-31T18:00:00Z
+                tags = ["rust", "history"]
+                +++
+                Before the borrow checker got smarter.
+            "#},
+        );
+        write_test_file(
+            root,
+            "content/posts/wip.md",
+            indoc! {r#"
+                +++
+                title = "WIP"
+                draft = true
+                tags = ["rust"]
+                +++
+                Borrow checker notes.
+            "#},
+        );
+    }
+
+    fn paths(report: &GrepReport) -> Vec<String> {
+        report
+            .pages
+            .iter()
+            .map(|page| page.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    // ── grep ──
+
+    #[test]
+    fn grep_matches_lines_with_urls() {
+        let root = tempfile::tempdir().unwrap();
+        write_site(root.path());
+
+        let query = GrepQuery {
+            pattern: Some("borrow checker".into()),
+            ..GrepQuery::default()
+        };
+        let report = grep(root.path(), &query).unwrap();
+        assert_eq!(
+            report.pages[0],
+            PageMatch {
+                path: PathBuf::from("content/posts/note/borrow/index.md"),
+                url: Some("https://example.com/posts/note/borrow/".into()),
+                title: "Taming the Borrow Checker".into(),
+                lines: vec![LineMatch {
+                    line: 6,
+                    text: "The borrow checker rejects this.".into(),
+                }],
+            }
+        );
+        assert_eq!(paths(&report).len(), 2, "drafts are skipped by default");
+
+        let query = GrepQuery {
+            pattern: Some("borrow checker".into()),
+            ignore_case: true,
+            drafts: true,
+            ..GrepQuery::default()
+        };
+        let report = grep(root.path(), &query).unwrap();
+        assert_eq!(report.pages[0].lines.len(), 2, "title line matches too");
+        let draft = report.pages.last().unwrap();
+        assert_eq!(draft.path, PathBuf::from("content/posts/wip.md"));
+        assert_eq!(draft.url, None);
+    }
+
+    #[test]
+    fn grep_applies_filters() {
+        let root = tempfile::tempdir().unwrap();
+        write_site(root.path());
+
+        let query = GrepQuery {
+            tags: vec!["RUST".into(), "History".into()],
+            ..GrepQuery::default()
+        };
+        assert_eq!(
+            paths(&grep(root.path(), &query).unwrap()),
+            ["content/posts/old.md"]
+        );
+
+        let query = GrepQuery {
+            section: Some("note".into()),
+            ..GrepQuery::default()
+        };
+        assert_eq!(
+            paths(&grep(root.path(), &query).unwrap()),
+            ["content/posts/note/borrow/index.md"]
+        );
+
+        // 2023-12-31T18:00Z is already 2024-01-01 in Asia/Shanghai.
+        let query = GrepQuery {
+            after: Some("2024-01-01".parse().unwrap()),
+            before: Some("2024-01-01".parse().unwrap()),
+            drafts: true,
+            ..GrepQuery::default()
+        };
+        assert_eq!(
+            paths(&grep(root.path(), &query).unwrap()),
+            ["content/posts/old.md"]
+        );
+    }
+
+    #[test]
+    fn grep_invalid_pattern_returns_error() {
+        let root = tempfile::tempdir().unwrap();
+        write_site(root.path());
+
+        let query = GrepQuery {
+            pattern: Some("(".into()),
+            ..GrepQuery::default()
+        };
+        let err = grep(root.path(), &query).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid pattern `(`"),
+            "got: {err}"
+        );
+    }
+
+    // ── Display ──
+
+    #[test]
+    fn report_display() {
+        let report = GrepReport {
+            pages: vec![
+                PageMatch {
+                    path: PathBuf::from("content/posts/a.md"),
+                    url: Some("https://example.com/posts/a/".into()),
+                    title: "A".into(),
+                    lines: vec![LineMatch {
+                        line: 3,
+                        text: "hit".into(),
+                    }],
+                },
+                PageMatch {
+                    path: PathBuf::from("content/posts/b.md"),
+                    url: None,
+                    title: "B".into(),
+                    lines: Vec::new(),
+                },
+            ],
+        };
+        assert_eq!(
+            report.to_string(),
+            indoc! {"
+                content/posts/a.md (https://example.com/posts/a/)
+                  3: hit
+                content/posts/b.md (draft)
+                2 page(s) matched.
+            "}
+        );
+    }
+}
@@ -18,6 +18,35 @@ pub fn escape(input: &str) -> String {
     output
 }
 
+/// Decodes a URL as written in an `href` / `src` attribute: HTML entities
+/// for `&`, `'`, and `"`, then percent-encoding. Invalid escapes are kept
+/// as-is.
+#[must_use]
+pub fn decode_href(href: &str) -> String {
+    let unescaped = href
+        .replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let bytes = unescaped.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| unescaped.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = hex {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 /// Appends `level` × 2 spaces of indentation to an HTML string.
 pub fn indent(html: &mut String, level: u8) {
     for _ in 0..level {
@@ -61,6 +90,18 @@ mod tests {
         assert_eq!(escape(""), "");
     }
 
+    // ── decode_href ──
+
+    #[test]
+    fn decode_href_reverses_escaping() {
+        assert_eq!(
+            decode_href("@a;%20@b&amp;c&#x27;d&#39;%E4%BD%A0"),
+            "@a; @b&c'd'你"
+        );
+        assert_eq!(decode_href("100%"), "100%");
+        assert_eq!(decode_href("%zz%4"), "%zz%4");
+    }
+
     // ── indent ──
 
     #[test]
@@ -135,6 +135,13 @@ impl I18n {
         Cow::Owned(render_miss(key, kiln_dev_enabled()).into_owned())
     }
 
+    /// Looks up a string by key without warning on a miss, for built-in
+    /// strings that have an English default of their own.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.inner.strings.get(key).map(String::as_str)
+    }
+
     /// Looks up a string by key and interpolates Python-style `{name}`
     /// placeholders from `args`.
     ///
@@ -91,6 +91,7 @@ const DEFAULT_I18N_EN: &str = indoc! {r#"
     # `{{ t("key", name=value) }}` to substitute `{name}` placeholders.
 
     all_posts = "All Posts"
+    archive = "Archive"
     back_to_top = "Back to Top"
     table_of_contents = "Table of Contents"
 "#};
@@ -101,6 +102,7 @@ const DEFAULT_I18N_ZH_HANS: &str = indoc! {r#"
     # See i18n/en.toml for a description of the resolution order.
 
     all_posts = "全部文章"
+    archive = "归档"
     back_to_top = "回到顶部"
     table_of_contents = "目录"
 "#};
@@ -1,9 +1,15 @@
+pub mod audit;
 pub mod build;
+pub mod check;
 pub mod config;
 pub mod content;
 pub mod convert;
+pub mod deploy;
+pub mod diagnostic;
 pub mod directive;
 pub mod feed;
+pub mod fix;
+pub mod grep;
 pub mod html;
 pub mod i18n;
 pub mod init;
@@ -15,16 +21,32 @@ pub mod render;
 pub mod search;
 pub mod section;
 pub mod serve;
+pub mod site;
 pub mod sitemap;
+pub mod stats;
 pub mod taxonomy;
 pub mod template;
 pub mod text;
+pub mod theme;
+pub mod transform;
+pub mod webmention;
+pub mod xml;
 
-pub use build::{BuildOptions, build};
+pub use audit::audit;
+pub use build::{BuildOptions, BuildReport, build};
+pub use check::check;
 pub use convert::convert;
+pub use deploy::deploy;
+pub use fix::fix;
+pub use grep::grep;
 pub use init::init_theme;
 pub use serve::DEFAULT_PORT;
 pub use serve::serve;
+pub use site::Site;
+pub use stats::content_stats;
+pub use theme::{install_theme, update_theme};
+pub use transform::ContentTransform;
+pub use webmention::send_webmentions;
 
 #[cfg(test)]
 pub(crate) mod test_utils;
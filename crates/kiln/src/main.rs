@@ -1,19 +1,67 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
+use jiff::civil::Date;
 use kiln::BuildOptions;
+use kiln::build::{BuildEvent, BuildReport};
+use kiln::check::BrokenLinks;
+use kiln::deploy::DeployOptions;
+use kiln::diagnostic::{Diagnostic, DiagnosticLayer};
+use kiln::fix::FixOptions;
+use kiln::grep::GrepQuery;
+use kiln::webmention::WebmentionOptions;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 #[derive(Parser)]
 #[command(name = "kiln", about = "A custom static site generator")]
 struct Cli {
     #[command(subcommand)]
     command: Command,
+
+    /// How to print warnings, errors, build progress, and reports: as
+    /// text, or as JSON records (one per line) on stdout.
+    #[arg(
+        long,
+        visible_alias = "format",
+        global = true,
+        value_enum,
+        default_value_t
+    )]
+    message_format: MessageFormat,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+impl Cli {
+    /// The message format, with the hidden `--json` flags of `grep` and
+    /// `stats content` standing for `--message-format json`.
+    fn message_format(&self) -> MessageFormat {
+        match self.command {
+            Command::Grep { json: true, .. }
+            | Command::Stats {
+                command: StatsCommand::Content { json: true, .. },
+            } => MessageFormat::Json,
+            _ => self.message_format,
+        }
+    }
 }
 
 #[derive(Subcommand)]
 enum Command {
+    /// Report directive and shortcode usage across content.
+    Audit {
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
     /// Build the site.
     Build {
         /// Project root directory (defaults to current directory).
@@ -23,6 +71,27 @@ enum Command {
         /// Minify HTML, CSS, and JS in the output directory.
         #[arg(long)]
         minify: bool,
+
+        /// Fail if any internal link or anchor in the output is broken.
+        #[arg(long)]
+        check_links: bool,
+
+        /// Fail on soft failures: unrecognized code block languages,
+        /// unclosed directives, missing alt text, broken internal links, and
+        /// undefined template variables.
+        #[arg(long)]
+        strict: bool,
+
+        /// Print the time spent in each build phase, and in each step of
+        /// rendering pages.
+        #[arg(long)]
+        timings: bool,
+    },
+    /// Check the built site for broken internal links and anchors.
+    Check {
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
     },
     /// Convert Hugo content to kiln format.
     Convert {
@@ -33,6 +102,71 @@ enum Command {
         #[arg(long)]
         dest: PathBuf,
     },
+    /// Publish the built output to the `[deploy]` target in `config.toml`.
+    Deploy {
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// Show what would be published without publishing it.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Migrate frontmatter in place: convert YAML to TOML, rename deprecated
+    /// keys, add UTC offsets, and pin down page IDs and post dates.
+    Fix {
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// Print the changes as diffs without writing them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Search content sources, with filters on frontmatter.
+    Grep {
+        /// Regex matched against each line of the source files; without
+        /// one, lists every page passing the filters.
+        pattern: Option<String>,
+
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// Match case-insensitively.
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Only pages with this tag (repeatable; all must match).
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+
+        /// Only posts in this section.
+        #[arg(long)]
+        section: Option<String>,
+
+        /// Only pages dated on or after this day (YYYY-MM-DD).
+        #[arg(long)]
+        after: Option<Date>,
+
+        /// Only pages dated on or before this day (YYYY-MM-DD).
+        #[arg(long)]
+        before: Option<Date>,
+
+        /// Include drafts.
+        #[arg(long)]
+        drafts: bool,
+
+        /// Alias for `--message-format json`.
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+    /// Print the CSS for a syntax highlighting theme.
+    HighlightCss {
+        /// Bundled syntect theme name (e.g., "Nord", "GitHub").
+        #[arg(long)]
+        theme: String,
+    },
     /// Scaffold a new theme.
     InitTheme {
         /// Theme name (used as directory name under themes/).
@@ -56,33 +190,219 @@ enum Command {
         #[arg(long)]
         open: bool,
     },
+    /// Summarize the site's content.
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommand,
+    },
+    /// Install and update themes from git repositories.
+    Theme {
+        #[command(subcommand)]
+        command: ThemeCommand,
+    },
+    /// Notify other sites linked from posts.
+    Webmention {
+        #[command(subcommand)]
+        command: WebmentionCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsCommand {
+    /// Posting cadence, tag distribution, and post lengths.
+    Content {
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// Alias for `--message-format json`.
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ThemeCommand {
+    /// Clone a theme repository into themes/<name>.
+    Install {
+        /// Git repository URL of the theme.
+        url: String,
+
+        /// Theme name (defaults to the repository name).
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
+    /// Pull the latest version of an installed theme.
+    Update {
+        /// Theme name (directory name under themes/).
+        name: String,
+
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebmentionCommand {
+    /// Send webmentions for links from posts not yet notified; run after
+    /// deploying, as receivers fetch the posts to verify them.
+    Send {
+        /// Project root directory (defaults to current directory).
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// List the links that would be notified without contacting them.
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
-        )
+    let cli = Cli::parse();
+    let message_format = cli.message_format();
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    if message_format == MessageFormat::Human {
+        tracing_subscriber::fmt().with_env_filter(filter).init();
+        return run(cli.command, message_format);
+    }
+
+    let base = std::env::current_dir()?.canonicalize()?;
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(DiagnosticLayer::new(&base, |d| print_diagnostic(&d)))
         .init();
+    if let Err(err) = run(cli.command, message_format) {
+        for diagnostic in Diagnostic::from_error(&err, &base) {
+            print_diagnostic(&diagnostic);
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
 
-    let cli = Cli::parse();
+fn print_diagnostic(diagnostic: &Diagnostic) {
+    let json = serde_json::to_string(diagnostic).expect("diagnostics serialize to JSON");
+    println!("{json}");
+}
+
+fn print_build_event(event: &BuildEvent<'_>) {
+    let json = serde_json::to_string(event).expect("build events serialize to JSON");
+    println!("{json}");
+}
+
+/// Builds the site at `root`, streaming build events as JSON records on
+/// stdout in JSON mode, then printing the timings to stderr with
+/// `--timings`.
+fn build(
+    root: &Path,
+    mut options: BuildOptions<'_>,
+    timings: bool,
+    message_format: MessageFormat,
+) -> Result<()> {
+    if message_format == MessageFormat::Json {
+        options.on_event = Some(&print_build_event);
+    }
+    let report = kiln::build(root, options)?;
+    if timings {
+        print_timings(&report);
+    }
+    report.into_result()
+}
 
-    match cli.command {
-        Command::Build { root, minify } => {
+/// Prints each build phase's time, with the render steps under `render`.
+fn print_timings(report: &BuildReport) {
+    eprintln!("Timings:");
+    for phase in &report.phases {
+        eprintln!("  {:<12}{:>8.3}s", phase.name, phase.duration.as_secs_f64());
+        if phase.name == "render" {
+            for step in &report.render_steps {
+                eprintln!("    {:<10}{:>8.3}s", step.name, step.duration.as_secs_f64());
+            }
+        }
+    }
+}
+
+fn run(command: Command, message_format: MessageFormat) -> Result<()> {
+    match command {
+        Command::Audit { root } => {
+            let root = root.canonicalize()?;
+            print!("{}", kiln::audit(&root)?);
+        }
+        Command::Build {
+            root,
+            minify,
+            check_links,
+            strict,
+            timings,
+        } => {
             let root = root.canonicalize()?;
-            kiln::build(
-                &root,
-                BuildOptions {
-                    minify,
-                    ..Default::default()
-                },
-            )?;
+            let options = BuildOptions {
+                minify,
+                check_links,
+                strict,
+                ..Default::default()
+            };
+            build(&root, options, timings, message_format)?;
+        }
+        Command::Check { root } => {
+            let root = root.canonicalize()?;
+            let report = kiln::check(&root)?;
+            match message_format {
+                MessageFormat::Human => print!("{report}"),
+                MessageFormat::Json => eprint!("{report}"),
+            }
+            if !report.issues.is_empty() {
+                return Err(BrokenLinks { root, report }.into());
+            }
         }
         Command::Convert { source, dest } => {
             let source = source.canonicalize()?;
             let dest = dest.canonicalize().unwrap_or(dest);
             kiln::convert(&source, &dest)?;
         }
+        Command::Deploy { root, dry_run } => {
+            let root = root.canonicalize()?;
+            kiln::deploy(&root, DeployOptions { dry_run })?;
+        }
+        Command::Fix { root, dry_run } => {
+            let root = root.canonicalize()?;
+            print!("{}", kiln::fix(&root, FixOptions { dry_run })?);
+        }
+        Command::Grep {
+            pattern,
+            root,
+            ignore_case,
+            tags,
+            section,
+            after,
+            before,
+            drafts,
+            json: _,
+        } => {
+            let root = root.canonicalize()?;
+            let query = GrepQuery {
+                pattern,
+                ignore_case,
+                tags,
+                section,
+                after,
+                before,
+                drafts,
+            };
+            let report = kiln::grep(&root, &query)?;
+            match message_format {
+                MessageFormat::Human => print!("{report}"),
+                MessageFormat::Json => println!("{}", serde_json::to_string(&report)?),
+            }
+        }
+        Command::HighlightCss { theme } => {
+            print!("{}", kiln::render::highlight::highlight_css(&theme)?);
+        }
         Command::InitTheme { name, root } => {
             let root = root.canonicalize()?;
             kiln::init_theme(&root, &name)?;
@@ -91,7 +411,61 @@ fn main() -> Result<()> {
             let root = root.canonicalize()?;
             kiln::serve(&root, port, open)?;
         }
+        Command::Stats {
+            command: StatsCommand::Content { root, .. },
+        } => {
+            let root = root.canonicalize()?;
+            let stats = kiln::content_stats(&root)?;
+            match message_format {
+                MessageFormat::Human => print!("{stats}"),
+                MessageFormat::Json => println!("{}", serde_json::to_string(&stats)?),
+            }
+        }
+        Command::Theme { command } => theme(command)?,
+        Command::Webmention { command } => webmention(command)?,
+    }
+
+    Ok(())
+}
+
+fn theme(command: ThemeCommand) -> Result<()> {
+    match command {
+        ThemeCommand::Install { url, name, root } => {
+            let root = root.canonicalize()?;
+            let name = kiln::install_theme(&root, &url, name.as_deref())?;
+            println!("installed theme `{name}`; set `theme = \"{name}\"` in config.toml to use it");
+        }
+        ThemeCommand::Update { name, root } => {
+            let root = root.canonicalize()?;
+            if kiln::update_theme(&root, &name)? {
+                println!("updated theme `{name}`");
+            } else {
+                println!("theme `{name}` is already up to date");
+            }
+        }
     }
+    Ok(())
+}
 
+fn webmention(command: WebmentionCommand) -> Result<()> {
+    match command {
+        WebmentionCommand::Send { root, dry_run } => {
+            let root = root.canonicalize()?;
+            let report = kiln::send_webmentions(&root, WebmentionOptions { dry_run })?;
+            if !dry_run {
+                eprintln!(
+                    "Sent {} webmentions; {} links have no webmention endpoint.",
+                    report.sent, report.unsupported
+                );
+            }
+            if !report.failed.is_empty() {
+                bail!(
+                    "{} webmentions failed and will be retried:\n{}",
+                    report.failed.len(),
+                    report.failed.join("\n")
+                );
+            }
+        }
+    }
     Ok(())
 }
@@ -1,3 +1,5 @@
+use std::fmt::Write;
+
 /// Detects an opening code fence (three or more `` ` `` or `~` characters).
 /// Handles up to 3 spaces of leading indentation.
 #[must_use]
@@ -101,12 +103,87 @@ pub(crate) fn for_each_non_code_line(
     }
 }
 
+/// Appends a `:::` directive opened with `header` (e.g., `callout {type=tip}`)
+/// and wrapping `body`.
+///
+/// The fence is one colon longer than any column-0 colon run in the body so
+/// that stray or nested fences cannot close the directive early.
+pub(crate) fn push_directive(output: &mut String, header: &str, body: &str) {
+    let fence = ":".repeat((max_leading_run(body, b':') + 1).max(3));
+    _ = writeln!(output, "{fence} {header}");
+    push_block_body(output, body);
+    _ = writeln!(output, "{fence}");
+}
+
+/// Appends a fenced code block with info string `info` around verbatim `code`.
+///
+/// The backtick fence outgrows any backtick run that starts a line of `code`.
+#[cfg_attr(
+    not(any(test, feature = "org", feature = "asciidoc")),
+    expect(dead_code, reason = "only used by the markup converters")
+)]
+pub(crate) fn push_code_block(output: &mut String, info: &str, code: &str) {
+    let fence = "`".repeat((max_leading_run(code, b'`') + 1).max(3));
+    _ = writeln!(output, "{fence}{info}");
+    push_block_body(output, code);
+    _ = writeln!(output, "{fence}");
+}
+
+/// Longest run of `byte` at the start of any line in `text`, ignoring up to
+/// three spaces of indentation.
+fn max_leading_run(text: &str, byte: u8) -> usize {
+    text.lines()
+        .map(|l| {
+            let trimmed = l.trim_start_matches(' ');
+            if l.len() - trimmed.len() > 3 {
+                return 0;
+            }
+            trimmed.bytes().take_while(|&b| b == byte).count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn push_block_body(output: &mut String, body: &str) {
+    output.push_str(body);
+    if !body.is_empty() && !body.ends_with('\n') {
+        output.push('\n');
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
 
     use super::*;
 
+    // ── push_directive ──
+
+    #[test]
+    fn push_directive_outgrows_inner_fences() {
+        let mut out = String::new();
+        push_directive(&mut out, "callout {type=tip}", "::: note\nInner\n:::");
+        assert_eq!(
+            out,
+            indoc! {"
+                :::: callout {type=tip}
+                ::: note
+                Inner
+                :::
+                ::::
+            "}
+        );
+    }
+
+    // ── push_code_block ──
+
+    #[test]
+    fn push_code_block_outgrows_inner_fences() {
+        let mut out = String::new();
+        push_code_block(&mut out, "markdown", "```rust\n```\n");
+        assert_eq!(out, "````markdown\n```rust\n```\n````\n");
+    }
+
     // ── detect_opening_code_fence ──
 
     #[test]
@@ -18,10 +18,13 @@ use oxc_codegen::{Codegen, CodegenOptions, CommentOptions};
 use oxc_minifier::{CompressOptions, Minifier, MinifierOptions};
 use oxc_parser::Parser;
 use oxc_span::SourceType;
+use serde::Serialize;
 use walkdir::WalkDir;
 
+use crate::output::OutputKind;
+
 /// Totals from a minification pass, suitable for printing as a build summary.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct MinifyStats {
     /// Total files inspected (regardless of whether they shrank).
     pub files_processed: u64,
@@ -43,8 +46,9 @@ enum AssetKind {
 
 /// Minifies every HTML, CSS, and JS file under `output_dir` in place.
 ///
-/// Pre-minified files (`*.min.css`, `*.min.js`) are skipped so that vendor
-/// bundles (e.g., Pagefind's UI JS) pass through untouched.
+/// Only [`OutputKind::Text`] files are touched: pre-minified (`*.min.css`,
+/// `*.min.js`) and pre-compressed files are skipped so that vendor bundles
+/// (e.g., Pagefind's UI JS) pass through untouched.
 ///
 /// Minifier parse failures are logged at warn level and leave the original
 /// file intact. Only filesystem errors (read, write, walk) abort the pass.
@@ -78,10 +82,10 @@ pub fn minify_output_dir(output_dir: &Path) -> Result<MinifyStats> {
 /// Matching is case-insensitive so files with uppercase extensions (e.g.,
 /// `INDEX.HTML` from a migrated Hugo / legacy site) are still handled.
 fn classify(path: &Path) -> Option<AssetKind> {
-    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
-    if name.ends_with(".min.css") || name.ends_with(".min.js") {
+    if OutputKind::of(path) != OutputKind::Text {
         return None;
     }
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
     match Path::new(&name).extension()?.to_str()? {
         "html" | "htm" => Some(AssetKind::Html),
         "css" => Some(AssetKind::Css),
@@ -126,7 +130,7 @@ fn minify_file(path: &Path, kind: AssetKind, stats: &mut MinifyStats) -> Result<
 fn decode_utf8<'a>(input: &'a [u8], path: &Path, kind: &str) -> Option<&'a str> {
     std::str::from_utf8(input)
         .inspect_err(|e| {
-            tracing::warn!("skipping {} ({kind}): invalid UTF-8: {e}", path.display());
+            tracing::warn!(file = %path.display(), "skipping ({kind}): invalid UTF-8: {e}");
         })
         .ok()
 }
@@ -143,7 +147,7 @@ fn minify_css_bytes(input: &[u8], path: &Path) -> Option<Vec<u8>> {
     let source = decode_utf8(input, path, "CSS")?;
     let mut stylesheet = StyleSheet::parse(source, ParserOptions::default())
         .inspect_err(|e| {
-            tracing::warn!("skipping {} (CSS parse failed): {e}", path.display());
+            tracing::warn!(file = %path.display(), "skipping (CSS parse failed): {e}");
         })
         .ok()?;
     // lightningcss's `minify` and `to_css` don't fail in practice with
@@ -167,7 +171,7 @@ fn minify_js_bytes(input: &[u8], path: &Path) -> Option<Vec<u8>> {
     let allocator = Allocator::default();
     let parsed = Parser::new(&allocator, source, source_type).parse();
     if let Some(first) = parsed.errors.first() {
-        tracing::warn!("skipping {} (JS parse failed): {first}", path.display());
+        tracing::warn!(file = %path.display(), "skipping (JS parse failed): {first}");
         return None;
     }
     let mut program = parsed.program;
@@ -209,7 +213,7 @@ impl fmt::Display for MinifyStats {
     }
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
     const KB: f64 = 1024.0;
     const MB: f64 = KB * 1024.0;
     let b = u64_to_f64(bytes);
@@ -332,6 +336,12 @@ mod tests {
         assert_eq!(classify(Path::new("a/vendor.min.js")), None);
     }
 
+    #[test]
+    fn classify_skips_pre_compressed() {
+        assert_eq!(classify(Path::new("a/app.js.gz")), None);
+        assert_eq!(classify(Path::new("a/index.html.br")), None);
+    }
+
     #[test]
     fn classify_skips_unknown_extensions() {
         assert_eq!(classify(Path::new("a/image.png")), None);
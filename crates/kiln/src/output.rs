@@ -1,26 +1,336 @@
-use std::fs;
-use std::path::Path;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, ensure};
+use same_file::is_same_file;
+use sha2::{Digest, Sha256};
 use walkdir::WalkDir;
 
-/// Removes and recreates the output directory for a clean build.
+/// Permission bits of every file written to the output directory: readable
+/// by the web server regardless of the source file's mode.
+#[cfg(unix)]
+const OUTPUT_FILE_MODE: u32 = 0o644;
+
+/// How an output file may be treated by post-processing, sniffed from its
+/// extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    /// Markup, styles, scripts, and data that post-processing may rewrite.
+    Text,
+    /// Images, fonts, media, documents, and unknown formats, copied as raw
+    /// bytes.
+    Binary,
+    /// Pre-minified (`*.min.js`) or pre-compressed (`*.gz`, `*.br`) files,
+    /// which must reach the output byte-for-byte.
+    Compressed,
+}
+
+impl OutputKind {
+    /// Classifies `path` by extension, case-insensitively. Files without a
+    /// known text extension are treated as binary.
+    #[must_use]
+    pub fn of(path: &Path) -> Self {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return Self::Binary;
+        };
+        let name = name.to_ascii_lowercase();
+        let Some((stem, ext)) = name.rsplit_once('.') else {
+            return Self::Binary;
+        };
+        match ext {
+            "gz" | "br" | "zst" | "zip" | "xz" | "bz2" | "7z" => Self::Compressed,
+            "css" | "js" | "mjs" | "cjs"
+                if Path::new(stem).extension().is_some_and(|e| e == "min") =>
+            {
+                Self::Compressed
+            }
+            "html" | "htm" | "css" | "js" | "mjs" | "cjs" | "json" | "xml" | "svg" | "txt"
+            | "md" | "csv" | "map" | "webmanifest" => Self::Text,
+            _ => Self::Binary,
+        }
+    }
+}
+
+/// A build's output directory under construction, written beside the live
+/// one (`public` → `public.staging`) so a failed build never destroys the
+/// previously published site.
+///
+/// [`StagingDir::promote`] brings the live output up to date with it and
+/// removes it, so no second copy of the site outlives the build. Without a
+/// promotion the live output is left as it was.
+#[derive(Debug)]
+pub struct StagingDir {
+    path: PathBuf,
+    target: PathBuf,
+}
+
+impl StagingDir {
+    /// Creates an empty staging directory for a build that will replace
+    /// `target`, discarding any left over from a failed or interrupted build.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the leftover directory cannot be removed or the
+    /// new one cannot be created.
+    pub fn create(target: &Path) -> Result<Self> {
+        let staging = Self {
+            path: append_suffix(target, ".staging"),
+            target: target.to_owned(),
+        };
+        if staging.path.exists() {
+            fs::remove_dir_all(&staging.path).with_context(|| {
+                format!(
+                    "failed to remove staging directory {}",
+                    staging.path.display()
+                )
+            })?;
+        }
+        fs::create_dir_all(&staging.path).with_context(|| {
+            format!(
+                "failed to create staging directory {}",
+                staging.path.display()
+            )
+        })?;
+        Ok(staging)
+    }
+
+    /// The directory the build writes into.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The live output directory the build replaces.
+    #[must_use]
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Copies `src` to `relative` in the staging directory, creating parent
+    /// directories as needed.
+    ///
+    /// When the live output already holds an unchanged copy of `src` (the
+    /// same size, modified no earlier), it is hard-linked into place instead,
+    /// so unchanged files are never copied and [`StagingDir::promote`] leaves
+    /// them alone. Files written later go through [`write_output`] and the
+    /// other output writers, which replace such a link rather than writing
+    /// through it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if copying fails.
+    pub fn stage_file(&self, src: &Path, relative: &Path) -> Result<()> {
+        let dest = self.path.join(relative);
+        let live = self.target.join(relative);
+        if is_unchanged_copy(src, &live) {
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create directory {}", parent.display()))?;
+            }
+            // Falls back to copying, e.g., across file systems.
+            if fs::hard_link(&live, &dest).is_ok() {
+                return Ok(());
+            }
+        }
+        copy_file(src, &dest)
+    }
+
+    /// Syncs the staged output into the live one, then removes the staging
+    /// directory. Returns how many files were replaced or added.
+    ///
+    /// Live files the same as their staged counterpart (hard-linked by
+    /// [`StagingDir::stage_file`], or with the same SHA-256 digest) are left
+    /// untouched. Every other file is renamed into place, so no reader sees a
+    /// half-written file, but the tree is updated file by file: while
+    /// promoting, readers may see new files next to old ones. Live files the
+    /// build no longer produces are removed last, once everything new is in
+    /// place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if walking either directory, or removing, comparing,
+    /// or moving a file fails. The staging directory is then left in place
+    /// and the live output may be partly updated, with no file removed yet.
+    pub fn promote(self) -> Result<usize> {
+        fs::create_dir_all(&self.target).with_context(|| {
+            format!(
+                "failed to create output directory {}",
+                self.target.display()
+            )
+        })?;
+        let entries = WalkDir::new(&self.path)
+            .min_depth(1)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("failed to read entry in {}", self.path.display()))?;
+        let mut staged = HashSet::new();
+        let mut updated = 0;
+        for entry in entries {
+            let Ok(relative) = entry.path().strip_prefix(&self.path) else {
+                continue;
+            };
+            let dest = self.target.join(relative);
+            if entry.file_type().is_dir() {
+                if fs::symlink_metadata(&dest).is_ok_and(|meta| !meta.is_dir()) {
+                    fs::remove_file(&dest)
+                        .with_context(|| format!("failed to remove {}", dest.display()))?;
+                }
+                fs::create_dir_all(&dest)
+                    .with_context(|| format!("failed to create directory {}", dest.display()))?;
+                continue;
+            }
+            staged.insert(relative.to_owned());
+            if same_content(entry.path(), &dest)? {
+                continue;
+            }
+            if dest.is_dir() {
+                fs::remove_dir_all(&dest)
+                    .with_context(|| format!("failed to remove {}", dest.display()))?;
+            }
+            fs::rename(entry.path(), &dest).with_context(|| {
+                format!(
+                    "failed to move {} to {}",
+                    entry.path().display(),
+                    dest.display()
+                )
+            })?;
+            updated += 1;
+        }
+        prune_dir(&self.target, |path| {
+            path.strip_prefix(&self.target)
+                .is_ok_and(|relative| staged.contains(relative))
+        })?;
+        fs::remove_dir_all(&self.path).with_context(|| {
+            format!("failed to remove staging directory {}", self.path.display())
+        })?;
+        Ok(updated)
+    }
+}
+
+/// Returns `true` if `live` is a file of the same size as `src`, modified no
+/// earlier: a copy made since `src` last changed.
+fn is_unchanged_copy(src: &Path, live: &Path) -> bool {
+    let (Ok(src_meta), Ok(live_meta)) = (fs::metadata(src), fs::symlink_metadata(live)) else {
+        return false;
+    };
+    let (Ok(src_modified), Ok(live_modified)) = (src_meta.modified(), live_meta.modified()) else {
+        return false;
+    };
+    live_meta.is_file() && live_meta.len() == src_meta.len() && live_modified >= src_modified
+}
+
+/// Removes the file at `path`, if any, so writing it creates a new file
+/// rather than writing through a hard link into the live output (see
+/// [`StagingDir::stage_file`]).
+fn unlink_existing(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("failed to replace {}", path.display())),
+    }
+}
+
+/// Returns `path` with `suffix` appended to its full OS string, so callers can
+/// derive a sibling path that preserves any nested components (e.g. `dist/site`
+/// → `dist/site.staging`).
+pub(crate) fn append_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut buf = path.as_os_str().to_owned();
+    buf.push(suffix);
+    PathBuf::from(buf)
+}
+
+/// Joins the components of `relative`, a path under the output directory,
+/// with `/`, giving its URL path below the site root on every platform.
+pub(crate) fn url_path(relative: &Path) -> String {
+    let segments: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    segments.join("/")
+}
+
+/// Parses a site-root URL path (leading and trailing `/` ignored) into a path
+/// under the output directory, or `None` if it is empty or has an empty, `.`,
+/// or `..` segment.
+pub(crate) fn output_path(url: &str) -> Option<PathBuf> {
+    let relative = url.trim_matches('/');
+    if relative.is_empty() {
+        return None;
+    }
+    let mut path = PathBuf::new();
+    for segment in relative.split('/') {
+        if matches!(segment, "" | "." | "..") {
+            return None;
+        }
+        path.push(segment);
+    }
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then_some(path)
+}
+
+/// Removes every file under `dir` that `keep` rejects, along with the
+/// directories that leaves empty, and returns how many files were removed.
+/// Creates `dir` if it does not exist.
+///
+/// # Errors
 ///
-/// Does nothing if the directory does not exist.
+/// Returns an error if `dir` cannot be created or walked, or removing a
+/// file fails.
+pub fn prune_dir(dir: &Path, keep: impl Fn(&Path) -> bool) -> Result<usize> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create output directory {}", dir.display()))?;
+    let mut removed = 0;
+    for entry in WalkDir::new(dir).min_depth(1).contents_first(true) {
+        let entry = entry.with_context(|| format!("failed to read entry in {}", dir.display()))?;
+        if entry.file_type().is_dir() {
+            // Fails, as intended, unless the directory was left empty.
+            _ = fs::remove_dir(entry.path());
+        } else if !keep(entry.path()) {
+            fs::remove_file(entry.path())
+                .with_context(|| format!("failed to remove {}", entry.path().display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Returns `true` if `dest` is a regular file with the same content as
+/// `src`: a hard link to it, or a file of the same size and the same
+/// SHA-256 digest.
 ///
 /// # Errors
 ///
-/// Returns an error if removal or creation fails.
-pub fn clean_output_dir(path: &Path) -> Result<()> {
-    if path.exists() {
-        fs::remove_dir_all(path)
-            .with_context(|| format!("failed to clean output directory {}", path.display()))?;
-    }
-    fs::create_dir_all(path)
-        .with_context(|| format!("failed to create output directory {}", path.display()))
+/// Returns an error if either file exists but cannot be read.
+fn same_content(src: &Path, dest: &Path) -> Result<bool> {
+    let Ok(dest_meta) = fs::symlink_metadata(dest) else {
+        return Ok(false);
+    };
+    if dest_meta.is_file() && is_same_file(src, dest).unwrap_or(false) {
+        return Ok(true);
+    }
+    let src_meta =
+        fs::metadata(src).with_context(|| format!("failed to read {}", src.display()))?;
+    if !dest_meta.is_file() || dest_meta.len() != src_meta.len() {
+        return Ok(false);
+    }
+    Ok(file_digest(src)? == file_digest(dest)?)
+}
+
+/// Returns the SHA-256 digest of the file at `path`, read in chunks.
+fn file_digest(path: &Path) -> Result<[u8; 32]> {
+    let mut file =
+        File::open(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(hasher.finalize().into())
 }
 
-/// Recursively copies all files from `src` into `dest`, preserving directory structure.
+/// Lists the files [`copy_static`] copies from `src` into `dest`, as
+/// `(source, destination)` pairs preserving the directory structure.
 ///
 /// Files and directories whose names start with `_` are skipped (including
 /// their entire subtrees). This mirrors the content-discovery convention and
@@ -28,21 +338,25 @@ pub fn clean_output_dir(path: &Path) -> Result<()> {
 /// under `static/css/_src/`) in the same tree as the shipped bundle without
 /// exposing them in the published site.
 ///
-/// Skips the copy entirely if `src` does not exist.
+/// Lists nothing if `src` does not exist.
 ///
 /// # Errors
 ///
-/// Returns an error if directory creation or file copying fails.
-pub fn copy_static(src: &Path, dest: &Path) -> Result<()> {
+/// Returns an error if walking `src` fails.
+pub fn static_files(src: &Path, dest: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
     if !src.exists() {
-        return Ok(());
+        return Ok(Vec::new());
     }
+    let mut files = Vec::new();
     let walker = WalkDir::new(src)
         .follow_links(false)
         .into_iter()
         .filter_entry(|e| e.depth() == 0 || !is_build_private(e));
     for entry in walker {
         let entry = entry.with_context(|| format!("failed to read entry in {}", src.display()))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
         let relative = entry.path().strip_prefix(src).with_context(|| {
             format!(
                 "path {} is not under {}",
@@ -50,15 +364,46 @@ pub fn copy_static(src: &Path, dest: &Path) -> Result<()> {
                 src.display()
             )
         })?;
-        let target = dest.join(relative);
-        if entry.file_type().is_dir() {
-            fs::create_dir_all(&target)
-                .with_context(|| format!("failed to create directory {}", target.display()))?;
-        } else {
-            copy_file(entry.path(), &target)?;
+        files.push((entry.path().to_owned(), dest.join(relative)));
+    }
+    Ok(files)
+}
+
+/// Recursively copies all files from `src` into `dest`, preserving directory
+/// structure and skipping build-private `_` entries (see [`static_files`]).
+///
+/// Skips the copy entirely if `src` does not exist. Returns the number of
+/// files copied.
+///
+/// # Errors
+///
+/// Returns an error if walking `src`, directory creation, or file copying
+/// fails.
+pub fn copy_static(src: &Path, dest: &Path) -> Result<usize> {
+    let files = static_files(src, dest)?;
+    for (source, target) in &files {
+        copy_file(source, target)?;
+    }
+    Ok(files.len())
+}
+
+/// Returns the total size in bytes of the files under `dir`.
+///
+/// # Errors
+///
+/// Returns an error if walking the directory or reading file metadata fails.
+pub fn dir_size(dir: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in WalkDir::new(dir).follow_links(false) {
+        let entry = entry.with_context(|| format!("failed to read entry in {}", dir.display()))?;
+        if entry.file_type().is_file() {
+            let metadata = entry
+                .metadata()
+                .with_context(|| format!("failed to read {}", entry.path().display()))?;
+            size += metadata.len();
         }
     }
-    Ok(())
+    Ok(size)
 }
 
 /// Returns `true` for entries whose file name starts with `_`. Such entries
@@ -71,32 +416,131 @@ fn is_build_private(entry: &walkdir::DirEntry) -> bool {
         .is_some_and(|name| name.starts_with('_'))
 }
 
-/// Copies a single file from `src` to `dest`, creating parent directories as needed.
+/// Copies a single file from `src` to `dest`, creating parent directories as
+/// needed. Bytes are copied verbatim, so binary assets pass through intact;
+/// the copy gets the standard output permissions rather than the source's.
 ///
 /// # Errors
 ///
-/// Returns an error if directory creation or file copying fails.
+/// Returns an error if directory creation, file copying, or setting
+/// permissions fails.
 pub fn copy_file(src: &Path, dest: &Path) -> Result<()> {
     if let Some(parent) = dest.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("failed to create directory {}", parent.display()))?;
     }
+    unlink_existing(dest)?;
     fs::copy(src, dest)
         .with_context(|| format!("failed to copy {} to {}", src.display(), dest.display()))?;
-    Ok(())
+    set_output_permissions(dest)
+}
+
+/// Joins the output-relative path `relative` onto `output_dir`, refusing
+/// any path that could land outside it: absolute paths and `..` segments
+/// are rejected, whatever frontmatter value they were computed from.
+///
+/// # Errors
+///
+/// Returns an error if `relative` is empty or has a component other than a
+/// plain name (or `.`).
+pub fn output_file(output_dir: &Path, relative: &Path) -> Result<PathBuf> {
+    let mut components = relative
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .peekable();
+    ensure!(
+        components.peek().is_some() && components.all(|c| matches!(c, Component::Normal(_))),
+        "output path `{}` would escape the output directory",
+        relative.display()
+    );
+    Ok(output_dir.join(relative))
 }
 
 /// Writes `content` to the given path, creating parent directories as needed.
 ///
 /// # Errors
 ///
-/// Returns an error if directory creation or file writing fails.
-pub fn write_output(path: &Path, content: &str) -> Result<()> {
+/// Returns an error if directory creation, file writing, or setting
+/// permissions fails.
+pub fn write_output(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("failed to create directory {}", parent.display()))?;
     }
-    fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+    unlink_existing(path)?;
+    fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))?;
+    set_output_permissions(path)
+}
+
+/// Writes the output of `write` to the given path through a buffered
+/// writer, creating parent directories as needed, so large outputs are
+/// never held in memory whole. A file `write` fails partway through is
+/// removed.
+///
+/// # Errors
+///
+/// Returns an error if directory creation, `write`, flushing the file, or
+/// setting permissions fails.
+pub fn write_output_with(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<()>,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory {}", parent.display()))?;
+    }
+    unlink_existing(path)?;
+    let file = File::create(path).with_context(|| format!("failed to write {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    let written = write(&mut out).and_then(|()| {
+        out.flush()
+            .with_context(|| format!("failed to write {}", path.display()))
+    });
+    if let Err(e) = written {
+        drop(out);
+        _ = fs::remove_file(path);
+        return Err(e);
+    }
+    drop(out);
+    set_output_permissions(path)
+}
+
+/// Appends `content` to the file at `path`, after any lines it already holds
+/// (e.g., a host config file copied from `static/`). Creates the file if it
+/// does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the existing file cannot be read or writing fails.
+pub fn append_output(path: &Path, content: &str) -> Result<()> {
+    let mut contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(content);
+    write_output(path, &contents)
+}
+
+/// Applies [`OUTPUT_FILE_MODE`] to a written file. A no-op off Unix.
+#[cfg(unix)]
+fn set_output_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(OUTPUT_FILE_MODE))
+        .with_context(|| format!("failed to set permissions on {}", path.display()))
+}
+
+#[cfg(not(unix))]
+#[expect(
+    clippy::unnecessary_wraps,
+    reason = "matches the Unix signature so callers stay platform-agnostic"
+)]
+fn set_output_permissions(_path: &Path) -> Result<()> {
+    Ok(())
 }
 
 #[cfg(test)]
@@ -104,47 +548,285 @@ mod tests {
     use super::*;
     use crate::test_utils::PermissionGuard;
 
-    // ── clean_output_dir ──
+    // ── OutputKind::of ──
+
+    #[test]
+    fn output_kind_sniffs_extension() {
+        for path in [
+            "a/index.html",
+            "a/style.CSS",
+            "a/app.mjs",
+            "feed.xml",
+            "logo.svg",
+        ] {
+            assert_eq!(OutputKind::of(Path::new(path)), OutputKind::Text, "{path}");
+        }
+        for path in [
+            "a/photo.JPG",
+            "font.woff2",
+            "paper.pdf",
+            "README",
+            "notes.unknown",
+        ] {
+            assert_eq!(
+                OutputKind::of(Path::new(path)),
+                OutputKind::Binary,
+                "{path}"
+            );
+        }
+        for path in [
+            "a/app.js.gz",
+            "style.css.br",
+            "vendor.min.js",
+            "vendor.MIN.CSS",
+        ] {
+            assert_eq!(
+                OutputKind::of(Path::new(path)),
+                OutputKind::Compressed,
+                "{path}"
+            );
+        }
+    }
+
+    // ── StagingDir ──
 
     #[test]
-    fn clean_creates_nonexistent_dir() {
+    fn staging_dir_create_discards_leftover_staging() {
         let dir = tempfile::tempdir().unwrap();
-        let output = dir.path().join("public");
+        let target = dir.path().join("public");
+        let leftover = dir.path().join("public.staging").join("stale");
+        fs::create_dir_all(&leftover).unwrap();
+        fs::write(leftover.join("old.html"), "leftover").unwrap();
 
-        clean_output_dir(&output).unwrap();
+        let staging = StagingDir::create(&target).unwrap();
+        assert_eq!(staging.path(), dir.path().join("public.staging"));
+        assert_eq!(staging.target(), target);
+        assert!(staging.path().is_dir());
+        assert!(!leftover.exists());
+    }
 
-        assert!(output.exists());
+    #[test]
+    fn staging_dir_promote_syncs_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("public");
+        fs::create_dir_all(target.join("gone")).unwrap();
+        fs::write(target.join("gone").join("index.html"), "gone").unwrap();
+        fs::write(target.join("same.html"), "same").unwrap();
+        fs::write(target.join("changed.html"), "before").unwrap();
+
+        let staging = StagingDir::create(&target).unwrap();
+        fs::write(staging.path().join("same.html"), "same").unwrap();
+        fs::write(staging.path().join("changed.html"), "after!").unwrap();
+        fs::create_dir(staging.path().join("posts")).unwrap();
+        fs::write(staging.path().join("posts").join("new.html"), "new").unwrap();
+        assert_eq!(staging.promote().unwrap(), 2);
+
+        assert_eq!(
+            fs::read_to_string(target.join("same.html")).unwrap(),
+            "same"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("changed.html")).unwrap(),
+            "after!"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("posts").join("new.html")).unwrap(),
+            "new"
+        );
+        assert!(!target.join("gone").exists(), "removed output is swept");
+        assert!(
+            !dir.path().join("public.staging").exists(),
+            "no copy of the site is kept"
+        );
     }
 
     #[test]
-    fn clean_removes_existing_contents() {
+    fn staging_dir_promote_replaces_same_size_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("public");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("page.html"), "original").unwrap();
+
+        let staging = StagingDir::create(&target).unwrap();
+        fs::write(staging.path().join("page.html"), "modified").unwrap();
+        assert_eq!(staging.promote().unwrap(), 1);
+
+        assert_eq!(
+            fs::read_to_string(target.join("page.html")).unwrap(),
+            "modified"
+        );
+    }
+
+    #[test]
+    fn staging_dir_promote_replaces_file_with_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("public");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("posts"), "file").unwrap();
+
+        let staging = StagingDir::create(&target).unwrap();
+        fs::create_dir(staging.path().join("posts")).unwrap();
+        fs::write(staging.path().join("posts").join("index.html"), "dir").unwrap();
+        staging.promote().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(target.join("posts").join("index.html")).unwrap(),
+            "dir"
+        );
+    }
+
+    #[test]
+    fn staging_dir_promote_replaces_dir_with_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("public");
+        fs::create_dir_all(target.join("feed")).unwrap();
+        fs::write(target.join("feed").join("index.xml"), "dir").unwrap();
+
+        let staging = StagingDir::create(&target).unwrap();
+        fs::write(staging.path().join("feed"), "file").unwrap();
+        staging.promote().unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("feed")).unwrap(), "file");
+    }
+
+    #[test]
+    fn staging_dir_stage_file_links_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("logo.png");
+        let target = dir.path().join("public");
+        fs::write(&src, "logo").unwrap();
+        copy_file(&src, &target.join("images").join("logo.png")).unwrap();
+
+        let staging = StagingDir::create(&target).unwrap();
+        let relative = Path::new("images/logo.png");
+        staging.stage_file(&src, relative).unwrap();
+        let staged = staging.path().join(relative);
+        assert!(is_same_file(&staged, target.join(relative)).unwrap());
+
+        // Writing the staged file must not write through to the live one.
+        write_output(&staged, "edited").unwrap();
+        assert_eq!(fs::read_to_string(target.join(relative)).unwrap(), "logo");
+        assert_eq!(staging.promote().unwrap(), 1);
+        assert_eq!(fs::read_to_string(target.join(relative)).unwrap(), "edited");
+    }
+
+    #[test]
+    fn staging_dir_stage_file_copies_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("style.css");
+        let target = dir.path().join("public");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("style.css"), "body{}").unwrap();
+        fs::write(&src, "body{color:red}").unwrap();
+
+        let staging = StagingDir::create(&target).unwrap();
+        staging.stage_file(&src, Path::new("style.css")).unwrap();
+        let staged = staging.path().join("style.css");
+        assert!(!is_same_file(&staged, target.join("style.css")).unwrap());
+        assert_eq!(fs::read_to_string(&staged).unwrap(), "body{color:red}");
+
+        staging
+            .stage_file(&src, Path::new("new/style.css"))
+            .unwrap();
+        assert!(staging.path().join("new").join("style.css").is_file());
+    }
+
+    #[test]
+    fn staging_dir_promote_without_existing_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("public");
+
+        let staging = StagingDir::create(&target).unwrap();
+        staging.promote().unwrap();
+
+        assert!(target.exists());
+        assert!(!dir.path().join("public.staging").exists());
+    }
+
+    #[test]
+    fn staging_dir_preserves_nested_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("dist").join("site");
+
+        let staging = StagingDir::create(&target).unwrap();
+        assert_eq!(staging.path(), dir.path().join("dist").join("site.staging"));
+    }
+
+    // ── url_path ──
+
+    #[test]
+    fn url_path_joins_components_with_slash() {
+        let path: PathBuf = ["css", "fonts", "body.woff2"].iter().collect();
+        assert_eq!(url_path(&path), "css/fonts/body.woff2");
+        assert_eq!(url_path(Path::new("")), "");
+    }
+
+    // ── output_path ──
+
+    #[test]
+    fn output_path_ignores_outer_slashes() {
+        assert_eq!(
+            output_path("/css/style.css"),
+            Some(PathBuf::from("css/style.css"))
+        );
+        assert_eq!(output_path("fonts/"), Some(PathBuf::from("fonts")));
+    }
+
+    #[test]
+    fn output_path_rejects_empty_and_dot_segments() {
+        for url in ["", "/", "css//style.css", "./css", "css/../../etc"] {
+            assert_eq!(output_path(url), None, "{url}");
+        }
+    }
+
+    // ── prune_dir ──
+
+    #[test]
+    fn prune_dir_removes_rejected_files_and_empty_dirs() {
         let dir = tempfile::tempdir().unwrap();
         let output = dir.path().join("public");
-        fs::create_dir_all(output.join("old")).unwrap();
-        fs::write(output.join("old").join("stale.html"), "stale").unwrap();
+        fs::create_dir_all(output.join("old").join("nested")).unwrap();
+        fs::write(output.join("old").join("nested").join("page.html"), "stale").unwrap();
+        fs::write(output.join("stale.html"), "stale").unwrap();
+        fs::write(output.join("kept.txt"), "kept").unwrap();
 
-        clean_output_dir(&output).unwrap();
+        let removed = prune_dir(&output, |path| path.ends_with("kept.txt")).unwrap();
 
-        assert!(output.exists(), "output dir should be recreated");
+        assert_eq!(removed, 2);
+        assert!(output.join("kept.txt").exists());
+        assert!(!output.join("stale.html").exists());
         assert!(
-            fs::read_dir(&output).unwrap().next().is_none(),
-            "output dir should be empty after clean"
+            !output.join("old").exists(),
+            "emptied directories are removed"
         );
     }
 
     #[test]
-    fn clean_permission_denied_returns_error() {
+    fn prune_dir_creates_missing_dir() {
         let dir = tempfile::tempdir().unwrap();
         let output = dir.path().join("public");
-        fs::create_dir_all(output.join("sub")).unwrap();
 
-        // Lock the parent so remove_dir_all fails on the child.
-        let _guard = PermissionGuard::restrict(&output, 0o444);
+        assert_eq!(prune_dir(&output, |_| false).unwrap(), 0);
+        assert!(output.is_dir());
+    }
 
-        let err = clean_output_dir(&output).unwrap_err().to_string();
-        assert!(
-            err.contains("failed to clean output directory"),
-            "should report clean failure, got: {err}"
+    // ── static_files ──
+
+    #[test]
+    fn static_files_lists_sources_and_destinations() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("static");
+        fs::create_dir_all(src.join("css").join("_src")).unwrap();
+        fs::write(src.join("css").join("style.css"), "body{}").unwrap();
+        fs::write(src.join("css").join("_src").join("main.css"), "private").unwrap();
+
+        let files = static_files(&src, Path::new("public")).unwrap();
+        assert_eq!(
+            files,
+            [(
+                src.join("css").join("style.css"),
+                PathBuf::from("public/css/style.css")
+            )]
         );
     }
 
@@ -160,7 +842,7 @@ mod tests {
         fs::write(src.join("favicon.ico"), "icon").unwrap();
         fs::write(src.join("images").join("logo.png"), "logo").unwrap();
 
-        copy_static(&src, &dest).unwrap();
+        assert_eq!(copy_static(&src, &dest).unwrap(), 2);
 
         assert_eq!(
             fs::read_to_string(dest.join("favicon.ico")).unwrap(),
@@ -195,7 +877,7 @@ mod tests {
         .unwrap();
         fs::write(src.join("_notes.txt"), "private-file").unwrap();
 
-        copy_static(&src, &dest).unwrap();
+        assert_eq!(copy_static(&src, &dest).unwrap(), 1);
 
         assert_eq!(
             fs::read_to_string(dest.join("css").join("style.css")).unwrap(),
@@ -218,8 +900,7 @@ mod tests {
         let src = dir.path().join("static");
         let dest = dir.path().join("public");
 
-        copy_static(&src, &dest).unwrap();
-
+        assert_eq!(copy_static(&src, &dest).unwrap(), 0);
         assert!(!dest.exists());
     }
 
@@ -260,6 +941,18 @@ mod tests {
         );
     }
 
+    // ── dir_size ──
+
+    #[test]
+    fn dir_size_sums_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("posts/hello")).unwrap();
+        fs::write(dir.path().join("index.html"), "12345").unwrap();
+        fs::write(dir.path().join("posts/hello/index.html"), "123").unwrap();
+
+        assert_eq!(dir_size(dir.path()).unwrap(), 8);
+    }
+
     // ── copy_file ──
 
     #[test]
@@ -274,6 +967,36 @@ mod tests {
         assert_eq!(fs::read_to_string(&dest).unwrap(), "image-data");
     }
 
+    #[test]
+    fn copy_file_preserves_binary_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("image.png");
+        let dest = dir.path().join("out").join("image.png");
+        let bytes = b"\x89PNG\r\n\x1a\n\x00\xff";
+        fs::write(&src, bytes).unwrap();
+
+        copy_file(&src, &dest).unwrap();
+
+        assert_eq!(fs::read(&dest).unwrap(), bytes);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_file_normalizes_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("private.pdf");
+        let dest = dir.path().join("public.pdf");
+        fs::write(&src, "pdf").unwrap();
+        fs::set_permissions(&src, fs::Permissions::from_mode(0o600)).unwrap();
+
+        copy_file(&src, &dest).unwrap();
+
+        let mode = fs::metadata(&dest).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, OUTPUT_FILE_MODE);
+    }
+
     #[test]
     fn copy_file_nonexistent_src_returns_error() {
         let dir = tempfile::tempdir().unwrap();
@@ -305,6 +1028,38 @@ mod tests {
         );
     }
 
+    // ── output_file ──
+
+    #[test]
+    fn output_file_joins_relative_paths() {
+        assert_eq!(
+            output_file(
+                Path::new("/site/public"),
+                Path::new("./posts/hello/index.html")
+            )
+            .unwrap(),
+            Path::new("/site/public/posts/hello/index.html")
+        );
+    }
+
+    #[test]
+    fn output_file_rejects_escaping_paths() {
+        for relative in [
+            "../index.html",
+            "posts/../../x.html",
+            "/etc/passwd",
+            "",
+            ".",
+        ] {
+            let err = output_file(Path::new("/site/public"), Path::new(relative)).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("would escape the output directory"),
+                "{relative:?}: {err}"
+            );
+        }
+    }
+
     // ── write_output ──
 
     #[test]
@@ -359,4 +1114,62 @@ mod tests {
             "should report write failure, got: {err}"
         );
     }
+
+    // ── write_output_with ──
+
+    #[test]
+    fn write_output_with_streams_into_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a").join("page.html");
+
+        write_output_with(&path, |out| {
+            out.write_all(b"<p>")?;
+            out.write_all(b"streamed</p>")?;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "<p>streamed</p>");
+    }
+
+    #[test]
+    fn write_output_with_removes_partial_file_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("page.html");
+
+        let err = write_output_with(&path, |out| {
+            out.write_all(b"<p>half")?;
+            anyhow::bail!("template failed")
+        })
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "template failed");
+        assert!(!path.exists());
+    }
+
+    // ── append_output ──
+
+    #[test]
+    fn append_output_keeps_existing_lines_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("_headers");
+        fs::write(&path, "/a\n  X-Frame-Options: DENY").unwrap();
+
+        append_output(&path, "/b\n").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/a\n  X-Frame-Options: DENY\n/b\n"
+        );
+    }
+
+    #[test]
+    fn append_output_creates_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("_headers");
+
+        append_output(&path, "/b\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "/b\n");
+    }
 }
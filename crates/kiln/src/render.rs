@@ -1,31 +1,184 @@
+pub mod abbreviation;
+pub mod alert;
 pub mod assets;
+pub mod citation;
 pub mod emoji;
+pub mod external;
 pub mod highlight;
 pub mod icon;
 pub mod image;
 pub mod image_attrs;
+pub mod link;
 pub mod markdown;
 pub mod mermaid;
 pub mod pipeline;
+pub mod reference;
+pub mod responsive;
+pub mod sanitize;
+pub mod snippet;
+pub mod strict;
 pub mod toc;
 
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tracing::warn;
+
+use self::assets::DirectiveAssets;
+use self::citation::Bibliography;
+use self::external::ExternalLinks;
+use self::link::ContentLinks;
+use self::markdown::HeadingAnchor;
+use crate::config::{Config, HeadingIds, HighlightClasses, Images, Markdown, SlugifyMode};
+use crate::i18n::I18n;
+use crate::template::cache::HighlightCache;
+use crate::text::case::TitleCaser;
+
 /// Feature flags and settings for the render pipeline.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent feature toggles, each set from its own config key"
+)]
 pub struct RenderOptions {
+    /// Markdown syntax extensions, from `[markdown]`.
+    pub markdown: Markdown,
     pub code_max_lines: Option<usize>,
+    /// Default for code blocks without a `linenos` option.
+    pub code_line_numbers: bool,
+    /// Class names of highlighted code tokens, from `[markup]`.
+    pub code_classes: HighlightClasses,
+    /// Prefix of `chroma` token class names, from `[markup]`.
+    pub code_class_prefix: String,
+    /// Highlighted code kept from earlier builds, from `[cache] highlight`.
+    pub highlight_cache: Option<Arc<HighlightCache>>,
     pub emojis: bool,
     pub fontawesome: bool,
+    /// Script URL registered on pages that contain a ` ```mermaid ` fence.
+    pub mermaid_script: Option<String>,
+    /// Stylesheets and scripts registered on pages that use a directive,
+    /// keyed by directive name.
+    pub directive_assets: BTreeMap<String, DirectiveAssets>,
+    /// Heading levels listed in the table of contents, from `[toc]`. `None`
+    /// skips the table of contents, as for pages with `toc = false`.
+    pub toc_levels: Option<RangeInclusive<u8>>,
+    /// Which headings get an `id` (and a `ToC` entry), from `[markup]`.
+    pub heading_ids: HeadingIds,
+    /// How heading IDs are generated, from `[slugify] mode`.
+    pub slugify: SlugifyMode,
+    /// Permalink added to every heading with an `id`, when enabled.
+    pub heading_anchor: Option<HeadingAnchor>,
+    /// Casing applied to headings, from `[markup] title_case`.
+    pub title_case: Option<TitleCaser>,
+    /// Site-wide link aliases from `[links]`, resolved from `link:name` URLs.
+    pub links: BTreeMap<String, String>,
+    /// The page's bibliography from the `bibliography` frontmatter key;
+    /// enables `[@key]` citations.
+    pub bibliography: Option<Bibliography>,
+    /// Output URLs of the site's pages; enables links to content files
+    /// (`[text](../other/index.md)`, `[text](@/posts/foo.md)`).
+    pub content_links: Option<Arc<ContentLinks>>,
+    /// Decoration of off-site links from `[external_links]`, when enabled.
+    pub external_links: Option<ExternalLinks>,
+    /// Responsive variants of page bundle images, from `[images]`.
+    pub images: Images,
+    /// Directories root-relative image sources are looked up in for their
+    /// intrinsic size: the site's `static/`, then the theme's.
+    pub static_dirs: Vec<PathBuf>,
+    /// The page bundle's co-located assets, listed by `gallery` directives.
+    pub bundle_assets: Vec<PathBuf>,
+    /// Fails rendering on the problems [`strict::check_markdown`] finds,
+    /// from `strict`.
+    pub strict: bool,
+    /// The site's i18n strings, for text the pipeline adds to pages (such
+    /// as the references heading).
+    pub i18n: Option<I18n>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            markdown: Markdown::default(),
+            code_max_lines: None,
+            code_line_numbers: true,
+            code_classes: HighlightClasses::Syntect,
+            code_class_prefix: String::new(),
+            highlight_cache: None,
+            emojis: false,
+            fontawesome: false,
+            mermaid_script: None,
+            directive_assets: BTreeMap::new(),
+            toc_levels: Some(1..=6),
+            heading_ids: HeadingIds::Auto,
+            slugify: SlugifyMode::Safe,
+            heading_anchor: None,
+            title_case: None,
+            links: BTreeMap::new(),
+            bibliography: None,
+            content_links: None,
+            external_links: None,
+            images: Images::default(),
+            static_dirs: Vec::new(),
+            bundle_assets: Vec::new(),
+            strict: false,
+            i18n: None,
+        }
+    }
 }
 
 impl RenderOptions {
+    /// Extracts render options from the site `[params]`, `[links]`,
+    /// `[external_links]`, `[markdown]`, `[markup]`, `[slugify]`, `[toc]`, and
+    /// `[images]` tables.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            markdown: config.markdown,
+            links: config.links.clone(),
+            toc_levels: Some(config.toc.levels()),
+            heading_ids: config.markup.heading_ids,
+            slugify: config.slugify.mode(),
+            code_classes: config.markup.highlight_classes,
+            code_class_prefix: config.markup.highlight_class_prefix.clone(),
+            title_case: TitleCaser::from_config(config),
+            external_links: config
+                .external_links
+                .enabled
+                .then(|| ExternalLinks::new(&config.base_url, config.external_links.class.clone())),
+            images: config.images.clone(),
+            strict: config.strict,
+            ..Self::from_params(&config.params)
+        }
+    }
+
+    /// Returns the i18n string `key`, or `default` when the site and theme
+    /// do not translate it.
+    #[must_use]
+    pub fn label<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.i18n
+            .as_ref()
+            .and_then(|i18n| i18n.get(key))
+            .unwrap_or(default)
+    }
+
     /// Extracts render options from the site `[params]` table.
     #[must_use]
     pub fn from_params(params: &toml::Table) -> Self {
         Self {
+            markdown: Markdown::default(),
             code_max_lines: params
                 .get("code_max_lines")
                 .and_then(toml::Value::as_integer)
                 .and_then(|n| usize::try_from(n).ok()),
+            code_line_numbers: params
+                .get("code_line_numbers")
+                .and_then(toml::Value::as_bool)
+                .unwrap_or(true),
+            code_classes: HighlightClasses::Syntect,
+            code_class_prefix: String::new(),
+            highlight_cache: None,
             emojis: params
                 .get("emojis")
                 .and_then(toml::Value::as_bool)
@@ -34,15 +187,69 @@ impl RenderOptions {
                 .get("fontawesome")
                 .and_then(toml::Value::as_bool)
                 .unwrap_or(false),
+            mermaid_script: params
+                .get("mermaid_script")
+                .and_then(toml::Value::as_str)
+                .map(String::from),
+            directive_assets: directive_assets_from_params(params),
+            toc_levels: Some(1..=6),
+            heading_ids: HeadingIds::Auto,
+            slugify: SlugifyMode::Safe,
+            heading_anchor: heading_anchor_from_params(params),
+            title_case: None,
+            links: BTreeMap::new(),
+            bibliography: None,
+            content_links: None,
+            external_links: None,
+            images: Images::default(),
+            static_dirs: Vec::new(),
+            bundle_assets: Vec::new(),
+            strict: false,
+            i18n: None,
         }
     }
 }
 
+/// Reads the `directive_assets` table of per-directive dependencies. A
+/// malformed table is ignored with a warning.
+fn directive_assets_from_params(params: &toml::Table) -> BTreeMap<String, DirectiveAssets> {
+    let Some(value) = params.get("directive_assets") else {
+        return BTreeMap::new();
+    };
+    value.clone().try_into().unwrap_or_else(|err| {
+        warn!(%err, "invalid directive_assets; expected tables of `styles` / `scripts` URLs");
+        BTreeMap::new()
+    })
+}
+
+/// Reads `heading_anchors` (`"before"` / `"after"` the heading text) and
+/// `heading_anchor_symbol` (default `¶`). Unset or unrecognized positions
+/// disable anchors.
+fn heading_anchor_from_params(params: &toml::Table) -> Option<HeadingAnchor> {
+    let position = params.get("heading_anchors")?.as_str()?;
+    let Ok(position) = position.parse() else {
+        warn!(
+            position,
+            "unknown heading_anchors position; expected \"before\" or \"after\""
+        );
+        return None;
+    };
+    let symbol = params
+        .get("heading_anchor_symbol")
+        .and_then(toml::Value::as_str)
+        .unwrap_or("¶");
+    Some(HeadingAnchor {
+        symbol: symbol.to_owned(),
+        position,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::render::markdown::AnchorPosition;
 
     // ── RenderOptions::from_params ──
 
@@ -52,19 +259,88 @@ mod tests {
         assert!(!options.emojis);
         assert!(!options.fontawesome);
         assert!(options.code_max_lines.is_none());
+        assert!(options.code_line_numbers);
+        assert!(options.mermaid_script.is_none());
+        assert!(options.heading_anchor.is_none());
     }
 
     #[test]
     fn render_options_all_set() {
-        let params: toml::Table = toml::from_str(indoc! {r"
+        let params: toml::Table = toml::from_str(indoc! {r##"
             code_max_lines = 40
+            code_line_numbers = false
             emojis = true
             fontawesome = true
-        "})
+            mermaid_script = "/js/mermaid.min.js"
+            heading_anchors = "Before"
+            heading_anchor_symbol = "#"
+        "##})
         .unwrap();
         let options = RenderOptions::from_params(&params);
         assert_eq!(options.code_max_lines, Some(40));
+        assert!(!options.code_line_numbers);
         assert!(options.emojis);
         assert!(options.fontawesome);
+        assert_eq!(
+            options.mermaid_script.as_deref(),
+            Some("/js/mermaid.min.js")
+        );
+        assert_eq!(
+            options.heading_anchor,
+            Some(HeadingAnchor {
+                symbol: "#".into(),
+                position: AnchorPosition::Before,
+            })
+        );
+    }
+
+    #[test]
+    fn render_options_directive_assets() {
+        let params: toml::Table = toml::from_str(indoc! {r#"
+            [directive_assets.gallery]
+            styles = ["/css/gallery.css"]
+            scripts = ["/js/gallery.js"]
+
+            [directive_assets.chart]
+            scripts = ["/js/chart.js"]
+        "#})
+        .unwrap();
+        let options = RenderOptions::from_params(&params);
+        assert_eq!(
+            options.directive_assets,
+            BTreeMap::from([
+                (
+                    "chart".into(),
+                    DirectiveAssets {
+                        styles: Vec::new(),
+                        scripts: vec!["/js/chart.js".into()],
+                    }
+                ),
+                (
+                    "gallery".into(),
+                    DirectiveAssets {
+                        styles: vec!["/css/gallery.css".into()],
+                        scripts: vec!["/js/gallery.js".into()],
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn render_options_invalid_directive_assets_are_ignored() {
+        let params: toml::Table =
+            toml::from_str(r#"directive_assets = { gallery = { style = "/a.css" } }"#).unwrap();
+        assert!(
+            RenderOptions::from_params(&params)
+                .directive_assets
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn render_options_unknown_heading_anchor_position_disables_anchors() {
+        let params: toml::Table = toml::from_str(r#"heading_anchors = "inside""#).unwrap();
+        assert!(RenderOptions::from_params(&params).heading_anchor.is_none());
     }
 }
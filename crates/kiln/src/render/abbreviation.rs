@@ -0,0 +1,154 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::markdown::for_each_non_code_line;
+
+/// Matches an abbreviation definition line, e.g.,
+/// `*[HTML]: HyperText Markup Language`.
+static DEFINITION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^ {0,3}\*\[([^\]]+)\]:[ \t]*(.*?)\s*$")
+        .expect("abbreviation definition regex should compile")
+});
+
+/// Abbreviations defined on a page, with their expansions.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Abbreviations {
+    /// `(abbreviation, expansion)` pairs, longest abbreviation first so
+    /// `HTML5` is matched before `HTML`.
+    definitions: Vec<(String, String)>,
+}
+
+impl Abbreviations {
+    pub(crate) const fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Finds the first whole-word occurrence of an abbreviation in `text`,
+    /// returning its byte offset, the abbreviation, and its expansion.
+    ///
+    /// Matching is case-sensitive; an occurrence must not be preceded or
+    /// followed by a letter, digit, or underscore.
+    pub(crate) fn find(&self, text: &str) -> Option<(usize, &str, &str)> {
+        let mut prev: Option<char> = None;
+        for (i, ch) in text.char_indices() {
+            if !prev.is_some_and(is_word_char) {
+                let found = self.definitions.iter().find(|(abbr, _)| {
+                    text[i..].starts_with(abbr.as_str())
+                        && !text[i + abbr.len()..]
+                            .chars()
+                            .next()
+                            .is_some_and(is_word_char)
+                });
+                if let Some((abbr, expansion)) = found {
+                    return Some((i, abbr, expansion));
+                }
+            }
+            prev = Some(ch);
+        }
+        None
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Removes PHP Markdown Extra abbreviation definitions from `input`,
+/// returning the remaining markdown and the definitions.
+///
+/// ```markdown
+/// *[HTML]: HyperText Markup Language
+/// ```
+///
+/// - A definition is a line of its own, anywhere on the page outside fenced
+///   code blocks, directive bodies included. The first definition of an
+///   abbreviation wins.
+/// - An empty expansion (`*[HTML]:`) still marks the abbreviation, without a
+///   `title`.
+pub(crate) fn extract_abbreviations(input: &str) -> (String, Abbreviations) {
+    let mut output = String::with_capacity(input.len());
+    let mut definitions: Vec<(String, String)> = Vec::new();
+    for_each_non_code_line(input, &mut output, |line, out| {
+        let Some(caps) = DEFINITION_RE.captures(line) else {
+            out.push_str(line);
+            return;
+        };
+        let abbr = caps[1].trim();
+        if !abbr.is_empty() && !definitions.iter().any(|(a, _)| a == abbr) {
+            definitions.push((abbr.to_owned(), caps[2].to_owned()));
+        }
+    });
+    definitions.sort_by_key(|(abbr, _)| std::cmp::Reverse(abbr.len()));
+    (output, Abbreviations { definitions })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── extract_abbreviations ──
+
+    #[test]
+    fn extract_abbreviations_removes_definition_lines() {
+        let (content, abbreviations) = extract_abbreviations(indoc! {"
+            The HTML spec.
+
+            *[HTML]: HyperText Markup Language
+            *[W3C]:  World Wide Web Consortium
+        "});
+        assert_eq!(content, "The HTML spec.\n\n");
+        assert_eq!(
+            abbreviations.find("by the W3C."),
+            Some((7, "W3C", "World Wide Web Consortium"))
+        );
+    }
+
+    #[test]
+    fn extract_abbreviations_skips_code_blocks() {
+        let input = indoc! {"
+            ```markdown
+            *[HTML]: HyperText Markup Language
+            ```
+        "};
+        let (content, abbreviations) = extract_abbreviations(input);
+        assert_eq!(content, input);
+        assert!(abbreviations.is_empty());
+    }
+
+    #[test]
+    fn extract_abbreviations_keeps_first_definition() {
+        let (_, abbreviations) = extract_abbreviations(indoc! {"
+            *[API]: Application Programming Interface
+            *[API]: Something else
+        "});
+        assert_eq!(
+            abbreviations.find("API"),
+            Some((0, "API", "Application Programming Interface"))
+        );
+    }
+
+    // ── Abbreviations::find ──
+
+    #[test]
+    fn find_matches_whole_words_only() {
+        let (_, abbreviations) = extract_abbreviations("*[HTML]: HyperText Markup Language\n");
+        assert_eq!(abbreviations.find("XHTML and HTMLs"), None);
+        assert_eq!(abbreviations.find("(HTML)").map(|(i, ..)| i), Some(1));
+        assert_eq!(abbreviations.find("html"), None);
+    }
+
+    #[test]
+    fn find_prefers_longest_abbreviation() {
+        let (_, abbreviations) = extract_abbreviations(indoc! {"
+            *[HTML]: HyperText Markup Language
+            *[HTML 5]: HyperText Markup Language, version 5
+        "});
+        assert_eq!(
+            abbreviations.find("HTML 5 added").map(|(_, abbr, _)| abbr),
+            Some("HTML 5")
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt::Write;
+
+use crate::directive::CalloutKind;
+use crate::markdown::{detect_opening_code_fence, is_closing_code_fence, push_directive};
+
+/// Rewrites GitHub / Obsidian alert blockquotes into `::: callout` directives
+/// so they share the callout renderer and styling.
+///
+/// ```markdown
+/// > [!WARNING] Optional title
+/// > Body text.
+/// ```
+///
+/// - The marker must be the first line of a column-0 blockquote; the alert
+///   ends at the first line that does not start with `>`.
+/// - GitHub kinds map onto callout kinds (`important` → `info`, `caution` →
+///   `danger`), as do common Obsidian aliases (`tldr`, `hint`, `faq`, ...).
+///   Unrecognized kinds leave the blockquote untouched.
+/// - Obsidian fold markers are honored: `[!TIP]-` renders collapsed,
+///   `[!TIP]+` (or no marker) renders expanded.
+/// - Nested alerts (`> > [!NOTE]`) are rewritten recursively. Fenced code
+///   blocks outside alerts are skipped.
+#[must_use]
+pub fn replace_alerts(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut lines = input.split_inclusive('\n').peekable();
+    let mut code_fence = None;
+    let mut in_quote = false;
+
+    while let Some(line) = lines.next() {
+        if let Some((fence_char, fence_count)) = code_fence {
+            if is_closing_code_fence(line, fence_char, fence_count) {
+                code_fence = None;
+            }
+            output.push_str(line);
+            continue;
+        }
+        if let Some(fence) = detect_opening_code_fence(line) {
+            code_fence = Some(fence);
+            output.push_str(line);
+            continue;
+        }
+
+        let quoted = line.starts_with('>');
+        let opens_quote = quoted && !in_quote;
+        in_quote = quoted;
+        let Some(header) = line
+            .strip_prefix('>')
+            .filter(|_| opens_quote)
+            .and_then(parse_alert_header)
+        else {
+            output.push_str(line);
+            continue;
+        };
+        in_quote = false;
+
+        let mut body = String::new();
+        while let Some(next) = lines.next_if(|l| l.starts_with('>')) {
+            let stripped = &next[1..];
+            body.push_str(stripped.strip_prefix(' ').unwrap_or(stripped));
+        }
+        push_callout(&mut output, &header, &replace_alerts(&body));
+    }
+
+    output
+}
+
+/// A parsed `[!KIND]± title` alert marker line.
+struct AlertHeader {
+    kind: CalloutKind,
+    title: Option<String>,
+    open: bool,
+}
+
+/// Parses the text after the blockquote `>` as an alert marker.
+fn parse_alert_header(rest: &str) -> Option<AlertHeader> {
+    let rest = rest.trim_start().strip_prefix("[!")?;
+    let (name, rest) = rest.split_once(']')?;
+    let kind = alert_kind(name)?;
+
+    let (open, title) = match rest.as_bytes().first() {
+        Some(b'-') => (false, &rest[1..]),
+        Some(b'+') => (true, &rest[1..]),
+        _ => (true, rest),
+    };
+    let title = title.trim();
+
+    Some(AlertHeader {
+        kind,
+        title: (!title.is_empty()).then(|| title.to_owned()),
+        open,
+    })
+}
+
+/// Maps an alert name (case-insensitive) to a callout kind, accepting GitHub
+/// and Obsidian aliases.
+pub(crate) fn alert_kind(name: &str) -> Option<CalloutKind> {
+    if let Ok(kind) = name.parse() {
+        return Some(kind);
+    }
+    let kind = match name.to_ascii_lowercase().as_str() {
+        "summary" | "tldr" => CalloutKind::Abstract,
+        "important" | "todo" => CalloutKind::Info,
+        "hint" => CalloutKind::Tip,
+        "check" | "done" => CalloutKind::Success,
+        "help" | "faq" => CalloutKind::Question,
+        "attention" => CalloutKind::Warning,
+        "fail" | "missing" => CalloutKind::Failure,
+        "caution" | "error" => CalloutKind::Danger,
+        "cite" => CalloutKind::Quote,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Appends a `::: callout` directive wrapping `body`.
+fn push_callout(output: &mut String, header: &AlertHeader, body: &str) {
+    let mut attrs = format!("type={}", header.kind.as_ref());
+    if let Some(title) = &header.title {
+        let escaped = title.replace('\\', r"\\").replace('"', r#"\""#);
+        _ = write!(attrs, r#" title="{escaped}""#);
+    }
+    if !header.open {
+        attrs.push_str(" open=false");
+    }
+    push_directive(output, &format!("callout {{{attrs}}}"), body);
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── replace_alerts ──
+
+    #[test]
+    fn replace_alerts_github_note() {
+        let input = indoc! {"
+            Before.
+
+            > [!NOTE]
+            > Useful **info**.
+            > Second line.
+
+            After.
+        "};
+        assert_eq!(
+            replace_alerts(input),
+            indoc! {"
+                Before.
+
+                ::: callout {type=note}
+                Useful **info**.
+                Second line.
+                :::
+
+                After.
+            "},
+        );
+    }
+
+    #[test]
+    fn replace_alerts_maps_github_kinds() {
+        assert!(replace_alerts("> [!IMPORTANT]\n> x\n").starts_with("::: callout {type=info}"));
+        assert!(replace_alerts("> [!CAUTION]\n> x\n").starts_with("::: callout {type=danger}"));
+    }
+
+    #[test]
+    fn replace_alerts_obsidian_title_and_fold() {
+        let input = indoc! {r#"
+            > [!faq]- Why "this"?
+            > Because.
+        "#};
+        assert_eq!(
+            replace_alerts(input),
+            indoc! {r#"
+                ::: callout {type=question title="Why \"this\"?" open=false}
+                Because.
+                :::
+            "#},
+        );
+    }
+
+    #[test]
+    fn replace_alerts_nested_alert_and_directive() {
+        let input = indoc! {"
+            > [!warning]
+            > ::: callout {type=tip}
+            > Inner.
+            > :::
+            > > [!bug]
+            > > Nested.
+        "};
+        assert_eq!(
+            replace_alerts(input),
+            indoc! {"
+                :::: callout {type=warning}
+                ::: callout {type=tip}
+                Inner.
+                :::
+                ::: callout {type=bug}
+                Nested.
+                :::
+                ::::
+            "},
+        );
+    }
+
+    #[test]
+    fn replace_alerts_leaves_plain_blockquotes_and_code() {
+        let input = indoc! {"
+            > Just a quote.
+            > [!NOTE] not on the first line
+
+            > [!UNKNOWN]
+            > Body.
+
+            ```markdown
+            > [!NOTE]
+            > Example.
+            ```
+        "};
+        assert_eq!(replace_alerts(input), input);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use anyhow::{Result, bail};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use strum::AsRefStr;
 
 /// Per-page collection of asset declarations gathered during render.
@@ -18,10 +18,16 @@ pub struct PageAssets {
     /// Scripts in registration order. Order matters for dependency chains
     /// (e.g., a library script must be registered before its consumer).
     ///
-    /// Reserved for the planned directive→script bridge; no caller writes to
-    /// this field today, so themes will currently see an empty list.
+    /// Populated by the render pipeline for detected features with a
+    /// configured runtime (e.g., `mermaid_script` for Mermaid diagrams).
     pub scripts: Vec<ScriptTag>,
 
+    /// Stylesheet URLs in registration order, without duplicates.
+    ///
+    /// Populated from the dependencies declared for directives used on the
+    /// page (see [`DirectiveAssets`]).
+    pub styles: Vec<String>,
+
     /// Features auto-detected during render (math expressions, mermaid fences).
     /// Themes use these to conditionally load CSS / JS for the feature.
     pub features: BTreeSet<Feature>,
@@ -60,12 +66,46 @@ impl PageAssets {
         Ok(())
     }
 
+    /// Registers a stylesheet for the current page. Re-registering a URL is a
+    /// no-op.
+    pub fn register_style(&mut self, url: &str) {
+        if !self.styles.iter().any(|s| s == url) {
+            self.styles.push(url.to_owned());
+        }
+    }
+
+    /// Registers the stylesheets and deferred scripts a directive depends on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a script conflicts with one already registered
+    /// (see [`Self::register_script`]).
+    pub fn register_directive(&mut self, deps: &DirectiveAssets) -> Result<()> {
+        for url in &deps.styles {
+            self.register_style(url);
+        }
+        for url in &deps.scripts {
+            self.register_script(ScriptTag::deferred(url))?;
+        }
+        Ok(())
+    }
+
     /// Marks a feature as needed by the current page.
     pub fn add_feature(&mut self, feature: Feature) {
         self.features.insert(feature);
     }
 }
 
+/// Stylesheets and scripts a directive needs on any page that uses it,
+/// declared per directive name under `[params.directive_assets.<name>]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DirectiveAssets {
+    pub styles: Vec<String>,
+    /// Loaded with `defer`.
+    pub scripts: Vec<String>,
+}
+
 /// A `<script>` tag declaration.
 ///
 /// Equality compares all fields — re-registering the same URL with different
@@ -189,6 +229,21 @@ mod tests {
         );
     }
 
+    // ── PageAssets::register_directive ──
+
+    #[test]
+    fn register_directive_dedupes_styles_and_scripts() {
+        let mut assets = PageAssets::default();
+        let deps = DirectiveAssets {
+            styles: vec!["/css/gallery.css".into()],
+            scripts: vec!["/js/gallery.js".into()],
+        };
+        assets.register_directive(&deps).unwrap();
+        assets.register_directive(&deps).unwrap();
+        assert_eq!(assets.styles, ["/css/gallery.css"]);
+        assert_eq!(assets.scripts, [ScriptTag::deferred("/js/gallery.js")]);
+    }
+
     // ── PageAssets::add_feature ──
 
     #[test]
@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use biblatex::ChunksExt;
+use serde::Deserialize;
+
+use crate::html::{decode_href, escape};
+
+/// URL scheme the page parser gives `[@key]` citations; see
+/// [`PageRefs::parser`](super::reference::PageRefs::parser).
+pub(crate) const CITATION_SCHEME: &str = "cite:";
+
+/// Opening markup of a citation as rendered by `pulldown-cmark`, replaced by
+/// [`render_citations`] once the whole page has been assembled.
+const CITATION_OPEN: &str = r#"<a href="cite:"#;
+
+/// Bibliography entries a page can cite, keyed by citation key.
+///
+/// Loaded from the file named by the `bibliography` frontmatter key, either
+/// `BibTeX` (`.bib`) or CSL-JSON (`.json`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bibliography {
+    entries: HashMap<String, Reference>,
+}
+
+/// A bibliography entry, reduced to the fields shown in the references list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Reference {
+    authors: Vec<String>,
+    title: String,
+    /// Journal, proceedings, or publisher.
+    container: Option<String>,
+    year: Option<String>,
+    /// Link target: the `url` field, or the DOI resolver URL.
+    url: Option<String>,
+}
+
+impl Bibliography {
+    /// Loads a `BibTeX` or CSL-JSON bibliography, picking the format by file
+    /// extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or has neither
+    /// a `.bib` nor a `.json` extension.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read bibliography {}", path.display()))?;
+        let parsed = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("bib") => Self::parse_bibtex(&raw),
+            Some("json") => Self::parse_csl_json(&raw),
+            _ => bail!(
+                "unsupported bibliography format {}; expected .bib or .json",
+                path.display()
+            ),
+        };
+        parsed.with_context(|| format!("failed to parse bibliography {}", path.display()))
+    }
+
+    fn parse_bibtex(src: &str) -> Result<Self> {
+        let bib = biblatex::Bibliography::parse(src).map_err(|err| anyhow!("{err}"))?;
+        let field = |entry: &biblatex::Entry, keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| entry.get(key))
+                .map(ChunksExt::format_verbatim)
+        };
+
+        let entries = bib
+            .iter()
+            .map(|entry| {
+                let authors = entry
+                    .author()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|person| {
+                        [person.given_name, person.prefix, person.name, person.suffix]
+                            .into_iter()
+                            .filter(|part| !part.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect();
+                let year = field(entry, &["year", "date"])
+                    .map(|date| date.split('-').next().unwrap_or_default().to_owned());
+                let url = field(entry, &["url"])
+                    .or_else(|| field(entry, &["doi"]).map(|doi| format!("https://doi.org/{doi}")));
+                let reference = Reference {
+                    authors,
+                    title: field(entry, &["title"]).unwrap_or_default(),
+                    container: field(
+                        entry,
+                        &["journal", "journaltitle", "booktitle", "publisher"],
+                    ),
+                    year,
+                    url,
+                };
+                (entry.key.clone(), reference)
+            })
+            .collect();
+        Ok(Self { entries })
+    }
+
+    fn parse_csl_json(src: &str) -> Result<Self> {
+        let items: Vec<CslItem> = serde_json::from_str(src)?;
+        let entries = items
+            .into_iter()
+            .map(|item| {
+                let authors = item
+                    .author
+                    .into_iter()
+                    .map(|name| {
+                        name.literal.unwrap_or_else(|| {
+                            [name.given, name.family]
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        })
+                    })
+                    .collect();
+                let year = item
+                    .issued
+                    .and_then(|issued| issued.date_parts.into_iter().next())
+                    .and_then(|parts| parts.into_iter().next())
+                    .map(|year| match year {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    });
+                let url = item
+                    .url
+                    .or_else(|| item.doi.map(|doi| format!("https://doi.org/{doi}")));
+                let reference = Reference {
+                    authors,
+                    title: item.title.unwrap_or_default(),
+                    container: item.container_title.or(item.publisher),
+                    year,
+                    url,
+                };
+                (item.id, reference)
+            })
+            .collect();
+        Ok(Self { entries })
+    }
+}
+
+/// The subset of a CSL-JSON item used for references.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CslItem {
+    id: String,
+    #[serde(default)]
+    author: Vec<CslName>,
+    title: Option<String>,
+    container_title: Option<String>,
+    publisher: Option<String>,
+    issued: Option<CslDate>,
+    #[serde(rename = "URL")]
+    url: Option<String>,
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CslName {
+    family: Option<String>,
+    given: Option<String>,
+    literal: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CslDate {
+    #[serde(rename = "date-parts", default)]
+    date_parts: Vec<Vec<serde_json::Value>>,
+}
+
+impl Reference {
+    /// Renders the entry as `Authors (Year). Title. <em>Container</em>. URL`,
+    /// leaving out missing parts.
+    fn to_html(&self) -> String {
+        let mut parts = Vec::new();
+        match (join_authors(&self.authors), &self.year) {
+            (Some(authors), Some(year)) => {
+                parts.push(format!("{} ({})", escape(&authors), escape(year)));
+            }
+            (Some(authors), None) => parts.push(escape(&authors)),
+            (None, Some(year)) => parts.push(format!("({})", escape(year))),
+            (None, None) => {}
+        }
+        if !self.title.is_empty() {
+            parts.push(escape(&self.title));
+        }
+        if let Some(container) = &self.container {
+            parts.push(format!("<em>{}</em>", escape(container)));
+        }
+        let mut html = parts
+            .iter()
+            .map(|part| part.trim_end_matches('.'))
+            .collect::<Vec<_>>()
+            .join(". ");
+        if !html.is_empty() {
+            html.push('.');
+        }
+        if let Some(url) = &self.url {
+            let url = escape(url);
+            _ = write!(html, r#" <a href="{url}">{url}</a>"#);
+        }
+        html.trim_start().to_owned()
+    }
+}
+
+/// Joins author names as "A", "A and B", or "A, B, and C".
+fn join_authors(authors: &[String]) -> Option<String> {
+    match authors {
+        [] => None,
+        [only] => Some(only.clone()),
+        [first, second] => Some(format!("{first} and {second}")),
+        [init @ .., last] => Some(format!("{}, and {last}", init.join(", "))),
+    }
+}
+
+/// Parses a `[@key]`, `[@a; @b]`, or `[@key, p. 12]` link label into
+/// `(key, locator)` pairs. Returns `None` for labels that are not citations.
+pub(crate) fn parse_citation(label: &str) -> Option<Vec<(&str, &str)>> {
+    label
+        .split(';')
+        .map(|item| {
+            let item = item.trim().strip_prefix('@')?;
+            let (key, locator) = item.split_once(',').unwrap_or((item, ""));
+            let key = key.trim();
+            let valid = !key.is_empty() && !key.contains(char::is_whitespace);
+            valid.then_some((key, locator.trim()))
+        })
+        .collect()
+}
+
+/// Numbers citations across the assembled page HTML and appends the
+/// references section under `heading`.
+///
+/// Entries are numbered in order of first citation and only cited entries
+/// are listed.
+///
+/// # Errors
+///
+/// Returns an error if a citation key is not in `bibliography`.
+pub(crate) fn render_citations(
+    html: &str,
+    bibliography: &Bibliography,
+    heading: &str,
+) -> Result<String> {
+    let mut cited: Vec<&str> = Vec::new();
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(pos) = rest.find(CITATION_OPEN) {
+        let label_start = pos + CITATION_OPEN.len();
+        let Some(label_len) = rest[label_start..].find('"') else {
+            break;
+        };
+        let Some(close) = rest[label_start..].find("</a>") else {
+            break;
+        };
+        let label = decode_href(&rest[label_start..label_start + label_len]);
+        let items = parse_citation(&label).unwrap_or_default();
+
+        let mut links = Vec::with_capacity(items.len());
+        for (key, locator) in &items {
+            let Some((key, _)) = bibliography.entries.get_key_value(*key) else {
+                bail!("unknown citation key `{key}`");
+            };
+            let number = if let Some(index) = cited.iter().position(|k| k == key) {
+                index + 1
+            } else {
+                cited.push(key);
+                cited.len()
+            };
+            let mut link = format!(r##"<a href="#ref-{}">{number}</a>"##, escape(key));
+            if !locator.is_empty() {
+                _ = write!(link, ", {}", escape(locator));
+            }
+            links.push(link);
+        }
+        let separator = if items.iter().any(|(_, locator)| !locator.is_empty()) {
+            "; "
+        } else {
+            ", "
+        };
+
+        output.push_str(&rest[..pos]);
+        _ = write!(
+            output,
+            r#"<span class="citation">[{}]</span>"#,
+            links.join(separator)
+        );
+        rest = &rest[label_start + close + "</a>".len()..];
+    }
+    output.push_str(rest);
+
+    if !cited.is_empty() {
+        _ = write!(
+            output,
+            "<section class=\"references\">\n<h2>{}</h2>\n<ol>\n",
+            escape(heading),
+        );
+        for key in cited {
+            _ = writeln!(
+                output,
+                r#"<li id="ref-{}">{}</li>"#,
+                escape(key),
+                bibliography.entries[key].to_html(),
+            );
+        }
+        output.push_str("</ol>\n</section>\n");
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn bibliography() -> Bibliography {
+        Bibliography::parse_bibtex(indoc! {r"
+            @article{castellan84,
+              author = {Mira Castellan},
+              title = {Annotated Programs},
+              journal = {Journal of Fictional Computing},
+              year = {1984},
+              doi = {10.5555/fictional.1984},
+            }
+            @book{ferrow96,
+              author = {Ferrow, Oren and Pellucid, Ida},
+              title = {Patterns of Imaginary Machines},
+              publisher = {Example Press},
+              date = {1996-07-25},
+            }
+        "})
+        .unwrap()
+    }
+
+    // ── Bibliography::load ──
+
+    #[test]
+    fn load_bibtex() {
+        let bib = bibliography();
+        assert_eq!(
+            bib.entries["castellan84"],
+            Reference {
+                authors: vec!["Mira Castellan".into()],
+                title: "Annotated Programs".into(),
+                container: Some("Journal of Fictional Computing".into()),
+                year: Some("1984".into()),
+                url: Some("https://doi.org/10.5555/fictional.1984".into()),
+            }
+        );
+        assert_eq!(
+            bib.entries["ferrow96"].authors,
+            ["Oren Ferrow", "Ida Pellucid"]
+        );
+        assert_eq!(bib.entries["ferrow96"].year.as_deref(), Some("1996"));
+    }
+
+    #[test]
+    fn load_csl_json() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(
+            dir.path(),
+            "refs.json",
+            indoc! {r#"
+                [{
+                  "id": "castellan84",
+                  "type": "article-journal",
+                  "author": [{ "family": "Castellan", "given": "Mira" }],
+                  "title": "Annotated Programs",
+                  "container-title": "Journal of Fictional Computing",
+                  "issued": { "date-parts": [[1984, 5]] },
+                  "URL": "https://example.com/lp"
+                }]
+            "#},
+        );
+        let bib = Bibliography::load(&dir.path().join("refs.json")).unwrap();
+        assert_eq!(
+            bib.entries["castellan84"],
+            Reference {
+                authors: vec!["Mira Castellan".into()],
+                title: "Annotated Programs".into(),
+                container: Some("Journal of Fictional Computing".into()),
+                year: Some("1984".into()),
+                url: Some("https://example.com/lp".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn load_unsupported_extension_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "refs.yaml", "");
+        let err = Bibliography::load(&dir.path().join("refs.yaml")).unwrap_err();
+        assert!(
+            err.to_string().contains("unsupported bibliography format"),
+            "got: {err}"
+        );
+    }
+
+    // ── Reference::to_html ──
+
+    #[test]
+    fn reference_to_html_formats_present_fields() {
+        let bib = bibliography();
+        assert_eq!(
+            bib.entries["ferrow96"].to_html(),
+            "Oren Ferrow and Ida Pellucid (1996). Patterns of Imaginary Machines. <em>Example Press</em>."
+        );
+        let bare = Reference {
+            title: "Untitled".into(),
+            url: Some("https://example.com/?a=1&b=2".into()),
+            ..Reference::default()
+        };
+        assert_eq!(
+            bare.to_html(),
+            r#"Untitled. <a href="https://example.com/?a=1&amp;b=2">https://example.com/?a=1&amp;b=2</a>"#
+        );
+    }
+
+    // ── join_authors ──
+
+    #[test]
+    fn join_authors_uses_serial_comma() {
+        let names = |n: &[&str]| n.iter().map(|s| (*s).to_owned()).collect::<Vec<_>>();
+        assert_eq!(join_authors(&[]), None);
+        assert_eq!(join_authors(&names(&["A"])).as_deref(), Some("A"));
+        assert_eq!(
+            join_authors(&names(&["A", "B"])).as_deref(),
+            Some("A and B")
+        );
+        assert_eq!(
+            join_authors(&names(&["A", "B", "C"])).as_deref(),
+            Some("A, B, and C")
+        );
+    }
+
+    // ── parse_citation ──
+
+    #[test]
+    fn parse_citation_accepts_keys_and_locators() {
+        assert_eq!(
+            parse_citation("@castellan84"),
+            Some(vec![("castellan84", "")])
+        );
+        assert_eq!(
+            parse_citation("@castellan84, p. 12; @ferrow96"),
+            Some(vec![("castellan84", "p. 12"), ("ferrow96", "")])
+        );
+    }
+
+    #[test]
+    fn parse_citation_rejects_other_labels() {
+        assert_eq!(parse_citation("label"), None);
+        assert_eq!(parse_citation("@a; b"), None);
+        assert_eq!(parse_citation("@"), None);
+        assert_eq!(parse_citation("@two words"), None);
+    }
+
+    // ── render_citations ──
+
+    #[test]
+    fn render_citations_numbers_by_first_citation() {
+        let html = indoc! {r#"
+            <p>See <a href="cite:@ferrow96">@ferrow96</a> and <a href="cite:@castellan84;%20@ferrow96">@castellan84; @ferrow96</a>.</p>
+            <p>Again <a href="cite:@castellan84,%20p.%2012">@castellan84, p. 12</a>.</p>
+        "#};
+        assert_eq!(
+            render_citations(html, &bibliography(), "References").unwrap(),
+            indoc! {r##"
+                <p>See <span class="citation">[<a href="#ref-ferrow96">1</a>]</span> and <span class="citation">[<a href="#ref-castellan84">2</a>, <a href="#ref-ferrow96">1</a>]</span>.</p>
+                <p>Again <span class="citation">[<a href="#ref-castellan84">2</a>, p. 12]</span>.</p>
+                <section class="references">
+                <h2>References</h2>
+                <ol>
+                <li id="ref-ferrow96">Oren Ferrow and Ida Pellucid (1996). Patterns of Imaginary Machines. <em>Example Press</em>.</li>
+                <li id="ref-castellan84">Mira Castellan (1984). Annotated Programs. <em>Journal of Fictional Computing</em>. <a href="https://doi.org/10.5555/fictional.1984">https://doi.org/10.5555/fictional.1984</a></li>
+                </ol>
+                </section>
+            "##},
+        );
+    }
+
+    #[test]
+    fn render_citations_without_citations_is_unchanged() {
+        let html = "<p>No citations.</p>\n";
+        assert_eq!(
+            render_citations(html, &bibliography(), "References").unwrap(),
+            html
+        );
+    }
+
+    #[test]
+    fn render_citations_unknown_key_returns_error() {
+        let err = render_citations(
+            r#"<a href="cite:@nope">@nope</a>"#,
+            &bibliography(),
+            "References",
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "unknown citation key `nope`");
+    }
+}
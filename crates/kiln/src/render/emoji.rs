@@ -8,7 +8,7 @@ use crate::markdown::{for_each_non_code_line, scan_code_span};
 ///
 /// Character set mirrors GitHub's shortcode names: lowercase ASCII, digits,
 /// underscores, hyphens, and `+`.
-static EMOJI_RE: LazyLock<Regex> =
+pub(crate) static EMOJI_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r":([a-z0-9_+\-]+):").expect("emoji regex should compile"));
 
 /// Replaces `:shortcode:` emoji shortcodes with Unicode emoji characters.
@@ -0,0 +1,190 @@
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use crate::html::escape;
+
+/// Matches an `<a ...>` opening tag.
+static ANCHOR_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<a\s[^>]*>").expect("anchor tag regex should compile"));
+
+/// Matches a double-quoted attribute inside a tag.
+static ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\s([A-Za-z][\w-]*)="([^"]*)""#).expect("attribute regex should compile")
+});
+
+/// `rel` tokens added to external links opened in a new tab.
+const EXTERNAL_REL: [&str; 2] = ["noopener", "noreferrer"];
+
+/// Decorates links that leave the site, as configured by `[external_links]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalLinks {
+    /// Host (with port, if any) of the site's `base_url`; links to any other
+    /// host are external.
+    site_host: String,
+    /// Class added to external links, e.g. for a trailing icon.
+    class: Option<String>,
+}
+
+impl ExternalLinks {
+    #[must_use]
+    pub fn new(base_url: &str, class: Option<String>) -> Self {
+        Self {
+            site_host: url_host(base_url).unwrap_or_default().to_ascii_lowercase(),
+            class: class.filter(|class| !class.is_empty()),
+        }
+    }
+
+    /// Adds `target="_blank"`, `rel="noopener noreferrer"`, and the
+    /// configured class to every `<a>` whose `href` points to another host.
+    ///
+    /// Attributes already on the tag are kept: an existing `target` wins,
+    /// and missing `rel` tokens and the class are appended to existing
+    /// values.
+    #[must_use]
+    pub(crate) fn decorate(&self, html: &str) -> String {
+        ANCHOR_TAG_RE
+            .replace_all(html, |caps: &Captures<'_>| self.decorate_tag(&caps[0]))
+            .into_owned()
+    }
+
+    fn decorate_tag(&self, tag: &str) -> String {
+        let attrs: Vec<_> = ATTR_RE.captures_iter(tag).collect();
+        let attr = |name: &str| attrs.iter().find(|caps| caps[1].eq_ignore_ascii_case(name));
+        if !attr("href").is_some_and(|href| self.is_external(&href[2])) {
+            return tag.to_owned();
+        }
+
+        // (offset, text) insertions into `tag`, applied back to front.
+        let close = tag.len() - 1;
+        let mut edits = Vec::new();
+        if attr("target").is_none() {
+            edits.push((close, r#" target="_blank""#.to_owned()));
+        }
+        match attr("rel") {
+            Some(rel) => {
+                let value = rel.get(2).expect("attribute regex has a value group");
+                let missing = missing_tokens(value.as_str(), EXTERNAL_REL);
+                if !missing.is_empty() {
+                    edits.push((value.end(), format!(" {missing}")));
+                }
+            }
+            None => edits.push((close, format!(r#" rel="{}""#, EXTERNAL_REL.join(" ")))),
+        }
+        if let Some(class) = &self.class {
+            match attr("class") {
+                Some(existing) => {
+                    let value = existing.get(2).expect("attribute regex has a value group");
+                    if !value
+                        .as_str()
+                        .split_whitespace()
+                        .any(|token| token == class)
+                    {
+                        edits.push((value.end(), format!(" {}", escape(class))));
+                    }
+                }
+                None => edits.push((close, format!(r#" class="{}""#, escape(class)))),
+            }
+        }
+
+        let mut decorated = tag.to_owned();
+        edits.sort_by_key(|(offset, _)| *offset);
+        for (offset, text) in edits.into_iter().rev() {
+            decorated.insert_str(offset, &text);
+        }
+        decorated
+    }
+
+    /// Returns `true` for absolute `http(s)` and protocol-relative URLs to a
+    /// host other than the site's.
+    fn is_external(&self, href: &str) -> bool {
+        url_host(href).is_some_and(|host| !host.eq_ignore_ascii_case(&self.site_host))
+    }
+}
+
+/// Extracts the host (and port) of an `http`, `https`, or protocol-relative
+/// URL.
+pub(crate) fn url_host(url: &str) -> Option<&str> {
+    let lower = url.get(..8).unwrap_or(url).to_ascii_lowercase();
+    let rest = if lower.starts_with("https://") {
+        &url[8..]
+    } else if lower.starts_with("http://") {
+        &url[7..]
+    } else {
+        url.strip_prefix("//")?
+    };
+    let host_len = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let host = &rest[..host_len];
+    (!host.is_empty()).then_some(host)
+}
+
+/// Returns the `tokens` not already in the whitespace-separated `value`,
+/// joined by spaces.
+fn missing_tokens(value: &str, tokens: [&str; 2]) -> String {
+    tokens
+        .into_iter()
+        .filter(|token| {
+            !value
+                .split_whitespace()
+                .any(|existing| existing.eq_ignore_ascii_case(token))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn external_links(class: Option<&str>) -> ExternalLinks {
+        ExternalLinks::new("https://example.com/blog/", class.map(String::from))
+    }
+
+    // ── ExternalLinks::decorate ──
+
+    #[test]
+    fn decorate_external_link() {
+        let html = external_links(Some("external"))
+            .decorate(r#"<p><a href="https://docs.example.org/learn">Docs</a></p>"#);
+        assert_eq!(
+            html,
+            r#"<p><a href="https://docs.example.org/learn" target="_blank" rel="noopener noreferrer" class="external">Docs</a></p>"#
+        );
+    }
+
+    #[test]
+    fn decorate_leaves_internal_links() {
+        let links = external_links(Some("external"));
+        for tag in [
+            r#"<a href="https://example.com/posts/">"#,
+            r#"<a href="HTTPS://Example.com">"#,
+            r#"<a href="/posts/">"#,
+            r#"<a href="../other/">"#,
+            r##"<a href="#intro">"##,
+            r#"<a href="mailto:me@example.com">"#,
+            r#"<a id="top">"#,
+        ] {
+            assert_eq!(links.decorate(tag), tag, "tag: {tag}");
+        }
+    }
+
+    #[test]
+    fn decorate_merges_existing_attributes() {
+        let html = external_links(Some("external")).decorate(
+            r#"<a class="card" href="//cdn.example.net/x" target="_self" rel="me noopener">"#,
+        );
+        assert_eq!(
+            html,
+            r#"<a class="card external" href="//cdn.example.net/x" target="_self" rel="me noopener noreferrer">"#
+        );
+    }
+
+    #[test]
+    fn decorate_without_class() {
+        let html = external_links(None).decorate(r#"<a href="http://example.com:8080/">"#);
+        assert_eq!(
+            html,
+            r#"<a href="http://example.com:8080/" target="_blank" rel="noopener noreferrer">"#
+        );
+    }
+}
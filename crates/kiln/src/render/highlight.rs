@@ -1,19 +1,207 @@
-use syntect::html::{ClassStyle, ClassedHTMLGenerator};
-use syntect::parsing::{SyntaxReference, SyntaxSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use syntect::easy::ScopeRegionIterator;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator, css_for_theme_with_class_style};
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
+use two_face::theme::EmbeddedLazyThemeSet;
 
 use tracing::{debug, warn};
+use walkdir::WalkDir;
 
+use crate::config::HighlightClasses;
+use crate::directive::parse_pandoc_attrs;
 use crate::html::{escape, writeln_indented};
+use crate::template::cache::{HighlightCache, highlight_key};
+
+/// Presentation options parsed from a fenced code block's info string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodeBlockInfo {
+    /// Language token (the first word), empty when absent.
+    pub lang: String,
+    /// Filename or caption shown in the header bar (`title="main.rs"`).
+    pub title: Option<String>,
+    /// Whether to show the line-number column (`linenos=false`). `None`
+    /// defers to the site-wide [`CodeOptions::line_numbers`].
+    pub linenos: Option<bool>,
+    /// Number of the first line (`linenostart=10`), defaulting to 1.
+    pub linenostart: Option<usize>,
+}
+
+impl CodeBlockInfo {
+    /// Parses an info string such as `rust title="main.rs" no_run`.
+    ///
+    /// The first word is the language unless it is a `key=value` pair. The
+    /// remaining words are read as `key=value` attributes; unknown keys,
+    /// unparsable values, and bare words (e.g., `no_run`) are ignored.
+    #[must_use]
+    pub fn parse(info: &str) -> Self {
+        let info = info.trim();
+        let lang_end = info.find(char::is_whitespace).unwrap_or(info.len());
+        let (lang, rest) = if info[..lang_end].contains('=') {
+            ("", info)
+        } else {
+            (&info[..lang_end], &info[lang_end..])
+        };
+
+        let mut result = Self {
+            lang: lang.to_owned(),
+            ..Self::default()
+        };
+        for (key, value) in parse_pandoc_attrs(rest).kvs {
+            match key {
+                "title" if !value.is_empty() => result.title = Some(value.into_owned()),
+                "linenos" => result.linenos = value.parse().ok().or(result.linenos),
+                "linenostart" => result.linenostart = value.parse().ok().or(result.linenostart),
+                _ => {}
+            }
+        }
+        result
+    }
+}
+
+/// Site-wide code block settings, taken from `[params]`, `[markup]`, and
+/// `[cache]`.
+#[derive(Debug, Clone, Copy)]
+pub struct CodeOptions<'a> {
+    /// Line count past which themes may collapse the block, emitted as
+    /// `data-max-lines`.
+    pub max_lines: Option<usize>,
+    /// Whether blocks without a `linenos` option show line numbers.
+    pub line_numbers: bool,
+    /// Class names of highlighted tokens.
+    pub classes: HighlightClasses,
+    /// Prefix of [`HighlightClasses::Chroma`] class names.
+    pub class_prefix: &'a str,
+    /// Highlighted code kept from earlier builds, when enabled.
+    pub cache: Option<&'a HighlightCache>,
+}
+
+impl Default for CodeOptions<'_> {
+    fn default() -> Self {
+        Self {
+            max_lines: None,
+            line_numbers: true,
+            classes: HighlightClasses::Syntect,
+            class_prefix: "",
+            cache: None,
+        }
+    }
+}
+
+/// The bundled `two-face` syntaxes, deserialized once per process.
+static BUNDLED_SYNTAXES: LazyLock<Arc<SyntaxSet>> =
+    LazyLock::new(|| Arc::new(two_face::syntax::extra_newlines()));
+
+/// The syntax files the last syntax set with custom syntaxes was built
+/// from, with their modification times, and that syntax set.
+type SyntaxCache = Option<(Vec<(PathBuf, SystemTime)>, Arc<SyntaxSet>)>;
+
+/// The last syntax set built with custom syntaxes, reused by watch-mode
+/// rebuilds until a syntax file is added, removed, or modified.
+static CUSTOM_SYNTAXES: Mutex<SyntaxCache> = Mutex::new(None);
+
+/// Builds the syntax set used for highlighting: the bundled `two-face`
+/// syntaxes extended with the `.sublime-syntax` files under each of `dirs`.
+///
+/// Missing directories are skipped. Later directories take precedence, so a
+/// site's `syntaxes/` can override a theme's, and both override the bundled
+/// definitions for the same language token.
+///
+/// Syntax sets are cached for the process: the bundled one is shared by
+/// every build without custom syntaxes, and the last one with custom
+/// syntaxes is reused while its syntax files are unchanged.
+///
+/// # Errors
+///
+/// Returns an error if a syntax file cannot be read or parsed.
+pub fn load_syntax_set(dirs: &[&Path]) -> Result<Arc<SyntaxSet>> {
+    let dirs: Vec<_> = dirs.iter().filter(|dir| dir.is_dir()).collect();
+    if dirs.is_empty() {
+        return Ok(Arc::clone(&BUNDLED_SYNTAXES));
+    }
+
+    let files = syntax_files(&dirs);
+    let mut cache = CUSTOM_SYNTAXES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some((cached_files, syntax_set)) = cache.as_ref()
+        && *cached_files == files
+    {
+        return Ok(Arc::clone(syntax_set));
+    }
+
+    let mut builder = SyntaxSet::clone(&BUNDLED_SYNTAXES).into_builder();
+    for dir in dirs {
+        builder
+            .add_from_folder(dir, true)
+            .with_context(|| format!("failed to load syntaxes from {}", dir.display()))?;
+    }
+    let syntax_set = Arc::new(builder.build());
+    *cache = Some((files, Arc::clone(&syntax_set)));
+    Ok(syntax_set)
+}
+
+/// Lists the `.sublime-syntax` files under `dirs`, in load order, with
+/// their modification times.
+fn syntax_files(dirs: &[&&Path]) -> Vec<(PathBuf, SystemTime)> {
+    dirs.iter()
+        .flat_map(|dir| WalkDir::new(dir).sort_by_file_name())
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry
+                    .path()
+                    .extension()
+                    .is_some_and(|ext| ext == "sublime-syntax")
+        })
+        .map(|entry| {
+            let modified = entry
+                .metadata()
+                .ok()
+                .and_then(|meta| meta.modified().ok())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (entry.into_path(), modified)
+        })
+        .collect()
+}
+
+/// Generates the stylesheet coloring [`highlight_code`] output with the
+/// bundled syntect theme `theme_name` (case-insensitive, e.g., `"Nord"`).
+///
+/// # Errors
+///
+/// Returns an error if no bundled theme has that name.
+pub fn highlight_css(theme_name: &str) -> Result<String> {
+    let names = EmbeddedLazyThemeSet::theme_names();
+    let Some(name) = names
+        .iter()
+        .find(|name| name.as_name().eq_ignore_ascii_case(theme_name))
+    else {
+        let available: Vec<_> = names.iter().map(|name| name.as_name()).collect();
+        bail!(
+            "unknown highlight theme `{theme_name}`; available: {}",
+            available.join(", ")
+        );
+    };
+    let theme = two_face::theme::extra().get(*name).clone();
+    css_for_theme_with_class_style(&theme, ClassStyle::Spaced)
+        .with_context(|| format!("failed to generate CSS for theme `{theme_name}`"))
+}
 
 /// Highlights a code block with syntax highlighting, line numbers, and a
-/// header with a language label and copy button.
+/// header with an optional title, a language label, and a copy button.
 ///
 /// Output structure:
 ///
 /// ```html
 /// <div class="code-block" data-lang="rust">
 ///   <div class="code-header">
+///     <span class="code-title">main.rs</span>
 ///     <span class="code-lang">Rust</span>
 ///     <button class="copy-btn">Copy</button>
 ///   </div>
@@ -32,26 +220,39 @@ use crate::html::{escape, writeln_indented};
 ///
 /// Language labels are canonicalized: derived from syntect's syntax name,
 /// lowercased. Empty and unrecognized tags normalize to `"plaintext"`.
-/// The header's display label uses the original syntax name casing.
+/// The header's display label uses the original syntax name casing. The
+/// `code-title` span is only emitted when the info string sets a `title`.
+/// The `line-numbers` cell is omitted when `info.linenos` is `Some(false)`,
+/// and counts from `info.linenostart` when set.
+///
+/// With [`HighlightClasses::Chroma`], tokens get Chroma class names instead
+/// of syntect scopes, and the `<pre>` gets the `chroma` class that Hugo
+/// stylesheets nest their rules under.
 #[must_use]
 pub fn highlight_code(
     syntax_set: &SyntaxSet,
-    lang: &str,
+    info: &CodeBlockInfo,
     code: &str,
-    max_lines: Option<usize>,
+    options: &CodeOptions<'_>,
 ) -> String {
+    let lang = info.lang.as_str();
     let (syntax, effective_lang, display_label) = find_syntax(syntax_set, lang);
 
-    let mut generator =
-        ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set, ClassStyle::Spaced);
-
-    for line in LinesWithEndings::from(code) {
-        if let Err(e) = generator.parse_html_for_line_which_includes_newline(line) {
-            warn!(lang, error = %e, "syntax highlighting failed for line, falling back to plain text");
+    let highlight = || match options.classes {
+        HighlightClasses::Syntect => highlight_syntect(syntax_set, syntax, lang, code),
+        HighlightClasses::Chroma => {
+            highlight_chroma(syntax_set, syntax, lang, code, options.class_prefix)
         }
-    }
-
-    let highlighted = generator.finalize();
+    };
+    let highlighted = match options.cache {
+        Some(cache) => {
+            let style = format!("{:?}:{}", options.classes, options.class_prefix);
+            cache.get_or_insert_with(highlight_key(&syntax.name, &style, code), highlight)
+        }
+        None => highlight(),
+    };
+    let pre_class = (options.classes == HighlightClasses::Chroma)
+        .then(|| format!(r#" class="{}chroma""#, escape(options.class_prefix)));
     let line_count = code.lines().count().max(1);
 
     let mut html =
@@ -66,6 +267,14 @@ pub fn highlight_code(
         r#"<div class="code-block" data-lang="{escaped_lang}">"#
     );
     writeln_indented!(&mut html, 1, r#"<div class="code-header">"#);
+    if let Some(title) = &info.title {
+        writeln_indented!(
+            &mut html,
+            2,
+            r#"<span class="code-title">{}</span>"#,
+            escape(title)
+        );
+    }
     writeln_indented!(
         &mut html,
         2,
@@ -76,7 +285,8 @@ pub fn highlight_code(
     writeln_indented!(&mut html, 1, "</div>");
 
     // Code body (with optional max-lines for JS-driven collapse).
-    let max_lines_attr = max_lines
+    let max_lines_attr = options
+        .max_lines
         .map(|n| format!(r#" data-max-lines="{n}""#))
         .unwrap_or_default();
     writeln_indented!(&mut html, 1, r#"<div class="code-body"{max_lines_attr}>"#);
@@ -87,21 +297,25 @@ pub fn highlight_code(
     writeln_indented!(&mut html, 4, "<tr>");
 
     // Line numbers column.
-    let line_numbers: String = (1..=line_count)
-        .map(|i| i.to_string())
-        .collect::<Vec<_>>()
-        .join("\n");
-    writeln_indented!(
-        &mut html,
-        5,
-        r#"<td class="line-numbers"><pre>{line_numbers}</pre></td>"#
-    );
+    if info.linenos.unwrap_or(true) {
+        let start = info.linenostart.unwrap_or(1);
+        let line_numbers: String = (start..start + line_count)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        writeln_indented!(
+            &mut html,
+            5,
+            r#"<td class="line-numbers"><pre>{line_numbers}</pre></td>"#
+        );
+    }
 
     // Code column.
     writeln_indented!(
         &mut html,
         5,
-        r#"<td class="code"><pre><code class="language-{escaped_lang}" data-lang="{escaped_lang}">{highlighted}</code></pre></td>"#
+        r#"<td class="code"><pre{}><code class="language-{escaped_lang}" data-lang="{escaped_lang}">{highlighted}</code></pre></td>"#,
+        pre_class.unwrap_or_default()
     );
 
     writeln_indented!(&mut html, 4, "</tr>");
@@ -112,6 +326,164 @@ pub fn highlight_code(
     html
 }
 
+/// Highlights `code` with syntect scope classes (`<span class="keyword
+/// control rust">`).
+fn highlight_syntect(
+    syntax_set: &SyntaxSet,
+    syntax: &SyntaxReference,
+    lang: &str,
+    code: &str,
+) -> String {
+    let mut generator =
+        ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set, ClassStyle::Spaced);
+    for line in LinesWithEndings::from(code) {
+        if let Err(e) = generator.parse_html_for_line_which_includes_newline(line) {
+            warn!(lang, error = %e, "syntax highlighting failed for line, falling back to plain text");
+        }
+    }
+    generator.finalize()
+}
+
+/// Highlights `code` with flat Chroma token classes (`<span class="k">`),
+/// merging adjacent runs of the same class. Whitespace between tokens is
+/// left unwrapped, as Chroma does.
+fn highlight_chroma(
+    syntax_set: &SyntaxSet,
+    syntax: &SyntaxReference,
+    lang: &str,
+    code: &str,
+    prefix: &str,
+) -> String {
+    let mut html = String::with_capacity(code.len() * 2);
+    let mut run: (Option<&str>, String) = (None, String::new());
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    for line in LinesWithEndings::from(code) {
+        let ops = state.parse_line(line, syntax_set).unwrap_or_else(|e| {
+            warn!(lang, error = %e, "syntax highlighting failed for line, falling back to plain text");
+            Vec::new()
+        });
+        for (text, op) in ScopeRegionIterator::new(&ops, line) {
+            if let Err(e) = stack.apply(op) {
+                warn!(lang, error = %e, "syntax highlighting failed for line, falling back to plain text");
+            }
+            if text.is_empty() {
+                continue;
+            }
+            let class = if text.trim().is_empty() {
+                None
+            } else {
+                chroma_class(&stack)
+            };
+            if class != run.0 {
+                push_chroma_run(&mut html, prefix, run.0, &run.1);
+                run = (class, String::new());
+            }
+            run.1.push_str(text);
+        }
+    }
+    push_chroma_run(&mut html, prefix, run.0, &run.1);
+    html
+}
+
+/// Appends a run of same-class text, wrapped in a span unless unclassed.
+fn push_chroma_run(html: &mut String, prefix: &str, class: Option<&str>, text: &str) {
+    match class {
+        Some(class) if !text.is_empty() => {
+            _ = write!(
+                html,
+                r#"<span class="{}{class}">{}</span>"#,
+                escape(prefix),
+                escape(text)
+            );
+        }
+        _ => html.push_str(&escape(text)),
+    }
+}
+
+/// Chroma token classes by syntect scope prefix, most specific first.
+static CHROMA_CLASSES: LazyLock<Vec<(Scope, &str)>> = LazyLock::new(|| {
+    [
+        ("comment.block", "cm"),
+        ("comment.line", "c1"),
+        ("comment", "c"),
+        ("meta.preprocessor", "cp"),
+        ("string.regexp", "sr"),
+        ("string.quoted.single", "s1"),
+        ("string.quoted.double", "s2"),
+        ("string", "s"),
+        ("constant.character.escape", "se"),
+        ("constant.character", "sc"),
+        ("constant.numeric.float", "mf"),
+        ("constant.numeric.integer", "mi"),
+        ("constant.numeric", "m"),
+        ("constant.language", "kc"),
+        ("constant.other.symbol", "ss"),
+        ("constant", "no"),
+        ("keyword.operator.word", "ow"),
+        ("keyword.operator", "o"),
+        ("keyword.declaration", "kd"),
+        ("keyword.control.import", "kn"),
+        ("keyword", "k"),
+        ("storage.type", "kt"),
+        ("storage.modifier", "kd"),
+        ("storage", "k"),
+        ("entity.name.function", "nf"),
+        ("variable.function", "nf"),
+        ("entity.name.class", "nc"),
+        ("entity.name.struct", "nc"),
+        ("entity.name.enum", "nc"),
+        ("entity.name.type", "nc"),
+        ("entity.other.inherited-class", "nc"),
+        ("entity.name.tag", "nt"),
+        ("entity.other.attribute-name", "na"),
+        ("entity.name.namespace", "nn"),
+        ("entity.name.module", "nn"),
+        ("entity.name.label", "nl"),
+        ("entity.name.constant", "no"),
+        ("entity.name", "nx"),
+        ("support.type", "kt"),
+        ("support.class", "nc"),
+        ("support.constant", "no"),
+        ("support", "nb"),
+        ("variable.language", "bp"),
+        ("variable", "nv"),
+        ("markup.heading", "gh"),
+        ("markup.inserted", "gi"),
+        ("markup.deleted", "gd"),
+        ("markup.bold", "gs"),
+        ("markup.italic", "ge"),
+        ("punctuation", "p"),
+        ("invalid", "err"),
+    ]
+    .into_iter()
+    .map(|(scope, class)| (Scope::new(scope).expect("scope should parse"), class))
+    .collect()
+});
+
+/// Maps the scopes of a token, innermost first, to a Chroma class.
+///
+/// Delimiters (`punctuation.definition.*`, e.g., quotes and `//`) take the
+/// class of what they delimit, so a string keeps its quotes.
+fn chroma_class(stack: &ScopeStack) -> Option<&'static str> {
+    static DELIMITER: LazyLock<Scope> =
+        LazyLock::new(|| Scope::new("punctuation.definition").expect("scope should parse"));
+    let mut delimiter = false;
+    for scope in stack.as_slice().iter().rev() {
+        if DELIMITER.is_prefix_of(*scope) {
+            delimiter = true;
+            continue;
+        }
+        if let Some((_, class)) = CHROMA_CLASSES
+            .iter()
+            .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+        {
+            return Some(class);
+        }
+    }
+    delimiter.then_some("p")
+}
+
 /// Resolves a markdown language token to a syntect `SyntaxReference`, a
 /// canonical language label, and a human-readable display label.
 ///
@@ -127,26 +499,14 @@ pub fn highlight_code(
 /// the lowercased token. Known plain text names ("text", "plaintext",
 /// "plain") display as "Plain Text".
 fn find_syntax<'a>(syntax_set: &'a SyntaxSet, lang: &str) -> (&'a SyntaxReference, String, String) {
-    if !lang.is_empty() {
-        let syntax = syntax_set
-            .find_syntax_by_extension(lang)
-            .or_else(|| syntax_set.find_syntax_by_name(lang))
-            .or_else(|| {
-                syntax_set
-                    .syntaxes()
-                    .iter()
-                    .find(|s| s.name.eq_ignore_ascii_case(lang))
-            });
-
-        if let Some(s) = syntax {
-            return (s, canonical_lang(&s.name), s.name.clone());
-        }
+    if let Some(s) = lookup_syntax(syntax_set, lang) {
+        return (s, canonical_lang(&s.name), s.name.clone());
     }
 
     // No syntax found or empty tag — fall back to plain text.
     let lower = lang.to_ascii_lowercase();
     let display = match lower.as_str() {
-        "" | "text" | "plaintext" | "plain" => "Plain Text".into(),
+        _ if is_plain_text(&lower) => "Plain Text".into(),
         _ => {
             debug!(lang, "unrecognized language, falling back to plain text");
             capitalize_first(&lower)
@@ -160,6 +520,34 @@ fn find_syntax<'a>(syntax_set: &'a SyntaxSet, lang: &str) -> (&'a SyntaxReferenc
     )
 }
 
+/// Returns whether `lang` names a syntax in `syntax_set`, or plain text.
+/// Empty tags count as plain text.
+#[must_use]
+pub fn is_known_language(syntax_set: &SyntaxSet, lang: &str) -> bool {
+    is_plain_text(&lang.to_ascii_lowercase()) || lookup_syntax(syntax_set, lang).is_some()
+}
+
+/// Looks up `lang` by file extension, exact name, then case-insensitive name.
+fn lookup_syntax<'a>(syntax_set: &'a SyntaxSet, lang: &str) -> Option<&'a SyntaxReference> {
+    if lang.is_empty() {
+        return None;
+    }
+    syntax_set
+        .find_syntax_by_extension(lang)
+        .or_else(|| syntax_set.find_syntax_by_name(lang))
+        .or_else(|| {
+            syntax_set
+                .syntaxes()
+                .iter()
+                .find(|s| s.name.eq_ignore_ascii_case(lang))
+        })
+}
+
+/// Returns whether a lowercased language tag names plain text.
+fn is_plain_text(lower: &str) -> bool {
+    matches!(lower, "" | "text" | "plaintext" | "plain")
+}
+
 /// Derives a canonical HTML-safe language label from a syntect syntax name.
 ///
 /// Lowercases the name and replaces spaces with hyphens. The "Plain Text"
@@ -192,11 +580,131 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::test_utils::write_test_file;
 
     static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(two_face::syntax::extra_newlines);
 
+    fn info(lang: &str) -> CodeBlockInfo {
+        CodeBlockInfo {
+            lang: lang.to_owned(),
+            ..CodeBlockInfo::default()
+        }
+    }
+
     fn highlight(lang: &str, code: &str) -> String {
-        highlight_code(&SYNTAX_SET, lang, code, None)
+        highlight_code(&SYNTAX_SET, &info(lang), code, &CodeOptions::default())
+    }
+
+    // ── load_syntax_set ──
+
+    #[test]
+    fn load_syntax_set_adds_custom_syntaxes() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(
+            dir.path(),
+            "kiln-conf.sublime-syntax",
+            indoc! {r"
+                %YAML 1.2
+                ---
+                name: KilnConf
+                file_extensions: [kconf]
+                scope: source.kconf
+                contexts:
+                  main:
+                    - match: '#.*$'
+                      scope: comment.line.kconf
+            "},
+        );
+
+        let syntax_set = load_syntax_set(&[dir.path(), &dir.path().join("missing")]).unwrap();
+        let html = highlight_code(
+            &syntax_set,
+            &info("kconf"),
+            "# note\n",
+            &CodeOptions::default(),
+        );
+        assert!(html.contains(r#"data-lang="kilnconf""#), "html:\n{html}");
+        assert!(
+            html.contains(r#"<span class="comment line kconf">"#),
+            "html:\n{html}"
+        );
+        assert!(syntax_set.find_syntax_by_token("rust").is_some());
+    }
+
+    #[test]
+    fn load_syntax_set_reuses_bundled_syntaxes() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = load_syntax_set(&[]).unwrap();
+        let second = load_syntax_set(&[&dir.path().join("missing")]).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn load_syntax_set_invalid_file_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "broken.sublime-syntax", "name: [\n");
+        let err = load_syntax_set(&[dir.path()]).unwrap_err();
+        assert!(
+            err.to_string().contains("failed to load syntaxes"),
+            "got: {err}"
+        );
+    }
+
+    // ── highlight_css ──
+
+    #[test]
+    fn highlight_css_matches_theme_name_case_insensitively() {
+        let css = highlight_css("nord").unwrap();
+        assert!(css.contains(".code {"), "css:\n{css}");
+        assert!(css.contains(".comment"), "css:\n{css}");
+    }
+
+    #[test]
+    fn highlight_css_unknown_theme_lists_available() {
+        let err = highlight_css("nope").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("unknown highlight theme `nope`; available: "),
+            "got: {err}"
+        );
+        assert!(err.to_string().contains("Nord"), "got: {err}");
+    }
+
+    // ── CodeBlockInfo::parse ──
+
+    #[test]
+    fn code_block_info_parse_lang_and_title() {
+        assert_eq!(
+            CodeBlockInfo::parse(r#"rust title="src/main.rs" no_run"#),
+            CodeBlockInfo {
+                lang: "rust".into(),
+                title: Some("src/main.rs".into()),
+                ..CodeBlockInfo::default()
+            }
+        );
+    }
+
+    #[test]
+    fn code_block_info_parse_line_number_options() {
+        let info = CodeBlockInfo::parse("py linenos=false linenostart=10");
+        assert_eq!(info.linenos, Some(false));
+        assert_eq!(info.linenostart, Some(10));
+
+        let info = CodeBlockInfo::parse("py linenos=maybe linenostart=-1");
+        assert_eq!(info.linenos, None);
+        assert_eq!(info.linenostart, None);
+    }
+
+    #[test]
+    fn code_block_info_parse_title_without_lang() {
+        let info = CodeBlockInfo::parse("title=notes.txt");
+        assert_eq!(info.lang, "");
+        assert_eq!(info.title.as_deref(), Some("notes.txt"));
+    }
+
+    #[test]
+    fn code_block_info_parse_lang_only() {
+        assert_eq!(CodeBlockInfo::parse("  py  "), info("py"));
     }
 
     // ── highlight_code (structure) ──
@@ -234,9 +742,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn highlight_code_title() {
+        let info = CodeBlockInfo::parse(r#"rs title="<main>.rs""#);
+        let html = highlight_code(
+            &SYNTAX_SET,
+            &info,
+            "fn main() {}\n",
+            &CodeOptions::default(),
+        );
+        assert!(
+            html.contains(concat!(
+                "    <span class=\"code-title\">&lt;main&gt;.rs</span>\n",
+                "    <span class=\"code-lang\">Rust</span>\n",
+            )),
+            "title should precede the language label, html:\n{html}"
+        );
+        assert!(
+            !highlight("rs", "fn main() {}\n").contains("code-title"),
+            "no title span without a title"
+        );
+    }
+
     #[test]
     fn highlight_code_max_lines() {
-        let html = highlight_code(&SYNTAX_SET, "rs", "fn main() {}\n", Some(40));
+        let html = highlight_code(
+            &SYNTAX_SET,
+            &info("rs"),
+            "fn main() {}\n",
+            &CodeOptions {
+                max_lines: Some(40),
+                ..CodeOptions::default()
+            },
+        );
         assert!(
             html.contains(r#"<div class="code-body" data-max-lines="40">"#),
             "should have data-max-lines attribute, html:\n{html}"
@@ -272,6 +810,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn highlight_code_line_number_start() {
+        let info = CodeBlockInfo::parse("txt linenostart=9");
+        let html = highlight_code(&SYNTAX_SET, &info, "a\nb\n", &CodeOptions::default());
+        assert!(
+            html.contains(
+                r#"<td class="line-numbers"><pre>9
+10</pre></td>"#
+            ),
+            "should count from linenostart, html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn highlight_code_without_line_numbers() {
+        let info = CodeBlockInfo::parse("rs linenos=false");
+        let html = highlight_code(
+            &SYNTAX_SET,
+            &info,
+            "fn main() {}\n",
+            &CodeOptions::default(),
+        );
+        assert!(
+            !html.contains("line-numbers"),
+            "should omit the line-number column, html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<td class="code">"#),
+            "should keep the code column, html:\n{html}"
+        );
+    }
+
     // ── highlight_code (language resolution) ──
 
     #[test]
@@ -364,6 +934,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn highlight_code_chroma_classes() {
+        let options = CodeOptions {
+            classes: HighlightClasses::Chroma,
+            ..CodeOptions::default()
+        };
+        let code = "// note\nlet s = \"hi\";\n";
+        let html = highlight_code(&SYNTAX_SET, &info("rust"), code, &options);
+        assert!(
+            html.contains(r#"<pre class="chroma"><code"#),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<span class="c1">// note"#),
+            "comment delimiters keep the comment class, html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<span class="s2">&quot;hi&quot;</span>"#),
+            "quotes keep the string class, html:\n{html}"
+        );
+        assert!(!html.contains("source rust"), "html:\n{html}");
+    }
+
+    #[test]
+    fn highlight_code_chroma_class_prefix() {
+        let options = CodeOptions {
+            classes: HighlightClasses::Chroma,
+            class_prefix: "hl-",
+            ..CodeOptions::default()
+        };
+        let html = highlight_code(&SYNTAX_SET, &info("rust"), "// note\n", &options);
+        assert!(
+            html.contains(r#"<pre class="hl-chroma"><code"#),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"<span class="hl-c1">// note"#),
+            "html:\n{html}"
+        );
+    }
+
     // ── find_syntax ──
 
     #[test]
@@ -394,6 +1005,16 @@ mod tests {
         );
     }
 
+    // ── is_known_language ──
+
+    #[test]
+    fn is_known_language_accepts_syntaxes_and_plain_text() {
+        for lang in ["rust", "rs", "JavaScript", "", "Text", "plain"] {
+            assert!(is_known_language(&SYNTAX_SET, lang), "{lang}");
+        }
+        assert!(!is_known_language(&SYNTAX_SET, "rsut"));
+    }
+
     // ── capitalize_first ──
 
     #[test]
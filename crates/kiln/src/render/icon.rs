@@ -7,7 +7,7 @@ use crate::html::escape;
 use crate::markdown::{for_each_non_code_line, scan_code_span};
 
 /// Matches icon shortcodes, e.g., `:(fas fa-link):`.
-static ICON_RE: LazyLock<Regex> =
+pub(crate) static ICON_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r":\(([^)]+)\):").expect("icon regex should compile"));
 
 /// Replaces `:(class):` shortcodes with `<i>` tags.
@@ -1,6 +1,7 @@
 use std::fmt::Write;
 
 use super::image_attrs::ImageAttrs;
+use super::responsive::ResolvedImage;
 use crate::html::escape;
 
 /// Renders a standalone (block-level) image as a `<figure>` element.
@@ -8,8 +9,16 @@ use crate::html::escape;
 /// The image gets `loading="lazy" decoding="async"`. If `alt` is non-empty, a `<figcaption>` is
 /// included. The `title` attribute is omitted when empty. Optional `attrs`
 /// apply `id` CSS classes to `<figure>`, and `width` / `height` to `<img>`.
+/// A resolved `image` adds its intrinsic size and responsive variants (see
+/// [`push_img_tag`]).
 #[must_use]
-pub fn render_block_image(src: &str, alt: &str, title: &str, attrs: Option<&ImageAttrs>) -> String {
+pub fn render_block_image(
+    src: &str,
+    alt: &str,
+    title: &str,
+    attrs: Option<&ImageAttrs>,
+    image: Option<&ResolvedImage>,
+) -> String {
     let fig_id = attrs
         .and_then(|a| a.id.as_deref())
         .map(|v| format!(r#" id="{}""#, escape(v)))
@@ -24,7 +33,7 @@ pub fn render_block_image(src: &str, alt: &str, title: &str, attrs: Option<&Imag
         .unwrap_or_default();
 
     let mut html = format!("<figure{fig_id}{fig_class}>\n  ");
-    push_img_tag(&mut html, src, alt, title, attrs, false);
+    push_img_tag(&mut html, src, alt, title, attrs, image, false);
     html.push('\n');
 
     if !alt.is_empty() {
@@ -45,26 +54,60 @@ pub fn render_inline_image(
     alt: &str,
     title: &str,
     attrs: Option<&ImageAttrs>,
+    image: Option<&ResolvedImage>,
 ) -> String {
     let mut html = String::new();
-    push_img_tag(&mut html, src, alt, title, attrs, true);
+    push_img_tag(&mut html, src, alt, title, attrs, image, true);
     html
 }
 
+/// Writes the `<img>` element. A resolved `image` adds its intrinsic `width` /
+/// `height` unless `attrs` set either, and its responsive variants add
+/// `srcset` / `sizes` and a `<picture>` around the `<img>` with a `<source>`
+/// per converted format.
 fn push_img_tag(
     html: &mut String,
     src: &str,
     alt: &str,
     title: &str,
     attrs: Option<&ImageAttrs>,
+    image: Option<&ResolvedImage>,
     include_identity: bool,
 ) {
+    let responsive = image.and_then(|i| i.responsive.as_ref());
+    let picture = responsive.filter(|r| !r.sources.is_empty());
+    if let Some(r) = picture {
+        html.push_str("<picture>");
+        for (mime_type, srcset) in &r.sources {
+            _ = write!(
+                html,
+                r#"<source type="{mime_type}" srcset="{}" sizes="{}" />"#,
+                escape(srcset),
+                escape(&r.sizes)
+            );
+        }
+    }
+
     _ = write!(html, r#"<img src="{}" alt="{}""#, escape(src), escape(alt));
 
     if !title.is_empty() {
         _ = write!(html, r#" title="{}""#, escape(title));
     }
 
+    if let Some(r) = responsive {
+        _ = write!(
+            html,
+            r#" srcset="{}" sizes="{}""#,
+            escape(&r.srcset),
+            escape(&r.sizes)
+        );
+    }
+    if let Some(i) = image
+        && attrs.is_none_or(|a| a.width.is_none() && a.height.is_none())
+    {
+        _ = write!(html, r#" width="{}" height="{}""#, i.width, i.height);
+    }
+
     if let Some(a) = attrs {
         if include_identity {
             if let Some(id) = &a.id {
@@ -84,17 +127,21 @@ fn push_img_tag(
     }
 
     html.push_str(r#" loading="lazy" decoding="async" />"#);
+    if picture.is_some() {
+        html.push_str("</picture>");
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::responsive::ResponsiveImage;
 
     // ── render_block_image ──
 
     #[test]
     fn block_image_produces_figure() {
-        let html = render_block_image("img.png", "A photo", "", None);
+        let html = render_block_image("img.png", "A photo", "", None, None);
         assert!(html.contains("<figure>"), "html:\n{html}");
         assert!(html.contains(r#"src="img.png""#), "html:\n{html}");
         assert!(html.contains(r#"alt="A photo""#), "html:\n{html}");
@@ -108,14 +155,14 @@ mod tests {
 
     #[test]
     fn block_image_empty_alt_no_figcaption() {
-        let html = render_block_image("img.png", "", "", None);
+        let html = render_block_image("img.png", "", "", None, None);
         assert!(html.contains("<figure>"), "html:\n{html}");
         assert!(!html.contains("<figcaption>"), "html:\n{html}");
     }
 
     #[test]
     fn block_image_with_title() {
-        let html = render_block_image("img.png", "alt text", "My Title", None);
+        let html = render_block_image("img.png", "alt text", "My Title", None, None);
         assert!(html.contains(r#"title="My Title""#), "html:\n{html}");
         assert!(
             html.contains("<figcaption>alt text</figcaption>"),
@@ -130,6 +177,7 @@ mod tests {
             r#"a <photo> & "test""#,
             "title's <value>",
             None,
+            None,
         );
         assert!(
             html.contains(r#"src="img.png?a=1&amp;b=2""#),
@@ -151,7 +199,7 @@ mod tests {
             id: Some("fig-1".into()),
             ..ImageAttrs::default()
         };
-        let html = render_block_image("img.png", "alt", "", Some(&attrs));
+        let html = render_block_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"<figure id="fig-1">"#), "html:\n{html}");
     }
 
@@ -161,7 +209,7 @@ mod tests {
             classes: vec!["hero".into()],
             ..ImageAttrs::default()
         };
-        let html = render_block_image("img.png", "alt", "", Some(&attrs));
+        let html = render_block_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"<figure class="hero">"#), "html:\n{html}");
     }
 
@@ -171,7 +219,7 @@ mod tests {
             width: Some("500".into()),
             ..ImageAttrs::default()
         };
-        let html = render_block_image("img.png", "alt", "", Some(&attrs));
+        let html = render_block_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"width="500""#), "html:\n{html}");
     }
 
@@ -181,15 +229,66 @@ mod tests {
             height: Some("300".into()),
             ..ImageAttrs::default()
         };
-        let html = render_block_image("img.png", "alt", "", Some(&attrs));
+        let html = render_block_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"height="300""#), "html:\n{html}");
     }
 
+    fn responsive(sources: Vec<(&'static str, String)>) -> ResolvedImage {
+        ResolvedImage {
+            width: 1000,
+            height: 500,
+            responsive: Some(ResponsiveImage {
+                srcset: "img.480w.png 480w, img.png 1000w".into(),
+                sources,
+                sizes: "(max-width: 768px) 100vw, 768px".into(),
+                variants: Vec::new(),
+            }),
+        }
+    }
+
+    #[test]
+    fn block_image_with_intrinsic_size() {
+        let image = ResolvedImage {
+            width: 640,
+            height: 480,
+            responsive: None,
+        };
+        let html = render_block_image("/logo.png", "alt", "", None, Some(&image));
+        assert!(
+            html.contains(
+                r#"<img src="/logo.png" alt="alt" width="640" height="480" loading="lazy""#
+            ),
+            "html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn block_image_with_responsive_sources() {
+        let image = responsive(vec![(
+            "image/webp",
+            "img.480w.webp 480w, img.1000w.webp 1000w".into(),
+        )]);
+        let html = render_block_image("img.png", "alt", "", None, Some(&image));
+        assert!(
+            html.contains(concat!(
+                "<figure>\n  ",
+                r#"<picture><source type="image/webp" "#,
+                r#"srcset="img.480w.webp 480w, img.1000w.webp 1000w" "#,
+                r#"sizes="(max-width: 768px) 100vw, 768px" />"#,
+                r#"<img src="img.png" alt="alt" srcset="img.480w.png 480w, img.png 1000w" "#,
+                r#"sizes="(max-width: 768px) 100vw, 768px" width="1000" height="500" "#,
+                r#"loading="lazy" decoding="async" /></picture>"#,
+                "\n",
+            )),
+            "html:\n{html}"
+        );
+    }
+
     // ── render_inline_image ──
 
     #[test]
     fn inline_image_no_figure() {
-        let html = render_inline_image("img.png", "alt text", "", None);
+        let html = render_inline_image("img.png", "alt text", "", None, None);
         assert!(!html.contains("<figure>"), "html:\n{html}");
         assert!(html.starts_with("<img "), "html:\n{html}");
         assert!(html.contains(r#"src="img.png""#), "html:\n{html}");
@@ -198,13 +297,40 @@ mod tests {
         assert!(html.contains(r#"decoding="async""#), "html:\n{html}");
     }
 
+    #[test]
+    fn inline_image_with_responsive_srcset() {
+        let attrs = ImageAttrs {
+            width: Some("300".into()),
+            ..ImageAttrs::default()
+        };
+        let html = render_inline_image(
+            "img.png",
+            "alt",
+            "",
+            Some(&attrs),
+            Some(&responsive(vec![])),
+        );
+        assert!(
+            html.starts_with("<img "),
+            "no <picture> without sources:\n{html}"
+        );
+        assert!(
+            html.contains(r#"srcset="img.480w.png 480w, img.png 1000w""#),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r#"width="300""#) && !html.contains("height="),
+            "explicit size wins over the intrinsic one:\n{html}"
+        );
+    }
+
     #[test]
     fn inline_image_with_id() {
         let attrs = ImageAttrs {
             id: Some("pic-1".into()),
             ..ImageAttrs::default()
         };
-        let html = render_inline_image("img.png", "alt", "", Some(&attrs));
+        let html = render_inline_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"id="pic-1""#), "html:\n{html}");
     }
 
@@ -214,7 +340,7 @@ mod tests {
             classes: vec!["centered".into()],
             ..ImageAttrs::default()
         };
-        let html = render_inline_image("img.png", "alt", "", Some(&attrs));
+        let html = render_inline_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"class="centered""#), "html:\n{html}");
     }
 
@@ -224,7 +350,7 @@ mod tests {
             width: Some("500".into()),
             ..ImageAttrs::default()
         };
-        let html = render_inline_image("img.png", "alt", "", Some(&attrs));
+        let html = render_inline_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"width="500""#), "html:\n{html}");
     }
 
@@ -234,7 +360,7 @@ mod tests {
             height: Some("300".into()),
             ..ImageAttrs::default()
         };
-        let html = render_inline_image("img.png", "alt", "", Some(&attrs));
+        let html = render_inline_image("img.png", "alt", "", Some(&attrs), None);
         assert!(html.contains(r#"height="300""#), "html:\n{html}");
     }
 }
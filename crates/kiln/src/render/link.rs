@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+
+use crate::content::format::ContentFormat;
+
+/// Prefix of link targets relative to the content directory:
+/// `[text](@/posts/foo.md)`.
+const CONTENT_ROOT_PREFIX: &str = "@/";
+
+/// Output URLs of every content page and section asset, keyed by source
+/// path, for resolving links that point at content files instead of their
+/// published URLs.
+#[derive(Debug, Clone, Default)]
+pub struct ContentLinks {
+    content_dir: PathBuf,
+    urls: HashMap<PathBuf, String>,
+}
+
+impl ContentLinks {
+    #[must_use]
+    pub fn new(content_dir: &Path) -> Self {
+        Self {
+            content_dir: normalize(content_dir),
+            urls: HashMap::new(),
+        }
+    }
+
+    /// Records that the page or asset at `source_path` is published at `url`.
+    pub fn insert(&mut self, source_path: &Path, url: String) {
+        self.urls.insert(normalize(source_path), url);
+    }
+}
+
+/// [`ContentLinks`] as seen from one page, whose source directory anchors
+/// relative targets.
+#[derive(Debug, Clone)]
+pub(crate) struct PageLinks {
+    site: Arc<ContentLinks>,
+    source_dir: Option<PathBuf>,
+}
+
+impl PageLinks {
+    pub(crate) fn new(site: Arc<ContentLinks>, source_dir: Option<&Path>) -> Self {
+        Self {
+            site,
+            source_dir: source_dir.map(Path::to_path_buf),
+        }
+    }
+
+    /// Resolves a link destination to the URL of the content page it names.
+    ///
+    /// Handles `@/`-prefixed paths (relative to the content directory) and
+    /// relative paths to `.md`, `.org`, or `.adoc` files (relative to the
+    /// page). Other relative paths resolve only when they name a section
+    /// asset (`../_assets/logo.png`). A `#fragment` or `?query` is carried
+    /// over. Returns `None` for any other destination, which is left as
+    /// written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the destination names a content file that is not
+    /// a page of the site.
+    pub(crate) fn resolve(&self, dest: &str) -> Result<Option<String>> {
+        let path_len = dest.find(['#', '?']).unwrap_or(dest.len());
+        let (path, suffix) = dest.split_at(path_len);
+
+        let target = if let Some(rest) = path.strip_prefix(CONTENT_ROOT_PREFIX) {
+            self.site.content_dir.join(rest)
+        } else if is_relative_path(path)
+            && let Some(source_dir) = &self.source_dir
+        {
+            let target = normalize(&source_dir.join(path));
+            if !is_content_file(path) {
+                let url = self.site.urls.get(&target);
+                return Ok(url.map(|url| format!("{url}{suffix}")));
+            }
+            target
+        } else {
+            return Ok(None);
+        };
+        let target = normalize(&target);
+
+        let Some(url) = self.site.urls.get(&target) else {
+            bail!(
+                "broken link `{dest}`: no content page at {}",
+                target.display()
+            );
+        };
+        Ok(Some(format!("{url}{suffix}")))
+    }
+}
+
+/// Returns `true` for relative paths: no scheme, not root-relative.
+fn is_relative_path(path: &str) -> bool {
+    let has_scheme = path
+        .find(':')
+        .is_some_and(|colon| !path[..colon].contains('/'));
+    !path.is_empty() && !path.starts_with('/') && !has_scheme
+}
+
+/// Returns `true` for paths to a markup content file.
+fn is_content_file(path: &str) -> bool {
+    ContentFormat::from_path(Path::new(path)).is_some_and(|format| format != ContentFormat::Html)
+}
+
+/// Lexically resolves `.` and `..` components so that equal paths compare
+/// equal without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_links(source_dir: &str) -> PageLinks {
+        let mut site = ContentLinks::new(Path::new("/site/content"));
+        site.insert(
+            Path::new("/site/content/posts/hello/index.md"),
+            "https://example.com/posts/hello/".into(),
+        );
+        site.insert(
+            Path::new("/site/content/about.md"),
+            "https://example.com/about/".into(),
+        );
+        site.insert(
+            Path::new("/site/content/posts/_assets/logo.png"),
+            "https://example.com/posts/_assets/logo.png".into(),
+        );
+        PageLinks::new(Arc::new(site), Some(Path::new(source_dir)))
+    }
+
+    // ── PageLinks::resolve ──
+
+    #[test]
+    fn resolve_relative_and_content_root_paths() {
+        let links = page_links("/site/content/posts/world");
+        assert_eq!(
+            links.resolve("../hello/index.md").unwrap().as_deref(),
+            Some("https://example.com/posts/hello/")
+        );
+        assert_eq!(
+            links.resolve("./../../about.md#team").unwrap().as_deref(),
+            Some("https://example.com/about/#team")
+        );
+        assert_eq!(
+            links
+                .resolve("@/posts/hello/index.md?ref=1")
+                .unwrap()
+                .as_deref(),
+            Some("https://example.com/posts/hello/?ref=1")
+        );
+    }
+
+    #[test]
+    fn resolve_section_assets() {
+        let links = page_links("/site/content/posts/world");
+        assert_eq!(
+            links.resolve("../_assets/logo.png").unwrap().as_deref(),
+            Some("https://example.com/posts/_assets/logo.png")
+        );
+        let links = page_links("/site/content/posts");
+        assert_eq!(
+            links.resolve("_assets/logo.png?v=2").unwrap().as_deref(),
+            Some("https://example.com/posts/_assets/logo.png?v=2")
+        );
+        assert_eq!(
+            links
+                .resolve("@/posts/_assets/logo.png")
+                .unwrap()
+                .as_deref(),
+            Some("https://example.com/posts/_assets/logo.png")
+        );
+    }
+
+    #[test]
+    fn resolve_leaves_other_destinations() {
+        let links = page_links("/site/content/posts/world");
+        for dest in [
+            "https://example.com/readme.md",
+            "/docs/readme.md",
+            "mailto:me@example.com",
+            "#section",
+            "assets/diagram.png",
+            "legacy.html",
+            "",
+        ] {
+            assert_eq!(links.resolve(dest).unwrap(), None, "dest: {dest}");
+        }
+    }
+
+    #[test]
+    fn resolve_missing_page_returns_error() {
+        let links = page_links("/site/content/posts/world");
+        let err = links.resolve("../missing/index.md").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "broken link `../missing/index.md`: no content page at /site/content/posts/missing/index.md"
+        );
+    }
+
+    #[test]
+    fn resolve_without_source_dir_only_handles_content_root() {
+        let mut site = ContentLinks::new(Path::new("/site/content"));
+        site.insert(Path::new("/site/content/about.md"), "/about/".into());
+        let links = PageLinks::new(Arc::new(site), None);
+        assert_eq!(links.resolve("about.md").unwrap(), None);
+        assert_eq!(
+            links.resolve("@/about.md").unwrap().as_deref(),
+            Some("/about/")
+        );
+    }
+}
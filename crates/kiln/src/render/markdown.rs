@@ -1,15 +1,23 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
 
-use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use anyhow::Result;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Tag, TagEnd};
+use strum::EnumString;
 use syntect::parsing::SyntaxSet;
 
+use super::abbreviation::Abbreviations;
 use super::assets::Feature;
-use super::highlight::highlight_code;
+use super::highlight::{CodeBlockInfo, CodeOptions, highlight_code};
 use super::image::{render_block_image, render_inline_image};
 use super::image_attrs::ImageAttrs;
 use super::mermaid::render_mermaid;
+use super::reference::{FootnoteDefinition, PageRefs, footnote_reference_html};
+use super::responsive::{ImageResolver, ImageVariant, ResolvedImage};
+use super::sanitize::is_safe_url;
 use super::toc::TocEntry;
+use crate::config::{HeadingIds, Markdown, SlugifyMode};
 use crate::html::escape;
+use crate::text::case::TitleCaser;
 use crate::text::slugify;
 
 /// The result of rendering markdown content.
@@ -19,6 +27,52 @@ pub struct MarkdownOutput {
     pub html: String,
     /// Table of contents entries collected from headings.
     pub headings: Vec<TocEntry>,
+    /// Footnote definitions lifted out of `html`; see
+    /// [`number_footnotes`](super::reference::number_footnotes).
+    pub(crate) footnotes: Vec<FootnoteDefinition>,
+    /// Bundle image variants referenced by `srcset`s in `html`.
+    pub images: Vec<ImageVariant>,
+}
+
+/// Site-wide settings for rendering a markdown fragment.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct MarkdownOptions<'a> {
+    pub(crate) code: CodeOptions<'a>,
+    pub(crate) heading_ids: HeadingIds,
+    pub(crate) slugify: SlugifyMode,
+    pub(crate) heading_anchor: Option<&'a HeadingAnchor>,
+    /// Casing applied to heading text, from `[markup] title_case`.
+    pub(crate) title_case: Option<&'a TitleCaser>,
+    /// Where local images are looked up, for their intrinsic size and
+    /// responsive variants.
+    pub(crate) images: Option<ImageResolver<'a>>,
+}
+
+/// A permalink emitted inside every heading:
+/// `<a class="anchor" href="#id" aria-hidden="true">¶</a>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadingAnchor {
+    /// Link text, e.g. `¶` or `#`.
+    pub symbol: String,
+    pub position: AnchorPosition,
+}
+
+/// Where a [`HeadingAnchor`] sits relative to the heading text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum AnchorPosition {
+    Before,
+    After,
+}
+
+impl HeadingAnchor {
+    fn to_html(&self, id: &str) -> String {
+        format!(
+            r##"<a class="anchor" href="#{}" aria-hidden="true">{}</a>"##,
+            escape(id),
+            escape(&self.symbol)
+        )
+    }
 }
 
 /// Renders markdown content to HTML with GFM extensions, math support, syntax
@@ -32,69 +86,106 @@ pub struct MarkdownOutput {
 /// - Headings receive auto-generated `id` attributes (CJK-aware slugification)
 ///   and are collected into `headings` for table of contents generation.
 ///   Explicit heading IDs (`## Foo {#bar}`) are respected when present.
+///   `options.heading_ids` limits which headings get an ID at all; those
+///   without one are left out of `headings`.
+///   IDs are deduplicated against `used_ids`, which the caller shares across
+///   every fragment of a page so directive bodies never collide with the
+///   surrounding document. With `options.heading_anchor` set, each heading
+///   also carries a permalink to its own ID. With `options.title_case` set,
+///   heading text is recased, in the body and `headings` alike.
+/// - Reference links and footnotes resolve against the page-wide `refs`, so
+///   definitions may live in another fragment. Footnote references are
+///   emitted unnumbered and definitions are returned in `footnotes` rather
+///   than `html`; the caller numbers and places them once per page.
+/// - Links to content files (`../other/index.md`, `@/posts/foo.md`) are
+///   rewritten to the target page's URL when `refs` carries the site's
+///   page links.
 /// - Math events are rendered as KaTeX-compatible `<span>` elements.
 /// - Fenced code blocks with a language tag receive syntect CSS-class
-///   highlighting with line numbers.
+///   highlighting, with line numbers unless disabled by `options.code` or
+///   the block's info string.
 /// - Block images (sole image in a paragraph) are wrapped in `<figure>`
 ///   elements. Optional `image_attrs` from Pandoc `{...}` preprocessing
 ///   are applied (width, height, classes).
+/// - Images found through `options.images` get their intrinsic `width` /
+///   `height`, and page bundle images a `srcset` of their variants; the
+///   variants are listed in `images`.
+///
+/// # Errors
+///
+/// Returns an error if a link names a content file that is not a page.
 ///
 /// [`PageAssets`]: crate::render::assets::PageAssets
-#[must_use]
 pub(crate) fn render_markdown(
     content: &str,
     syntax_set: &SyntaxSet,
     image_attrs: &HashMap<usize, ImageAttrs>,
-    code_max_lines: Option<usize>,
+    options: MarkdownOptions<'_>,
     features: &mut BTreeSet<Feature>,
-) -> MarkdownOutput {
-    let options = markdown_options();
+    used_ids: &mut HashSet<String>,
+    refs: &PageRefs,
+) -> Result<MarkdownOutput> {
+    let source = refs.fragment_source(content);
+
+    let mut events = fragment_events(&source, content.len(), refs)?;
+    if let Some(caser) = options.title_case {
+        case_headings(&mut events, caser);
+    }
 
     // Pass 1: collect heading metadata (text, level, IDs).
-    let headings = collect_headings(content, options);
+    let headings = collect_headings(&events, &options, used_ids);
 
     // Pass 2: transform events through a manual loop for N:1 buffering.
-    let parser = Parser::new_ext(content, options).into_offset_iter();
-    let mut output_events: Vec<Event<'_>> = Vec::new();
+    let mut output = HtmlOutput::default();
 
     let mut heading_index: usize = 0;
     let mut in_code_block = false;
-    let mut code_lang: Option<String> = None;
+    let mut code_info = CodeBlockInfo::default();
     let mut code_buf = String::new();
     let mut is_mermaid_block = false;
     let mut para_buf: Vec<(Event<'_>, std::ops::Range<usize>)> = Vec::new();
     let mut in_para = false;
+    let mut images = ImageContext::new(image_attrs, options.images);
 
-    for (event, range) in parser {
+    for (event, range) in events {
         match event {
             // ── Headings ──
-            Event::Start(Tag::Heading { .. }) => {
-                let entry = &headings[heading_index];
+            Event::Start(Tag::Heading { level, .. }) => {
+                let Some(entry) = &headings[heading_index] else {
+                    heading_index += 1;
+                    output.push(Event::Html(format!("<{level}>").into()));
+                    continue;
+                };
                 heading_index += 1;
-                output_events.push(Event::Html(
-                    format!(r#"<{} id="{}">"#, entry.level, escape(&entry.id)).into(),
-                ));
+                let mut html = format!(r#"<{} id="{}">"#, entry.level, escape(&entry.id));
+                if let Some(anchor) = options.heading_anchor
+                    && anchor.position == AnchorPosition::Before
+                {
+                    html.push_str(&anchor.to_html(&entry.id));
+                }
+                output.push(Event::Html(html.into()));
             }
             Event::End(TagEnd::Heading(level)) => {
-                output_events.push(Event::Html(format!("</{level}>\n").into()));
+                if let Some(anchor) = options.heading_anchor
+                    && anchor.position == AnchorPosition::After
+                    && let Some(entry) = &headings[heading_index - 1]
+                {
+                    output.push(Event::Html(anchor.to_html(&entry.id).into()));
+                }
+                output.push(Event::Html(format!("</{level}>\n").into()));
             }
 
             // ── Code blocks: buffer content, emit on End ──
             Event::Start(Tag::CodeBlock(kind)) => {
                 in_code_block = true;
-                code_lang = match kind {
-                    // Info strings can contain metadata after the language
-                    // token (e.g., "rust no_run"); extract just the first word.
-                    CodeBlockKind::Fenced(lang) => lang
-                        .split_ascii_whitespace()
-                        .next()
-                        .filter(|s| !s.is_empty())
-                        .map(String::from),
-                    CodeBlockKind::Indented => None,
+                code_info = match kind {
+                    // Info strings can carry attributes after the language
+                    // token (e.g., `rust title="main.rs" no_run`).
+                    CodeBlockKind::Fenced(info) => CodeBlockInfo::parse(&info),
+                    CodeBlockKind::Indented => CodeBlockInfo::default(),
                 };
-                is_mermaid_block = code_lang
-                    .as_deref()
-                    .is_some_and(|l| l.eq_ignore_ascii_case("mermaid"));
+                code_info.linenos.get_or_insert(options.code.line_numbers);
+                is_mermaid_block = code_info.lang.eq_ignore_ascii_case("mermaid");
                 if is_mermaid_block {
                     features.insert(Feature::Mermaid);
                 }
@@ -105,10 +196,9 @@ pub(crate) fn render_markdown(
                 let html = if is_mermaid_block {
                     render_mermaid(&code_buf)
                 } else {
-                    let lang = code_lang.take().unwrap_or_default();
-                    highlight_code(syntax_set, &lang, &code_buf, code_max_lines)
+                    highlight_code(syntax_set, &code_info, &code_buf, &options.code)
                 };
-                output_events.push(Event::Html(html.into()));
+                output.push(Event::Html(html.into()));
                 code_buf.clear();
                 is_mermaid_block = false;
             }
@@ -123,12 +213,12 @@ pub(crate) fn render_markdown(
             }
             Event::End(TagEnd::Paragraph) => {
                 in_para = false;
-                if let Some(html) = try_render_block_image(&para_buf, image_attrs) {
-                    output_events.push(Event::Html(html.into()));
+                if let Some(html) = try_render_block_image(&para_buf, &mut images) {
+                    output.push(Event::Html(html.into()));
                 } else {
-                    output_events.push(Event::Html("<p>".into()));
-                    flush_paragraph(&para_buf, image_attrs, &mut output_events, features);
-                    output_events.push(Event::Html("</p>\n".into()));
+                    output.push(Event::Html("<p>".into()));
+                    flush_paragraph(&para_buf, &mut images, &mut output, features);
+                    output.push(Event::Html("</p>\n".into()));
                 }
                 para_buf.clear();
             }
@@ -138,15 +228,454 @@ pub(crate) fn render_markdown(
 
             // ── Everything else (math, etc.) ──
             other => {
-                output_events.push(transform_math(other, features));
+                output.push(transform_math(other, features));
+            }
+        }
+    }
+
+    Ok(MarkdownOutput {
+        html: output.html,
+        headings: headings.into_iter().flatten().collect(),
+        footnotes: output.footnotes,
+        images: images.variants,
+    })
+}
+
+/// Image settings for a fragment, and the variants its images referenced
+/// so far.
+struct ImageContext<'a> {
+    /// Pandoc `{...}` attributes, keyed by the image's byte offset.
+    attrs: &'a HashMap<usize, ImageAttrs>,
+    resolver: Option<ImageResolver<'a>>,
+    variants: Vec<ImageVariant>,
+}
+
+impl<'a> ImageContext<'a> {
+    fn new(attrs: &'a HashMap<usize, ImageAttrs>, resolver: Option<ImageResolver<'a>>) -> Self {
+        Self {
+            attrs,
+            resolver,
+            variants: Vec::new(),
+        }
+    }
+
+    /// Renders an image through `render`, with its intrinsic size and
+    /// responsive variants when it is found on disk.
+    fn render(
+        &mut self,
+        src: &str,
+        render: impl FnOnce(Option<&ResolvedImage>) -> String,
+    ) -> String {
+        let image = self.resolver.and_then(|resolver| resolver.resolve(src));
+        if let Some(responsive) = image.as_ref().and_then(|i| i.responsive.as_ref()) {
+            self.variants.extend(responsive.variants.iter().cloned());
+        }
+        render(image.as_ref())
+    }
+}
+
+/// Parses `source`, a fragment of `content_len` bytes followed by
+/// placeholder footnotes, into the events [`render_markdown`] renders.
+fn fragment_events<'a>(
+    source: &'a str,
+    content_len: usize,
+    refs: &'a PageRefs,
+) -> Result<Vec<(Event<'a>, std::ops::Range<usize>)>> {
+    let events = resolve_footnote_events(refs.parser(source).into_offset_iter(), content_len);
+    let mut events = resolve_content_links(events, refs)?;
+    if refs.extensions.sanitize {
+        drop_unsafe_urls(&mut events);
+    }
+    Ok(resolve_inline_extensions(events, source, refs))
+}
+
+/// Empties the destinations of links and images with a script URL, e.g.,
+/// `[x](javascript:alert(1))`; see [`is_safe_url`].
+fn drop_unsafe_urls(events: &mut [(Event<'_>, std::ops::Range<usize>)]) {
+    for (event, _) in events {
+        if let Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) = event
+            && !is_safe_url(dest_url)
+        {
+            *dest_url = "".into();
+        }
+    }
+}
+
+/// Rewrites the destinations of links to content files to the target page's
+/// URL, and of links and images to section assets to the asset's URL; see
+/// [`PageLinks::resolve`](super::link::PageLinks::resolve).
+fn resolve_content_links<'a>(
+    events: impl Iterator<Item = (Event<'a>, std::ops::Range<usize>)>,
+    refs: &PageRefs,
+) -> Result<Vec<(Event<'a>, std::ops::Range<usize>)>> {
+    let Some(page_links) = &refs.page_links else {
+        return Ok(events.collect());
+    };
+    events
+        .map(|(event, range)| match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => {
+                let dest_url = page_links.resolve(&dest_url)?.map_or(dest_url, Into::into);
+                Ok((
+                    Event::Start(Tag::Link {
+                        link_type,
+                        dest_url,
+                        title,
+                        id,
+                    }),
+                    range,
+                ))
+            }
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => {
+                let dest_url = page_links.resolve(&dest_url)?.map_or(dest_url, Into::into);
+                Ok((
+                    Event::Start(Tag::Image {
+                        link_type,
+                        dest_url,
+                        title,
+                        id,
+                    }),
+                    range,
+                ))
+            }
+            other => Ok((other, range)),
+        })
+        .collect()
+}
+
+/// Renders the inline extensions the parser lacks or only partly covers:
+/// `==text==` as `<mark>`, intraword `^sup^` / `~sub~` (`x^2^`, `H~2~O`),
+/// which pulldown-cmark only recognizes between words, and the page's
+/// abbreviations as `<abbr>`.
+///
+/// Like emphasis, an opening `==` must be followed and a closing `==`
+/// preceded by non-whitespace, and a pair cannot span blocks or cross the
+/// boundary of an inline element (`==**both**==` works, `**==half**==` does
+/// not). Intraword scripts follow Pandoc: no whitespace between the
+/// delimiters. Code, math, and image alt text are left untouched; unpaired
+/// and backslash-escaped delimiters stay literal.
+///
+/// `source` is the text the `events` were parsed from.
+fn resolve_inline_extensions<'a>(
+    events: Vec<(Event<'a>, std::ops::Range<usize>)>,
+    source: &str,
+    refs: &PageRefs,
+) -> Vec<(Event<'a>, std::ops::Range<usize>)> {
+    let extensions = refs.extensions;
+    if !(extensions.mark || extensions.superscript || extensions.subscript)
+        && refs.abbreviations.is_empty()
+    {
+        return events;
+    }
+    let mut state = InlineState {
+        extensions,
+        abbreviations: &refs.abbreviations,
+        output: Vec::with_capacity(events.len()),
+        open_mark: None,
+        depth: 0,
+    };
+    let mut in_code_block = false;
+    let mut image_depth = 0;
+
+    for (event, range, escaped) in merge_text(events, source) {
+        match &event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(Tag::Image { .. }) => image_depth += 1,
+            Event::End(TagEnd::Image) => image_depth -= 1,
+            _ => {}
+        }
+        match &event {
+            Event::Text(text) if !in_code_block && image_depth == 0 => {
+                state.push_scripts(text, &range, escaped);
+                continue;
+            }
+            Event::Start(tag) if is_inline_tag(tag) => state.depth += 1,
+            Event::End(tag) if is_inline_tag_end(*tag) => {
+                state.depth -= 1;
+                if state
+                    .open_mark
+                    .is_some_and(|(_, depth)| depth > state.depth)
+                {
+                    state.open_mark = None;
+                }
+            }
+            Event::Start(_) | Event::End(_) => state.open_mark = None,
+            _ => {}
+        }
+        state.output.push((event, range));
+    }
+    state.output
+}
+
+/// Joins adjacent text events that are verbatim source text, which the
+/// parser splits at would-be delimiters (`x^2^` arrives as `x^2`, `^`).
+///
+/// Each event comes with whether it is text whose first character was
+/// backslash-escaped; the parser drops the backslash, leaving a gap in
+/// `source` before the event.
+fn merge_text<'a>(
+    events: Vec<(Event<'a>, std::ops::Range<usize>)>,
+    source: &str,
+) -> Vec<(Event<'a>, std::ops::Range<usize>, bool)> {
+    let verbatim = |text: &str, range: &std::ops::Range<usize>| text.len() == range.len();
+    let mut merged: Vec<(Event<'a>, std::ops::Range<usize>, bool)> =
+        Vec::with_capacity(events.len());
+    for (event, range) in events {
+        let after_text = matches!(
+            merged.last(),
+            Some((Event::Text(_), prev_range, _)) if prev_range.end == range.start
+        );
+        if let Event::Text(text) = &event
+            && after_text
+            && let Some((Event::Text(prev), prev_range, _)) = merged.last_mut()
+            && verbatim(prev, prev_range)
+            && verbatim(text, &range)
+        {
+            *prev = format!("{prev}{text}").into();
+            prev_range.end = range.end;
+            continue;
+        }
+        let escaped =
+            matches!(event, Event::Text(_)) && !after_text && source[..range.start].ends_with('\\');
+        merged.push((event, range, escaped));
+    }
+    merged
+}
+
+/// Output of [`resolve_inline_extensions`] so far, with its pending `==`.
+struct InlineState<'a, 'r> {
+    extensions: Markdown,
+    abbreviations: &'r Abbreviations,
+    output: Vec<(Event<'a>, std::ops::Range<usize>)>,
+    /// Index of the pending opening `==` in `output`, and the inline depth
+    /// it sits at.
+    open_mark: Option<(usize, usize)>,
+    /// Number of enclosing inline elements.
+    depth: usize,
+}
+
+impl InlineState<'_, '_> {
+    /// Pushes `text`, with intraword `^sup^` / `~sub~` turned into tags.
+    /// `escaped` is whether the first character of `text` was escaped.
+    fn push_scripts(&mut self, text: &str, range: &std::ops::Range<usize>, escaped: bool) {
+        let mut rest = 0;
+        let mut i = 0;
+        while let Some((pos, delim)) = text[i..].char_indices().find(|&(_, c)| {
+            (c == '^' && self.extensions.superscript) || (c == '~' && self.extensions.subscript)
+        }) {
+            let pos = pos + i;
+            i = pos + 1;
+            if pos == 0 && escaped {
+                continue;
             }
+            let Some(len) = script_len(&text[pos..], delim, text[..pos].ends_with(delim)) else {
+                continue;
+            };
+            let tag = if delim == '^' { "sup" } else { "sub" };
+            self.push_marks(&text[rest..pos], range, escaped && rest == 0);
+            self.push_html(format!("<{tag}>"), range);
+            self.push_marks(&text[pos + 1..pos + 1 + len], range, false);
+            self.push_html(format!("</{tag}>"), range);
+            i = pos + len + 2;
+            rest = i;
         }
+        self.push_marks(&text[rest..], range, escaped && rest == 0);
     }
 
-    let mut html = String::new();
-    pulldown_cmark::html::push_html(&mut html, output_events.into_iter());
+    /// Pushes `text`, pairing its `==` delimiters into `<mark>` tags.
+    /// `escaped` is whether the first character of `text` was escaped.
+    fn push_marks(&mut self, text: &str, range: &std::ops::Range<usize>, escaped: bool) {
+        if !self.extensions.mark {
+            self.push_text(text, range);
+            return;
+        }
+        let mut rest = 0;
+        let mut i = 0;
+        while let Some(pos) = text[i..].find("==").map(|p| p + i) {
+            let run_end = pos + text[pos..].bytes().take_while(|&b| b == b'=').count();
+            i = run_end;
+            if run_end - pos != 2 || (pos == 0 && escaped) {
+                continue;
+            }
+            let can_open = text[run_end..]
+                .chars()
+                .next()
+                .is_none_or(|c| !c.is_whitespace());
+            let can_close = text[..pos]
+                .chars()
+                .next_back()
+                .is_none_or(|c| !c.is_whitespace());
+
+            match self.open_mark {
+                Some((index, depth)) if depth == self.depth && can_close => {
+                    self.push_text(&text[rest..pos], range);
+                    self.output[index].0 = Event::InlineHtml("<mark>".into());
+                    self.push_html("</mark>".to_owned(), range);
+                    self.open_mark = None;
+                }
+                None if can_open => {
+                    self.push_text(&text[rest..pos], range);
+                    self.open_mark = Some((self.output.len(), self.depth));
+                    self.output.push((Event::Text("==".into()), range.clone()));
+                }
+                _ => continue,
+            }
+            rest = run_end;
+        }
+        self.push_text(&text[rest..], range);
+    }
+
+    /// Pushes `text`, with the page's abbreviations wrapped in `<abbr>`.
+    fn push_text(&mut self, mut text: &str, range: &std::ops::Range<usize>) {
+        while let Some((start, abbr, expansion)) = self.abbreviations.find(text) {
+            self.push_plain_text(&text[..start], range);
+            let html = if expansion.is_empty() {
+                "<abbr>".to_owned()
+            } else {
+                format!(r#"<abbr title="{}">"#, escape(expansion))
+            };
+            self.push_html(html, range);
+            self.push_plain_text(abbr, range);
+            self.push_html("</abbr>".to_owned(), range);
+            text = &text[start + abbr.len()..];
+        }
+        self.push_plain_text(text, range);
+    }
+
+    fn push_plain_text(&mut self, text: &str, range: &std::ops::Range<usize>) {
+        if !text.is_empty() {
+            self.output
+                .push((Event::Text(text.to_owned().into()), range.clone()));
+        }
+    }
+
+    fn push_html(&mut self, html: String, range: &std::ops::Range<usize>) {
+        self.output
+            .push((Event::InlineHtml(html.into()), range.clone()));
+    }
+}
+
+/// Returns the length of the content of an intraword script starting at the
+/// `delim` that begins `text`: non-empty, without whitespace, and closed by a
+/// single `delim`. Doubled delimiters (`~~`, `^^`) never open or close one.
+fn script_len(text: &str, delim: char, after_delim: bool) -> Option<usize> {
+    if after_delim {
+        return None;
+    }
+    let body = &text[delim.len_utf8()..];
+    let len = body.find(|c: char| c == delim || c.is_whitespace())?;
+    let closed = body[len..].starts_with(delim) && !body[len + 1..].starts_with(delim);
+    (len > 0 && closed).then_some(len)
+}
+
+/// Whether `tag` is an inline element a `==` pair may contain.
+const fn is_inline_tag(tag: &Tag<'_>) -> bool {
+    matches!(
+        tag,
+        Tag::Emphasis
+            | Tag::Strong
+            | Tag::Strikethrough
+            | Tag::Superscript
+            | Tag::Subscript
+            | Tag::Link { .. }
+            | Tag::Image { .. }
+    )
+}
+
+const fn is_inline_tag_end(tag: TagEnd) -> bool {
+    matches!(
+        tag,
+        TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image
+    )
+}
+
+/// Drops the events of placeholder footnote definitions (those starting at or
+/// past `content_len`; see [`PageRefs::fragment_source`]) and swaps footnote
+/// references for their unnumbered markup.
+fn resolve_footnote_events<'a>(
+    events: impl Iterator<Item = (Event<'a>, std::ops::Range<usize>)>,
+    content_len: usize,
+) -> impl Iterator<Item = (Event<'a>, std::ops::Range<usize>)> {
+    let mut in_placeholder = false;
+    events.filter_map(move |(event, range)| {
+        if in_placeholder {
+            in_placeholder = !matches!(event, Event::End(TagEnd::FootnoteDefinition));
+            return None;
+        }
+        match event {
+            Event::Start(Tag::FootnoteDefinition(_)) if range.start >= content_len => {
+                in_placeholder = true;
+                None
+            }
+            Event::FootnoteReference(label) => Some((
+                Event::InlineHtml(footnote_reference_html(&label).into()),
+                range,
+            )),
+            other => Some((other, range)),
+        }
+    })
+}
+
+/// The HTML of rendered events, written out one top-level block at a time
+/// so a fragment's events are never all held at once. Footnote definitions
+/// are lifted out of `html` into `footnotes`, each body rendered on its own.
+#[derive(Default)]
+struct HtmlOutput<'a> {
+    html: String,
+    footnotes: Vec<FootnoteDefinition>,
+    /// Events of the block being rendered, and how deeply nested they are.
+    block: Vec<Event<'a>>,
+    depth: usize,
+    /// Label and events of the footnote definition being rendered.
+    footnote: Option<(String, Vec<Event<'a>>)>,
+}
 
-    MarkdownOutput { html, headings }
+impl<'a> HtmlOutput<'a> {
+    fn push(&mut self, event: Event<'a>) {
+        match event {
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                self.footnote = Some((label.into_string(), Vec::new()));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                if let Some((label, body)) = self.footnote.take() {
+                    let mut html = String::new();
+                    pulldown_cmark::html::push_html(&mut html, body.into_iter());
+                    self.footnotes.push(FootnoteDefinition { label, html });
+                }
+            }
+            other => {
+                if let Some((_, body)) = &mut self.footnote {
+                    body.push(other);
+                    return;
+                }
+                match other {
+                    Event::Start(_) => self.depth += 1,
+                    Event::End(_) => self.depth -= 1,
+                    _ => {}
+                }
+                self.block.push(other);
+                if self.depth == 0 {
+                    pulldown_cmark::html::push_html(&mut self.html, self.block.drain(..));
+                }
+            }
+        }
+    }
 }
 
 /// Checks if a paragraph's buffered events represent a sole image (block image).
@@ -155,7 +684,7 @@ pub(crate) fn render_markdown(
 /// with no other images in the paragraph.
 fn try_render_block_image(
     events: &[(Event<'_>, std::ops::Range<usize>)],
-    image_attrs: &HashMap<usize, ImageAttrs>,
+    images: &mut ImageContext<'_>,
 ) -> Option<String> {
     let (src, title, byte_offset) = match &events.first()?.0 {
         Event::Start(Tag::Image {
@@ -185,16 +714,18 @@ fn try_render_block_image(
     }
 
     let alt = extract_alt_text(inner);
-    let attrs = image_attrs.get(&byte_offset);
-    Some(render_block_image(&src, &alt, &title, attrs))
+    let attrs = images.attrs.get(&byte_offset);
+    Some(images.render(&src, |image| {
+        render_block_image(&src, &alt, &title, attrs, image)
+    }))
 }
 
 /// Flushes buffered paragraph events, replacing inline image sequences with
 /// `render_inline_image()` output while passing other events through.
 fn flush_paragraph<'a>(
     events: &[(Event<'a>, std::ops::Range<usize>)],
-    image_attrs: &HashMap<usize, ImageAttrs>,
-    output: &mut Vec<Event<'a>>,
+    images: &mut ImageContext<'_>,
+    output: &mut HtmlOutput<'a>,
     features: &mut BTreeSet<Feature>,
 ) {
     let mut i = 0;
@@ -218,10 +749,11 @@ fn flush_paragraph<'a>(
                 i += 1; // skip End(Image)
             }
 
-            let attrs = image_attrs.get(&byte_offset);
-            output.push(Event::Html(
-                render_inline_image(&src, &alt, &title, attrs).into(),
-            ));
+            let attrs = images.attrs.get(&byte_offset);
+            let html = images.render(&src, |image| {
+                render_inline_image(&src, &alt, &title, attrs, image)
+            });
+            output.push(Event::Html(html.into()));
         } else {
             output.push(transform_math(events[i].0.clone(), features));
             i += 1;
@@ -241,53 +773,117 @@ fn extract_alt_text(events: &[(Event<'_>, std::ops::Range<usize>)]) -> String {
     alt
 }
 
-fn markdown_options() -> Options {
-    Options::ENABLE_TABLES
-        | Options::ENABLE_FOOTNOTES
-        | Options::ENABLE_STRIKETHROUGH
-        | Options::ENABLE_TASKLISTS
-        | Options::ENABLE_HEADING_ATTRIBUTES
-        | Options::ENABLE_MATH
+/// Returns the parser options for the syntax extensions enabled in
+/// `[markdown]`. `==mark==` has no parser option; see [`resolve_inline_extensions`].
+pub(crate) fn markdown_options(extensions: Markdown) -> Options {
+    let mut options = Options::empty();
+    options.set(Options::ENABLE_TABLES, extensions.tables);
+    options.set(Options::ENABLE_FOOTNOTES, extensions.footnotes);
+    options.set(Options::ENABLE_STRIKETHROUGH, extensions.strikethrough);
+    options.set(Options::ENABLE_TASKLISTS, extensions.tasklists);
+    options.set(
+        Options::ENABLE_HEADING_ATTRIBUTES,
+        extensions.heading_attributes,
+    );
+    options.set(Options::ENABLE_MATH, extensions.math);
+    options.set(Options::ENABLE_DEFINITION_LIST, extensions.definition_lists);
+    options.set(Options::ENABLE_SUPERSCRIPT, extensions.superscript);
+    options.set(Options::ENABLE_SUBSCRIPT, extensions.subscript);
+    options
 }
 
-/// Scans the markdown for headings, collecting their level, plain text, and
-/// generating unique slugified IDs.
-fn collect_headings(content: &str, options: Options) -> Vec<TocEntry> {
-    let parser = Parser::new_ext(content, options);
+/// Recases the text of each heading in `events` as one title, leaving code
+/// and math spans as written.
+fn case_headings(events: &mut [(Event<'_>, std::ops::Range<usize>)], caser: &TitleCaser) {
+    let mut start = None;
+    for idx in 0..events.len() {
+        match events[idx].0 {
+            Event::Start(Tag::Heading { .. }) => start = Some(idx + 1),
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(start) = start.take() {
+                    case_heading(&mut events[start..idx], caser);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Recases the text events of a single heading's `events`.
+fn case_heading(events: &mut [(Event<'_>, std::ops::Range<usize>)], caser: &TitleCaser) {
+    let spans: Vec<&str> = events
+        .iter()
+        .filter_map(|(event, _)| heading_span(event))
+        .collect();
+    let recased = caser.apply_spans(&spans);
+    let text_events = events
+        .iter_mut()
+        .filter(|(event, _)| heading_span(event).is_some());
+    for ((event, _), text) in text_events.zip(recased) {
+        if let Event::Text(t) = event {
+            *t = text.into();
+        }
+    }
+}
+
+/// Returns the text a heading event shows, for recasing it in context.
+fn heading_span<'a>(event: &'a Event<'_>) -> Option<&'a str> {
+    match event {
+        Event::Text(t) | Event::Code(t) | Event::InlineMath(t) => Some(t),
+        Event::SoftBreak | Event::HardBreak => Some(" "),
+        _ => None,
+    }
+}
+
+/// Collects the level, plain text, and slugified ID (unique within
+/// `used_ids`) of each heading in `events`.
+///
+/// Returns one slot per heading in document order, `None` for headings that
+/// get no ID under `options.heading_ids`. Automatic IDs are slugified under
+/// `options.slugify`.
+fn collect_headings(
+    events: &[(Event<'_>, std::ops::Range<usize>)],
+    options: &MarkdownOptions<'_>,
+    used_ids: &mut HashSet<String>,
+) -> Vec<Option<TocEntry>> {
     let mut headings = Vec::new();
-    let mut used_ids = HashSet::new();
 
     let mut level = HeadingLevel::H1;
     let mut explicit_id: Option<String> = None;
     let mut text = String::new();
     let mut in_heading = false;
 
-    for event in parser {
+    for (event, _) in events {
         match event {
             Event::Start(Tag::Heading {
                 level: l, id: eid, ..
             }) => {
-                level = l;
-                explicit_id = eid.map(|s| s.to_string());
+                level = *l;
+                explicit_id = eid.as_ref().map(ToString::to_string);
                 text.clear();
                 in_heading = true;
             }
             Event::End(TagEnd::Heading(_)) if in_heading => {
                 in_heading = false;
-                let raw_id = explicit_id.take().unwrap_or_else(|| slugify(&text));
+                let explicit_id = explicit_id.take();
+                let title = std::mem::take(&mut text);
+                let raw_id = match (options.heading_ids, explicit_id) {
+                    (HeadingIds::None, _) | (HeadingIds::ExplicitOnly, None) => {
+                        headings.push(None);
+                        continue;
+                    }
+                    (_, Some(id)) => id,
+                    (HeadingIds::Auto, None) => slugify(&title, options.slugify),
+                };
                 let raw_id = if raw_id.is_empty() {
                     "section".to_owned()
                 } else {
                     raw_id
                 };
-                let id = deduplicate_id(&mut used_ids, &raw_id);
-                headings.push(TocEntry {
-                    level,
-                    id,
-                    title: std::mem::take(&mut text),
-                });
+                let id = deduplicate_id(used_ids, &raw_id);
+                headings.push(Some(TocEntry { level, id, title }));
             }
-            _ if in_heading => push_plain_text(&mut text, &event),
+            _ if in_heading => push_plain_text(&mut text, event),
             _ => {}
         }
     }
@@ -364,12 +960,62 @@ mod tests {
     use syntect::parsing::SyntaxSet;
 
     use super::*;
+    use crate::render::abbreviation::extract_abbreviations;
+    use crate::render::reference::number_footnotes;
 
     static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(two_face::syntax::extra_newlines);
 
+    /// Every syntax extension enabled, including the opt-in ones.
+    const EXTENSIONS: Markdown = Markdown {
+        tables: true,
+        footnotes: true,
+        tasklists: true,
+        strikethrough: true,
+        math: true,
+        heading_attributes: true,
+        definition_lists: true,
+        superscript: true,
+        subscript: true,
+        mark: true,
+        abbreviations: true,
+        sanitize: false,
+    };
+
     fn render(content: &str) -> MarkdownOutput {
+        render_with(content, MarkdownOptions::default())
+    }
+
+    fn render_with(content: &str, options: MarkdownOptions<'_>) -> MarkdownOutput {
         let mut features = BTreeSet::new();
-        render_markdown(content, &SYNTAX_SET, &HashMap::new(), None, &mut features)
+        let mut out = render_markdown(
+            content,
+            &SYNTAX_SET,
+            &HashMap::new(),
+            options,
+            &mut features,
+            &mut HashSet::new(),
+            &PageRefs::collect(content, EXTENSIONS),
+        )
+        .unwrap();
+        out.html = number_footnotes(&out.html, &out.footnotes);
+        out
+    }
+
+    // ── markdown_options ──
+
+    #[test]
+    fn markdown_options_follows_extension_toggles() {
+        let all = markdown_options(Markdown::default());
+        assert!(all.contains(Options::ENABLE_TABLES | Options::ENABLE_MATH));
+
+        let options = markdown_options(Markdown {
+            tables: false,
+            math: false,
+            ..Markdown::default()
+        });
+        assert!(!options.contains(Options::ENABLE_TABLES));
+        assert!(!options.contains(Options::ENABLE_MATH));
+        assert!(options.contains(Options::ENABLE_FOOTNOTES | Options::ENABLE_STRIKETHROUGH));
     }
 
     // ── deduplicate_id ──
@@ -536,6 +1182,115 @@ mod tests {
         assert_eq!(out.headings[2].id, "foo-2");
     }
 
+    #[test]
+    fn render_heading_ids_explicit_only() {
+        let anchor = HeadingAnchor {
+            symbol: "¶".into(),
+            position: AnchorPosition::After,
+        };
+        let out = render_with(
+            "## Curated {#curated}\n\n## Plain\n",
+            MarkdownOptions {
+                heading_ids: HeadingIds::ExplicitOnly,
+                heading_anchor: Some(&anchor),
+                ..MarkdownOptions::default()
+            },
+        );
+        assert_eq!(
+            out.html,
+            indoc! {r##"
+                <h2 id="curated">Curated<a class="anchor" href="#curated" aria-hidden="true">¶</a></h2>
+                <h2>Plain</h2>
+            "##}
+        );
+        assert_eq!(out.headings.len(), 1);
+        assert_eq!(out.headings[0].id, "curated");
+    }
+
+    #[test]
+    fn render_heading_ids_none() {
+        let out = render_with(
+            "# Title {#title}\n\n## Section\n",
+            MarkdownOptions {
+                heading_ids: HeadingIds::None,
+                ..MarkdownOptions::default()
+            },
+        );
+        assert_eq!(out.html, "<h1>Title</h1>\n<h2>Section</h2>\n");
+        assert!(out.headings.is_empty());
+    }
+
+    #[test]
+    fn render_heading_ids_ascii_slugify() {
+        let out = render_with(
+            "## Café Notes\n\n## 你好\n\n## Größe Überblick\n",
+            MarkdownOptions {
+                slugify: SlugifyMode::Ascii,
+                ..MarkdownOptions::default()
+            },
+        );
+        assert_eq!(
+            out.html,
+            concat!(
+                "<h2 id=\"cafe-notes\">Café Notes</h2>\n",
+                "<h2 id=\"section\">你好</h2>\n",
+                "<h2 id=\"grosse-uberblick\">Größe Überblick</h2>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn render_heading_ids_github_slugify() {
+        let out = render_with(
+            "## Q&A: `foo()` -- v1.2\n\n## Q&A: `foo()` -- v1.2\n",
+            MarkdownOptions {
+                slugify: SlugifyMode::Github,
+                ..MarkdownOptions::default()
+            },
+        );
+        let ids: Vec<_> = out.headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["qa-foo----v12", "qa-foo----v12-1"]);
+    }
+
+    #[test]
+    fn render_heading_with_anchor_after_text() {
+        let anchor = HeadingAnchor {
+            symbol: "¶".into(),
+            position: AnchorPosition::After,
+        };
+        let out = render_with(
+            "## Intro & Setup",
+            MarkdownOptions {
+                heading_anchor: Some(&anchor),
+                ..MarkdownOptions::default()
+            },
+        );
+        assert_eq!(
+            out.html,
+            "<h2 id=\"intro-setup\">Intro &amp; Setup<a class=\"anchor\" href=\"#intro-setup\" aria-hidden=\"true\">¶</a></h2>\n"
+        );
+        assert_eq!(out.headings[0].title, "Intro & Setup");
+    }
+
+    #[test]
+    fn render_heading_with_anchor_before_text() {
+        let anchor = HeadingAnchor {
+            symbol: "#".into(),
+            position: AnchorPosition::Before,
+        };
+        let out = render_with(
+            "## Foo {#bar}",
+            MarkdownOptions {
+                heading_anchor: Some(&anchor),
+                ..MarkdownOptions::default()
+            },
+        );
+        assert_eq!(
+            out.html,
+            "<h2 id=\"bar\"><a class=\"anchor\" href=\"#bar\" aria-hidden=\"true\">#</a>Foo</h2>\n"
+        );
+    }
+
     // ── render_markdown: GFM extensions ──
 
     #[test]
@@ -609,6 +1364,164 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_definition_list() {
+        let md = indoc! {"
+            Term
+            : First definition.
+            : Second definition.
+        "};
+        let out = render(md);
+        assert!(
+            out.html
+                .contains("<dl>\n<dt>Term</dt>\n<dd>First definition.</dd>"),
+            "html:\n{}",
+            out.html
+        );
+        assert!(
+            out.html.contains("<dd>Second definition.</dd>"),
+            "html:\n{}",
+            out.html
+        );
+    }
+
+    #[test]
+    fn render_superscript_and_subscript() {
+        let out = render("x^2^ and H~2~O, but ~~deleted~~");
+        assert!(out.html.contains("x<sup>2</sup>"), "html:\n{}", out.html);
+        assert!(out.html.contains("H<sub>2</sub>O"), "html:\n{}", out.html);
+        assert!(
+            out.html.contains("<del>deleted</del>"),
+            "html:\n{}",
+            out.html
+        );
+    }
+
+    #[test]
+    fn render_mark() {
+        let out = render("Some ==highlighted **bold**== text");
+        assert!(
+            out.html
+                .contains("Some <mark>highlighted <strong>bold</strong></mark> text"),
+            "html:\n{}",
+            out.html
+        );
+    }
+
+    #[test]
+    fn render_mark_in_heading_is_left_out_of_toc_title() {
+        let out = render("## A ==key== point");
+        assert_eq!(out.headings[0].title, "A key point");
+        assert_eq!(out.headings[0].id, "a-key-point");
+    }
+
+    #[test]
+    fn render_mark_disabled() {
+        let options = MarkdownOptions::default();
+        let mut features = BTreeSet::new();
+        let content = "Some ==text==";
+        let refs = PageRefs::collect(
+            content,
+            Markdown {
+                mark: false,
+                ..EXTENSIONS
+            },
+        );
+        let out = render_markdown(
+            content,
+            &SYNTAX_SET,
+            &HashMap::new(),
+            options,
+            &mut features,
+            &mut HashSet::new(),
+            &refs,
+        )
+        .unwrap();
+        assert!(out.html.contains("Some ==text=="), "html:\n{}", out.html);
+    }
+
+    // ── resolve_inline_extensions ──
+
+    fn inline(content: &str) -> String {
+        let (content, abbreviations) = extract_abbreviations(content);
+        let mut refs = PageRefs::collect(&content, EXTENSIONS);
+        refs.abbreviations = abbreviations;
+        let events = refs.parser(&content).into_offset_iter().collect();
+        let events = resolve_inline_extensions(events, &content, &refs);
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events.into_iter().map(|(event, _)| event));
+        html
+    }
+
+    #[test]
+    fn resolve_inline_extensions_pairs_delimiters() {
+        assert_eq!(
+            inline("a ==b== c ==d=="),
+            "<p>a <mark>b</mark> c <mark>d</mark></p>\n"
+        );
+    }
+
+    #[test]
+    fn resolve_inline_extensions_requires_flanking() {
+        assert_eq!(inline("a == b == c"), "<p>a == b == c</p>\n");
+        assert_eq!(inline("x ==y"), "<p>x ==y</p>\n");
+    }
+
+    #[test]
+    fn resolve_inline_extensions_ignores_longer_runs() {
+        assert_eq!(inline("a ===b=== c"), "<p>a ===b=== c</p>\n");
+    }
+
+    #[test]
+    fn resolve_inline_extensions_does_not_cross_inline_boundaries() {
+        assert_eq!(inline("**==half**=="), "<p><strong>==half</strong>==</p>\n");
+    }
+
+    #[test]
+    fn resolve_inline_extensions_renders_intraword_scripts() {
+        assert_eq!(
+            inline("x^2^ and H~2~O, ==e^x^=="),
+            "<p>x<sup>2</sup> and H<sub>2</sub>O, <mark>e<sup>x</sup></mark></p>\n"
+        );
+    }
+
+    #[test]
+    fn resolve_inline_extensions_leaves_other_tildes() {
+        assert_eq!(inline("a~b c~d"), "<p>a~b c~d</p>\n");
+        assert_eq!(inline(r"x\^2^"), "<p>x^2^</p>\n");
+        assert_eq!(inline(r"a ==b\== c=="), "<p>a <mark>b== c</mark></p>\n");
+    }
+
+    #[test]
+    fn resolve_inline_extensions_wraps_abbreviations() {
+        let html = inline(indoc! {r#"
+            The ==HTML== spec, by the W3C, in `HTML`.
+
+            *[HTML]: HyperText Markup Language
+            *[W3C]: World Wide Web "Consortium"
+        "#});
+        assert_eq!(
+            html,
+            concat!(
+                r#"<p>The <mark><abbr title="HyperText Markup Language">HTML</abbr></mark> spec, "#,
+                r#"by the <abbr title="World Wide Web &quot;Consortium&quot;">W3C</abbr>, "#,
+                "in <code>HTML</code>.</p>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn resolve_inline_extensions_skips_code_and_math() {
+        assert_eq!(
+            inline("`==a==` and $b==c==d$"),
+            r#"<p><code>==a==</code> and <span class="math math-inline">b==c==d</span></p>"#
+                .to_owned()
+                + "\n"
+        );
+        let html = inline("```\n==a==\n```\n");
+        assert!(!html.contains("<mark>"), "html:\n{html}");
+    }
+
     // ── render_markdown: footnotes ──
 
     #[test]
@@ -620,13 +1533,14 @@ mod tests {
         "};
         let out = render(md);
         assert!(
-            out.html.contains(r##"<a href="#1">"##),
+            out.html
+                .contains(r##"<a href="#fn:1" role="doc-noteref">1</a>"##),
             "should link to footnote definition, html:\n{}",
             out.html
         );
         assert!(
             out.html
-                .contains(r#"<div class="footnote-definition" id="1">"#),
+                .contains(r#"<div class="footnote-definition" id="fn:1">"#),
             "should have footnote definition with matching id, html:\n{}",
             out.html
         );
@@ -645,12 +1559,20 @@ mod tests {
             [^1]: Shared footnote.
         "};
         let out = render(md);
-        let count = out.html.matches(r##"<a href="#1">"##).count();
+        let count = out
+            .html
+            .matches(r##"<a href="#fn:1" role="doc-noteref">"##)
+            .count();
         assert!(
             count == 2,
             "exactly two references should link to the same footnote (found {count}), html:\n{}",
             out.html
         );
+        assert!(
+            out.html.contains(r#"id="fnref:1-2""#) && out.html.contains(r##"href="#fnref:1-2""##),
+            "the second reference should get its own id and backlink, html:\n{}",
+            out.html
+        );
     }
 
     #[test]
@@ -806,6 +1728,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_code_block_title() {
+        let md = indoc! {r#"
+            ```rust title="src/main.rs" no_run
+            fn main() {}
+            ```
+        "#};
+        let out = render(md);
+        assert!(
+            out.html
+                .contains(r#"<span class="code-title">src/main.rs</span>"#),
+            "should render title in header, html:\n{}",
+            out.html
+        );
+        assert!(
+            out.html.contains(r#"data-lang="rust""#),
+            "language should still resolve, html:\n{}",
+            out.html
+        );
+    }
+
     #[test]
     fn render_code_block_mermaid_emits_bare_pre() {
         let md = indoc! {"
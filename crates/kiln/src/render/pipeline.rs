@@ -1,38 +1,89 @@
+use std::collections::HashSet;
 use std::path::Path;
 
 use anyhow::Result;
 use syntect::parsing::SyntaxSet;
 
 use super::RenderOptions;
-use super::assets::PageAssets;
+use super::abbreviation::{Abbreviations, extract_abbreviations};
+use super::alert::replace_alerts;
+use super::assets::{Feature, PageAssets, ScriptTag};
+use super::citation::render_citations;
 use super::emoji::replace_emojis;
+use super::highlight::CodeOptions;
 use super::icon::replace_icons;
 use super::image_attrs::extract_image_attrs;
-use super::markdown::render_markdown;
-use super::toc::render_toc_html;
+use super::link::PageLinks;
+use super::markdown::{MarkdownOptions, markdown_options, render_markdown};
+use super::reference::{FootnoteDefinition, PageRefs, number_footnotes, resolve_link_aliases};
+use super::responsive::{ImageResolver, ImageVariant};
+use super::sanitize::sanitize_markdown;
+use super::strict::ensure_strict;
+use super::toc::{TocEntry, TocNode, build_toc_tree, render_toc_html};
 use crate::directive::callout::render_callout;
+use crate::directive::csv::{read_csv_file, render_csv_table};
 use crate::directive::div::render_div;
+use crate::directive::gallery::{gallery_images, render_gallery};
 use crate::directive::parser::parse_directives;
+use crate::directive::spoiler::{self, render_spoiler};
 use crate::directive::{DirectiveBlock, DirectiveContext, DirectiveKind};
+use crate::html::escape;
 use crate::template::TemplateEngine;
+use crate::template::vars::TocTemplateVars;
 
 /// The fully rendered output of a single page.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RenderedPage {
     pub content_html: String,
     pub toc_html: String,
+    /// The same headings as `toc_html`, as a tree for themes that render
+    /// their own `ToC` markup.
+    pub toc_entries: Vec<TocNode>,
     /// Page-level asset declarations rolled up from the markdown body and any
     /// nested directive bodies. Templates iterate this to load conditional
     /// runtime dependencies (`KaTeX` for math, `mermaid.js` for diagrams).
     pub assets: PageAssets,
+    /// Bundle image variants the page's `srcset`s refer to, for the build to
+    /// write next to the bundle's assets.
+    pub images: Vec<ImageVariant>,
 }
 
-/// Renders raw markdown through the full pipeline: directive processing,
-/// markdown rendering, and `ToC` generation.
+/// Page-level state shared by the body and every nested directive fragment.
+#[derive(Default)]
+struct PageState {
+    assets: PageAssets,
+    /// Link reference and footnote definitions from every fragment.
+    refs: PageRefs,
+    /// Footnote definitions lifted out of directive bodies.
+    footnotes: Vec<FootnoteDefinition>,
+    /// Heading IDs already assigned anywhere on the page.
+    used_ids: HashSet<String>,
+    /// Headings collected from directive bodies.
+    headings: Vec<TocEntry>,
+    /// Bodies of `raw` directives, restored in place of their
+    /// [`raw_placeholder`]s once the page is rendered.
+    raw_bodies: Vec<String>,
+    /// Image variants referenced from directive bodies.
+    images: Vec<ImageVariant>,
+}
+
+/// Renders raw markdown through the full pipeline: alert rewriting, HTML
+/// sanitization, abbreviation collection, directive processing, markdown
+/// rendering, `ToC` generation, citation numbering, `link:` alias
+/// resolution, and external link decoration.
+///
+/// The `ToC` is rendered through the theme's `toc.html` template when one
+/// exists, falling back to the built-in `<nav class="toc">` markup.
+///
+/// When the page contains a Mermaid diagram and `options.mermaid_script` is
+/// set, that script is registered once on the page's [`PageAssets`].
 ///
 /// # Errors
 ///
-/// Returns an error if a template-based directive fails to render.
+/// Returns an error if a template-based directive or `toc.html` fails to
+/// render, a link names a content file that is not a page, a citation
+/// names a key missing from the page's bibliography, or, with
+/// `options.strict`, [`ensure_strict`] finds a problem.
 pub fn render_page(
     raw_content: &str,
     syntax_set: &SyntaxSet,
@@ -40,8 +91,22 @@ pub fn render_page(
     options: &RenderOptions,
     source_dir: Option<&Path>,
 ) -> Result<RenderedPage> {
-    let mut assets = PageAssets::default();
-    let processed = render_directives(raw_content, syntax_set, engine, source_dir, &mut assets)?;
+    if options.strict {
+        ensure_strict(raw_content, syntax_set)?;
+    }
+    let (with_alerts, refs) = collect_refs(replace_alerts(raw_content), options, source_dir);
+    let mut state = PageState {
+        refs,
+        ..PageState::default()
+    };
+    let processed = render_directives(
+        &with_alerts,
+        syntax_set,
+        engine,
+        options,
+        source_dir,
+        &mut state,
+    )?;
 
     // Pre-process: extract image attrs, optionally replace shortcodes.
     let mut preprocessed = processed;
@@ -57,34 +122,167 @@ pub fn render_page(
         &cleaned,
         syntax_set,
         &image_attrs,
-        options.code_max_lines,
-        &mut assets.features,
-    );
-    let toc_html = render_toc_html(&md_output.headings);
+        MarkdownOptions {
+            code: CodeOptions {
+                max_lines: options.code_max_lines,
+                line_numbers: options.code_line_numbers,
+                classes: options.code_classes,
+                class_prefix: &options.code_class_prefix,
+                cache: options.highlight_cache.as_deref(),
+            },
+            heading_ids: options.heading_ids,
+            slugify: options.slugify,
+            heading_anchor: options.heading_anchor.as_ref(),
+            title_case: options.title_case.as_ref(),
+            images: image_resolver(options, source_dir),
+        },
+        &mut state.assets.features,
+        &mut state.used_ids,
+        &state.refs,
+    )?;
+    let mut headings = state.headings;
+    headings.extend(md_output.headings);
+    let (toc_html, toc_entries) = render_toc(headings, &md_output.html, engine, options)?;
+
+    let mut footnotes = state.footnotes;
+    footnotes.extend(md_output.footnotes);
+    let mut content_html = number_footnotes(&md_output.html, &footnotes);
+    if let Some(bibliography) = &options.bibliography {
+        content_html = render_citations(
+            &content_html,
+            bibliography,
+            options.label("references", "References"),
+        )?;
+    }
+    if !options.links.is_empty() {
+        content_html = resolve_link_aliases(&content_html, &options.links);
+    }
+    if let Some(external_links) = &options.external_links {
+        content_html = external_links.decorate(&content_html);
+    }
+    for (index, body) in state.raw_bodies.iter().enumerate() {
+        content_html = content_html.replacen(&raw_placeholder(index), &format!("{body}\n"), 1);
+    }
+
+    let mut assets = state.assets;
+    if let Some(url) = &options.mermaid_script
+        && assets.features.contains(&Feature::Mermaid)
+    {
+        assets.register_script(ScriptTag::deferred(url))?;
+    }
 
     Ok(RenderedPage {
-        content_html: md_output.html,
+        content_html,
         toc_html,
+        toc_entries,
         assets,
+        images: state.images.into_iter().chain(md_output.images).collect(),
+    })
+}
+
+/// Sanitizes the raw HTML in `content` when `[markdown] sanitize` is set and
+/// strips its abbreviation definitions, returning the rest and the page-wide
+/// definitions every fragment of the page resolves against.
+fn collect_refs(
+    mut content: String,
+    options: &RenderOptions,
+    source_dir: Option<&Path>,
+) -> (String, PageRefs) {
+    if options.markdown.sanitize {
+        content = sanitize_markdown(&content, markdown_options(options.markdown));
+    }
+    let (content, abbreviations) = if options.markdown.abbreviations {
+        extract_abbreviations(&content)
+    } else {
+        (content, Abbreviations::default())
+    };
+    let mut refs = PageRefs::collect(&content, options.markdown);
+    refs.abbreviations = abbreviations;
+    refs.citations = options.bibliography.is_some();
+    refs.page_links = options
+        .content_links
+        .clone()
+        .map(|site| PageLinks::new(site, source_dir));
+    (content, refs)
+}
+
+/// Returns the resolver for images of a page at `source_dir`, when there is
+/// anywhere to look them up.
+fn image_resolver<'a>(
+    options: &'a RenderOptions,
+    source_dir: Option<&'a Path>,
+) -> Option<ImageResolver<'a>> {
+    if source_dir.is_none() && options.static_dirs.is_empty() {
+        return None;
+    }
+    Some(ImageResolver {
+        config: &options.images,
+        bundle_dir: source_dir,
+        static_dirs: &options.static_dirs,
     })
 }
 
+/// Renders the table of contents of the page `html` from its `headings`,
+/// returning its HTML and entry tree. Both are empty with `toc_levels` unset.
+fn render_toc(
+    headings: Vec<TocEntry>,
+    html: &str,
+    engine: &TemplateEngine,
+    options: &RenderOptions,
+) -> Result<(String, Vec<TocNode>)> {
+    let Some(levels) = &options.toc_levels else {
+        return Ok((String::new(), Vec::new()));
+    };
+    let headings = order_by_position(headings, html);
+    let toc_entries = build_toc_tree(&headings, levels);
+    if toc_entries.is_empty() {
+        return Ok((String::new(), toc_entries));
+    }
+    let vars = TocTemplateVars {
+        toc_entries: &toc_entries,
+    };
+    let toc_html = match engine.render_toc(&vars) {
+        Some(html) => html?,
+        None => render_toc_html(&headings, levels),
+    };
+    Ok((toc_html, toc_entries))
+}
+
+/// Orders headings gathered from separately rendered fragments by where their
+/// opening tag lands in the final page HTML.
+///
+/// Headings whose tag is absent (e.g., a directive template that drops
+/// `body_html`) are left out of the `ToC`, since there is nothing to link to.
+fn order_by_position(headings: Vec<TocEntry>, html: &str) -> Vec<TocEntry> {
+    let mut positioned: Vec<(usize, TocEntry)> = headings
+        .into_iter()
+        .filter_map(|entry| {
+            let tag = format!(r#"<{} id="{}">"#, entry.level, escape(&entry.id));
+            html.find(&tag).map(|pos| (pos, entry))
+        })
+        .collect();
+    positioned.sort_by_key(|(pos, _)| *pos);
+    positioned.into_iter().map(|(_, entry)| entry).collect()
+}
+
 /// Recursively processes directive blocks in content, replacing them with
 /// rendered HTML.
 ///
 /// Top-level blocks are rendered first (their bodies are recursively processed),
 /// then replaced right-to-left so byte offsets stay valid.
 ///
-/// Each directive body is rendered as an isolated markdown document. This means:
-/// - Headings inside directives do **not** appear in the page-level `ToC`.
-/// - Footnotes and reference-link definitions do not resolve across directive
-///   boundaries.
+/// Each directive body is rendered as its own markdown document, sharing the
+/// page's heading ID set so IDs stay unique. Body headings are collected into
+/// `state.headings` for the page-level `ToC`. Reference links and footnotes
+/// resolve against `state.refs`, and footnote definitions are collected into
+/// `state.footnotes` so every footnote lands in one list at the page end.
 fn render_directives(
     content: &str,
     syntax_set: &SyntaxSet,
     engine: &TemplateEngine,
+    options: &RenderOptions,
     source_dir: Option<&Path>,
-    assets: &mut PageAssets,
+    state: &mut PageState,
 ) -> Result<String> {
     let all_blocks = parse_directives(content);
     if all_blocks.is_empty() {
@@ -96,16 +294,52 @@ fn render_directives(
 
     // Replace right-to-left so earlier ranges remain valid.
     for block in top_level.into_iter().rev() {
-        let inner = render_directives(&block.body, syntax_set, engine, source_dir, assets)?;
+        // CSV bodies are data, gallery bodies unused, and raw bodies
+        // verbatim: none of them is markdown.
+        if matches!(
+            block.kind,
+            DirectiveKind::Csv { .. } | DirectiveKind::Gallery { .. } | DirectiveKind::Raw
+        ) {
+            let html = render_directive_block(block, "", engine, options, source_dir, state)?;
+            if let Some(deps) = options.directive_assets.get(block.kind.name()) {
+                state.assets.register_directive(deps)?;
+            }
+            result.replace_range(block.range.clone(), &format!("\n{html}\n"));
+            continue;
+        }
+
+        let inner = render_directives(&block.body, syntax_set, engine, options, source_dir, state)?;
         let (cleaned, image_attrs) = extract_image_attrs(&inner);
         let md_output = render_markdown(
             &cleaned,
             syntax_set,
             &image_attrs,
-            None,
-            &mut assets.features,
-        );
-        let html = render_directive_block(block, &md_output.html, engine, source_dir)?;
+            MarkdownOptions {
+                code: CodeOptions {
+                    max_lines: None,
+                    line_numbers: options.code_line_numbers,
+                    classes: options.code_classes,
+                    class_prefix: &options.code_class_prefix,
+                    cache: options.highlight_cache.as_deref(),
+                },
+                heading_ids: options.heading_ids,
+                slugify: options.slugify,
+                heading_anchor: options.heading_anchor.as_ref(),
+                title_case: options.title_case.as_ref(),
+                images: image_resolver(options, source_dir),
+            },
+            &mut state.assets.features,
+            &mut state.used_ids,
+            &state.refs,
+        )?;
+        state.headings.extend(md_output.headings);
+        state.footnotes.extend(md_output.footnotes);
+        state.images.extend(md_output.images);
+        let html =
+            render_directive_block(block, &md_output.html, engine, options, source_dir, state)?;
+        if let Some(deps) = options.directive_assets.get(block.kind.name()) {
+            state.assets.register_directive(deps)?;
+        }
 
         // Blank-line padding: <details> / <div> are CommonMark type 6 HTML
         // blocks which cannot interrupt paragraphs. Safe because the directive
@@ -120,7 +354,7 @@ fn render_directives(
 /// Filters to only top-level directive blocks (those not nested inside another).
 ///
 /// Assumes `blocks` are sorted by ascending `range.start`.
-fn top_level_blocks(blocks: &[DirectiveBlock]) -> Vec<&DirectiveBlock> {
+pub(super) fn top_level_blocks(blocks: &[DirectiveBlock]) -> Vec<&DirectiveBlock> {
     let mut result = Vec::new();
     let mut outer_end: usize = 0;
 
@@ -134,16 +368,28 @@ fn top_level_blocks(blocks: &[DirectiveBlock]) -> Vec<&DirectiveBlock> {
     result
 }
 
+/// Returns the HTML comment standing in for the `index`-th `raw` directive
+/// body until the rest of the page is rendered, so no later pass touches it.
+fn raw_placeholder(index: usize) -> String {
+    format!("<!--kiln-raw:{index}-->\n")
+}
+
 /// Dispatches a directive block to its renderer.
 ///
-/// For `Unknown` directives, checks the template engine for a
-/// `directives/<name>.html` template. Falls back to `render_div` if no
-/// template exists.
+/// `Csv` directives read their data from the block body, or from `file` in
+/// `source_dir`, ignoring `body_html`. `Gallery` directives list the images
+/// among `options.bundle_assets`, collecting their responsive variants into
+/// `state.images`. `Raw` directives stash their body in `state.raw_bodies`
+/// behind a [`raw_placeholder`]. For `Unknown` directives, checks the
+/// template engine for a `directives/<name>.html` template. Falls back to
+/// `render_div` if no template exists.
 fn render_directive_block(
     block: &DirectiveBlock,
     body_html: &str,
     engine: &TemplateEngine,
+    options: &RenderOptions,
     source_dir: Option<&Path>,
+    state: &mut PageState,
 ) -> Result<String> {
     let id = block.id.as_deref();
     let classes = &block.classes;
@@ -157,6 +403,45 @@ fn render_directive_block(
             classes,
             body_html,
         )),
+        DirectiveKind::Spoiler { label } => {
+            let label = label
+                .as_deref()
+                .unwrap_or_else(|| options.label("spoiler", spoiler::DEFAULT_LABEL));
+            Ok(render_spoiler(label, id, classes, body_html))
+        }
+        DirectiveKind::Raw => {
+            state.raw_bodies.push(block.body.clone());
+            Ok(raw_placeholder(state.raw_bodies.len() - 1))
+        }
+        DirectiveKind::Csv {
+            file,
+            header,
+            delimiter,
+        } => {
+            let data = match file {
+                Some(file) => read_csv_file(file, source_dir)?,
+                None => block.body.clone(),
+            };
+            render_csv_table(&data, *header, *delimiter, id, classes)
+        }
+        DirectiveKind::Gallery { glob } => {
+            let srcs = source_dir
+                .map(|dir| gallery_images(&options.bundle_assets, dir, glob.as_deref()))
+                .transpose()?
+                .unwrap_or_default();
+            let resolver = image_resolver(options, source_dir);
+            let images: Vec<_> = srcs
+                .into_iter()
+                .map(|src| {
+                    let image = resolver.and_then(|r| r.resolve(&src));
+                    if let Some(responsive) = image.as_ref().and_then(|i| i.responsive.as_ref()) {
+                        state.images.extend(responsive.variants.iter().cloned());
+                    }
+                    (src, image)
+                })
+                .collect();
+            Ok(render_gallery(&images, id, classes))
+        }
         DirectiveKind::Unknown {
             name,
             positional_args,
@@ -174,76 +459,681 @@ fn render_directive_block(
             };
             match engine.render_directive(name, ctx) {
                 Some(result) => result,
-                None => Ok(render_div(name, id, classes, body_html)),
+                None => Ok(render_div(name, id, classes, named_args, body_html)),
             }
         }
     }
-}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::sync::{Arc, LazyLock};
+
+    use indoc::indoc;
+
+    use super::*;
+    use crate::config::{Markdown, TitleCase};
+    use crate::i18n::I18n;
+    use crate::render::assets::DirectiveAssets;
+    use crate::render::citation::Bibliography;
+    use crate::render::external::ExternalLinks;
+    use crate::render::link::ContentLinks;
+    use crate::test_utils::{test_engine, test_i18n, write_test_file};
+    use crate::text::case::TitleCaser;
+
+    static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(two_face::syntax::extra_newlines);
+
+    fn render(input: &str) -> RenderedPage {
+        render_with(input, &test_engine())
+    }
+
+    fn render_with(input: &str, engine: &TemplateEngine) -> RenderedPage {
+        render_page(input, &SYNTAX_SET, engine, &RenderOptions::default(), None).unwrap()
+    }
+
+    // ── render_page ──
+
+    #[test]
+    fn render_page_no_directives() {
+        let page = render(indoc! {"
+            # Hello
+
+            Some text.
+        "});
+        assert!(
+            page.content_html.contains("<p>Some text.</p>"),
+            "html:\n{}",
+            page.content_html
+        );
+        assert!(
+            !page.toc_html.is_empty(),
+            "should generate ToC from heading"
+        );
+    }
+
+    #[test]
+    fn render_page_with_emojis_and_fontawesome() {
+        let engine = test_engine();
+        let options = RenderOptions {
+            emojis: true,
+            fontawesome: true,
+            ..RenderOptions::default()
+        };
+        let input = "Hello :smile: and :(fas fa-link):";
+        let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
+        assert!(
+            page.content_html.contains('\u{1f604}'),
+            "emoji should be replaced, html:\n{}",
+            page.content_html
+        );
+        assert!(
+            page.content_html.contains(r#"class="fas fa-link""#),
+            "icon should be replaced, html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_page_abbreviations_span_directive_bodies() {
+        let page = render(indoc! {"
+            ::: callout
+            Written in HTML.
+            :::
+
+            *[HTML]: HyperText Markup Language
+        "});
+        let html = &page.content_html;
+        assert!(
+            html.contains(r#"<abbr title="HyperText Markup Language">HTML</abbr>"#),
+            "html:\n{html}"
+        );
+        assert!(!html.contains("*[HTML]"), "html:\n{html}");
+    }
+
+    #[test]
+    fn render_page_sanitizes_raw_html_but_not_directive_output() {
+        let engine = test_engine();
+        let options = RenderOptions {
+            markdown: Markdown {
+                sanitize: true,
+                ..Markdown::default()
+            },
+            ..RenderOptions::default()
+        };
+        let input = indoc! {r#"
+            > [!NOTE]
+            > <img src="x.png" onerror="alert(1)"> [link](javascript:alert(1))
+
+            <script>alert(2)</script>
+        "#};
+        let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
+        let html = &page.content_html;
+        assert!(html.contains("callout"), "html:\n{html}");
+        assert!(html.contains(r#"<img src="x.png">"#), "html:\n{html}");
+        assert!(html.contains(r#"<a href="">link</a>"#), "html:\n{html}");
+        assert!(html.contains("&lt;script&gt;alert(2)"), "html:\n{html}");
+        assert!(!html.contains("<script>"), "html:\n{html}");
+    }
+
+    #[test]
+    fn render_page_without_markdown_extensions_is_commonmark() {
+        let engine = test_engine();
+        let options = RenderOptions {
+            markdown: Markdown {
+                tables: false,
+                footnotes: false,
+                tasklists: false,
+                strikethrough: false,
+                math: false,
+                heading_attributes: false,
+                definition_lists: false,
+                superscript: false,
+                subscript: false,
+                mark: false,
+                abbreviations: false,
+                sanitize: false,
+            },
+            ..RenderOptions::default()
+        };
+        let input = indoc! {"
+            ## Title {#custom}
+
+            | A | B |
+            |---|---|
+            | 1 | 2 |
+
+            - [x] Done
+
+            ~~kept~~ and $x$ with a note[^1], x^2^, H~2~O, and ==mark==.
+
+            Term
+            : Definition
+
+            [^1]: Note.
+
+            *[HTML]: HyperText Markup Language
+        "};
+        let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
+        let html = &page.content_html;
+        assert!(html.contains("Title {#custom}"), "html:\n{html}");
+        assert!(!html.contains("<table>"), "html:\n{html}");
+        assert!(!html.contains("<input"), "html:\n{html}");
+        assert!(html.contains("~~kept~~"), "html:\n{html}");
+        assert!(html.contains("$x$"), "html:\n{html}");
+        assert!(!html.contains("footnote"), "html:\n{html}");
+        assert!(html.contains("x^2^, H~2~O, and ==mark=="), "html:\n{html}");
+        assert!(!html.contains("<dl>"), "html:\n{html}");
+        assert!(html.contains("*[HTML]: HyperText"), "html:\n{html}");
+    }
+
+    #[test]
+    fn render_page_code_line_numbers_default_applies_to_directive_bodies() {
+        let engine = test_engine();
+        let options = RenderOptions {
+            code_line_numbers: false,
+            ..RenderOptions::default()
+        };
+        let input = indoc! {"
+            ```rust
+            fn outer() {}
+            ```
+
+            ::: callout
+            ```rust
+            fn inner() {}
+            ```
+            :::
+
+            ```rust linenos=true
+            fn opted_in() {}
+            ```
+        "};
+        let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
+        assert_eq!(
+            page.content_html
+                .matches(r#"<td class="line-numbers">"#)
+                .count(),
+            1,
+            "only the opted-in block should have line numbers, html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_page_resolves_link_aliases_in_directive_bodies() {
+        let engine = test_engine();
+        let options = RenderOptions {
+            links: [("rustbook".into(), "https://docs.example.org/book/".into())].into(),
+            ..RenderOptions::default()
+        };
+        let input = indoc! {"
+            See [the book](link:rustbook).
+
+            ::: callout
+            Read [chapter 1](link:rustbook/ch01-00.html).
+            :::
+        "};
+        let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
+        assert!(
+            page.content_html
+                .contains(r#"<a href="https://docs.example.org/book/">the book</a>"#),
+            "html:\n{}",
+            page.content_html
+        );
+        assert!(
+            page.content_html
+                .contains(r#"<a href="https://docs.example.org/book/ch01-00.html">chapter 1</a>"#),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_page_decorates_external_links_after_alias_resolution() {
+        let engine = test_engine();
+        let options = RenderOptions {
+            links: [("rustbook".into(), "https://docs.example.org/book/".into())].into(),
+            external_links: Some(ExternalLinks::new("https://example.com", None)),
+            ..RenderOptions::default()
+        };
+        let input = "[The book](link:rustbook) and [home](https://example.com/).\n";
+        let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
+        assert!(
+            page.content_html.contains(
+                r#"<a href="https://docs.example.org/book/" target="_blank" rel="noopener noreferrer">The book</a>"#
+            ),
+            "html:\n{}",
+            page.content_html
+        );
+        assert!(
+            page.content_html
+                .contains(r#"<a href="https://example.com/">home</a>"#),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_page_numbers_citations_across_fragments() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("refs.bib"),
+            indoc! {"
+                @article{castellan84, author = {Mira Castellan}, title = {Annotated Programs}}
+                @book{ferrow96, author = {Oren Ferrow}, title = {Imaginary Machines}}
+            "},
+        )
+        .unwrap();
+        let options = RenderOptions {
+            bibliography: Some(Bibliography::load(&dir.path().join("refs.bib")).unwrap()),
+            ..RenderOptions::default()
+        };
+        let input = indoc! {"
+            As shown in [@castellan84].
+
+            ::: callout
+            Compare [@ferrow96; @castellan84].
+            :::
+
+            ```text
+            [@castellan84]
+            ```
+        "};
+        let page = render_page(input, &SYNTAX_SET, &test_engine(), &options, None).unwrap();
+        let html = &page.content_html;
+        assert!(
+            html.contains(
+                r##"As shown in <span class="citation">[<a href="#ref-castellan84">1</a>]</span>."##
+            ),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains(r##"Compare <span class="citation">[<a href="#ref-ferrow96">2</a>, <a href="#ref-castellan84">1</a>]</span>."##),
+            "html:\n{html}"
+        );
+        assert!(
+            html.contains("[@castellan84]"),
+            "code should be left alone, html:\n{html}"
+        );
+        assert!(
+            html.find(r#"<li id="ref-castellan84">"#) < html.find(r#"<li id="ref-ferrow96">"#),
+            "html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn render_page_translates_references_heading() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(
+            dir.path(),
+            "refs.bib",
+            "@article{castellan84, author = {Mira Castellan}, title = {Annotated Programs}}",
+        );
+        write_test_file(
+            dir.path(),
+            "i18n/zh-Hans.toml",
+            r#"references = "参考文献""#,
+        );
+        let options = RenderOptions {
+            bibliography: Some(Bibliography::load(&dir.path().join("refs.bib")).unwrap()),
+            i18n: Some(I18n::load(dir.path(), None, "zh-Hans").unwrap()),
+            ..RenderOptions::default()
+        };
+        let page = render_page(
+            "As shown in [@castellan84].",
+            &SYNTAX_SET,
+            &test_engine(),
+            &options,
+            None,
+        )
+        .unwrap();
+        assert!(
+            page.content_html.contains("<h2>参考文献</h2>"),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_page_translates_default_spoiler_label() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "i18n/zh-Hans.toml", r#"spoiler = "剧透""#);
+        let options = RenderOptions {
+            i18n: Some(I18n::load(dir.path(), None, "zh-Hans").unwrap()),
+            ..RenderOptions::default()
+        };
+        let page = render_page(
+            "::: spoiler\nThe map was upside down.\n:::\n",
+            &SYNTAX_SET,
+            &test_engine(),
+            &options,
+            None,
+        )
+        .unwrap();
+        assert!(
+            page.content_html
+                .contains(r#"<summary class="spoiler-label">剧透</summary>"#),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_page_resolves_content_links_in_directive_bodies() {
+        let mut site = ContentLinks::new(Path::new("/site/content"));
+        site.insert(
+            Path::new("/site/content/posts/b/index.md"),
+            "/posts/b/".into(),
+        );
+        let options = RenderOptions {
+            content_links: Some(Arc::new(site)),
+            ..RenderOptions::default()
+        };
+        let input = indoc! {"
+            ::: callout
+            Next: [B](../b/index.md).
+            :::
+        "};
+        let source_dir = Path::new("/site/content/posts/a");
+        let page = render_page(
+            input,
+            &SYNTAX_SET,
+            &test_engine(),
+            &options,
+            Some(source_dir),
+        )
+        .unwrap();
+        assert!(
+            page.content_html.contains(r#"<a href="/posts/b/">B</a>"#),
+            "html:\n{}",
+            page.content_html
+        );
+
+        let err = render_page(
+            "[C](../c/index.md)",
+            &SYNTAX_SET,
+            &test_engine(),
+            &options,
+            Some(source_dir),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("broken link"), "got: {err}");
+    }
+
+    #[test]
+    fn render_page_leaves_citations_without_bibliography() {
+        let page = render("See [@castellan84].");
+        assert!(
+            page.content_html.contains("<p>See [@castellan84].</p>"),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_page_renders_alert_as_callout() {
+        let page = render(indoc! {"
+            > [!TIP] Shortcut
+            > Use **this**.
+        "});
+        assert!(
+            page.content_html
+                .contains(r#"<details class="callout tip" open>"#),
+            "alert should render as callout, html:\n{}",
+            page.content_html
+        );
+        assert!(
+            page.content_html
+                .contains(r#"<summary class="callout-title">Shortcut</summary>"#),
+            "alert title should be used, html:\n{}",
+            page.content_html
+        );
+        assert!(
+            page.content_html.contains("<strong>this</strong>"),
+            "alert body should be markdown-rendered, html:\n{}",
+            page.content_html
+        );
+        assert!(
+            !page.content_html.contains("<blockquote>"),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
+    // ── render_directives ──
+
+    #[test]
+    fn render_directives_headings_join_page_toc_in_order() {
+        let page = render(indoc! {"
+            ## Intro
+
+            ::: callout
+            ## Setup
+            Body.
+            :::
+
+            ## Intro
+        "});
+        assert_eq!(
+            page.toc_html,
+            indoc! {r##"
+                <nav class="toc">
+                  <ul>
+                    <li><a href="#intro">Intro</a>
+                    </li>
+                    <li><a href="#setup">Setup</a>
+                    </li>
+                    <li><a href="#intro-1">Intro</a>
+                    </li>
+                  </ul>
+                </nav>
+            "##},
+        );
+        assert!(
+            page.content_html.contains(r#"<h2 id="setup">"#),
+            "html:\n{}",
+            page.content_html
+        );
+    }
 
-#[cfg(test)]
-mod tests {
-    use std::fs;
-    use std::sync::LazyLock;
+    #[test]
+    fn render_directives_heading_ids_unique_across_fragments() {
+        let page = render(indoc! {"
+            ::: callout
+            ## Notes
+            :::
 
-    use indoc::indoc;
+            ::: callout
+            ## Notes
+            :::
+        "});
+        assert!(
+            page.content_html.contains(r#"<h2 id="notes">"#)
+                && page.content_html.contains(r#"<h2 id="notes-1">"#),
+            "html:\n{}",
+            page.content_html
+        );
+        assert_eq!(
+            page.toc_html.matches("<li>").count(),
+            2,
+            "toc:\n{}",
+            page.toc_html
+        );
+    }
 
-    use super::*;
-    use crate::render::assets::Feature;
-    use crate::test_utils::{test_engine, test_i18n};
+    #[test]
+    fn render_directives_share_reference_links() {
+        let page = render(indoc! {"
+            ::: callout
+            See [the docs] and [inner].
+            :::
 
-    static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(two_face::syntax::extra_newlines);
+            Also [inner].
 
-    fn render(input: &str) -> RenderedPage {
-        render_with(input, &test_engine())
-    }
+            [the docs]: https://docs.example
 
-    fn render_with(input: &str, engine: &TemplateEngine) -> RenderedPage {
-        render_page(input, &SYNTAX_SET, engine, &RenderOptions::default(), None).unwrap()
+            ::: callout
+            [inner]: /inner
+            :::
+        "});
+        assert!(
+            page.content_html.contains(
+                r#"See <a href="https://docs.example">the docs</a> and <a href="/inner">inner</a>."#
+            ),
+            "html:\n{}",
+            page.content_html
+        );
+        assert!(
+            page.content_html
+                .contains(r#"Also <a href="/inner">inner</a>."#),
+            "html:\n{}",
+            page.content_html
+        );
     }
 
-    // ── render_page ──
-
     #[test]
-    fn render_page_no_directives() {
+    fn render_directives_share_footnotes() {
         let page = render(indoc! {"
-            # Hello
+            Intro[^a].
 
-            Some text.
+            ::: callout
+            Inside[^b] and again[^a].
+
+            [^b]: Defined in the callout.
+            :::
+
+            [^a]: Defined outside.
         "});
+        let html = &page.content_html;
         assert!(
-            page.content_html.contains("<p>Some text.</p>"),
-            "html:\n{}",
-            page.content_html
+            html.contains(r##"Intro<sup class="footnote-ref" id="fnref:1"><a href="#fn:1" role="doc-noteref">1</a></sup>."##),
+            "html:\n{html}"
         );
         assert!(
-            !page.toc_html.is_empty(),
-            "should generate ToC from heading"
+            html.contains(r##"Inside<sup class="footnote-ref" id="fnref:2"><a href="#fn:2" role="doc-noteref">2</a></sup> and again<sup class="footnote-ref" id="fnref:1-2"><a href="#fn:1" role="doc-noteref">1</a></sup>."##),
+            "html:\n{html}"
+        );
+
+        let callout_end = html.find("</details>").unwrap();
+        let def_a = html
+            .find(r#"<div class="footnote-definition" id="fn:1"><sup class="footnote-definition-label">1</sup>"#)
+            .unwrap();
+        let def_b = html
+            .find(r#"<div class="footnote-definition" id="fn:2"><sup class="footnote-definition-label">2</sup>"#)
+            .unwrap();
+        assert!(
+            callout_end < def_a && def_a < def_b,
+            "definitions should follow the content in number order, html:\n{html}"
         );
+        assert!(!html.contains("[^"), "html:\n{html}");
     }
 
     #[test]
-    fn render_page_with_emojis_and_fontawesome() {
+    fn render_directives_headings_dropped_by_template_excluded_from_toc() {
+        let dir = tempfile::tempdir().unwrap();
+        let directives = dir.path().join("directives");
+        fs::create_dir_all(&directives).unwrap();
+        fs::write(directives.join("hidden.html"), "<aside></aside>").unwrap();
+        let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
+
+        let page = render_with(
+            indoc! {"
+                ::: hidden
+                ## Gone
+                :::
+            "},
+            &engine,
+        );
+        assert_eq!(page.toc_html, "");
+    }
+
+    #[test]
+    fn render_page_toc_uses_template_when_present() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("toc.html"),
+            indoc! {"
+                <ol>
+                {%- for entry in toc_entries recursive -%}
+                <li>{{ entry.title }}{% if entry.children %}<ol>{{ loop(entry.children) }}</ol>{% endif %}</li>
+                {%- endfor -%}
+                </ol>
+            "},
+        )
+        .unwrap();
+        let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
+
+        let page = render_with("## Intro\n\n### Detail\n\n## Outro\n", &engine);
+        assert_eq!(
+            page.toc_html.trim(),
+            "<ol><li>Intro<ol><li>Detail</li></ol></li><li>Outro</li></ol>"
+        );
+
+        let page = render_with("No headings.\n", &engine);
+        assert_eq!(page.toc_html, "");
+    }
+
+    #[test]
+    fn render_page_toc_levels() {
         let engine = test_engine();
+        let input = "# Title\n\n## Intro\n\n### Detail\n";
         let options = RenderOptions {
-            emojis: true,
-            fontawesome: true,
+            toc_levels: Some(2..=2),
             ..RenderOptions::default()
         };
-        let input = "Hello :smile: and :(fas fa-link):";
         let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
         assert!(
-            page.content_html.contains('\u{1f604}'),
-            "emoji should be replaced, html:\n{}",
-            page.content_html
+            page.toc_html.contains(r##"href="#intro""##),
+            "toc:\n{}",
+            page.toc_html
         );
         assert!(
-            page.content_html.contains(r#"class="fas fa-link""#),
-            "icon should be replaced, html:\n{}",
-            page.content_html
+            !page.toc_html.contains(r##"href="#title""##),
+            "toc:\n{}",
+            page.toc_html
+        );
+        assert!(
+            !page.toc_html.contains(r##"href="#detail""##),
+            "toc:\n{}",
+            page.toc_html
         );
+
+        let options = RenderOptions {
+            toc_levels: None,
+            ..RenderOptions::default()
+        };
+        let page = render_page(input, &SYNTAX_SET, &engine, &options, None).unwrap();
+        assert_eq!(page.toc_html, "");
+        assert!(page.content_html.contains(r#"<h2 id="intro">"#));
     }
 
-    // ── render_directives ──
+    #[test]
+    fn render_page_applies_title_case_to_headings() {
+        let engine = test_engine();
+        let options = RenderOptions {
+            title_case: TitleCaser::new(TitleCase::Title, "en", &[]),
+            ..RenderOptions::default()
+        };
+        let page = render_page(
+            "## using `serde` in the app\n",
+            &SYNTAX_SET,
+            &engine,
+            &options,
+            None,
+        )
+        .unwrap();
+        assert!(
+            page.toc_html.contains("Using serde in the App"),
+            "toc:\n{}",
+            page.toc_html
+        );
+        assert_eq!(
+            page.content_html,
+            "<h2 id=\"using-serde-in-the-app\">Using <code>serde</code> in the App</h2>\n"
+        );
+    }
 
     #[test]
     fn render_directives_sequential() {
@@ -367,6 +1257,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_directive_csv_body() {
+        let page = render(indoc! {"
+            Before.
+
+            ::: csv {#scores .compact}
+            Name,Score
+            # Alice,*90*
+            :::
+        "});
+        assert_eq!(
+            page.content_html,
+            indoc! {r#"
+                <p>Before.</p>
+                <table id="scores" class="csv-table compact">
+                  <thead>
+                    <tr><th>Name</th><th>Score</th></tr>
+                  </thead>
+                  <tbody>
+                    <tr><td># Alice</td><td>*90*</td></tr>
+                  </tbody>
+                </table>
+            "#}
+        );
+        assert!(page.toc_html.is_empty(), "CSV rows are not headings");
+    }
+
+    #[test]
+    fn render_directive_csv_file() {
+        let source = tempfile::tempdir().unwrap();
+        fs::write(source.path().join("data.tsv"), "a\tb\n").unwrap();
+
+        let page = render_page(
+            "::: csv {file=data.tsv delimiter=tab header=false}\n:::\n",
+            &SYNTAX_SET,
+            &test_engine(),
+            &RenderOptions::default(),
+            Some(source.path()),
+        )
+        .unwrap();
+        assert!(
+            page.content_html.contains("<tr><td>a</td><td>b</td></tr>"),
+            "html:\n{}",
+            page.content_html
+        );
+
+        let err = render_page(
+            "::: csv {file=missing.csv}\n:::\n",
+            &SYNTAX_SET,
+            &test_engine(),
+            &RenderOptions::default(),
+            Some(source.path()),
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("missing.csv"), "{err:#}");
+    }
+
+    #[test]
+    fn render_directive_gallery() {
+        let source = tempfile::tempdir().unwrap();
+        let photos = source.path().join("photos");
+        fs::create_dir(&photos).unwrap();
+        image::RgbImage::new(8, 6)
+            .save(photos.join("a.png"))
+            .unwrap();
+        fs::write(source.path().join("cover.jpg"), "not an image").unwrap();
+        let options = RenderOptions {
+            bundle_assets: vec![source.path().join("cover.jpg"), photos.join("a.png")],
+            ..RenderOptions::default()
+        };
+
+        let page = render_page(
+            "::: gallery {glob=\"photos/*\"}\nignored\n:::\n",
+            &SYNTAX_SET,
+            &test_engine(),
+            &options,
+            Some(source.path()),
+        )
+        .unwrap();
+        assert_eq!(
+            page.content_html,
+            indoc! {r#"
+                <div class="gallery">
+                  <figure><a href="photos/a.png"><img src="photos/a.png" alt="a" width="8" height="6" loading="lazy" decoding="async" /></a></figure>
+                </div>
+            "#}
+        );
+    }
+
     #[test]
     fn render_directive_with_id_and_classes() {
         let page = render(indoc! {"
@@ -387,6 +1366,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_directive_spoiler_renders_markdown_body() {
+        let page = render(indoc! {r#"
+            ::: spoiler {label="Answer"}
+            It was **the lighthouse keeper**.
+            :::
+        "#});
+        assert_eq!(
+            page.content_html,
+            indoc! {r#"
+                <details class="spoiler">
+                  <summary class="spoiler-label">Answer</summary>
+                  <div class="spoiler-body"><p>It was <strong>the lighthouse keeper</strong>.</p>
+                </div>
+                </details>
+            "#}
+        );
+    }
+
+    #[test]
+    fn render_directive_raw_is_verbatim() {
+        let page = render(indoc! {"
+            ::: callout
+            :::: raw
+            <div id=\"widget\">
+
+                *not emphasis* :smile:
+            ::: note
+            :::
+            </div>
+            ::::
+            :::
+        "});
+        assert!(
+            page.content_html.contains(indoc! {r#"
+                <div class="callout-body"><div id="widget">
+
+                    *not emphasis* :smile:
+                ::: note
+                :::
+                </div>
+                </div>
+            "#}),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
+    #[test]
+    fn render_directive_raw_is_sanitized() {
+        let options = RenderOptions {
+            markdown: Markdown {
+                sanitize: true,
+                ..Markdown::default()
+            },
+            ..RenderOptions::default()
+        };
+        let page = render_page(
+            "::: raw\n<b>bold</b><script>alert(1)</script>\n:::\n",
+            &SYNTAX_SET,
+            &test_engine(),
+            &options,
+            None,
+        )
+        .unwrap();
+        assert!(
+            page.content_html.contains("<b>bold</b>&lt;script>"),
+            "html:\n{}",
+            page.content_html
+        );
+    }
+
     #[test]
     fn render_directive_with_code_and_math() {
         let page = render(indoc! {"
@@ -479,6 +1530,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_page_registers_mermaid_script_once() {
+        let options = RenderOptions {
+            mermaid_script: Some("/js/mermaid.min.js".into()),
+            ..RenderOptions::default()
+        };
+        let input = indoc! {"
+            ```mermaid
+            graph TD
+              A --> B
+            ```
+
+            ::: callout
+            ```mermaid
+            graph LR
+              C --> D
+            ```
+            :::
+        "};
+        let page = render_page(input, &SYNTAX_SET, &test_engine(), &options, None).unwrap();
+        assert_eq!(
+            page.assets.scripts,
+            vec![ScriptTag::deferred("/js/mermaid.min.js")],
+        );
+    }
+
+    #[test]
+    fn render_page_skips_mermaid_script_without_diagram() {
+        let options = RenderOptions {
+            mermaid_script: Some("/js/mermaid.min.js".into()),
+            ..RenderOptions::default()
+        };
+        let page = render_page(
+            "No diagrams here.",
+            &SYNTAX_SET,
+            &test_engine(),
+            &options,
+            None,
+        )
+        .unwrap();
+        assert!(
+            page.assets.scripts.is_empty(),
+            "scripts: {:?}",
+            page.assets.scripts
+        );
+    }
+
+    #[test]
+    fn render_page_registers_directive_assets() {
+        let options = RenderOptions {
+            directive_assets: BTreeMap::from([
+                (
+                    "gallery".into(),
+                    DirectiveAssets {
+                        styles: vec!["/css/gallery.css".into()],
+                        scripts: vec!["/js/gallery.js".into()],
+                    },
+                ),
+                (
+                    "chart".into(),
+                    DirectiveAssets {
+                        styles: Vec::new(),
+                        scripts: vec!["/js/chart.js".into()],
+                    },
+                ),
+            ]),
+            ..RenderOptions::default()
+        };
+        let input = indoc! {"
+            ::: gallery
+            First.
+            :::
+
+            :::: callout
+            ::: gallery
+            Nested.
+            :::
+            ::::
+        "};
+        let page = render_page(input, &SYNTAX_SET, &test_engine(), &options, None).unwrap();
+        assert_eq!(page.assets.styles, ["/css/gallery.css"]);
+        assert_eq!(
+            page.assets.scripts,
+            [ScriptTag::deferred("/js/gallery.js")],
+            "unused directives should not register assets"
+        );
+    }
+
     #[test]
     fn render_page_no_features_for_plain_content() {
         let page = render(indoc! {"
@@ -558,7 +1697,7 @@ mod tests {
     }
 
     #[test]
-    fn render_directive_template_accesses_source_dir() {
+    fn render_directive_template_reads_bundle_files() {
         let dir = tempfile::tempdir().unwrap();
         let directives = dir.path().join("directives");
         fs::create_dir_all(&directives).unwrap();
@@ -572,20 +1711,23 @@ mod tests {
         fs::write(source.path().join("data.csv"), "A,B\n1,2").unwrap();
 
         let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
-        let page = render_page(
-            indoc! {r#"
-                ::: reader {"data.csv"}
-                :::
-            "#},
-            &SYNTAX_SET,
-            &engine,
-            &RenderOptions::default(),
-            Some(source.path()),
-        )
-        .unwrap();
+        let page = engine
+            .with_bundle_dir(Some(source.path()), || {
+                render_page(
+                    indoc! {r#"
+                        ::: reader {"data.csv"}
+                        :::
+                    "#},
+                    &SYNTAX_SET,
+                    &engine,
+                    &RenderOptions::default(),
+                    Some(source.path()),
+                )
+            })
+            .unwrap();
         assert!(
             page.content_html.contains("DATA:A,B\n1,2"),
-            "template should read file via source_dir, html:\n{}",
+            "template should read file from the bundle, html:\n{}",
             page.content_html
         );
     }
@@ -0,0 +1,480 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use pulldown_cmark::{BrokenLink, BrokenLinkCallback, CowStr, Event, LinkType, Parser, Tag};
+use tracing::warn;
+
+use super::abbreviation::Abbreviations;
+use super::citation::{CITATION_SCHEME, parse_citation};
+use super::link::PageLinks;
+use super::markdown::markdown_options;
+use super::pipeline::top_level_blocks;
+use crate::config::Markdown;
+use crate::directive::DirectiveKind;
+use crate::directive::parser::parse_directives;
+use crate::html::escape;
+
+/// Opening markup of an unnumbered footnote reference placeholder, replaced
+/// by [`number_footnotes`] once the whole page has been assembled.
+const REFERENCE_OPEN: &str = r#"<sup class="footnote-ref" data-footnote=""#;
+
+/// Closing markup of an unnumbered footnote reference placeholder.
+const REFERENCE_CLOSE: &str = r#""></sup>"#;
+
+/// URL scheme of site-wide link aliases: `[text](link:name)`.
+const LINK_ALIAS_SCHEME: &str = "link:";
+
+/// Link reference and footnote definitions, and other link resolution state,
+/// visible to every fragment of a page.
+///
+/// Directive bodies are rendered as separate markdown documents, so without a
+/// shared scope a `[label]` or `[^note]` defined outside a callout would not
+/// resolve inside it (and vice versa).
+#[derive(Debug, Default)]
+pub(crate) struct PageRefs {
+    /// Link reference definitions as `(destination, title)`, keyed by
+    /// normalized label. The first definition of a label wins.
+    links: HashMap<String, (String, String)>,
+    /// Footnote labels defined anywhere on the page.
+    footnotes: BTreeSet<String>,
+    /// Whether undefined `[@key]` links are citations, i.e. the page has a
+    /// bibliography.
+    pub(crate) citations: bool,
+    /// Output URLs for links to content files (`../other/index.md`).
+    pub(crate) page_links: Option<PageLinks>,
+    /// Enabled syntax extensions, from `[markdown]`.
+    pub(crate) extensions: Markdown,
+    /// Abbreviations defined anywhere on the page; see
+    /// [`extract_abbreviations`](super::abbreviation::extract_abbreviations).
+    pub(crate) abbreviations: Abbreviations,
+}
+
+impl PageRefs {
+    /// Collects definitions from the page body and every (nested) directive
+    /// body in `content`, parsed with `extensions`.
+    pub(crate) fn collect(content: &str, extensions: Markdown) -> Self {
+        let mut refs = Self {
+            extensions,
+            ..Self::default()
+        };
+        refs.collect_fragment(content);
+        refs
+    }
+
+    fn collect_fragment(&mut self, content: &str) {
+        let blocks = parse_directives(content);
+        let top_level = top_level_blocks(&blocks);
+
+        // Blank out directive blocks so their bodies are scanned as the
+        // standalone documents they are rendered as.
+        let mut outer = content.to_owned();
+        for block in top_level.iter().rev() {
+            outer.replace_range(block.range.clone(), "\n");
+        }
+
+        let parser = Parser::new_ext(&outer, markdown_options(self.extensions));
+        for (label, def) in parser.reference_definitions().iter() {
+            self.links.entry(normalize_label(label)).or_insert_with(|| {
+                let title = def.title.as_deref().unwrap_or_default();
+                (def.dest.to_string(), title.to_owned())
+            });
+        }
+        for event in parser {
+            if let Event::Start(Tag::FootnoteDefinition(label)) = event {
+                self.footnotes.insert(label.into_string());
+            }
+        }
+
+        // Raw bodies are emitted verbatim, so their definitions are not.
+        for block in top_level {
+            if block.kind != DirectiveKind::Raw {
+                self.collect_fragment(&block.body);
+            }
+        }
+    }
+
+    /// Appends one placeholder definition per page-wide footnote label to
+    /// `content`, so references to footnotes defined in other fragments are
+    /// still recognized by the parser.
+    ///
+    /// Events of footnote definitions starting at or past `content.len()`
+    /// belong to these placeholders and must be skipped.
+    pub(crate) fn fragment_source(&self, content: &str) -> String {
+        let mut source = content.to_owned();
+        if !self.footnotes.is_empty() {
+            source.push_str("\n\n");
+            for label in &self.footnotes {
+                _ = writeln!(source, "[^{label}]: -");
+            }
+        }
+        source
+    }
+
+    /// Creates a parser that resolves otherwise-undefined reference links
+    /// against the page-wide definitions.
+    ///
+    /// With [`citations`](Self::citations) enabled, remaining `[@key]` links
+    /// point at a `cite:` URL for
+    /// [`render_citations`](super::citation::render_citations) to number.
+    pub(crate) fn parser<'a>(&'a self, source: &'a str) -> Parser<'a, impl BrokenLinkCallback<'a>> {
+        let callback = |link: BrokenLink<'a>| {
+            if let Some((dest, title)) = self.links.get(&normalize_label(&link.reference)) {
+                return Some((CowStr::from(dest.as_str()), CowStr::from(title.as_str())));
+            }
+            (self.citations
+                && link.link_type == LinkType::Shortcut
+                && parse_citation(&link.reference).is_some())
+            .then(|| {
+                let dest = format!("{CITATION_SCHEME}{}", link.reference);
+                (CowStr::from(dest), CowStr::from(""))
+            })
+        };
+        Parser::new_with_broken_link_callback(
+            source,
+            markdown_options(self.extensions),
+            Some(callback),
+        )
+    }
+}
+
+/// Normalizes a link label for matching: case-insensitive, with runs of
+/// whitespace collapsed.
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// A footnote definition rendered out of its fragment, to be placed at the
+/// end of the page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FootnoteDefinition {
+    pub label: String,
+    /// Rendered body HTML, without the surrounding `<div>`.
+    pub html: String,
+}
+
+/// Returns the markup for a reference to footnote `label`, left unnumbered.
+pub(crate) fn footnote_reference_html(label: &str) -> String {
+    format!("{REFERENCE_OPEN}{}{REFERENCE_CLOSE}", escape(label))
+}
+
+/// Numbers footnote references across the assembled page HTML and appends
+/// the definitions.
+///
+/// Footnotes are numbered in order of first reference, matching `pulldown-cmark`
+/// for single-document pages. Definitions follow in number order; unreferenced
+/// definitions come last, and repeated labels keep only the first definition.
+///
+/// IDs are derived from the number (`fn:1`, `fnref:1`, `fnref:1-2` for the
+/// second reference), so they never collide with heading IDs, which contain
+/// no `:`. Each definition links back to every reference to it.
+#[must_use]
+pub(crate) fn number_footnotes(html: &str, definitions: &[FootnoteDefinition]) -> String {
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+    let mut references: Vec<usize> = Vec::new();
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(pos) = rest.find(REFERENCE_OPEN) {
+        let id_start = pos + REFERENCE_OPEN.len();
+        let Some(id_len) = rest[id_start..].find(REFERENCE_CLOSE) else {
+            break;
+        };
+        let id = &rest[id_start..id_start + id_len];
+        let next = numbers.len() + 1;
+        let number = *numbers.entry(id.to_lowercase()).or_insert(next);
+        if references.len() < number {
+            references.resize(number, 0);
+        }
+        references[number - 1] += 1;
+
+        output.push_str(&rest[..pos]);
+        _ = write!(
+            output,
+            r##"<sup class="footnote-ref" id="{}"><a href="#fn:{number}" role="doc-noteref">{number}</a></sup>"##,
+            reference_id(number, references[number - 1]),
+        );
+        rest = &rest[id_start + id_len + REFERENCE_CLOSE.len()..];
+    }
+    output.push_str(rest);
+
+    let mut placed = Vec::new();
+    let mut seen = BTreeSet::new();
+    for def in definitions {
+        let id = escape(&def.label).to_lowercase();
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        let next = numbers.len() + 1;
+        let number = *numbers.entry(id).or_insert(next);
+        placed.push((number, def));
+    }
+    if placed.is_empty() {
+        return output;
+    }
+    placed.sort_by_key(|(number, _)| *number);
+
+    output.push_str("<section class=\"footnotes\" role=\"doc-endnotes\">\n");
+    for (number, def) in placed {
+        let count = references.get(number - 1).copied().unwrap_or(0);
+        let backrefs = footnote_backrefs(number, count);
+        let body = def.html.trim_end();
+        let body = match body.strip_suffix("</p>") {
+            Some(paragraph) if !backrefs.is_empty() => format!("{paragraph} {backrefs}</p>"),
+            _ if !backrefs.is_empty() => format!("{body}\n{backrefs}"),
+            _ => body.to_owned(),
+        };
+        _ = writeln!(
+            output,
+            r#"<div class="footnote-definition" id="fn:{number}"><sup class="footnote-definition-label">{number}</sup>{body}</div>"#,
+        );
+    }
+    output.push_str("</section>\n");
+
+    output
+}
+
+/// Returns the ID of the `nth` (1-based) reference to footnote `number`.
+fn reference_id(number: usize, nth: usize) -> String {
+    if nth <= 1 {
+        format!("fnref:{number}")
+    } else {
+        format!("fnref:{number}-{nth}")
+    }
+}
+
+/// Returns the backlinks from footnote `number` to each of its `count`
+/// references, space-separated.
+fn footnote_backrefs(number: usize, count: usize) -> String {
+    (1..=count)
+        .map(|nth| {
+            let mark = if nth == 1 {
+                String::new()
+            } else {
+                format!("<sup>{nth}</sup>")
+            };
+            format!(
+                r##"<a href="#{}" class="footnote-backref" role="doc-backlink">↩{mark}</a>"##,
+                reference_id(number, nth),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Replaces `link:name` URLs in `href` and `src` attributes of the assembled
+/// page HTML with the matching entry of the site's `[links]` table.
+///
+/// Anything after the name (`link:rustbook/ch01-00.html#intro`) is appended
+/// to the target, without doubling the `/` between them. Unknown names are
+/// left as-is with a warning.
+#[must_use]
+pub(crate) fn resolve_link_aliases(html: &str, links: &BTreeMap<String, String>) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(value_start) = find_alias_value(rest) {
+        let Some(value_len) = rest[value_start..].find('"') else {
+            break;
+        };
+        let alias = &rest[value_start + LINK_ALIAS_SCHEME.len()..value_start + value_len];
+        let name_len = alias.find(['/', '#', '?']).unwrap_or(alias.len());
+        let (name, suffix) = alias.split_at(name_len);
+
+        output.push_str(&rest[..value_start]);
+        if let Some(target) = links.get(name) {
+            let suffix = if target.ends_with('/') {
+                suffix.strip_prefix('/').unwrap_or(suffix)
+            } else {
+                suffix
+            };
+            output.push_str(&escape(target));
+            output.push_str(suffix);
+        } else {
+            warn!(name, "unknown link alias; add it to [links] in config.toml");
+            output.push_str(&rest[value_start..value_start + value_len]);
+        }
+        rest = &rest[value_start + value_len..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Returns the offset of the next `link:` value of an `href` or `src`
+/// attribute.
+fn find_alias_value(html: &str) -> Option<usize> {
+    ["href=\"", "src=\""]
+        .into_iter()
+        .filter_map(|attr| {
+            let pattern = format!("{attr}{LINK_ALIAS_SCHEME}");
+            html.find(&pattern).map(|pos| pos + attr.len())
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── PageRefs::collect ──
+
+    #[test]
+    fn collect_spans_directive_bodies() {
+        let content = indoc! {r#"
+            [Outer]: https://outer.example "Outer"
+
+            ::: callout
+            [^inner]: Inner note.
+
+            :::: callout
+            [deep  link]: /deep
+            ::::
+            :::
+
+            [^outer]: Outer note.
+        "#};
+        let refs = PageRefs::collect(content, Markdown::default());
+        assert_eq!(
+            refs.links["outer"],
+            ("https://outer.example".to_owned(), "Outer".to_owned())
+        );
+        assert_eq!(refs.links["deep link"], ("/deep".to_owned(), String::new()));
+        assert_eq!(
+            refs.footnotes.iter().collect::<Vec<_>>(),
+            ["inner", "outer"]
+        );
+    }
+
+    #[test]
+    fn collect_ignores_code_blocks() {
+        let content = indoc! {"
+            ```markdown
+            [a]: /a
+            [^b]: B.
+            ```
+        "};
+        let refs = PageRefs::collect(content, Markdown::default());
+        assert!(refs.links.is_empty());
+        assert!(refs.footnotes.is_empty());
+    }
+
+    // ── PageRefs::parser ──
+
+    #[test]
+    fn parser_resolves_page_links() {
+        let refs = PageRefs::collect("[Docs]: /docs\n", Markdown::default());
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, refs.parser("See [docs]."));
+        assert_eq!(html, "<p>See <a href=\"/docs\">docs</a>.</p>\n");
+    }
+
+    #[test]
+    fn parser_marks_citations_when_enabled() {
+        let source = "See [@castellan84; @ferrow96] and [@handle][x].";
+        let mut refs = PageRefs::collect(source, Markdown::default());
+
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, refs.parser(source));
+        assert_eq!(
+            html,
+            "<p>See [@castellan84; @ferrow96] and [@handle][x].</p>\n"
+        );
+
+        refs.citations = true;
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, refs.parser(source));
+        assert_eq!(
+            html,
+            "<p>See <a href=\"cite:@castellan84;%20@ferrow96\">@castellan84; @ferrow96</a> and [@handle][x].</p>\n"
+        );
+    }
+
+    // ── resolve_link_aliases ──
+
+    #[test]
+    fn resolve_link_aliases_expands_known_names() {
+        let links = BTreeMap::from([
+            (
+                "rustbook".to_owned(),
+                "https://docs.example.org/book/".to_owned(),
+            ),
+            ("search".to_owned(), "https://example.com/?q=a&b".to_owned()),
+        ]);
+        let html = indoc! {r#"
+            <a href="link:rustbook">Book</a>
+            <a href="link:rustbook/ch01-00.html#intro">Ch. 1</a>
+            <img src="link:search" alt="">
+            <a href="link:missing">Missing</a>
+        "#};
+        assert_eq!(
+            resolve_link_aliases(html, &links),
+            indoc! {r#"
+                <a href="https://docs.example.org/book/">Book</a>
+                <a href="https://docs.example.org/book/ch01-00.html#intro">Ch. 1</a>
+                <img src="https://example.com/?q=a&amp;b" alt="">
+                <a href="link:missing">Missing</a>
+            "#},
+        );
+    }
+
+    // ── number_footnotes ──
+
+    #[test]
+    fn number_footnotes_orders_by_first_reference() {
+        let html = format!(
+            "<p>{}{}{}</p>\n",
+            footnote_reference_html("b"),
+            footnote_reference_html("a"),
+            footnote_reference_html("B"),
+        );
+        let definitions = [
+            FootnoteDefinition {
+                label: "a".into(),
+                html: "<p>A</p>\n".into(),
+            },
+            FootnoteDefinition {
+                label: "unused".into(),
+                html: "<p>U</p>\n".into(),
+            },
+            FootnoteDefinition {
+                label: "b".into(),
+                html: "<p>B</p>\n".into(),
+            },
+        ];
+        assert_eq!(
+            number_footnotes(&html, &definitions),
+            indoc! {r##"
+                <p><sup class="footnote-ref" id="fnref:1"><a href="#fn:1" role="doc-noteref">1</a></sup><sup class="footnote-ref" id="fnref:2"><a href="#fn:2" role="doc-noteref">2</a></sup><sup class="footnote-ref" id="fnref:1-2"><a href="#fn:1" role="doc-noteref">1</a></sup></p>
+                <section class="footnotes" role="doc-endnotes">
+                <div class="footnote-definition" id="fn:1"><sup class="footnote-definition-label">1</sup><p>B <a href="#fnref:1" class="footnote-backref" role="doc-backlink">↩</a> <a href="#fnref:1-2" class="footnote-backref" role="doc-backlink">↩<sup>2</sup></a></p></div>
+                <div class="footnote-definition" id="fn:2"><sup class="footnote-definition-label">2</sup><p>A <a href="#fnref:2" class="footnote-backref" role="doc-backlink">↩</a></p></div>
+                <div class="footnote-definition" id="fn:3"><sup class="footnote-definition-label">3</sup><p>U</p></div>
+                </section>
+            "##},
+        );
+    }
+
+    #[test]
+    fn number_footnotes_appends_backref_after_non_paragraph_body() {
+        let html = format!("<p>x{}</p>\n", footnote_reference_html("n"));
+        let definitions = [FootnoteDefinition {
+            label: "n".into(),
+            html: "<ul>\n<li>item</li>\n</ul>\n".into(),
+        }];
+        assert!(number_footnotes(&html, &definitions).contains(indoc! {r##"
+                </ul>
+                <a href="#fnref:1" class="footnote-backref" role="doc-backlink">↩</a></div>
+            "##}),);
+    }
+
+    #[test]
+    fn number_footnotes_without_definitions_adds_no_section() {
+        assert_eq!(number_footnotes("<p>x</p>\n", &[]), "<p>x</p>\n");
+    }
+}
@@ -0,0 +1,361 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::Images;
+
+/// Image formats variants are generated for, by extension. GIFs are left
+/// alone so animations survive.
+const RESPONSIVE_EXTENSIONS: [&str; 4] = ["jpeg", "jpg", "png", "webp"];
+
+/// A scaled-down or converted copy of a bundle image, written next to it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ImageVariant {
+    /// The bundle image, relative to the bundle directory.
+    pub source: PathBuf,
+    /// The copy, relative to the bundle directory
+    /// (`assets/cover.jpg` → `assets/cover.480w.webp`).
+    pub path: PathBuf,
+    /// Width of the copy in pixels: the source's own width for a plain
+    /// format conversion.
+    pub width: u32,
+}
+
+/// Resolves markdown image sources to files on disk: relative sources
+/// against the page bundle, root-relative ones against the `static/`
+/// directories.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ImageResolver<'a> {
+    /// The `[images]` settings; variants are only generated for bundle images.
+    pub(crate) config: &'a Images,
+    pub(crate) bundle_dir: Option<&'a Path>,
+    /// Site `static/`, then the theme's, in lookup order.
+    pub(crate) static_dirs: &'a [PathBuf],
+}
+
+/// An image found on disk: its intrinsic size, and responsive variants
+/// when it is a bundle image `[images]` generates any for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedImage {
+    /// Intrinsic width of the image, in pixels.
+    pub width: u32,
+    /// Intrinsic height of the image, in pixels.
+    pub height: u32,
+    pub responsive: Option<ResponsiveImage>,
+}
+
+/// The `srcset` markup of a bundle image, and the variants it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponsiveImage {
+    /// `srcset` of the `<img>`, in the source's format.
+    pub srcset: String,
+    /// `<picture>` sources in other formats, as `(MIME type, srcset)`.
+    pub sources: Vec<(&'static str, String)>,
+    pub sizes: String,
+    pub variants: Vec<ImageVariant>,
+}
+
+/// A page bundle image named by a relative image source, split for naming
+/// the copies written next to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BundleImage<'a> {
+    /// The image, relative to the bundle directory.
+    pub(crate) path: PathBuf,
+    /// The source without its extension (`./assets/cover`).
+    pub(crate) stem: &'a str,
+    /// The extension as written in the source.
+    pub(crate) ext: &'a str,
+}
+
+/// Returns the bundle image `src` names, or `None` unless it is a relative
+/// path that stays inside the bundle and ends in one of `extensions`
+/// (matched case-insensitively).
+pub(crate) fn bundle_image<'a>(src: &'a str, extensions: &[&str]) -> Option<BundleImage<'a>> {
+    if src.starts_with('/') || src.contains([':', '?', '#']) {
+        return None;
+    }
+    let path = normalize(src)?;
+    let ext = path.extension()?.to_str()?;
+    if !extensions.contains(&ext.to_ascii_lowercase().as_str()) {
+        return None;
+    }
+    let (stem, ext) = src.split_at(src.len() - ext.len());
+    Some(BundleImage {
+        path,
+        stem: &stem[..stem.len() - 1],
+        ext,
+    })
+}
+
+impl ImageResolver<'_> {
+    /// Returns the image at `src` with its intrinsic size, or `None` unless
+    /// `src` is a local path to an image whose size can be read.
+    pub(crate) fn resolve(&self, src: &str) -> Option<ResolvedImage> {
+        if src.contains([':', '?', '#']) {
+            return None;
+        }
+        if let Some(rooted) = src.strip_prefix('/') {
+            let path = normalize(rooted)?;
+            let (width, height) = self
+                .static_dirs
+                .iter()
+                .find_map(|dir| image::image_dimensions(dir.join(&path)).ok())?;
+            return Some(ResolvedImage {
+                width,
+                height,
+                responsive: None,
+            });
+        }
+        let source = normalize(src)?;
+        let (width, height) = image::image_dimensions(self.bundle_dir?.join(&source)).ok()?;
+        Some(ResolvedImage {
+            width,
+            height,
+            responsive: self.responsive(src, width),
+        })
+    }
+
+    /// Returns the responsive variants of the bundle image `src`, or `None`
+    /// unless it is a JPEG, PNG, or WebP with something to generate: a
+    /// configured width below its own, or a format other than its own.
+    fn responsive(&self, src: &str, width: u32) -> Option<ResponsiveImage> {
+        if !self.config.enabled() {
+            return None;
+        }
+        let BundleImage {
+            path: source,
+            stem: url_stem,
+            ext,
+        } = bundle_image(src, &RESPONSIVE_EXTENSIONS)?;
+        let lower_ext = ext.to_ascii_lowercase();
+
+        let mut widths: Vec<u32> = self
+            .config
+            .widths
+            .iter()
+            .copied()
+            .filter(|&w| w > 0 && w < width)
+            .collect();
+        widths.sort_unstable();
+        widths.dedup();
+
+        let file_stem = source.file_stem()?.to_string_lossy().into_owned();
+        let mut variants = Vec::new();
+        let mut candidates = |widths: &[u32], ext: &str| -> Vec<String> {
+            widths
+                .iter()
+                .map(|&w| {
+                    variants.push(ImageVariant {
+                        source: source.clone(),
+                        path: source.with_file_name(format!("{file_stem}.{w}w.{ext}")),
+                        width: w,
+                    });
+                    format!("{url_stem}.{w}w.{ext} {w}w")
+                })
+                .collect()
+        };
+
+        let mut srcset = candidates(&widths, ext);
+        srcset.push(format!("{src} {width}w"));
+        widths.push(width);
+        let sources = self
+            .config
+            .formats
+            .iter()
+            .filter(|format| format.extension() != lower_ext)
+            .map(|format| {
+                let srcset = candidates(&widths, format.extension()).join(", ");
+                (format.mime_type(), srcset)
+            })
+            .collect();
+        if variants.is_empty() {
+            return None;
+        }
+
+        Some(ResponsiveImage {
+            srcset: srcset.join(", "),
+            sources,
+            sizes: self.config.sizes.clone(),
+            variants,
+        })
+    }
+}
+
+/// Turns a URL path into a relative file path, or `None` if it climbs out
+/// with `..`.
+fn normalize(path: &str) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(segment) => normalized.push(segment),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbImage;
+
+    use super::*;
+    use crate::config::ImageFormat;
+
+    fn images(widths: &[u32], formats: &[ImageFormat]) -> Images {
+        Images {
+            widths: widths.to_vec(),
+            formats: formats.to_vec(),
+            ..Images::default()
+        }
+    }
+
+    fn write_image(dir: &Path, rel: &str, width: u32, height: u32) {
+        let path = dir.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        RgbImage::new(width, height).save(path).unwrap();
+    }
+
+    fn resolver<'a>(config: &'a Images, dir: &'a Path) -> ImageResolver<'a> {
+        ImageResolver {
+            config,
+            bundle_dir: Some(dir),
+            static_dirs: &[],
+        }
+    }
+
+    // ── bundle_image ──
+
+    #[test]
+    fn bundle_image_splits_relative_source() {
+        assert_eq!(
+            bundle_image("./assets/cover.JPG", &RESPONSIVE_EXTENSIONS),
+            Some(BundleImage {
+                path: PathBuf::from("assets/cover.JPG"),
+                stem: "./assets/cover",
+                ext: "JPG",
+            })
+        );
+        for src in [
+            "/images/cover.png",
+            "https://cdn.example.com/cover.png",
+            "../cover.png",
+            "cover.png?v=2",
+            "anim.gif",
+        ] {
+            assert_eq!(bundle_image(src, &RESPONSIVE_EXTENSIONS), None, "{src}");
+        }
+    }
+
+    // ── ImageResolver::resolve ──
+
+    #[test]
+    fn resolve_lists_smaller_widths_and_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        write_image(dir.path(), "assets/cover.png", 1000, 500);
+        let config = images(&[1920, 960, 480, 480], &[ImageFormat::Webp]);
+
+        let resolved = resolver(&config, dir.path())
+            .resolve("./assets/cover.png")
+            .unwrap();
+        assert_eq!((resolved.width, resolved.height), (1000, 500));
+        let image = resolved.responsive.unwrap();
+        assert_eq!(
+            image.srcset,
+            "./assets/cover.480w.png 480w, ./assets/cover.960w.png 960w, ./assets/cover.png 1000w"
+        );
+        assert_eq!(
+            image.sources,
+            [(
+                "image/webp",
+                "./assets/cover.480w.webp 480w, ./assets/cover.960w.webp 960w, ./assets/cover.1000w.webp 1000w".to_owned()
+            )]
+        );
+        assert_eq!(image.sizes, "100vw");
+        let paths: Vec<_> = image.variants.iter().map(|v| (&v.path, v.width)).collect();
+        assert_eq!(
+            paths,
+            [
+                (&PathBuf::from("assets/cover.480w.png"), 480),
+                (&PathBuf::from("assets/cover.960w.png"), 960),
+                (&PathBuf::from("assets/cover.480w.webp"), 480),
+                (&PathBuf::from("assets/cover.960w.webp"), 960),
+                (&PathBuf::from("assets/cover.1000w.webp"), 1000),
+            ]
+        );
+        assert!(
+            image
+                .variants
+                .iter()
+                .all(|v| v.source == Path::new("assets/cover.png"))
+        );
+    }
+
+    #[test]
+    fn resolve_skips_variants_with_nothing_to_generate() {
+        let dir = tempfile::tempdir().unwrap();
+        write_image(dir.path(), "small.png", 300, 200);
+        write_image(dir.path(), "photo.webp", 300, 200);
+        write_image(dir.path(), "anim.gif", 1000, 500);
+
+        let responsive = |config: &Images, src: &str| {
+            let resolved = resolver(config, dir.path()).resolve(src).unwrap();
+            resolved.responsive
+        };
+        let config = images(&[480], &[]);
+        assert_eq!(responsive(&config, "small.png"), None, "already narrower");
+        assert_eq!(responsive(&config, "anim.gif"), None, "GIF");
+        assert_eq!(responsive(&Images::default(), "anim.gif"), None, "disabled");
+
+        let config = images(&[], &[ImageFormat::Webp]);
+        assert_eq!(responsive(&config, "photo.webp"), None, "already WebP");
+        assert!(responsive(&config, "small.png").is_some());
+    }
+
+    #[test]
+    fn resolve_reads_static_images() {
+        let site = tempfile::tempdir().unwrap();
+        let theme = tempfile::tempdir().unwrap();
+        write_image(site.path(), "images/logo.png", 64, 32);
+        write_image(theme.path(), "images/logo.png", 16, 16);
+        write_image(theme.path(), "images/icon.gif", 24, 24);
+        let config = images(&[16], &[]);
+        let static_dirs = [site.path().to_owned(), theme.path().to_owned()];
+        let resolver = ImageResolver {
+            config: &config,
+            bundle_dir: None,
+            static_dirs: &static_dirs,
+        };
+
+        let logo = resolver.resolve("/images/logo.png").unwrap();
+        assert_eq!((logo.width, logo.height, logo.responsive), (64, 32, None));
+        let icon = resolver.resolve("/images/icon.gif").unwrap();
+        assert_eq!((icon.width, icon.height), (24, 24));
+        assert_eq!(resolver.resolve("images/logo.png"), None, "no bundle");
+    }
+
+    #[test]
+    fn resolve_skips_other_images() {
+        let dir = tempfile::tempdir().unwrap();
+        write_image(dir.path(), "cover.png", 1000, 500);
+        std::fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+
+        let config = images(&[480], &[]);
+        let static_dirs = [dir.path().to_owned()];
+        let resolver = ImageResolver {
+            config: &config,
+            bundle_dir: Some(dir.path()),
+            static_dirs: &static_dirs,
+        };
+        for src in [
+            "https://cdn.example.com/cover.png",
+            "data:image/png;base64,AAAA",
+            "../cover.png",
+            "/../cover.png",
+            "cover.png?v=2",
+            "missing.png",
+            "/missing.png",
+            "notes.txt",
+        ] {
+            assert_eq!(resolver.resolve(src), None, "{src}");
+        }
+    }
+}
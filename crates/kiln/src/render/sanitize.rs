@@ -0,0 +1,490 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Options, Parser};
+
+use super::pipeline::top_level_blocks;
+use crate::directive::DirectiveKind;
+use crate::directive::parser::parse_directives;
+
+/// Elements [`sanitize_html`] keeps; any other tag is escaped.
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+    "var",
+];
+
+/// Attributes kept on every allowed element.
+const GLOBAL_ATTRIBUTES: &[&str] = &["title", "lang", "dir"];
+
+/// Attributes kept on specific elements, on top of [`GLOBAL_ATTRIBUTES`].
+const ELEMENT_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("img", &["src", "alt", "width", "height"]),
+    ("ol", &["start", "reversed"]),
+    ("td", &["colspan", "rowspan", "align"]),
+    ("th", &["colspan", "rowspan", "align", "scope"]),
+    ("details", &["open"]),
+];
+
+/// Attributes holding a URL, checked with [`is_safe_url`].
+const URL_ATTRIBUTES: &[&str] = &["href", "src"];
+
+/// URL schemes allowed in links and images; relative URLs are always
+/// allowed.
+const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Sanitizes the raw HTML in markdown `content`, parsed with `options`, for
+/// `[markdown] sanitize`; see [`sanitize_html`].
+///
+/// Directive bodies are parsed as the standalone documents they are rendered
+/// as, so the HTML found here is the HTML the renderer sees. Markdown syntax,
+/// code blocks, and code spans are left as they are.
+#[must_use]
+pub(crate) fn sanitize_markdown(content: &str, options: Options) -> String {
+    let mut edits = Vec::new();
+    collect_html_edits(content, 0, options, &mut edits);
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut output = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, html) in edits {
+        output.push_str(&content[last..range.start]);
+        output.push_str(&html);
+        last = range.end;
+    }
+    output.push_str(&content[last..]);
+    output
+}
+
+/// Collects the sanitized replacement of each raw HTML event in `content`,
+/// which starts at byte `offset` of the page.
+fn collect_html_edits(
+    content: &str,
+    offset: usize,
+    options: Options,
+    edits: &mut Vec<(Range<usize>, String)>,
+) {
+    let blocks = parse_directives(content);
+    let top_level = top_level_blocks(&blocks);
+
+    // Blank out directive blocks, keeping byte offsets, so the rest is
+    // parsed as the fragment it is rendered as.
+    let mut outer = content.to_owned();
+    for block in &top_level {
+        let blank: String = content[block.range.clone()]
+            .bytes()
+            .map(|b| if b == b'\n' { '\n' } else { ' ' })
+            .collect();
+        outer.replace_range(block.range.clone(), &blank);
+    }
+
+    let mut last_end = 0;
+    for (event, range) in Parser::new_ext(&outer, options).into_offset_iter() {
+        if matches!(event, Event::Html(_) | Event::InlineHtml(_)) && range.start >= last_end {
+            let html = sanitize_html(&content[range.clone()]);
+            edits.push((offset + range.start..offset + range.end, html));
+            last_end = range.end;
+        }
+    }
+
+    for block in top_level {
+        let body_start = content[block.range.clone()]
+            .find('\n')
+            .map_or(block.range.end, |i| block.range.start + i + 1);
+        match block.kind {
+            // CSV bodies are data, escaped cell by cell when rendered; gallery
+            // bodies are not rendered at all.
+            DirectiveKind::Csv { .. } | DirectiveKind::Gallery { .. } => {}
+            // Raw bodies are emitted verbatim, so they are HTML throughout.
+            DirectiveKind::Raw => {
+                let start = offset + body_start;
+                edits.push((start..start + block.body.len(), sanitize_html(&block.body)));
+            }
+            _ => collect_html_edits(&block.body, offset + body_start, options, edits),
+        }
+    }
+}
+
+/// Sanitizes an HTML fragment against an allowlist.
+///
+/// - Allowed elements keep only their allowed attributes; `href` and `src`
+///   are dropped unless [`is_safe_url`].
+/// - Any other tag, e.g., `<script>` or `<iframe>`, is escaped and shows up
+///   as text. So does a `<` that does not start a tag.
+/// - Comments are dropped, ending where a browser ends them, so
+///   `<!-->` cannot hide markup after it; text is kept.
+#[must_use]
+pub(crate) fn sanitize_html(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        output.push_str(&rest[..lt]);
+        rest = &rest[lt..];
+        if let Some(body) = rest.strip_prefix("<!--") {
+            rest = &body[comment_len(body)..];
+        } else if let Some((tag, len)) = Tag::parse(rest)
+            && tag.write_allowed(&mut output)
+        {
+            rest = &rest[len..];
+        } else {
+            output.push_str("&lt;");
+            rest = &rest[1..];
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The length of the comment body `body`, which follows `<!--`, including
+/// the delimiter a browser closes it with; an unclosed comment runs to the
+/// end.
+fn comment_len(body: &str) -> usize {
+    if body.starts_with('>') {
+        return 1;
+    }
+    if body.starts_with("->") {
+        return 2;
+    }
+    ["-->", "--!>"]
+        .iter()
+        .filter_map(|end| body.find(end).map(|i| i + end.len()))
+        .min()
+        .unwrap_or(body.len())
+}
+
+/// Whether `url` is relative or uses one of [`SAFE_URL_SCHEMES`].
+///
+/// A character reference before the path, e.g., `javascript&colon;`, could
+/// hide a scheme, so it makes the URL unsafe.
+#[must_use]
+pub(crate) fn is_safe_url(url: &str) -> bool {
+    let url = url.trim_start_matches(|c: char| c.is_ascii_whitespace() || c.is_ascii_control());
+    let head = &url[..url.find(['/', '?', '#']).unwrap_or(url.len())];
+    if head.contains('&') {
+        return false;
+    }
+    let Some(colon) = head.find(':') else {
+        return true;
+    };
+    // Browsers ignore tabs and newlines inside a scheme.
+    let scheme: String = head[..colon]
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect();
+    SAFE_URL_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str())
+}
+
+/// An opening or closing HTML tag.
+struct Tag<'a> {
+    closing: bool,
+    name: String,
+    attributes: Vec<(String, Option<&'a str>)>,
+}
+
+impl<'a> Tag<'a> {
+    /// Parses the tag that begins `input`, returning it and its length.
+    fn parse(input: &'a str) -> Option<(Self, usize)> {
+        let mut i = 1;
+        let closing = input[i..].starts_with('/');
+        if closing {
+            i += 1;
+        }
+        let name_len = input[i..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(input.len() - i);
+        let name = &input[i..i + name_len];
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        i += name_len;
+
+        let mut attributes = Vec::new();
+        loop {
+            i += whitespace_len(&input[i..]);
+            let rest = &input[i..];
+            if rest.starts_with('>') {
+                i += 1;
+                break;
+            }
+            if rest.starts_with("/>") {
+                i += 2;
+                break;
+            }
+            let attr_len = rest
+                .find(|c: char| c.is_ascii_whitespace() || "/>=\"'<".contains(c))
+                .unwrap_or(rest.len());
+            if attr_len == 0 {
+                return None;
+            }
+            let attr = rest[..attr_len].to_ascii_lowercase();
+            i += attr_len;
+
+            let after = whitespace_len(&input[i..]);
+            if !input[i + after..].starts_with('=') {
+                attributes.push((attr, None));
+                continue;
+            }
+            i += after + 1;
+            i += whitespace_len(&input[i..]);
+            let rest = &input[i..];
+            let (value, len) =
+                if let Some(quote) = rest.chars().next().filter(|c| "\"'".contains(*c)) {
+                    let end = rest[1..].find(quote)?;
+                    (&rest[1..=end], end + 2)
+                } else {
+                    let end = rest
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .unwrap_or(rest.len());
+                    (&rest[..end], end)
+                };
+            if len == 0 {
+                return None;
+            }
+            attributes.push((attr, Some(value)));
+            i += len;
+        }
+
+        let tag = Self {
+            closing,
+            name: name.to_ascii_lowercase(),
+            attributes,
+        };
+        Some((tag, i))
+    }
+
+    /// Writes the tag with only its allowed attributes, if the element is
+    /// allowed at all.
+    fn write_allowed(&self, output: &mut String) -> bool {
+        if !ALLOWED_TAGS.contains(&self.name.as_str()) {
+            return false;
+        }
+        if self.closing {
+            _ = write!(output, "</{}>", self.name);
+            return true;
+        }
+        let element_attributes = ELEMENT_ATTRIBUTES
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .map_or(&[][..], |(_, attributes)| attributes);
+        _ = write!(output, "<{}", self.name);
+        for (attr, value) in &self.attributes {
+            let allowed = GLOBAL_ATTRIBUTES.contains(&attr.as_str())
+                || element_attributes.contains(&attr.as_str());
+            let safe = !URL_ATTRIBUTES.contains(&attr.as_str()) || value.is_some_and(is_safe_url);
+            if !allowed || !safe {
+                continue;
+            }
+            match value {
+                Some(value) => _ = write!(output, r#" {attr}="{}""#, escape_attribute(value)),
+                None => _ = write!(output, " {attr}"),
+            }
+        }
+        output.push('>');
+        true
+    }
+}
+
+fn whitespace_len(input: &str) -> usize {
+    input.len()
+        - input
+            .trim_start_matches(|c: char| c.is_ascii_whitespace())
+            .len()
+}
+
+/// Escapes an attribute value for a double-quoted attribute, keeping its
+/// character references.
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    // ── sanitize_html ──
+
+    #[test]
+    fn sanitize_html_escapes_disallowed_tags() {
+        assert_eq!(
+            sanitize_html("<script>alert(1)</script>"),
+            "&lt;script>alert(1)&lt;/script>"
+        );
+        assert_eq!(
+            sanitize_html(r#"<iframe src="https://example.com"></iframe>"#),
+            r#"&lt;iframe src="https://example.com">&lt;/iframe>"#
+        );
+    }
+
+    #[test]
+    fn sanitize_html_drops_disallowed_attributes() {
+        assert_eq!(
+            sanitize_html(r#"<img src="a.png" onerror='alert(1)' alt="A > B" class=x>"#),
+            r#"<img src="a.png" alt="A &gt; B">"#
+        );
+        assert_eq!(
+            sanitize_html(r#"<DIV STYLE="color: red" title=Note>x</DIV>"#),
+            r#"<div title="Note">x</div>"#
+        );
+    }
+
+    #[test]
+    fn sanitize_html_drops_unsafe_urls() {
+        assert_eq!(
+            sanitize_html(r#"<a href="javascript:alert(1)" title="t">x</a>"#),
+            r#"<a title="t">x</a>"#
+        );
+        assert_eq!(
+            sanitize_html(r#"<a href="/docs?a=1&amp;b=2">x</a>"#),
+            r#"<a href="/docs?a=1&amp;b=2">x</a>"#
+        );
+    }
+
+    #[test]
+    fn sanitize_html_drops_comments_and_keeps_text() {
+        assert_eq!(sanitize_html("<!-- note --> a < b"), " a &lt; b");
+        assert_eq!(sanitize_html("a<!-- x --!> b"), "a b");
+        assert_eq!(sanitize_html("a<!-- unclosed"), "a");
+        assert_eq!(sanitize_html("<details open>"), "<details open>");
+    }
+
+    #[test]
+    fn sanitize_html_sanitizes_markup_after_abruptly_closed_comments() {
+        assert_eq!(
+            sanitize_html("<!--><img src=x onerror=alert(1)>-->"),
+            r#"<img src="x">-->"#
+        );
+        assert_eq!(
+            sanitize_html("<!---><script>alert(1)</script>"),
+            "&lt;script>alert(1)&lt;/script>"
+        );
+    }
+
+    // ── is_safe_url ──
+
+    #[test]
+    fn is_safe_url_allows_relative_and_web_urls() {
+        assert!(is_safe_url("/posts/hello/"));
+        assert!(is_safe_url("image.png"));
+        assert!(is_safe_url("#top"));
+        assert!(is_safe_url("//cdn.example.com/x.js"));
+        assert!(is_safe_url("https://example.com/a:b"));
+        assert!(is_safe_url("mailto:me@example.com"));
+    }
+
+    #[test]
+    fn is_safe_url_rejects_script_schemes() {
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url(" JavaScript:alert(1)"));
+        assert!(!is_safe_url("java\tscript:alert(1)"));
+        assert!(!is_safe_url("javascript&colon;alert(1)"));
+        assert!(!is_safe_url("data:text/html,<script>"));
+    }
+
+    // ── sanitize_markdown ──
+
+    #[test]
+    fn sanitize_markdown_cleans_html_blocks_and_inline_html() {
+        let output = sanitize_markdown(
+            indoc! {r#"
+                <div onclick="x()">
+                <script>alert(1)</script>
+                </div>
+
+                Text with <b>bold</b> and <span onmouseover="x()">hover</span>.
+            "#},
+            Options::empty(),
+        );
+        assert_eq!(
+            output,
+            indoc! {"
+                <div>
+                &lt;script>alert(1)&lt;/script>
+                </div>
+
+                Text with <b>bold</b> and <span>hover</span>.
+            "}
+        );
+    }
+
+    #[test]
+    fn sanitize_markdown_skips_code() {
+        let input = indoc! {"
+            `<script>`
+
+            ```html
+            <script>alert(1)</script>
+            ```
+        "};
+        assert_eq!(sanitize_markdown(input, Options::empty()), input);
+    }
+
+    #[test]
+    fn sanitize_markdown_parses_directive_bodies_on_their_own() {
+        // A code span opened outside a callout does not continue into it.
+        let output = sanitize_markdown(
+            indoc! {"
+                `a
+                ::: callout
+                <img src=x onerror=alert(1)>`
+                :::
+            "},
+            Options::empty(),
+        );
+        assert!(output.contains("<img src=\"x\">`"), "output:\n{output}");
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::directive::DirectiveKind;
+use crate::directive::parser::parse_directives;
+
+/// Directive name that inserts a snippet: `::: snippet { "name" }`.
+pub(crate) const SNIPPET_DIRECTIVE: &str = "snippet";
+
+/// Maximum snippet nesting depth, guarding against runaway expansion.
+const MAX_DEPTH: usize = 16;
+
+/// Site-wide markdown snippets, keyed by name.
+///
+/// Loaded from `data/snippets.toml` (a table of string values) and from
+/// `content/_snippets/<name>.md` files. Defining the same name in both places
+/// is an error.
+#[derive(Debug, Clone, Default)]
+pub struct Snippets {
+    map: HashMap<String, String>,
+}
+
+impl Snippets {
+    /// Loads snippets from `snippets_toml` and the `.md` files in
+    /// `snippets_dir`. Missing sources are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a source cannot be read, `snippets_toml` is not a
+    /// table of strings, or a name is defined twice.
+    pub fn load(snippets_toml: &Path, snippets_dir: &Path) -> Result<Self> {
+        let mut map = HashMap::new();
+
+        if snippets_toml.is_file() {
+            let raw = fs::read_to_string(snippets_toml)
+                .with_context(|| format!("failed to read {}", snippets_toml.display()))?;
+            let table: HashMap<String, String> = toml::from_str(&raw)
+                .with_context(|| format!("failed to parse {}", snippets_toml.display()))?;
+            map.extend(table);
+        }
+
+        if snippets_dir.is_dir() {
+            let entries = fs::read_dir(snippets_dir)
+                .with_context(|| format!("failed to read {}", snippets_dir.display()))?;
+            for entry in entries {
+                let path = entry?.path();
+                if path.extension().is_none_or(|ext| ext != "md") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                let body = fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read snippet {}", path.display()))?;
+                if map.insert(name.to_owned(), body).is_some() {
+                    bail!(
+                        "snippet `{name}` is defined in both {} and {}",
+                        snippets_toml.display(),
+                        path.display()
+                    );
+                }
+            }
+        }
+
+        Ok(Self { map })
+    }
+
+    /// Replaces every `::: snippet { "name" }` directive in `content` with
+    /// the snippet's markdown. Snippets may themselves use snippets.
+    ///
+    /// Directives inside code blocks and `raw` directives are left alone, as
+    /// are any body lines inside the snippet directive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a snippet directive names no snippet or an unknown
+    /// one, or if snippets include each other in a cycle.
+    pub fn expand(&self, content: &str) -> Result<String> {
+        self.expand_nested(content, &mut Vec::new())
+    }
+
+    fn expand_nested<'a>(&'a self, content: &str, stack: &mut Vec<&'a str>) -> Result<String> {
+        let blocks = parse_directives(content);
+
+        // Snippet directives not nested in another snippet directive's body
+        // (whose lines are ignored) or a raw one, in source order.
+        let mut top_level = Vec::new();
+        let mut outer_end = 0;
+        for block in &blocks {
+            if block.kind == DirectiveKind::Raw && block.range.start >= outer_end {
+                outer_end = block.range.end;
+            } else if let DirectiveKind::Unknown {
+                name,
+                positional_args,
+                ..
+            } = &block.kind
+                && name == SNIPPET_DIRECTIVE
+                && block.range.start >= outer_end
+            {
+                top_level.push((block.range.clone(), positional_args.first()));
+                outer_end = block.range.end;
+            }
+        }
+
+        // Replace right-to-left so earlier ranges stay valid.
+        let mut result = content.to_owned();
+        for (range, key) in top_level.into_iter().rev() {
+            let Some(key) = key else {
+                bail!("snippet directive is missing a snippet name");
+            };
+            let Some((key, body)) = self.map.get_key_value(key.as_str()) else {
+                bail!("unknown snippet `{key}`");
+            };
+            if stack.contains(&key.as_str()) || stack.len() >= MAX_DEPTH {
+                bail!("snippet `{key}` includes itself");
+            }
+
+            stack.push(key);
+            let expanded = self.expand_nested(body, stack)?;
+            stack.pop();
+
+            let mut replacement = expanded.trim_end().to_owned();
+            replacement.push('\n');
+            result.replace_range(range, &replacement);
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn snippets(entries: &[(&str, &str)]) -> Snippets {
+        Snippets {
+            map: entries
+                .iter()
+                .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+                .collect(),
+        }
+    }
+
+    // ── Snippets::load ──
+
+    #[test]
+    fn load_reads_toml_and_markdown_files() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(
+            root.path(),
+            "data/snippets.toml",
+            r#"disclaimer = "Links may be affiliate links.""#,
+        );
+        write_test_file(root.path(), "content/_snippets/outro.md", "Thanks!\n");
+        write_test_file(root.path(), "content/_snippets/notes.txt", "ignored");
+
+        let snippets = Snippets::load(
+            &root.path().join("data").join("snippets.toml"),
+            &root.path().join("content").join("_snippets"),
+        )
+        .unwrap();
+        assert_eq!(snippets.map.len(), 2);
+        assert_eq!(snippets.map["disclaimer"], "Links may be affiliate links.");
+        assert_eq!(snippets.map["outro"], "Thanks!\n");
+    }
+
+    #[test]
+    fn load_missing_sources_is_empty() {
+        let root = tempfile::tempdir().unwrap();
+        let snippets =
+            Snippets::load(&root.path().join("none.toml"), &root.path().join("none")).unwrap();
+        assert!(snippets.map.is_empty());
+    }
+
+    #[test]
+    fn load_duplicate_name_returns_error() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "snippets.toml", r#"outro = "A""#);
+        write_test_file(root.path(), "_snippets/outro.md", "B");
+
+        let err = Snippets::load(
+            &root.path().join("snippets.toml"),
+            &root.path().join("_snippets"),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("snippet `outro` is defined in both"),
+            "got: {err}"
+        );
+    }
+
+    // ── Snippets::expand ──
+
+    #[test]
+    fn expand_replaces_directives() {
+        let snippets = snippets(&[("disclaimer", "*Affiliate links ahead.*\n\n")]);
+        let content = indoc! {r#"
+            Intro.
+
+            ::: snippet { "disclaimer" }
+            :::
+
+            ::: callout
+            ::: snippet { "disclaimer" }
+            :::
+            :::
+
+            ```markdown
+            ::: snippet { "missing" }
+            :::
+            ```
+
+            :::: raw
+            ::: snippet { "missing" }
+            :::
+            ::::
+        "#};
+        assert_eq!(
+            snippets.expand(content).unwrap(),
+            indoc! {r#"
+                Intro.
+
+                *Affiliate links ahead.*
+
+                ::: callout
+                *Affiliate links ahead.*
+                :::
+
+                ```markdown
+                ::: snippet { "missing" }
+                :::
+                ```
+
+                :::: raw
+                ::: snippet { "missing" }
+                :::
+                ::::
+            "#},
+        );
+    }
+
+    #[test]
+    fn expand_nested_snippets() {
+        let snippets = snippets(&[
+            ("outer", "Before.\n\n::: snippet { \"inner\" }\n:::\n"),
+            ("inner", "Inner."),
+        ]);
+        assert_eq!(
+            snippets.expand("::: snippet { \"outer\" }\n:::\n").unwrap(),
+            "Before.\n\nInner.\n"
+        );
+    }
+
+    #[test]
+    fn expand_unknown_snippet_returns_error() {
+        let err = Snippets::default()
+            .expand("::: snippet { \"nope\" }\n:::\n")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unknown snippet `nope`");
+    }
+
+    #[test]
+    fn expand_cycle_returns_error() {
+        let snippets = snippets(&[
+            ("a", "::: snippet { \"b\" }\n:::\n"),
+            ("b", "::: snippet { \"a\" }\n:::\n"),
+        ]);
+        let err = snippets.expand("::: snippet { \"a\" }\n:::\n").unwrap_err();
+        assert_eq!(err.to_string(), "snippet `a` includes itself");
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::{Result, bail};
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use syntect::parsing::SyntaxSet;
+
+use super::highlight::{CodeBlockInfo, is_known_language};
+use crate::directive::parser::unclosed_directives;
+
+/// Returns the problems in a page's markdown that `strict = true` turns
+/// into errors, one line each: directives that are never closed, fenced
+/// code blocks in a language no syntax is loaded for, and images without
+/// alt text.
+///
+/// Otherwise, unclosed directives render as plain text, unknown languages
+/// as plain text code blocks, and images with an empty `alt`.
+#[must_use]
+pub fn check_markdown(content: &str, syntax_set: &SyntaxSet) -> Vec<String> {
+    let mut problems: Vec<String> = unclosed_directives(content)
+        .into_iter()
+        .map(|name| match name.as_str() {
+            "" => "unclosed fenced div".to_owned(),
+            _ => format!("unclosed directive `{name}`"),
+        })
+        .collect();
+
+    // Source and alt text of the image being read.
+    let mut image: Option<(String, String)> = None;
+    for event in Parser::new_ext(content, Options::all()) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                let lang = CodeBlockInfo::parse(&info).lang;
+                if !lang.eq_ignore_ascii_case("mermaid") && !is_known_language(syntax_set, &lang) {
+                    problems.push(format!("unrecognized code block language `{lang}`"));
+                }
+            }
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                image = Some((dest_url.into_string(), String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, alt)) = &mut image {
+                    alt.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some((src, alt)) = image.take()
+                    && alt.trim().is_empty()
+                {
+                    problems.push(format!("image `{src}` has no alt text"));
+                }
+            }
+            _ => {}
+        }
+    }
+    problems
+}
+
+/// Fails with every problem [`check_markdown`] finds in `content`.
+///
+/// # Errors
+///
+/// Returns an error listing the problems, if there are any.
+pub fn ensure_strict(content: &str, syntax_set: &SyntaxSet) -> Result<()> {
+    let problems = check_markdown(content, syntax_set);
+    if !problems.is_empty() {
+        bail!("strict mode: {}", problems.join("; "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::render::highlight::load_syntax_set;
+
+    // ── check_markdown ──
+
+    #[test]
+    fn check_markdown_reports_soft_failures() {
+        let syntax_set = load_syntax_set(&[]).unwrap();
+        let content = indoc! {"
+            ```rust
+            fn main() {}
+            ```
+
+            ```rsut
+            fn main() {}
+            ```
+
+            ```mermaid
+            graph TD
+            ```
+
+            ![A cat](cat.png) and ![](dog.png) and ![ ](bird.png)
+
+            ::: callout
+            Never closed
+        "};
+        assert_eq!(
+            check_markdown(content, &syntax_set),
+            [
+                "unclosed directive `callout`",
+                "unrecognized code block language `rsut`",
+                "image `dog.png` has no alt text",
+                "image `bird.png` has no alt text",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_markdown_accepts_clean_content() {
+        let syntax_set = load_syntax_set(&[]).unwrap();
+        let content = indoc! {"
+            ::: {.note}
+            ![A *formatted* `alt`](cat.png)
+            :::
+
+            ```
+            plain
+            ```
+        "};
+        assert!(check_markdown(content, &syntax_set).is_empty());
+    }
+}
@@ -1,4 +1,8 @@
+use std::iter::Peekable;
+use std::ops::RangeInclusive;
+
 use pulldown_cmark::HeadingLevel;
+use serde::Serialize;
 
 use crate::html::{escape, writeln_indented};
 
@@ -13,15 +17,69 @@ pub struct TocEntry {
     pub title: String,
 }
 
+/// A node in the structured table of contents exposed to templates as
+/// `toc_entries`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TocNode {
+    /// Heading level, 1 for H1.
+    pub level: u8,
+    pub id: String,
+    pub title: String,
+    /// Headings nested under this one, in document order.
+    pub children: Vec<Self>,
+}
+
+/// Builds the `ToC` tree from headings in document order, keeping only those
+/// whose level falls in `levels`.
+///
+/// Each heading nests under the nearest preceding heading of a smaller level,
+/// so skipped levels (H2 → H4) do not produce empty intermediate nodes, and a
+/// heading with no such ancestor becomes a root.
+#[must_use]
+pub fn build_toc_tree(entries: &[TocEntry], levels: &RangeInclusive<u8>) -> Vec<TocNode> {
+    let entries = listed_entries(entries, levels);
+    let mut iter = entries.into_iter().peekable();
+    nest_entries(&mut iter, 0)
+}
+
+/// Consumes entries deeper than `parent_level` into sibling nodes, recursing
+/// for each node's children.
+fn nest_entries<'a>(
+    iter: &mut Peekable<impl Iterator<Item = &'a TocEntry>>,
+    parent_level: u8,
+) -> Vec<TocNode> {
+    let mut nodes = Vec::new();
+    while let Some(entry) = iter.next_if(|entry| entry.level as u8 > parent_level) {
+        let level = entry.level as u8;
+        nodes.push(TocNode {
+            level,
+            id: entry.id.clone(),
+            title: entry.title.clone(),
+            children: nest_entries(iter, level),
+        });
+    }
+    nodes
+}
+
+/// Returns the entries whose heading level falls in `levels`.
+fn listed_entries<'a>(entries: &'a [TocEntry], levels: &RangeInclusive<u8>) -> Vec<&'a TocEntry> {
+    entries
+        .iter()
+        .filter(|entry| levels.contains(&(entry.level as u8)))
+        .collect()
+}
+
 /// Renders a list of `TocEntry` values into a `<nav>` HTML structure with
 /// nested `<ul>` / `<li>` / `<a>` elements.
 ///
-/// Heading levels are normalized so the smallest level in the input becomes
-/// depth 1, avoiding empty outer wrappers when content starts at H2 or deeper.
+/// Only entries whose heading level (1 for H1) falls in `levels` are listed.
+/// Heading levels are normalized so the smallest listed level becomes depth
+/// 1, avoiding empty outer wrappers when content starts at H2 or deeper.
 ///
-/// Returns an empty string if `entries` is empty.
+/// Returns an empty string if no entry is listed.
 #[must_use]
-pub fn render_toc_html(entries: &[TocEntry]) -> String {
+pub fn render_toc_html(entries: &[TocEntry], levels: &RangeInclusive<u8>) -> String {
+    let entries = listed_entries(entries, levels);
     if entries.is_empty() {
         return String::new();
     }
@@ -85,11 +143,13 @@ mod tests {
 
     use super::*;
 
+    const ALL_LEVELS: RangeInclusive<u8> = 1..=6;
+
     // ── render_toc_html ──
 
     #[test]
     fn empty_entries() {
-        assert_eq!(render_toc_html(&[]), "");
+        assert_eq!(render_toc_html(&[], &ALL_LEVELS), "");
     }
 
     #[test]
@@ -100,7 +160,7 @@ mod tests {
             title: "Hello".into(),
         }];
         assert_eq!(
-            render_toc_html(&entries),
+            render_toc_html(&entries, &ALL_LEVELS),
             indoc! {r##"
                 <nav class="toc">
                   <ul>
@@ -137,7 +197,7 @@ mod tests {
             },
         ];
         assert_eq!(
-            render_toc_html(&entries),
+            render_toc_html(&entries, &ALL_LEVELS),
             indoc! {r##"
                 <nav class="toc">
                   <ul>
@@ -173,7 +233,7 @@ mod tests {
             },
         ];
         assert_eq!(
-            render_toc_html(&entries),
+            render_toc_html(&entries, &ALL_LEVELS),
             indoc! {r##"
                 <nav class="toc">
                   <ul>
@@ -209,7 +269,7 @@ mod tests {
             },
         ];
         assert_eq!(
-            render_toc_html(&entries),
+            render_toc_html(&entries, &ALL_LEVELS),
             indoc! {r##"
                 <nav class="toc">
                   <ul>
@@ -253,7 +313,7 @@ mod tests {
             },
         ];
         assert_eq!(
-            render_toc_html(&entries),
+            render_toc_html(&entries, &ALL_LEVELS),
             indoc! {r##"
                 <nav class="toc">
                   <ul>
@@ -284,7 +344,7 @@ mod tests {
             title: "Only".into(),
         }];
         assert_eq!(
-            render_toc_html(&entries),
+            render_toc_html(&entries, &ALL_LEVELS),
             indoc! {r##"
                 <nav class="toc">
                   <ul>
@@ -316,7 +376,7 @@ mod tests {
             },
         ];
         assert_eq!(
-            render_toc_html(&entries),
+            render_toc_html(&entries, &ALL_LEVELS),
             indoc! {r##"
                 <nav class="toc">
                   <ul>
@@ -339,7 +399,7 @@ mod tests {
             id: "generics".into(),
             title: "Vec<T> & Friends".into(),
         }];
-        let html = render_toc_html(&entries);
+        let html = render_toc_html(&entries, &ALL_LEVELS);
         assert!(
             html.contains("Vec&lt;T&gt; &amp; Friends"),
             "should escape HTML in titles, html:\n{html}"
@@ -353,10 +413,107 @@ mod tests {
             id: "foo&bar".into(),
             title: "Foo".into(),
         }];
-        let html = render_toc_html(&entries);
+        let html = render_toc_html(&entries, &ALL_LEVELS);
         assert!(
             html.contains(r##"href="#foo&amp;bar""##),
             "should escape HTML in href, html:\n{html}"
         );
     }
+
+    #[test]
+    fn levels_outside_range_are_skipped() {
+        let entries = vec![
+            entry(HeadingLevel::H1, "title"),
+            entry(HeadingLevel::H2, "intro"),
+            entry(HeadingLevel::H3, "detail"),
+            entry(HeadingLevel::H4, "aside"),
+        ];
+        assert_eq!(
+            render_toc_html(&entries, &(2..=3)),
+            indoc! {r##"
+                <nav class="toc">
+                  <ul>
+                    <li><a href="#intro">INTRO</a>
+                      <ul>
+                        <li><a href="#detail">DETAIL</a>
+                        </li>
+                      </ul>
+                    </li>
+                  </ul>
+                </nav>
+            "##}
+        );
+        assert_eq!(render_toc_html(&entries, &(5..=6)), "");
+    }
+
+    // ── build_toc_tree ──
+
+    fn entry(level: HeadingLevel, id: &str) -> TocEntry {
+        TocEntry {
+            level,
+            id: id.into(),
+            title: id.to_uppercase(),
+        }
+    }
+
+    fn node(level: u8, id: &str, children: Vec<TocNode>) -> TocNode {
+        TocNode {
+            level,
+            id: id.into(),
+            title: id.to_uppercase(),
+            children,
+        }
+    }
+
+    #[test]
+    fn tree_nests_by_level() {
+        let entries = vec![
+            entry(HeadingLevel::H2, "intro"),
+            entry(HeadingLevel::H3, "detail"),
+            entry(HeadingLevel::H5, "deep"),
+            entry(HeadingLevel::H4, "aside"),
+            entry(HeadingLevel::H2, "outro"),
+        ];
+        assert_eq!(
+            build_toc_tree(&entries, &ALL_LEVELS),
+            [
+                node(
+                    2,
+                    "intro",
+                    vec![node(
+                        3,
+                        "detail",
+                        vec![node(5, "deep", vec![]), node(4, "aside", vec![])]
+                    )]
+                ),
+                node(2, "outro", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn tree_deeper_heading_first_becomes_root() {
+        let entries = vec![
+            entry(HeadingLevel::H3, "detail"),
+            entry(HeadingLevel::H2, "overview"),
+        ];
+        assert_eq!(
+            build_toc_tree(&entries, &ALL_LEVELS),
+            [node(3, "detail", vec![]), node(2, "overview", vec![])]
+        );
+    }
+
+    #[test]
+    fn tree_respects_levels() {
+        let entries = vec![
+            entry(HeadingLevel::H1, "title"),
+            entry(HeadingLevel::H2, "intro"),
+            entry(HeadingLevel::H4, "aside"),
+        ];
+        assert_eq!(
+            build_toc_tree(&entries, &(2..=3)),
+            [node(2, "intro", vec![])]
+        );
+        assert!(build_toc_tree(&[], &ALL_LEVELS).is_empty());
+    }
 }
@@ -1,9 +1,12 @@
+use std::fs;
 use std::path::Path;
 use std::process::Command;
 
 use anyhow::{Context, Result, bail};
 use indoc::formatdoc;
 
+use crate::output::append_suffix;
+
 const DEFAULT_BINARY: &str = "pagefind";
 
 /// Runs the Pagefind indexer on the given output directory.
@@ -11,12 +14,55 @@ const DEFAULT_BINARY: &str = "pagefind";
 /// Expects `output_dir` to contain the fully built site HTML. Pagefind writes
 /// its search index and client assets to `{output_dir}/pagefind/`.
 ///
+/// The top-level directories named in `exclude` are moved beside
+/// `output_dir` while Pagefind runs, so nothing under them is indexed.
+///
 /// # Errors
 ///
 /// Returns an error if the Pagefind binary cannot be executed or exits
-/// with a non-zero status.
-pub fn run_pagefind(output_dir: &Path, binary: Option<&str>) -> Result<()> {
-    let binary = binary.unwrap_or(DEFAULT_BINARY);
+/// with a non-zero status, or if an excluded directory cannot be moved.
+pub fn run_pagefind(output_dir: &Path, binary: Option<&str>, exclude: &[&str]) -> Result<()> {
+    let aside = append_suffix(output_dir, ".unindexed");
+    let moved = move_excluded(output_dir, &aside, exclude)?;
+    let result = index(output_dir, binary.unwrap_or(DEFAULT_BINARY));
+    for name in moved {
+        fs::rename(aside.join(name), output_dir.join(name))
+            .with_context(|| format!("failed to restore {}", output_dir.join(name).display()))?;
+    }
+    if aside.exists() {
+        fs::remove_dir(&aside).with_context(|| format!("failed to remove {}", aside.display()))?;
+    }
+    result
+}
+
+/// Moves each directory in `exclude` that exists under `output_dir` into
+/// `aside`, returning the names moved.
+fn move_excluded<'a>(output_dir: &Path, aside: &Path, exclude: &[&'a str]) -> Result<Vec<&'a str>> {
+    let mut moved = Vec::new();
+    for &name in exclude {
+        let dir = output_dir.join(name);
+        if !dir.is_dir() {
+            continue;
+        }
+        if moved.is_empty() {
+            // A leftover from an interrupted build.
+            if aside.exists() {
+                fs::remove_dir_all(aside)
+                    .with_context(|| format!("failed to remove {}", aside.display()))?;
+            }
+            fs::create_dir_all(aside)
+                .with_context(|| format!("failed to create {}", aside.display()))?;
+        }
+        let dest = aside.join(name);
+        fs::rename(&dir, &dest)
+            .with_context(|| format!("failed to move {} out of the search index", dir.display()))?;
+        moved.push(name);
+    }
+    Ok(moved)
+}
+
+/// Runs `binary` over `output_dir`.
+fn index(output_dir: &Path, binary: &str) -> Result<()> {
     let site_arg = output_dir
         .to_str()
         .context("output directory path is not valid UTF-8")?;
@@ -69,13 +115,13 @@ mod tests {
     #[test]
     fn run_pagefind_succeeds_with_output() {
         let dir = tempfile::tempdir().unwrap();
-        run_pagefind(dir.path(), Some("echo")).unwrap();
+        run_pagefind(dir.path(), Some("echo"), &[]).unwrap();
     }
 
     #[test]
     fn run_pagefind_missing_binary_returns_error() {
         let dir = tempfile::tempdir().unwrap();
-        let err = run_pagefind(dir.path(), Some("nonexistent-pagefind-binary-xyz"))
+        let err = run_pagefind(dir.path(), Some("nonexistent-pagefind-binary-xyz"), &[])
             .unwrap_err()
             .to_string();
         assert!(
@@ -91,7 +137,7 @@ mod tests {
     #[test]
     fn run_pagefind_non_zero_exit_returns_error() {
         let dir = tempfile::tempdir().unwrap();
-        let result = run_pagefind(dir.path(), Some("false"));
+        let result = run_pagefind(dir.path(), Some("false"), &[]);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(
@@ -99,4 +145,42 @@ mod tests {
             "should report exit status, got: {err}"
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_pagefind_skips_excluded_dirs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let site = dir.path().join("public");
+        fs::create_dir_all(site.join("posts")).unwrap();
+        fs::create_dir_all(site.join("drafts").join("wip-0123")).unwrap();
+        fs::write(site.join("posts").join("index.html"), "post").unwrap();
+        fs::write(
+            site.join("drafts").join("wip-0123").join("index.html"),
+            "draft",
+        )
+        .unwrap();
+        // Stands in for Pagefind, recording the files it would index.
+        let binary = dir.path().join("fake-pagefind");
+        fs::write(
+            &binary,
+            "#!/bin/sh\nfind \"$2\" -name '*.html' > \"$2/../indexed\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+
+        run_pagefind(&site, binary.to_str(), &["drafts", "missing"]).unwrap();
+
+        let indexed = fs::read_to_string(dir.path().join("indexed")).unwrap();
+        assert!(indexed.contains("posts"), "indexed:\n{indexed}");
+        assert!(!indexed.contains("drafts"), "indexed:\n{indexed}");
+        assert!(
+            site.join("drafts")
+                .join("wip-0123")
+                .join("index.html")
+                .exists()
+        );
+        assert!(!append_suffix(&site, ".unindexed").exists());
+    }
 }
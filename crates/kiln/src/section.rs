@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 
-use crate::content::frontmatter;
+use crate::content::frontmatter::{self, Frontmatter};
 use crate::content::page::{Page, PageKind};
 use crate::text::titlecase;
 
@@ -11,6 +11,9 @@ pub struct Section {
     pub slug: String,
     pub title: String,
     pub page_count: usize,
+    /// Default template for the section's posts, from `page_template` in its
+    /// `_index.md`.
+    pub page_template: Option<String>,
 }
 
 /// Collects sections from discovered pages.
@@ -18,7 +21,8 @@ pub struct Section {
 /// A section is the first subdirectory under `content/posts/` for pages with
 /// `PageKind::Post { section: Some(_) }`. Each section's display title is loaded
 /// from `content/posts/<section>/_index.md` if present, falling back to the
-/// titlecased slug.
+/// titlecased slug. The same file may set `page_template`, the default
+/// template of the section's posts.
 ///
 /// Returns sections sorted alphabetically by slug.
 #[must_use]
@@ -37,11 +41,18 @@ pub fn collect_sections(pages: &[Page], content_dir: &Path) -> Vec<Section> {
         .into_iter()
         .map(|(slug, page_count)| {
             let section_dir = content_dir.join("posts").join(&slug);
-            let title = load_index_title(&section_dir).unwrap_or_else(|| titlecase(&slug));
+            let index = load_index(&section_dir);
+            let title = index
+                .as_ref()
+                .map(|fm| fm.title.clone())
+                .filter(|title| !title.is_empty())
+                .unwrap_or_else(|| titlecase(&slug));
+            let page_template = index.and_then(|fm| fm.page_template);
             Section {
                 slug,
                 title,
                 page_count,
+                page_template,
             }
         })
         .collect()
@@ -52,8 +63,7 @@ pub fn collect_sections(pages: &[Page], content_dir: &Path) -> Vec<Section> {
 /// Returns `None` if the file is missing, has invalid frontmatter, or an
 /// empty title.
 pub(crate) fn load_index_title(dir: &Path) -> Option<String> {
-    let content = std::fs::read_to_string(dir.join("_index.md")).ok()?;
-    let (fm, _) = frontmatter::parse(&content).ok()?;
+    let fm = load_index(dir)?;
     if fm.title.is_empty() {
         None
     } else {
@@ -61,6 +71,15 @@ pub(crate) fn load_index_title(dir: &Path) -> Option<String> {
     }
 }
 
+/// Loads the frontmatter of `_index.md` in the given directory.
+///
+/// Returns `None` if the file is missing or has invalid frontmatter.
+fn load_index(dir: &Path) -> Option<Frontmatter> {
+    let content = std::fs::read_to_string(dir.join("_index.md")).ok()?;
+    let (fm, _) = frontmatter::parse(&content).ok()?;
+    Some(fm)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -130,6 +149,30 @@ mod tests {
         assert_eq!(sections.len(), 1);
         assert_eq!(sections[0].title, "笔记");
         assert_eq!(sections[0].slug, "note");
+        assert_eq!(sections[0].page_template, None);
+    }
+
+    #[test]
+    fn collect_sections_reads_page_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().join("content");
+        let section_dir = content_dir.join("posts").join("gallery");
+        fs::create_dir_all(&section_dir).unwrap();
+        fs::write(
+            section_dir.join("_index.md"),
+            indoc! {r#"
+                +++
+                page_template = "photo.html"
+                +++
+            "#},
+        )
+        .unwrap();
+
+        let pages = vec![make_page("Post 1", Some("gallery"))];
+        let sections = collect_sections(&pages, &content_dir);
+
+        assert_eq!(sections[0].title, "Gallery");
+        assert_eq!(sections[0].page_template.as_deref(), Some("photo.html"));
     }
 
     #[test]
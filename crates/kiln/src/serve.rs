@@ -3,8 +3,9 @@
 use std::fs;
 use std::future::Future;
 use std::net::SocketAddr;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
 use axum::Router;
@@ -21,7 +22,7 @@ use tokio::sync::{broadcast, mpsc};
 use tower::ServiceExt;
 use tower_http::services::ServeDir;
 
-use crate::build::BuildOptions;
+use crate::build::{BuildOptions, BuildReport};
 use crate::config::Config;
 
 /// Default port for `kiln serve` (KILN on a phone keypad: K=5 I=4 L=5 N=6).
@@ -39,6 +40,10 @@ const LIVE_RELOAD_PATH: &str = "/__kiln_live_reload";
 /// Debounce duration for file watcher events.
 const DEBOUNCE: Duration = Duration::from_millis(100);
 
+/// How far a file's modification time may fall before the start of the
+/// `[hooks]` command that wrote it.
+const MTIME_SLACK: Duration = Duration::from_millis(50);
+
 /// JavaScript snippet injected before `</body>` in HTML responses.
 ///
 /// Uses a WebSocket instead of `EventSource` (SSE) for live reload.
@@ -127,9 +132,11 @@ async fn serve_until(
         root,
         BuildOptions {
             base_url_override: Some(&base_url),
+            dev: true,
             ..Default::default()
         },
     )
+    .and_then(BuildReport::into_result)
     .context("initial build failed")?;
 
     let config = Config::load(root).context("failed to load config")?;
@@ -148,6 +155,7 @@ async fn serve_until(
     tokio::spawn(watch_loop(
         rebuild_root,
         base_url.clone(),
+        !config.hooks.is_empty(),
         watch_rx,
         rebuild_tx,
     ));
@@ -155,7 +163,7 @@ async fn serve_until(
     let app = build_router(&output_dir, reload_tx);
 
     eprintln!("\nServing at {base_url} (Press Ctrl+C to stop)");
-    eprint!("Watching: config.toml, content/, templates/, static/");
+    eprint!("Watching: config.toml, content/, templates/, static/, sass/, data/, syntaxes/");
     if let Some(ref theme) = config.theme {
         eprint!(", themes/{theme}/");
     }
@@ -190,7 +198,7 @@ struct WatchEntry {
 fn setup_watcher(
     root: &Path,
     config: &Config,
-    event_tx: mpsc::UnboundedSender<()>,
+    event_tx: mpsc::UnboundedSender<Vec<PathBuf>>,
 ) -> Result<notify::RecommendedWatcher> {
     let mut watcher =
         notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
@@ -202,7 +210,7 @@ fn setup_watcher(
                         | notify::EventKind::Remove(_)
                 ) =>
             {
-                _ = event_tx.send(());
+                _ = event_tx.send(event.paths);
             }
             Ok(_) => {}
             Err(e) => tracing::warn!("file watcher error: {e}"),
@@ -238,7 +246,7 @@ fn watch_paths(root: &Path, config: &Config) -> Vec<WatchEntry> {
         });
     }
 
-    for dir in ["content", "templates", "static"] {
+    for dir in ["content", "templates", "static", "sass", "data", "syntaxes"] {
         let path = root.join(dir);
         if path.is_dir() {
             paths.push(WatchEntry {
@@ -263,14 +271,20 @@ fn watch_paths(root: &Path, config: &Config) -> Vec<WatchEntry> {
 }
 
 /// Debounced rebuild loop: waits for watcher events, rebuilds, and notifies WebSocket clients.
+///
+/// With `hooks`, events raised while rebuilding only trigger another rebuild
+/// for files the `[hooks]` commands did not write: hooks may write into
+/// watched directories, and reacting to their writes would rebuild forever.
 async fn watch_loop(
     root: PathBuf,
     base_url: String,
-    mut event_rx: mpsc::UnboundedReceiver<()>,
+    hooks: bool,
+    mut event_rx: mpsc::UnboundedReceiver<Vec<PathBuf>>,
     reload_tx: broadcast::Sender<()>,
 ) {
+    let mut edited = false;
     loop {
-        if event_rx.recv().await.is_none() {
+        if !edited && event_rx.recv().await.is_none() {
             break;
         }
         tokio::time::sleep(DEBOUNCE).await;
@@ -280,12 +294,25 @@ async fn watch_loop(
         let root = root.clone();
         let base_url = base_url.clone();
         let result = tokio::task::spawn_blocking(move || safe_rebuild(&root, &base_url)).await;
+        let hook_runs = match &result {
+            Ok((_, hook_runs)) => hook_runs.as_deref(),
+            Err(_) => None,
+        };
+        edited = false;
+        if hooks {
+            while let Ok(paths) = event_rx.try_recv() {
+                // Without the times the hooks ran, their writes cannot be
+                // told apart, so the events are dropped.
+                edited |= hook_runs
+                    .is_some_and(|runs| paths.iter().any(|path| !written_by_hooks(path, runs)));
+            }
+        }
 
         match result {
-            Ok(Ok(())) => {
+            Ok((Ok(()), _)) => {
                 _ = reload_tx.send(());
             }
-            Ok(Err(e)) => {
+            Ok((Err(e), _)) => {
                 eprintln!("Rebuild failed: {e:?}");
             }
             Err(e) => {
@@ -295,58 +322,44 @@ async fn watch_loop(
     }
 }
 
-/// Builds the site into a staging directory, then swaps it into place.
-///
-/// The live output directory stays intact while the new build runs, so the
-/// server never serves from a missing or partially written directory. On
-/// success the swap is two back-to-back renames (microseconds). On failure
-/// the staging directory is removed and the live output is untouched.
-fn safe_rebuild(root: &Path, base_url: &str) -> Result<()> {
-    let config = Config::load(root).context("failed to load config")?;
-    let output_dir = config
-        .resolved_output_dir(root)
-        .context("failed to resolve output_dir")?;
-    let staging_dir = append_suffix(&output_dir, ".staging");
-    let backup_dir = append_suffix(&output_dir, ".prev");
-
-    if staging_dir.exists() {
-        _ = fs::remove_dir_all(&staging_dir);
-    }
+/// Returns whether `path` was last modified while one of the `[hooks]`
+/// commands in `hook_runs` ran. Files since removed were not.
+fn written_by_hooks(path: &Path, hook_runs: &[Range<SystemTime>]) -> bool {
+    let Ok(modified) = fs::symlink_metadata(path).and_then(|meta| meta.modified()) else {
+        return false;
+    };
+    hook_runs.iter().any(|run| {
+        // Filesystem timestamps may lag the clock by a tick.
+        let start = run.start.checked_sub(MTIME_SLACK).unwrap_or(run.start);
+        (start..=run.end).contains(&modified)
+    })
+}
 
-    let build_result = crate::build(
+/// Rebuilds the site against the dev server's `base_url`, returning the
+/// outcome along with when its `[hooks]` commands ran: `None` if the build
+/// stopped before it could report them, in which case they may have run.
+///
+/// [`crate::build`] writes into a staging directory and syncs it into place
+/// only on success, so a failed rebuild leaves the live output untouched.
+/// Each file is replaced by a rename, so the server never serves a
+/// half-written file, though a request during the sync may see old and new
+/// pages side by side.
+fn safe_rebuild(root: &Path, base_url: &str) -> (Result<()>, Option<Vec<Range<SystemTime>>>) {
+    let built = crate::build(
         root,
         BuildOptions {
             base_url_override: Some(base_url),
-            output_dir_override: Some(&staging_dir),
+            dev: true,
             ..Default::default()
         },
     );
-    if let Err(e) = build_result {
-        _ = fs::remove_dir_all(&staging_dir);
-        return Err(e);
-    }
-
-    // Quick swap: live → backup, staging → live, remove backup.
-    if backup_dir.exists() {
-        _ = fs::remove_dir_all(&backup_dir);
-    }
-    if output_dir.exists() {
-        fs::rename(&output_dir, &backup_dir).context("failed to back up output directory")?;
-    }
-    fs::rename(&staging_dir, &output_dir).context("failed to promote staging directory")?;
-    if backup_dir.exists() {
-        _ = fs::remove_dir_all(&backup_dir);
+    match built {
+        Ok(mut report) => {
+            let hook_runs = std::mem::take(&mut report.hook_runs);
+            (report.into_result(), Some(hook_runs))
+        }
+        Err(e) => (Err(e), None),
     }
-    Ok(())
-}
-
-/// Returns `path` with `suffix` appended to its full OS string, so callers can
-/// derive a sibling path that preserves any nested components (e.g. `dist/site`
-/// → `dist/site.staging`).
-fn append_suffix(path: &Path, suffix: &str) -> PathBuf {
-    let mut buf = path.as_os_str().to_owned();
-    buf.push(suffix);
-    PathBuf::from(buf)
 }
 
 /// Creates the axum router with WebSocket live reload and static file serving.
@@ -756,16 +769,18 @@ mod tests {
         fs::create_dir(root.path().join("content")).unwrap();
         fs::create_dir(root.path().join("templates")).unwrap();
         fs::create_dir(root.path().join("static")).unwrap();
+        fs::create_dir(root.path().join("sass")).unwrap();
         fs::write(root.path().join("config.toml"), "").unwrap();
 
         let config: Config = toml::from_str("").unwrap();
         let paths = watch_paths(root.path(), &config);
 
-        assert_eq!(paths.len(), 4);
+        assert_eq!(paths.len(), 5);
         assert!(paths[0].path.ends_with("config.toml") && !paths[0].recursive);
         assert!(paths[1].path.ends_with("content") && paths[1].recursive);
         assert!(paths[2].path.ends_with("templates") && paths[2].recursive);
         assert!(paths[3].path.ends_with("static") && paths[3].recursive);
+        assert!(paths[4].path.ends_with("sass") && paths[4].recursive);
     }
 
     #[test]
@@ -827,12 +842,13 @@ mod tests {
         tokio::spawn(watch_loop(
             root_path,
             "http://localhost:0".to_owned(),
+            false,
             event_rx,
             reload_tx,
         ));
 
         // Trigger a rebuild event.
-        event_tx.send(()).unwrap();
+        event_tx.send(Vec::new()).unwrap();
 
         // Should receive a reload signal after successful rebuild.
         let result = tokio::time::timeout(Duration::from_secs(5), reload_rx.recv()).await;
@@ -861,12 +877,13 @@ mod tests {
         tokio::spawn(watch_loop(
             root_path,
             "http://localhost:0".to_owned(),
+            false,
             event_rx,
             reload_tx,
         ));
 
         // Trigger a rebuild event — rebuild will fail.
-        event_tx.send(()).unwrap();
+        event_tx.send(Vec::new()).unwrap();
 
         // Allow time for debounce + rebuild attempt.
         tokio::time::sleep(Duration::from_millis(500)).await;
@@ -889,6 +906,7 @@ mod tests {
         let handle = tokio::spawn(watch_loop(
             root_path,
             "http://localhost:0".to_owned(),
+            false,
             event_rx,
             reload_tx,
         ));
@@ -907,17 +925,16 @@ mod tests {
     // ── safe_rebuild ──
 
     #[test]
-    fn safe_rebuild_success_cleans_temp_dirs() {
+    fn safe_rebuild_success_removes_staging() {
         let root = tempfile::tempdir().unwrap();
         setup_site(root.path());
 
         crate::build(root.path(), BuildOptions::default()).unwrap();
         assert!(root.path().join("public").exists());
 
-        safe_rebuild(root.path(), "http://localhost:0").unwrap();
+        safe_rebuild(root.path(), "http://localhost:0").0.unwrap();
         assert!(root.path().join("public").exists());
         assert!(!root.path().join("public.staging").exists());
-        assert!(!root.path().join("public.prev").exists());
     }
 
     #[test]
@@ -940,15 +957,13 @@ mod tests {
         )
         .unwrap();
 
-        assert!(safe_rebuild(root.path(), "http://localhost:0").is_err());
+        assert!(safe_rebuild(root.path(), "http://localhost:0").0.is_err());
 
         let preserved = fs::read_to_string(&output).unwrap();
         assert_eq!(
             preserved, original,
             "output should be untouched after failed rebuild"
         );
-        assert!(!root.path().join("public.staging").exists());
-        assert!(!root.path().join("public.prev").exists());
     }
 
     #[test]
@@ -958,13 +973,13 @@ mod tests {
 
         assert!(!root.path().join("public").exists());
 
-        safe_rebuild(root.path(), "http://localhost:0").unwrap();
+        safe_rebuild(root.path(), "http://localhost:0").0.unwrap();
         assert!(root.path().join("public").exists());
         assert!(!root.path().join("public.staging").exists());
     }
 
     #[test]
-    fn safe_rebuild_cleans_leftover_staging() {
+    fn safe_rebuild_sweeps_leftover_staging() {
         let root = tempfile::tempdir().unwrap();
         setup_site(root.path());
         crate::build(root.path(), BuildOptions::default()).unwrap();
@@ -973,24 +988,61 @@ mod tests {
         fs::create_dir_all(staging.join("stale")).unwrap();
         fs::write(staging.join("stale").join("old.html"), "leftover").unwrap();
 
-        safe_rebuild(root.path(), "http://localhost:0").unwrap();
+        safe_rebuild(root.path(), "http://localhost:0").0.unwrap();
         assert!(root.path().join("public").exists());
-        assert!(!staging.exists(), "leftover staging dir should be removed");
+        assert!(!root.path().join("public").join("stale").exists());
+        assert!(
+            !staging.join("stale").exists(),
+            "leftover staging files should be removed"
+        );
     }
 
     #[test]
-    fn safe_rebuild_cleans_leftover_backup() {
+    fn safe_rebuild_reports_hook_runs() {
         let root = tempfile::tempdir().unwrap();
         setup_site(root.path());
-        crate::build(root.path(), BuildOptions::default()).unwrap();
+        fs::write(
+            root.path().join("config.toml"),
+            "[hooks]\npre_build = [\"echo hi > content/generated.txt\"]\n",
+        )
+        .unwrap();
+
+        let (result, hook_runs) = safe_rebuild(root.path(), "http://localhost:0");
+        result.unwrap();
+        let hook_runs = hook_runs.unwrap();
+        assert_eq!(hook_runs.len(), 1);
+        assert!(written_by_hooks(
+            &root.path().join("content/generated.txt"),
+            &hook_runs
+        ));
+    }
 
-        let backup = root.path().join("public.prev");
-        fs::create_dir_all(&backup).unwrap();
-        fs::write(backup.join("old.html"), "leftover").unwrap();
+    // ── written_by_hooks ──
 
-        safe_rebuild(root.path(), "http://localhost:0").unwrap();
-        assert!(root.path().join("public").exists());
-        assert!(!backup.exists(), "leftover backup dir should be removed");
+    #[test]
+    fn written_by_hooks_checks_modification_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let (written, edited) = (
+            dir.path().join("written.txt"),
+            dir.path().join("edited.txt"),
+        );
+        let start = SystemTime::now();
+        fs::write(&written, "hook").unwrap();
+        let hook_runs = [start..SystemTime::now()];
+        fs::write(&edited, "author").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&edited)
+            .unwrap()
+            .set_modified(start + Duration::from_mins(1))
+            .unwrap();
+
+        assert!(written_by_hooks(&written, &hook_runs));
+        assert!(!written_by_hooks(&edited, &hook_runs));
+        assert!(!written_by_hooks(
+            &dir.path().join("removed.txt"),
+            &hook_runs
+        ));
     }
 
     // ── build_router ──
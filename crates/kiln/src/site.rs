@@ -0,0 +1,339 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::build::{BuildOptions, BuildReport, build};
+use crate::config::Config;
+use crate::content::discovery::discover_content;
+use crate::content::page::Page;
+use crate::transform::ContentTransform;
+
+/// A kiln site, for driving builds from other Rust programs (custom CLIs,
+/// tests, serverless builders) without going through the `kiln` binary.
+///
+/// Load a site from its root directory, adjust it with the `with_*`
+/// methods, then build it or inspect its pages:
+///
+/// ```no_run
+/// # fn main() -> anyhow::Result<()> {
+/// let report = kiln::Site::load("my-blog")?
+///     .with_drafts(true)
+///     .with_base_url("https://preview.example.com")
+///     .build()?;
+/// report.into_result()?;
+/// # Ok(())
+/// # }
+/// ```
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent build switches, each set by its own `with_*` method"
+)]
+pub struct Site {
+    root: PathBuf,
+    config: Config,
+    drafts: bool,
+    base_url: Option<String>,
+    output_dir: Option<PathBuf>,
+    minify: bool,
+    check_links: bool,
+    strict: bool,
+    transforms: Vec<Arc<dyn ContentTransform>>,
+}
+
+impl fmt::Debug for Site {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Site")
+            .field("root", &self.root)
+            .field("config", &self.config)
+            .field("drafts", &self.drafts)
+            .field("base_url", &self.base_url)
+            .field("output_dir", &self.output_dir)
+            .field("minify", &self.minify)
+            .field("check_links", &self.check_links)
+            .field("strict", &self.strict)
+            .field("transforms", &self.transforms.len())
+            .finish()
+    }
+}
+
+impl Site {
+    /// Loads the site rooted at `root`, reading its `config.toml` (see
+    /// [`Config::load`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config cannot be loaded.
+    pub fn load(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        let config = Config::load(&root).context("failed to load config")?;
+        Ok(Self {
+            root,
+            config,
+            drafts: false,
+            base_url: None,
+            output_dir: None,
+            minify: false,
+            check_links: false,
+            strict: false,
+            transforms: Vec::new(),
+        })
+    }
+
+    /// Publishes drafts like any other page, for previews.
+    #[must_use]
+    pub fn with_drafts(mut self, drafts: bool) -> Self {
+        self.drafts = drafts;
+        self
+    }
+
+    /// Builds with `base_url` in place of the one in `config.toml`.
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Writes into `output_dir` instead of the configured output directory.
+    #[must_use]
+    pub fn with_output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    /// Minifies HTML, CSS, and JS output, as `kiln build --minify` does.
+    #[must_use]
+    pub fn with_minify(mut self, minify: bool) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    /// Fails the build on broken internal links, as `--check-links` does.
+    #[must_use]
+    pub fn with_check_links(mut self, check_links: bool) -> Self {
+        self.check_links = check_links;
+        self
+    }
+
+    /// Fails the build on soft failures, as `--strict` does.
+    #[must_use]
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Adds a custom processing step over every page, run after the ones
+    /// added before it.
+    #[must_use]
+    pub fn with_transform(mut self, transform: impl ContentTransform + 'static) -> Self {
+        self.transforms.push(Arc::new(transform));
+        self
+    }
+
+    /// The site's root directory.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The site's config, as loaded from `config.toml`.
+    #[must_use]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Loads the site's published pages, followed by its drafts when
+    /// [`with_drafts`](Self::with_drafts) is set, as they are on disk:
+    /// [`ContentTransform`]s only run when building.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content directory cannot be read or a page
+    /// fails to load.
+    pub fn pages(&self) -> Result<Vec<Page>> {
+        let mut content = discover_content(&self.root, self.config.slugify)?;
+        if self.drafts {
+            content.pages.append(&mut content.drafts);
+        }
+        Ok(content.pages)
+    }
+
+    /// Builds the site (see [`build`]) with the config read by
+    /// [`load`](Self::load), not `config.toml` as it is now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the build fails; pages that fail to build are
+    /// listed in the returned [`BuildReport`] instead.
+    pub fn build(&self) -> Result<BuildReport> {
+        build(
+            &self.root,
+            BuildOptions {
+                config: Some(&self.config),
+                base_url_override: self.base_url.as_deref(),
+                output_dir_override: self.output_dir.as_deref(),
+                minify: self.minify,
+                check_links: self.check_links,
+                strict: self.strict,
+                drafts: self.drafts,
+                transforms: &self.transforms,
+                ..BuildOptions::default()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use anyhow::bail;
+
+    use super::*;
+    use crate::test_utils::{copy_templates, write_test_file};
+
+    /// Expands `{{ year }}` in markdown and appends a marker to the HTML.
+    struct Stamp;
+
+    impl ContentTransform for Stamp {
+        fn transform_markdown(&self, _page: &Page, markdown: &str) -> Result<String> {
+            Ok(markdown.replace("{{ year }}", "2026"))
+        }
+
+        fn transform_html(&self, page: &Page, html: &str) -> Result<String> {
+            Ok(format!("{html}<!-- stamped {} -->", page.frontmatter.title))
+        }
+    }
+
+    /// Fails every page.
+    struct Broken;
+
+    impl ContentTransform for Broken {
+        fn transform_markdown(&self, _page: &Page, _markdown: &str) -> Result<String> {
+            bail!("no")
+        }
+    }
+
+    fn setup_site(root: &Path) {
+        write_test_file(root, "config.toml", r#"base_url = "https://example.com""#);
+        copy_templates(&root.join("templates"));
+        write_test_file(
+            root,
+            "content/posts/hello/index.md",
+            indoc! {r#"
+                +++
+                title = "Hello"
+                +++
+                Body of {{ year }}
+            "#},
+        );
+        write_test_file(
+            root,
+            "content/posts/wip/index.md",
+            indoc! {r#"
+                +++
+                title = "WIP"
+                draft = true
+                +++
+                Soon
+            "#},
+        );
+    }
+
+    // ── pages ──
+
+    #[test]
+    fn pages_includes_drafts_when_enabled() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site(root.path());
+
+        let site = Site::load(root.path()).unwrap();
+        let titles = |site: &Site| -> Vec<String> {
+            site.pages()
+                .unwrap()
+                .into_iter()
+                .map(|page| page.frontmatter.title)
+                .collect()
+        };
+        assert_eq!(titles(&site), ["Hello"]);
+        assert_eq!(titles(&site.with_drafts(true)), ["Hello", "WIP"]);
+    }
+
+    // ── build ──
+
+    #[test]
+    fn build_applies_options() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site(root.path());
+        let output = root.path().join("preview");
+
+        let report = Site::load(root.path())
+            .unwrap()
+            .with_drafts(true)
+            .with_base_url("https://preview.example.com")
+            .with_output_dir(&output)
+            .build()
+            .unwrap();
+        assert!(report.failures.is_empty());
+        assert!(output.join("posts/wip/index.html").exists());
+        let html = std::fs::read_to_string(output.join("posts/hello/index.html")).unwrap();
+        assert!(
+            html.contains("https://preview.example.com"),
+            "html:\n{html}"
+        );
+    }
+
+    #[test]
+    fn build_uses_loaded_config() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site(root.path());
+        let site = Site::load(root.path()).unwrap();
+        write_test_file(
+            root.path(),
+            "config.toml",
+            r#"base_url = "https://changed.example.com""#,
+        );
+
+        let report = site.build().unwrap();
+        assert!(report.failures.is_empty());
+        let html =
+            std::fs::read_to_string(root.path().join("public/posts/hello/index.html")).unwrap();
+        assert!(html.contains("https://example.com"), "html:\n{html}");
+        assert!(!html.contains("changed.example.com"), "html:\n{html}");
+    }
+
+    #[test]
+    fn build_runs_transforms() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site(root.path());
+
+        let report = Site::load(root.path())
+            .unwrap()
+            .with_transform(Stamp)
+            .build()
+            .unwrap();
+        assert!(report.failures.is_empty());
+        let html =
+            std::fs::read_to_string(root.path().join("public/posts/hello/index.html")).unwrap();
+        assert!(html.contains("Body of 2026"), "html:\n{html}");
+        assert!(html.ends_with("<!-- stamped Hello -->"), "html:\n{html}");
+    }
+
+    #[test]
+    fn build_reports_failing_transforms_per_page() {
+        let root = tempfile::tempdir().unwrap();
+        setup_site(root.path());
+
+        let report = Site::load(root.path())
+            .unwrap()
+            .with_transform(Broken)
+            .build()
+            .unwrap();
+        let err = report.into_result().unwrap_err();
+        assert!(
+            format!("{err:#}").contains("content transform failed"),
+            "got: {err:#}"
+        );
+    }
+}
@@ -1,28 +1,53 @@
-use indoc::{formatdoc, indoc};
+use indoc::formatdoc;
 
-use crate::html::{self, writeln_indented};
+use crate::html::writeln_indented;
+use crate::xml::{escape, write_element};
 
 /// A single URL entry in the sitemap.
 #[derive(Debug)]
 pub struct SitemapEntry {
     pub loc: String,
     pub lastmod: Option<String>,
+    /// Every language version of the page, itself included, listed as
+    /// `hreflang` alternates. Empty for untranslated pages.
+    pub alternates: Vec<SitemapAlternate>,
+}
+
+/// A language version of a sitemap URL.
+#[derive(Debug)]
+pub struct SitemapAlternate {
+    pub hreflang: String,
+    pub href: String,
 }
 
 /// Generates an XML sitemap from a list of URL entries.
+///
+/// The XHTML namespace for `hreflang` alternates is declared only when some
+/// entry has them.
 #[must_use]
 pub fn generate_sitemap(entries: &[SitemapEntry]) -> String {
-    let mut xml = String::from(indoc! {r#"
-        <?xml version="1.0" encoding="utf-8" standalone="yes"?>
-        <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
-    "#});
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8" standalone="yes"?>"#);
+    xml.push_str("\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"");
+    if entries.iter().any(|entry| !entry.alternates.is_empty()) {
+        xml.push_str(r#" xmlns:xhtml="http://www.w3.org/1999/xhtml""#);
+    }
+    xml.push_str(">\n");
 
     for entry in entries {
         writeln_indented!(&mut xml, 1, "<url>");
-        writeln_indented!(&mut xml, 2, "<loc>{}</loc>", html::escape(&entry.loc));
+        write_element(&mut xml, 2, "loc", &entry.loc);
 
         if let Some(ref lastmod) = entry.lastmod {
-            writeln_indented!(&mut xml, 2, "<lastmod>{}</lastmod>", html::escape(lastmod));
+            write_element(&mut xml, 2, "lastmod", lastmod);
+        }
+        for alternate in &entry.alternates {
+            writeln_indented!(
+                &mut xml,
+                2,
+                r#"<xhtml:link rel="alternate" hreflang="{}" href="{}"/>"#,
+                escape(&alternate.hreflang),
+                escape(&alternate.href)
+            );
         }
 
         writeln_indented!(&mut xml, 1, "</url>");
@@ -58,10 +83,12 @@ mod tests {
             SitemapEntry {
                 loc: "https://example.com/".into(),
                 lastmod: None,
+                alternates: Vec::new(),
             },
             SitemapEntry {
                 loc: "https://example.com/posts/hello/".into(),
                 lastmod: Some("2026-03-15T10:00:00+00:00".into()),
+                alternates: Vec::new(),
             },
         ];
 
@@ -79,6 +106,7 @@ mod tests {
         let entries = vec![SitemapEntry {
             loc: "https://example.com/tags/c&c++/".into(),
             lastmod: None,
+            alternates: Vec::new(),
         }];
 
         let xml = generate_sitemap(&entries);
@@ -89,6 +117,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn generate_sitemap_lists_hreflang_alternates() {
+        let alternates = || {
+            vec![
+                SitemapAlternate {
+                    hreflang: "en".into(),
+                    href: "https://example.com/hello/".into(),
+                },
+                SitemapAlternate {
+                    hreflang: "zh".into(),
+                    href: "https://example.com/ni-hao/".into(),
+                },
+            ]
+        };
+        let entries = vec![
+            SitemapEntry {
+                loc: "https://example.com/hello/".into(),
+                lastmod: None,
+                alternates: alternates(),
+            },
+            SitemapEntry {
+                loc: "https://example.com/ni-hao/".into(),
+                lastmod: None,
+                alternates: alternates(),
+            },
+        ];
+
+        let xml = generate_sitemap(&entries);
+
+        assert!(
+            xml.contains(r#"xmlns:xhtml="http://www.w3.org/1999/xhtml""#),
+            "xml:\n{xml}"
+        );
+        assert_eq!(
+            xml.matches(
+                r#"<xhtml:link rel="alternate" hreflang="zh" href="https://example.com/ni-hao/"/>"#
+            )
+            .count(),
+            2,
+            "xml:\n{xml}"
+        );
+        assert!(
+            !generate_sitemap(&[]).contains("xmlns:xhtml"),
+            "namespace only declared when needed"
+        );
+    }
+
     #[test]
     fn generate_sitemap_empty() {
         let xml = generate_sitemap(&[]);
@@ -104,6 +179,7 @@ mod tests {
         let entries = vec![SitemapEntry {
             loc: "https://example.com/about/".into(),
             lastmod: None,
+            alternates: Vec::new(),
         }];
 
         let xml = generate_sitemap(&entries);
@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use jiff::tz::TimeZone;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::config::{Config, Markdown};
+use crate::content::discovery::discover_content;
+use crate::content::format::ContentFormat;
+use crate::content::page::Page;
+use crate::render::markdown::markdown_options;
+use crate::taxonomy::{TaxonomyKind, build_taxonomies};
+use crate::text::word_count;
+
+/// Matches an HTML tag, for reducing HTML pages to their text.
+static TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<[^>]*>").expect("tag regex should compile"));
+
+/// Authorship statistics over the site's non-draft posts.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ContentStats {
+    pub posts: usize,
+    /// Posts without a `date`, left out of `per_year` / `per_month`.
+    pub undated_posts: usize,
+    pub total_words: usize,
+    /// Mean words per post, rounded down.
+    pub average_words: usize,
+    /// Posts per year, in the site's time zone.
+    pub per_year: BTreeMap<i16, usize>,
+    /// Posts per month (`YYYY-MM`), in the site's time zone.
+    pub per_month: BTreeMap<String, usize>,
+    /// Tags by page count descending, then name ascending.
+    pub tags: Vec<TagCount>,
+    pub longest: Option<PostLength>,
+    pub shortest: Option<PostLength>,
+}
+
+/// Number of pages carrying a tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagCount {
+    pub name: String,
+    pub pages: usize,
+}
+
+/// A post and its word count.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PostLength {
+    pub title: String,
+    /// Source file, relative to the content directory.
+    pub path: PathBuf,
+    pub words: usize,
+}
+
+/// Summarizes posting cadence, tag distribution, and post lengths across the
+/// site's content.
+///
+/// Word counts cover the text of the page body, excluding code blocks and
+/// markup; see [`word_count`] for how words are counted.
+///
+/// # Errors
+///
+/// Returns an error if configuration loading or content discovery fails, or
+/// if the configured time zone is invalid.
+pub fn content_stats(root: &Path) -> Result<ContentStats> {
+    let config = Config::load(root).context("failed to load config")?;
+    let time_zone = config.time_zone()?.unwrap_or(TimeZone::UTC);
+    let content = discover_content(root, config.slugify)?;
+
+    let mut stats = ContentStats::default();
+    for page in content.pages.iter().filter(|page| page.is_post()) {
+        stats.posts += 1;
+        match page.frontmatter.date {
+            Some(date) => {
+                let zoned = date.to_zoned(time_zone.clone());
+                *stats.per_year.entry(zoned.year()).or_default() += 1;
+                let month = format!("{:04}-{:02}", zoned.year(), zoned.month());
+                *stats.per_month.entry(month).or_default() += 1;
+            }
+            None => stats.undated_posts += 1,
+        }
+
+        let length = PostLength {
+            title: page.frontmatter.title.clone(),
+            path: page
+                .source_path
+                .strip_prefix(&content.content_dir)
+                .unwrap_or(&page.source_path)
+                .to_owned(),
+            words: word_count(&plain_text(page, config.markdown)),
+        };
+        stats.total_words += length.words;
+        if stats
+            .longest
+            .as_ref()
+            .is_none_or(|longest| length.words > longest.words)
+        {
+            stats.longest = Some(length.clone());
+        }
+        if stats
+            .shortest
+            .as_ref()
+            .is_none_or(|shortest| length.words < shortest.words)
+        {
+            stats.shortest = Some(length);
+        }
+    }
+    stats.average_words = stats.total_words.checked_div(stats.posts).unwrap_or(0);
+
+    let taxonomies = build_taxonomies(
+        &content.pages,
+        &[TaxonomyKind::Tags],
+        config.slugify.mode(),
+        Some(&content.content_dir),
+    );
+    stats.tags = taxonomies
+        .taxonomies
+        .into_iter()
+        .flat_map(|taxonomy| taxonomy.terms)
+        .map(|term| TagCount {
+            name: term.name,
+            pages: term.page_count,
+        })
+        .collect();
+
+    Ok(stats)
+}
+
+/// Extracts the readable text of a page body: markdown without markup and
+/// code blocks, or HTML without tags.
+fn plain_text(page: &Page, extensions: Markdown) -> String {
+    if page.format == ContentFormat::Html {
+        return TAG_RE.replace_all(&page.raw_content, " ").into_owned();
+    }
+
+    let mut text = String::new();
+    let mut in_code_block = false;
+    for event in Parser::new_ext(&page.raw_content, markdown_options(extensions)) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(t) | Event::Code(t) if !in_code_block => text.push_str(&t),
+            Event::End(
+                TagEnd::Emphasis
+                | TagEnd::Strong
+                | TagEnd::Strikethrough
+                | TagEnd::Superscript
+                | TagEnd::Subscript
+                | TagEnd::Link,
+            ) => {}
+            Event::End(_) | Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    text
+}
+
+impl fmt::Display for ContentStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Posts: {}", self.posts)?;
+        if self.undated_posts > 0 {
+            write!(f, " ({} undated)", self.undated_posts)?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Words: {} total, {} per post on average",
+            self.total_words, self.average_words
+        )?;
+        for (label, post) in [("Longest", &self.longest), ("Shortest", &self.shortest)] {
+            if let Some(post) = post {
+                writeln!(
+                    f,
+                    "{label}: {} ({}, {} words)",
+                    post.title,
+                    post.path.display(),
+                    post.words
+                )?;
+            }
+        }
+
+        write_counts(f, "Posts per year", self.per_year.iter())?;
+        write_counts(f, "Posts per month", self.per_month.iter())?;
+        write_counts(
+            f,
+            "Tags",
+            self.tags.iter().map(|tag| (&tag.name, &tag.pages)),
+        )
+    }
+}
+
+/// Writes a titled two-column table, or `(none)` when empty.
+fn write_counts<'a, K: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    title: &str,
+    rows: impl Iterator<Item = (K, &'a usize)>,
+) -> fmt::Result {
+    writeln!(f, "{title}:")?;
+    let rows: Vec<(String, usize)> = rows.map(|(key, count)| (key.to_string(), *count)).collect();
+    if rows.is_empty() {
+        writeln!(f, "  (none)")?;
+    }
+    let width = rows
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (key, count) in rows {
+        writeln!(f, "  {key:<width$}  {count}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    fn write_site(root: &Path) {
+        write_test_file(root, "config.toml", r#"timezone = "Asia/Shanghai""#);
+        write_test_file(
+            root,
+            "content/posts/short/index.md",
+            indoc! {r#"
+                +++
+                title = "Short"
+                date = 2026-01-31T18:00:00Z
+                tags = ["Rust"]
+                +++
+                Just **three** words.
+            "#},
+        );
+        write_test_file(
+            root,
+            "content/posts/long/index.md",
+            indoc! {r#"
+                +++
+                title = "Long"
+                date = 2026-01-10T00:00:00Z
+                tags = ["rust", "Web"]
+                +++
+                # Heading here
+
+                Some `inline` text and [a link](https://example.com).
+
+                ```rust
+                fn ignored() {}
+                ```
+            "#},
+        );
+        write_test_file(
+            root,
+            "content/posts/undated.md",
+            "+++\ntitle = \"Undated\"\n+++\n你好世界\n",
+        );
+        write_test_file(
+            root,
+            "content/about.md",
+            "+++\ntitle = \"About\"\n+++\nNot a post at all.\n",
+        );
+    }
+
+    // ── content_stats ──
+
+    #[test]
+    fn content_stats_summarizes_posts() {
+        let root = tempfile::tempdir().unwrap();
+        write_site(root.path());
+
+        let stats = content_stats(root.path()).unwrap();
+        assert_eq!(stats.posts, 3);
+        assert_eq!(stats.undated_posts, 1);
+        assert_eq!(stats.total_words, 3 + 8 + 4);
+        assert_eq!(stats.average_words, 5);
+        // 2026-01-31T18:00Z is already February in Asia/Shanghai.
+        assert_eq!(stats.per_year, BTreeMap::from([(2026, 2)]));
+        assert_eq!(
+            stats.per_month,
+            BTreeMap::from([("2026-01".into(), 1), ("2026-02".into(), 1)])
+        );
+        assert_eq!(
+            stats.tags,
+            [
+                TagCount {
+                    name: "Rust".into(),
+                    pages: 2,
+                },
+                TagCount {
+                    name: "Web".into(),
+                    pages: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            stats.longest.as_ref().map(|post| post.title.as_str()),
+            Some("Long")
+        );
+        assert_eq!(
+            stats.shortest,
+            Some(PostLength {
+                title: "Short".into(),
+                path: PathBuf::from("posts/short/index.md"),
+                words: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn content_stats_empty_site() {
+        let root = tempfile::tempdir().unwrap();
+        write_test_file(root.path(), "config.toml", "");
+
+        let stats = content_stats(root.path()).unwrap();
+        assert_eq!(stats, ContentStats::default());
+        assert_eq!(
+            stats.to_string(),
+            indoc! {"
+                Posts: 0
+                Words: 0 total, 0 per post on average
+                Posts per year:
+                  (none)
+                Posts per month:
+                  (none)
+                Tags:
+                  (none)
+            "}
+        );
+    }
+}
@@ -1,16 +1,17 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use strum::{EnumIter, IntoEnumIterator};
-
+use crate::config::SlugifyMode;
 use crate::content::frontmatter;
 use crate::content::page::Page;
 use crate::text::slugify;
 
 /// Built-in taxonomy kinds.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TaxonomyKind {
     Tags,
+    /// Page authors, built with `author_pages`.
+    Authors,
 }
 
 impl TaxonomyKind {
@@ -19,6 +20,7 @@ impl TaxonomyKind {
     pub fn singular(self) -> &'static str {
         match self {
             Self::Tags => "tag",
+            Self::Authors => "author",
         }
     }
 
@@ -27,6 +29,15 @@ impl TaxonomyKind {
     pub fn plural(self) -> &'static str {
         match self {
             Self::Tags => "tags",
+            Self::Authors => "authors",
+        }
+    }
+
+    /// Returns the frontmatter values of this kind on `page`.
+    fn terms(self, page: &Page) -> &[String] {
+        match self {
+            Self::Tags => &page.frontmatter.tags,
+            Self::Authors => &page.frontmatter.authors,
         }
     }
 }
@@ -58,26 +69,29 @@ pub struct TaxonomySet {
     pub term_pages: HashMap<(TaxonomyKind, String), Vec<usize>>,
 }
 
-/// Builds taxonomies from the given page collection.
+/// Builds the taxonomies of `kinds` from the given page collection.
 ///
-/// Groups pages by their tag values, deduplicates terms by slug, and sorts
+/// Groups pages by their term values, deduplicates terms by slug, and sorts
 /// terms by page count descending (then name ascending). Page indices within
 /// each term are in the same order as the input (newest first).
 ///
-/// When `content_dir` is provided, looks for `<kind>/<slug>/_index.md` files
-/// with a `title` field to override the display name.
+/// Term slugs are generated under `slugify_mode`. When `content_dir` is
+/// provided, looks for `<kind>/<slug>/_index.md` files with a `title` field
+/// to override the display name.
 #[must_use]
-pub fn build_taxonomies(pages: &[Page], content_dir: Option<&Path>) -> TaxonomySet {
+pub fn build_taxonomies(
+    pages: &[Page],
+    kinds: &[TaxonomyKind],
+    slugify_mode: SlugifyMode,
+    content_dir: Option<&Path>,
+) -> TaxonomySet {
     // Collect (kind, slug) → (display_name, Vec<page_index>).
     let mut grouped: HashMap<(TaxonomyKind, String), (String, Vec<usize>)> = HashMap::new();
 
     for (idx, page) in pages.iter().enumerate() {
-        collect_terms(
-            &page.frontmatter.tags,
-            TaxonomyKind::Tags,
-            idx,
-            &mut grouped,
-        );
+        for &kind in kinds {
+            collect_terms(kind.terms(page), kind, slugify_mode, idx, &mut grouped);
+        }
     }
 
     let mut term_pages = HashMap::new();
@@ -96,14 +110,14 @@ pub fn build_taxonomies(pages: &[Page], content_dir: Option<&Path>) -> TaxonomyS
         term_pages.insert((kind, slug), indices);
     }
 
-    // Sort terms: page count descending, then name ascending.
     for terms in kind_terms.values_mut() {
-        terms.sort_by(|a, b| b.page_count.cmp(&a.page_count).then(a.name.cmp(&b.name)));
+        sort_terms(terms);
     }
 
-    // Always emit one Taxonomy per kind so index pages are generated even when empty.
-    let taxonomies = TaxonomyKind::iter()
-        .map(|kind| Taxonomy {
+    // Always emit one Taxonomy per requested kind so index pages are generated even when empty.
+    let taxonomies = kinds
+        .iter()
+        .map(|&kind| Taxonomy {
             kind,
             terms: kind_terms.remove(&kind).unwrap_or_default(),
         })
@@ -115,6 +129,11 @@ pub fn build_taxonomies(pages: &[Page], content_dir: Option<&Path>) -> TaxonomyS
     }
 }
 
+/// Sorts terms by page count descending, then name ascending.
+pub(crate) fn sort_terms(terms: &mut [Term]) {
+    terms.sort_by(|a, b| b.page_count.cmp(&a.page_count).then(a.name.cmp(&b.name)));
+}
+
 /// Loads the display title from a term's `_index.md` file.
 ///
 /// Looks for `<content_dir>/<kind_plural>/<slug>/_index.md` with TOML
@@ -135,6 +154,7 @@ fn load_term_title(content_dir: &Path, kind: TaxonomyKind, slug: &str) -> Option
 fn collect_terms(
     values: &[String],
     kind: TaxonomyKind,
+    slugify_mode: SlugifyMode,
     page_idx: usize,
     grouped: &mut HashMap<(TaxonomyKind, String), (String, Vec<usize>)>,
 ) {
@@ -143,11 +163,34 @@ fn collect_terms(
         if trimmed.is_empty() {
             continue;
         }
-        let slug = slugify(trimmed);
+        let slug = term_slug(trimmed, slugify_mode);
         grouped
             .entry((kind, slug))
             .and_modify(|(_, indices)| indices.push(page_idx))
-            .or_insert_with(|| (trimmed.to_owned(), vec![page_idx]));
+            .or_insert_with_key(|(_, slug)| {
+                if slugify(trimmed, slugify_mode).is_empty() {
+                    tracing::warn!(
+                        term = trimmed,
+                        slug = %slug,
+                        "{} is empty once slugified under [slugify] mode; using the safe slug",
+                        kind.singular()
+                    );
+                }
+                (trimmed.to_owned(), vec![page_idx])
+            });
+    }
+}
+
+/// Returns the URL slug of the taxonomy term `term`, generated under
+/// `slugify_mode`. Terms that slugify to nothing (e.g., a Chinese tag under
+/// `ascii`) fall back to the `safe` slug, so they keep a page of their own.
+#[must_use]
+pub fn term_slug(term: &str, slugify_mode: SlugifyMode) -> String {
+    let slug = slugify(term, slugify_mode);
+    if slug.is_empty() {
+        slugify(term, SlugifyMode::Safe)
+    } else {
+        slug
     }
 }
 
@@ -170,14 +213,16 @@ mod tests {
     fn kind_names() {
         assert_eq!(TaxonomyKind::Tags.singular(), "tag");
         assert_eq!(TaxonomyKind::Tags.plural(), "tags");
+        assert_eq!(TaxonomyKind::Authors.singular(), "author");
+        assert_eq!(TaxonomyKind::Authors.plural(), "authors");
     }
 
     // ── build_taxonomies ──
 
     #[test]
     fn build_taxonomies_empty() {
-        let set = build_taxonomies(&[], None);
-        // Always produces one Taxonomy per kind, even with no pages.
+        let set = build_taxonomies(&[], &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
+        // Always produces one Taxonomy per requested kind, even with no pages.
         assert_eq!(set.taxonomies.len(), 1);
         assert_eq!(set.taxonomies[0].kind, TaxonomyKind::Tags);
         assert!(set.taxonomies[0].terms.is_empty());
@@ -186,7 +231,7 @@ mod tests {
     #[test]
     fn build_taxonomies_single_tag() {
         let pages = [make_page("Post 1", &["rust"])];
-        let set = build_taxonomies(&pages, None);
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
 
         let tags = set
             .taxonomies
@@ -206,7 +251,7 @@ mod tests {
             make_page("Post 2", &["rust"]),
             make_page("Post 3", &["web"]),
         ];
-        let set = build_taxonomies(&pages, None);
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
 
         let tags = set
             .taxonomies
@@ -221,13 +266,37 @@ mod tests {
         assert_eq!(tags.terms[1].page_count, 2);
     }
 
+    #[test]
+    fn build_taxonomies_ascii_slugs() {
+        let pages = [make_page("Post", &["Café Culture"])];
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Ascii, None);
+
+        let term = &set.taxonomies[0].terms[0];
+        assert_eq!(term.name, "Café Culture");
+        assert_eq!(term.slug, "cafe-culture");
+    }
+
+    #[test]
+    fn build_taxonomies_ascii_keeps_cjk_terms_apart() {
+        let pages = [make_page("Post", &["测试", "随笔"])];
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Ascii, None);
+
+        let mut slugs: Vec<_> = set.taxonomies[0]
+            .terms
+            .iter()
+            .map(|term| term.slug.as_str())
+            .collect();
+        slugs.sort_unstable();
+        assert_eq!(slugs, ["测试", "随笔"]);
+    }
+
     #[test]
     fn build_taxonomies_case_insensitive_slugs() {
         let pages = [
             make_page("Post 1", &["Rust"]),
             make_page("Post 2", &["rust"]),
         ];
-        let set = build_taxonomies(&pages, None);
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
 
         let tags = set
             .taxonomies
@@ -249,7 +318,7 @@ mod tests {
             make_page("Post 2", &["common", "alpha"]),
             make_page("Post 3", &["common"]),
         ];
-        let set = build_taxonomies(&pages, None);
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
 
         let tags = set
             .taxonomies
@@ -272,7 +341,7 @@ mod tests {
             make_page("Newest", &["rust"]),
             make_page("Oldest", &["rust"]),
         ];
-        let set = build_taxonomies(&pages, None);
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
 
         let indices = &set.term_pages[&(TaxonomyKind::Tags, "rust".to_owned())];
         assert_eq!(
@@ -285,7 +354,7 @@ mod tests {
     #[test]
     fn build_taxonomies_empty_tags_ignored() {
         let pages = [make_page("Post 1", &["", "  ", "rust"])];
-        let set = build_taxonomies(&pages, None);
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
 
         let tags = set
             .taxonomies
@@ -296,6 +365,34 @@ mod tests {
         assert_eq!(tags.terms[0].name, "rust");
     }
 
+    #[test]
+    fn build_taxonomies_only_requested_kinds() {
+        let mut page = make_page("Post 1", &["rust"]);
+        page.frontmatter.authors = vec!["alice".into()];
+        let pages = [page];
+
+        let set = build_taxonomies(&pages, &[TaxonomyKind::Tags], SlugifyMode::Safe, None);
+        assert_eq!(set.taxonomies.len(), 1);
+        assert!(
+            !set.term_pages
+                .contains_key(&(TaxonomyKind::Authors, "alice".into()))
+        );
+
+        let set = build_taxonomies(
+            &pages,
+            &[TaxonomyKind::Tags, TaxonomyKind::Authors],
+            SlugifyMode::Safe,
+            None,
+        );
+        let authors = &set.taxonomies[1];
+        assert_eq!(authors.kind, TaxonomyKind::Authors);
+        assert_eq!(authors.terms[0].slug, "alice");
+        assert_eq!(
+            set.term_pages[&(TaxonomyKind::Authors, "alice".into())],
+            [0]
+        );
+    }
+
     // ── load_term_title ──
 
     #[test]
@@ -317,7 +414,12 @@ mod tests {
         .unwrap();
 
         let pages = [make_page("Post 1", &["ml"])];
-        let set = build_taxonomies(&pages, Some(&content_dir));
+        let set = build_taxonomies(
+            &pages,
+            &[TaxonomyKind::Tags],
+            SlugifyMode::Safe,
+            Some(&content_dir),
+        );
 
         let tags = set
             .taxonomies
@@ -339,7 +441,12 @@ mod tests {
         std::fs::create_dir_all(&content_dir).unwrap();
 
         let pages = [make_page("Post 1", &["rust"])];
-        let set = build_taxonomies(&pages, Some(&content_dir));
+        let set = build_taxonomies(
+            &pages,
+            &[TaxonomyKind::Tags],
+            SlugifyMode::Safe,
+            Some(&content_dir),
+        );
 
         let tags = set
             .taxonomies
@@ -370,7 +477,12 @@ mod tests {
         .unwrap();
 
         let pages = [make_page("Post 1", &["rust"])];
-        let set = build_taxonomies(&pages, Some(&content_dir));
+        let set = build_taxonomies(
+            &pages,
+            &[TaxonomyKind::Tags],
+            SlugifyMode::Safe,
+            Some(&content_dir),
+        );
 
         let tags = set
             .taxonomies
@@ -382,4 +494,16 @@ mod tests {
             "should fall back when _index.md has empty title"
         );
     }
+
+    // ── term_slug ──
+
+    #[test]
+    fn term_slug_falls_back_to_safe_when_empty() {
+        assert_eq!(
+            term_slug("Café Culture", SlugifyMode::Ascii),
+            "cafe-culture"
+        );
+        assert_eq!(term_slug("测试", SlugifyMode::Ascii), "测试");
+        assert_eq!(term_slug("测试", SlugifyMode::Safe), "测试");
+    }
 }
@@ -1,28 +1,71 @@
+pub mod asset_url;
+mod builtin;
+pub mod cache;
+mod date;
+mod filters;
+mod head;
+mod share;
 pub mod vars;
 
-use std::path::{Component, Path};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
 
 use anyhow::{Context, Result, ensure};
-use minijinja::path_loader;
 use minijinja::value::Kwargs;
+use minijinja::{AutoEscape, UndefinedBehavior, default_auto_escape_callback, path_loader};
+use regex::Regex;
 use serde::Serialize;
-
+use strum::VariantNames;
+
+use self::asset_url::AssetUrls;
+use self::builtin::builtin_template;
+use self::cache::{DirectiveCache, directive_key};
+use self::date::{DateLocale, format_date};
+use self::head::{HeadInjector, inject_head};
+use self::share::ShareNetwork;
 use self::vars::{
-    ArchivePageVars, ErrorPageVars, HomePageVars, OverviewPageVars, PostTemplateVars,
+    ArchivePageVars, ErrorPageVars, HomePageVars, OverviewPageVars, PageSummary, SiteVars,
+    TocTemplateVars,
 };
+use crate::config::SlugifyMode;
 use crate::i18n::I18n;
 
+/// Globals whose value can change between builds with the directive
+/// context unchanged, or with side effects on the output; directive
+/// templates using them are never cached.
+const UNCACHEABLE_GLOBALS: [&str; 3] = ["asset_url", "now", "site"];
+
+/// Matches an `include`, `import`, `from`, or `extends` tag, capturing the
+/// name of the template it loads when written as a string literal.
+static TEMPLATE_REF_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\{%[-+]?\s*(?:include|import|from|extends)\s+(?:"([^"]*)"|'([^']*)'|\S)"#)
+        .expect("template reference regex should compile")
+});
+
 #[derive(Debug)]
 pub struct TemplateEngine {
     env: minijinja::Environment<'static>,
+    /// Cache of rendered directive templates, when enabled.
+    directive_cache: Option<DirectiveCache>,
+    /// Files read by `read_file()` and `load_asset()` since the last
+    /// directive render began, recorded for the directive cache.
+    reads: Arc<Mutex<Vec<PathBuf>>>,
+    /// Directories `read_file()` and `load_asset()` resolve against, kept
+    /// out of the template context so templates never see them.
+    file_dirs: Arc<Mutex<FileDirs>>,
+    /// HTML inserted before `</head>` of every rendered page (e.g., the
+    /// `[analytics]` script).
+    head_html: Option<String>,
 }
 
 impl TemplateEngine {
     /// Creates a new template engine with layered template loading.
     ///
     /// Templates are resolved by checking `site_dir` first (user overrides),
-    /// then `theme_dir` (theme defaults). At least one directory must be
-    /// provided.
+    /// then `theme_dir` (theme defaults), then kiln's built-in partials
+    /// (e.g., `comments.html`). At least one directory must be provided.
     ///
     /// `site_dir` is silently ignored if it doesn't exist (it's an optional
     /// override layer). `theme_dir`, when provided, must exist.
@@ -58,54 +101,210 @@ impl TemplateEngine {
             .collect();
 
         let mut env = minijinja::Environment::new();
+        env.set_auto_escape_callback(auto_escape);
         env.set_loader(move |name| {
             for loader in &loaders {
                 if let Some(content) = loader(name)? {
                     return Ok(Some(content));
                 }
             }
-            Ok(None)
+            Ok(builtin_template(name).map(str::to_owned))
         });
+        let reads = Arc::new(Mutex::new(Vec::new()));
+        let file_dirs = Arc::new(Mutex::new(FileDirs::default()));
         env.add_function("now", tpl_now);
-        env.add_function("read_file", tpl_read_file);
+        let read_file_reads = Arc::clone(&reads);
+        let read_file_dirs = Arc::clone(&file_dirs);
+        env.add_function("read_file", move |filename: &str| {
+            tpl_read_file(filename, &read_file_dirs, &read_file_reads)
+        });
+        let load_asset_reads = Arc::clone(&reads);
+        let load_asset_dirs = Arc::clone(&file_dirs);
+        env.add_function("load_asset", move |filename: &str| {
+            tpl_load_asset(filename, &load_asset_dirs, &load_asset_reads)
+        });
         env.add_function("parse_csv", tpl_parse_csv);
+        env.add_function("share_url", tpl_share_url);
 
         let t_i18n = i18n.clone();
         env.add_function("t", move |key: &str, kwargs: Kwargs| {
             tpl_t(&t_i18n, key, &kwargs)
         });
+        let date_locale = DateLocale::from_language(i18n.language());
+        env.add_filter(
+            "date",
+            move |value: &str, format: Option<&str>, kwargs: Kwargs| {
+                tpl_date(date_locale, value, format, &kwargs)
+            },
+        );
+        env.add_filter(
+            "truncate",
+            |value: &str, length: Option<usize>, kwargs: Kwargs| {
+                filters::truncate(value, length, &kwargs)
+            },
+        );
+        env.add_filter("slugify", filters::slugify_with(SlugifyMode::default()));
+        env.add_filter("jsonify", |value: &minijinja::Value, kwargs: Kwargs| {
+            filters::jsonify(value, &kwargs)
+        });
+        env.add_filter("group_by", filters::group_by);
+
+        Ok(Self {
+            env,
+            directive_cache: None,
+            reads,
+            file_dirs,
+            head_html: None,
+        })
+    }
 
-        Ok(Self { env })
+    /// Reuses directive template output from `cache` (see
+    /// [`DirectiveCache`]).
+    pub fn set_directive_cache(&mut self, cache: DirectiveCache) {
+        self.directive_cache = Some(cache);
     }
 
-    /// Renders a post page using the `post.html` template.
+    /// Writes the directive cache back to disk, if one is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be written.
+    pub fn save_directive_cache(&self) -> Result<()> {
+        self.directive_cache
+            .as_ref()
+            .map_or(Ok(()), DirectiveCache::save)
+    }
+
+    /// Makes printing or iterating over an undefined variable, or reading
+    /// an attribute of one, a render error (for `strict`). Testing one in an
+    /// `if` is still allowed, so optional variables keep working.
+    pub fn set_strict(&mut self) {
+        self.env
+            .set_undefined_behavior(UndefinedBehavior::SemiStrict);
+    }
+
+    /// Makes the `slugify` filter follow `mode`, so slugs built in templates
+    /// match the site's heading IDs and taxonomy URLs.
+    pub fn set_slugify_mode(&mut self, mode: SlugifyMode) {
+        self.env.add_filter("slugify", filters::slugify_with(mode));
+    }
+
+    /// Inserts `html` before `</head>` of every page rendered from a
+    /// template, so site-wide scripts need no template edits.
+    pub fn set_head_html(&mut self, html: String) {
+        self.head_html = Some(html);
+    }
+
+    /// Sets the site-level `data/` directory searched by `load_asset()` after
+    /// the page bundle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock on the file directories is poisoned.
+    pub fn set_data_dir(&mut self, dir: &Path) {
+        self.file_dirs.lock().expect("file dirs lock poisoned").data = Some(dir.to_owned());
+    }
+
+    /// Runs `render` with `read_file()` and `load_asset()` resolving against
+    /// the page bundle directory `dir` (see [`Page::bundle_dir`]). Outside
+    /// it, and for pages that are not bundles, only `data/` is readable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock on the file directories is poisoned.
+    ///
+    /// [`Page::bundle_dir`]: crate::content::page::Page::bundle_dir
+    pub fn with_bundle_dir<T>(&self, dir: Option<&Path>, render: impl FnOnce() -> T) -> T {
+        let lock = || self.file_dirs.lock().expect("file dirs lock poisoned");
+        let previous = std::mem::replace(&mut lock().bundle, dir.map(Path::to_owned));
+        let result = render();
+        lock().bundle = previous;
+        result
+    }
+
+    /// Exposes `asset_url()`, resolving output file paths to URLs through
+    /// `urls` (see [`AssetUrls`]).
+    pub fn set_asset_urls(&mut self, urls: Arc<AssetUrls>) {
+        self.env.add_function("asset_url", move |path: &str| {
+            urls.url(path).map_err(|e| {
+                minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, format!("{e:#}"))
+            })
+        });
+    }
+
+    /// Exposes `site.pages`, the summaries of every listable page, to all
+    /// templates.
+    pub fn set_site_pages(&mut self, pages: &[PageSummary]) {
+        self.env
+            .add_global("site", minijinja::Value::from_serialize(SiteVars { pages }));
+    }
+
+    /// Renders the template `name` (e.g., a page's frontmatter `template`)
+    /// with `vars`.
     ///
     /// # Errors
     ///
     /// Returns an error if the template is missing or rendering fails.
-    pub fn render_post(&self, vars: &PostTemplateVars<'_>) -> Result<String> {
+    pub fn render_template(&self, name: &str, vars: impl Serialize) -> Result<String> {
         let template = self
             .env
-            .get_template("post.html")
-            .context("failed to load post.html template")?;
-        template
+            .get_template(name)
+            .with_context(|| format!("failed to load {name} template"))?;
+        let html = template
             .render(vars)
-            .context("failed to render post template")
+            .with_context(|| format!("failed to render {name} template"))?;
+        Ok(self.with_head_html(html))
     }
 
-    /// Renders a standalone page using the `page.html` template.
+    /// Renders the template `name` with `vars` straight into `out`, without
+    /// holding the whole output in memory.
     ///
     /// # Errors
     ///
-    /// Returns an error if the template is missing or rendering fails.
-    pub fn render_page(&self, vars: &PostTemplateVars<'_>) -> Result<String> {
+    /// Returns an error if the template is missing, or rendering or writing
+    /// fails.
+    pub fn render_template_to(
+        &self,
+        name: &str,
+        vars: impl Serialize,
+        out: impl io::Write,
+    ) -> Result<()> {
         let template = self
             .env
-            .get_template("page.html")
-            .context("failed to load page.html template")?;
+            .get_template(name)
+            .with_context(|| format!("failed to load {name} template"))?;
+        let Some(head_html) = &self.head_html else {
+            template
+                .render_to_write(vars, out)
+                .with_context(|| format!("failed to render {name} template"))?;
+            return Ok(());
+        };
+        let mut out = HeadInjector::new(out, head_html);
         template
-            .render(vars)
-            .context("failed to render page template")
+            .render_to_write(vars, &mut out)
+            .with_context(|| format!("failed to render {name} template"))?;
+        out.finish()
+            .with_context(|| format!("failed to write {name} template output"))
+    }
+
+    /// Renders the template `name`, or `fallback` as its source when no
+    /// template directory has one (e.g., a built-in default).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering fails.
+    pub fn render_template_or(
+        &self,
+        name: &str,
+        fallback: &str,
+        vars: impl Serialize,
+    ) -> Result<String> {
+        if self.has_template(name) {
+            return self.render_template(name, vars);
+        }
+        self.env
+            .render_named_str(name, fallback, vars)
+            .with_context(|| format!("failed to render {name} template"))
     }
 
     /// Renders the home page using the `home.html` template.
@@ -114,13 +313,7 @@ impl TemplateEngine {
     ///
     /// Returns an error if the template is missing or rendering fails.
     pub fn render_home(&self, vars: &HomePageVars<'_>) -> Result<String> {
-        let template = self
-            .env
-            .get_template("home.html")
-            .context("failed to load home.html template")?;
-        template
-            .render(vars)
-            .context("failed to render home template")
+        self.render_template("home.html", vars)
     }
 
     /// Renders an archive page using the `archive.html` template.
@@ -129,13 +322,7 @@ impl TemplateEngine {
     ///
     /// Returns an error if the template is missing or rendering fails.
     pub fn render_archive(&self, vars: &ArchivePageVars<'_>) -> Result<String> {
-        let template = self
-            .env
-            .get_template("archive.html")
-            .context("failed to load archive.html template")?;
-        template
-            .render(vars)
-            .context("failed to render archive template")
+        self.render_template("archive.html", vars)
     }
 
     /// Renders a bucket overview page (e.g., `/tags/`, `/sections/`).
@@ -144,13 +331,7 @@ impl TemplateEngine {
     ///
     /// Returns an error if the template is missing or rendering fails.
     pub fn render_overview(&self, vars: &OverviewPageVars<'_>) -> Result<String> {
-        let template = self
-            .env
-            .get_template("overview.html")
-            .context("failed to load overview.html template")?;
-        template
-            .render(vars)
-            .context("failed to render overview template")
+        self.render_template("overview.html", vars)
     }
 
     /// Renders the 404 error page using the `404.html` template.
@@ -162,29 +343,120 @@ impl TemplateEngine {
         Some(
             template
                 .render(vars)
+                .map(|html| self.with_head_html(html))
                 .context("failed to render 404 template"),
         )
     }
 
+    /// Renders a page's table of contents using the `toc.html` template.
+    ///
+    /// Returns `None` if the template does not exist. Returns `Some(Err(_))`
+    /// if the template exists but rendering fails.
+    pub fn render_toc(&self, vars: &TocTemplateVars<'_>) -> Option<Result<String>> {
+        let template = self.env.get_template("toc.html").ok()?;
+        Some(
+            template
+                .render(vars)
+                .context("failed to render toc template"),
+        )
+    }
+
     /// Tries to render a directive using a theme template at
     /// `directives/<name>.html`.
     ///
     /// Returns `None` if no template exists for the directive name.
     /// Returns `Some(Err(_))` if the template exists but rendering fails.
+    ///
+    /// With a directive cache set, output is reused while the context and
+    /// the files the template read are unchanged. Templates using `now()`
+    /// or `site` are always rendered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock on the recorded file reads is poisoned.
     pub fn render_directive(&self, name: &str, ctx: impl Serialize) -> Option<Result<String>> {
         let template_name = format!("directives/{name}.html");
         let template = self.env.get_template(&template_name).ok()?;
-        Some(
+        let render = || {
             template
-                .render(ctx)
-                .with_context(|| format!("failed to render directive template: {template_name}")),
-        )
+                .render(&ctx)
+                .with_context(|| format!("failed to render directive template: {template_name}"))
+        };
+
+        let cacheable = |_: &&DirectiveCache| {
+            !self.uses_uncacheable_globals(&template_name, &mut HashSet::new())
+        };
+        let Some(cache) = self.directive_cache.as_ref().filter(cacheable) else {
+            return Some(render());
+        };
+        let Ok(ctx_json) = serde_json::to_string(&ctx) else {
+            return Some(render());
+        };
+        let key = directive_key(&template_name, template.source(), &ctx_json);
+        if let Some(html) = cache.get(&key) {
+            return Some(Ok(html));
+        }
+
+        self.reads.lock().expect("reads lock poisoned").clear();
+        let result = render();
+        if let Ok(html) = &result {
+            let files = std::mem::take(&mut *self.reads.lock().expect("reads lock poisoned"));
+            cache.insert(key, html.clone(), &files);
+        }
+        Some(result)
+    }
+
+    /// Returns `true` if the template `name`, or one it includes, imports, or
+    /// extends, uses one of [`UNCACHEABLE_GLOBALS`]. Templates loaded by a
+    /// computed name, or missing, count as using them, since they cannot be
+    /// followed; `seen` breaks cycles.
+    fn uses_uncacheable_globals(&self, name: &str, seen: &mut HashSet<String>) -> bool {
+        if !seen.insert(name.to_owned()) {
+            return false;
+        }
+        let Ok(template) = self.env.get_template(name) else {
+            return true;
+        };
+        let globals = template.undeclared_variables(false);
+        if UNCACHEABLE_GLOBALS
+            .iter()
+            .any(|global| globals.contains(*global))
+        {
+            return true;
+        }
+        TEMPLATE_REF_RE
+            .captures_iter(template.source())
+            .any(|caps| match caps.get(1).or_else(|| caps.get(2)) {
+                Some(referenced) => self.uses_uncacheable_globals(referenced.as_str(), seen),
+                None => true,
+            })
     }
 
     /// Returns `true` if a template with the given name exists.
     pub fn has_template(&self, name: &str) -> bool {
         self.env.get_template(name).is_ok()
     }
+
+    /// Inserts the [`set_head_html`](Self::set_head_html) HTML into `html`.
+    fn with_head_html(&self, html: String) -> String {
+        match &self.head_html {
+            Some(head_html) => inject_head(html, head_html),
+            None => html,
+        }
+    }
+}
+
+/// Escapes SVG templates (e.g., `og_image.svg`) like HTML, on top of
+/// `MiniJinja`'s defaults.
+fn auto_escape(name: &str) -> AutoEscape {
+    if Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+    {
+        AutoEscape::Html
+    } else {
+        default_auto_escape_callback(name)
+    }
 }
 
 /// `MiniJinja` template function: returns the current local timestamp as an
@@ -195,34 +467,114 @@ fn tpl_now() -> String {
     jiff::Zoned::now().to_string()
 }
 
-/// `MiniJinja` template function: reads a file relative to the directive's
-/// `source_dir` context variable.
+/// Directories template functions may read files from.
+#[derive(Debug, Default)]
+struct FileDirs {
+    /// The bundle directory of the page being rendered, if it is a bundle.
+    bundle: Option<PathBuf>,
+    /// The site's `data/` directory.
+    data: Option<PathBuf>,
+}
+
+/// `MiniJinja` template function: reads a file relative to the bundle
+/// directory of the page being rendered.
 ///
 /// Usage in templates: `{% set data = read_file("data.csv") %}`
 ///
 /// Rejects `..`, absolute, and rooted path components to prevent reading
-/// outside the page's source directory.
+/// outside the page bundle.
 fn tpl_read_file(
-    state: &minijinja::State,
     filename: &str,
+    dirs: &Mutex<FileDirs>,
+    reads: &Mutex<Vec<PathBuf>>,
 ) -> std::result::Result<String, minijinja::Error> {
-    let source_dir = state
-        .lookup("source_dir")
-        .filter(|v| !v.is_none() && !v.is_undefined())
+    let bundle_dir = dirs
+        .lock()
+        .expect("file dirs lock poisoned")
+        .bundle
+        .clone()
         .ok_or_else(|| {
             minijinja::Error::new(
                 minijinja::ErrorKind::InvalidOperation,
-                "read_file requires source_dir in directive context",
+                "read_file requires a page bundle",
             )
         })?;
+    let rel = checked_relative_path(filename)?;
+
+    let path = bundle_dir.join(rel);
+    let text = std::fs::read_to_string(&path).map_err(|e| {
+        minijinja::Error::new(
+            minijinja::ErrorKind::InvalidOperation,
+            format!("failed to read {}: {e}", path.display()),
+        )
+    })?;
+    reads.lock().expect("reads lock poisoned").push(path);
+    Ok(text)
+}
+
+/// `MiniJinja` template function: loads a data file from the bundle of the
+/// page being rendered, falling back to the site's `data/` directory.
+///
+/// Usage in templates: `{% set links = load_asset("links.json") %}`
+///
+/// `.json` and `.toml` files are parsed into structured values; any other
+/// extension is returned as raw text. Path rules match [`tpl_read_file`].
+fn tpl_load_asset(
+    filename: &str,
+    dirs: &Mutex<FileDirs>,
+    reads: &Mutex<Vec<PathBuf>>,
+) -> std::result::Result<minijinja::Value, minijinja::Error> {
+    let rel = checked_relative_path(filename)?;
+    let path = {
+        let dirs = dirs.lock().expect("file dirs lock poisoned");
+        [&dirs.bundle, &dirs.data]
+            .into_iter()
+            .flatten()
+            .map(|dir| dir.join(rel))
+            .find(|path| path.is_file())
+    }
+    .ok_or_else(|| {
+        minijinja::Error::new(
+            minijinja::ErrorKind::InvalidOperation,
+            format!("asset not found in page bundle or data/: {filename}"),
+        )
+    })?;
 
-    let source_dir = source_dir.as_str().ok_or_else(|| {
+    let text = std::fs::read_to_string(&path).map_err(|e| {
         minijinja::Error::new(
             minijinja::ErrorKind::InvalidOperation,
-            "source_dir must be a string",
+            format!("failed to read {}: {e}", path.display()),
         )
     })?;
+    reads
+        .lock()
+        .expect("reads lock poisoned")
+        .push(path.clone());
+    let parse_error = |e: &dyn std::fmt::Display| {
+        minijinja::Error::new(
+            minijinja::ErrorKind::InvalidOperation,
+            format!("failed to parse {}: {e}", path.display()),
+        )
+    };
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str::<serde_json::Value>(&text)
+            .map(minijinja::Value::from_serialize)
+            .map_err(|e| parse_error(&e)),
+        Some("toml") => toml::from_str::<toml::Value>(&text)
+            .map(minijinja::Value::from_serialize)
+            .map_err(|e| parse_error(&e)),
+        _ => Ok(minijinja::Value::from(text)),
+    }
+}
 
+/// Validates that `filename` is a plain relative path.
+///
+/// Rejects `..`, absolute, and rooted components so template file access
+/// cannot escape the directory it is resolved against.
+pub(crate) fn checked_relative_path(
+    filename: &str,
+) -> std::result::Result<&Path, minijinja::Error> {
     let rel = Path::new(filename);
     for component in rel.components() {
         if matches!(
@@ -235,14 +587,7 @@ fn tpl_read_file(
             ));
         }
     }
-
-    let path = Path::new(source_dir).join(rel);
-    std::fs::read_to_string(&path).map_err(|e| {
-        minijinja::Error::new(
-            minijinja::ErrorKind::InvalidOperation,
-            format!("failed to read {}: {e}", path.display()),
-        )
-    })
+    Ok(rel)
 }
 
 /// `MiniJinja` template function: looks up an i18n string and interpolates
@@ -277,6 +622,30 @@ fn tpl_t(i18n: &I18n, key: &str, kwargs: &Kwargs) -> std::result::Result<String,
     Ok(i18n.t_interp(key, &args))
 }
 
+/// `MiniJinja` filter: formats an RFC 3339 date with a strftime format,
+/// naming months and weekdays in the site language or `locale`.
+///
+/// Usage in templates: `{{ date | date }}` (`June 15, 2024` / `2024年6月15日`),
+/// `{{ date | date("%Y-%m-%d") }}`, `{{ date | date(format="%b %-d", locale="zh") }}`.
+fn tpl_date(
+    locale: DateLocale,
+    value: &str,
+    format: Option<&str>,
+    kwargs: &Kwargs,
+) -> std::result::Result<String, minijinja::Error> {
+    let format = match kwargs.get::<Option<&str>>("format")? {
+        Some(format) => Some(format),
+        None => format,
+    };
+    let locale = kwargs
+        .get::<Option<&str>>("locale")?
+        .map_or(locale, DateLocale::from_language);
+    kwargs.assert_all_used()?;
+    format_date(value, format, locale).map_err(|e| {
+        minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, format!("{e:#}"))
+    })
+}
+
 /// `MiniJinja` template function: parses CSV text into a list of rows,
 /// where each row is a list of field strings.
 ///
@@ -307,6 +676,46 @@ fn tpl_parse_csv(text: &str) -> std::result::Result<minijinja::Value, minijinja:
     Ok(minijinja::Value::from(rows))
 }
 
+/// `MiniJinja` template function: builds a share link for a social network.
+///
+/// Usage in templates: `{{ share_url("twitter") }}` in post / page templates,
+/// or `{{ share_url("reddit", page) }}` with any object that has a `url` and
+/// optional `title` (e.g., a listing's page summary).
+///
+/// Without `page`, the template's own `url` and `title` variables are used.
+fn tpl_share_url(
+    state: &minijinja::State,
+    network: &str,
+    page: Option<&minijinja::Value>,
+) -> std::result::Result<String, minijinja::Error> {
+    let network: ShareNetwork = network.parse().map_err(|_| {
+        minijinja::Error::new(
+            minijinja::ErrorKind::InvalidOperation,
+            format!(
+                "unknown share network `{network}` (expected one of: {})",
+                ShareNetwork::VARIANTS.join(", ")
+            ),
+        )
+    })?;
+
+    let field = |name: &str| {
+        let value = match page {
+            Some(page) => page.get_attr(name).ok(),
+            None => state.lookup(name),
+        };
+        value.and_then(|v| v.as_str().map(str::to_owned))
+    };
+    let url = field("url").ok_or_else(|| {
+        minijinja::Error::new(
+            minijinja::ErrorKind::InvalidOperation,
+            "share_url requires a page url",
+        )
+    })?;
+    let title = field("title").unwrap_or_default();
+
+    Ok(network.share_url(&url, &title))
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -319,12 +728,13 @@ mod tests {
     use crate::content::frontmatter::FeaturedImage;
     use crate::pagination::PaginationVars;
     use crate::render::assets::PageAssets;
+    use crate::render::toc::TocNode;
     use crate::serve::{DEFAULT_PORT, localhost_url};
     use crate::template::vars::{
-        ArchivePageVars, BucketSummary, ErrorPageVars, HomePageVars, OverviewPageVars, PageGroup,
-        PageSummary, PostTemplateVars,
+        ArchivePageVars, BucketSummary, ErrorPageVars, HomePageVars, LinkedTerm, OverviewPageVars,
+        PageGroup, PageSummary, PostTemplateVars,
     };
-    use crate::test_utils::{test_config, test_engine, test_i18n};
+    use crate::test_utils::{test_config, test_engine, test_i18n, write_test_file};
 
     // ── new ──
 
@@ -394,29 +804,43 @@ mod tests {
         );
     }
 
-    // ── render_post ──
+    // ── render_template: post.html ──
 
     #[test]
     fn render_post_basic() {
         let engine = test_engine();
         let config = test_config();
         let vars = PostTemplateVars {
+            id: "",
             title: "Hello World",
             description: "A test post",
+            summary_html: "",
             url: "https://example.com/posts/hello-world/",
             featured_image: Some(FeaturedImage {
                 src: "/images/hello.webp".into(),
                 ..Default::default()
             }),
+            featured_image_thumb: None,
+            audio: None,
+            og_image: Some("/images/hello.webp".into()),
             page_css: None,
             date: Some("2026-02-24T12:34:56Z".into()),
+            updated: None,
+            authors: Vec::new(),
             section: None,
             assets: PageAssets::default(),
             content: "<p>Body</p>",
             toc: "",
+            toc_entries: &[],
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
             config: &config,
         };
-        let html = engine.render_post(&vars).unwrap();
+        let html = engine.render_template("post.html", &vars).unwrap();
 
         // <head>
         assert!(
@@ -474,19 +898,33 @@ mod tests {
         let engine = test_engine();
         let config = test_config();
         let vars = PostTemplateVars {
+            id: "",
             title: "Test",
             description: "",
+            summary_html: "",
             url: "",
             featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            og_image: None,
             page_css: None,
             date: None,
+            updated: None,
+            authors: Vec::new(),
             section: None,
             assets: PageAssets::default(),
             content: "<strong>bold</strong>",
             toc: r#"<nav class="toc">ToC</nav>"#,
+            toc_entries: &[],
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
             config: &config,
         };
-        let html = engine.render_post(&vars).unwrap();
+        let html = engine.render_template("post.html", &vars).unwrap();
         assert!(
             html.contains("<strong>bold</strong>"),
             "content should not be double-escaped, html:\n{html}"
@@ -502,19 +940,33 @@ mod tests {
         let engine = test_engine();
         let config = test_config();
         let vars = PostTemplateVars {
+            id: "",
             title: "<script>alert(1)</script>",
             description: "",
+            summary_html: "",
             url: "",
             featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            og_image: None,
             page_css: None,
             date: None,
+            updated: None,
+            authors: Vec::new(),
             section: None,
             assets: PageAssets::default(),
             content: "",
             toc: "",
+            toc_entries: &[],
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
             config: &config,
         };
-        let html = engine.render_post(&vars).unwrap();
+        let html = engine.render_template("post.html", &vars).unwrap();
         assert!(
             !html.contains("<script>alert(1)</script>"),
             "title should be auto-escaped, html:\n{html}"
@@ -525,51 +977,138 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_post_exposes_toc_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        test_fs::write(
+            dir.path().join("post.html"),
+            indoc! {"
+                {%- for entry in toc_entries recursive -%}
+                [{{ entry.level }} {{ entry.id }} {{ entry.title }}{{ loop(entry.children) }}]
+                {%- endfor -%}
+            "},
+        )
+        .unwrap();
+        let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
+        let config = test_config();
+        let toc_entries = [TocNode {
+            level: 2,
+            id: "intro".into(),
+            title: "Intro".into(),
+            children: vec![TocNode {
+                level: 3,
+                id: "detail".into(),
+                title: "Detail".into(),
+                children: Vec::new(),
+            }],
+        }];
+        let vars = PostTemplateVars {
+            id: "",
+            title: "Test",
+            description: "",
+            summary_html: "",
+            url: "",
+            featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            og_image: None,
+            page_css: None,
+            date: None,
+            updated: None,
+            authors: Vec::new(),
+            section: None,
+            assets: PageAssets::default(),
+            content: "",
+            toc: "",
+            toc_entries: &toc_entries,
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
+            config: &config,
+        };
+        let html = engine.render_template("post.html", &vars).unwrap();
+        assert_eq!(html.trim(), "[2 intro Intro[3 detail Detail]]");
+    }
+
     #[test]
     fn render_post_missing_template_returns_error() {
         let dir = tempfile::tempdir().unwrap();
         let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
         let config = test_config();
         let vars = PostTemplateVars {
+            id: "",
             title: "Test",
             description: "",
+            summary_html: "",
             url: "",
             featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            og_image: None,
             page_css: None,
             date: None,
+            updated: None,
+            authors: Vec::new(),
             section: None,
             assets: PageAssets::default(),
             content: "",
             toc: "",
+            toc_entries: &[],
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
             config: &config,
         };
-        let err = engine.render_post(&vars).unwrap_err().to_string();
+        let err = engine
+            .render_template("post.html", &vars)
+            .unwrap_err()
+            .to_string();
         assert!(
             err.contains("failed to load post.html template"),
             "should have context message, got: {err}"
         );
     }
 
-    // ── render_page ──
+    // ── render_template: page.html ──
 
     #[test]
     fn render_page_basic() {
         let engine = test_engine();
         let config = test_config();
         let vars = PostTemplateVars {
+            id: "",
             title: "About Me",
             description: "A page about me",
+            summary_html: "",
             url: "https://example.com/about-me/",
             featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            og_image: None,
             page_css: None,
             date: None,
+            updated: None,
+            authors: Vec::new(),
             section: None,
             assets: PageAssets::default(),
             content: "<p>Hello</p>",
             toc: "",
+            toc_entries: &[],
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
             config: &config,
         };
-        let html = engine.render_page(&vars).unwrap();
+        let html = engine.render_template("page.html", &vars).unwrap();
         assert!(
             html.contains(r#"<article class="page">"#),
             "should use page template, html:\n{html}"
@@ -590,19 +1129,36 @@ mod tests {
         let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
         let config = test_config();
         let vars = PostTemplateVars {
+            id: "",
             title: "Test",
             description: "",
+            summary_html: "",
             url: "",
             featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            og_image: None,
             page_css: None,
             date: None,
+            updated: None,
+            authors: Vec::new(),
             section: None,
             assets: PageAssets::default(),
             content: "",
             toc: "",
+            toc_entries: &[],
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
             config: &config,
         };
-        let err = engine.render_page(&vars).unwrap_err().to_string();
+        let err = engine
+            .render_template("page.html", &vars)
+            .unwrap_err()
+            .to_string();
         assert!(
             err.contains("failed to load page.html template"),
             "should report missing template, got: {err}"
@@ -625,11 +1181,15 @@ mod tests {
                 date: Some("2026-01-01T00:00:00Z".into()),
                 pinned: false,
                 description: String::new(),
+                summary_html: String::new(),
                 featured_image: None,
+                featured_image_thumb: None,
+                audio: None,
                 tags: Vec::new(),
                 section: None,
             }],
             pagination: PaginationVars::new("", 1, 1),
+            json_ld: String::new(),
             config: &config,
         };
         let html = engine.render_home(&vars).unwrap();
@@ -653,11 +1213,15 @@ mod tests {
                 date: None,
                 pinned: false,
                 description: String::new(),
+                summary_html: String::new(),
                 featured_image: None,
+                featured_image_thumb: None,
+                audio: None,
                 tags: Vec::new(),
                 section: None,
             }],
             pagination: PaginationVars::new("", 2, 3),
+            json_ld: String::new(),
             config: &config,
         };
         let html = engine.render_home(&vars).unwrap();
@@ -678,6 +1242,7 @@ mod tests {
             url: format!("{}/", config.base_url),
             pages: Vec::new(),
             pagination: PaginationVars::new("", 1, 1),
+            json_ld: String::new(),
             config: &config,
         };
         let err = engine.render_home(&vars).unwrap_err().to_string();
@@ -706,7 +1271,10 @@ mod tests {
                     date: Some("2026-01-15T00:00:00Z".into()),
                     pinned: false,
                     description: String::new(),
+                    summary_html: String::new(),
                     featured_image: None,
+                    featured_image_thumb: None,
+                    audio: None,
                     tags: Vec::new(),
                     section: None,
                 }],
@@ -746,7 +1314,10 @@ mod tests {
                     date: Some("2025-06-01T00:00:00Z".into()),
                     pinned: false,
                     description: String::new(),
+                    summary_html: String::new(),
                     featured_image: None,
+                    featured_image_thumb: None,
+                    audio: None,
                     tags: Vec::new(),
                     section: None,
                 }],
@@ -822,7 +1393,10 @@ mod tests {
                         date: None,
                         pinned: false,
                         description: String::new(),
+                        summary_html: String::new(),
                         featured_image: None,
+                        featured_image_thumb: None,
+                        audio: None,
                         tags: Vec::new(),
                         section: None,
                     }],
@@ -866,7 +1440,10 @@ mod tests {
                 date: None,
                 pinned: false,
                 description: String::new(),
+                summary_html: String::new(),
                 featured_image: None,
+                featured_image_thumb: None,
+                audio: None,
                 tags: Vec::new(),
                 section: None,
             })
@@ -949,6 +1526,49 @@ mod tests {
         );
     }
 
+    // ── render_template ──
+
+    #[test]
+    fn render_template_renders_named_template() {
+        let dir = tempfile::tempdir().unwrap();
+        test_fs::write(dir.path().join("landing.html"), "<main>{{ title }}</main>").unwrap();
+        let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
+
+        let html = engine
+            .render_template("landing.html", minijinja::context! { title => "Hi" })
+            .unwrap();
+        assert_eq!(html, "<main>Hi</main>");
+
+        let err = engine
+            .render_template("missing.html", ())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("failed to load missing.html template"),
+            "got: {err}"
+        );
+    }
+
+    // ── render_template_or ──
+
+    #[test]
+    fn render_template_or_falls_back_and_escapes_svg() {
+        let dir = tempfile::tempdir().unwrap();
+        test_fs::write(dir.path().join("card.svg"), "<svg>{{ title }}!</svg>").unwrap();
+        let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
+        let vars = minijinja::context! { title => "Tom & Jerry" };
+
+        let svg = engine
+            .render_template_or("card.svg", "<svg />", &vars)
+            .unwrap();
+        assert_eq!(svg, "<svg>Tom &amp; Jerry!</svg>");
+
+        let svg = engine
+            .render_template_or("og_image.svg", "<svg>{{ title }}</svg>", &vars)
+            .unwrap();
+        assert_eq!(svg, "<svg>Tom &amp; Jerry</svg>");
+    }
+
     // ── render_directive ──
 
     #[test]
@@ -1030,6 +1650,151 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_directive_reuses_cached_output_until_files_change() {
+        #[derive(Serialize)]
+        struct Ctx {
+            source_dir: String,
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        test_fs::create_dir_all(dir.path().join("templates/directives")).unwrap();
+        test_fs::write(
+            dir.path().join("templates/directives/chart.html"),
+            r#"DATA:{{ read_file("data.csv") }}"#,
+        )
+        .unwrap();
+        test_fs::write(dir.path().join("data.csv"), "a").unwrap();
+        let cache_path = dir.path().join("directives.json");
+        let render = || {
+            let mut engine =
+                TemplateEngine::new(Some(&dir.path().join("templates")), None, &test_i18n())
+                    .unwrap();
+            engine.set_directive_cache(DirectiveCache::load(&cache_path, "v1".into()));
+            let ctx = Ctx {
+                source_dir: dir.path().to_string_lossy().into_owned(),
+            };
+            let html = engine
+                .with_bundle_dir(Some(dir.path()), || engine.render_directive("chart", ctx))
+                .unwrap()
+                .unwrap();
+            engine.save_directive_cache().unwrap();
+            html
+        };
+
+        assert_eq!(render(), "DATA:a");
+        let cached = test_fs::read_to_string(&cache_path).unwrap();
+        test_fs::write(&cache_path, cached.replace("DATA:a", "DATA:cached")).unwrap();
+        assert_eq!(
+            render(),
+            "DATA:cached",
+            "unchanged directives are not rendered"
+        );
+
+        test_fs::write(dir.path().join("data.csv"), "b").unwrap();
+        assert_eq!(render(), "DATA:b", "a read file changed");
+    }
+
+    #[test]
+    fn render_directive_does_not_cache_site_or_now_even_in_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let directives_dir = dir.path().join("directives");
+        test_fs::create_dir_all(&directives_dir).unwrap();
+        test_fs::write(directives_dir.join("clock.html"), "{{ now() }}").unwrap();
+        test_fs::write(
+            directives_dir.join("count.html"),
+            "{{ site.pages | length }}",
+        )
+        .unwrap();
+        test_fs::write(directives_dir.join("plain.html"), "plain").unwrap();
+        test_fs::write(
+            directives_dir.join("stamped.html"),
+            r#"{% include "partials/stamp.html" %}"#,
+        )
+        .unwrap();
+        test_fs::write(
+            directives_dir.join("picked.html"),
+            "{% include stamp_template %}",
+        )
+        .unwrap();
+        test_fs::create_dir_all(dir.path().join("partials")).unwrap();
+        test_fs::write(dir.path().join("partials/stamp.html"), "{{ now() }}").unwrap();
+
+        let cache_path = dir.path().join("directives.json");
+        let mut engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
+        engine.set_site_pages(&[]);
+        engine.set_directive_cache(DirectiveCache::load(&cache_path, "v1".into()));
+        for name in ["clock", "count", "plain", "stamped"] {
+            engine.render_directive(name, ()).unwrap().unwrap();
+        }
+        engine.save_directive_cache().unwrap();
+
+        let cached = test_fs::read_to_string(&cache_path).unwrap();
+        assert_eq!(cached.matches(r#""html":"#).count(), 1, "cache:\n{cached}");
+        assert!(cached.contains(r#""html":"plain""#), "cache:\n{cached}");
+        assert!(
+            engine.uses_uncacheable_globals("directives/picked.html", &mut HashSet::new()),
+            "computed includes cannot be followed"
+        );
+    }
+
+    // ── set_asset_urls ──
+
+    #[test]
+    fn set_asset_urls_exposes_asset_url() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "css/style.css", "a {}");
+        let mut engine = test_engine();
+        engine.set_asset_urls(Arc::new(AssetUrls::new(
+            dir.path(),
+            "https://example.com",
+            false,
+        )));
+
+        let html = engine
+            .env
+            .render_str(r#"{{ asset_url("css/style.css") }}"#, ())
+            .unwrap();
+        assert_eq!(html, "https://example.com/css/style.css");
+        let err = engine
+            .env
+            .render_str(r#"{{ asset_url("missing.css") }}"#, ())
+            .unwrap_err();
+        assert!(err.to_string().contains("asset not found"), "got: {err}");
+    }
+
+    // ── set_site_pages ──
+
+    #[test]
+    fn set_site_pages_exposes_pages_to_every_template() {
+        let mut engine = test_engine();
+        engine.set_site_pages(&[PageSummary {
+            title: "Hello World".into(),
+            url: "/hello/".into(),
+            date: Some("2026-01-01".into()),
+            pinned: false,
+            description: "Greetings.".into(),
+            summary_html: String::new(),
+            featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            tags: vec![LinkedTerm {
+                name: "rust".into(),
+                url: "/tags/rust/".into(),
+            }],
+            section: None,
+        }]);
+        let html = engine
+            .env
+            .render_str(
+                "{% for p in site.pages %}{{ p.title }} {{ p.url }} {{ p.date }} \
+                 {{ p.tags[0].name }} {{ p.description }}{% endfor %}",
+                (),
+            )
+            .unwrap();
+        assert_eq!(html, "Hello World /hello/ 2026-01-01 rust Greetings.");
+    }
+
     // ── has_template ──
 
     #[test]
@@ -1064,7 +1829,7 @@ mod tests {
     // ── tpl_read_file ──
 
     #[test]
-    fn read_file_reads_relative_to_source_dir() {
+    fn read_file_reads_relative_to_bundle_dir() {
         let dir = tempfile::tempdir().unwrap();
         let directives_dir = dir.path().join("directives");
         test_fs::create_dir_all(&directives_dir).unwrap();
@@ -1090,7 +1855,9 @@ mod tests {
             source_dir: Some(source.path().to_string_lossy().into_owned()),
         };
 
-        let result = engine.render_directive("csv-reader", ctx);
+        let result = engine.with_bundle_dir(Some(source.path()), || {
+            engine.render_directive("csv-reader", ctx)
+        });
         let html = result.unwrap().unwrap();
         assert!(
             html.contains("DATA:A,B\n1,2"),
@@ -1110,7 +1877,7 @@ mod tests {
         .unwrap();
 
         let source = tempfile::tempdir().unwrap();
-        // Place a secret file outside source_dir.
+        // Place a secret file outside the bundle.
         test_fs::write(source.path().join("secret.txt"), "SECRET").unwrap();
 
         let engine = TemplateEngine::new(Some(dir.path()), None, &test_i18n()).unwrap();
@@ -1125,7 +1892,9 @@ mod tests {
             source_dir: Some(source.path().join("subdir").to_string_lossy().into_owned()),
         };
 
-        let result = engine.render_directive("reader", ctx);
+        let result = engine.with_bundle_dir(Some(source.path()), || {
+            engine.render_directive("reader", ctx)
+        });
         let err = format!("{:#}", result.unwrap().unwrap_err());
         assert!(
             err.contains("path traversal not allowed"),
@@ -1158,7 +1927,9 @@ mod tests {
             source_dir: Some(source.path().to_string_lossy().into_owned()),
         };
 
-        let result = engine.render_directive("reader", ctx);
+        let result = engine.with_bundle_dir(Some(source.path()), || {
+            engine.render_directive("reader", ctx)
+        });
         let err = format!("{:#}", result.unwrap().unwrap_err());
         assert!(
             err.contains("path traversal not allowed"),
@@ -1167,7 +1938,7 @@ mod tests {
     }
 
     #[test]
-    fn read_file_without_source_dir_returns_error() {
+    fn read_file_without_bundle_dir_returns_error() {
         let dir = tempfile::tempdir().unwrap();
         let directives_dir = dir.path().join("directives");
         test_fs::create_dir_all(&directives_dir).unwrap();
@@ -1192,8 +1963,8 @@ mod tests {
         let result = engine.render_directive("reader", ctx);
         let err = format!("{:#}", result.unwrap().unwrap_err());
         assert!(
-            err.contains("read_file requires source_dir"),
-            "should report missing source_dir, got: {err}"
+            err.contains("read_file requires a page bundle"),
+            "should report the missing bundle, got: {err}"
         );
     }
 
@@ -1222,7 +1993,9 @@ mod tests {
             source_dir: Some(source.path().to_string_lossy().into_owned()),
         };
 
-        let result = engine.render_directive("reader", ctx);
+        let result = engine.with_bundle_dir(Some(source.path()), || {
+            engine.render_directive("reader", ctx)
+        });
         let err = format!("{:#}", result.unwrap().unwrap_err());
         assert!(
             err.contains("failed to read"),
@@ -1230,6 +2003,95 @@ mod tests {
         );
     }
 
+    // ── tpl_load_asset ──
+
+    #[test]
+    fn load_asset_parses_json_from_bundle_dir() {
+        let source = tempfile::tempdir().unwrap();
+        test_fs::write(
+            source.path().join("links.json"),
+            r#"{"items": [{"name": "A"}, {"name": "B"}]}"#,
+        )
+        .unwrap();
+
+        let engine = test_engine();
+        let html = engine
+            .with_bundle_dir(Some(source.path()), || {
+                engine.env.render_str(
+                    r#"{% set d = load_asset("links.json") %}{{ d.items[1].name }}"#,
+                    (),
+                )
+            })
+            .unwrap();
+        assert_eq!(html, "B");
+    }
+
+    #[test]
+    fn load_asset_prefers_bundle_then_falls_back_to_data_dir() {
+        let source = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        test_fs::write(source.path().join("shared.toml"), r#"who = "bundle""#).unwrap();
+        test_fs::write(data.path().join("shared.toml"), r#"who = "data""#).unwrap();
+        test_fs::write(data.path().join("site.toml"), "count = 3").unwrap();
+        test_fs::write(data.path().join("note.txt"), "plain <b>text</b>").unwrap();
+
+        let mut engine = test_engine();
+        engine.set_data_dir(data.path());
+        let html = engine
+            .with_bundle_dir(Some(source.path()), || {
+                engine.env.render_str(
+                    indoc! {r#"
+                    {{- load_asset("shared.toml").who }}
+                    {{ load_asset("site.toml").count }}
+                    {{ load_asset("note.txt") }}"#},
+                    (),
+                )
+            })
+            .unwrap();
+        assert_eq!(html, "bundle\n3\nplain <b>text</b>");
+    }
+
+    #[test]
+    fn load_asset_missing_file_returns_error() {
+        let data = tempfile::tempdir().unwrap();
+        let mut engine = test_engine();
+        engine.set_data_dir(data.path());
+        let err = engine
+            .env
+            .render_str(r#"{{ load_asset("missing.json") }}"#, ())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("asset not found in page bundle or data/: missing.json"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn load_asset_path_traversal_returns_error() {
+        let engine = test_engine();
+        let err = engine
+            .env
+            .render_str(r#"{{ load_asset("../config.toml") }}"#, ())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("path traversal not allowed"), "got: {err}");
+    }
+
+    #[test]
+    fn load_asset_invalid_json_returns_error() {
+        let source = tempfile::tempdir().unwrap();
+        test_fs::write(source.path().join("bad.json"), "{not json").unwrap();
+        let engine = test_engine();
+        let err = engine
+            .with_bundle_dir(Some(source.path()), || {
+                engine.env.render_str(r#"{{ load_asset("bad.json") }}"#, ())
+            })
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("failed to parse"), "got: {err}");
+    }
+
     // ── tpl_t ──
 
     #[test]
@@ -1297,6 +2159,56 @@ mod tests {
         assert_eq!(result, "Hi !");
     }
 
+    // ── tpl_date ──
+
+    #[test]
+    fn date_formats_in_site_language_or_locale() {
+        let engine = test_engine();
+        let result = engine
+            .env
+            .render_str(
+                indoc! {r#"
+                    {{- date | date }}
+                    {{ date | date("%Y/%m/%d %H:%M") }}
+                    {{ date | date(locale="zh-CN") }}
+                    {{ date | date(format="%a, %b %-d", locale="ja") }}"#},
+                minijinja::context! { date => "2024-06-15T09:30:00+08:00" },
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            indoc! {"
+                June 15, 2024
+                2024/06/15 09:30
+                2024年6月15日
+                土, 6月 15"}
+        );
+    }
+
+    #[test]
+    fn date_uses_configured_language_by_default() {
+        let i18n = crate::i18n::I18n::load(Path::new("/nonexistent"), None, "zh-Hans").unwrap();
+        let templates = tempfile::tempdir().unwrap();
+        let engine = TemplateEngine::new(Some(templates.path()), None, &i18n).unwrap();
+        let result = engine
+            .env
+            .render_str(r#"{{ "2024-06-15" | date("%B %A") }}"#, ())
+            .unwrap();
+        assert_eq!(result, "6月 星期六");
+    }
+
+    #[test]
+    fn date_invalid_input_returns_error() {
+        let engine = test_engine();
+        for (template, expected) in [
+            (r#"{{ "soon" | date }}"#, "invalid date `soon`"),
+            (r#"{{ "2024-06-15" | date(lang="en") }}"#, "lang"),
+        ] {
+            let err = engine.env.render_str(template, ()).unwrap_err();
+            assert!(format!("{err:#}").contains(expected), "{template}: {err:#}");
+        }
+    }
+
     // ── tpl_parse_csv ──
 
     #[test]
@@ -1333,7 +2245,12 @@ mod tests {
             source_dir: Some(source.path().to_string_lossy().into_owned()),
         };
 
-        let html = engine.render_directive("csv-test", ctx).unwrap().unwrap();
+        let html = engine
+            .with_bundle_dir(Some(source.path()), || {
+                engine.render_directive("csv-test", ctx)
+            })
+            .unwrap()
+            .unwrap();
         assert_eq!(html, "A,B;1,2;3,4;");
     }
 
@@ -1370,7 +2287,12 @@ mod tests {
             source_dir: Some(source.path().to_string_lossy().into_owned()),
         };
 
-        let html = engine.render_directive("csv-test", ctx).unwrap().unwrap();
+        let html = engine
+            .with_bundle_dir(Some(source.path()), || {
+                engine.render_directive("csv-test", ctx)
+            })
+            .unwrap()
+            .unwrap();
         assert_eq!(
             html,
             "[name|value][field with, comma|has &quot;quotes&quot;]"
@@ -1430,7 +2352,9 @@ mod tests {
             source_dir: Some(source.path().to_string_lossy().into_owned()),
         };
 
-        let result = engine.render_directive("csv-test", ctx);
+        let result = engine.with_bundle_dir(Some(source.path()), || {
+            engine.render_directive("csv-test", ctx)
+        });
         let err = format!("{:#}", result.unwrap().unwrap_err());
         assert!(
             err.contains("CSV parse error"),
@@ -1438,6 +2362,57 @@ mod tests {
         );
     }
 
+    // ── tpl_share_url ──
+
+    #[test]
+    fn share_url_uses_template_context_or_page_argument() {
+        let engine = test_engine();
+        let html = engine
+            .env
+            .render_str(
+                indoc! {r#"
+                    {{- share_url("x") }}
+                    {{ share_url("HackerNews", {"url": "https://e.com/b/", "title": "B"}) }}"#},
+                minijinja::context! { url => "https://e.com/a/", title => "A & B" },
+            )
+            .unwrap();
+        assert_eq!(
+            html,
+            indoc! {"
+                https://x.com/intent/tweet?url=https%3A%2F%2Fe.com%2Fa%2F&text=A%20%26%20B
+                https://news.ycombinator.com/submitlink?u=https%3A%2F%2Fe.com%2Fb%2F&t=B"}
+        );
+    }
+
+    #[test]
+    fn share_url_unknown_network_returns_error() {
+        let engine = test_engine();
+        let err = engine
+            .env
+            .render_str(
+                r#"{{ share_url("myspace") }}"#,
+                minijinja::context! { url => "/" },
+            )
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown share network `myspace`"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn share_url_missing_url_returns_error() {
+        let engine = test_engine();
+        let err = engine
+            .env
+            .render_str(r#"{{ share_url("twitter") }}"#, minijinja::context! {})
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("share_url requires a page url"),
+            "got: {err}"
+        );
+    }
+
     // ── Integration ──
 
     #[test]
@@ -1474,19 +2449,33 @@ mod tests {
         let engine = TemplateEngine::new(Some(templates.path()), None, &i18n).unwrap();
         let config = test_config();
         let vars = PostTemplateVars {
+            id: "",
             title: "",
             description: "",
+            summary_html: "",
             url: "",
             featured_image: None,
+            featured_image_thumb: None,
+            audio: None,
+            og_image: None,
             page_css: None,
             date: Some("2026-03-15T09:00:00Z".into()),
+            updated: None,
+            authors: Vec::new(),
             section: None,
             assets: PageAssets::default(),
             content: "",
             toc: "",
+            toc_entries: &[],
+            related: &[],
+            noindex: false,
+            language: "en",
+            translations: &[],
+            json_ld: String::new(),
+            h_entry: String::new(),
             config: &config,
         };
-        let html = engine.render_post(&vars).unwrap();
+        let html = engine.render_template("post.html", &vars).unwrap();
         assert!(
             html.contains("Posted on 2026-03-15"),
             "should render localized prefix alongside the ISO date slice, html:\n{html}"
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result, bail};
+use sha1::{Digest, Sha1};
+
+use crate::output::{copy_file, output_path, url_path};
+use crate::text::hex;
+
+/// Hex digits of the content hash in a fingerprinted file name (32 bits).
+const HASH_LEN: usize = 8;
+
+/// Resolves `asset_url()` paths to URLs of files in the output directory.
+///
+/// With fingerprinting, each file is copied next to itself under a name
+/// holding a hash of its contents (`css/style.css` →
+/// `css/style.0badf00d.css`), and the URL points at the copy, so it can be
+/// cached for good: changed contents get a new URL.
+#[derive(Debug)]
+pub struct AssetUrls {
+    output_dir: PathBuf,
+    base_url: String,
+    fingerprint: bool,
+    /// Fingerprinted copies written so far, by asset path.
+    fingerprinted: Mutex<BTreeMap<PathBuf, PathBuf>>,
+}
+
+impl AssetUrls {
+    #[must_use]
+    pub fn new(output_dir: &Path, base_url: &str, fingerprint: bool) -> Self {
+        Self {
+            output_dir: output_dir.to_owned(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            fingerprint,
+            fingerprinted: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the URL of the output file at `path` (relative to the output
+    /// directory; a leading `/` is ignored), fingerprinting it if enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` leaves the output directory or names no
+    /// file there, or the fingerprinted copy cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock on the fingerprinted copies is poisoned.
+    pub fn url(&self, path: &str) -> Result<String> {
+        let Some(relative) = output_path(path) else {
+            bail!("invalid asset path `{path}`");
+        };
+        let relative = relative.as_path();
+        let source = self.output_dir.join(relative);
+        if !source.is_file() {
+            bail!("asset not found in output: {path}");
+        }
+        if !self.fingerprint {
+            return Ok(self.to_url(relative));
+        }
+
+        let mut fingerprinted = self.fingerprinted.lock().expect("asset URL lock poisoned");
+        if let Some(copy) = fingerprinted.get(relative) {
+            return Ok(self.to_url(copy));
+        }
+        let bytes =
+            fs::read(&source).with_context(|| format!("failed to read {}", source.display()))?;
+        let copy = fingerprinted_path(relative, &Sha1::digest(bytes));
+        copy_file(&source, &self.output_dir.join(&copy))
+            .with_context(|| format!("failed to fingerprint {}", source.display()))?;
+        let url = self.to_url(&copy);
+        fingerprinted.insert(relative.to_owned(), copy);
+        Ok(url)
+    }
+
+    /// Returns the fingerprinted copies written so far, relative to the
+    /// output directory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock on the fingerprinted copies is poisoned.
+    #[must_use]
+    pub fn fingerprinted(&self) -> Vec<PathBuf> {
+        self.fingerprinted
+            .lock()
+            .expect("asset URL lock poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    fn to_url(&self, relative: &Path) -> String {
+        format!("{}/{}", self.base_url, url_path(relative))
+    }
+}
+
+/// Inserts the first [`HASH_LEN`] hex digits of `digest` before the
+/// extension of `path` (or after its name, without one).
+fn fingerprinted_path(path: &Path, digest: &[u8]) -> PathBuf {
+    let hash = hex(&digest[..HASH_LEN / 2]);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}.{hash}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{hash}"),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    // ── AssetUrls ──
+
+    #[test]
+    fn asset_urls_without_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "css/style.css", "a {}");
+        let urls = AssetUrls::new(dir.path(), "https://example.com/", false);
+
+        assert_eq!(
+            urls.url("/css/style.css").unwrap(),
+            "https://example.com/css/style.css"
+        );
+        assert!(urls.fingerprinted().is_empty());
+    }
+
+    #[test]
+    fn asset_urls_fingerprints_by_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "css/style.css", "a {}");
+        let urls = AssetUrls::new(dir.path(), "https://example.com", true);
+
+        let url = urls.url("css/style.css").unwrap();
+        let hash = url
+            .strip_prefix("https://example.com/css/style.")
+            .and_then(|rest| rest.strip_suffix(".css"));
+        assert_eq!(hash.map(str::len), Some(HASH_LEN), "got: {url}");
+        assert_eq!(urls.url("css/style.css").unwrap(), url, "reused");
+
+        let copy = urls.fingerprinted();
+        assert_eq!(copy.len(), 1);
+        assert_eq!(
+            fs::read_to_string(dir.path().join(&copy[0])).unwrap(),
+            "a {}"
+        );
+
+        write_test_file(dir.path(), "css/style.css", "b {}");
+        let changed = AssetUrls::new(dir.path(), "https://example.com", true);
+        assert_ne!(changed.url("css/style.css").unwrap(), url);
+    }
+
+    #[test]
+    fn asset_urls_rejects_missing_and_escaping_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let urls = AssetUrls::new(&dir.path().join("public"), "https://example.com", true);
+        write_test_file(dir.path(), "secret.txt", "");
+
+        let err = urls.url("missing.css").unwrap_err();
+        assert!(err.to_string().contains("asset not found"), "got: {err}");
+        let err = urls.url("../secret.txt").unwrap_err();
+        assert!(err.to_string().contains("invalid asset path"), "got: {err}");
+    }
+
+    // ── fingerprinted_path ──
+
+    #[test]
+    fn fingerprinted_path_inserts_hash() {
+        let digest = [0x0b, 0xad, 0xf0, 0x0d, 0xff];
+        assert_eq!(
+            fingerprinted_path(Path::new("js/app.min.js"), &digest),
+            PathBuf::from("js/app.min.0badf00d.js")
+        );
+        assert_eq!(
+            fingerprinted_path(Path::new("LICENSE"), &digest),
+            PathBuf::from("LICENSE.0badf00d")
+        );
+    }
+}
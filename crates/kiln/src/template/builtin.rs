@@ -0,0 +1,123 @@
+use indoc::indoc;
+
+/// Partial rendering the `[comments]` thread of a page, for themes to
+/// `{% include "comments.html" %}` in `post.html`. Renders nothing without
+/// `[comments]`.
+///
+/// Threads are keyed by the page `id`, falling back to the page path for
+/// pages without one.
+const COMMENTS: &str = indoc! {r#"
+    {%- if config.comments %}
+    {%- set comments = config.comments %}
+    <section class="comments">
+      {%- if comments.provider == "giscus" %}
+      <script src="https://giscus.app/client.js"
+              data-repo="{{ comments.repo }}"
+              data-repo-id="{{ comments.repo_id }}"
+              data-category="{{ comments.category }}"
+              data-category-id="{{ comments.category_id }}"
+              {%- if id %}
+              data-mapping="specific"
+              data-term="{{ id }}"
+              {%- else %}
+              data-mapping="pathname"
+              {%- endif %}
+              data-reactions-enabled="1"
+              data-theme="{{ comments.theme }}"
+              data-lang="{{ language | default(config.language) }}"
+              data-loading="lazy"
+              crossorigin="anonymous"
+              async></script>
+      {%- else %}
+      <script src="https://utteranc.es/client.js"
+              repo="{{ comments.repo }}"
+              issue-term="{{ id if id else "pathname" }}"
+              theme="{{ comments.theme }}"
+              crossorigin="anonymous"
+              async></script>
+      {%- endif %}
+    </section>
+    {%- endif %}
+"#};
+
+/// Returns the source of the built-in template `name`, loaded when neither
+/// the site nor the theme has one.
+pub(super) fn builtin_template(name: &str) -> Option<&'static str> {
+    match name {
+        "comments.html" => Some(COMMENTS),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minijinja::context;
+
+    use crate::config::Comments;
+    use crate::test_utils::{test_config, test_engine};
+
+    fn render_comments(comments: Comments, id: &str) -> String {
+        let mut config = test_config();
+        config.comments = Some(comments);
+        test_engine()
+            .env
+            .render_str(
+                r#"{% include "comments.html" %}"#,
+                context! { config, id, language => "en" },
+            )
+            .unwrap()
+    }
+
+    // ── builtin_template ──
+
+    #[test]
+    fn comments_giscus_keyed_by_page_id() {
+        let html = render_comments(
+            Comments::Giscus {
+                repo: "me/blog".into(),
+                repo_id: "R_1".into(),
+                category: "Comments".into(),
+                category_id: "DIC_1".into(),
+                theme: "dark".into(),
+            },
+            "posts/hello",
+        );
+        for attr in [
+            r#"data-repo="me&#x2f;blog""#,
+            r#"data-repo-id="R_1""#,
+            r#"data-category-id="DIC_1""#,
+            r#"data-mapping="specific""#,
+            r#"data-term="posts&#x2f;hello""#,
+            r#"data-theme="dark""#,
+            r#"data-lang="en""#,
+        ] {
+            assert!(html.contains(attr), "missing {attr} in:\n{html}");
+        }
+    }
+
+    #[test]
+    fn comments_utterances_falls_back_to_pathname() {
+        let html = render_comments(
+            Comments::Utterances {
+                repo: "me/blog".into(),
+                theme: "github-light".into(),
+            },
+            "",
+        );
+        assert!(html.contains("https://utteranc.es/client.js"), "{html}");
+        assert!(html.contains(r#"issue-term="pathname""#), "{html}");
+        assert!(html.contains(r#"theme="github-light""#), "{html}");
+    }
+
+    #[test]
+    fn comments_empty_without_config() {
+        let html = test_engine()
+            .env
+            .render_str(
+                r#"{% include "comments.html" %}"#,
+                context! { config => test_config() },
+            )
+            .unwrap();
+        assert_eq!(html.trim(), "");
+    }
+}
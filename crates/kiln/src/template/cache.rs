@@ -0,0 +1,418 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use walkdir::WalkDir;
+
+use crate::output::write_output;
+use crate::text::hex;
+
+/// Build cache directory, relative to the site root.
+pub const CACHE_DIR: &str = ".kiln-cache";
+
+/// Rendered directive templates, kept across builds so unchanged directives
+/// are not rendered again.
+///
+/// Entries are keyed by the directive's template and context, and hold the
+/// hashes of the files the template read (`read_file()`, `load_asset()`):
+/// an entry is reused only while they are unchanged. The whole cache is
+/// dropped when its fingerprint (templates, i18n strings, config) changes.
+#[derive(Debug)]
+pub struct DirectiveCache {
+    path: PathBuf,
+    fingerprint: String,
+    entries: Mutex<BTreeMap<String, CacheEntry>>,
+    /// Keys looked up or added this build; the rest are pruned on save.
+    used: Mutex<HashSet<String>>,
+}
+
+/// On-disk form of a [`DirectiveCache`].
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CacheFile {
+    fingerprint: String,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct CacheEntry {
+    html: String,
+    /// Files read while rendering, with their content hashes.
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl DirectiveCache {
+    /// Loads the cache at `path`, starting empty if it is missing,
+    /// unreadable, or was written with a different `fingerprint`.
+    #[must_use]
+    pub fn load(path: &Path, fingerprint: String) -> Self {
+        let file = match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                tracing::warn!(file = %path.display(), "ignoring corrupt directive cache: {e}");
+                CacheFile::default()
+            }),
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!(
+                        file = %path.display(),
+                        "ignoring unreadable directive cache: {e}"
+                    );
+                }
+                CacheFile::default()
+            }
+        };
+        let entries = if file.fingerprint == fingerprint {
+            file.entries
+        } else {
+            BTreeMap::new()
+        };
+        Self {
+            path: path.to_owned(),
+            fingerprint,
+            entries: Mutex::new(entries),
+            used: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Returns the cached HTML for `key`, if its files are unchanged.
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        let entries = self.entries.lock().expect("directive cache lock poisoned");
+        let entry = entries.get(key)?;
+        let fresh = entry
+            .files
+            .iter()
+            .all(|(path, hash)| hash_file(path).is_some_and(|current| current == *hash));
+        if !fresh {
+            return None;
+        }
+        self.mark_used(key);
+        Some(entry.html.clone())
+    }
+
+    /// Stores the HTML rendered for `key`, along with the `files` read
+    /// while rendering it.
+    pub(crate) fn insert(&self, key: String, html: String, files: &[PathBuf]) {
+        let files = files
+            .iter()
+            .filter_map(|path| Some((path.clone(), hash_file(path)?)))
+            .collect();
+        self.mark_used(&key);
+        self.entries
+            .lock()
+            .expect("directive cache lock poisoned")
+            .insert(key, CacheEntry { html, files });
+    }
+
+    fn mark_used(&self, key: &str) {
+        self.used
+            .lock()
+            .expect("directive cache lock poisoned")
+            .insert(key.to_owned());
+    }
+
+    /// Writes the entries used this build back to disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a cache lock is poisoned.
+    pub fn save(&self) -> Result<()> {
+        let entries = self.entries.lock().expect("directive cache lock poisoned");
+        let used = self.used.lock().expect("directive cache lock poisoned");
+        let entries = entries
+            .iter()
+            .filter(|(key, _)| used.contains(*key))
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect();
+        let file = CacheFile {
+            fingerprint: self.fingerprint.clone(),
+            entries,
+        };
+        let json = serde_json::to_string(&file).context("failed to serialize directive cache")?;
+        write_output(&self.path, json)
+    }
+}
+
+/// Highlighted code blocks, kept across builds so unchanged blocks are not
+/// highlighted again.
+///
+/// Entries map a [`highlight_key`] to the highlighted token HTML. The whole
+/// cache is dropped when its fingerprint (kiln version, custom syntaxes)
+/// changes, as either can change how the same code highlights.
+#[derive(Debug)]
+pub struct HighlightCache {
+    path: PathBuf,
+    fingerprint: String,
+    entries: Mutex<BTreeMap<String, String>>,
+    /// Keys looked up or added this build; the rest are pruned on save.
+    used: Mutex<HashSet<String>>,
+}
+
+/// On-disk form of a [`HighlightCache`].
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct HighlightCacheFile {
+    fingerprint: String,
+    entries: BTreeMap<String, String>,
+}
+
+impl HighlightCache {
+    /// Loads the cache at `path`, starting empty if it is missing,
+    /// unreadable, or was written with a different `fingerprint`.
+    #[must_use]
+    pub fn load(path: &Path, fingerprint: String) -> Self {
+        let file: HighlightCacheFile = match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                tracing::warn!(file = %path.display(), "ignoring corrupt highlight cache: {e}");
+                HighlightCacheFile::default()
+            }),
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!(
+                        file = %path.display(),
+                        "ignoring unreadable highlight cache: {e}"
+                    );
+                }
+                HighlightCacheFile::default()
+            }
+        };
+        let entries = if file.fingerprint == fingerprint {
+            file.entries
+        } else {
+            BTreeMap::new()
+        };
+        Self {
+            path: path.to_owned(),
+            fingerprint,
+            entries: Mutex::new(entries),
+            used: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Returns the highlighted HTML cached for `key`, or highlights the
+    /// code with `highlight` and caches the result.
+    pub(crate) fn get_or_insert_with(
+        &self,
+        key: String,
+        highlight: impl FnOnce() -> String,
+    ) -> String {
+        let cached = self
+            .entries
+            .lock()
+            .expect("highlight cache lock poisoned")
+            .get(&key)
+            .cloned();
+        let html = cached.unwrap_or_else(|| {
+            let html = highlight();
+            self.entries
+                .lock()
+                .expect("highlight cache lock poisoned")
+                .insert(key.clone(), html.clone());
+            html
+        });
+        self.used
+            .lock()
+            .expect("highlight cache lock poisoned")
+            .insert(key);
+        html
+    }
+
+    /// Writes the entries used this build back to disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a cache lock is poisoned.
+    pub fn save(&self) -> Result<()> {
+        let entries = self.entries.lock().expect("highlight cache lock poisoned");
+        let used = self.used.lock().expect("highlight cache lock poisoned");
+        let entries = entries
+            .iter()
+            .filter(|(key, _)| used.contains(*key))
+            .map(|(key, html)| (key.clone(), html.clone()))
+            .collect();
+        let file = HighlightCacheFile {
+            fingerprint: self.fingerprint.clone(),
+            entries,
+        };
+        let json = serde_json::to_string(&file).context("failed to serialize highlight cache")?;
+        write_output(&self.path, json)
+    }
+}
+
+/// Derives the cache key of a highlighted code block from the name of the
+/// syntax it is highlighted with, the token class `style` (class names and
+/// prefix), and the `code` itself.
+#[must_use]
+pub(crate) fn highlight_key(syntax: &str, style: &str, code: &str) -> String {
+    hex(&Sha1::new()
+        .chain_update(syntax)
+        .chain_update([0])
+        .chain_update(style)
+        .chain_update([0])
+        .chain_update(code)
+        .finalize())
+}
+
+/// Derives the cache key of a directive render from its template's `name`
+/// and `source`, and its serialized context.
+#[must_use]
+pub(crate) fn directive_key(name: &str, source: &str, ctx_json: &str) -> String {
+    hex(&Sha1::new()
+        .chain_update(name)
+        .chain_update([0])
+        .chain_update(source)
+        .chain_update([0])
+        .chain_update(ctx_json)
+        .finalize())
+}
+
+/// Hashes the kiln version and every file under `paths` (files or
+/// directories; missing ones are skipped) into a cache fingerprint.
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be walked or a file cannot be
+/// read.
+pub fn fingerprint(paths: &[&Path]) -> Result<String> {
+    let mut hasher = Sha1::new().chain_update(env!("CARGO_PKG_VERSION"));
+    for path in paths.iter().filter(|path| path.exists()) {
+        for entry in WalkDir::new(path).sort_by_file_name() {
+            let entry = entry.with_context(|| format!("failed to read {}", path.display()))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let bytes = fs::read(entry.path())
+                .with_context(|| format!("failed to read {}", entry.path().display()))?;
+            hasher.update([0]);
+            hasher.update(entry.path().to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update(bytes);
+        }
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Hashes a file's contents, or `None` if it cannot be read.
+fn hash_file(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|bytes| hex(&Sha1::digest(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    // ── DirectiveCache ──
+
+    #[test]
+    fn directive_cache_round_trips_and_checks_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".kiln-cache/directives.json");
+        write_test_file(dir.path(), "data.csv", "a,b");
+        let data = dir.path().join("data.csv");
+
+        let cache = DirectiveCache::load(&path, "v1".into());
+        assert_eq!(cache.get("key"), None);
+        cache.insert(
+            "key".into(),
+            "<p>a,b</p>".into(),
+            std::slice::from_ref(&data),
+        );
+        cache.insert("stale".into(), "<p>old</p>".into(), &[]);
+        cache.save().unwrap();
+
+        let cache = DirectiveCache::load(&path, "v1".into());
+        assert_eq!(cache.get("key").as_deref(), Some("<p>a,b</p>"));
+        fs::write(&data, "c,d").unwrap();
+        assert_eq!(cache.get("key"), None, "a read file changed");
+
+        let cache = DirectiveCache::load(&path, "v2".into());
+        assert_eq!(cache.get("stale"), None, "the fingerprint changed");
+    }
+
+    #[test]
+    fn directive_cache_save_prunes_unused_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("directives.json");
+        let cache = DirectiveCache::load(&path, "v1".into());
+        cache.insert("a".into(), "A".into(), &[]);
+        cache.insert("b".into(), "B".into(), &[]);
+        cache.save().unwrap();
+
+        let cache = DirectiveCache::load(&path, "v1".into());
+        assert_eq!(cache.get("a").as_deref(), Some("A"));
+        cache.save().unwrap();
+
+        let cache = DirectiveCache::load(&path, "v1".into());
+        assert_eq!(cache.get("b"), None);
+    }
+
+    #[test]
+    fn directive_cache_load_ignores_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "directives.json", "{not json");
+        let cache = DirectiveCache::load(&dir.path().join("directives.json"), "v1".into());
+        assert_eq!(cache.get("key"), None);
+    }
+
+    // ── HighlightCache ──
+
+    #[test]
+    fn highlight_cache_reuses_and_prunes_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("highlight.json");
+        let cache = HighlightCache::load(&path, "v1".into());
+        assert_eq!(cache.get_or_insert_with("a".into(), || "A".into()), "A");
+        assert_eq!(cache.get_or_insert_with("b".into(), || "B".into()), "B");
+        cache.save().unwrap();
+
+        let cache = HighlightCache::load(&path, "v1".into());
+        let html = cache.get_or_insert_with("a".into(), || unreachable!("a is cached"));
+        assert_eq!(html, "A");
+        cache.save().unwrap();
+
+        let cache = HighlightCache::load(&path, "v1".into());
+        assert_eq!(cache.get_or_insert_with("b".into(), || "B2".into()), "B2");
+        let cache = HighlightCache::load(&path, "v2".into());
+        assert_eq!(cache.get_or_insert_with("a".into(), || "A2".into()), "A2");
+    }
+
+    // ── highlight_key ──
+
+    #[test]
+    fn highlight_key_separates_fields() {
+        assert_ne!(
+            highlight_key("Rust", "", "ab"),
+            highlight_key("Rust", "a", "b")
+        );
+        assert_eq!(
+            highlight_key("Rust", "", "ab"),
+            highlight_key("Rust", "", "ab")
+        );
+    }
+
+    // ── fingerprint ──
+
+    #[test]
+    fn fingerprint_changes_with_file_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "templates/directives/chart.html", "v1");
+        let templates = dir.path().join("templates");
+        let missing = dir.path().join("missing");
+
+        let before = fingerprint(&[&templates, &missing]).unwrap();
+        assert_eq!(fingerprint(&[&templates, &missing]).unwrap(), before);
+        write_test_file(dir.path(), "templates/directives/chart.html", "v2");
+        assert_ne!(fingerprint(&[&templates, &missing]).unwrap(), before);
+    }
+}
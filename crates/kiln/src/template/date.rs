@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use jiff::fmt::strtime;
+use jiff::fmt::temporal::Pieces;
+use jiff::tz::{Offset, TimeZone};
+
+/// Month and weekday names of a language, for the `date` template filter.
+///
+/// jiff only knows English names, so `%B` / `%b` / `%A` / `%a` are
+/// substituted before formatting for other languages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DateLocale {
+    English,
+    Chinese,
+    Japanese,
+}
+
+const CJK_MONTHS: [&str; 12] = [
+    "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+];
+const CHINESE_WEEKDAYS: [&str; 7] = [
+    "星期日",
+    "星期一",
+    "星期二",
+    "星期三",
+    "星期四",
+    "星期五",
+    "星期六",
+];
+const CHINESE_SHORT_WEEKDAYS: [&str; 7] = ["周日", "周一", "周二", "周三", "周四", "周五", "周六"];
+const JAPANESE_WEEKDAYS: [&str; 7] = [
+    "日曜日",
+    "月曜日",
+    "火曜日",
+    "水曜日",
+    "木曜日",
+    "金曜日",
+    "土曜日",
+];
+const JAPANESE_SHORT_WEEKDAYS: [&str; 7] = ["日", "月", "火", "水", "木", "金", "土"];
+
+impl DateLocale {
+    /// Picks the locale of a BCP 47 language tag by its primary subtag
+    /// (`zh-Hans` → Chinese). Unknown languages get English names.
+    #[must_use]
+    pub(crate) fn from_language(language: &str) -> Self {
+        let primary = language.split(['-', '_']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "zh" => Self::Chinese,
+            "ja" => Self::Japanese,
+            _ => Self::English,
+        }
+    }
+
+    /// Format used when the filter is given none: `June 15, 2024` or
+    /// `2024年6月15日`.
+    const fn default_format(self) -> &'static str {
+        match self {
+            Self::English => "%B %-d, %Y",
+            Self::Chinese | Self::Japanese => "%Y年%-m月%-d日",
+        }
+    }
+
+    /// Name of month `month` (1-based), or `None` for jiff's English names.
+    /// CJK months have no abbreviated form.
+    fn month(self, month: i8) -> Option<&'static str> {
+        match self {
+            Self::English => None,
+            Self::Chinese | Self::Japanese => Some(CJK_MONTHS[usize::try_from(month - 1).ok()?]),
+        }
+    }
+
+    /// Name of a weekday, counted from Sunday (`0`), full or abbreviated,
+    /// or `None` for jiff's English names.
+    fn weekday(self, weekday: i8, short: bool) -> Option<&'static str> {
+        let names = match (self, short) {
+            (Self::English, _) => return None,
+            (Self::Chinese, false) => CHINESE_WEEKDAYS,
+            (Self::Chinese, true) => CHINESE_SHORT_WEEKDAYS,
+            (Self::Japanese, false) => JAPANESE_WEEKDAYS,
+            (Self::Japanese, true) => JAPANESE_SHORT_WEEKDAYS,
+        };
+        Some(names[usize::try_from(weekday).ok()?])
+    }
+}
+
+/// Formats an RFC 3339 timestamp (or a bare `YYYY-MM-DD` date) with a
+/// strftime `format` in its own UTC offset, naming months and weekdays in
+/// `locale`. Without `format`, the locale's long date is used.
+///
+/// # Errors
+///
+/// Returns an error if `value` is not a date or `format` is invalid.
+pub(crate) fn format_date(value: &str, format: Option<&str>, locale: DateLocale) -> Result<String> {
+    let pieces = Pieces::parse(value).with_context(|| format!("invalid date `{value}`"))?;
+    let offset = pieces.to_numeric_offset().unwrap_or(Offset::UTC);
+    let zoned = pieces
+        .date()
+        .to_datetime(pieces.time().unwrap_or_default())
+        .to_zoned(TimeZone::fixed(offset))
+        .with_context(|| format!("invalid date `{value}`"))?;
+
+    let format = format.unwrap_or(locale.default_format());
+    let month = zoned.month();
+    let weekday = zoned.weekday().to_sunday_zero_offset();
+    let mut localized = String::with_capacity(format.len());
+    let mut rest = format;
+    while let Some(i) = rest.find('%') {
+        localized.push_str(&rest[..i]);
+        let spec = &rest[i..];
+        let name = match spec[1..].chars().next() {
+            Some('B' | 'b' | 'h') => locale.month(month),
+            Some('A') => locale.weekday(weekday, false),
+            Some('a') => locale.weekday(weekday, true),
+            _ => None,
+        };
+        // Other conversions (and `%%`) are left to jiff, two bytes at a time
+        // so an escaped `%` is never read as the start of a conversion.
+        let len = 1 + spec[1..].chars().next().map_or(0, char::len_utf8);
+        match name {
+            Some(name) => localized.push_str(name),
+            None => localized.push_str(&spec[..len]),
+        }
+        rest = &spec[len..];
+    }
+    localized.push_str(rest);
+
+    strtime::format(&localized, &zoned).with_context(|| format!("invalid date format `{format}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── DateLocale::from_language ──
+
+    #[test]
+    fn from_language_uses_primary_subtag() {
+        assert_eq!(DateLocale::from_language("zh-Hans"), DateLocale::Chinese);
+        assert_eq!(DateLocale::from_language("zh_CN"), DateLocale::Chinese);
+        assert_eq!(DateLocale::from_language("ja"), DateLocale::Japanese);
+        assert_eq!(DateLocale::from_language("en-US"), DateLocale::English);
+        assert_eq!(DateLocale::from_language("fr"), DateLocale::English);
+    }
+
+    // ── format_date ──
+
+    #[test]
+    fn format_date_default_formats() {
+        let date = "2024-06-15T23:30:00+08:00";
+        assert_eq!(
+            format_date(date, None, DateLocale::English).unwrap(),
+            "June 15, 2024"
+        );
+        assert_eq!(
+            format_date(date, None, DateLocale::Chinese).unwrap(),
+            "2024年6月15日",
+            "keeps the date in its own offset"
+        );
+        assert_eq!(
+            format_date("2024-06-15", None, DateLocale::Japanese).unwrap(),
+            "2024年6月15日"
+        );
+    }
+
+    #[test]
+    fn format_date_localizes_names() {
+        let date = "2024-06-15T12:00:00Z";
+        let format = Some("%A, %B %-d (%a %b) %H:%M %z 100%%");
+        assert_eq!(
+            format_date(date, format, DateLocale::English).unwrap(),
+            "Saturday, June 15 (Sat Jun) 12:00 +0000 100%"
+        );
+        assert_eq!(
+            format_date(date, format, DateLocale::Chinese).unwrap(),
+            "星期六, 6月 15 (周六 6月) 12:00 +0000 100%"
+        );
+        assert_eq!(
+            format_date(date, Some("%%B"), DateLocale::Japanese).unwrap(),
+            "%B"
+        );
+    }
+
+    #[test]
+    fn format_date_rejects_invalid_input() {
+        let err = format_date("yesterday", None, DateLocale::English).unwrap_err();
+        assert!(err.to_string().contains("invalid date"), "got: {err}");
+        let err = format_date("2024-06-15", Some("%Y %"), DateLocale::English).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid date format"),
+            "got: {err}"
+        );
+    }
+}
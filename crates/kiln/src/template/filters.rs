@@ -0,0 +1,195 @@
+use minijinja::value::{Kwargs, Value};
+use minijinja::{Error, ErrorKind};
+
+use crate::config::SlugifyMode;
+use crate::text::slugify;
+
+/// Default `length` of [`truncate`], as in Jinja.
+const TRUNCATE_LENGTH: usize = 255;
+
+/// `MiniJinja` filter: shortens text to at most `length` characters
+/// (default 255), cutting at the last word boundary when there is one, and
+/// appends `end` (default `…`) when anything was cut.
+///
+/// Usage in templates: `{{ page.description | truncate(120) }}` or
+/// `{{ title | truncate(40, end=" [...]") }}`.
+pub(super) fn truncate(
+    value: &str,
+    length: Option<usize>,
+    kwargs: &Kwargs,
+) -> Result<String, Error> {
+    let end: Option<&str> = kwargs.get("end")?;
+    kwargs.assert_all_used()?;
+    let length = length.unwrap_or(TRUNCATE_LENGTH);
+
+    let Some((cut, _)) = value.char_indices().nth(length) else {
+        return Ok(value.to_owned());
+    };
+    let kept = &value[..cut];
+    let kept = match kept.rfind(char::is_whitespace) {
+        Some(space) if !value[cut..].starts_with(char::is_whitespace) => &kept[..space],
+        _ => kept,
+    };
+    Ok(format!("{}{}", kept.trim_end(), end.unwrap_or("…")))
+}
+
+/// `MiniJinja` filter: turns text into a URL slug, the way heading IDs and
+/// taxonomy terms are slugified under `[slugify] mode`.
+///
+/// Usage in templates: `{{ term.name | slugify }}`.
+pub(super) fn slugify_with(mode: SlugifyMode) -> impl Fn(&str) -> String + Send + Sync + 'static {
+    move |value| slugify(value, mode)
+}
+
+/// `MiniJinja` filter: serializes a value to JSON, safe to embed in a
+/// `<script>` element or an HTML attribute: `<`, `>`, `&`, and `'` are
+/// written as `\u` escapes.
+///
+/// Usage in templates: `<script>const pages = {{ site.pages | jsonify }};</script>`,
+/// or `jsonify(pretty=true)` for indented output.
+pub(super) fn jsonify(value: &Value, kwargs: &Kwargs) -> Result<Value, Error> {
+    let pretty: Option<bool> = kwargs.get("pretty")?;
+    kwargs.assert_all_used()?;
+    let json = if pretty.unwrap_or(false) {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+    .map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("cannot serialize to JSON: {e}"),
+        )
+    })?;
+    let json = json
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+        .replace('\'', "\\u0027");
+    Ok(Value::from_safe_string(json))
+}
+
+/// `MiniJinja` filter: groups a sequence by an attribute, which may be a
+/// dotted path (`date.year`), into a list of `{grouper, list}` groups.
+///
+/// Unlike the built-in `groupby`, groups keep the order in which their key
+/// first appears, and items keep their order within a group, so a list
+/// already sorted by date stays sorted. Items missing the attribute are
+/// grouped under `none`.
+///
+/// Usage in templates:
+/// `{% for group in pages | group_by("section.name") %}{{ group.grouper }}: {{ group.list | length }}{% endfor %}`
+pub(super) fn group_by(values: &Value, attribute: &str) -> Result<Value, Error> {
+    let mut groups: Vec<(Value, Vec<Value>)> = Vec::new();
+    for item in values.try_iter()? {
+        let mut key = item.clone();
+        for segment in attribute.split('.') {
+            if key.is_undefined() || key.is_none() {
+                break;
+            }
+            key = key.get_attr(segment)?;
+        }
+        if key.is_undefined() {
+            key = Value::from(());
+        }
+        match groups.iter_mut().find(|(grouper, _)| *grouper == key) {
+            Some((_, list)) => list.push(item),
+            None => groups.push((key, vec![item])),
+        }
+    }
+    Ok(groups
+        .into_iter()
+        .map(|(grouper, list)| {
+            Value::from_iter([("grouper", grouper), ("list", Value::from(list))])
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use minijinja::context;
+
+    use super::*;
+    use crate::test_utils::test_engine;
+
+    fn render(source: &str, ctx: Value) -> String {
+        test_engine().env.render_str(source, ctx).unwrap()
+    }
+
+    // ── truncate ──
+
+    #[test]
+    fn truncate_cuts_at_word_boundary() {
+        let ctx = context! { text => "The quick brown fox" };
+        assert_eq!(
+            render("{{ text | truncate(12) }}", ctx.clone()),
+            "The quick…"
+        );
+        assert_eq!(
+            render("{{ text | truncate(15) }}", ctx.clone()),
+            "The quick brown…"
+        );
+        assert_eq!(
+            render("{{ text | truncate(19) }}", ctx.clone()),
+            "The quick brown fox"
+        );
+        assert_eq!(
+            render(r#"{{ text | truncate(3, end="...") }}"#, ctx),
+            "The..."
+        );
+    }
+
+    #[test]
+    fn truncate_counts_characters() {
+        let ctx = context! { text => "你好世界" };
+        assert_eq!(render("{{ text | truncate(2) }}", ctx), "你好…");
+    }
+
+    // ── slugify_with ──
+
+    #[test]
+    fn slugify_follows_mode() {
+        assert_eq!(
+            slugify_with(SlugifyMode::Safe)("Hello, World!"),
+            "hello-world"
+        );
+        assert_eq!(
+            render("{{ 'Rust & Go' | slugify }}", context! {}),
+            "rust-go"
+        );
+    }
+
+    // ── jsonify ──
+
+    #[test]
+    fn jsonify_escapes_html_sensitive_characters() {
+        let ctx = context! { data => context! { title => "</script><b>'&'" } };
+        assert_eq!(
+            render("{{ data | jsonify }}", ctx),
+            r#"{"title":"\u003c/script\u003e\u003cb\u003e\u0027\u0026\u0027"}"#
+        );
+        assert_eq!(
+            render("{{ [1] | jsonify(pretty=true) }}", context! {}),
+            "[\n  1\n]"
+        );
+    }
+
+    // ── group_by ──
+
+    #[test]
+    fn group_by_keeps_first_seen_order() {
+        let ctx = context! {
+            pages => vec![
+                context! { title => "C", meta => context! { year => 2024 } },
+                context! { title => "A", meta => context! { year => 2023 } },
+                context! { title => "B", meta => context! { year => 2024 } },
+                context! { title => "D" },
+            ],
+        };
+        let html = render(
+            "{% for g in pages | group_by('meta.year') %}{{ g.grouper }}: {{ g.list | map(attribute='title') | join(',') }}; {% endfor %}",
+            ctx,
+        );
+        assert_eq!(html, "2024: C,B; 2023: A; none: D; ");
+    }
+}
@@ -0,0 +1,115 @@
+use std::io;
+
+/// Closing tag the head HTML is inserted before.
+const HEAD_CLOSE: &[u8] = b"</head>";
+
+/// Inserts `snippet` before the first `</head>` of `html`. Documents
+/// without one (fragments, SVG) are returned unchanged.
+pub(super) fn inject_head(mut html: String, snippet: &str) -> String {
+    if let Some(pos) = find_head_close(html.as_bytes()) {
+        html.insert_str(pos, snippet);
+    }
+    html
+}
+
+/// Writer inserting `snippet` before the first `</head>` written through
+/// it, for streamed renders. Output is held back only until `</head>`
+/// appears; call [`finish`](Self::finish) to flush documents without one.
+pub(super) struct HeadInjector<'a, W: io::Write> {
+    out: W,
+    snippet: &'a str,
+    /// Output held back while `</head>` is still ahead.
+    pending: Option<Vec<u8>>,
+}
+
+impl<'a, W: io::Write> HeadInjector<'a, W> {
+    pub(super) fn new(out: W, snippet: &'a str) -> Self {
+        Self {
+            out,
+            snippet,
+            pending: Some(Vec::new()),
+        }
+    }
+
+    /// Writes out anything still held back.
+    pub(super) fn finish(mut self) -> io::Result<()> {
+        if let Some(pending) = self.pending.take() {
+            self.out.write_all(&pending)?;
+        }
+        self.out.flush()
+    }
+}
+
+impl<W: io::Write> io::Write for HeadInjector<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(pending) = &mut self.pending else {
+            return self.out.write(buf);
+        };
+        // Only the new bytes and a tag's length before them can complete
+        // a `</head>` not seen by earlier writes.
+        let start = pending.len().saturating_sub(HEAD_CLOSE.len() - 1);
+        pending.extend_from_slice(buf);
+        if let Some(pos) = find_head_close(&pending[start..]).map(|pos| start + pos) {
+            self.out.write_all(&pending[..pos])?;
+            self.out.write_all(self.snippet.as_bytes())?;
+            self.out.write_all(&pending[pos..])?;
+            self.pending = None;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Returns the byte offset of the first `</head>` in `html`, in any case.
+fn find_head_close(html: &[u8]) -> Option<usize> {
+    html.windows(HEAD_CLOSE.len())
+        .position(|window| window.eq_ignore_ascii_case(HEAD_CLOSE))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+
+    const SNIPPET: &str = "<script src=\"/a.js\"></script>\n";
+
+    // ── inject_head ──
+
+    #[test]
+    fn inject_head_before_first_head_close() {
+        assert_eq!(
+            inject_head("<HEAD><title>x</title></HEAD><body></body>".into(), SNIPPET),
+            "<HEAD><title>x</title><script src=\"/a.js\"></script>\n</HEAD><body></body>"
+        );
+        assert_eq!(inject_head("<svg></svg>".into(), SNIPPET), "<svg></svg>");
+    }
+
+    // ── HeadInjector ──
+
+    #[test]
+    fn head_injector_finds_head_close_across_writes() {
+        let mut out = Vec::new();
+        let mut writer = HeadInjector::new(&mut out, SNIPPET);
+        for chunk in ["<head><title>x</title></he", "ad>", "<body></body>"] {
+            writer.write_all(chunk.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "<head><title>x</title><script src=\"/a.js\"></script>\n</head><body></body>"
+        );
+    }
+
+    #[test]
+    fn head_injector_flushes_documents_without_head() {
+        let mut out = Vec::new();
+        let mut writer = HeadInjector::new(&mut out, SNIPPET);
+        writer.write_all(b"<p>fragment</p>").unwrap();
+        writer.finish().unwrap();
+        assert_eq!(out, b"<p>fragment</p>");
+    }
+}
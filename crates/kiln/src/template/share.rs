@@ -0,0 +1,115 @@
+use std::fmt::Write;
+
+use strum::{EnumString, VariantNames};
+
+/// Social networks supported by the `share_url()` template function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, VariantNames)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub(crate) enum ShareNetwork {
+    Email,
+    Facebook,
+    HackerNews,
+    LinkedIn,
+    Reddit,
+    Telegram,
+    #[strum(serialize = "twitter", serialize = "x")]
+    Twitter,
+    Weibo,
+    WhatsApp,
+}
+
+impl ShareNetwork {
+    /// Builds the share link for a page with the given absolute `url` and
+    /// `title`. Both are percent-encoded; an empty title is left out where
+    /// the network allows it.
+    #[must_use]
+    pub(crate) fn share_url(self, url: &str, title: &str) -> String {
+        let u = encode_component(url);
+        let t = encode_component(title);
+        match self {
+            Self::Email => format!("mailto:?subject={t}&body={u}"),
+            Self::Facebook => format!("https://www.facebook.com/sharer/sharer.php?u={u}"),
+            Self::HackerNews => format!("https://news.ycombinator.com/submitlink?u={u}&t={t}"),
+            Self::LinkedIn => format!("https://www.linkedin.com/sharing/share-offsite/?url={u}"),
+            Self::Reddit => format!("https://www.reddit.com/submit?url={u}&title={t}"),
+            Self::Telegram => with_text(&format!("https://t.me/share/url?url={u}"), &t),
+            Self::Twitter => with_text(&format!("https://x.com/intent/tweet?url={u}"), &t),
+            Self::Weibo => format!("https://service.weibo.com/share/share.php?url={u}&title={t}"),
+            Self::WhatsApp if t.is_empty() => format!("https://wa.me/?text={u}"),
+            Self::WhatsApp => format!("https://wa.me/?text={t}%20{u}"),
+        }
+    }
+}
+
+/// Appends a `text` query parameter unless it is empty.
+fn with_text(base: &str, text: &str) -> String {
+    if text.is_empty() {
+        base.to_owned()
+    } else {
+        format!("{base}&text={text}")
+    }
+}
+
+/// Percent-encodes `s` for use as a URL query component, keeping only
+/// RFC 3986 unreserved characters as-is.
+fn encode_component(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── ShareNetwork ──
+
+    #[test]
+    fn share_network_parses_case_insensitively_with_aliases() {
+        assert_eq!("Twitter".parse(), Ok(ShareNetwork::Twitter));
+        assert_eq!("x".parse(), Ok(ShareNetwork::Twitter));
+        assert_eq!("hackernews".parse(), Ok(ShareNetwork::HackerNews));
+        assert!("myspace".parse::<ShareNetwork>().is_err());
+    }
+
+    // ── ShareNetwork::share_url ──
+
+    #[test]
+    fn share_url_encodes_url_and_title() {
+        let url = "https://example.com/posts/a b/?x=1&y=2";
+        assert_eq!(
+            ShareNetwork::Twitter.share_url(url, "Rust & 你好"),
+            "https://x.com/intent/tweet?url=https%3A%2F%2Fexample.com%2Fposts%2Fa%20b%2F%3Fx%3D1%26y%3D2&text=Rust%20%26%20%E4%BD%A0%E5%A5%BD"
+        );
+        assert_eq!(
+            ShareNetwork::Reddit.share_url("https://example.com/", "Hi"),
+            "https://www.reddit.com/submit?url=https%3A%2F%2Fexample.com%2F&title=Hi"
+        );
+    }
+
+    #[test]
+    fn share_url_omits_empty_text() {
+        assert_eq!(
+            ShareNetwork::Telegram.share_url("https://example.com/", ""),
+            "https://t.me/share/url?url=https%3A%2F%2Fexample.com%2F"
+        );
+        assert_eq!(
+            ShareNetwork::WhatsApp.share_url("https://example.com/", ""),
+            "https://wa.me/?text=https%3A%2F%2Fexample.com%2F"
+        );
+    }
+
+    // ── encode_component ──
+
+    #[test]
+    fn encode_component_keeps_unreserved() {
+        assert_eq!(encode_component("aZ0-_.~"), "aZ0-_.~");
+        assert_eq!(encode_component("/?#[]@ +"), "%2F%3F%23%5B%5D%40%20%2B");
+    }
+}
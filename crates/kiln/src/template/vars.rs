@@ -4,6 +4,7 @@ use crate::config::Config;
 use crate::content::frontmatter::FeaturedImage;
 use crate::pagination::PaginationVars;
 use crate::render::assets::PageAssets;
+use crate::render::toc::TocNode;
 
 /// Template variables for rendering a post page.
 ///
@@ -12,12 +13,30 @@ use crate::render::assets::PageAssets;
 /// avoid double-escaping. All other string fields are auto-escaped by `MiniJinja`.
 #[derive(Debug, Serialize)]
 pub struct PostTemplateVars<'a> {
+    /// Stable page ID (see `kiln fix`), for comment threads and analytics.
+    pub id: &'a str,
     pub title: &'a str,
     pub description: &'a str,
+    /// Rendered summary (see `[summary]`), with absolute URLs. Empty when
+    /// the page has none.
+    pub summary_html: &'a str,
     pub url: &'a str,
     pub featured_image: Option<FeaturedImage>,
+    /// Scaled-down copy of a bundle `featured_image` (see `[thumbnails]`).
+    pub featured_image_thumb: Option<String>,
+    /// Podcast episode of the page (see `audio`), for an audio player.
+    pub audio: Option<PageAudio>,
+    /// Image for `og:image`: the `featured_image`, else the card generated
+    /// with `[og_image]`.
+    pub og_image: Option<String>,
     pub page_css: Option<String>,
     pub date: Option<String>,
+    /// Last modification date: frontmatter `updated`, else the last git
+    /// commit's date with `lastmod_from_git`.
+    pub updated: Option<String>,
+    /// Authors the page credits (see `authors`), else the site `[author]`
+    /// when named.
+    pub authors: Vec<PageAuthor>,
     pub section: Option<LinkedTerm>,
     /// Auto-detected runtime dependencies (math, mermaid, registered scripts).
     /// Themes iterate `assets.features` and `assets.scripts` to load the right
@@ -25,9 +44,68 @@ pub struct PostTemplateVars<'a> {
     pub assets: PageAssets,
     pub content: &'a str,
     pub toc: &'a str,
+    /// Structured `ToC` for themes that build their own markup.
+    pub toc_entries: &'a [TocNode],
+    /// Posts sharing tags or a section with this one, best match first
+    /// (see `[related]`). Empty for standalone pages.
+    pub related: &'a [PageSummary],
+    /// Set by `noindex` (or a `[cascade]`): the page should carry
+    /// `<meta name="robots" content="noindex">`.
+    pub noindex: bool,
+    /// The page's language: its frontmatter `language`, else the site's.
+    pub language: &'a str,
+    /// Every translation of the page (see `translation_key`), itself
+    /// included, sorted by language. Empty for untranslated pages.
+    pub translations: &'a [Translation],
+    /// schema.org JSON-LD of the page and its breadcrumbs, serialized for a
+    /// `<script type="application/ld+json">`.
+    pub json_ld: String,
+    /// Hidden microformats2 properties of the page (name, URL, dates,
+    /// authors, tags), for themes to emit inside an `h-entry` element.
+    pub h_entry: String,
     pub config: &'a Config,
 }
 
+/// Template variables for the `[og_image]` SVG template.
+///
+/// SVG has no text wrapping, so `title_lines` carries the title pre-wrapped
+/// for the built-in card's font size.
+#[derive(Debug, Serialize)]
+pub struct OgImageVars<'a> {
+    pub title: &'a str,
+    pub title_lines: Vec<String>,
+    pub description: &'a str,
+    pub date: Option<String>,
+    pub site_name: &'a str,
+    pub url: &'a str,
+    pub font_family: &'a str,
+    pub background: &'a str,
+    pub foreground: &'a str,
+    pub accent: Option<&'a str>,
+}
+
+/// Template variables for the optional `toc.html` template, which replaces
+/// the built-in table of contents markup.
+#[derive(Debug, Serialize)]
+pub struct TocTemplateVars<'a> {
+    pub toc_entries: &'a [TocNode],
+}
+
+/// Site-wide variables exposed to every template as `site`.
+#[derive(Debug, Serialize)]
+pub struct SiteVars<'a> {
+    /// Every listable page (posts and standalone pages, drafts and redirects
+    /// excluded), newest first, for sidebars and custom lists.
+    pub pages: &'a [PageSummary],
+}
+
+/// A translation of a page, for `hreflang` alternate links.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Translation {
+    pub language: String,
+    pub url: String,
+}
+
 /// A named item with a URL, used for tags and sections in page summaries.
 #[derive(Debug, Clone, Serialize)]
 pub struct LinkedTerm {
@@ -35,6 +113,19 @@ pub struct LinkedTerm {
     pub url: String,
 }
 
+/// An author credited by a page, resolved from `[authors]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PageAuthor {
+    /// Key in `[authors]`; empty for the site `[author]`.
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    pub link: String,
+    pub avatar: String,
+    /// The author's listing page, with `author_pages`.
+    pub url: Option<String>,
+}
+
 /// Lightweight page summary for list / taxonomy templates.
 #[derive(Debug, Clone, Serialize)]
 pub struct PageSummary {
@@ -46,11 +137,27 @@ pub struct PageSummary {
     /// puts pinned posts at the top of listings.
     pub pinned: bool,
     pub description: String,
+    /// Rendered summary (see `[summary]`), with absolute URLs.
+    pub summary_html: String,
     pub featured_image: Option<FeaturedImage>,
+    /// Scaled-down copy of a bundle `featured_image`, for list pages.
+    pub featured_image_thumb: Option<String>,
+    /// Podcast episode of the page, the feed item's `<enclosure>`.
+    pub audio: Option<PageAudio>,
     pub tags: Vec<LinkedTerm>,
     pub section: Option<LinkedTerm>,
 }
 
+/// A page's `audio` episode, resolved against the page URL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageAudio {
+    pub url: String,
+    /// File size in bytes, or `0` when the file is not a bundle asset.
+    pub length: u64,
+    pub mime_type: String,
+    pub duration: Option<String>,
+}
+
 /// A group of pages sharing a common key (e.g., year).
 #[derive(Debug, Clone, Serialize)]
 pub struct PageGroup {
@@ -66,6 +173,9 @@ pub struct HomePageVars<'a> {
     pub url: String,
     pub pages: Vec<PageSummary>,
     pub pagination: PaginationVars,
+    /// schema.org `WebSite` JSON-LD, serialized for a
+    /// `<script type="application/ld+json">`.
+    pub json_ld: String,
     pub config: &'a Config,
 }
 
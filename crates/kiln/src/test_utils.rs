@@ -7,10 +7,13 @@ use indoc::indoc;
 use tempfile::TempDir;
 
 use crate::config::Config;
+use crate::content::format::ContentFormat;
 use crate::content::frontmatter::Frontmatter;
 use crate::content::page::{Page, PageKind};
 use crate::i18n::I18n;
+use crate::render::assets::PageAssets;
 use crate::template::TemplateEngine;
+use crate::template::vars::{LinkedTerm, PostTemplateVars};
 
 static BASE_HTML: &str = indoc! {r#"
     <!DOCTYPE html>
@@ -39,16 +42,20 @@ static POST_HTML: &str = indoc! {r#"
       {%- if description %}
       <meta name="description" content="{{ description }}">
       {%- endif %}
+      {%- if noindex %}
+      <meta name="robots" content="noindex">
+      {%- endif %}
       <link rel="canonical" href="{{ url | safe }}">
       <meta property="og:title" content="{{ title }}">
       <meta property="og:description" content="{{ description }}">
       <meta property="og:url" content="{{ url | safe }}">
       <meta property="og:type" content="article">
       <meta property="og:site_name" content="{{ config.title }}">
-      {%- if featured_image %}
-      <meta property="og:image" content="{{ config.base_url | safe }}{{ featured_image.src | safe }}">
+      {%- if og_image %}
+      <meta property="og:image" content="{{ config.base_url | safe }}{{ og_image | safe }}">
       {%- endif %}
-      <meta name="twitter:card" content="{% if featured_image %}summary_large_image{% else %}summary{% endif %}">
+      <meta name="twitter:card" content="{% if og_image %}summary_large_image{% else %}summary{% endif %}">
+      <script type="application/ld+json">{{ json_ld | safe }}</script>
     {% endblock %}
 
     {% block body %}
@@ -225,6 +232,41 @@ pub fn test_config() -> Config {
     toml::from_str("").unwrap()
 }
 
+/// Creates `PostTemplateVars` for a dated post in a `Notes` section, with
+/// everything else empty.
+pub fn test_post_vars<'a>(config: &'a Config, title: &'a str) -> PostTemplateVars<'a> {
+    PostTemplateVars {
+        id: "",
+        title,
+        description: "A post",
+        summary_html: "",
+        url: "https://example.com/posts/note/hello/",
+        featured_image: None,
+        featured_image_thumb: None,
+        audio: None,
+        og_image: Some("/posts/note/hello/og.png".into()),
+        page_css: None,
+        date: Some("2026-01-15T00:00:00Z".into()),
+        updated: None,
+        authors: Vec::new(),
+        section: Some(LinkedTerm {
+            name: "Notes".into(),
+            url: "https://example.com/posts/note/".into(),
+        }),
+        assets: PageAssets::default(),
+        content: "",
+        toc: "",
+        toc_entries: &[],
+        related: &[],
+        noindex: false,
+        language: "en",
+        translations: &[],
+        json_ld: String::new(),
+        h_entry: String::new(),
+        config,
+    }
+}
+
 /// Creates a minimal `Page` with defaults for testing.
 ///
 /// Returns a `PageKind::Page` (standalone) with an empty body.
@@ -236,8 +278,11 @@ pub fn test_page(title: &str) -> Page {
             ..Frontmatter::default()
         },
         raw_content: String::new(),
+        body_line: Some(1),
+        format: ContentFormat::Markdown,
         kind: PageKind::Page,
         slug: title.to_lowercase().replace(' ', "-"),
+        id: String::new(),
         summary: None,
         source_path: PathBuf::from(format!("content/{title}/index.md")),
         assets: Vec::new(),
@@ -1,21 +1,135 @@
+pub mod case;
+
+use std::fmt::Write as _;
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use crate::config::SlugifyMode;
+
+/// Characters replaced under [`SlugifyMode::Off`]: those that would end or
+/// split a URL path segment or fragment, or need escaping in HTML attributes.
+const URL_UNSAFE: &[char] = &[
+    '/', '\\', '?', '#', '%', '"', '\'', '<', '>', '[', ']', '{', '}', '|', '^', '`',
+];
+
+/// Connector punctuation (Unicode category `Pc`) other than `_`, kept by
+/// [`SlugifyMode::Github`].
+const CONNECTOR_PUNCTUATION: &[char] = &[
+    '\u{203F}', '\u{2040}', '\u{2054}', '\u{FE33}', '\u{FE34}', '\u{FE4D}', '\u{FE4E}', '\u{FE4F}',
+    '\u{FF3F}',
+];
+
+/// Latin letters that Unicode decomposition leaves as they are, with the
+/// ASCII spelling [`SlugifyMode::Ascii`] gives them.
+const ASCII_SPELLINGS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('ẞ', "ss"),
+    ('æ', "ae"),
+    ('Æ', "ae"),
+    ('œ', "oe"),
+    ('Œ', "oe"),
+    ('ø', "o"),
+    ('Ø', "o"),
+    ('đ', "d"),
+    ('Đ', "d"),
+    ('ł', "l"),
+    ('Ł', "l"),
+    ('ð', "d"),
+    ('Ð', "d"),
+    ('þ', "th"),
+    ('Þ', "th"),
+];
+
+/// Formats `bytes` as lowercase hex digits, two per byte.
+#[must_use]
+pub fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
 /// Converts text into a URL-safe slug.
 ///
-/// Used for heading IDs and taxonomy term slugs. Unicode-aware lowercasing
+/// Used for heading IDs, taxonomy term slugs, and page slugs derived from
+/// file names. Under [`SlugifyMode::Safe`], Unicode-aware lowercasing
 /// preserves CJK and accented characters.
 ///
-/// - Lowercases all characters (Unicode-aware)
-/// - Preserves alphanumeric characters (ASCII, CJK, accented letters)
-/// - Replaces non-alphanumeric characters with `-`
+/// - Keeps the characters `mode` allows, lowercased unless `mode` is
+///   [`SlugifyMode::Off`]
+/// - Replaces every other character with `-`
 /// - Collapses consecutive `-` and strips leading / trailing `-`
+///
+/// [`SlugifyMode::Github`] instead follows GitHub's heading anchors exactly:
+/// see [`github_slug`].
 #[must_use]
-pub fn slugify(text: &str) -> String {
-    let mut result = String::with_capacity(text.len());
+pub fn slugify(text: &str, mode: SlugifyMode) -> String {
+    match mode {
+        SlugifyMode::Safe => join_kept(text.chars(), char::is_alphanumeric, true),
+        SlugifyMode::Ascii => join_kept(
+            text.nfkd()
+                .filter(|&ch| !is_combining_mark(ch))
+                .flat_map(ascii_spelling),
+            |ch| ch.is_ascii_alphanumeric(),
+            true,
+        ),
+        SlugifyMode::Off => join_kept(
+            text.chars(),
+            |ch| ch != '-' && !ch.is_whitespace() && !ch.is_control() && !URL_UNSAFE.contains(&ch),
+            false,
+        ),
+        SlugifyMode::Github => github_slug(text),
+    }
+}
+
+/// Spells `ch` in ASCII letters if it is one of [`ASCII_SPELLINGS`].
+fn ascii_spelling(ch: char) -> impl Iterator<Item = char> {
+    let spelling = ASCII_SPELLINGS
+        .iter()
+        .find(|&&(letter, _)| letter == ch)
+        .map(|&(_, spelling)| spelling);
+    spelling
+        .into_iter()
+        .flat_map(str::chars)
+        .chain(spelling.is_none().then_some(ch))
+}
+
+/// Generates a heading anchor the way GitHub (`github-slugger`) does:
+/// lowercases the text, drops every character but letters, digits, marks,
+/// connector punctuation, `-`, and spaces, then turns each space into `-`.
+///
+/// Unlike the other modes, runs of `-` are neither collapsed nor stripped,
+/// so `A -- B` becomes `a----b`, as on GitHub.
+fn github_slug(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter_map(|ch| match ch {
+            ' ' => Some('-'),
+            '-' | '_' => Some(ch),
+            _ if ch.is_alphanumeric()
+                || is_combining_mark(ch)
+                || CONNECTOR_PUNCTUATION.contains(&ch) =>
+            {
+                Some(ch)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Joins the runs of `chars` accepted by `keep` with `-`.
+fn join_kept(chars: impl Iterator<Item = char>, keep: fn(char) -> bool, lowercase: bool) -> String {
+    let mut result = String::new();
     let mut prev_dash = true; // strip leading dashes
 
-    for ch in text.chars() {
-        if ch.is_alphanumeric() {
-            for lower in ch.to_lowercase() {
-                result.push(lower);
+    for ch in chars {
+        if keep(ch) {
+            if lowercase {
+                result.extend(ch.to_lowercase());
+            } else {
+                result.push(ch);
             }
             prev_dash = false;
         } else if !prev_dash {
@@ -49,50 +163,201 @@ pub fn titlecase(s: &str) -> String {
         .join(" ")
 }
 
+/// Counts the words in plain text.
+///
+/// Runs of non-whitespace count as one word when they contain a letter or
+/// digit, so stray punctuation is not counted. Each CJK character counts as
+/// a word of its own, since those scripts don't separate words with spaces.
+#[must_use]
+pub fn word_count(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            count += 1;
+            in_word = false;
+        } else if ch.is_whitespace() {
+            in_word = false;
+        } else if !in_word && ch.is_alphanumeric() {
+            count += 1;
+            in_word = true;
+        }
+    }
+    count
+}
+
+/// Greedily wraps text into lines of at most `columns` columns, for layouts
+/// without text wrapping of their own (e.g., SVG).
+///
+/// Lines break between words, and between any two CJK characters, which
+/// count as two columns. Text beyond `max_lines` lines is cut off, marking
+/// the last line with `…`. A single word wider than `columns` overflows.
+#[must_use]
+pub fn wrap_lines(text: &str, columns: usize, max_lines: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0;
+    for (token, spaced) in wrap_tokens(text) {
+        let token_width: usize = token.chars().map(|ch| 1 + usize::from(is_cjk(ch))).sum();
+        let gap = usize::from(spaced && !line.is_empty());
+        if !line.is_empty() && width + gap + token_width > columns {
+            if lines.len() + 1 >= max_lines {
+                line.push('…');
+                break;
+            }
+            lines.push(std::mem::take(&mut line));
+            width = 0;
+        } else if gap > 0 {
+            line.push(' ');
+            width += 1;
+        }
+        line.push_str(token);
+        width += token_width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Splits text into the units [`wrap_lines`] keeps together: runs of
+/// non-whitespace, with each CJK character on its own. Each is paired with
+/// whether whitespace precedes it.
+fn wrap_tokens(text: &str) -> Vec<(&str, bool)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut spaced = false;
+    for (i, ch) in text.char_indices() {
+        if !ch.is_whitespace() && !is_cjk(ch) {
+            start.get_or_insert(i);
+            continue;
+        }
+        if let Some(s) = start.take() {
+            tokens.push((&text[s..i], spaced));
+            spaced = false;
+        }
+        if ch.is_whitespace() {
+            spaced = true;
+        } else {
+            tokens.push((&text[i..i + ch.len_utf8()], spaced));
+            spaced = false;
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((&text[s..], spaced));
+    }
+    tokens
+}
+
+/// Returns `true` for Han, kana, and Hangul characters.
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // ── hex ──
+
+    #[test]
+    fn hex_pads_each_byte() {
+        assert_eq!(hex(&[0x0b, 0xad, 0xf0, 0x0d]), "0badf00d");
+        assert_eq!(hex(&[]), "");
+    }
+
     // ── slugify ──
 
     #[test]
     fn slugify_ascii() {
-        assert_eq!(slugify("Hello World"), "hello-world");
+        assert_eq!(slugify("Hello World", SlugifyMode::Safe), "hello-world");
     }
 
     #[test]
     fn slugify_cjk() {
-        assert_eq!(slugify("你好世界"), "你好世界");
+        assert_eq!(slugify("你好世界", SlugifyMode::Safe), "你好世界");
     }
 
     #[test]
     fn slugify_accented_latin() {
-        assert_eq!(slugify("Café Résumé"), "café-résumé");
+        assert_eq!(slugify("Café Résumé", SlugifyMode::Safe), "café-résumé");
     }
 
     #[test]
     fn slugify_mixed() {
-        assert_eq!(slugify("1.1 Foobar - 测试文本"), "1-1-foobar-测试文本");
+        assert_eq!(
+            slugify("1.1 Foobar - 测试文本", SlugifyMode::Safe),
+            "1-1-foobar-测试文本"
+        );
     }
 
     #[test]
     fn slugify_collapses_dashes() {
-        assert_eq!(slugify("a - - b"), "a-b");
+        assert_eq!(slugify("a - - b", SlugifyMode::Safe), "a-b");
     }
 
     #[test]
     fn slugify_strips_leading_trailing() {
-        assert_eq!(slugify(" hello "), "hello");
+        assert_eq!(slugify(" hello ", SlugifyMode::Safe), "hello");
     }
 
     #[test]
     fn slugify_empty() {
-        assert_eq!(slugify(""), "");
+        assert_eq!(slugify("", SlugifyMode::Safe), "");
     }
 
     #[test]
     fn slugify_only_punctuation() {
-        assert_eq!(slugify("..."), "");
+        assert_eq!(slugify("...", SlugifyMode::Safe), "");
+    }
+
+    #[test]
+    fn slugify_ascii_mode_strips_accents_and_other_scripts() {
+        assert_eq!(
+            slugify("1.1 Café Résumé - 测试文本", SlugifyMode::Ascii),
+            "1-1-cafe-resume"
+        );
+        assert_eq!(slugify("你好世界", SlugifyMode::Ascii), "");
+    }
+
+    #[test]
+    fn slugify_ascii_mode_spells_out_undecomposed_letters() {
+        assert_eq!(
+            slugify("Größe Überblick", SlugifyMode::Ascii),
+            "grosse-uberblick"
+        );
+        assert_eq!(
+            slugify("Æsir Øresund Łódź", SlugifyMode::Ascii),
+            "aesir-oresund-lodz"
+        );
+    }
+
+    #[test]
+    fn slugify_off_mode_keeps_text_as_written() {
+        assert_eq!(
+            slugify("1.1 Foobar - 测试文本", SlugifyMode::Off),
+            "1.1-Foobar-测试文本"
+        );
+        assert_eq!(slugify(" C# / .NET? ", SlugifyMode::Off), "C-.NET");
+    }
+
+    #[test]
+    fn slugify_github_mode_matches_github_anchors() {
+        let github = |text| slugify(text, SlugifyMode::Github);
+        assert_eq!(github("Getting Started"), "getting-started");
+        assert_eq!(github("What's new in v1.2?"), "whats-new-in-v12");
+        assert_eq!(github("A -- B"), "a----b");
+        assert_eq!(github("snake_case & C++"), "snake_case--c");
+        assert_eq!(github("你好，世界"), "你好世界");
+        assert_eq!(github("Emoji 🎉 heading"), "emoji--heading");
     }
 
     // ── titlecase ──
@@ -116,4 +381,53 @@ mod tests {
     fn titlecase_empty() {
         assert_eq!(titlecase(""), "");
     }
+
+    // ── word_count ──
+
+    #[test]
+    fn word_count_latin() {
+        assert_eq!(word_count("Hello, world — it's kiln 2.0!"), 5);
+    }
+
+    #[test]
+    fn word_count_cjk_characters_count_individually() {
+        assert_eq!(word_count("你好世界"), 4);
+        assert_eq!(word_count("Rust 很好用"), 4);
+        assert_eq!(word_count("こんにちは"), 5);
+    }
+
+    #[test]
+    fn word_count_empty_and_punctuation() {
+        assert_eq!(word_count(""), 0);
+        assert_eq!(word_count("  --- * ... "), 0);
+    }
+
+    // ── wrap_lines ──
+
+    #[test]
+    fn wrap_lines_breaks_between_words() {
+        assert_eq!(
+            wrap_lines("Building a  static site generator", 12, 3),
+            ["Building a", "static site", "generator"]
+        );
+        assert_eq!(
+            wrap_lines("Supercalifragilistic", 8, 3),
+            ["Supercalifragilistic"]
+        );
+        assert!(wrap_lines("  ", 8, 3).is_empty());
+    }
+
+    #[test]
+    fn wrap_lines_breaks_cjk_characters() {
+        assert_eq!(wrap_lines("静态网站生成器", 8, 3), ["静态网站", "生成器"]);
+        assert_eq!(wrap_lines("用 Rust 写博客", 8, 3), ["用 Rust", "写博客"]);
+    }
+
+    #[test]
+    fn wrap_lines_cuts_off_extra_lines() {
+        assert_eq!(
+            wrap_lines("one two three four five", 8, 2),
+            ["one two", "three…"]
+        );
+    }
 }
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use crate::config::{Config, TitleCase};
+
+/// Applies the site's `title_case` style to page titles and headings.
+///
+/// Words with capitals past their first letter (`iPhone`, `API`, `macOS`)
+/// are left alone, and words in the exceptions list are written exactly as
+/// listed, in any position. Casing follows the site `language`: minor words
+/// (`of`, `the`, ...) stay lowercase in title case, German keeps its
+/// capitalized nouns in sentence case, and Turkish and Azerbaijani map
+/// dotted and dotless `i` correctly.
+#[derive(Debug, Clone)]
+pub struct TitleCaser {
+    style: TitleCase,
+    /// Primary language subtag, lowercased (`en` for `en-US`).
+    language: String,
+    /// Exceptions, keyed by their lowercase form.
+    exceptions: HashMap<String, String>,
+}
+
+impl TitleCaser {
+    /// Creates a caser, or `None` for [`TitleCase::None`].
+    #[must_use]
+    pub fn new(style: TitleCase, language: &str, exceptions: &[String]) -> Option<Self> {
+        if style == TitleCase::None {
+            return None;
+        }
+        let language = language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let exceptions = exceptions
+            .iter()
+            .map(|word| (lowercase(word, &language), word.clone()))
+            .collect();
+        Some(Self {
+            style,
+            language,
+            exceptions,
+        })
+    }
+
+    /// Creates a caser from `[markup]`, or `None` when `title_case` is off.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Option<Self> {
+        Self::new(
+            config.markup.title_case,
+            &config.language,
+            &config.markup.title_case_exceptions,
+        )
+    }
+
+    /// Recases `text`, keeping whitespace and punctuation as-is.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        self.apply_spans(&[text]).concat()
+    }
+
+    /// Recases a title split into `spans` (e.g., the text around inline
+    /// markup) as one string, returning each span recased. A word running
+    /// across spans is recased in its first span only.
+    #[must_use]
+    pub fn apply_spans(&self, spans: &[&str]) -> Vec<String> {
+        // Each word, as its pieces: (span, start, end).
+        let mut words: Vec<Vec<(usize, usize, usize)>> = Vec::new();
+        let mut glued = false;
+        for (span_idx, span) in spans.iter().enumerate() {
+            let mut offset = 0;
+            for piece in span.split_whitespace() {
+                let start = offset + span[offset..].find(piece).unwrap_or_default();
+                offset = start + piece.len();
+                match words.last_mut() {
+                    Some(word) if glued && start == 0 => word.push((span_idx, start, offset)),
+                    _ => words.push(vec![(span_idx, start, offset)]),
+                }
+                glued = false;
+            }
+            if let Some(ch) = span.chars().next_back() {
+                glued = !ch.is_whitespace();
+            }
+        }
+
+        let mut output: Vec<String> = spans.iter().map(|&span| span.to_owned()).collect();
+        let last = words.len().saturating_sub(1);
+        let mut starts_clause = true;
+        let mut recased = Vec::with_capacity(words.len());
+        for (idx, word) in words.iter().enumerate() {
+            let (span_idx, start, end) = word[0];
+            let parts: Vec<String> = spans[span_idx][start..end]
+                .split('-')
+                .enumerate()
+                .map(|(part_idx, part)| {
+                    let boundary = part_idx == 0 && (starts_clause || idx == last);
+                    self.recase(part, starts_clause && part_idx == 0, boundary)
+                })
+                .collect();
+            recased.push((span_idx, start..end, parts.join("-")));
+            let (span_idx, _, end) = word[word.len() - 1];
+            starts_clause = spans[span_idx][..end].ends_with([':', '.', '?', '!']);
+        }
+        // Back to front, so each replacement leaves earlier offsets valid.
+        for (span_idx, range, text) in recased.into_iter().rev() {
+            output[span_idx].replace_range(range, &text);
+        }
+        output
+    }
+
+    /// Recases one word (or hyphenated part), leaving leading and trailing
+    /// punctuation alone. `first` marks the first word of a clause; `edge`
+    /// marks the first or last word, which title case always capitalizes.
+    fn recase(&self, word: &str, first: bool, edge: bool) -> String {
+        let Some(start) = word.find(char::is_alphanumeric) else {
+            return word.to_owned();
+        };
+        let end = word
+            .char_indices()
+            .rev()
+            .find(|(_, ch)| ch.is_alphanumeric())
+            .map_or(word.len(), |(i, ch)| i + ch.len_utf8());
+        let (prefix, core, suffix) = (&word[..start], &word[start..end], &word[end..]);
+
+        let lower = lowercase(core, &self.language);
+        let core = if let Some(exception) = self.exceptions.get(&lower) {
+            exception.clone()
+        } else if core.chars().skip(1).any(char::is_uppercase) {
+            core.to_owned()
+        } else {
+            match self.style {
+                TitleCase::Title if !edge && is_minor_word(&lower, &self.language) => lower,
+                TitleCase::Title => capitalize(core, &self.language),
+                TitleCase::Sentence if first => capitalize(&lower, &self.language),
+                // German capitalizes every noun, so only clause starts change.
+                TitleCase::Sentence if self.language == "de" => core.to_owned(),
+                TitleCase::Sentence => lower,
+                TitleCase::None => core.to_owned(),
+            }
+        };
+        format!("{prefix}{core}{suffix}")
+    }
+}
+
+/// Returns `true` for words left lowercase inside a title-cased title.
+fn is_minor_word(word: &str, language: &str) -> bool {
+    let minor: &[&str] = match language {
+        "en" => &[
+            "a", "an", "and", "as", "at", "but", "by", "for", "if", "in", "nor", "of", "on", "or",
+            "per", "the", "to", "v", "vs", "via",
+        ],
+        "fr" => &[
+            "à", "au", "aux", "de", "des", "du", "et", "en", "la", "le", "les", "ou", "par",
+            "pour", "sur", "un", "une",
+        ],
+        "es" => &[
+            "a", "al", "con", "de", "del", "e", "el", "en", "la", "las", "los", "o", "para", "por",
+            "u", "un", "una", "y",
+        ],
+        "it" => &[
+            "a", "al", "con", "da", "dei", "del", "della", "di", "e", "il", "in", "la", "le", "lo",
+            "o", "per", "tra", "un", "una",
+        ],
+        "pt" => &[
+            "a", "ao", "as", "com", "da", "das", "de", "do", "dos", "e", "em", "na", "no", "o",
+            "os", "ou", "para", "por", "um", "uma",
+        ],
+        "de" => &[
+            "am", "an", "auf", "das", "dem", "den", "der", "des", "die", "ein", "eine", "für",
+            "im", "in", "mit", "oder", "und", "von", "zu",
+        ],
+        "nl" => &[
+            "de", "een", "en", "het", "in", "of", "op", "te", "van", "voor",
+        ],
+        _ => &[],
+    };
+    minor.contains(&word)
+}
+
+/// Returns `true` for languages with dotted and dotless `i` as separate
+/// letters.
+fn has_dotted_i(language: &str) -> bool {
+    matches!(language, "tr" | "az")
+}
+
+/// Uppercases the first letter of `word`.
+fn capitalize(word: &str, language: &str) -> String {
+    let mut chars = word.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+    let mut output = match first {
+        'i' if has_dotted_i(language) => "İ".to_owned(),
+        _ => first.to_uppercase().collect(),
+    };
+    output.push_str(chars.as_str());
+    output
+}
+
+/// Lowercases `word`.
+fn lowercase(word: &str, language: &str) -> String {
+    if has_dotted_i(language) {
+        word.chars()
+            .map(|ch| match ch {
+                'I' => "ı".to_owned(),
+                'İ' => "i".to_owned(),
+                _ => ch.to_lowercase().collect(),
+            })
+            .collect()
+    } else {
+        word.to_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caser(style: TitleCase, language: &str, exceptions: &[&str]) -> TitleCaser {
+        let exceptions: Vec<String> = exceptions.iter().map(|&word| word.to_owned()).collect();
+        TitleCaser::new(style, language, &exceptions).unwrap()
+    }
+
+    // ── new ──
+
+    #[test]
+    fn new_none_returns_none() {
+        assert!(TitleCaser::new(TitleCase::None, "en", &[]).is_none());
+    }
+
+    // ── apply ──
+
+    #[test]
+    fn apply_title_case() {
+        let caser = caser(TitleCase::Title, "en-US", &["kiln"]);
+        assert_eq!(
+            caser.apply("the art of building a static site with kiln"),
+            "The Art of Building a Static Site With kiln"
+        );
+        assert_eq!(
+            caser.apply("why rust: a love story of the borrow checker"),
+            "Why Rust: A Love Story of the Borrow Checker"
+        );
+        assert_eq!(
+            caser.apply("self-hosted  iPhone backups via API (part 2)"),
+            "Self-Hosted  iPhone Backups via API (Part 2)"
+        );
+        assert_eq!(caser.apply("what to look for"), "What to Look For");
+    }
+
+    #[test]
+    fn apply_sentence_case() {
+        let caser = caser(TitleCase::Sentence, "en", &["Rust", "GitHub"]);
+        assert_eq!(
+            caser.apply("Getting Started With Rust On GitHub Actions"),
+            "Getting started with Rust on GitHub actions"
+        );
+        assert_eq!(
+            caser.apply("\"quoted\" Titles: Second Clause"),
+            "\"Quoted\" titles: Second clause"
+        );
+        assert_eq!(caser.apply("Using the API"), "Using the API");
+    }
+
+    #[test]
+    fn apply_is_locale_aware() {
+        let german = caser(TitleCase::Sentence, "de", &[]);
+        assert_eq!(german.apply("der Weg zum Ziel"), "Der Weg zum Ziel");
+
+        let turkish = caser(TitleCase::Title, "tr", &[]);
+        assert_eq!(turkish.apply("istanbul ılık"), "İstanbul Ilık");
+        let turkish = caser(TitleCase::Sentence, "tr", &[]);
+        assert_eq!(turkish.apply("İyi Işık"), "İyi ışık");
+
+        let french = caser(TitleCase::Title, "fr", &[]);
+        assert_eq!(french.apply("le chat et la souris"), "Le Chat et la Souris");
+    }
+
+    #[test]
+    fn apply_leaves_uncased_scripts_alone() {
+        let caser = caser(TitleCase::Title, "zh", &[]);
+        assert_eq!(caser.apply("静态站点 generator"), "静态站点 Generator");
+    }
+
+    // ── apply_spans ──
+
+    #[test]
+    fn apply_spans_cases_spans_as_one_title() {
+        let title = caser(TitleCase::Title, "en", &[]);
+        assert_eq!(
+            title.apply_spans(&["using ", "serde", " in the app"]),
+            ["Using ", "Serde", " in the App"]
+        );
+        assert_eq!(
+            title.apply_spans(&["the ", "self", "-hosted way"]),
+            ["The ", "Self", "-hosted Way"],
+            "a word running across spans is recased in its first span"
+        );
+
+        let sentence = caser(TitleCase::Sentence, "en", &[]);
+        assert_eq!(
+            sentence.apply_spans(&["", "Why", " It Matters"]),
+            ["", "Why", " it matters"]
+        );
+    }
+}
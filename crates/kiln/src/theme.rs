@@ -0,0 +1,231 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail, ensure};
+
+use crate::config::check_theme;
+
+/// Clones the theme repository at `url` into `themes/<name>/`, then checks
+/// its `theme.toml` and `min_kiln_version`. The name defaults to the
+/// repository's (`https://github.com/hakula139/IgnIt.git` → `IgnIt`).
+///
+/// A theme that fails the check is removed again. Returns the theme name.
+///
+/// # Errors
+///
+/// Returns an error if the name cannot be derived or is not a plain
+/// directory name, the theme directory already exists, `git clone` fails,
+/// or the cloned theme is unusable.
+pub fn install_theme(root: &Path, url: &str, name: Option<&str>) -> Result<String> {
+    let name = match name {
+        Some(name) => name,
+        None => repo_name(url)
+            .with_context(|| format!("cannot derive a theme name from `{url}`; pass --name"))?,
+    };
+    let theme_dir = theme_dir(root, name)?;
+    if theme_dir.exists() {
+        bail!(
+            "theme directory already exists: {} (use `kiln theme update {name}`)",
+            theme_dir.display()
+        );
+    }
+    let parent = root.join("themes");
+    fs::create_dir_all(&parent).context("failed to create themes directory")?;
+
+    git(&parent, &["clone", "--depth", "1", "--", url, name])?;
+    if let Err(e) = check_theme(&theme_dir, name) {
+        fs::remove_dir_all(&theme_dir)
+            .with_context(|| format!("failed to remove {}", theme_dir.display()))?;
+        return Err(e.context(format!("theme `{name}` from {url} is not usable")));
+    }
+    Ok(name.to_owned())
+}
+
+/// Fast-forwards the theme checkout at `themes/<name>/` to its upstream,
+/// then checks its `theme.toml` and `min_kiln_version` again. An update
+/// that fails the check is rolled back.
+///
+/// Returns whether the theme changed.
+///
+/// # Errors
+///
+/// Returns an error if the name is not a plain directory name, the theme
+/// is not a git checkout, `git pull` fails (e.g., on local changes), or the
+/// updated theme is unusable.
+pub fn update_theme(root: &Path, name: &str) -> Result<bool> {
+    let theme_dir = theme_dir(root, name)?;
+    if !theme_dir.join(".git").exists() {
+        bail!(
+            "theme `{name}` is not a git checkout: {}",
+            theme_dir.display()
+        );
+    }
+
+    let before = git(&theme_dir, &["rev-parse", "HEAD"])?;
+    git(&theme_dir, &["pull", "--ff-only"])?;
+    let after = git(&theme_dir, &["rev-parse", "HEAD"])?;
+    if let Err(e) = check_theme(&theme_dir, name) {
+        git(&theme_dir, &["reset", "--hard", &before])?;
+        return Err(e.context(format!("update of theme `{name}` rolled back")));
+    }
+    Ok(before != after)
+}
+
+/// Returns `themes/<name>` under `root`, rejecting names that are not a
+/// plain directory name.
+fn theme_dir(root: &Path, name: &str) -> Result<PathBuf> {
+    ensure!(
+        Path::new(name).file_name() == Some(OsStr::new(name)),
+        "invalid theme name `{name}`"
+    );
+    Ok(root.join("themes").join(name))
+}
+
+/// Derives a theme name from the last path segment of a repository URL,
+/// without a trailing `.git`.
+fn repo_name(url: &str) -> Option<&str> {
+    let last = url.trim_end_matches('/').rsplit(['/', ':']).next()?;
+    let name = last.strip_suffix(".git").unwrap_or(last);
+    (!name.is_empty() && name != "." && name != "..").then_some(name)
+}
+
+/// Runs `git` with `args` in `dir`, returning its trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("failed to run `git` — is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` exited with {}:\n{}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    /// Creates a git repository holding a theme with the given `theme.toml`.
+    fn theme_repo(dir: &Path, theme_toml: &str) -> PathBuf {
+        let repo = dir.join("upstream");
+        write_test_file(&repo, "theme.toml", theme_toml);
+        git(&repo, &["init", "--quiet"]).unwrap();
+        commit(&repo, "init");
+        repo
+    }
+
+    fn commit(repo: &Path, message: &str) {
+        git(repo, &["add", "-A"]).unwrap();
+        git(
+            repo,
+            &[
+                "-c",
+                "user.name=kiln",
+                "-c",
+                "user.email=kiln@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                message,
+            ],
+        )
+        .unwrap();
+    }
+
+    // ── install_theme ──
+
+    #[test]
+    fn install_theme_clones_into_themes_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = theme_repo(dir.path(), "min_kiln_version = \"0.1.0\"\n");
+        let root = dir.path().join("site");
+
+        let name = install_theme(&root, repo.to_str().unwrap(), Some("ignit")).unwrap();
+        assert_eq!(name, "ignit");
+        assert!(root.join("themes/ignit/theme.toml").is_file());
+
+        let err = install_theme(&root, repo.to_str().unwrap(), Some("ignit")).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "got: {err}");
+    }
+
+    #[test]
+    fn install_theme_removes_incompatible_theme() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = theme_repo(dir.path(), "min_kiln_version = \"999.0.0\"\n");
+        let root = dir.path().join("site");
+
+        let err = install_theme(&root, repo.to_str().unwrap(), None).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("requires kiln >= 999.0.0"),
+            "got: {err:#}"
+        );
+        assert!(!root.join("themes/upstream").exists());
+    }
+
+    #[test]
+    fn install_theme_rejects_path_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let err =
+            install_theme(dir.path(), "https://example.com/t.git", Some("../escape")).unwrap_err();
+        assert!(err.to_string().contains("invalid theme name"), "got: {err}");
+    }
+
+    // ── update_theme ──
+
+    #[test]
+    fn update_theme_pulls_and_rolls_back_incompatible_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = theme_repo(dir.path(), "");
+        let root = dir.path().join("site");
+        install_theme(&root, repo.to_str().unwrap(), Some("ignit")).unwrap();
+        assert!(!update_theme(&root, "ignit").unwrap(), "already up to date");
+
+        write_test_file(&repo, "templates/base.html", "<html></html>");
+        commit(&repo, "add base");
+        assert!(update_theme(&root, "ignit").unwrap());
+        assert!(root.join("themes/ignit/templates/base.html").is_file());
+
+        write_test_file(&repo, "theme.toml", "min_kiln_version = \"999.0.0\"\n");
+        commit(&repo, "require newer kiln");
+        let err = update_theme(&root, "ignit").unwrap_err();
+        assert!(err.to_string().contains("rolled back"), "got: {err}");
+        assert_eq!(
+            fs::read_to_string(root.join("themes/ignit/theme.toml")).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn update_theme_not_a_checkout_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_file(dir.path(), "themes/local/theme.toml", "");
+        let err = update_theme(dir.path(), "local").unwrap_err();
+        assert!(err.to_string().contains("not a git checkout"), "got: {err}");
+    }
+
+    // ── repo_name ──
+
+    #[test]
+    fn repo_name_from_urls() {
+        for (url, expected) in [
+            ("https://github.com/hakula139/IgnIt.git", Some("IgnIt")),
+            ("https://github.com/hakula139/IgnIt/", Some("IgnIt")),
+            ("git@github.com:IgnIt.git", Some("IgnIt")),
+            ("/path/to/theme", Some("theme")),
+            ("https://example.com/..", None),
+            ("", None),
+        ] {
+            assert_eq!(repo_name(url), expected, "{url}");
+        }
+    }
+}
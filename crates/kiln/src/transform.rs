@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::content::page::Page;
+
+/// A custom processing step over every page, for programs embedding kiln
+/// (custom shortcodes, analytics injection), registered with
+/// [`Site::with_transform`](crate::Site::with_transform).
+///
+/// Both methods pass their input through unchanged by default, so a
+/// transform implements only the stages it needs. Transforms run in the
+/// order they were registered, each on the output of the one before. An
+/// error fails the page, like any other page error.
+pub trait ContentTransform: Send + Sync {
+    /// Rewrites the body of a markdown, Org, or AsciiDoc `page` (the latter
+    /// two already converted to markdown) once the page is loaded.
+    ///
+    /// Runs before snippets, directives, and shortcodes are expanded, so it
+    /// can turn custom syntax into markup kiln understands. The summary is
+    /// re-derived from the rewritten body, so listings see it too.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the body cannot be transformed.
+    fn transform_markdown(&self, page: &Page, markdown: &str) -> Result<String> {
+        _ = page;
+        Ok(markdown.to_owned())
+    }
+
+    /// Rewrites the final HTML of `page`, its template applied.
+    ///
+    /// Runs for published pages and shared drafts; listings, feeds, and the
+    /// 404 page are left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTML cannot be transformed.
+    fn transform_html(&self, page: &Page, html: &str) -> Result<String> {
+        _ = page;
+        Ok(html.to_owned())
+    }
+}
+
+/// Runs the body of `page` through each of `transforms` in turn.
+pub(crate) fn transform_markdown(
+    transforms: &[Arc<dyn ContentTransform>],
+    page: &mut Page,
+) -> Result<()> {
+    for transform in transforms {
+        let body = transform.transform_markdown(page, &page.raw_content)?;
+        page.set_body(body);
+    }
+    Ok(())
+}
+
+/// Runs the final `html` of `page` through each of `transforms` in turn.
+pub(crate) fn transform_html(
+    transforms: &[Arc<dyn ContentTransform>],
+    page: &Page,
+    mut html: String,
+) -> Result<String> {
+    for transform in transforms {
+        html = transform.transform_html(page, &html)?;
+    }
+    Ok(html)
+}
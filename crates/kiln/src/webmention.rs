@@ -0,0 +1,515 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, bail};
+use pulldown_cmark::{Event, Parser, Tag};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::build::url::page_url;
+use crate::config::Config;
+use crate::content::discovery::discover_content;
+use crate::content::page::PageKind;
+use crate::output::write_output;
+use crate::template::cache::CACHE_DIR;
+use crate::xml::absolute_url;
+
+/// File under [`CACHE_DIR`] recording the webmentions already sent.
+const SENT_LOG_FILE: &str = "webmentions.json";
+
+/// Seconds to wait for each request.
+const TIMEOUT_SECS: &str = "30";
+
+const USER_AGENT: &str = concat!("kiln/", env!("CARGO_PKG_VERSION"), " (webmention)");
+
+/// Matches each link of an HTTP `Link` header: its URL and parameters.
+static LINK_HEADER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<([^>]*)>([^<]*)").expect("link header regex should compile"));
+
+/// Matches `<link>` and `<a>` start tags.
+static LINK_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<(?:link|a)\b[^>]*>").expect("link tag regex should compile")
+});
+
+/// Matches a `rel` or `href` attribute (or `Link` header parameter), quoted
+/// or not.
+static ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(rel|href)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'<>;,]+))"#)
+        .expect("attribute regex should compile")
+});
+
+/// Options for [`send_webmentions`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WebmentionOptions {
+    /// List the links that would be notified without contacting them.
+    pub dry_run: bool,
+}
+
+/// What [`send_webmentions`] did.
+#[derive(Debug, Default)]
+pub struct WebmentionReport {
+    /// Webmentions accepted by the target's endpoint.
+    pub sent: usize,
+    /// Links whose targets advertise no webmention endpoint.
+    pub unsupported: usize,
+    /// Webmentions that could not be sent, with the reason. They are tried
+    /// again next time.
+    pub failed: Vec<String>,
+}
+
+/// Sends a [webmention](https://www.w3.org/TR/webmention/) for each link
+/// from a published post to another site, telling it about the post.
+///
+/// Each target is fetched for the endpoint it advertises, in a `Link`
+/// header or a `<link>` / `<a>` with `rel="webmention"`, and the endpoint
+/// is notified with the post's URL as the source. Requests go through
+/// `curl`. Links sent, or found to have no endpoint, are recorded in
+/// `.kiln-cache/webmentions.json` and skipped from then on, so only new
+/// posts and links added to updated posts are notified.
+///
+/// Receivers fetch the post to verify the link, so run this once the site
+/// is deployed.
+///
+/// # Errors
+///
+/// Returns an error if the config or content cannot be loaded, or the sent
+/// log cannot be written. Webmentions that fail to send are listed in the
+/// returned [`WebmentionReport`] instead.
+pub fn send_webmentions(root: &Path, options: WebmentionOptions) -> Result<WebmentionReport> {
+    let config = Config::load(root).context("failed to load config")?;
+    let content = discover_content(root, config.slugify)?;
+    let log_path = root.join(CACHE_DIR).join(SENT_LOG_FILE);
+    let mut log = SentLog::load(&log_path);
+    let mut report = WebmentionReport::default();
+
+    for page in &content.pages {
+        if !matches!(page.kind, PageKind::Post { .. }) || page.frontmatter.redirect_to.is_some() {
+            continue;
+        }
+        let source = page_url(&config.base_url, &page.output_path(&content.content_dir)?);
+        for target in outgoing_links(&page.raw_content, &config.base_url) {
+            if log.contains(&source, &target) {
+                continue;
+            }
+            if options.dry_run {
+                eprintln!("Would notify {target} of {source}");
+                continue;
+            }
+            match send_webmention(&source, &target) {
+                Ok(Some(endpoint)) => {
+                    eprintln!("Sent webmention for {target} to {endpoint}");
+                    report.sent += 1;
+                }
+                Ok(None) => report.unsupported += 1,
+                Err(e) => {
+                    report.failed.push(format!("{source} → {target}: {e:#}"));
+                    continue;
+                }
+            }
+            log.insert(&source, &target);
+        }
+    }
+
+    if !options.dry_run {
+        log.save(&log_path)?;
+    }
+    Ok(report)
+}
+
+/// Webmentions already handled, by source URL and then target URL.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct SentLog {
+    sent: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SentLog {
+    /// Reads the log at `path`, starting afresh when it is missing or
+    /// corrupt.
+    fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                tracing::warn!(file = %path.display(), "ignoring corrupt webmention log: {e}");
+                Self::default()
+            }),
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!(file = %path.display(), "ignoring unreadable webmention log: {e}");
+                }
+                Self::default()
+            }
+        }
+    }
+
+    fn contains(&self, source: &str, target: &str) -> bool {
+        self.sent
+            .get(source)
+            .is_some_and(|targets| targets.contains(target))
+    }
+
+    fn insert(&mut self, source: &str, target: &str) {
+        self.sent
+            .entry(source.to_owned())
+            .or_default()
+            .insert(target.to_owned());
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(self).context("failed to serialize webmention log")?;
+        write_output(path, json)
+    }
+}
+
+// ── Helpers ──
+
+/// Returns the distinct `http(s)` links in `markdown` to pages outside the
+/// site at `base_url`, in order of appearance.
+fn outgoing_links(markdown: &str, base_url: &str) -> Vec<String> {
+    let base = base_url.trim_end_matches('/');
+    let is_own = |url: &str| {
+        url.strip_prefix(base)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+    };
+    let mut links = Vec::new();
+    for event in Parser::new(markdown) {
+        if let Event::Start(Tag::Link { dest_url, .. }) = event
+            && (dest_url.starts_with("https://") || dest_url.starts_with("http://"))
+            && !is_own(&dest_url)
+            && !links.iter().any(|link| link == dest_url.as_ref())
+        {
+            links.push(dest_url.into_string());
+        }
+    }
+    links
+}
+
+/// Notifies the webmention endpoint of `target` that `source` links to it,
+/// returning the endpoint, or `None` if `target` advertises none.
+fn send_webmention(source: &str, target: &str) -> Result<Option<String>> {
+    let mut fetch = curl();
+    fetch
+        .args([
+            "--location",
+            "--include",
+            "--write-out",
+            "\n%{url_effective}",
+        ])
+        .arg(target);
+    let response = parse_response(&run_curl(fetch)?);
+    let Some(endpoint) = discover_endpoint(&response) else {
+        return Ok(None);
+    };
+
+    let mut notify = curl();
+    notify
+        .arg("--data-urlencode")
+        .arg(format!("source={source}"))
+        .arg("--data-urlencode")
+        .arg(format!("target={target}"))
+        .arg(&endpoint);
+    run_curl(notify).with_context(|| format!("endpoint {endpoint} rejected the webmention"))?;
+    Ok(Some(endpoint))
+}
+
+/// A fetched page: its final URL after redirects, the headers of the last
+/// response, and its body.
+#[derive(Debug, PartialEq, Eq)]
+struct Response {
+    url: String,
+    headers: String,
+    body: String,
+}
+
+/// Splits the output of `curl --include --write-out "\n%{url_effective}"`,
+/// which holds the headers of every response in a redirect chain.
+fn parse_response(output: &str) -> Response {
+    let (mut rest, url) = output.rsplit_once('\n').unwrap_or(("", output));
+    let mut headers = "";
+    while rest.starts_with("HTTP/") {
+        let (block, body) = rest
+            .split_once("\r\n\r\n")
+            .or_else(|| rest.split_once("\n\n"))
+            .unwrap_or((rest, ""));
+        headers = block;
+        rest = body;
+    }
+    Response {
+        url: url.to_owned(),
+        headers: headers.to_owned(),
+        body: rest.to_owned(),
+    }
+}
+
+/// Finds the webmention endpoint `response` advertises, resolved against
+/// its URL: the first `Link` header with `rel="webmention"`, else the first
+/// such `<link>` or `<a>` in its HTML.
+fn discover_endpoint(response: &Response) -> Option<String> {
+    let from_headers = response
+        .headers
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("link"))
+        .flat_map(|(_, value)| LINK_HEADER_RE.captures_iter(value))
+        .find(|link| has_webmention_rel(&link[2]))
+        .map(|link| link[1].to_owned());
+    let endpoint = from_headers.or_else(|| {
+        LINK_TAG_RE
+            .find_iter(&response.body)
+            .map(|tag| tag.as_str())
+            .find(|tag| has_webmention_rel(tag))
+            .and_then(|tag| attribute(tag, "href"))
+            .map(|href| href.replace("&amp;", "&"))
+    })?;
+
+    // An empty URL refers to the target page itself.
+    if endpoint.is_empty() {
+        return Some(response.url.clone());
+    }
+    Some(absolute_url(&endpoint, &response.url))
+}
+
+/// Reports whether the `rel` of `attrs` includes `webmention`.
+fn has_webmention_rel(attrs: &str) -> bool {
+    attribute(attrs, "rel").is_some_and(|rel| {
+        rel.split_whitespace()
+            .any(|value| value.eq_ignore_ascii_case("webmention"))
+    })
+}
+
+/// Returns the value of attribute `name` in `attrs`.
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    ATTR_RE
+        .captures_iter(attrs)
+        .find(|caps| caps[1].eq_ignore_ascii_case(name))
+        .and_then(|caps| caps.get(2).or(caps.get(3)).or(caps.get(4)))
+        .map(|value| value.as_str())
+}
+
+/// Builds a `curl` command failing on HTTP errors, quiet but for errors.
+fn curl() -> Command {
+    let mut command = Command::new("curl");
+    command.args([
+        "--silent",
+        "--show-error",
+        "--fail",
+        "--max-time",
+        TIMEOUT_SECS,
+        "--user-agent",
+        USER_AGENT,
+    ]);
+    command
+}
+
+/// Runs a `curl` command and returns its stdout.
+fn run_curl(mut command: Command) -> Result<String> {
+    let output = command
+        .output()
+        .context("failed to run `curl` — is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "`curl` exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    use indoc::{formatdoc, indoc};
+
+    use super::*;
+    use crate::test_utils::write_test_file;
+
+    /// Serves one canned HTTP response per connection, in order, on a local
+    /// port, returning its origin and a handle yielding the requests.
+    fn serve(responses: Vec<String>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                loop {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.strip_prefix("Content-Length: "))
+                            .map_or(0, |len| len.trim().parse().unwrap());
+                        if body.len() >= length {
+                            break;
+                        }
+                    }
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(String::from_utf8(request).unwrap());
+            }
+            requests
+        });
+        (origin, handle)
+    }
+
+    fn http_response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    fn setup_site(root: &Path, target: &str) {
+        write_test_file(root, "config.toml", r#"base_url = "https://example.com""#);
+        write_test_file(
+            root,
+            "content/posts/hello/index.md",
+            &formatdoc! {r#"
+                +++
+                title = "Hello"
+                +++
+                Replying to [this post]({target}), see [my other post](https://example.com/posts/other/).
+            "#},
+        );
+    }
+
+    // ── send_webmentions ──
+
+    #[test]
+    fn send_webmentions_notifies_endpoint_once() {
+        let root = tempfile::tempdir().unwrap();
+        let (origin, server) = serve(vec![
+            http_response(
+                "200 OK",
+                "Content-Type: text/html\r\n",
+                r#"<html><head><link rel="webmention" href="/webmention"></head></html>"#,
+            ),
+            http_response("202 Accepted", "", ""),
+        ]);
+        let target = format!("{origin}/post");
+        setup_site(root.path(), &target);
+
+        let report = send_webmentions(root.path(), WebmentionOptions::default()).unwrap();
+        assert_eq!(report.sent, 1, "failed: {:?}", report.failed);
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /post "), "{}", requests[0]);
+        assert!(
+            requests[1].starts_with("POST /webmention "),
+            "{}",
+            requests[1]
+        );
+        assert!(
+            requests[1].ends_with(&format!(
+                "source=https%3A%2F%2Fexample.com%2Fposts%2Fhello%2F&target={}",
+                target.replace(':', "%3A").replace('/', "%2F")
+            )),
+            "{}",
+            requests[1]
+        );
+
+        // The server is gone, so anything sent again would fail.
+        let report = send_webmentions(root.path(), WebmentionOptions::default()).unwrap();
+        assert_eq!((report.sent, report.failed.len()), (0, 0));
+    }
+
+    #[test]
+    fn send_webmentions_retries_failures() {
+        let root = tempfile::tempdir().unwrap();
+        let (origin, server) = serve(vec![http_response("500 Internal Server Error", "", "")]);
+        setup_site(root.path(), &format!("{origin}/post"));
+
+        let report = send_webmentions(root.path(), WebmentionOptions::default()).unwrap();
+        server.join().unwrap();
+        assert_eq!(report.sent, 0);
+        assert_eq!(report.failed.len(), 1, "failed: {:?}", report.failed);
+        let log = SentLog::load(&root.path().join(CACHE_DIR).join(SENT_LOG_FILE));
+        assert!(log.sent.is_empty(), "failures are not logged: {log:?}");
+    }
+
+    // ── outgoing_links ──
+
+    #[test]
+    fn outgoing_links_skips_own_site_and_duplicates() {
+        let markdown = indoc! {"
+            [a](https://other.org/a) [b](/posts/b/) [c](https://example.com/c)
+            [a again](https://other.org/a) [d][ref] <https://example.community/>
+            [e](mailto:me@example.com)
+
+            [ref]: http://third.net/d
+        "};
+        assert_eq!(
+            outgoing_links(markdown, "https://example.com/"),
+            [
+                "https://other.org/a",
+                "http://third.net/d",
+                "https://example.community/"
+            ]
+        );
+    }
+
+    // ── parse_response ──
+
+    #[test]
+    fn parse_response_keeps_last_headers() {
+        let output = "HTTP/1.1 301 Moved\r\nLocation: /b\r\n\r\nHTTP/1.1 200 OK\r\nLink: <x>\r\n\r\n<html></html>\nhttps://a.org/b";
+        assert_eq!(
+            parse_response(output),
+            Response {
+                url: "https://a.org/b".into(),
+                headers: "HTTP/1.1 200 OK\r\nLink: <x>".into(),
+                body: "<html></html>".into(),
+            }
+        );
+    }
+
+    // ── discover_endpoint ──
+
+    fn response(headers: &str, body: &str) -> Response {
+        Response {
+            url: "https://a.org/posts/one".into(),
+            headers: headers.into(),
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn discover_endpoint_prefers_link_header() {
+        let found = discover_endpoint(&response(
+            "HTTP/1.1 200 OK\r\nlink: <https://a.org/style.css>; rel=stylesheet, <wm?x=1>; rel=\"webmention other\"",
+            r#"<link rel="webmention" href="/ignored">"#,
+        ));
+        assert_eq!(found.as_deref(), Some("https://a.org/posts/wm?x=1"));
+    }
+
+    #[test]
+    fn discover_endpoint_reads_html() {
+        let body = indoc! {r#"
+            <a href="/about" rel="me">About</a>
+            <A REL='webmention' HREF="/endpoint?a=1&amp;b=2">mention</A>
+            <link rel="webmention" href="/second">
+        "#};
+        assert_eq!(
+            discover_endpoint(&response("HTTP/1.1 200 OK", body)).as_deref(),
+            Some("https://a.org/endpoint?a=1&b=2")
+        );
+        assert_eq!(
+            discover_endpoint(&response("", r#"<link rel="webmention" href="">"#)).as_deref(),
+            Some("https://a.org/posts/one")
+        );
+        assert_eq!(
+            discover_endpoint(&response("", r#"<link rel="me" href="/x">"#)),
+            None
+        );
+    }
+}
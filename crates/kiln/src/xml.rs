@@ -0,0 +1,231 @@
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use crate::html::writeln_indented;
+
+/// Matches an `href` or `src` attribute and its double-quoted value.
+static URL_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)(\s(?:href|src)=")([^"]*)""#).expect("url attribute regex should compile")
+});
+
+/// Escapes characters that are special in XML, and drops characters XML 1.0
+/// does not allow at all (control characters other than tab, newline, and
+/// carriage return).
+///
+/// Escapes `&`, `<`, `>`, `"`, `'`.
+/// Safe for use in both element content and attribute values.
+#[must_use]
+pub fn escape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&apos;"),
+            '\t' | '\n' | '\r' => output.push(ch),
+            _ if is_xml_char(ch) => output.push(ch),
+            _ => {}
+        }
+    }
+    output
+}
+
+/// Wraps text in a CDATA section, for embedding HTML in feed elements.
+///
+/// A `]]>` in the text would end the section early, so it is split across
+/// two sections. Characters XML does not allow are dropped, as in
+/// [`escape`].
+#[must_use]
+pub fn cdata(input: &str) -> String {
+    let text: String = input
+        .chars()
+        .filter(|&ch| matches!(ch, '\t' | '\n' | '\r') || is_xml_char(ch))
+        .collect();
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+/// Writes an escaped element on its own indented line:
+/// `<tag>content</tag>`.
+pub fn write_element(xml: &mut String, level: u8, tag: &str, content: &str) {
+    writeln_indented!(xml, level, "<{tag}>{}</{tag}>", escape(content));
+}
+
+/// Resolves a URL as it appears on the page at `page_url` (which must be
+/// absolute and end with `/` for directory pages) to an absolute URL.
+///
+/// URLs with a scheme (`https:`, `mailto:`, `data:`) and protocol-relative
+/// URLs are returned as-is. Root-relative paths resolve against the origin
+/// of `page_url`, fragments against the page itself, and relative paths
+/// against the page's directory, with `.` and `..` segments removed.
+#[must_use]
+pub fn absolute_url(url: &str, page_url: &str) -> String {
+    if url.starts_with("//") || has_scheme(url) {
+        return url.to_owned();
+    }
+    let page = page_url.split_once('#').map_or(page_url, |(page, _)| page);
+    if url.starts_with('#') {
+        return format!("{page}{url}");
+    }
+
+    let origin_end = page.find("://").map_or(0, |i| {
+        page[i + 3..].find('/').map_or(page.len(), |j| i + 3 + j)
+    });
+    let (origin, page_path) = page.split_at(origin_end);
+    let path = if url.starts_with('/') {
+        url.to_owned()
+    } else {
+        let page_path = page_path
+            .split_once('?')
+            .map_or(page_path, |(path, _)| path);
+        let dir = page_path.rfind('/').map_or("/", |i| &page_path[..=i]);
+        format!("{dir}{url}")
+    };
+    format!("{origin}{}", remove_dot_segments(&path))
+}
+
+/// Rewrites every relative `href` and `src` attribute in `html` to an
+/// absolute URL, so content embedded in a feed still links correctly when
+/// read outside the site. See [`absolute_url`] for how URLs are resolved.
+#[must_use]
+pub fn absolutize_urls(html: &str, page_url: &str) -> String {
+    URL_ATTR_RE
+        .replace_all(html, |caps: &Captures<'_>| {
+            format!("{}{}\"", &caps[1], absolute_url(&caps[2], page_url))
+        })
+        .into_owned()
+}
+
+// ── Helpers ──
+
+/// Returns `true` for characters allowed anywhere in an XML 1.0 document,
+/// except the whitespace controls, which callers allow explicitly.
+fn is_xml_char(ch: char) -> bool {
+    matches!(ch, '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// Returns `true` if `url` starts with a URI scheme such as `https:`.
+fn has_scheme(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Removes `.` and `..` path segments, keeping any query or fragment intact.
+fn remove_dot_segments(url: &str) -> String {
+    let split = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(split);
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." => {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+            _ => segments.push(segment),
+        }
+    }
+    // A trailing `.` or `..` names a directory.
+    if path.ends_with("/.") || path.ends_with("/..") {
+        segments.push("");
+    }
+    format!("{}{suffix}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── escape ──
+
+    #[test]
+    fn escape_special_chars() {
+        assert_eq!(escape("a&b<c>d\"e'f"), "a&amp;b&lt;c&gt;d&quot;e&apos;f");
+    }
+
+    #[test]
+    fn escape_drops_invalid_chars() {
+        assert_eq!(escape("a\u{0}b\u{1B}c\td\ne\u{FFFE}"), "abc\td\ne");
+    }
+
+    // ── cdata ──
+
+    #[test]
+    fn cdata_wraps_html() {
+        assert_eq!(cdata("<p>A & B</p>"), "<![CDATA[<p>A & B</p>]]>");
+    }
+
+    #[test]
+    fn cdata_splits_terminator() {
+        assert_eq!(cdata("a]]>b\u{8}"), "<![CDATA[a]]]]><![CDATA[>b]]>");
+    }
+
+    // ── absolute_url ──
+
+    #[test]
+    fn absolute_url_resolves_relative_forms() {
+        let page = "https://example.com/posts/hello/";
+        assert_eq!(
+            absolute_url("cover.webp", page),
+            "https://example.com/posts/hello/cover.webp"
+        );
+        assert_eq!(
+            absolute_url("../other/?q=1#top", page),
+            "https://example.com/posts/other/?q=1#top"
+        );
+        assert_eq!(
+            absolute_url("/tags/rust/", page),
+            "https://example.com/tags/rust/"
+        );
+        assert_eq!(
+            absolute_url("#intro", "https://example.com/posts/hello/#old"),
+            "https://example.com/posts/hello/#intro"
+        );
+        assert_eq!(absolute_url("../../../..", page), "https://example.com/");
+        assert_eq!(
+            absolute_url("img.png", "https://example.com/blog/post.html"),
+            "https://example.com/blog/img.png"
+        );
+        assert_eq!(
+            absolute_url("/a/", "https://example.com"),
+            "https://example.com/a/"
+        );
+    }
+
+    #[test]
+    fn absolute_url_keeps_absolute_urls() {
+        let page = "https://example.com/posts/hello/";
+        for url in [
+            "https://other.org/x",
+            "//cdn.example.org/lib.js",
+            "mailto:me@example.com",
+            "data:image/png;base64,AAAA",
+        ] {
+            assert_eq!(absolute_url(url, page), url);
+        }
+    }
+
+    // ── absolutize_urls ──
+
+    #[test]
+    fn absolutize_urls_rewrites_href_and_src() {
+        let html = r##"<a href="../b/">B</a> <img src="cover.webp" alt=""> <a href="#x" class="k">X</a> <a href="https://o.org/">O</a>"##;
+        assert_eq!(
+            absolutize_urls(html, "https://example.com/posts/a/"),
+            r#"<a href="https://example.com/posts/b/">B</a> <img src="https://example.com/posts/a/cover.webp" alt=""> <a href="https://example.com/posts/a/#x" class="k">X</a> <a href="https://o.org/">O</a>"#
+        );
+    }
+
+    #[test]
+    fn absolutize_urls_ignores_text() {
+        let html = r#"<p data-src="a.png">src="b.png"</p>"#;
+        assert_eq!(absolutize_urls(html, "https://example.com/"), html);
+    }
+}